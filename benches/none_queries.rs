@@ -102,5 +102,50 @@ fn bench_none_query(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, bench_none_query);
+system!(ChangedMovement {
+    query fn update(_vel: View<Velocity>, _pos: View<Position>) {
+        unsafe { COUNT_MATCHED += 1; }
+    }
+    None=[Frozen]
+    Changed=[Velocity]
+});
+
+/// 10k movers (every 16th frozen) at indices `index(0)..index(9_999)`.
+fn mask_world(index: impl Fn(u32) -> u32) -> World {
+    let mut world = World::new();
+    let frame = Frame::new(world.current_tick());
+    for i in 0..10_000u32 {
+        world
+            .get_storage_mut::<Position>()
+            .set(&frame, index(i), Position { x: 0.0, y: 0.0 });
+        world
+            .get_storage_mut::<Velocity>()
+            .set(&frame, index(i), Velocity { x: 1.0, y: 2.0 });
+        if i % 16 == 0 {
+            world
+                .get_storage_mut::<Frozen>()
+                .set(&frame, index(i), Frozen);
+        }
+    }
+    world
+}
+
+fn bench_changed_query(c: &mut Criterion) {
+    // Sparse: indices 0..10k span 3 storage indices. Dense: every storage index.
+    let worlds = [
+        ("sparse", mask_world(|i| i)),
+        ("dense", mask_world(|i| ((i % 64) << 12) | (i / 64))),
+    ];
+    for (name, mut world) in worlds {
+        // Changed queries redo the page walk on every run; nothing changes here, so
+        // the run is the walk alone.
+        let system = ChangedMovement::new(&mut world);
+        system.run(&world.frame());
+        c.bench_function(&format!("changed_query_{name}"), |b| {
+            b.iter(|| system.run(&world.frame()))
+        });
+    }
+}
+
+criterion_group!(benches, bench_none_query, bench_changed_query);
 criterion_main!(benches);
@@ -570,7 +570,7 @@ fn bench_perf_reference_like(c: &mut Criterion) {
             let n_entities = 1000u32;
             for pass in 0..24u32 {
                 // advance tick per pass
-                world.set_tick(Tick(pass));
+                world.advance_tick();
                 // write components for all entities
                 let frame = Frame::new(world.current_tick());
                 for id in 0..n_entities {
@@ -1167,7 +1167,7 @@ fn bench_rollback_only(c: &mut Criterion) {
                 }
                 let n_entities = 10000u32;
                 for pass in 0..24u32 {
-                    world.set_tick(Tick(pass));
+                    world.advance_tick();
                     let frame = Frame::new(world.current_tick());
                     for id in 0..n_entities {
                         macro_rules! set_large {
@@ -1834,7 +1834,7 @@ fn bench_apples_to_apples_10k_15_12(c: &mut Criterion) {
             let _ = world.get_storage::<Tag4>();
             let n_entities = 10000u32;
             for pass in 0..12u32 {
-                world.set_tick(Tick(pass));
+                world.advance_tick();
                 let frame = Frame::new(world.current_tick());
                 for id in 0..n_entities {
                     unsafe { &mut *world.get_storage::<L0>() }.set(
@@ -1888,8 +1888,37 @@ fn bench_apples_to_apples_10k_15_12(c: &mut Criterion) {
     });
 }
 
+fn bench_bulk_write_20k(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bulk_write_20k");
+    group.bench_function("set", |b| {
+        let mut storage = Storage::<BenchComp>::new();
+        let mut pass = 0u32;
+        b.iter(|| {
+            pass += 1;
+            let frame = Frame::new(Tick(pass));
+            for id in 0..20_000u32 {
+                storage.set(&frame, id, BenchComp { v: pass as i32 });
+            }
+        });
+    });
+    group.bench_function("writer", |b| {
+        let mut storage = Storage::<BenchComp>::new();
+        let mut pass = 0u32;
+        b.iter(|| {
+            pass += 1;
+            let frame = Frame::new(Tick(pass));
+            let mut writer = storage.writer(&frame);
+            for id in 0..20_000u32 {
+                writer.set(id, BenchComp { v: pass as i32 });
+            }
+        });
+    });
+    group.finish();
+}
+
 criterion_group!(
     benches,
+    bench_bulk_write_20k,
     bench_full_pipeline_20k,
     bench_rollback_only,
     bench_perf_reference_like,
@@ -4,7 +4,7 @@ use quote::quote;
 use std::collections::HashMap;
 use syn::parse::{Parse, ParseStream};
 use syn::DeriveInput;
-use syn::{parse_macro_input, token, FnArg, Ident, ItemFn, Pat, Type};
+use syn::{parse_macro_input, token, FnArg, Ident, ItemFn, Pat, ReturnType, Type};
 
 /// Input structure for the system! macro
 ///
@@ -142,9 +142,14 @@ fn extract_view_params(query_fn: &ItemFn) -> Vec<(Ident, Type, bool)> {
 
                     if type_name == "View" || is_mut {
                         if let syn::PathArguments::AngleBracketed(args) = &last_segment.arguments {
-                            if let Some(syn::GenericArgument::Type(component_type)) =
-                                args.args.first()
-                            {
+                            // Skip explicit lifetimes (e.g. View<'a, T>) to find the component type
+                            if let Some(component_type) = args.args.iter().find_map(|arg| {
+                                if let syn::GenericArgument::Type(ty) = arg {
+                                    Some(ty)
+                                } else {
+                                    None
+                                }
+                            }) {
                                 params.push((
                                     pat_ident.ident.clone(),
                                     component_type.clone(),
@@ -161,6 +166,112 @@ fn extract_view_params(query_fn: &ItemFn) -> Vec<(Ident, Type, bool)> {
     params
 }
 
+/// A `Lookup<T>` query parameter: name, component type, whether it may write (declared
+/// `&mut Lookup<T>` or bound `mut`) and how it is passed (`Some(mutable)` for references).
+struct LookupParam {
+    name: Ident,
+    ty: Type,
+    is_mut: bool,
+    by_ref: Option<bool>,
+}
+
+/// Extracts `Lookup<T>`, `&Lookup<T>` and `&mut Lookup<T>` parameters
+fn extract_lookup_params(query_fn: &ItemFn) -> Vec<LookupParam> {
+    let mut lookups = Vec::new();
+    for arg in &query_fn.sig.inputs {
+        let FnArg::Typed(pat_type) = arg else {
+            continue;
+        };
+        let Pat::Ident(pat_ident) = &*pat_type.pat else {
+            continue;
+        };
+        let (inner_type, by_ref) = match &*pat_type.ty {
+            Type::Reference(type_ref) => (&*type_ref.elem, Some(type_ref.mutability.is_some())),
+            ty => (ty, None),
+        };
+        let Type::Path(type_path) = inner_type else {
+            continue;
+        };
+        let last_segment = type_path.path.segments.last().unwrap();
+        if last_segment.ident != "Lookup" {
+            continue;
+        }
+        let syn::PathArguments::AngleBracketed(args) = &last_segment.arguments else {
+            continue;
+        };
+        if let Some(ty) = args.args.iter().find_map(|arg| match arg {
+            syn::GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        }) {
+            lookups.push(LookupParam {
+                name: pat_ident.ident.clone(),
+                ty: ty.clone(),
+                is_mut: by_ref == Some(true) || pat_ident.mutability.is_some(),
+                by_ref,
+            });
+        }
+    }
+    lookups
+}
+
+/// Extracts `ChunkView<T>` parameters: name and component type
+fn extract_chunk_view_params(query_fn: &ItemFn) -> Vec<(Ident, Type)> {
+    let mut chunk_views = Vec::new();
+    for arg in &query_fn.sig.inputs {
+        let FnArg::Typed(pat_type) = arg else {
+            continue;
+        };
+        let Pat::Ident(pat_ident) = &*pat_type.pat else {
+            continue;
+        };
+        let Type::Path(type_path) = &*pat_type.ty else {
+            continue;
+        };
+        let last_segment = type_path.path.segments.last().unwrap();
+        if last_segment.ident != "ChunkView" {
+            continue;
+        }
+        let syn::PathArguments::AngleBracketed(args) = &last_segment.arguments else {
+            continue;
+        };
+        if let Some(ty) = args.args.iter().find_map(|arg| match arg {
+            syn::GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        }) {
+            chunk_views.push((pat_ident.ident.clone(), ty.clone()));
+        }
+    }
+    chunk_views
+}
+
+/// Returns true for a `Commands` query parameter.
+fn is_commands_param(ty: &Type) -> bool {
+    if let Type::Path(type_path) = ty {
+        return type_path
+            .path
+            .segments
+            .last()
+            .is_some_and(|seg| seg.ident == "Commands");
+    }
+    false
+}
+
+/// Returns true for a `&Frame` query parameter.
+fn is_frame_param(ty: &Type) -> bool {
+    if let Type::Reference(type_ref) = ty {
+        if type_ref.mutability.is_none() {
+            if let Type::Path(type_path) = &*type_ref.elem {
+                return type_path
+                    .path
+                    .segments
+                    .last()
+                    .is_some_and(|seg| seg.ident == "Frame");
+            }
+        }
+    }
+    false
+}
+
 #[proc_macro]
 pub fn system(input: TokenStream) -> TokenStream {
     let SystemInput {
@@ -174,6 +285,8 @@ pub fn system(input: TokenStream) -> TokenStream {
     } = parse_macro_input!(input as SystemInput);
 
     let params = extract_view_params(&query_fn);
+    let lookups = extract_lookup_params(&query_fn);
+    let chunk_views = extract_chunk_view_params(&query_fn);
 
     if params.is_empty() {
         return syn::Error::new_spanned(
@@ -184,9 +297,32 @@ pub fn system(input: TokenStream) -> TokenStream {
         .into();
     }
 
+    // A chunk view borrows the whole chunk, so nothing else in the query may write it
+    for (name, ty) in &chunk_views {
+        let key = quote! { #ty }.to_string();
+        let written = params
+            .iter()
+            .any(|(_, view_ty, is_mut)| *is_mut && quote! { #view_ty }.to_string() == key)
+            || lookups.iter().any(|l| {
+                let lookup_ty = &l.ty;
+                l.is_mut && quote! { #lookup_ty }.to_string() == key
+            });
+        if written {
+            return syn::Error::new_spanned(
+                name,
+                "ChunkView<T> cannot be combined with a ViewMut<T> or mutable Lookup<T> of the same component",
+            )
+            .to_compile_error()
+            .into();
+        }
+    }
+
     let query_fn_name = &query_fn.sig.ident;
     let query_fn_body = &query_fn.block;
     let query_params = &query_fn.sig.inputs;
+    let query_generics = &query_fn.sig.generics;
+    let query_where = &query_fn.sig.generics.where_clause;
+    let query_output = &query_fn.sig.output;
 
     // Build required types from params + All + Changed; negative-only from None
     let mut required_index: HashMap<String, usize> = HashMap::new();
@@ -202,7 +338,7 @@ pub fn system(input: TokenStream) -> TokenStream {
             required_types.push((ty.clone(), *is_mut));
         }
     }
-    for ty in &all_types {
+    for ty in chunk_views.iter().map(|(_, ty)| ty).chain(&all_types) {
         let key = quote! { #ty }.to_string();
         if !required_index.contains_key(&key) {
             required_index.insert(key.clone(), required_types.len());
@@ -268,7 +404,7 @@ pub fn system(input: TokenStream) -> TokenStream {
             }
         }
     }
-    for ty in &all_types {
+    for ty in chunk_views.iter().map(|(_, ty)| ty).chain(&all_types) {
         let key = quote! { #ty }.to_string();
         if read_keys.insert(key.clone(), true).is_none() {
             read_types.push(quote! { std::any::TypeId::of::<#ty>() });
@@ -280,11 +416,35 @@ pub fn system(input: TokenStream) -> TokenStream {
             read_types.push(quote! { std::any::TypeId::of::<#ty>() });
         }
     }
+    // None=[...] filters read the excluded storages' masks, so they order against writers too
+    for ty in &negative_types {
+        let key = quote! { #ty }.to_string();
+        if read_keys.insert(key.clone(), true).is_none() {
+            read_types.push(quote! { std::any::TypeId::of::<#ty>() });
+        }
+    }
+
+    // Lookups read the Entity storage to check generations
+    if !lookups.is_empty() {
+        let key = quote! { decs::entity::Entity }.to_string();
+        if read_keys.insert(key, true).is_none() {
+            read_types.push(quote! { std::any::TypeId::of::<decs::entity::Entity>() });
+        }
+    }
+    for lookup in lookups.iter().filter(|l| !l.is_mut) {
+        let ty = &lookup.ty;
+        let key = quote! { #ty }.to_string();
+        if read_keys.insert(key.clone(), true).is_none() {
+            read_types.push(quote! { std::any::TypeId::of::<#ty>() });
+        }
+    }
 
     let write_types: Vec<_> = params
         .iter()
         .filter(|(_, _, is_mut)| *is_mut)
-        .map(|(_, ty, _)| quote! { std::any::TypeId::of::<#ty>() })
+        .map(|(_, ty, _)| ty)
+        .chain(lookups.iter().filter(|l| l.is_mut).map(|l| &l.ty))
+        .map(|ty| quote! { std::any::TypeId::of::<#ty>() })
         .collect();
 
     let struct_fields = storage_fields
@@ -296,6 +456,58 @@ pub fn system(input: TokenStream) -> TokenStream {
                 quote! { pub #field_name: *const decs::storage::Storage<#ty> }
             }
         });
+    let lookup_fields: Vec<Ident> = (0..lookups.len())
+        .map(|k| Ident::new(&format!("lookup_{}", k), system_name.span()))
+        .collect();
+    let lookup_struct_fields = lookups.iter().zip(&lookup_fields).map(|(lookup, field)| {
+        let ty = &lookup.ty;
+        quote! { pub #field: *mut decs::storage::Storage<#ty>, }
+    });
+    let (entities_struct_field, entities_init, entities_field_init) = if lookups.is_empty() {
+        (quote! {}, quote! {}, quote! {})
+    } else {
+        (
+            quote! { pub __decs_entities: *const decs::storage::Storage<decs::entity::Entity>, },
+            quote! { let __decs_entities = world.get_entity_storage() as *const _; },
+            quote! { __decs_entities, },
+        )
+    };
+    // A `Commands` parameter receives the world's handle, captured when the system is
+    // created, so the body can queue structural changes (e.g. insert what `None` excluded).
+    let uses_commands = query_fn
+        .sig
+        .inputs
+        .iter()
+        .any(|arg| matches!(arg, FnArg::Typed(pat_type) if is_commands_param(&pat_type.ty)));
+    // A query function with a return type returns `Result<(), SystemError>`; the run
+    // keeps the first error, tagged with its index, for the scheduler to collect.
+    let returns_result = !matches!(query_fn.sig.output, ReturnType::Default);
+    let (error_struct_field, error_field_init, take_error_impl) = if returns_result {
+        (
+            quote! { __decs_error: std::cell::Cell<Option<decs::system::SystemError>>, },
+            quote! { __decs_error: std::cell::Cell::new(None), },
+            quote! {
+                fn take_error(&self) -> Option<decs::system::SystemError> {
+                    self.__decs_error.take()
+                }
+            },
+        )
+    } else {
+        (quote! {}, quote! {}, quote! {})
+    };
+    let (commands_struct_field, commands_init, commands_field_init) = if uses_commands {
+        (
+            quote! { pub __decs_commands: decs::commands::Commands, },
+            quote! { let __decs_commands = world.commands(); },
+            quote! { __decs_commands, },
+        )
+    } else {
+        (quote! {}, quote! {}, quote! {})
+    };
+    let lookup_init = lookups.iter().zip(&lookup_fields).map(|(lookup, field)| {
+        let ty = &lookup.ty;
+        quote! { let #field = world.get_storage::<#ty>(); }
+    });
     let debug_struct_fields = quote! {};
 
     let new_storage_init = storage_fields.iter().map(|(field_name, ty, _, is_mut)| {
@@ -349,35 +561,121 @@ pub fn system(input: TokenStream) -> TokenStream {
             let chunk_var = &param_chunk_idents[i];
             if *is_mut {
                 let storage_field = &storage_fields[param_storage_indices[i]].0;
+                // Re-derive the chunk reference from its raw pointer so the view's
+                // lifetime is bound only by the frame borrow, not by `#chunk_var`.
                 quote! {
                     let mut #param_name = decs::view::ViewMut::new(
-                        #chunk_var,
+                        &mut *(&raw mut *#chunk_var),
                         chunk_item_idx as u32,
                         self.#storage_field,
                         storage_idx as u32,
                         page_idx as u32,
-                        _frame.current_tick,
+                        _frame,
                     );
                 }
             } else {
                 quote! {
                     let #param_name = {
                         let data = unsafe { (&*#chunk_var).data[chunk_item_idx].assume_init_ref() };
-                        decs::view::View::new(data)
+                        decs::view::View::new(
+                            data,
+                            ((storage_idx << 12) | (page_idx << 6) | chunk_item_idx) as u32,
+                            _frame,
+                        )
                     };
                 }
             }
         })
         .collect();
 
-    let call_args: Vec<_> = params
+    // Chunk views share the chunk reference of their required storage
+    let chunk_view_gathering: Vec<_> = chunk_views
         .iter()
-        .map(|(name, _, is_mut)| {
-            if *is_mut {
-                quote! { &mut #name }
+        .map(|(name, ty)| {
+            let key = quote! { #ty }.to_string();
+            let chunk_var = Ident::new(
+                &format!("chunk_{}", required_index[&key]),
+                system_name.span(),
+            );
+            quote! {
+                let #name = decs::view::ChunkView::new(
+                    #chunk_var,
+                    ((storage_idx << 12) | (page_idx << 6)) as u32,
+                    chunk_item_idx as u32,
+                    _frame,
+                );
+            }
+        })
+        .collect();
+
+    // Lookups are rebuilt per item so they know which index the query's own view of
+    // the same component holds.
+    let lookup_gathering: Vec<_> = lookups
+        .iter()
+        .zip(&lookup_fields)
+        .map(|(lookup, field)| {
+            let name = &lookup.name;
+            let ty = &lookup.ty;
+            let key = quote! { #ty }.to_string();
+            let viewed = params
+                .iter()
+                .filter(|(_, view_ty, _)| quote! { #view_ty }.to_string() == key)
+                .map(|(_, _, is_mut)| *is_mut)
+                .reduce(|a, b| a || b);
+            let viewed = match viewed {
+                Some(view_mut) => quote! {
+                    Some((((storage_idx << 12) | (page_idx << 6) | chunk_item_idx) as u32, #view_mut))
+                },
+                None => quote! { None },
+            };
+            let binding = if lookup.by_ref == Some(true) {
+                quote! { mut #name }
             } else {
                 quote! { #name }
+            };
+            quote! {
+                let #binding = decs::view::Lookup::new(self.#field, self.__decs_entities, #viewed, _frame);
+            }
+        })
+        .collect();
+
+    // Call arguments follow the declared parameter order; a `&Frame` parameter
+    // receives the running frame (tick and timing) and a `Commands` one the world's
+    // command handle instead of a view.
+    let call_args: Vec<_> = query_fn
+        .sig
+        .inputs
+        .iter()
+        .filter_map(|arg| {
+            let FnArg::Typed(pat_type) = arg else {
+                return None;
+            };
+            if is_frame_param(&pat_type.ty) {
+                return Some(quote! { _frame });
+            }
+            if is_commands_param(&pat_type.ty) {
+                return Some(quote! { self.__decs_commands });
+            }
+            let Pat::Ident(pat_ident) = &*pat_type.pat else {
+                return None;
+            };
+            if let Some((name, _)) = chunk_views.iter().find(|(n, _)| *n == pat_ident.ident) {
+                return Some(quote! { #name });
             }
+            if let Some(lookup) = lookups.iter().find(|l| l.name == pat_ident.ident) {
+                let name = &lookup.name;
+                return Some(match lookup.by_ref {
+                    Some(true) => quote! { &mut #name },
+                    Some(false) => quote! { &#name },
+                    None => quote! { #name },
+                });
+            }
+            let (name, _, is_mut) = params.iter().find(|(n, _, _)| *n == pat_ident.ident)?;
+            Some(if *is_mut {
+                quote! { &mut #name }
+            } else {
+                quote! { #name }
+            })
         })
         .collect();
 
@@ -413,52 +711,76 @@ pub fn system(input: TokenStream) -> TokenStream {
         .filter_map(|i| {
             if *i < required_count {
                 let page_var = Ident::new(&format!("page_{}", i), system_name.span());
-                Some(quote! { page_mask &= unsafe { (*#page_var).changed_mask }; })
+                Some(quote! { page_mask &= unsafe { (*#page_var).changed_since(__decs_since) }; })
             } else {
                 None
             }
         })
         .collect();
+    // Report storage borrows to the debug aliasing checker (no-op unless enabled)
+    let borrow_tracking: Vec<_> = storage_fields
+        .iter()
+        .map(|(_, ty, _, is_mut)| {
+            quote! {
+                decs::aliasing::track_borrow::<#ty>(decs::system::System::name(self), #is_mut);
+            }
+        })
+        .chain(lookups.iter().map(|lookup| {
+            let (ty, is_mut) = (&lookup.ty, lookup.is_mut);
+            quote! {
+                decs::aliasing::track_borrow::<#ty>(decs::system::System::name(self), #is_mut);
+            }
+        }))
+        .collect();
+
+    // The storages borrowed by `run`, reported by `System::accesses`
+    let storage_accesses: Vec<_> = storage_fields
+        .iter()
+        .map(|(name, _, _, is_mut)| {
+            quote! { decs::system::StorageAccess::new(self.#name as *const _, #is_mut) }
+        })
+        .chain(lookup_fields.iter().zip(&lookups).map(|(field, lookup)| {
+            let is_mut = lookup.is_mut;
+            quote! { decs::system::StorageAccess::new(self.#field as *const _, #is_mut) }
+        }))
+        .chain((!lookups.is_empty()).then(|| {
+            quote! { decs::system::StorageAccess::new(self.__decs_entities, false) }
+        }))
+        .collect();
+
     // Generate rollback initialization sequences (hoisted to top of run)
     let rollback_inits: Vec<_> = storage_fields
         .iter()
         .filter(|(_, _, _, is_mut)| *is_mut)
         .map(|(name, _ty, _mutability, _)| {
             quote! {
-                {
-                    let ct = _frame.current_tick;
-                    if ((*self.#name).rollback.tick() != ct) {
-                        let new_current = if let Some(mut pooled) = (*self.#name).rollback_pool.pop() {
-                            pooled.reset_for_tick(ct);
-                            pooled
-                        } else {
-                            Box::new(decs::rollback::RollbackStorage::with_tick(ct))
-                        };
-                        let old = std::mem::replace(&mut (*self.#name).rollback, new_current);
-                        let mut merged = std::mem::take(&mut (*self.#name).prev);
-                        merged.push_back(old);
-                        (*self.#name).prev = merged;
-                        while (*self.#name).prev.len() > 64 {
-                            (*self.#name).prev.pop_front();
-                        }
-                    }
-                }
+                (*self.#name).ensure_rollback_tick(_frame.current_tick);
             }
         })
         .collect();
 
     let chunk_refs_init: Vec<_> = (0..required_count).map(|i| {
+        let name = &storage_fields[i].0;
         let is_mut = storage_fields[i].3;
         let page_var = Ident::new(&format!("page_{}", i), system_name.span());
         let chunk_var = Ident::new(&format!("chunk_{}", i), system_name.span());
         if is_mut {
             quote! {
+                (*#page_var).unshare_chunk(page_idx, &(*self.#name).chunk_layout);
                 let mut #chunk_var = &mut *(*#page_var).data[page_idx];
             }
         } else {
             quote! { let #chunk_var = &*#page_var.data[page_idx]; }
         }
     }).collect();
+    // Hint the next matched chunk of every storage into cache while this one runs
+    // (no-op unless decs' `prefetch` feature is on).
+    let chunk_prefetches: Vec<_> = (0..required_count)
+        .map(|i| {
+            let page_var = Ident::new(&format!("page_{}", i), system_name.span());
+            quote! { (*#page_var).prefetch_next(page_mask_iter & (page_mask_iter - 1)); }
+        })
+        .collect();
     let item_mask_intersections: Vec<_> = (1..required_count)
         .map(|i| {
             let chunk_var = Ident::new(&format!("chunk_{}", i), system_name.span());
@@ -470,7 +792,7 @@ pub fn system(input: TokenStream) -> TokenStream {
         .filter_map(|i| {
             if *i < required_count {
                 let chunk_var = Ident::new(&format!("chunk_{}", i), system_name.span());
-                Some(quote! { m &= #chunk_var.changed_mask; })
+                Some(quote! { m &= #chunk_var.changed_since(__decs_since); })
             } else {
                 None
             }
@@ -528,8 +850,246 @@ pub fn system(input: TokenStream) -> TokenStream {
         })
         .collect();
 
+    // Changed=[...] storages also prune storage indices holding no value written since
+    // the last run.
+    let storage_changed_intersections: Vec<_> = changed_indices_set
+        .iter()
+        .filter(|i| **i < required_count)
+        .map(|i| {
+            let name = &storage_fields[*i].0;
+            quote! { storage_mask &= (*self.#name).changed_since(__decs_since); }
+        })
+        .collect();
+    // Page-level candidate masks, read from each candidate storage index's pages.
+    let page_mask_init = quote! {
+        let mut page_mask = page_0.presence_mask;
+        #(#page_mask_intersections)*
+        #(#page_changed_intersections)*
+        let mut none_chunk_full_or: u64 = 0u64;
+        #(#none_chunk_full_or_inits)*
+        page_mask &= !none_chunk_full_or;
+    };
+
+    // Queries without Changed filters only depend on the page/chunk layout of their
+    // storages, so the matched pages are cached and rescanned only when a storage's
+    // structure_version moves.
+    let cached = changed_indices_set.is_empty();
+    let all_storage_names: Vec<_> = storage_fields.iter().map(|(name, ..)| name).collect();
+    let plan_page_refs_init: Vec<_> = (0..required_count)
+        .map(|i| {
+            let name = &storage_fields[i].0;
+            let page_var = Ident::new(&format!("page_{}", i), system_name.span());
+            quote! { let #page_var = &*(*self.#name).data[storage_idx]; }
+        })
+        .collect();
+    let query_call = if returns_result {
+        quote! {
+            let __decs_result: Result<(), decs::system::SystemError> =
+                #system_name::#query_fn_name(#(#call_args),*);
+            if let Err(mut err) = __decs_result {
+                err.index.get_or_insert(
+                    ((storage_idx << 12) | (page_idx << 6) | chunk_item_idx) as u32,
+                );
+                let first = self.__decs_error.take().unwrap_or(err);
+                self.__decs_error.set(Some(first));
+            }
+        }
+    } else {
+        quote! { #system_name::#query_fn_name(#(#call_args),*); }
+    };
+    let chunk_loop = quote! {
+        let mut page_mask_iter = page_mask & __decs_range.page_mask(storage_idx);
+        while page_mask_iter != 0 {
+            let page_idx = page_mask_iter.trailing_zeros() as usize;
+            #(#chunk_refs_init)*
+            #(#chunk_prefetches)*
+            let mut item_mask = {
+                let mut m = chunk_0.presence_mask;
+                #(#item_mask_intersections)*
+                #(#item_changed_intersections)*
+                m
+            };
+            let mut none_item_presence_or: u64 = 0u64;
+            #(#none_item_presence_or_inits)*
+            item_mask &= !none_item_presence_or;
+            item_mask &= __decs_range.chunk_mask(storage_idx, page_idx);
+
+            let mut item_mask_iter = item_mask;
+            while item_mask_iter != 0 {
+                let chunk_item_idx = item_mask_iter.trailing_zeros() as usize;
+                #(#param_gathering)*
+                #(#chunk_view_gathering)*
+                #(#lookup_gathering)*
+                #query_call
+                item_mask_iter &= item_mask_iter - 1;
+            }
+            #(#propagate_changes)*
+
+            page_mask_iter &= page_mask_iter - 1;
+        }
+    };
+    // Changed=[...] matches values stamped after the previous run of this system. The
+    // world's change tick (reached through the frame) is advanced before the run (so
+    // this run's own writes are not matched next time) and after it (so later writers
+    // stamp something newer).
+    let (changed_struct_field, changed_field_init, changed_begin, changed_end) = if cached {
+        (quote! {}, quote! {}, quote! {}, quote! {})
+    } else {
+        (
+            quote! { __decs_last_run: std::cell::UnsafeCell<decs::tick::Tick>, },
+            quote! { __decs_last_run: std::cell::UnsafeCell::new(decs::tick::Tick(0)), },
+            quote! {
+                let __decs_since = *self.__decs_last_run.get();
+                *self.__decs_last_run.get() = _frame.advance_change_tick();
+            },
+            quote! {
+                _frame.advance_change_tick();
+            },
+        )
+    };
+    let (plan_struct_field, plan_field_init, plan_accessor, run_body) = if cached {
+        (
+            quote! { __decs_plan: std::cell::UnsafeCell<decs::system::QueryPlanCache>, },
+            quote! { __decs_plan: std::cell::UnsafeCell::new(decs::system::QueryPlanCache::new()), },
+            quote! {
+                /// Returns the cached page matches of this system's query.
+                pub fn query_plan(&self) -> &decs::system::QueryPlanCache {
+                    unsafe { &*self.__decs_plan.get() }
+                }
+            },
+            quote! {
+                let plan = &mut *self.__decs_plan.get();
+                let versions = [#((*self.#all_storage_names).structure_version),*];
+                if !plan.is_current(&versions) {
+                    plan.rebuild(&versions);
+                    let mut storage_mask = #mask_intersection & !#none_full_pages_or;
+                    while storage_mask != 0 {
+                        let storage_idx = storage_mask.trailing_zeros() as usize;
+                        #(#plan_page_refs_init)*
+                        #page_mask_init
+                        if page_mask != 0 {
+                            plan.push(storage_idx, page_mask);
+                        }
+                        storage_mask &= storage_mask - 1;
+                    }
+                }
+                for &(storage_idx, page_mask) in plan.pages() {
+                    #(#page_refs_init)*
+                    #chunk_loop
+                }
+            },
+        )
+    } else {
+        (
+            quote! {},
+            quote! {},
+            quote! {},
+            quote! {
+                let mut storage_mask =
+                    #mask_intersection & !#none_full_pages_or & __decs_range.storage_mask();
+                #(#storage_changed_intersections)*
+                while storage_mask != 0 {
+                    let storage_idx = storage_mask.trailing_zeros() as usize;
+                    #(#page_refs_init)*
+                    #page_mask_init
+
+                    #chunk_loop
+
+                    storage_mask &= storage_mask - 1;
+                }
+            },
+        )
+    };
+
+    // System::match_count / matches_any: the query's mask walk without touching any
+    // storage or running the query function.
+    let match_page_refs: Vec<_> = (0..required_count)
+        .map(|i| {
+            let name = &storage_fields[i].0;
+            let page_var = Ident::new(&format!("page_{}", i), system_name.span());
+            quote! { let #page_var = &*(*self.#name).data[storage_idx]; }
+        })
+        .collect();
+    let match_chunk_refs: Vec<_> = (0..required_count)
+        .map(|i| {
+            let page_var = Ident::new(&format!("page_{}", i), system_name.span());
+            let chunk_var = Ident::new(&format!("chunk_{}", i), system_name.span());
+            quote! { let #chunk_var = &*#page_var.data[page_idx]; }
+        })
+        .collect();
+    let match_since = if cached {
+        quote! {}
+    } else {
+        quote! { let __decs_since = *self.__decs_last_run.get(); }
+    };
+
+    // Changed=[...] needs the storage to maintain changed masks
+    let change_detection_checks: Vec<_> = changed_types
+        .iter()
+        .map(|ty| {
+            quote! {
+                const _: () = assert!(
+                    <#ty as decs::component::Component>::CHANGE_DETECTION,
+                    concat!(
+                        "Changed=[",
+                        stringify!(#ty),
+                        "] requires change detection, but the component opts out with #[component(change_detection = false)]"
+                    )
+                );
+            }
+        })
+        .collect();
+
+    // System::explain: report every filter that rejects an index, in the order the
+    // query applies them (required presence, Changed ticks, None exclusions).
+    let explain_missing: Vec<_> = (0..required_count)
+        .map(|i| {
+            let (name, ty, _, _) = &storage_fields[i];
+            quote! {
+                if (*self.#name).get(index).is_none() {
+                    out.push(decs::system::QueryMismatch::Missing(std::any::type_name::<#ty>()));
+                }
+            }
+        })
+        .collect();
+    let mut explain_changed_indices: Vec<usize> = changed_indices_set.iter().copied().collect();
+    explain_changed_indices.sort_unstable();
+    let explain_unchanged: Vec<_> = explain_changed_indices
+        .iter()
+        .map(|&i| {
+            let (name, ty, _, _) = &storage_fields[i];
+            quote! {
+                if (*self.#name)
+                    .value_change_tick(index)
+                    .is_some_and(|tick| !tick.is_after(*self.__decs_last_run.get()))
+                {
+                    out.push(decs::system::QueryMismatch::Unchanged(std::any::type_name::<#ty>()));
+                }
+            }
+        })
+        .collect();
+    let explain_excluded: Vec<_> = (required_count..storage_fields.len())
+        .map(|i| {
+            let (name, ty, _, _) = &storage_fields[i];
+            quote! {
+                if (*self.#name).get(index).is_some() {
+                    out.push(decs::system::QueryMismatch::Excluded(std::any::type_name::<#ty>()));
+                }
+            }
+        })
+        .collect();
+
     // Generate storage refresh sequences
 
+    // Systems writing nothing (no `&mut ViewMut`, mutable lookups or `Commands`) are
+    // read-only, which the scheduler uses to gather them into shared wavefronts.
+    let read_only = write_types.is_empty() && !uses_commands;
+    let read_only_impl = if read_only {
+        quote! { impl decs::system::ReadOnlySystem for #system_name {} }
+    } else {
+        quote! {}
+    };
+
     let parent_impl = if let Some(pt) = parent_type {
         quote! {
             fn parent(&self) -> Option<&dyn decs::system::SystemGroup> {
@@ -543,9 +1103,18 @@ pub fn system(input: TokenStream) -> TokenStream {
     let expanded = quote! {
         pub struct #system_name {
             #(#struct_fields,)*
+            #(#lookup_struct_fields)*
+            __decs_range: std::sync::atomic::AtomicU64,
+            #entities_struct_field
+            #commands_struct_field
+            #error_struct_field
+            #plan_struct_field
+            #changed_struct_field
             #debug_struct_fields
         }
 
+        #(#change_detection_checks)*
+
         unsafe impl Send for #system_name {}
         unsafe impl Sync for #system_name {}
 
@@ -553,62 +1122,100 @@ pub fn system(input: TokenStream) -> TokenStream {
             pub fn new(world: &mut decs::world::World) -> Self {
                 unsafe {
                     #(#new_storage_init)*
+                    #(#lookup_init)*
+                    #entities_init
+                    #commands_init
 
                     Self {
                         #(#new_field_init,)*
+                        #(#lookup_fields,)*
+                        __decs_range: std::sync::atomic::AtomicU64::new(
+                            decs::system::IndexRange::ALL.to_bits(),
+                        ),
+                        #entities_field_init
+                        #commands_field_init
+                        #error_field_init
+                        #plan_field_init
+                        #changed_field_init
                         #new_debug_init
                     }
                 }
             }
 
-            fn #query_fn_name(#query_params) #query_fn_body
-        }
+            #plan_accessor
 
-        impl decs::system::System for #system_name {
-            fn run(&self, _frame: &decs::frame::Frame) {
+            /// Restricts the query to the global indices in `range`, e.g. to spread a
+            /// large query over several ticks or shards. Every index by default.
+            /// `Changed` filters still match writes since the previous run only, so
+            /// changes outside the range are missed rather than deferred.
+            pub fn set_index_range(&self, range: std::ops::Range<u32>) {
+                self.__decs_range.store(
+                    decs::system::IndexRange::new(range).to_bits(),
+                    std::sync::atomic::Ordering::Relaxed,
+                );
+            }
+
+            /// The global indices the query is restricted to.
+            pub fn index_range(&self) -> std::ops::Range<u32> {
+                self.__decs_index_range().range()
+            }
+
+            fn __decs_index_range(&self) -> decs::system::IndexRange {
+                decs::system::IndexRange::from_bits(
+                    self.__decs_range.load(std::sync::atomic::Ordering::Relaxed),
+                )
+            }
+
+            fn #query_fn_name #query_generics (#query_params) #query_output #query_where #query_fn_body
+
+            /// Calls `f` with the nonzero mask of matched items of every chunk the query
+            /// would visit, until it returns false.
+            fn __decs_match_masks(&self, mut f: impl FnMut(u64) -> bool) {
                 unsafe {
-                    #(#rollback_inits)*
-                    let mut storage_mask = #mask_intersection & !#none_full_pages_or;
+                    let __decs_range = self.__decs_index_range();
+                    #match_since
+                    let mut storage_mask =
+                        #mask_intersection & !#none_full_pages_or & __decs_range.storage_mask();
                     while storage_mask != 0 {
                         let storage_idx = storage_mask.trailing_zeros() as usize;
-                        #(#page_refs_init)*
+                        #(#match_page_refs)*
                         let mut page_mask = page_0.presence_mask;
                         #(#page_mask_intersections)*
                         #(#page_changed_intersections)*
                         let mut none_chunk_full_or: u64 = 0u64;
                         #(#none_chunk_full_or_inits)*
-                        page_mask &= !none_chunk_full_or;
-
-                        let mut page_mask_iter = page_mask;
-                        while page_mask_iter != 0 {
-                            let page_idx = page_mask_iter.trailing_zeros() as usize;
-                            #(#chunk_refs_init)*
-                            let mut item_mask = {
-                                let mut m = chunk_0.presence_mask;
-                                #(#item_mask_intersections)*
-                                #(#item_changed_intersections)*
-                                m
-                            };
+                        page_mask &= !none_chunk_full_or & __decs_range.page_mask(storage_idx);
+                        while page_mask != 0 {
+                            let page_idx = page_mask.trailing_zeros() as usize;
+                            #(#match_chunk_refs)*
+                            let mut m = chunk_0.presence_mask;
+                            #(#item_mask_intersections)*
+                            #(#item_changed_intersections)*
                             let mut none_item_presence_or: u64 = 0u64;
                             #(#none_item_presence_or_inits)*
-                            item_mask &= !none_item_presence_or;
-
-                            let mut item_mask_iter = item_mask;
-                            while item_mask_iter != 0 {
-                                let chunk_item_idx = item_mask_iter.trailing_zeros() as usize;
-                                #(#param_gathering)*
-                                #system_name::#query_fn_name(#(#call_args),*);
-                                item_mask_iter &= item_mask_iter - 1;
+                            m &= !none_item_presence_or & __decs_range.chunk_mask(storage_idx, page_idx);
+                            if m != 0 && !f(m) {
+                                return;
                             }
-                            #(#propagate_changes)*
-
-                            page_mask_iter &= page_mask_iter - 1;
+                            page_mask &= page_mask - 1;
                         }
-
                         storage_mask &= storage_mask - 1;
                     }
                 }
             }
+        }
+
+        impl decs::system::System for #system_name {
+            fn run(&self, _frame: &decs::frame::Frame) {
+                #(#borrow_tracking)*
+                unsafe {
+                    #(#rollback_inits)*
+                    let __decs_range = self.__decs_index_range();
+                    #changed_begin
+                    #run_body
+                    #changed_end
+                }
+            }
 
             fn reads(&self) -> &[std::any::TypeId] {
                 static READS: &[std::any::TypeId] = &[#(#read_types),*];
@@ -620,31 +1227,291 @@ pub fn system(input: TokenStream) -> TokenStream {
                 WRITES
             }
 
+            fn accesses(&self) -> Vec<decs::system::StorageAccess> {
+                vec![#(#storage_accesses),*]
+            }
+
+            fn read_only(&self) -> bool {
+                #read_only
+            }
+
             fn as_any(&self) -> &dyn std::any::Any {
                 self
             }
 
             fn debug_counts(&self) -> (usize, usize) { (0, 0) }
+
+            fn explain(&self, index: u32) -> Vec<decs::system::QueryMismatch> {
+                let mut out = Vec::new();
+                unsafe {
+                    #(#explain_missing)*
+                    #(#explain_unchanged)*
+                    #(#explain_excluded)*
+                }
+                if !self.__decs_index_range().contains(index) {
+                    out.push(decs::system::QueryMismatch::OutOfRange);
+                }
+                out
+            }
+
+            fn match_count(&self) -> usize {
+                let mut count = 0;
+                self.__decs_match_masks(|mask| {
+                    count += mask.count_ones() as usize;
+                    true
+                });
+                count
+            }
+
+            fn matches_any(&self) -> bool {
+                let mut any = false;
+                self.__decs_match_masks(|_| {
+                    any = true;
+                    false
+                });
+                any
+            }
+            #take_error_impl
             #parent_impl
         }
+
+        #read_only_impl
     };
 
     TokenStream::from(expanded)
 }
 
-#[proc_macro_derive(Component)]
+#[proc_macro_derive(Component, attributes(component))]
 pub fn derive_component(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
+    let mut change_detection = true;
+    let mut diff = false;
+    let mut version: Option<syn::LitInt> = None;
+    let mut event = false;
+    let mut resource = false;
+    let mut category: Option<syn::LitStr> = None;
+    let mut replicated = false;
+    let mut priority: Option<syn::LitInt> = None;
+    let mut quantize: Option<syn::Path> = None;
+    let mut out_of_line = false;
+    let mut field_mask: Option<Vec<syn::Path>> = None;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("component")) {
+        let parsed = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("change_detection") {
+                let value: syn::LitBool = meta.value()?.parse()?;
+                change_detection = value.value;
+                Ok(())
+            } else if meta.path.is_ident("diff") {
+                diff = true;
+                Ok(())
+            } else if meta.path.is_ident("version") {
+                version = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("event") {
+                event = true;
+                Ok(())
+            } else if meta.path.is_ident("resource") {
+                resource = true;
+                Ok(())
+            } else if meta.path.is_ident("category") {
+                category = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("replicated") {
+                replicated = true;
+                Ok(())
+            } else if meta.path.is_ident("priority") {
+                let value: syn::LitInt = meta.value()?.parse()?;
+                value.base10_parse::<u8>()?;
+                priority = Some(value);
+                Ok(())
+            } else if meta.path.is_ident("quantize") {
+                quantize = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("out_of_line") {
+                out_of_line = true;
+                Ok(())
+            } else if meta.path.is_ident("field_mask") {
+                // `field_mask(Serialize, ...)` lists derives for the setter enum
+                let mut derives = Vec::new();
+                if meta.input.peek(token::Paren) {
+                    meta.parse_nested_meta(|derive| {
+                        derives.push(derive.path);
+                        Ok(())
+                    })?;
+                }
+                field_mask = Some(derives);
+                Ok(())
+            } else {
+                Err(meta.error(
+                    "expected `change_detection = <bool>`, `diff`, `version = <u32>`, `event`, `resource`, `category = \"...\"`, `replicated`, `priority = <u8>`, `quantize = <path>`, `out_of_line` or `field_mask(<derives>)`",
+                ))
+            }
+        });
+        if let Err(err) = parsed {
+            return err.to_compile_error().into();
+        }
+    }
+    if event && resource {
+        return syn::Error::new_spanned(
+            &input.ident,
+            "a component cannot be both an `event` and a `resource`",
+        )
+        .to_compile_error()
+        .into();
+    }
+    if !replicated {
+        let unreplicated = match (&priority, &quantize) {
+            (Some(priority), _) => Some(syn::Error::new_spanned(
+                priority,
+                "`priority` requires `replicated`",
+            )),
+            (None, Some(quantize)) => Some(syn::Error::new_spanned(
+                quantize,
+                "`quantize` requires `replicated`",
+            )),
+            (None, None) => None,
+        };
+        if let Some(err) = unreplicated {
+            return err.to_compile_error().into();
+        }
+    }
+    let change_detection_const = if change_detection {
+        quote! {}
+    } else {
+        quote! { const CHANGE_DETECTION: bool = false; }
+    };
+    let version_const = match version {
+        Some(version) => quote! { const VERSION: u32 = #version; },
+        None => quote! {},
+    };
+    // The doc comment and `category`/`replicated`/`priority` end up in `Ecs::metadata`.
+    let doc = input
+        .attrs
+        .iter()
+        .filter(|a| a.path().is_ident("doc"))
+        .filter_map(|a| match &a.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                value: syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(s), .. }),
+                ..
+            }) => Some(s.value()),
+            _ => None,
+        })
+        .map(|line| line.strip_prefix(' ').map(str::to_owned).unwrap_or(line))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let metadata_const = if doc.is_empty() && category.is_none() && !replicated {
+        quote! {}
+    } else {
+        let category = match category {
+            Some(category) => quote! { Some(#category) },
+            None => quote! { None },
+        };
+        let priority = match priority {
+            Some(priority) => quote! { #priority },
+            None => quote! { 0 },
+        };
+        quote! {
+            const METADATA: decs::component::ComponentMetadata = decs::component::ComponentMetadata {
+                doc: #doc,
+                category: #category,
+                replicated: #replicated,
+                priority: #priority,
+                ..decs::component::ComponentMetadata::NONE
+            };
+        }
+    };
+    // `quantize = path` rounds replicated values before they are encoded.
+    let quantize_const = match quantize {
+        Some(quantize) => quote! {
+            const QUANTIZE: Option<fn(&Self) -> Self> = Some(#quantize);
+        },
+        None => quote! {},
+    };
+    // `#[component(out_of_line)]` makes chunks point to pooled values instead of holding them.
+    let repr_type = if out_of_line {
+        quote! { type Repr = decs::slot::OutOfLine<Self>; }
+    } else {
+        quote! {}
+    };
+    // Peers compare it in `net::Handshake` to catch components whose fields differ
+    // between builds.
+    let schema_hash = schema_hash(&input.generics, &input.data);
+    // `#[component(diff)]` lets net::diff_worlds compare and print values.
+    let diff_fns = if diff {
+        quote! {
+            fn desync_eq(&self, other: &Self) -> bool {
+                self == other
+            }
+            fn desync_debug(&self) -> Option<String> {
+                Some(format!("{:?}", self))
+            }
+        }
+    } else {
+        quote! {}
+    };
+    // `#[component(field_mask)]` generates the `{Name}Field` setter enum for Storage::set_field.
+    let field_setter = match field_mask {
+        Some(derives) => match field_setter(&input, &derives) {
+            Ok(tokens) => tokens,
+            Err(err) => return err.to_compile_error().into(),
+        },
+        None => quote! {},
+    };
     let name = input.ident;
     let generics = input.generics;
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
     let id_mod = format_ident!("__decs_component_id_{}", name);
+    // Events are dropped wholesale at the end of every tick instead of following Destroyed.
+    let cleanup_system = if event {
+        quote! { decs::system::TemporaryComponentCleanupSystem::<#name, decs::world::CleanupGroup> }
+    } else {
+        quote! { decs::system::ComponentCleanupSystem::<#name> }
+    };
+    let event_const = if event {
+        quote! { const EVENT: bool = true; }
+    } else {
+        quote! {}
+    };
+    // Resource storages hold no entity components, so there is nothing to clean up.
+    let resource_const = if resource {
+        quote! { const RESOURCE: bool = true; }
+    } else {
+        quote! {}
+    };
+    let schedule_cleanup = if resource {
+        quote! {
+            fn schedule_cleanup_system(_world: &mut decs::world::World) {}
+        }
+    } else {
+        quote! {
+            fn schedule_cleanup_system(world: &mut decs::world::World) {
+                let sys = #cleanup_system::new(world);
+                world.scheduler_mut().add_system(sys);
+            }
+        }
+    };
+    let event_impl = if event {
+        quote! { impl #impl_generics decs::event::Event for #name #ty_generics #where_clause {} }
+    } else {
+        quote! {}
+    };
     TokenStream::from(quote! {
         #[allow(non_snake_case)]
         mod #id_mod {
             pub(super) static mut ID: u32 = u32::MAX;
         }
         impl #impl_generics decs::component::Component for #name #ty_generics #where_clause {
+            #change_detection_const
+            #version_const
+            #event_const
+            #resource_const
+            #metadata_const
+            #quantize_const
+            #repr_type
+            #diff_fns
+
+            const SCHEMA_HASH: u64 = #schema_hash;
+
             fn id() -> u32 {
                 unsafe { self::#id_mod::ID }
             }
@@ -656,14 +1523,129 @@ pub fn derive_component(input: TokenStream) -> TokenStream {
                 }
             }
 
-            fn schedule_cleanup_system(world: &mut decs::world::World) {
-                let sys = decs::system::ComponentCleanupSystem::<#name>::new(world);
-                world.scheduler_mut().add_system(sys);
+            #schedule_cleanup
+        }
+        #event_impl
+        #field_setter
+
+    })
+}
+
+/// The `{Name}Field` enum of `#[component(field_mask)]`, with a variant per field, and
+/// its `FieldSetter` impl.
+fn field_setter(
+    input: &DeriveInput,
+    derives: &[syn::Path],
+) -> syn::Result<proc_macro2::TokenStream> {
+    let syn::Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "`field_mask` requires a struct",
+        ));
+    };
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "`field_mask` does not support generic components",
+        ));
+    }
+    if data.fields.len() > 64 {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "`field_mask` supports at most 64 fields",
+        ));
+    }
+    let name = &input.ident;
+    let vis = &input.vis;
+    let setter = format_ident!("{}Field", name);
+    let mut variants = Vec::new();
+    let mut names = Vec::new();
+    let mut members = Vec::new();
+    let mut types = Vec::new();
+    for (i, field) in data.fields.iter().enumerate() {
+        let (variant, member, field_name) = match &field.ident {
+            Some(ident) => {
+                let unraw = ident.to_string().trim_start_matches("r#").to_string();
+                let camel: String = unraw
+                    .split('_')
+                    .filter(|part| !part.is_empty())
+                    .map(|part| {
+                        let mut chars = part.chars();
+                        let first = chars.next().unwrap().to_ascii_uppercase();
+                        std::iter::once(first).chain(chars).collect::<String>()
+                    })
+                    .collect();
+                (format_ident!("{}", camel), quote! { #ident }, unraw)
+            }
+            None => {
+                let index = syn::Index::from(i);
+                (
+                    format_ident!("Field{}", i),
+                    quote! { #index },
+                    i.to_string(),
+                )
             }
+        };
+        variants.push(variant);
+        members.push(member);
+        names.push(field_name);
+        types.push(&field.ty);
+    }
+    let indices = (0..variants.len() as u32).collect::<Vec<_>>();
+    let doc = format!(
+        "Setters for the fields of `{}`, one variant per field; see `decs::fields`.",
+        name
+    );
+    Ok(quote! {
+        #[doc = #doc]
+        #[derive(Clone, #(#derives),*)]
+        #vis enum #setter {
+            #(#variants(#types),)*
         }
 
+        impl decs::fields::FieldSetter<#name> for #setter {
+            const NAMES: &'static [&'static str] = &[#(#names),*];
+
+            fn index(&self) -> u32 {
+                match self {
+                    #(Self::#variants(_) => #indices,)*
+                }
+            }
+
+            fn apply(self, target: &mut #name) {
+                match self {
+                    #(Self::#variants(value) => target.#members = value,)*
+                }
+            }
+
+            fn read(value: &#name, index: u32) -> Option<Self> {
+                match index {
+                    #(#indices => Some(Self::#variants(value.#members.clone())),)*
+                    _ => None,
+                }
+            }
+        }
     })
 }
+
+/// FNV-1a hash of a component's generics and fields (or variants), whitespace removed,
+/// for `Component::SCHEMA_HASH`.
+fn schema_hash(generics: &syn::Generics, data: &syn::Data) -> u64 {
+    use quote::ToTokens;
+    let body = match data {
+        syn::Data::Struct(s) => s.fields.to_token_stream(),
+        syn::Data::Enum(e) => e.variants.to_token_stream(),
+        syn::Data::Union(u) => u.fields.to_token_stream(),
+    };
+    let schema = format!("{}{}", generics.to_token_stream(), body);
+    schema
+        .bytes()
+        .filter(|b| !b.is_ascii_whitespace())
+        .fold(0xCBF2_9CE4_8422_2325, |hash, b| {
+            (hash ^ b as u64).wrapping_mul(0x0000_0100_0000_01B3)
+        })
+}
+
 struct SystemGroupInput {
     group_name: Ident,
     before_types: Vec<Type>,
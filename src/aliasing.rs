@@ -0,0 +1,132 @@
+//! Debug-mode storage aliasing checker.
+//!
+//! Systems hold raw `*mut Storage<T>` pointers, so two systems touching the same
+//! component in one wavefront (where at least one writes) is undefined behavior once
+//! wavefronts run in parallel, and silently order-dependent while they run serially.
+//!
+//! With the `debug-aliasing` feature enabled, the scheduler opens a batch per wavefront
+//! and every system reports the storages it actually dereferences through
+//! `track_borrow`. A second system borrowing a storage that conflicts with an
+//! outstanding borrow panics with both system names. Without the feature every
+//! function here is an inlined no-op.
+//!
+//! Systems generated by `system!` and the built-in systems report their borrows
+//! automatically; hand-written systems should call `track_borrow` for each storage
+//! they dereference at the start of `run`.
+
+#[cfg(feature = "debug-aliasing")]
+mod imp {
+    use std::any::TypeId;
+    use std::cell::RefCell;
+
+    struct Borrow {
+        type_id: TypeId,
+        type_name: &'static str,
+        system: &'static str,
+        mutable: bool,
+    }
+
+    thread_local! {
+        static BATCH: RefCell<Option<Vec<Borrow>>> = const { RefCell::new(None) };
+    }
+
+    pub fn begin_batch() {
+        BATCH.with(|b| *b.borrow_mut() = Some(Vec::new()));
+    }
+
+    pub fn end_batch() {
+        BATCH.with(|b| *b.borrow_mut() = None);
+    }
+
+    pub fn track_borrow_id(
+        type_id: TypeId,
+        type_name: &'static str,
+        system: &'static str,
+        mutable: bool,
+    ) {
+        BATCH.with(|b| {
+            let mut batch = b.borrow_mut();
+            let Some(borrows) = batch.as_mut() else {
+                return;
+            };
+            if let Some(other) = borrows.iter().find(|o| {
+                o.type_id == type_id && o.system != system && (o.mutable || mutable)
+            }) {
+                let mode = |m: bool| if m { "writes" } else { "reads" };
+                let message = format!(
+                    "storage aliasing conflict on {}: {} {} it while {} {} it in the same wavefront",
+                    other.type_name,
+                    system,
+                    mode(mutable),
+                    other.system,
+                    mode(other.mutable),
+                );
+                // Release the batch before unwinding so later runs start clean.
+                *batch = None;
+                drop(batch);
+                panic!("{}", message);
+            }
+            borrows.push(Borrow {
+                type_id,
+                type_name,
+                system,
+                mutable,
+            });
+        });
+    }
+}
+
+#[cfg(not(feature = "debug-aliasing"))]
+mod imp {
+    use std::any::TypeId;
+
+    #[inline(always)]
+    pub fn begin_batch() {}
+
+    #[inline(always)]
+    pub fn end_batch() {}
+
+    #[inline(always)]
+    pub fn track_borrow_id(
+        _type_id: TypeId,
+        _type_name: &'static str,
+        _system: &'static str,
+        _mutable: bool,
+    ) {
+    }
+}
+
+/// Starts tracking borrows for one wavefront. Called by the scheduler.
+#[inline(always)]
+pub fn begin_batch() {
+    imp::begin_batch()
+}
+
+/// Drops all outstanding borrows of the current wavefront. Called by the scheduler.
+#[inline(always)]
+pub fn end_batch() {
+    imp::end_batch()
+}
+
+/// Records that `system` dereferences the storage of `T` in the current wavefront,
+/// panicking if it conflicts with a borrow taken by another system.
+#[inline(always)]
+pub fn track_borrow<T: 'static>(system: &'static str, mutable: bool) {
+    track_borrow_id(
+        std::any::TypeId::of::<T>(),
+        std::any::type_name::<T>(),
+        system,
+        mutable,
+    )
+}
+
+/// Type-erased variant of `track_borrow` for systems that only hold `dyn StorageLike`.
+#[inline(always)]
+pub fn track_borrow_id(
+    type_id: std::any::TypeId,
+    type_name: &'static str,
+    system: &'static str,
+    mutable: bool,
+) {
+    imp::track_borrow_id(type_id, type_name, system, mutable)
+}
@@ -67,6 +67,16 @@ impl Arena {
         }
     }
 
+    /// Frees every block, unlike `reset`, which keeps the first one for reuse.
+    pub fn release(&mut self) {
+        let mut next = self.current.get_mut().take();
+        while let Some(mut c) = next {
+            next = c.next.take();
+        }
+        *self.ptr.get_mut() = 0;
+        *self.end.get_mut() = 0;
+    }
+
     fn alloc_chunk(&self, size: usize) -> Result<(), AllocError> {
         let size = size.max(CHUNK_SIZE);
         let mut new_chunk = Chunk::new(size).ok_or(AllocError)?;
@@ -0,0 +1,148 @@
+//! Opt-in structural change log (feature `audit-log`).
+//!
+//! Records every insert and removal of a component value made through `Storage::set`,
+//! `Storage::remove`, the storage writer and the cleanup systems, together with the tick
+//! and the system that was running, to answer "who removed my component". The log is
+//! per thread (systems run on the thread calling `World::run`), keeps the newest
+//! `MAX_ENTRIES` entries and can be dumped with `to_json`. Without the feature the
+//! `record`/`enter_system` hooks are inlined no-ops and the log is always empty.
+
+use crate::tick::Tick;
+#[cfg(feature = "audit-log")]
+use std::cell::{Cell, RefCell};
+#[cfg(feature = "audit-log")]
+use std::collections::VecDeque;
+use std::fmt::Write;
+
+/// Number of entries kept before the oldest are dropped.
+pub const MAX_ENTRIES: usize = 1 << 16;
+
+/// Kind of structural operation.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StructuralOp {
+    /// A value was written to an empty slot.
+    Insert,
+    /// A value was removed.
+    Remove,
+}
+
+/// One logged operation.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AuditEntry {
+    pub tick: Tick,
+    pub component: &'static str,
+    pub index: u32,
+    pub op: StructuralOp,
+    /// The scheduled system that was running, or None for writes from outside `run`.
+    pub system: Option<&'static str>,
+}
+
+#[cfg(feature = "audit-log")]
+thread_local! {
+    static LOG: RefCell<VecDeque<AuditEntry>> = const { RefCell::new(VecDeque::new()) };
+    static CURRENT_SYSTEM: Cell<Option<&'static str>> = const { Cell::new(None) };
+}
+
+/// Records a structural operation on the storage of `T`.
+#[inline(always)]
+pub fn record<T>(tick: Tick, index: u32, op: StructuralOp) {
+    #[cfg(feature = "audit-log")]
+    LOG.with(|log| {
+        let mut log = log.borrow_mut();
+        if log.len() == MAX_ENTRIES {
+            log.pop_front();
+        }
+        log.push_back(AuditEntry {
+            tick,
+            component: std::any::type_name::<T>(),
+            index,
+            op,
+            system: CURRENT_SYSTEM.with(|c| c.get()),
+        });
+    });
+    #[cfg(not(feature = "audit-log"))]
+    let _ = (tick, index, op);
+}
+
+/// Marks `name` as the running system until `exit_system`; called by the scheduler.
+#[inline(always)]
+pub fn enter_system(name: &'static str) {
+    #[cfg(feature = "audit-log")]
+    CURRENT_SYSTEM.with(|c| c.set(Some(name)));
+    #[cfg(not(feature = "audit-log"))]
+    let _ = name;
+}
+
+#[inline(always)]
+pub fn exit_system() {
+    #[cfg(feature = "audit-log")]
+    CURRENT_SYSTEM.with(|c| c.set(None));
+}
+
+/// Returns a copy of the log, oldest first.
+pub fn entries() -> Vec<AuditEntry> {
+    #[cfg(feature = "audit-log")]
+    return LOG.with(|log| log.borrow().iter().copied().collect());
+    #[cfg(not(feature = "audit-log"))]
+    Vec::new()
+}
+
+/// Returns the logged operations of `tick`.
+pub fn entries_for_tick(tick: Tick) -> Vec<AuditEntry> {
+    let mut entries = entries();
+    entries.retain(|e| e.tick == tick);
+    entries
+}
+
+/// Empties the log of the current thread.
+pub fn clear() {
+    #[cfg(feature = "audit-log")]
+    LOG.with(|log| log.borrow_mut().clear());
+}
+
+/// Formats entries as a JSON array of
+/// `{"tick":..,"component":..,"index":..,"op":..,"system":..}` objects.
+pub fn to_json(entries: &[AuditEntry]) -> String {
+    let mut out = String::from("[");
+    for (i, e) in entries.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        let op = match e.op {
+            StructuralOp::Insert => "insert",
+            StructuralOp::Remove => "remove",
+        };
+        let _ = write!(
+            out,
+            "{{\"tick\":{},\"component\":{},\"index\":{},\"op\":\"{}\",\"system\":",
+            e.tick.0,
+            json_string(e.component),
+            e.index,
+            op
+        );
+        match e.system {
+            Some(system) => out.push_str(&json_string(system)),
+            None => out.push_str("null"),
+        }
+        out.push('}');
+    }
+    out.push(']');
+    out
+}
+
+pub(crate) fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
@@ -0,0 +1,284 @@
+//! Owned copies of a storage's three-level presence masks, for joins written by hand.
+//!
+//! `Storage::presence_bitmap` copies the storage, page and chunk presence masks into a
+//! `HierBitmap`. Bitmaps of different storages combine with `and`, `or`, `and_not` and
+//! `complement` (or the `&`, `|`, `!` operators) level by level, skipping empty pages and
+//! chunks like generated queries do, and `iter` yields the resulting indices in order:
+//!
+//! ```ignore
+//! let moving = positions.presence_bitmap() & velocities.presence_bitmap();
+//! for index in moving.and_not(&frozen.presence_bitmap()).iter() { /* ... */ }
+//! ```
+//!
+//! A bitmap is a copy: it doesn't follow later writes to the storage.
+
+use std::ops::{BitAnd, BitOr, Not};
+
+/// Chunk-level bits of one page: bit `p` of `mask` is set when `chunks[p] != 0`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct BitmapPage {
+    mask: u64,
+    chunks: [u64; 64],
+}
+
+impl BitmapPage {
+    const EMPTY: Self = Self {
+        mask: 0,
+        chunks: [0; 64],
+    };
+
+    const FULL: Self = Self {
+        mask: u64::MAX,
+        chunks: [u64::MAX; 64],
+    };
+
+    /// Builds a page from per-chunk bits, or None if every chunk is empty.
+    fn from_chunks(chunks: [u64; 64]) -> Option<Box<Self>> {
+        let mut mask = 0u64;
+        for (p, &bits) in chunks.iter().enumerate() {
+            mask |= ((bits != 0) as u64) << p;
+        }
+        (mask != 0).then(|| Box::new(Self { mask, chunks }))
+    }
+}
+
+/// Set of storage indices laid out like a storage's masks: bit `s` of the top mask
+/// marks a non-empty page, whose mask marks non-empty chunks, whose bits are indices
+/// `(s << 12) | (p << 6) | c`. Empty pages are not allocated.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct HierBitmap {
+    mask: u64,
+    pages: [Option<Box<BitmapPage>>; 64],
+}
+
+impl Default for HierBitmap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HierBitmap {
+    /// Number of indices a bitmap can hold, the same as a storage.
+    pub const CAPACITY: u32 = 64 * 64 * 64;
+
+    /// Creates an empty bitmap.
+    pub const fn new() -> Self {
+        Self {
+            mask: 0,
+            pages: [const { None }; 64],
+        }
+    }
+
+    /// Builds a bitmap from page-level masks and the chunk masks of each page.
+    /// `chunks(s)` is only called for the pages set in `mask`.
+    pub(crate) fn from_masks(mask: u64, mut chunks: impl FnMut(u32) -> [u64; 64]) -> Self {
+        let mut bitmap = Self::new();
+        let mut pages = mask;
+        while pages != 0 {
+            let s = pages.trailing_zeros();
+            pages &= pages - 1;
+            bitmap.set_page(s, BitmapPage::from_chunks(chunks(s)));
+        }
+        bitmap
+    }
+
+    fn set_page(&mut self, s: u32, page: Option<Box<BitmapPage>>) {
+        self.mask &= !(1u64 << s);
+        self.mask |= (page.is_some() as u64) << s;
+        self.pages[s as usize] = page;
+    }
+
+    fn page(&self, s: u32) -> &BitmapPage {
+        self.pages[s as usize]
+            .as_deref()
+            .unwrap_or(&BitmapPage::EMPTY)
+    }
+
+    /// Adds `index`. Panics if it is not below `CAPACITY`.
+    pub fn insert(&mut self, index: u32) {
+        assert!(index < Self::CAPACITY, "index {index} out of range");
+        let (s, p, c) = (index >> 12, (index >> 6) & 63, index & 63);
+        let page = self.pages[s as usize].get_or_insert_with(|| Box::new(BitmapPage::EMPTY));
+        page.chunks[p as usize] |= 1u64 << c;
+        page.mask |= 1u64 << p;
+        self.mask |= 1u64 << s;
+    }
+
+    /// Returns true if `index` is in the bitmap.
+    pub fn contains(&self, index: u32) -> bool {
+        if index >= Self::CAPACITY {
+            return false;
+        }
+        let (s, p, c) = (index >> 12, (index >> 6) & 63, index & 63);
+        (self.page(s).chunks[p as usize] >> c) & 1 != 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mask == 0
+    }
+
+    /// Counts the indices in the bitmap.
+    pub fn len(&self) -> usize {
+        self.pages
+            .iter()
+            .flatten()
+            .flat_map(|page| page.chunks.iter())
+            .map(|bits| bits.count_ones() as usize)
+            .sum()
+    }
+
+    /// Indices in both bitmaps.
+    pub fn and(&self, other: &Self) -> Self {
+        self.combine(other, self.mask & other.mask, |a, b| a & b)
+    }
+
+    /// Indices in either bitmap.
+    pub fn or(&self, other: &Self) -> Self {
+        self.combine(other, self.mask | other.mask, |a, b| a | b)
+    }
+
+    /// Indices in `self` but not in `other`; cheaper than `self & !other`.
+    pub fn and_not(&self, other: &Self) -> Self {
+        self.combine(other, self.mask, |a, b| a & !b)
+    }
+
+    /// Every index below `CAPACITY` that is not in the bitmap. Pages absent from `self`
+    /// become full, so prefer `and_not` when the result is intersected anyway.
+    pub fn complement(&self) -> Self {
+        let mut out = Self::new();
+        for s in 0..64 {
+            let page = match &self.pages[s as usize] {
+                None => Some(Box::new(BitmapPage::FULL)),
+                Some(page) => BitmapPage::from_chunks(page.chunks.map(|bits| !bits)),
+            };
+            out.set_page(s, page);
+        }
+        out
+    }
+
+    /// Applies `op` to the chunk bits of the pages in `pages`.
+    fn combine(&self, other: &Self, pages: u64, op: impl Fn(u64, u64) -> u64) -> Self {
+        Self::from_masks(pages, |s| {
+            let (a, b) = (self.page(s), other.page(s));
+            std::array::from_fn(|p| op(a.chunks[p], b.chunks[p]))
+        })
+    }
+
+    /// Iterates the indices in ascending order.
+    pub fn iter(&self) -> HierBitmapIter<'_> {
+        HierBitmapIter {
+            bitmap: self,
+            pages: self.mask,
+            chunks: 0,
+            bits: 0,
+            base: 0,
+            page: 0,
+        }
+    }
+}
+
+impl BitAnd for &HierBitmap {
+    type Output = HierBitmap;
+
+    fn bitand(self, other: Self) -> HierBitmap {
+        self.and(other)
+    }
+}
+
+impl BitAnd for HierBitmap {
+    type Output = HierBitmap;
+
+    fn bitand(self, other: Self) -> HierBitmap {
+        self.and(&other)
+    }
+}
+
+impl BitOr for &HierBitmap {
+    type Output = HierBitmap;
+
+    fn bitor(self, other: Self) -> HierBitmap {
+        self.or(other)
+    }
+}
+
+impl BitOr for HierBitmap {
+    type Output = HierBitmap;
+
+    fn bitor(self, other: Self) -> HierBitmap {
+        self.or(&other)
+    }
+}
+
+impl Not for &HierBitmap {
+    type Output = HierBitmap;
+
+    fn not(self) -> HierBitmap {
+        self.complement()
+    }
+}
+
+impl Not for HierBitmap {
+    type Output = HierBitmap;
+
+    fn not(self) -> HierBitmap {
+        self.complement()
+    }
+}
+
+impl FromIterator<u32> for HierBitmap {
+    fn from_iter<I: IntoIterator<Item = u32>>(indices: I) -> Self {
+        let mut bitmap = Self::new();
+        for index in indices {
+            bitmap.insert(index);
+        }
+        bitmap
+    }
+}
+
+impl<'a> IntoIterator for &'a HierBitmap {
+    type Item = u32;
+    type IntoIter = HierBitmapIter<'a>;
+
+    fn into_iter(self) -> HierBitmapIter<'a> {
+        self.iter()
+    }
+}
+
+/// Ascending iterator over the indices of a `HierBitmap`.
+pub struct HierBitmapIter<'a> {
+    bitmap: &'a HierBitmap,
+    /// Pages not visited yet.
+    pages: u64,
+    /// Chunks of the current page not visited yet.
+    chunks: u64,
+    /// Bits of the current chunk not yielded yet.
+    bits: u64,
+    /// Index of bit 0 of the current chunk.
+    base: u32,
+    /// Current page.
+    page: u32,
+}
+
+impl Iterator for HierBitmapIter<'_> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        while self.bits == 0 {
+            while self.chunks == 0 {
+                if self.pages == 0 {
+                    return None;
+                }
+                self.page = self.pages.trailing_zeros();
+                self.pages &= self.pages - 1;
+                self.chunks = self.bitmap.page(self.page).mask;
+            }
+            let p = self.chunks.trailing_zeros();
+            self.chunks &= self.chunks - 1;
+            self.bits = self.bitmap.page(self.page).chunks[p as usize];
+            self.base = (self.page << 12) | (p << 6);
+        }
+        let c = self.bits.trailing_zeros();
+        self.bits &= self.bits - 1;
+        Some(self.base | c)
+    }
+}
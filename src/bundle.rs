@@ -0,0 +1,64 @@
+//! Bundles: tuples of components inserted or removed together.
+//!
+//! `World::insert_bundle`, `World::remove_bundle` and `World::spawn_bundle` apply every
+//! component of the tuple with the same frame, so all writes land in that tick's
+//! rollback snapshot and a rollback either restores the whole set or none of it.
+//! `World::write_components` also covers storages whose snapshot was already rotated
+//! past the frame's tick.
+
+use crate::component::Component;
+use crate::frame::Frame;
+use crate::tick::Tick;
+use crate::world::World;
+
+/// A tuple of up to eight distinct component types.
+pub trait Bundle: 'static {
+    /// Sets every component of the bundle at `index`.
+    fn insert(self, world: &mut World, frame: &Frame, index: u32);
+
+    /// Removes every component of the bundle type at `index`; returns true if any was
+    /// present.
+    fn remove(world: &mut World, frame: &Frame, index: u32) -> bool;
+
+    /// Returns the newest tick any storage of the bundle has its rollback snapshot open
+    /// for, or `tick` if that is newer.
+    fn newest_snapshot_tick(world: &mut World, tick: Tick) -> Tick;
+}
+
+macro_rules! impl_bundle {
+    ($($name:ident),+) => {
+        impl<$($name: Component),+> Bundle for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn insert(self, world: &mut World, frame: &Frame, index: u32) {
+                let ($($name,)+) = self;
+                $(world.get_storage_mut::<$name>().set(frame, index, $name);)+
+            }
+
+            fn remove(world: &mut World, frame: &Frame, index: u32) -> bool {
+                let mut removed = false;
+                $(removed |= world.get_storage_mut::<$name>().remove(frame, index);)+
+                removed
+            }
+
+            fn newest_snapshot_tick(world: &mut World, tick: Tick) -> Tick {
+                let mut newest = tick;
+                $(
+                    let open = world.get_storage_mut::<$name>().rollback.tick();
+                    if open.is_after(newest) {
+                        newest = open;
+                    }
+                )+
+                newest
+            }
+        }
+    };
+}
+
+impl_bundle!(A);
+impl_bundle!(A, B);
+impl_bundle!(A, B, C);
+impl_bundle!(A, B, C, D);
+impl_bundle!(A, B, C, D, E);
+impl_bundle!(A, B, C, D, E, F);
+impl_bundle!(A, B, C, D, E, F, G);
+impl_bundle!(A, B, C, D, E, F, G, H);
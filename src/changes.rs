@@ -0,0 +1,133 @@
+//! What the last tick touched, for debug overlays and profilers.
+//!
+//! `World::change_summary` reads the created/changed/removed masks of every storage's
+//! rollback snapshot for the current tick, so after `World::run` it describes the tick
+//! that just ran without the caller scanning the storages:
+//!
+//! ```ignore
+//! world.run();
+//! let summary = world.change_summary();
+//! for c in &summary.components {
+//!     overlay.line(format!("{}: +{} ~{} -{}", c.name, c.created, c.changed, c.removed));
+//! }
+//! ```
+//!
+//! Pages are the 4096-index blocks of a storage (`index >> 12`). Touches come from the
+//! rollback history, so a storage whose snapshot of the tick was already discarded
+//! reports nothing, and writes made after the tick ended (outside `run`) are counted
+//! in it until the next one starts.
+
+use crate::component::ComponentId;
+use crate::storage::StorageLike;
+use crate::tick::Tick;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Touches of one component type in a tick. An index created and removed again within
+/// the tick counts as changed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ComponentChanges {
+    pub id: ComponentId,
+    pub name: &'static str,
+    /// Values inserted at an index that had none at the start of the tick.
+    pub created: u32,
+    /// Values present at both ends of the tick and written in between.
+    pub changed: u32,
+    /// Values present at the start of the tick and gone at its end.
+    pub removed: u32,
+}
+
+impl ComponentChanges {
+    pub fn total(&self) -> u32 {
+        self.created + self.changed + self.removed
+    }
+}
+
+/// Output of `World::change_summary`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChangeSummary {
+    pub tick: Tick,
+    /// Components touched in the tick, by id.
+    pub components: Vec<ComponentChanges>,
+    /// The `TOP` pages with the most touches across components as `(page, touches)`,
+    /// most touched first.
+    pub hot_pages: Vec<(u32, u32)>,
+    /// The `TOP` indices touched in the most component types as `(index, components)`,
+    /// most touched first.
+    pub top_entities: Vec<(u32, u32)>,
+}
+
+impl ChangeSummary {
+    /// Length limit of `hot_pages` and `top_entities`.
+    pub const TOP: usize = 8;
+
+    /// Counts the touches the rollback snapshots of `tick` recorded in `storages`.
+    pub(crate) fn collect<'a>(
+        tick: Tick,
+        storages: impl Iterator<Item = (ComponentId, &'a dyn StorageLike)>,
+    ) -> Self {
+        let mut components = Vec::new();
+        let mut pages = [0u32; 64];
+        let mut entities: HashMap<u32, u32> = HashMap::new();
+        for (id, storage) in storages {
+            let mut changes = ComponentChanges {
+                id,
+                name: storage.component_name(),
+                created: 0,
+                changed: 0,
+                removed: 0,
+            };
+            storage.for_each_touched_at(tick, &mut |index, was_present, present| {
+                match (was_present, present) {
+                    (false, true) => changes.created += 1,
+                    (true, false) => changes.removed += 1,
+                    _ => changes.changed += 1,
+                }
+                pages[(index >> 12) as usize] += 1;
+                *entities.entry(index).or_insert(0) += 1;
+            });
+            if changes.total() > 0 {
+                components.push(changes);
+            }
+        }
+        Self {
+            tick,
+            components,
+            hot_pages: top((0..64u32)
+                .map(|page| (page, pages[page as usize]))
+                .filter(|&(_, touches)| touches > 0)),
+            top_entities: top(entities.into_iter()),
+        }
+    }
+
+    /// Total touches of the tick across components.
+    pub fn total(&self) -> u32 {
+        self.components.iter().map(ComponentChanges::total).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+}
+
+/// The `ChangeSummary::TOP` pairs with the highest counts, ties by lowest key.
+fn top(counts: impl Iterator<Item = (u32, u32)>) -> Vec<(u32, u32)> {
+    let mut counts: Vec<_> = counts.collect();
+    counts.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    counts.truncate(ChangeSummary::TOP);
+    counts
+}
+
+impl fmt::Display for ChangeSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}: {} touches", self.tick, self.total())?;
+        for c in &self.components {
+            write!(
+                f,
+                "\n  {}: +{} ~{} -{}",
+                c.name, c.created, c.changed, c.removed
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,271 @@
+//! Pluggable byte formats for saves and network snapshots.
+//!
+//! A `SnapshotCodec` turns serde values into bytes and back. `encode_storage` and
+//! `decode_storage` use one to write every value of a storage with its index and the
+//! component's schema version, and to load them back (migrating older data through
+//! `Migrate`), so consumers never pack bytes around pages and chunks themselves.
+//!
+//! The codec is picked per use:
+//! - `Bincode` (feature `bincode`): fixed-width integers, cheap to decode; disk saves.
+//! - `Postcard` (feature `postcard`): varint integers, compact; network snapshots.
+//!
+//! ```ignore
+//! let save = codec::encode_storage(&Bincode, world.get_storage_mut::<Health>())?;
+//! let packet = codec::encode_storage(&Postcard, world.get_storage_mut::<Health>())?;
+//! let frame = world.frame();
+//! codec::decode_storage(&Postcard, world.get_storage_mut::<Health>(), &frame, &packet)?;
+//! ```
+//!
+//! A save starts with `encode_header`: the tick and the world's seed
+//! (`World::new_with_seed`), so the loading side can create an identically seeded world
+//! with `SnapshotHeader::new_world` before decoding the storages into it.
+//!
+//! Both formats are not self-describing, so data is only readable with the codec that
+//! wrote it.
+
+use crate::component::Component;
+use crate::frame::Frame;
+use crate::migrate::{Migrate, ValueSeed};
+use crate::storage::Storage;
+use crate::tick::Tick;
+use crate::world::World;
+use serde::de::{self, DeserializeOwned, DeserializeSeed, SeqAccess, Visitor};
+use serde::ser::{SerializeSeq, SerializeStruct};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// A binary format for snapshot data.
+pub trait SnapshotCodec {
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    fn encode<T: Serialize + ?Sized>(&self, value: &T) -> Result<Vec<u8>, Self::Error>;
+
+    fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, Self::Error>;
+}
+
+/// bincode 1 with its default options: little-endian fixed-width integers.
+#[cfg(feature = "bincode")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Bincode;
+
+#[cfg(feature = "bincode")]
+impl SnapshotCodec for Bincode {
+    type Error = bincode::Error;
+
+    fn encode<T: Serialize + ?Sized>(&self, value: &T) -> Result<Vec<u8>, Self::Error> {
+        bincode::serialize(value)
+    }
+
+    fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, Self::Error> {
+        bincode::deserialize(bytes)
+    }
+}
+
+/// postcard: varint integers and no padding, for bandwidth-bound snapshots.
+#[cfg(feature = "postcard")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Postcard;
+
+#[cfg(feature = "postcard")]
+impl SnapshotCodec for Postcard {
+    type Error = postcard::Error;
+
+    fn encode<T: Serialize + ?Sized>(&self, value: &T) -> Result<Vec<u8>, Self::Error> {
+        postcard::to_allocvec(value)
+    }
+
+    fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, Self::Error> {
+        postcard::from_bytes(bytes)
+    }
+}
+
+/// World-level data of a save or snapshot, encoded ahead of its storages.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotHeader {
+    /// Tick the snapshot was taken at.
+    pub tick: Tick,
+    /// Seed of `World::new_with_seed`, if the world was created with one.
+    pub seed: Option<u64>,
+}
+
+impl SnapshotHeader {
+    /// Header of `world` at its current tick.
+    pub fn of(world: &World) -> Self {
+        Self {
+            tick: world.current_tick(),
+            seed: world.seed(),
+        }
+    }
+
+    /// Creates a world to decode the snapshot into, seeded like the world that wrote it.
+    pub fn new_world(&self) -> World {
+        World::new_with_recorded_seed(self.seed)
+    }
+}
+
+/// Encodes the `SnapshotHeader` of `world`.
+pub fn encode_header<C: SnapshotCodec>(codec: &C, world: &World) -> Result<Vec<u8>, C::Error> {
+    codec.encode(&SnapshotHeader::of(world))
+}
+
+/// Reads back a header written by `encode_header`.
+pub fn decode_header<C: SnapshotCodec>(
+    codec: &C,
+    bytes: &[u8],
+) -> Result<SnapshotHeader, C::Error> {
+    codec.decode(bytes)
+}
+
+/// Encodes every value of `storage` with its index, tagged with `T::VERSION`.
+pub fn encode_storage<T, C>(codec: &C, storage: &Storage<T>) -> Result<Vec<u8>, C::Error>
+where
+    T: Component + Serialize,
+    C: SnapshotCodec,
+{
+    let mut entries = Vec::new();
+    let mut cursor = storage.cursor();
+    while let Some(index) = cursor.next(storage) {
+        entries.push((index, storage.get(index).unwrap()));
+    }
+    codec.encode(&EncodedStorage(entries))
+}
+
+/// Replaces the values of `storage` with the ones `encode_storage` wrote into `bytes`,
+/// in `frame`'s tick: indices missing from the data are removed, the others set. Data
+/// of an older `T::VERSION` is migrated. On error `storage` is left untouched.
+pub fn decode_storage<T, C>(
+    codec: &C,
+    storage: &mut Storage<T>,
+    frame: &Frame,
+    bytes: &[u8],
+) -> Result<(), C::Error>
+where
+    T: Migrate,
+    C: SnapshotCodec,
+{
+    let DecodedStorage(entries) = codec.decode::<DecodedStorage<T>>(bytes)?;
+    let mut stale = Vec::new();
+    let mut cursor = storage.cursor();
+    while let Some(index) = cursor.next(storage) {
+        stale.push(index);
+    }
+    for index in stale {
+        storage.remove(frame, index);
+    }
+    for (index, value) in entries {
+        storage.set(frame, index, value);
+    }
+    Ok(())
+}
+
+/// Wire format: `{ version, entries: [(index, value)] }`.
+struct EncodedStorage<'a, T>(Vec<(u32, &'a T)>);
+
+impl<T: Component + Serialize> Serialize for EncodedStorage<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("StorageSnapshot", 2)?;
+        state.serialize_field("version", &T::VERSION)?;
+        state.serialize_field("entries", &Entries(&self.0))?;
+        state.end()
+    }
+}
+
+struct Entries<'a, T>(&'a [(u32, &'a T)]);
+
+impl<T: Serialize> Serialize for Entries<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+        for entry in self.0 {
+            seq.serialize_element(entry)?;
+        }
+        seq.end()
+    }
+}
+
+struct DecodedStorage<T>(Vec<(u32, T)>);
+
+impl<'de, T: Migrate> Deserialize<'de> for DecodedStorage<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_struct(
+            "StorageSnapshot",
+            &["version", "entries"],
+            StorageVisitor::<T>(PhantomData),
+        )
+    }
+}
+
+struct StorageVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Migrate> Visitor<'de> for StorageVisitor<T> {
+    type Value = DecodedStorage<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a storage snapshot of {}", std::any::type_name::<T>())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let version: u32 = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let entries = seq
+            .next_element_seed(EntriesSeed::<T>(version, PhantomData))?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        Ok(DecodedStorage(entries))
+    }
+}
+
+/// Decodes the entries once the snapshot's version is known.
+struct EntriesSeed<T>(u32, PhantomData<T>);
+
+impl<'de, T: Migrate> DeserializeSeed<'de> for EntriesSeed<T> {
+    type Value = Vec<(u32, T)>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, T: Migrate> Visitor<'de> for EntriesSeed<T> {
+    type Value = Vec<(u32, T)>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a sequence of (index, {})", std::any::type_name::<T>())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut entries = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(entry) = seq.next_element_seed(EntrySeed::<T>(self.0, PhantomData))? {
+            entries.push(entry);
+        }
+        Ok(entries)
+    }
+}
+
+struct EntrySeed<T>(u32, PhantomData<T>);
+
+impl<'de, T: Migrate> DeserializeSeed<'de> for EntrySeed<T> {
+    type Value = (u32, T);
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_tuple(2, self)
+    }
+}
+
+impl<'de, T: Migrate> Visitor<'de> for EntrySeed<T> {
+    type Value = (u32, T);
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "an (index, {}) pair", std::any::type_name::<T>())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let index: u32 = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let value = seq
+            .next_element_seed(ValueSeed::<T>(self.0, PhantomData))?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        Ok((index, value))
+    }
+}
@@ -0,0 +1,291 @@
+//! Deferred structural changes.
+//!
+//! Systems that want to insert or remove components while other systems of the same
+//! stage may still be iterating those storages queue the change through `Commands`
+//! instead. The built-in `ApplyCommandsSystem` runs in `CommandsFlushGroup`, after every
+//! `SimulationGroup` system and before the hierarchy, cleanup and destroy stages, so all
+//! commands issued during simulation are applied before the later groups observe the
+//! world. Commands queued from outside a tick are applied at the next flush.
+//!
+//! Like other system state, a command targets a storage through the `*mut Storage<T>`
+//! obtained from `World::get_storage` when the system was created.
+//!
+//! Every system queues into its own list, keyed by its position in the schedule's
+//! resolved order, and the flush applies the lists in that order (commands from outside
+//! a tick first). The result therefore does not depend on which system ran first, so it
+//! stays the same when systems of one wavefront run on several threads; an executor
+//! doing that wraps each system in `with_issuer`, as `Scheduler::run` does.
+
+use crate::component::{Component, ComponentId};
+use crate::frame::Frame;
+use crate::replay::{RecordedWrite, ReplayLog, WriteFn, WriteKind};
+use crate::storage::Storage;
+use crate::system::{System, SystemGroup};
+use crate::world::{CommandsFlushGroup, StorageTable, World};
+use std::any::{Any, TypeId};
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+type Command = Box<dyn FnOnce(&Frame)>;
+
+/// A queued command. Writes are kept replayable so a recording queue can log them.
+enum Queued {
+    Once(Command),
+    Write {
+        storages: *mut StorageTable,
+        component: ComponentId,
+        index: u32,
+        kind: WriteKind,
+        write: WriteFn,
+    },
+}
+
+thread_local! {
+    /// Schedule position of the system running on this thread; 0 outside systems.
+    static ISSUER: Cell<u32> = const { Cell::new(0) };
+}
+
+/// Runs `f` with the commands it queues on this thread attributed to the system at
+/// `position` (1-based) of the schedule's resolved order.
+pub fn with_issuer<R>(position: u32, f: impl FnOnce() -> R) -> R {
+    struct Restore(u32);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            ISSUER.set(self.0);
+        }
+    }
+    let _restore = Restore(ISSUER.replace(position));
+    f()
+}
+
+/// Commands queued since the last flush, per issuing system in schedule order and in
+/// issue order within a system. Owned by the world.
+#[derive(Default)]
+pub struct CommandQueue {
+    queues: Mutex<BTreeMap<u32, Vec<Queued>>>,
+    /// Writes queued from outside the simulation and applied since recording started;
+    /// see `decs::replay`.
+    recording: Mutex<Option<ReplayLog>>,
+}
+
+impl CommandQueue {
+    fn queues(&self) -> MutexGuard<'_, BTreeMap<u32, Vec<Queued>>> {
+        self.queues
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn recording(&self) -> MutexGuard<'_, Option<ReplayLog>> {
+        self.recording
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Starts logging applied writes queued outside systems, dropping any earlier log.
+    /// `seed` is the recording world's seed, kept in the log's header.
+    pub fn start_recording(&self, seed: Option<u64>) {
+        *self.recording() = Some(ReplayLog::with_seed(seed));
+    }
+
+    /// Stops recording and returns the log, or None if the queue wasn't recording.
+    pub fn take_recording(&self) -> Option<ReplayLog> {
+        self.recording().take()
+    }
+
+    pub fn len(&self) -> usize {
+        self.queues().values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queues().is_empty()
+    }
+
+    fn push(&self, command: Queued) {
+        let issuer = ISSUER.get();
+        self.queues().entry(issuer).or_default().push(command);
+    }
+
+    /// Applies every queued command, system by system in schedule order, including
+    /// commands queued by the commands being applied.
+    pub fn apply(&self, frame: &Frame) {
+        loop {
+            let queues = std::mem::take(&mut *self.queues());
+            if queues.is_empty() {
+                break;
+            }
+            for (issuer, commands) in queues {
+                for command in commands {
+                    match command {
+                        Queued::Once(command) => command(frame),
+                        Queued::Write {
+                            storages,
+                            component,
+                            index,
+                            kind,
+                            write,
+                        } => {
+                            write(unsafe { &mut *storages }, frame);
+                            // Writes issued by systems happen again in a replay.
+                            if issuer == 0
+                                && let Some(log) = self.recording().as_mut()
+                            {
+                                let tick = frame.current_tick;
+                                log.push(RecordedWrite::new(tick, component, index, kind, write));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Drops every queued command without applying it.
+    pub fn clear(&self) {
+        self.queues().clear();
+    }
+}
+
+/// Handle for queueing commands on a world's `CommandQueue`; see `World::commands`.
+#[derive(Clone, Copy)]
+pub struct Commands {
+    queue: *const CommandQueue,
+    storages: *mut StorageTable,
+}
+
+// Safety: the queue is behind a mutex, commands are only run by `apply` on the thread
+// that owns the world, and the queue outlives every handle to it because the world keeps
+// it boxed for its whole lifetime.
+unsafe impl Send for Commands {}
+unsafe impl Sync for Commands {}
+
+impl Commands {
+    pub(crate) fn new(queue: *const CommandQueue, storages: *mut StorageTable) -> Self {
+        Self { queue, storages }
+    }
+
+    /// Returns the storage of `T` in the table, when a command is applied.
+    ///
+    /// # Panics
+    /// Panics if the world has no storage for `T`; `World::finalize` creates the storage
+    /// of every registered component.
+    fn storage<T: Component>(storages: &mut StorageTable) -> &mut Storage<T> {
+        storages
+            .get_mut(T::id() as usize)
+            .and_then(|s| s.as_mut())
+            .and_then(|s| s.as_any_mut().downcast_mut::<Storage<T>>())
+            .unwrap_or_else(|| {
+                panic!(
+                    "Commands: the world has no storage for {}",
+                    std::any::type_name::<T>()
+                )
+            })
+    }
+
+    /// Queues an arbitrary command, run with the frame of the tick it is applied in.
+    pub fn push(&self, command: impl FnOnce(&Frame) + 'static) {
+        unsafe { (*self.queue).push(Queued::Once(Box::new(command))) };
+    }
+
+    /// Queues a write to the world's storage of `T` that a recording queue can log.
+    fn push_write<T: Component>(
+        &self,
+        index: u32,
+        kind: WriteKind,
+        write: impl Fn(&mut Storage<T>, &Frame) + 'static,
+    ) {
+        let write: WriteFn =
+            Arc::new(move |storages, frame| write(Self::storage::<T>(storages), frame));
+        let command = Queued::Write {
+            storages: self.storages,
+            component: T::id(),
+            index,
+            kind,
+            write,
+        };
+        unsafe { (*self.queue).push(command) };
+    }
+
+    /// Queues `storage.set(frame, index, value)`.
+    ///
+    /// # Safety
+    /// `storage` must come from `World::get_storage` of the world owning this queue.
+    pub unsafe fn insert<T: Component>(&self, storage: *mut Storage<T>, index: u32, value: T) {
+        self.push(move |frame| unsafe { (*storage).set(frame, index, value) });
+    }
+
+    /// Queues `storage.remove(frame, index)`.
+    ///
+    /// # Safety
+    /// Same as `insert`.
+    pub unsafe fn remove<T: Component>(&self, storage: *mut Storage<T>, index: u32) {
+        self.push(move |frame| unsafe {
+            (*storage).remove(frame, index);
+        });
+    }
+
+    /// Queues setting `value` at `index` in the world's storage of `T`, found when the
+    /// command is applied. Lets `system!` bodies taking a `Commands` parameter insert
+    /// the component a `None=[T]` filter found missing.
+    pub fn insert_component<T: Component>(&self, index: u32, value: T) {
+        self.push_write(
+            index,
+            WriteKind::Insert,
+            move |storage: &mut Storage<T>, frame| storage.set(frame, index, value.clone()),
+        );
+    }
+
+    /// Queues removing the `T` at `index` from the world's storage of `T`, if present.
+    pub fn remove_component<T: Component>(&self, index: u32) {
+        self.push_write(
+            index,
+            WriteKind::Remove,
+            move |storage: &mut Storage<T>, frame| {
+                storage.remove(frame, index);
+            },
+        );
+    }
+}
+
+/// Applies the world's queued commands; added to every world by `World::new`.
+pub struct ApplyCommandsSystem {
+    queue: *const CommandQueue,
+}
+
+// Safety: see `Commands`.
+unsafe impl Send for ApplyCommandsSystem {}
+unsafe impl Sync for ApplyCommandsSystem {}
+
+impl ApplyCommandsSystem {
+    pub fn new(world: &mut World) -> Self {
+        Self {
+            queue: world.command_queue(),
+        }
+    }
+}
+
+impl System for ApplyCommandsSystem {
+    fn run(&self, frame: &Frame) {
+        unsafe { (*self.queue).apply(frame) };
+    }
+
+    fn reads(&self) -> &[TypeId] {
+        &[]
+    }
+
+    fn writes(&self) -> &[TypeId] {
+        &[]
+    }
+
+    fn exclusive(&self) -> bool {
+        true
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn parent(&self) -> Option<&dyn SystemGroup> {
+        Some(CommandsFlushGroup::instance())
+    }
+}
@@ -1,17 +1,106 @@
+use crate::slot::{Inline, SlotRepr};
 use crate::world::World;
-use decs::system::TemporaryComponentCleanupSystem;
+use decs::system::DestroyedCleanupSystem;
 use std::alloc::Allocator;
 
+/// Id handed out by `Ecs::register`; indexes a world's storage table.
+pub type ComponentId = u32;
+
+/// Descriptive attributes of a component type for tooling, e.g. inspector grouping or
+/// replication tables. `derive(Component)` fills them from the type's doc comment and
+/// `#[component(category = "physics", replicated, priority = 2)]`; `Ecs::metadata` returns them,
+/// with `name`, once the type is registered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ComponentMetadata {
+    /// `std::any::type_name` of the component; empty in `Component::METADATA`.
+    pub name: &'static str,
+    /// The type's doc comment, one line per `///` line; empty if it has none.
+    pub doc: &'static str,
+    pub category: Option<&'static str>,
+    /// Whether the component is sent over the network.
+    pub replicated: bool,
+    /// Order of replicated components in a bandwidth-limited packet, highest first
+    /// (`decs::replication`); 0 unless set with `priority = N`.
+    pub priority: u8,
+}
+
+impl ComponentMetadata {
+    /// No doc, category or replication.
+    pub const NONE: Self = Self {
+        name: "",
+        doc: "",
+        category: None,
+        replicated: false,
+        priority: 0,
+    };
+}
+
+impl Default for ComponentMetadata {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
 pub trait Component
 where
     Self: Sized + Clone + 'static,
 {
+    /// Whether storages of this type maintain `changed_mask`s. Opting out with
+    /// `#[component(change_detection = false)]` saves the per-write mask updates;
+    /// `Changed=[...]` filters on such a type fail to compile.
+    const CHANGE_DETECTION: bool = true;
+
+    /// Schema version of this component's layout, recorded by `Ecs::register`. Bump it
+    /// with `#[component(version = N)]` when the layout changes and implement
+    /// `migrate::Migrate` so data written by older builds still loads.
+    const VERSION: u32 = 1;
+
+    /// Hash of the type's fields (or variants) and their types, computed by the derive
+    /// and recorded by `Ecs::register`; 0 for hand-written impls. `net::Handshake`
+    /// compares it between peers.
+    const SCHEMA_HASH: u64 = 0;
+
+    /// Whether this is an event type, declared with `#[component(event)]`. Structural
+    /// change events (`event::ComponentAdded`/`ComponentRemoved`) are not sent for them.
+    const EVENT: bool = false;
+
+    /// Whether this is a resource type, declared with `#[component(resource)]`. Its
+    /// storage holds the world's single value at `resource::RESOURCE_INDEX` and no entity
+    /// components, so no cleanup system is scheduled for it and per-entity operations
+    /// (moving, duplicating) skip it.
+    const RESOURCE: bool = false;
+
+    /// Doc comment, category and replication flag recorded by `Ecs::register`; see
+    /// `ComponentMetadata`.
+    const METADATA: ComponentMetadata = ComponentMetadata::NONE;
+
+    /// Maps a value to the one sent when replicating (`decs::replication`), e.g. with
+    /// positions rounded to the precision the network needs. Set with
+    /// `#[component(replicated, quantize = path)]`; values are sent as they are without.
+    const QUANTIZE: Option<fn(&Self) -> Self> = None;
+
+    /// How chunks hold values of this type: in the chunk (`slot::Inline`), or behind a
+    /// pointer for large types declared with `#[component(out_of_line)]`
+    /// (`slot::OutOfLine`); see `decs::slot`.
+    type Repr: SlotRepr<Self> = Inline<Self>;
+
     fn id() -> u32;
 
     fn initialize(_id: u32) {}
 
     fn schedule_cleanup_system(world: &mut World);
 
+    /// Value comparison for `net::diff_worlds`. The default treats all values as equal,
+    /// so only presence is compared; `#[component(diff)]` uses `PartialEq`.
+    fn desync_eq(&self, _other: &Self) -> bool {
+        true
+    }
+
+    /// Formats a value for `net::DesyncReport`; `#[component(diff)]` uses `Debug`.
+    fn desync_debug(&self) -> Option<String> {
+        None
+    }
+
     fn clone_in(&self, _allocator: &dyn Allocator) -> Self {
         self.clone()
     }
@@ -38,8 +127,7 @@ impl Component for Destroyed {
     }
 
     fn schedule_cleanup_system(world: &mut World) {
-        let sys =
-            TemporaryComponentCleanupSystem::<Destroyed, crate::world::DestroyGroup>::new(world);
+        let sys = DestroyedCleanupSystem::new(world);
         world.scheduler_mut().add_system(sys);
     }
 }
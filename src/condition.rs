@@ -0,0 +1,221 @@
+//! Run conditions: predicates that decide each tick whether a system runs.
+//!
+//! Wrap a system with `RunIf::new(world, system, condition)` and add the wrapper to
+//! the scheduler instead of checking `frame.current_tick % n` inside `run`. Conditions
+//! compose with `and`/`or`; closures taking `&Frame` are conditions too. A skipped
+//! system keeps its place in the wavefronts, so ordering does not change between
+//! ticks where it runs and ticks where it doesn't.
+//!
+//! Conditions only see the frame and world storages, so they give the same answer when
+//! a tick is resimulated after rollback.
+
+use crate::event::Event;
+use crate::frame::Frame;
+use crate::storage::Storage;
+use crate::system::{QueryMismatch, System, SystemError, SystemGroup};
+use crate::world::World;
+use std::any::{Any, TypeId};
+
+pub trait RunCondition: Send + Sync + 'static {
+    /// Called once by `RunIf::new` so the condition can look up storages.
+    fn init(&mut self, _world: &mut World) {}
+
+    fn should_run(&self, frame: &Frame) -> bool;
+
+    /// Component types the condition reads; `RunIf` adds them to the system's reads so
+    /// the scheduler orders it after their writers.
+    fn reads(&self) -> Vec<TypeId> {
+        Vec::new()
+    }
+
+    /// Runs only when both conditions hold; `other` is not evaluated if `self` fails.
+    fn and<C: RunCondition>(self, other: C) -> And<Self, C>
+    where
+        Self: Sized,
+    {
+        And(self, other)
+    }
+
+    /// Runs when either condition holds; `other` is not evaluated if `self` passes.
+    fn or<C: RunCondition>(self, other: C) -> Or<Self, C>
+    where
+        Self: Sized,
+    {
+        Or(self, other)
+    }
+}
+
+impl<F: Fn(&Frame) -> bool + Send + Sync + 'static> RunCondition for F {
+    fn should_run(&self, frame: &Frame) -> bool {
+        self(frame)
+    }
+}
+
+pub struct And<A, B>(A, B);
+
+impl<A: RunCondition, B: RunCondition> RunCondition for And<A, B> {
+    fn init(&mut self, world: &mut World) {
+        self.0.init(world);
+        self.1.init(world);
+    }
+
+    fn should_run(&self, frame: &Frame) -> bool {
+        self.0.should_run(frame) && self.1.should_run(frame)
+    }
+
+    fn reads(&self) -> Vec<TypeId> {
+        let mut reads = self.0.reads();
+        reads.extend(self.1.reads());
+        reads
+    }
+}
+
+pub struct Or<A, B>(A, B);
+
+impl<A: RunCondition, B: RunCondition> RunCondition for Or<A, B> {
+    fn init(&mut self, world: &mut World) {
+        self.0.init(world);
+        self.1.init(world);
+    }
+
+    fn should_run(&self, frame: &Frame) -> bool {
+        self.0.should_run(frame) || self.1.should_run(frame)
+    }
+
+    fn reads(&self) -> Vec<TypeId> {
+        let mut reads = self.0.reads();
+        reads.extend(self.1.reads());
+        reads
+    }
+}
+
+/// Runs on ticks that are a multiple of `n`; see `every_n_ticks`.
+pub struct EveryNTicks(u32);
+
+impl RunCondition for EveryNTicks {
+    fn should_run(&self, frame: &Frame) -> bool {
+        frame.current_tick.0.is_multiple_of(self.0)
+    }
+}
+
+/// Runs on every `n`th tick (ticks `n`, `2n`, ...). `n` of 0 is treated as 1.
+pub fn every_n_ticks(n: u32) -> EveryNTicks {
+    EveryNTicks(n.max(1))
+}
+
+/// Runs when at least one `E` event is pending; see `on_event`.
+pub struct OnEvent<E: Event> {
+    storage: *mut Storage<E>,
+}
+
+// Safety: the storage pointer is owned by the world that runs the wrapped system and
+// is only read while that world is running it.
+unsafe impl<E: Event> Send for OnEvent<E> {}
+unsafe impl<E: Event> Sync for OnEvent<E> {}
+
+impl<E: Event> RunCondition for OnEvent<E> {
+    fn init(&mut self, world: &mut World) {
+        self.storage = world.get_storage::<E>();
+    }
+
+    fn should_run(&self, _frame: &Frame) -> bool {
+        assert!(
+            !self.storage.is_null(),
+            "on_event condition used without RunIf::new"
+        );
+        unsafe { (*self.storage).presence_mask != 0 }
+    }
+
+    fn reads(&self) -> Vec<TypeId> {
+        vec![TypeId::of::<E>()]
+    }
+}
+
+/// Runs when an `E` event was sent earlier in the tick (or before it started). The
+/// wrapper reads `E`, so it is scheduled after the systems that declare writing it.
+pub fn on_event<E: Event>() -> OnEvent<E> {
+    OnEvent {
+        storage: std::ptr::null_mut(),
+    }
+}
+
+/// A system that only runs on ticks where `condition` holds.
+///
+/// Reports the wrapped system's name, dependencies, group and `as_any` (plus the
+/// condition's reads), so scheduling
+/// and `Scheduler::get_system::<S>()` treat it like the wrapped system.
+pub struct RunIf<S: System, C: RunCondition> {
+    system: S,
+    condition: C,
+    /// The system's reads plus the condition's.
+    reads: Vec<TypeId>,
+}
+
+impl<S: System, C: RunCondition> RunIf<S, C> {
+    pub fn new(world: &mut World, system: S, mut condition: C) -> Self {
+        condition.init(world);
+        let mut reads = system.reads().to_vec();
+        for t in condition.reads() {
+            if !reads.contains(&t) {
+                reads.push(t);
+            }
+        }
+        Self {
+            system,
+            condition,
+            reads,
+        }
+    }
+
+    pub fn system(&self) -> &S {
+        &self.system
+    }
+}
+
+impl<S: System, C: RunCondition> System for RunIf<S, C> {
+    fn run(&self, frame: &Frame) {
+        if self.condition.should_run(frame) {
+            self.system.run(frame);
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        self.system.name()
+    }
+
+    fn before(&self) -> &[TypeId] {
+        self.system.before()
+    }
+
+    fn after(&self) -> &[TypeId] {
+        self.system.after()
+    }
+
+    fn reads(&self) -> &[TypeId] {
+        &self.reads
+    }
+
+    fn writes(&self) -> &[TypeId] {
+        self.system.writes()
+    }
+
+    fn parent(&self) -> Option<&dyn SystemGroup> {
+        self.system.parent()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self.system.as_any()
+    }
+
+    fn debug_counts(&self) -> (usize, usize) {
+        self.system.debug_counts()
+    }
+
+    fn explain(&self, index: u32) -> Vec<QueryMismatch> {
+        self.system.explain(index)
+    }
+
+    fn take_error(&self) -> Option<SystemError> {
+        self.system.take_error()
+    }
+}
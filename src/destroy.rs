@@ -0,0 +1,176 @@
+//! Dependency-aware teardown of destroyed entities.
+//!
+//! The cleanup systems remove every component of the entities marked `Destroyed` one
+//! component type at a time, without looking at references between entities. An
+//! entity referenced by others (a parent by its children, a body by its joints) thus
+//! leaves dangling handles behind. `OrderedDestroySystem` runs before the cleanup
+//! systems: it sorts the destroyed entities with the `DestroyOrder`s it was given, so
+//! each entity comes after the ones that have to go first, and lets every order tear
+//! down the references of each entity in that order.
+//!
+//! ```ignore
+//! let hierarchy = HierarchyOrder::new(&mut world);
+//! let destroy = OrderedDestroySystem::new(&mut world).with(hierarchy).with(JointOrder::new(&mut world));
+//! world.scheduler_mut().add_system(destroy);
+//! ```
+//!
+//! `HierarchyOrder` destroys children before their parent, unlinking each destroyed
+//! child from its siblings and orphaning the children a destroyed parent keeps.
+
+use crate::component::Destroyed;
+use crate::entity::Entity;
+use crate::frame::Frame;
+use crate::storage::Storage;
+use crate::system::{System, SystemGroup};
+use crate::world::{DestroyOrderGroup, World};
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// Decides which destroyed entities are torn down before others, and what tearing one
+/// down means.
+pub trait DestroyOrder: 'static {
+    /// Appends the entities that have to be torn down before `entity` when they are
+    /// destroyed in the same tick, e.g. its children or the joints attached to it.
+    fn before(&self, entity: Entity, out: &mut Vec<Entity>);
+
+    /// Clears the references between `entity` and the entities that outlive it. Called
+    /// for every destroyed entity in the sorted order, before its components are
+    /// removed. Does nothing by default.
+    fn tear_down(&self, frame: &Frame, entity: Entity) {
+        let _ = (frame, entity);
+    }
+
+    /// Component types `tear_down` writes.
+    fn writes(&self) -> &[TypeId] {
+        &[]
+    }
+}
+
+/// Sorts the entities marked `Destroyed` with its `DestroyOrder`s and tears them down
+/// in that order, ahead of the cleanup systems; see `decs::destroy`.
+pub struct OrderedDestroySystem {
+    destroyed: *const Storage<Destroyed>,
+    entities: *const Storage<Entity>,
+    orders: Vec<Box<dyn DestroyOrder>>,
+    writes: Vec<TypeId>,
+    /// Teardown order of the last run.
+    order: RefCell<Vec<Entity>>,
+}
+
+// Safety: the storages are owned by the World, which outlives its scheduler's systems;
+// systems run on the thread that owns the world.
+unsafe impl Send for OrderedDestroySystem {}
+unsafe impl Sync for OrderedDestroySystem {}
+
+impl OrderedDestroySystem {
+    pub fn new(world: &mut World) -> Self {
+        Self {
+            destroyed: world.get_storage::<Destroyed>(),
+            entities: world.get_entity_storage(),
+            orders: Vec::new(),
+            writes: Vec::new(),
+            order: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Adds `order`. An entity has to wait for the entities every order puts before it;
+    /// teardown calls the orders in the order they were added.
+    pub fn with(mut self, order: impl DestroyOrder) -> Self {
+        for ty in order.writes() {
+            if !self.writes.contains(ty) {
+                self.writes.push(*ty);
+            }
+        }
+        self.orders.push(Box::new(order));
+        self
+    }
+
+    /// The entities the last run tore down, in order.
+    pub fn last_order(&self) -> Vec<Entity> {
+        self.order.borrow().clone()
+    }
+
+    /// Sorts `destroyed` so every entity comes after the destroyed entities the orders
+    /// put before it. Entities depending on each other in a cycle keep index order.
+    fn sort(&self, destroyed: &[Entity]) -> Vec<Entity> {
+        // 0: not visited, 1: on the stack, 2: sorted.
+        let mut state: HashMap<Entity, u8> = destroyed.iter().map(|&e| (e, 0)).collect();
+        let mut sorted = Vec::with_capacity(destroyed.len());
+        let mut deps = Vec::new();
+        for &root in destroyed {
+            if state[&root] != 0 {
+                continue;
+            }
+            state.insert(root, 1);
+            // Depth-first, so an entity is pushed once everything it waits for is.
+            let mut stack = vec![(root, self.dependencies(root, &mut deps))];
+            while let Some((entity, pending)) = stack.last_mut() {
+                match pending.pop() {
+                    Some(dep) if state.get(&dep) == Some(&0) => {
+                        state.insert(dep, 1);
+                        let dep_deps = self.dependencies(dep, &mut deps);
+                        stack.push((dep, dep_deps));
+                    }
+                    Some(_) => {}
+                    None => {
+                        state.insert(*entity, 2);
+                        sorted.push(*entity);
+                        stack.pop();
+                    }
+                }
+            }
+        }
+        sorted
+    }
+
+    /// The entities the orders put before `entity`, last first.
+    fn dependencies(&self, entity: Entity, scratch: &mut Vec<Entity>) -> Vec<Entity> {
+        scratch.clear();
+        for order in &self.orders {
+            order.before(entity, scratch);
+        }
+        scratch.iter().rev().copied().collect()
+    }
+}
+
+impl System for OrderedDestroySystem {
+    fn run(&self, frame: &Frame) {
+        crate::aliasing::track_borrow::<Destroyed>(self.name(), false);
+        crate::aliasing::track_borrow::<Entity>(self.name(), false);
+        let destroyed = unsafe { &*self.destroyed };
+        let entities = unsafe { &*self.entities };
+        let mut marked = Vec::new();
+        let mut next = destroyed.next_present(0);
+        while let Some(index) = next {
+            if let Some(&entity) = entities.get(index) {
+                marked.push(entity);
+            }
+            next = destroyed.next_present(index + 1);
+        }
+        let sorted = self.sort(&marked);
+        for &entity in &sorted {
+            for order in &self.orders {
+                order.tear_down(frame, entity);
+            }
+        }
+        *self.order.borrow_mut() = sorted;
+    }
+
+    fn reads(&self) -> &[TypeId] {
+        static READS: &[TypeId] = &[TypeId::of::<Destroyed>(), TypeId::of::<Entity>()];
+        READS
+    }
+
+    fn writes(&self) -> &[TypeId] {
+        &self.writes
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn parent(&self) -> Option<&dyn SystemGroup> {
+        Some(DestroyOrderGroup::instance())
+    }
+}
@@ -1,9 +1,44 @@
-use crate::component::Component;
+use crate::component::{Component, ComponentId, ComponentMetadata};
+use crate::entity::Entity;
+use crate::world::World;
 
 pub struct Ecs;
 
 static mut NEXT_ID: u32 = 2;
 
+/// Number of component ids a world has storage slots for.
+const MAX_IDS: usize = 256;
+
+/// `Component::VERSION` per registered id; 0 for ids that were never handed out.
+/// Entity (0) and Destroyed (1) are built in.
+static mut VERSIONS: [u32; MAX_IDS] = {
+    let mut versions = [0; MAX_IDS];
+    versions[0] = 1;
+    versions[1] = 1;
+    versions
+};
+
+/// `Component::SCHEMA_HASH` per registered id.
+static mut SCHEMA_HASHES: [u64; MAX_IDS] = [0; MAX_IDS];
+
+/// Per registered id, creates the component's storage (and so schedules its cleanup
+/// system) in a world; used by `World::finalize`.
+static mut STORAGE_INITIALIZERS: [Option<fn(&mut World)>; MAX_IDS] = [None; MAX_IDS];
+
+/// `Component::METADATA`, with the type name, per registered id.
+static mut METADATA: [Option<ComponentMetadata>; MAX_IDS] = [None; MAX_IDS];
+
+fn create_storage<T: Component>(world: &mut World) {
+    let _ = world.get_storage::<T>();
+}
+
+fn metadata_of<T: Component>() -> ComponentMetadata {
+    ComponentMetadata {
+        name: std::any::type_name::<T>(),
+        ..T::METADATA
+    }
+}
+
 impl Ecs {
     pub fn register<T: Component>() {
         unsafe {
@@ -12,6 +47,57 @@ impl Ecs {
             NEXT_ID = NEXT_ID.wrapping_add(1);
 
             T::initialize(id);
+
+            let id = T::id() as usize;
+            if id < MAX_IDS {
+                VERSIONS[id] = T::VERSION;
+                SCHEMA_HASHES[id] = T::SCHEMA_HASH;
+                STORAGE_INITIALIZERS[id] = Some(create_storage::<T>);
+                METADATA[id] = Some(metadata_of::<T>());
+            }
+        }
+    }
+
+    /// Returns the schema version registered for component `id`, or None if no
+    /// component was registered under it. Snapshot writers store this next to each
+    /// storage so readers can tell which layout the data uses.
+    pub fn version(id: u32) -> Option<u32> {
+        let id = id as usize;
+        if id >= MAX_IDS {
+            return None;
+        }
+        Some(unsafe { VERSIONS[id] }).filter(|&v| v != 0)
+    }
+
+    /// Returns the `Component::SCHEMA_HASH` registered for component `id`, or None if
+    /// no component was registered under it.
+    pub fn schema_hash(id: ComponentId) -> Option<u64> {
+        Self::version(id)?;
+        Some(unsafe { SCHEMA_HASHES[id as usize] })
+    }
+
+    /// Returns the metadata of component `id` (see `ComponentMetadata`), or None if no
+    /// component was registered under it.
+    pub fn metadata(id: ComponentId) -> Option<ComponentMetadata> {
+        let registered = &raw const METADATA;
+        match id {
+            0 => Some(metadata_of::<Entity>()),
+            1 => Some(metadata_of::<crate::component::Destroyed>()),
+            _ => unsafe { (*registered).get(id as usize).copied().flatten() },
+        }
+    }
+
+    /// Iterates the id and metadata of every component registered so far, built-in ones
+    /// included, in id order. Tooling filters it, e.g. by `category` or `replicated`.
+    pub fn registered() -> impl Iterator<Item = (ComponentId, ComponentMetadata)> {
+        (0..MAX_IDS as ComponentId).filter_map(|id| Some((id, Self::metadata(id)?)))
+    }
+
+    /// Creates the storage of every registered component in `world`.
+    pub(crate) fn create_registered_storages(world: &mut World) {
+        let initializers = unsafe { STORAGE_INITIALIZERS };
+        for init in initializers.into_iter().flatten() {
+            init(world);
         }
     }
 }
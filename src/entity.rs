@@ -76,7 +76,7 @@ impl crate::component::Component for Entity {
         let _ = id;
     }
     fn schedule_cleanup_system(world: &mut crate::world::World) {
-        let sys = crate::system::ComponentCleanupSystem::<Entity>::new(world);
+        let sys = crate::system::EntityCleanupSystem::new(world);
         world.scheduler_mut().add_system(sys);
     }
 }
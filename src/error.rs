@@ -0,0 +1,110 @@
+use crate::component::ComponentId;
+use crate::tick::Tick;
+use std::alloc::AllocError;
+use std::fmt;
+
+/// Errors returned by the fallible (`try_*`) storage and world APIs.
+///
+/// The infallible counterparts (`set`, `spawn`, `rollback`) keep their existing
+/// behavior and panic/abort on these conditions; servers that need to degrade
+/// gracefully should use the `try_*` variants instead.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Error {
+    /// The global index does not fit in the 64 * 64 * 64 storage hierarchy.
+    IndexOutOfRange(u32),
+    /// Every slot of the storage is occupied.
+    StorageFull,
+    /// A page, chunk or rollback snapshot could not be allocated.
+    AllocationFailed,
+    /// The rollback target is older than the retained history.
+    /// `oldest` is the newest tick whose snapshot was already discarded.
+    HistoryExhausted { target: Tick, oldest: Tick },
+    /// `World::resimulate` was asked to go back further than the `RollbackConfig`'s
+    /// `max_rollback_frames` allows.
+    RollbackWindowExceeded { target: Tick, max_frames: u32 },
+    /// A system panicked while running `tick`; the world was rolled back to the start of it.
+    SystemPanicked { system: &'static str, tick: Tick },
+    /// A system returned an error during `tick` under `ErrorPolicy::AbortTick`; the world
+    /// was rolled back to the start of it. `Scheduler::last_errors` has the error.
+    SystemFailed { system: &'static str, tick: Tick },
+    /// `Scheduler::load_order` got an order that does not name exactly the scheduled
+    /// systems.
+    SystemOrderMismatch,
+    /// `Scheduler::load_order` got an order that runs `system` before `dependency`,
+    /// which has to run first.
+    SystemOrderViolated {
+        system: &'static str,
+        dependency: &'static str,
+    },
+    /// `Scheduler::try_add_system_unique` got a system of a type that is already scheduled,
+    /// under `DuplicatePolicy::Reject`.
+    DuplicateSystem { system: &'static str },
+    /// `World::restore_checkpoint` was given a label no checkpoint is saved under.
+    CheckpointNotFound,
+    /// `net::Handshake::validate` found that component `id` is missing on one peer or
+    /// registered with another name, version or schema hash.
+    RegistryMismatch { id: ComponentId },
+}
+
+/// Convenience alias for results produced by decs.
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl From<AllocError> for Error {
+    fn from(_: AllocError) -> Self {
+        Error::AllocationFailed
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::IndexOutOfRange(index) => {
+                write!(
+                    f,
+                    "index {} is out of range (max {})",
+                    index,
+                    64 * 64 * 64 - 1
+                )
+            }
+            Error::StorageFull => write!(f, "storage is full"),
+            Error::AllocationFailed => write!(f, "allocation failed"),
+            Error::HistoryExhausted { target, oldest } => write!(
+                f,
+                "cannot roll back to {:?}: history for {:?} was already discarded",
+                target, oldest
+            ),
+            Error::RollbackWindowExceeded { target, max_frames } => write!(
+                f,
+                "cannot roll back to {:?}: further than the rollback window of {} frames",
+                target, max_frames
+            ),
+            Error::SystemPanicked { system, tick } => write!(
+                f,
+                "system {} panicked during {:?}; world rolled back to the start of the tick",
+                system, tick
+            ),
+            Error::SystemFailed { system, tick } => write!(
+                f,
+                "system {} failed during {:?}; world rolled back to the start of the tick",
+                system, tick
+            ),
+            Error::SystemOrderMismatch => {
+                write!(f, "saved system order does not match the scheduled systems")
+            }
+            Error::SystemOrderViolated { system, dependency } => write!(
+                f,
+                "saved system order runs {} before {}, which it depends on",
+                system, dependency
+            ),
+            Error::DuplicateSystem { system } => {
+                write!(f, "system {} is already scheduled", system)
+            }
+            Error::CheckpointNotFound => write!(f, "no checkpoint is saved under that label"),
+            Error::RegistryMismatch { id } => {
+                write!(f, "component {} differs between the peers' registries", id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,244 @@
+//! Events: component values that live for a single tick.
+//!
+//! An event type is a component declared with `#[component(event)]`. Sent events are
+//! stored at the lowest free indices of the type's own storage (in send order while
+//! the storage only grows) and a `TemporaryComponentCleanupSystem` in `CleanupGroup`
+//! drops them at the end of the tick. Because they are ordinary storage values, events
+//! are restored by rollback and can be read by any system with a `View<E>` query;
+//! senders must run before readers, which the scheduler ensures for systems that
+//! declare `E` in `writes()` and `reads()`.
+//!
+//! Use `World::send_event`/`World::events` from the outside. Systems keep the
+//! `*mut Storage<E>` from `World::get_storage` and call `send` and `read` on it.
+//!
+//! `ComponentAdded` and `ComponentRemoved` report structural changes of every other
+//! storage. Once registered, their storage schedules a `StructuralEventSystem`, which
+//! compares each storage's rollback snapshot of the tick with its values right before
+//! `EndOfTickSystem`, so systems can react to inserts and removals in the next tick
+//! instead of polling every storage.
+
+use crate::component::{Component, ComponentId};
+use crate::entity::Entity;
+use crate::frame::Frame;
+use crate::storage::Storage;
+use crate::system::{EndOfTickSystem, System, SystemGroup, TemporaryComponentCleanupSystem};
+use crate::tick::Tick;
+use crate::world::{CleanupGroup, EndOfTickGroup, StorageTable, World};
+use decs_macros::Component;
+use std::any::{Any, TypeId};
+use std::cell::UnsafeCell;
+use std::collections::BTreeMap;
+
+/// Marker for components declared with `#[component(event)]`.
+pub trait Event: Component {}
+
+/// Sent by the entity cleanup system for every entity it removes, so systems holding
+/// entity references (targets, selections) can drop them. Register it with
+/// `Ecs::register` before creating the world to receive it.
+///
+/// Unlike events sent during simulation, despawn events are sent in `CleanupGroup` and
+/// stay readable until the same point of the next tick: `DestroyGroup` systems see them
+/// in the tick they were sent, every other system in the following one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Component)]
+#[component(event)]
+pub struct DespawnedEvent(pub Entity);
+
+/// Stores `event` in the first free slot of `storage` for the current tick and returns
+/// its index, or None if the storage is full.
+pub fn send<E: Event>(storage: &mut Storage<E>, frame: &Frame, event: E) -> Option<u32> {
+    let index = storage.first_free_index()?;
+    storage.set(frame, index, event);
+    Some(index)
+}
+
+/// Iterates the events in `storage` in index order.
+pub fn read<E: Event>(storage: &Storage<E>) -> impl Iterator<Item = &E> {
+    let mut cursor = storage.cursor();
+    std::iter::from_fn(move || cursor.next(storage)).filter_map(|index| storage.get(index))
+}
+
+/// Sent at the end of a tick for every index that gained a value of component
+/// `component` in it. Readable during the next tick, until its `CleanupGroup`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ComponentAdded {
+    pub index: u32,
+    pub component: ComponentId,
+}
+
+/// Sent at the end of a tick for every index that lost its value of component
+/// `component` in it, including removals by the cleanup systems on despawn. Readable
+/// during the next tick, until its `CleanupGroup`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ComponentRemoved {
+    pub index: u32,
+    pub component: ComponentId,
+}
+
+impl ComponentAdded {
+    /// Returns true if the added component is a `T`.
+    pub fn is<T: Component>(&self) -> bool {
+        self.component == T::id()
+    }
+}
+
+impl ComponentRemoved {
+    /// Returns true if the removed component is a `T`.
+    pub fn is<T: Component>(&self) -> bool {
+        self.component == T::id()
+    }
+}
+
+/// The structural change events `StructuralEventSystem` can send.
+pub trait StructuralEvent: Event {
+    /// True for additions, false for removals.
+    const ADDED: bool;
+
+    fn new(index: u32, component: ComponentId) -> Self;
+}
+
+macro_rules! structural_event {
+    ($name:ident, $id:ident, $added:expr) => {
+        #[allow(non_upper_case_globals)]
+        static mut $id: u32 = u32::MAX;
+
+        impl Component for $name {
+            const EVENT: bool = true;
+
+            fn id() -> u32 {
+                unsafe { $id }
+            }
+
+            fn initialize(id: u32) {
+                unsafe {
+                    if $id == u32::MAX {
+                        $id = id;
+                    }
+                }
+            }
+
+            fn schedule_cleanup_system(world: &mut World) {
+                let cleanup = TemporaryComponentCleanupSystem::<$name, CleanupGroup>::new(world);
+                world.scheduler_mut().add_system(cleanup);
+                let sender = StructuralEventSystem::<$name>::new(world);
+                world.scheduler_mut().add_system(sender);
+            }
+        }
+
+        impl Event for $name {}
+
+        impl StructuralEvent for $name {
+            const ADDED: bool = $added;
+
+            fn new(index: u32, component: ComponentId) -> Self {
+                Self { index, component }
+            }
+        }
+    };
+}
+
+structural_event!(ComponentAdded, __DECS_COMPONENT_ID_ComponentAdded, true);
+structural_event!(
+    ComponentRemoved,
+    __DECS_COMPONENT_ID_ComponentRemoved,
+    false
+);
+
+/// Sends an `E` for every structural change of the tick, in component id then index
+/// order. Runs in `EndOfTickGroup`, before `EndOfTickSystem` moves the snapshots into
+/// history; exclusive, since it reads every storage.
+///
+/// Changes made between runs are recorded in the previous tick (see `World::frame`),
+/// after its events were sent, so they are reported with the next tick's: the system
+/// remembers the presence it saw of every index touched in the tick it last ran, and
+/// compares the rest of that tick's snapshot against it. After a rollback, only the
+/// current tick's snapshot is used, so resimulated ticks send the same events.
+pub struct StructuralEventSystem<E: StructuralEvent> {
+    before: [TypeId; 1],
+    storages: *mut StorageTable,
+    events: *mut Storage<E>,
+    /// Tick of the last run.
+    sent: UnsafeCell<Option<Tick>>,
+    /// Per component id, the indices touched in the tick of the last run and whether
+    /// they had a value then, sorted by index.
+    seen: UnsafeCell<Vec<Vec<(u32, bool)>>>,
+}
+
+// Safety: the storage table is boxed by the World, which outlives its scheduler's systems;
+// systems run on the thread that owns the world.
+unsafe impl<E: StructuralEvent> Send for StructuralEventSystem<E> {}
+unsafe impl<E: StructuralEvent> Sync for StructuralEventSystem<E> {}
+
+impl<E: StructuralEvent> StructuralEventSystem<E> {
+    pub fn new(world: &mut World) -> Self {
+        Self {
+            before: [TypeId::of::<EndOfTickSystem>()],
+            storages: world.storage_table(),
+            events: world.get_storage::<E>(),
+            sent: UnsafeCell::new(None),
+            seen: UnsafeCell::new(Vec::new()),
+        }
+    }
+}
+
+impl<E: StructuralEvent> System for StructuralEventSystem<E> {
+    fn run(&self, frame: &Frame) {
+        let tick = frame.current_tick;
+        let storages = unsafe { &*self.storages };
+        let seen = unsafe { &mut *self.seen.get() };
+        let previous = unsafe { (*self.sent.get()).replace(tick) };
+        let follow_up = previous.is_none_or(|last| last.next() == tick);
+        seen.resize_with(storages.len(), Vec::new);
+
+        let mut changes = Vec::new();
+        let mut touched = BTreeMap::new();
+        for (id, storage) in storages.iter().enumerate() {
+            let seen = &mut seen[id];
+            let Some(storage) = storage.as_deref().filter(|s| !s.is_event()) else {
+                seen.clear();
+                continue;
+            };
+            // Presence each touched index had when events were last sent, and now.
+            touched.clear();
+            if follow_up {
+                storage.for_each_touched_at(tick.wrapping_sub(1), &mut |index, was, now| {
+                    let before = match seen.binary_search_by_key(&index, |&(i, _)| i) {
+                        Ok(at) => seen[at].1,
+                        Err(_) => was,
+                    };
+                    touched.insert(index, (before, now));
+                });
+            }
+            seen.clear();
+            storage.for_each_touched_at(tick, &mut |index, was, now| {
+                touched.entry(index).or_insert((was, now));
+                seen.push((index, now));
+            });
+            seen.sort_unstable_by_key(|&(index, _)| index);
+            for (&index, &(before, now)) in &touched {
+                if before != now && now == E::ADDED {
+                    changes.push(E::new(index, id as ComponentId));
+                }
+            }
+        }
+        let events = unsafe { &mut *self.events };
+        for event in changes {
+            send(events, frame, event);
+        }
+    }
+
+    fn before(&self) -> &[TypeId] {
+        &self.before
+    }
+
+    fn exclusive(&self) -> bool {
+        true
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn parent(&self) -> Option<&dyn SystemGroup> {
+        Some(EndOfTickGroup::instance())
+    }
+}
@@ -0,0 +1,128 @@
+//! Components that remove themselves a number of ticks after insertion.
+//!
+//! `World::insert_temporary` sets a value and records in the entity's `Expiries` the
+//! tick it expires at; `ExpirySystem` removes it in that tick's `CleanupGroup`. Since
+//! both the value and `Expiries` live in storages, a rollback restores the remaining
+//! time along with the component, replacing hand-written timer components for things
+//! like invulnerability flags.
+//!
+//! Register `Expiries` with `Ecs::register` before use; creating its storage schedules
+//! `ExpirySystem`.
+
+use crate::component::{Component, ComponentId};
+use crate::storage::Storage;
+use crate::system::{ComponentCleanupSystem, System, SystemGroup};
+use crate::tick::Tick;
+use crate::world::{CleanupGroup, StorageTable, World};
+use std::any::Any;
+
+/// Expiry tick of every temporary component of an entity, by component id.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Expiries(pub Vec<(ComponentId, Tick)>);
+
+#[allow(non_upper_case_globals)]
+static mut __DECS_COMPONENT_ID_Expiries: u32 = u32::MAX;
+
+impl Component for Expiries {
+    fn id() -> u32 {
+        unsafe { __DECS_COMPONENT_ID_Expiries }
+    }
+
+    fn initialize(id: u32) {
+        unsafe {
+            if __DECS_COMPONENT_ID_Expiries == u32::MAX {
+                __DECS_COMPONENT_ID_Expiries = id;
+            }
+        }
+    }
+
+    fn schedule_cleanup_system(world: &mut World) {
+        let cleanup = ComponentCleanupSystem::<Expiries>::new(world);
+        world.scheduler_mut().add_system(cleanup);
+        let expiry = ExpirySystem::new(world);
+        world.scheduler_mut().add_system(expiry);
+    }
+}
+
+impl Expiries {
+    /// Returns the tick component `id` expires at, if it was inserted as temporary.
+    pub fn get(&self, id: ComponentId) -> Option<Tick> {
+        self.0.iter().find(|(c, _)| *c == id).map(|&(_, at)| at)
+    }
+
+    /// Sets the expiry tick of component `id`, replacing an earlier one.
+    pub fn set(&mut self, id: ComponentId, at: Tick) {
+        match self.0.iter_mut().find(|(c, _)| *c == id) {
+            Some(entry) => entry.1 = at,
+            None => self.0.push((id, at)),
+        }
+    }
+}
+
+/// Removes temporary components whose expiry tick was reached, and the `Expiries` of
+/// entities left without any. Exclusive, since it writes any component type.
+pub struct ExpirySystem {
+    storages: *mut StorageTable,
+    expiries: *mut Storage<Expiries>,
+}
+
+// Safety: the storage table is boxed by the World, which outlives its scheduler's systems;
+// systems run on the thread that owns the world.
+unsafe impl Send for ExpirySystem {}
+unsafe impl Sync for ExpirySystem {}
+
+impl ExpirySystem {
+    pub fn new(world: &mut World) -> Self {
+        Self {
+            storages: world.storage_table(),
+            expiries: world.get_storage::<Expiries>(),
+        }
+    }
+}
+
+impl System for ExpirySystem {
+    fn run(&self, frame: &crate::frame::Frame) {
+        let storages = unsafe { &mut *self.storages };
+        let expiries = unsafe { &mut *self.expiries };
+        let now = frame.current_tick;
+
+        let mut due = Vec::new();
+        let mut cursor = expiries.cursor();
+        while let Some(index) = cursor.next(expiries) {
+            let entries = &expiries.get(index).unwrap().0;
+            if entries.iter().any(|&(_, at)| !at.is_after(now)) {
+                due.push(index);
+            }
+        }
+
+        for index in due {
+            let mut remaining = expiries.get(index).unwrap().clone();
+            remaining.0.retain(|&(id, at)| {
+                if at.is_after(now) {
+                    return true;
+                }
+                if let Some(storage) = storages.get_mut(id as usize).and_then(|s| s.as_mut()) {
+                    storage.remove(frame, index);
+                }
+                false
+            });
+            if remaining.0.is_empty() {
+                expiries.remove(frame, index);
+            } else {
+                expiries.set(frame, index, remaining);
+            }
+        }
+    }
+
+    fn exclusive(&self) -> bool {
+        true
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn parent(&self) -> Option<&dyn SystemGroup> {
+        Some(CleanupGroup::instance())
+    }
+}
@@ -0,0 +1,139 @@
+//! Field-level change tracking for large components.
+//!
+//! Change detection works per value: a write to `Transform::position` marks the whole
+//! transform changed, and the replication encoder sends all of it. Components declared
+//! `#[component(field_mask)]` get a setter enum, `{Name}Field`, with one variant per
+//! field; writing through it with `Storage::set_field` records which field changed:
+//!
+//! ```ignore
+//! #[derive(Clone, Component, Serialize)]
+//! #[component(replicated, field_mask(Serialize, Deserialize))]
+//! struct Transform { position: Vec3, rotation: Quat, scale: Vec3 }
+//!
+//! storage.set_field(&frame, index, TransformField::Position(target));
+//! assert_eq!(storage.changed_fields_since::<TransformField>(index, since), 0b001);
+//!
+//! table.add_fields::<Transform, TransformField>();
+//! ```
+//!
+//! `field_mask(...)` lists derives for the setter enum, e.g. so the encoder can
+//! serialize it. A `DeltaEncoder` sends the value of a component added with
+//! `ReplicationTable::add_fields` as one setter per changed field when only some of its
+//! fields changed; the receiver applies them with `set_field`.
+//!
+//! Writes by any other means (`get_mut`, views, rollbacks) may change any field and
+//! mark all of them. Field bits are kept for the last two change ticks a value was
+//! written in (see `World::change_tick`); a reader that last looked before those
+//! sees every field as changed.
+
+use crate::tick::Tick;
+use std::collections::HashMap;
+
+/// A write to one field of `T`: the `{T}Field` enum generated by
+/// `#[component(field_mask)]`, with a variant per field holding its new value.
+pub trait FieldSetter<T>: Sized {
+    /// Field names, in declaration order; at most 64.
+    const NAMES: &'static [&'static str];
+
+    /// Bits of every field.
+    const ALL: u64 = if Self::NAMES.len() >= 64 {
+        u64::MAX
+    } else {
+        (1u64 << Self::NAMES.len()) - 1
+    };
+
+    /// Position of the field this setter writes in `NAMES`.
+    fn index(&self) -> u32;
+
+    /// Writes the field into `target`.
+    fn apply(self, target: &mut T);
+
+    /// A setter holding a copy of field `index` of `value`; None past the last field.
+    fn read(value: &T, index: u32) -> Option<Self>;
+}
+
+/// Fields written in the last two change ticks a value was written in.
+#[derive(Clone, Copy)]
+struct FieldMask {
+    tick: Tick,
+    mask: u64,
+    prev_tick: Tick,
+    prev_mask: u64,
+    /// Newest change tick whose writes are no longer known field by field.
+    forgotten: Tick,
+}
+
+/// Per-value field bits of a storage, kept once `Storage::set_field` was first used.
+#[derive(Default)]
+pub(crate) struct FieldChanges {
+    enabled: bool,
+    /// Field being written by `set_field`; other writes touch every field.
+    writing: Option<u32>,
+    masks: HashMap<u32, FieldMask>,
+}
+
+impl FieldChanges {
+    /// Starts tracking and makes the next `note` record field `field` only.
+    pub(crate) fn begin_field_write(&mut self, field: u32) {
+        self.enabled = true;
+        self.writing = Some(field);
+    }
+
+    pub(crate) fn end_field_write(&mut self) {
+        self.writing = None;
+    }
+
+    /// Records a write of the value at `index` stamped `tick`, whose previous stamp was
+    /// `before`.
+    #[inline(always)]
+    pub(crate) fn note(&mut self, index: u32, tick: Tick, before: Tick) {
+        if self.enabled {
+            self.note_slow(index, tick, before);
+        }
+    }
+
+    #[cold]
+    fn note_slow(&mut self, index: u32, tick: Tick, before: Tick) {
+        let bits = self.writing.map_or(u64::MAX, |field| 1u64 << field);
+        // Writes before tracking started are only known by the value's stamp.
+        let entry = self.masks.entry(index).or_insert(FieldMask {
+            tick,
+            mask: 0,
+            prev_tick: Tick(0),
+            prev_mask: 0,
+            forgotten: before,
+        });
+        if entry.tick != tick {
+            if entry.prev_mask != 0 {
+                entry.forgotten = entry.prev_tick;
+            }
+            entry.prev_tick = entry.tick;
+            entry.prev_mask = entry.mask;
+            entry.tick = tick;
+            entry.mask = 0;
+        }
+        entry.mask |= bits;
+    }
+
+    /// Bits of the fields written after `since`, out of `all`, for the value at `index`
+    /// last stamped `stamp`.
+    pub(crate) fn changed_since(&self, index: u32, stamp: Tick, since: Tick, all: u64) -> u64 {
+        if !stamp.is_after(since) {
+            return 0;
+        }
+        let Some(entry) = self.masks.get(&index) else {
+            return all;
+        };
+        if entry.forgotten.is_after(since) || stamp.is_after(entry.tick) {
+            return all;
+        }
+        let mut bits = 0;
+        if entry.tick.is_after(since) {
+            bits |= entry.mask;
+        }
+        if entry.prev_tick.is_after(since) {
+            bits |= entry.prev_mask;
+        }
+        bits & all
+    }
+}
@@ -1,19 +1,102 @@
-use crate::tick::Tick;
+use crate::arena::Arena;
+use crate::tick::{ChangeTick, Tick};
+use std::ptr::NonNull;
+use std::rc::Rc;
 
+/// Per-tick context handed to every system.
+///
+/// Besides the tick being simulated, a frame carries the timing produced by
+/// the game loop helper (`World::update`), so integration and render
+/// extraction systems can read it instead of keeping their own globals.
 pub struct Frame {
     pub current_tick: Tick,
+    /// Length of one simulation step in seconds.
+    pub fixed_dt: f32,
+    /// Simulated time in seconds at `current_tick` (`current_tick * fixed_dt`).
+    pub elapsed: f64,
+    /// Fraction of a step left in the accumulator after the last update,
+    /// in `[0, 1)`. Render extraction interpolates previous/current state with it.
+    pub alpha: f32,
+    scratch: Scratch,
+    change_tick: Rc<ChangeTick>,
+}
+
+/// Arena behind `Frame::scratch`.
+enum Scratch {
+    /// The world's arena, lent to the frame of a running tick and reset after it.
+    World(NonNull<Arena>),
+    /// An arena of the frame's own, freed with it. It allocates on first use.
+    Owned(Arena),
 }
 
 impl Frame {
+    /// Step length used when no explicit timing is configured (60 Hz).
+    pub const DEFAULT_FIXED_DT: f32 = 1.0 / 60.0;
+
     pub fn new(current_tick: Tick) -> Self {
-        Self { current_tick }
+        Self::with_timing(current_tick, Self::DEFAULT_FIXED_DT, 0.0)
+    }
+
+    /// Creates a frame for `current_tick` with the given step length and
+    /// interpolation alpha; `elapsed` is derived from the tick.
+    pub fn with_timing(current_tick: Tick, fixed_dt: f32, alpha: f32) -> Self {
+        Self {
+            current_tick,
+            fixed_dt,
+            elapsed: current_tick.0 as f64 * fixed_dt as f64,
+            alpha,
+            scratch: Scratch::Owned(Arena::new()),
+            change_tick: Rc::default(),
+        }
+    }
+
+    /// Makes the frame advance `change_tick` instead of a counter of its own.
+    pub(crate) fn with_change_tick(mut self, change_tick: Rc<ChangeTick>) -> Self {
+        self.change_tick = change_tick;
+        self
+    }
+
+    /// Makes `scratch` allocate from `arena` instead of an arena of the frame's own.
+    ///
+    /// # Safety
+    /// `arena` must outlive the frame and must not be reset while anything allocated
+    /// through the frame is alive.
+    pub(crate) unsafe fn with_scratch(mut self, arena: NonNull<Arena>) -> Self {
+        self.scratch = Scratch::World(arena);
+        self
+    }
+
+    /// Bump allocator for temporary data of this tick, e.g. `Vec::new_in(frame.scratch())`
+    /// for a path or a list of candidates a system builds and throws away.
+    ///
+    /// Frames of `World::run` share one arena per world, reset after the tick, so the
+    /// blocks are reused tick after tick instead of going back to the heap; other frames
+    /// free theirs when dropped. Freeing single allocations is a no-op, so a growing
+    /// `Vec` leaves its old buffers behind until then. Nothing allocated here is part of
+    /// a storage, so it is never snapshot or rolled back.
+    pub fn scratch(&self) -> &Arena {
+        match &self.scratch {
+            Scratch::World(arena) => unsafe { arena.as_ref() },
+            Scratch::Owned(arena) => arena,
+        }
+    }
+
+    /// Returns the change tick of the world the frame was made by (`World::change_tick`).
+    /// Frames made with `new` or `with_timing` count on their own, so `Changed` systems
+    /// run with them don't see the world's writes as newer than their last run.
+    pub fn change_tick(&self) -> Tick {
+        self.change_tick.get()
+    }
+
+    /// Advances the change counter read by `change_tick` and returns the new value.
+    /// `Changed` systems call it on every run to tell later writes apart.
+    pub fn advance_change_tick(&self) -> Tick {
+        self.change_tick.advance()
     }
 }
 
 impl Default for Frame {
     fn default() -> Self {
-        Self {
-            current_tick: Tick(0),
-        }
+        Self::new(Tick(0))
     }
 }
@@ -0,0 +1,43 @@
+//! Memory a world holds on to without needing it, for servers that enforce memory
+//! ceilings.
+//!
+//! `World::pending_garbage` counts it and `World::collect_garbage(budget)` frees it a
+//! bounded number of snapshots at a time, so a server can spread the work over quiet
+//! frames instead of one `World::request_compaction`:
+//!
+//! ```ignore
+//! world.confirm_tick(last_tick_every_peer_acked);
+//! if world.pending_garbage().total() > LIMIT {
+//!     world.collect_garbage(32);
+//! }
+//! ```
+
+/// What `World::pending_garbage` found.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GarbageReport {
+    /// Entities marked `Destroyed` whose components were not cleaned up yet, e.g. kept
+    /// by `World::set_destroyed_retention` or marked outside `run`. The cleanup systems
+    /// of the next runs free them; `collect_garbage` does not, as their removal must be
+    /// recorded in a tick.
+    pub destroyed_entities: usize,
+    /// Rollback snapshots (with their pages and arenas) pooled for reuse by later ticks.
+    pub pooled_snapshots: usize,
+    /// History snapshots at or before `World::confirmed_tick`, which no rollback to a
+    /// confirmed tick needs.
+    pub confirmed_snapshots: usize,
+}
+
+impl GarbageReport {
+    /// Number of snapshots `World::collect_garbage` can free.
+    pub fn collectable(&self) -> usize {
+        self.pooled_snapshots + self.confirmed_snapshots
+    }
+
+    pub fn total(&self) -> usize {
+        self.destroyed_entities + self.collectable()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total() == 0
+    }
+}
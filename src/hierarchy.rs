@@ -2,6 +2,7 @@ use decs::entity::Entity;
 use decs_macros::Component;
 use std::any::TypeId;
 
+use crate::destroy::DestroyOrder;
 use crate::storage::Storage;
 use crate::system::{System, SystemGroup};
 use crate::world::{HierarchyGroup, World};
@@ -41,6 +42,9 @@ impl UpdateHierarchySystem {
 
 impl System for UpdateHierarchySystem {
     fn run(&self, frame: &crate::frame::Frame) {
+        crate::aliasing::track_borrow::<ChildOf>(self.name(), true);
+        crate::aliasing::track_borrow::<Parent>(self.name(), true);
+        crate::aliasing::track_borrow::<Entity>(self.name(), false);
         let storage = unsafe { &mut *self.child_storage };
         let parents = unsafe { &mut *self.parent_storage };
         let entities = unsafe { &mut *self.entity_storage };
@@ -104,45 +108,7 @@ impl System for UpdateHierarchySystem {
 
             // 1. Detach from old parent (if any)
             if let Some(old_parent) = change.old_parent {
-                let (prev_sibling, next_sibling) = {
-                    if let Some(child_comp) = storage.get(child_idx) {
-                        (child_comp.prev_sibling, child_comp.next_sibling)
-                    } else {
-                        (None, None)
-                    }
-                };
-
-                // Update siblings
-                if let Some(prev) = prev_sibling
-                    && let Some(pv) = storage.get_mut(frame, prev.index())
-                {
-                    pv.next_sibling = next_sibling;
-                }
-                if let Some(next) = next_sibling
-                    && let Some(nv) = storage.get_mut(frame, next.index())
-                {
-                    nv.prev_sibling = prev_sibling;
-                }
-
-                // Update old parent
-                let update_head = prev_sibling.is_none();
-                let update_tail = next_sibling.is_none();
-
-                if update_head || update_tail {
-                    // Get parent mut - ensure we create it if missing (though it should exist if it was a parent)
-                    // Use get_mut directly since we handle creation logic below if needed, but for old_parent it must exist
-                    if let Some(old_p) = parents.get_mut(frame, old_parent.index()) {
-                        if update_head {
-                            old_p.first_child = next_sibling.unwrap_or(Entity::none());
-                        }
-                        if update_tail {
-                            old_p.last_child = prev_sibling.unwrap_or(Entity::none());
-                        }
-                        if old_p.first_child.is_none() {
-                            old_p.last_child = Entity::none();
-                        }
-                    }
-                }
+                detach(frame, storage, parents, child_idx, old_parent);
             }
 
             // 2. Attach to new parent
@@ -184,6 +150,13 @@ impl System for UpdateHierarchySystem {
         }
     }
 
+    // Entity generations are looked up when resolving parents, so the
+    // scheduler must keep this system out of the Entity cleanup wavefront.
+    fn reads(&self) -> &'static [TypeId] {
+        static R: &[TypeId] = &[TypeId::of::<Entity>()];
+        R
+    }
+
     fn writes(&self) -> &'static [TypeId] {
         static W: &[TypeId] = &[TypeId::of::<ChildOf>(), TypeId::of::<Parent>()];
         W
@@ -202,3 +175,110 @@ impl System for UpdateHierarchySystem {
 // Raw storage pointer is only dereferenced during run with valid lifetime ensured by World.
 unsafe impl Send for UpdateHierarchySystem {}
 unsafe impl Sync for UpdateHierarchySystem {}
+
+/// Unlinks the child at `child_idx` from its siblings and from `parent`'s first/last
+/// child. The child's own `ChildOf` links are left as they were.
+fn detach(
+    frame: &crate::frame::Frame,
+    storage: &mut Storage<ChildOf>,
+    parents: &mut Storage<Parent>,
+    child_idx: u32,
+    parent: Entity,
+) {
+    let (prev_sibling, next_sibling) = match storage.get(child_idx) {
+        Some(child_comp) => (child_comp.prev_sibling, child_comp.next_sibling),
+        None => (None, None),
+    };
+
+    // Update siblings
+    if let Some(prev) = prev_sibling
+        && let Some(pv) = storage.get_mut(frame, prev.index())
+    {
+        pv.next_sibling = next_sibling;
+    }
+    if let Some(next) = next_sibling
+        && let Some(nv) = storage.get_mut(frame, next.index())
+    {
+        nv.prev_sibling = prev_sibling;
+    }
+
+    // Update the parent if the child was its head or tail
+    let update_head = prev_sibling.is_none();
+    let update_tail = next_sibling.is_none();
+    if (update_head || update_tail)
+        && let Some(p) = parents.get_mut(frame, parent.index())
+    {
+        if update_head {
+            p.first_child = next_sibling.unwrap_or(Entity::none());
+        }
+        if update_tail {
+            p.last_child = prev_sibling.unwrap_or(Entity::none());
+        }
+        if p.first_child.is_none() {
+            p.last_child = Entity::none();
+        }
+    }
+}
+
+/// `DestroyOrder` for the hierarchy: children are torn down before their parent. A
+/// destroyed child is unlinked from its parent and siblings; the children a destroyed
+/// parent still has are orphaned (`ChildOf::parent` cleared).
+pub struct HierarchyOrder {
+    child_storage: *mut Storage<ChildOf>,
+    parent_storage: *mut Storage<Parent>,
+}
+
+impl HierarchyOrder {
+    pub fn new(world: &mut World) -> Self {
+        Self {
+            child_storage: world.get_storage::<ChildOf>(),
+            parent_storage: world.get_storage::<Parent>(),
+        }
+    }
+}
+
+impl DestroyOrder for HierarchyOrder {
+    fn before(&self, entity: Entity, out: &mut Vec<Entity>) {
+        let storage = unsafe { &*self.child_storage };
+        let parents = unsafe { &*self.parent_storage };
+        let Some(parent) = parents.get(entity.index()) else {
+            return;
+        };
+        let mut child = Some(parent.first_child).filter(|c| !c.is_none());
+        while let Some(c) = child {
+            out.push(c);
+            child = storage.get(c.index()).and_then(|v| v.next_sibling);
+        }
+    }
+
+    fn tear_down(&self, frame: &crate::frame::Frame, entity: Entity) {
+        let storage = unsafe { &mut *self.child_storage };
+        let parents = unsafe { &mut *self.parent_storage };
+        let index = entity.index();
+        if let Some(parent) = storage.get(index).and_then(|v| v.parent) {
+            detach(frame, storage, parents, index, parent);
+        }
+        let first_child = parents.get(index).map(|p| p.first_child);
+        let mut child = first_child.filter(|c| !c.is_none());
+        while let Some(c) = child {
+            let Some(v) = storage.get_mut(frame, c.index()) else {
+                break;
+            };
+            child = v.next_sibling;
+            v.parent = None;
+            v.prev_sibling = None;
+            v.next_sibling = None;
+        }
+        if first_child.is_some_and(|c| !c.is_none())
+            && let Some(p) = parents.get_mut(frame, index)
+        {
+            p.first_child = Entity::none();
+            p.last_child = Entity::none();
+        }
+    }
+
+    fn writes(&self) -> &[TypeId] {
+        static W: &[TypeId] = &[TypeId::of::<ChildOf>(), TypeId::of::<Parent>()];
+        W
+    }
+}
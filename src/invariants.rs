@@ -0,0 +1,148 @@
+use crate::frame::Frame;
+use crate::storage::StorageLike;
+use crate::system::{System, SystemGroup};
+use crate::tick::Tick;
+use crate::world::{DestroyGroup, World};
+use std::any::{Any, TypeId};
+use std::fmt;
+
+/// Level of the storage hierarchy at which an invariant failed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InvariantLevel {
+    Storage,
+    Page { storage_idx: u32 },
+    Chunk { storage_idx: u32, page_idx: u32 },
+}
+
+/// A single failed invariant check.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct InvariantViolation {
+    pub component_id: u32,
+    pub type_name: &'static str,
+    /// `None` for the live storage, `Some(tick)` for the rollback snapshot of that tick.
+    pub rollback_tick: Option<Tick>,
+    pub level: InvariantLevel,
+}
+
+/// Result of `World::verify_all`: every failed check, in storage order.
+#[derive(Clone, Default, Debug)]
+pub struct InvariantReport {
+    pub violations: Vec<InvariantViolation>,
+}
+
+impl InvariantReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if no invariant failed.
+    pub fn is_ok(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    pub fn push(&mut self, violation: InvariantViolation) {
+        self.violations.push(violation);
+    }
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (id {})", self.type_name, self.component_id)?;
+        if let Some(tick) = self.rollback_tick {
+            write!(f, " rollback {:?}", tick)?;
+        }
+        match self.level {
+            InvariantLevel::Storage => write!(f, ": storage level"),
+            InvariantLevel::Page { storage_idx } => write!(f, ": page {}", storage_idx),
+            InvariantLevel::Chunk {
+                storage_idx,
+                page_idx,
+            } => write!(f, ": chunk {}/{}", storage_idx, page_idx),
+        }
+    }
+}
+
+impl fmt::Display for InvariantReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_ok() {
+            return write!(f, "all invariants hold");
+        }
+        writeln!(f, "{} invariant violation(s):", self.violations.len())?;
+        for v in &self.violations {
+            writeln!(f, "  {}", v)?;
+        }
+        Ok(())
+    }
+}
+
+/// Debug system that runs the full invariant verification every `interval` ticks
+/// and panics with the report on failure. It runs last in `DestroyGroup`, after
+/// every writer of the storages it checks.
+///
+/// Only storages that exist when the system is created are checked, so add it
+/// after registering the systems and components of interest.
+pub struct InvariantCheckSystem {
+    interval: u32,
+    storages: Vec<*const dyn StorageLike>,
+    reads: Vec<TypeId>,
+}
+
+// Safety: the storage pointers are owned by the World, which outlives its scheduler's systems.
+unsafe impl Send for InvariantCheckSystem {}
+unsafe impl Sync for InvariantCheckSystem {}
+
+impl InvariantCheckSystem {
+    pub fn new(world: &mut World, interval: u32) -> Self {
+        assert!(interval > 0, "interval must be positive");
+        let storages: Vec<*const dyn StorageLike> = world
+            .storages()
+            .map(|s| s as *const dyn StorageLike)
+            .collect();
+        let reads = storages
+            .iter()
+            .map(|&s| unsafe { (*s).component_type_id() })
+            .collect();
+        Self {
+            interval,
+            storages,
+            reads,
+        }
+    }
+}
+
+impl System for InvariantCheckSystem {
+    fn run(&self, frame: &Frame) {
+        if !frame.current_tick.0.is_multiple_of(self.interval) {
+            return;
+        }
+        let mut report = InvariantReport::new();
+        for &storage in &self.storages {
+            let storage = unsafe { &*storage };
+            crate::aliasing::track_borrow_id(
+                storage.component_type_id(),
+                storage.component_name(),
+                self.name(),
+                false,
+            );
+            storage.verify_report(&mut report);
+        }
+        assert!(
+            report.is_ok(),
+            "invariant check failed at {:?}: {}",
+            frame.current_tick,
+            report
+        );
+    }
+
+    fn reads(&self) -> &[TypeId] {
+        &self.reads
+    }
+
+    fn parent(&self) -> Option<&dyn SystemGroup> {
+        Some(DestroyGroup::instance())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
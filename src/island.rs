@@ -0,0 +1,148 @@
+//! Simulation islands: groups of entities that never interact, each simulated in its
+//! own `World` with the same schedule.
+//!
+//! Independent arenas or matches hosted on one server don't need to share storages:
+//! in separate worlds every query only scans its own island's masks, a rollback in one
+//! match leaves the others alone, and the islands can run on different threads.
+//!
+//! Tag entities with `Island(id)` and call `Islands::split_from` to move them into
+//! their island's world, or spawn them there directly through `Islands::world_mut`.
+//! Entities keep their index when moved, so `Entity` references between members of
+//! the same island stay valid.
+
+use crate::component::Component;
+use crate::storage::Storage;
+use crate::world::World;
+use decs_macros::Component;
+use std::collections::BTreeMap;
+
+/// Island an entity belongs to. Register it with `Ecs::register` before use.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Component)]
+pub struct Island(pub u32);
+
+/// Builds the schedule of a new island's world.
+type Setup = Box<dyn Fn(&mut World, u32)>;
+
+/// One world per island id, all built by the same setup function.
+pub struct Islands {
+    worlds: BTreeMap<u32, World>,
+    setup: Setup,
+}
+
+impl Islands {
+    /// Creates an empty set of islands. `setup` adds the schedule (and any resources) to
+    /// each island's world when the island is first used; the world is finalized after.
+    pub fn new(setup: impl Fn(&mut World, u32) + 'static) -> Self {
+        Self {
+            worlds: BTreeMap::new(),
+            setup: Box::new(setup),
+        }
+    }
+
+    /// Returns the world of island `id`, if it exists.
+    pub fn world(&self, id: u32) -> Option<&World> {
+        self.worlds.get(&id)
+    }
+
+    /// Returns the world of island `id`, creating it on first use.
+    pub fn world_mut(&mut self, id: u32) -> &mut World {
+        let setup = &self.setup;
+        self.worlds.entry(id).or_insert_with(|| {
+            let mut world = World::new();
+            setup(&mut world, id);
+            world.finalize();
+            world
+        })
+    }
+
+    /// Iterates the island ids in ascending order.
+    pub fn ids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.worlds.keys().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.worlds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.worlds.is_empty()
+    }
+
+    /// Removes island `id` (when its match ends), returning its world.
+    pub fn remove(&mut self, id: u32) -> Option<World> {
+        self.worlds.remove(&id)
+    }
+
+    /// Moves every entity of `world` carrying an `Island` into that island's world, at
+    /// the same index, and returns how many were moved. Entities whose index is already
+    /// taken in the island's world stay where they are. Resources stay in `world`.
+    pub fn split_from(&mut self, world: &mut World) -> usize {
+        let mut members = Vec::new();
+        if let Some(islands) = world
+            .get_storage_by_id(Island::id())
+            .and_then(|s| s.as_any().downcast_ref::<Storage<Island>>())
+        {
+            let mut cursor = islands.cursor();
+            while let Some(index) = cursor.next(islands) {
+                members.push((index, islands.get(index).unwrap().0));
+            }
+        }
+
+        let mut moved = 0;
+        for (index, id) in members {
+            let dst = self.world_mut(id);
+            if unsafe { (*dst.get_entity_storage()).get(index) }.is_some() {
+                continue;
+            }
+            if world.move_entity_to(index, dst) {
+                moved += 1;
+            }
+        }
+        // Moving may have created storages, which clears the schedule.
+        for island in self.worlds.values_mut() {
+            island.scheduler_mut().build_wavefronts();
+        }
+        moved
+    }
+
+    /// Runs one tick of every island, in id order.
+    pub fn run(&mut self) {
+        for world in self.worlds.values_mut() {
+            world.run();
+        }
+    }
+
+    /// Runs one tick of every island, each on its own scoped thread. A panic in any
+    /// island is resumed on the calling thread once all islands finished.
+    ///
+    /// # Safety
+    ///
+    /// Worlds are not `Send`. Every island's component values, queued commands and
+    /// systems must be safe to use from another thread: no `Rc`, thread-bound handles or
+    /// unsynchronized state shared between islands.
+    pub unsafe fn run_parallel(&mut self) {
+        struct SendWorld<'a>(&'a mut World);
+        unsafe impl Send for SendWorld<'_> {}
+        impl<'a> SendWorld<'a> {
+            fn into_inner(self) -> &'a mut World {
+                self.0
+            }
+        }
+
+        let results: Vec<_> = std::thread::scope(|scope| {
+            let handles: Vec<_> = self
+                .worlds
+                .values_mut()
+                .map(|world| {
+                    let world = SendWorld(world);
+                    scope.spawn(move || world.into_inner().run())
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join()).collect()
+        });
+
+        if let Some(Err(payload)) = results.into_iter().find(Result::is_err) {
+            std::panic::resume_unwind(payload);
+        }
+    }
+}
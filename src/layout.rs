@@ -0,0 +1,135 @@
+//! Per-storage control over where chunks, the blocks holding a storage's values, live.
+//!
+//! By default every storage allocates its chunks with `Box`, so hot and cold components
+//! share the global allocator and the 64-byte alignment of `Chunk`. A `ChunkLayout`
+//! set with `World::set_chunk_layout` (or `Storage::set_chunk_layout`) changes that
+//! for one component type:
+//!
+//! ```ignore
+//! static HOT: MyBumpAllocator = MyBumpAllocator::new();
+//! world.set_chunk_layout::<Position>(ChunkLayout::new().allocator(&HOT));
+//! world.set_chunk_layout::<Mesh>(ChunkLayout::new().aligned(ChunkLayout::HUGE_PAGE));
+//! ```
+//!
+//! Pages (the 64 chunk pointers and masks above each chunk) and rollback snapshots are
+//! not affected.
+
+use crate::component::Component;
+use crate::storage::Chunk;
+use std::alloc::{AllocError, Allocator, Global, Layout, handle_alloc_error};
+use std::ptr::NonNull;
+
+/// How a storage allocates its chunks: from which allocator, at which alignment.
+#[derive(Clone, Copy)]
+pub struct ChunkLayout {
+    align: usize,
+    allocator: &'static dyn Allocator,
+}
+
+impl Default for ChunkLayout {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for ChunkLayout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChunkLayout")
+            .field("align", &self.align)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ChunkLayout {
+    /// Alignment of a cache line pair, which keeps the adjacent-line prefetcher of
+    /// one chunk from pulling in its neighbour. `Chunk` is always 64-byte aligned.
+    pub const CACHE_LINE_PAIR: usize = 128;
+    /// Alignment of a 4 KiB memory page.
+    pub const PAGE: usize = 4096;
+    /// Alignment of a 2 MiB huge page, so transparent huge pages can back the chunks
+    /// of components whose 64 values span megabytes.
+    pub const HUGE_PAGE: usize = 2 << 20;
+
+    /// The default layout: the global allocator at `Chunk`'s own alignment.
+    pub const fn new() -> Self {
+        Self {
+            align: 1,
+            allocator: &Global,
+        }
+    }
+
+    /// Aligns every chunk to at least `align` bytes.
+    ///
+    /// # Panics
+    /// Panics if `align` is not a power of two.
+    pub const fn aligned(mut self, align: usize) -> Self {
+        assert!(
+            align.is_power_of_two(),
+            "chunk alignment must be a power of two"
+        );
+        self.align = align;
+        self
+    }
+
+    /// Allocates chunks from `allocator` instead of the global allocator.
+    pub const fn allocator(mut self, allocator: &'static dyn Allocator) -> Self {
+        self.allocator = allocator;
+        self
+    }
+
+    /// Returns the alignment of a chunk of `T` under this layout.
+    pub fn align_of<T: Component>(&self) -> usize {
+        Self::chunk_layout::<T>(self.align).align()
+    }
+
+    fn chunk_layout<T: Component>(align: usize) -> Layout {
+        Layout::new::<Chunk<T>>()
+            .align_to(align)
+            .expect("chunk alignment overflows the address space")
+    }
+
+    /// Allocates an empty chunk.
+    pub(crate) fn try_alloc<T: Component>(&self) -> Result<*mut Chunk<T>, AllocError> {
+        let ptr = self
+            .allocator
+            .allocate(Self::chunk_layout::<T>(self.align))?
+            .cast::<Chunk<T>>()
+            .as_ptr();
+        unsafe { ptr.write(Chunk::new()) };
+        Ok(ptr)
+    }
+
+    /// Allocates an empty chunk, aborting if the allocator fails.
+    pub(crate) fn alloc<T: Component>(&self) -> *mut Chunk<T> {
+        match self.try_alloc() {
+            Ok(ptr) => ptr,
+            Err(_) => handle_alloc_error(Self::chunk_layout::<T>(self.align)),
+        }
+    }
+
+    /// Drops a storage's hold on a chunk, dropping and freeing it unless a branch still
+    /// shares it (see `World::branch`).
+    ///
+    /// # Safety
+    /// Same as `free`.
+    pub(crate) unsafe fn release<T: Component>(&self, chunk: *mut Chunk<T>) {
+        if unsafe { (*chunk).release() } {
+            unsafe { self.free(chunk) };
+        }
+    }
+
+    /// Drops and frees a chunk.
+    ///
+    /// # Safety
+    /// `chunk` must come from `alloc`/`try_alloc` of a layout equal to this one, and must
+    /// not be used afterwards.
+    pub(crate) unsafe fn free<T: Component>(&self, chunk: *mut Chunk<T>) {
+        unsafe {
+            std::ptr::drop_in_place(chunk);
+            self.allocator.deallocate(
+                NonNull::new_unchecked(chunk).cast(),
+                Self::chunk_layout::<T>(self.align),
+            );
+        }
+    }
+}
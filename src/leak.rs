@@ -0,0 +1,135 @@
+//! Opt-in allocation tracking (feature `leak-tracking`).
+//!
+//! Counts live storage pages/chunks and rollback snapshots/pages/chunks per component
+//! type, so soak tests can assert that pools and rollback recycling return to a stable
+//! baseline. Counters are process-wide (shared by every `World`) and keyed by the
+//! component's type name. Without the feature the `record_*` hooks are inlined no-ops
+//! and every count reads as zero.
+
+#[cfg(feature = "leak-tracking")]
+use std::collections::HashMap;
+use std::fmt;
+#[cfg(feature = "leak-tracking")]
+use std::sync::{LazyLock, Mutex};
+
+/// Kind of tracked allocation.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AllocKind {
+    Page,
+    Chunk,
+    RollbackSnapshot,
+    RollbackPage,
+    RollbackChunk,
+}
+
+/// Live allocation counts for one component type.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct LiveAllocations {
+    pub pages: i64,
+    pub chunks: i64,
+    pub rollback_snapshots: i64,
+    pub rollback_pages: i64,
+    pub rollback_chunks: i64,
+}
+
+#[cfg(feature = "leak-tracking")]
+impl LiveAllocations {
+    fn slot(&mut self, kind: AllocKind) -> &mut i64 {
+        match kind {
+            AllocKind::Page => &mut self.pages,
+            AllocKind::Chunk => &mut self.chunks,
+            AllocKind::RollbackSnapshot => &mut self.rollback_snapshots,
+            AllocKind::RollbackPage => &mut self.rollback_pages,
+            AllocKind::RollbackChunk => &mut self.rollback_chunks,
+        }
+    }
+}
+
+#[cfg(feature = "leak-tracking")]
+static LIVE: LazyLock<Mutex<HashMap<&'static str, LiveAllocations>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[cfg(feature = "leak-tracking")]
+fn record(type_name: &'static str, kind: AllocKind, delta: i64) {
+    let mut live = LIVE.lock().unwrap_or_else(|e| e.into_inner());
+    *live.entry(type_name).or_default().slot(kind) += delta;
+}
+
+/// Records an allocation of `kind` owned by the storage of `T`.
+#[inline(always)]
+pub fn record_alloc<T>(kind: AllocKind) {
+    #[cfg(feature = "leak-tracking")]
+    record(std::any::type_name::<T>(), kind, 1);
+    #[cfg(not(feature = "leak-tracking"))]
+    let _ = kind;
+}
+
+/// Records that an allocation of `kind` owned by the storage of `T` was freed.
+#[inline(always)]
+pub fn record_free<T>(kind: AllocKind) {
+    #[cfg(feature = "leak-tracking")]
+    record(std::any::type_name::<T>(), kind, -1);
+    #[cfg(not(feature = "leak-tracking"))]
+    let _ = kind;
+}
+
+/// Returns the live allocation counts for the component type named `type_name`.
+pub fn live_allocations_by_name(type_name: &str) -> LiveAllocations {
+    #[cfg(feature = "leak-tracking")]
+    {
+        let live = LIVE.lock().unwrap_or_else(|e| e.into_inner());
+        live.get(type_name).copied().unwrap_or_default()
+    }
+    #[cfg(not(feature = "leak-tracking"))]
+    {
+        let _ = type_name;
+        LiveAllocations::default()
+    }
+}
+
+/// Returns the live allocation counts for component type `T`.
+pub fn live_allocations<T>() -> LiveAllocations {
+    live_allocations_by_name(std::any::type_name::<T>())
+}
+
+/// Live allocations of one component type, as reported by `World::leak_report`.
+#[derive(Clone, Debug)]
+pub struct LeakEntry {
+    pub type_name: &'static str,
+    pub live: LiveAllocations,
+}
+
+/// Per-component live allocation counts for every storage registered in a World.
+#[derive(Clone, Default, Debug)]
+pub struct LeakReport {
+    pub entries: Vec<LeakEntry>,
+}
+
+impl LeakReport {
+    /// Returns the counts for `T`, if the World has a storage for it.
+    pub fn get<T>(&self) -> Option<LiveAllocations> {
+        let name = std::any::type_name::<T>();
+        self.entries
+            .iter()
+            .find(|e| e.type_name == name)
+            .map(|e| e.live)
+    }
+}
+
+impl fmt::Display for LeakReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for e in &self.entries {
+            writeln!(
+                f,
+                "{}: pages {} chunks {} rollback snapshots {} pages {} chunks {}",
+                e.type_name,
+                e.live.pages,
+                e.live.chunks,
+                e.live.rollback_snapshots,
+                e.live.rollback_pages,
+                e.live.rollback_chunks
+            )?;
+        }
+        Ok(())
+    }
+}
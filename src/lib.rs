@@ -1,18 +1,55 @@
 #![feature(allocator_api)]
+#![feature(associated_type_defaults)]
 #![allow(non_upper_case_globals)]
 
 extern crate self as decs;
 
+pub mod aliasing;
+pub mod audit;
+pub mod bitmap;
+pub mod bundle;
+pub mod changes;
+#[cfg(feature = "serde")]
+pub mod codec;
+pub mod commands;
 pub mod component;
+pub mod condition;
+pub mod destroy;
 pub mod ecs;
 pub mod entity;
+pub mod error;
+pub mod event;
+pub mod expiry;
+pub mod fields;
 pub mod frame;
+pub mod gc;
 pub mod hierarchy;
+pub mod invariants;
+pub mod island;
+pub mod layout;
+pub mod leak;
+#[cfg(feature = "serde")]
+pub mod migrate;
+pub mod net;
+pub mod resource;
+pub mod rng;
+pub mod replay;
+#[cfg(feature = "serde")]
+pub mod replication;
 pub mod rollback;
 pub mod scheduler;
+pub mod shadow;
+pub mod signature;
+pub mod slot;
+pub mod snapshot;
+pub mod sorted;
+pub mod spatial;
 pub mod storage;
 pub mod system;
+pub mod testing;
 pub mod tick;
+pub mod timer;
+pub mod trace;
 pub mod view;
 pub mod world;
 pub mod arena;
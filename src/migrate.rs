@@ -0,0 +1,143 @@
+//! Loading component data written by older builds.
+//!
+//! Every component carries a schema version (`Component::VERSION`, set with
+//! `#[component(version = N)]` and recorded by `Ecs::register`). Saves and network
+//! snapshots store that version next to the data; when a reader finds an older version
+//! it hands the data to `Migrate::migrate` instead of failing to deserialize it.
+//!
+//! ```ignore
+//! #[derive(Deserialize)]
+//! struct HealthV1 { hp: u8 }
+//!
+//! #[derive(Clone, Component, Deserialize)]
+//! #[component(version = 2)]
+//! struct Health { hp: u32, max: u32 }
+//!
+//! impl Migrate for Health {
+//!     fn migrate<'de, D: Deserializer<'de>>(version: u32, data: D) -> Result<Self, D::Error> {
+//!         match version {
+//!             1 => HealthV1::deserialize(data).map(|old| Health { hp: old.hp as u32, max: 100 }),
+//!             _ => Err(unsupported_version::<Self, D::Error>(version)),
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::component::Component;
+use serde::de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Upgrades component data written with an older `Component::VERSION`.
+pub trait Migrate: Component + for<'de> Deserialize<'de> {
+    /// Decodes `data`, written when this component's version was `version`
+    /// (always older than `Self::VERSION`), into the current layout.
+    fn migrate<'de, D: Deserializer<'de>>(version: u32, data: D) -> Result<Self, D::Error>;
+}
+
+/// Error for a version `Migrate::migrate` (or `deserialize_versioned`) cannot read.
+pub fn unsupported_version<T: Component, E: de::Error>(version: u32) -> E {
+    E::custom(format_args!(
+        "unsupported version {} of {} (current version is {})",
+        version,
+        std::any::type_name::<T>(),
+        T::VERSION
+    ))
+}
+
+/// Deserializes a `T` stored with schema `version`: the current version decodes
+/// directly, older ones go through `Migrate::migrate` and newer ones are rejected.
+pub fn deserialize_versioned<'de, T: Migrate, D: Deserializer<'de>>(
+    version: u32,
+    data: D,
+) -> Result<T, D::Error> {
+    if version == T::VERSION {
+        T::deserialize(data)
+    } else if version < T::VERSION {
+        T::migrate(version, data)
+    } else {
+        Err(unsupported_version::<T, D::Error>(version))
+    }
+}
+
+/// A component value tagged with its schema version, for self-describing formats.
+///
+/// Serializes as `{ version, value }` using the current `T::VERSION`; deserializing
+/// reads the version first and migrates the value if it is older.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Versioned<T>(pub T);
+
+impl<T: Component + Serialize> Serialize for Versioned<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Versioned", 2)?;
+        state.serialize_field("version", &T::VERSION)?;
+        state.serialize_field("value", &self.0)?;
+        state.end()
+    }
+}
+
+impl<'de, T: Migrate> Deserialize<'de> for Versioned<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_struct(
+            "Versioned",
+            &["version", "value"],
+            VersionedVisitor::<T>(PhantomData),
+        )
+    }
+}
+
+struct VersionedVisitor<T>(PhantomData<T>);
+
+/// Decodes the `value` field once the `version` field is known.
+pub(crate) struct ValueSeed<T>(pub(crate) u32, pub(crate) PhantomData<T>);
+
+impl<'de, T: Migrate> DeserializeSeed<'de> for ValueSeed<T> {
+    type Value = T;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<T, D::Error> {
+        deserialize_versioned(self.0, deserializer)
+    }
+}
+
+impl<'de, T: Migrate> Visitor<'de> for VersionedVisitor<T> {
+    type Value = Versioned<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a versioned {}", std::any::type_name::<T>())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let version: u32 = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let value = seq
+            .next_element_seed(ValueSeed::<T>(version, PhantomData))?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        Ok(Versioned(value))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        // The value can only be decoded once its version is known, so `version` must
+        // come first (which is how `Versioned` serializes).
+        match map.next_key::<String>()?.as_deref() {
+            Some("version") => {}
+            Some(other) => {
+                return Err(de::Error::custom(format_args!(
+                    "expected field `version` before `{}`",
+                    other
+                )));
+            }
+            None => return Err(de::Error::missing_field("version")),
+        }
+        let version: u32 = map.next_value()?;
+        match map.next_key::<String>()?.as_deref() {
+            Some("value") => {}
+            Some(other) => return Err(de::Error::unknown_field(other, &["value"])),
+            None => return Err(de::Error::missing_field("value")),
+        }
+        let value = map.next_value_seed(ValueSeed::<T>(version, PhantomData))?;
+        Ok(Versioned(value))
+    }
+}
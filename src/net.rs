@@ -0,0 +1,239 @@
+//! Networking helpers: entity authority for client-side prediction.
+//!
+//! `Authority` records which peer simulates an entity. Alongside it every entity carries
+//! exactly one of the `Owned` / `Remote` marker components, derived from the owner and
+//! the local peer, so systems filter with the regular query syntax:
+//! - prediction systems use `All=[Owned]` and only resimulate what this peer owns,
+//! - interpolation systems use `All=[Remote]` (or `None=[Owned]`) for everything else.
+//!
+//! Keep the markers in sync through `set_authority` / `clear_authority`, and call
+//! `refresh_ownership` when the local peer changes. All three write through the storages,
+//! so authority changes are rolled back with the rest of the tick.
+//!
+//! Delta packets name components by id, which depends on registration order. Peers
+//! exchange a `Handshake` when connecting and refuse the connection when
+//! `Handshake::validate` fails, instead of decoding values into the wrong types.
+
+use crate::component::ComponentId;
+use crate::ecs::Ecs;
+use crate::error::{Error, Result};
+use crate::storage::Storage;
+use crate::world::World;
+use decs_macros::Component;
+
+/// Identifies a peer (client or server) in a session.
+pub type PeerId = u32;
+
+/// The peer that simulates an entity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Component)]
+pub struct Authority {
+    pub owner: PeerId,
+}
+
+/// Marker: the entity's `Authority` is the local peer.
+#[derive(Clone, Debug, PartialEq, Eq, Component)]
+pub struct Owned;
+
+/// Marker: the entity's `Authority` is another peer.
+#[derive(Clone, Debug, PartialEq, Eq, Component)]
+pub struct Remote;
+
+/// Gives `owner` authority over the entity at `index`, setting `Owned` or `Remote`
+/// according to `local`.
+pub fn set_authority(world: &mut World, index: u32, owner: PeerId, local: PeerId) {
+    let frame = world.frame();
+    world
+        .get_storage_mut::<Authority>()
+        .set(&frame, index, Authority { owner });
+    apply_markers(world, index, owner == local);
+}
+
+/// Removes `Authority` and both markers from the entity at `index`.
+pub fn clear_authority(world: &mut World, index: u32) {
+    let frame = world.frame();
+    world.get_storage_mut::<Authority>().remove(&frame, index);
+    world.get_storage_mut::<Owned>().remove(&frame, index);
+    world.get_storage_mut::<Remote>().remove(&frame, index);
+}
+
+/// Returns whether the local peer owns the entity at `index`.
+pub fn is_owned(world: &mut World, index: u32) -> bool {
+    world.get_storage_mut::<Owned>().get(index).is_some()
+}
+
+/// Re-derives `Owned` / `Remote` for every entity with `Authority`, e.g. after the
+/// local peer id was assigned or changed. Only entities whose marker flips are written.
+pub fn refresh_ownership(world: &mut World, local: PeerId) {
+    let authority: *const Storage<Authority> = world.get_storage::<Authority>();
+    let authority = unsafe { &*authority };
+    let mut cursor = authority.cursor();
+    while let Some(index) = cursor.next(authority) {
+        let owned = authority.get(index).unwrap().owner == local;
+        if is_owned(world, index) != owned {
+            apply_markers(world, index, owned);
+        }
+    }
+}
+
+fn apply_markers(world: &mut World, index: u32, owned: bool) {
+    let frame = world.frame();
+    if owned {
+        world.get_storage_mut::<Remote>().remove(&frame, index);
+        if world.get_storage_mut::<Owned>().get(index).is_none() {
+            world.get_storage_mut::<Owned>().set(&frame, index, Owned);
+        }
+    } else {
+        world.get_storage_mut::<Owned>().remove(&frame, index);
+        if world.get_storage_mut::<Remote>().get(index).is_none() {
+            world.get_storage_mut::<Remote>().set(&frame, index, Remote);
+        }
+    }
+}
+
+/// A registered component as announced in a `Handshake`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegisteredComponent {
+    pub id: ComponentId,
+    pub name: String,
+    /// `Component::VERSION`.
+    pub version: u32,
+    /// `Component::SCHEMA_HASH`.
+    pub schema_hash: u64,
+}
+
+/// The component registry of a peer, sent when connecting; see `validate`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Handshake {
+    /// In id order.
+    pub components: Vec<RegisteredComponent>,
+}
+
+/// A component on which two handshakes disagree; `local`/`remote` are None where the
+/// peer has nothing registered under the id.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegistryMismatch {
+    pub id: ComponentId,
+    pub local: Option<RegisteredComponent>,
+    pub remote: Option<RegisteredComponent>,
+}
+
+impl Handshake {
+    /// The registry of this process (`Ecs::registered`).
+    pub fn local() -> Self {
+        Self {
+            components: Ecs::registered()
+                .map(|(id, metadata)| RegisteredComponent {
+                    id,
+                    name: metadata.name.to_owned(),
+                    version: Ecs::version(id).unwrap_or(0),
+                    schema_hash: Ecs::schema_hash(id).unwrap_or(0),
+                })
+                .collect(),
+        }
+    }
+
+    /// The components registered differently on the two peers, in id order.
+    pub fn mismatches(&self, remote: &Handshake) -> Vec<RegistryMismatch> {
+        let find =
+            |handshake: &Handshake, id| handshake.components.iter().find(|c| c.id == id).cloned();
+        let mut ids: Vec<_> = self
+            .components
+            .iter()
+            .chain(&remote.components)
+            .map(|c| c.id)
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids.into_iter()
+            .filter_map(|id| {
+                let (local, remote) = (find(self, id), find(remote, id));
+                (local != remote).then_some(RegistryMismatch { id, local, remote })
+            })
+            .collect()
+    }
+
+    /// Fails with `Error::RegistryMismatch` naming the first component the two peers
+    /// registered differently, if any.
+    pub fn validate(&self, remote: &Handshake) -> Result<()> {
+        match self.mismatches(remote).first() {
+            Some(mismatch) => Err(Error::RegistryMismatch { id: mismatch.id }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// How a value differs between the two worlds given to `diff_worlds`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DivergenceKind {
+    OnlyInA,
+    OnlyInB,
+    /// Present in both with values that differ (`#[component(diff)]` types only).
+    ValueMismatch,
+}
+
+/// One divergent component value. `a`/`b` hold the formatted values for components
+/// deriving `#[component(diff)]` and None otherwise or when absent.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    pub component: &'static str,
+    pub index: u32,
+    pub kind: DivergenceKind,
+    pub a: Option<String>,
+    pub b: Option<String>,
+}
+
+/// Result of `diff_worlds`, in component id then index order.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DesyncReport {
+    pub divergences: Vec<Divergence>,
+    /// True when the divergence limit was reached, so later divergences may be missing.
+    pub truncated: bool,
+}
+
+impl DesyncReport {
+    pub fn is_in_sync(&self) -> bool {
+        self.divergences.is_empty()
+    }
+}
+
+/// Hash of the state `diff_worlds` compares: component presence everywhere and values
+/// of `#[component(diff)]` types. Peers exchange it every tick to detect desyncs
+/// cheaply and only run `diff_worlds` when it differs.
+pub fn world_checksum(world: &World) -> u64 {
+    let mut hash = crate::rng::FNV_OFFSET;
+    for id in 0..256 {
+        let Some(storage) = world.get_storage_by_id(id) else {
+            continue;
+        };
+        // Empty storages hash like missing ones, as diff_worlds treats them.
+        let checksum = storage.checksum();
+        if checksum != crate::rng::FNV_OFFSET {
+            hash = crate::rng::fnv1a(hash, &id.to_le_bytes());
+            hash = crate::rng::fnv1a(hash, &checksum.to_le_bytes());
+        }
+    }
+    hash
+}
+
+/// `diff_worlds_limit` with the first 16 divergences.
+pub fn diff_worlds(a: &World, b: &World) -> DesyncReport {
+    diff_worlds_limit(a, b, 16)
+}
+
+/// Compares two worlds storage by storage, for tracking down non-determinism between
+/// peers. Presence is compared for every component; values only for components that
+/// derive `#[component(diff)]`. Stops after `limit` divergences.
+pub fn diff_worlds_limit(a: &World, b: &World, limit: usize) -> DesyncReport {
+    let mut report = DesyncReport::default();
+    for id in 0..256 {
+        match (a.get_storage_by_id(id), b.get_storage_by_id(id)) {
+            (Some(sa), sb) => sa.diff_into(sb, true, limit, &mut report.divergences),
+            (None, Some(sb)) => sb.diff_into(None, false, limit, &mut report.divergences),
+            (None, None) => {}
+        }
+    }
+    report.truncated = report.divergences.len() >= limit;
+    report
+}
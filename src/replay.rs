@@ -0,0 +1,160 @@
+//! Recording of out-of-band writes for replays.
+//!
+//! A replay built from the input log alone misses every change injected from outside
+//! the simulation: entities a client creates from network packets, editor or console
+//! commands, debug tools. Queued through `World::commands()` outside a tick, those
+//! changes are applied at the next `CommandsFlushGroup` flush, and while a world is
+//! recording (`World::start_replay_recording`) the flush appends each
+//! `Commands::insert_component` / `remove_component` it applies to a `ReplayLog`,
+//! stamped with the tick it took effect in.
+//!
+//! The log's header keeps the seed of the recording world (`World::new_with_seed`), and
+//! `ReplayLog::new_world` creates the replaying world with it. `ReplayLog::inject`
+//! queues the writes of one tick on another world (or on the same one after a rollback)
+//! so they are applied at the same point of that tick:
+//!
+//! ```ignore
+//! world.start_replay_recording();
+//! // ... run the session, queueing network spawns through world.commands() ...
+//! let log = world.take_replay_log().unwrap();
+//!
+//! let mut replay = log.new_world();
+//! while replay.current_tick() != end {
+//!     let tick = replay.current_tick().next();
+//!     feed_inputs(&mut replay, tick);
+//!     log.inject(&mut replay, tick);
+//!     replay.run();
+//! }
+//! ```
+//!
+//! Closures queued with `Commands::push` can't be applied twice and are not recorded,
+//! nor are commands issued by systems, which the replay issues again by itself.
+
+use crate::component::ComponentId;
+use crate::frame::Frame;
+use crate::tick::Tick;
+use crate::world::{StorageTable, World};
+use std::sync::Arc;
+
+/// Applies a recorded write to a world's storages.
+pub(crate) type WriteFn = Arc<dyn Fn(&mut StorageTable, &Frame)>;
+
+/// Kind of a recorded write.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WriteKind {
+    Insert,
+    Remove,
+}
+
+/// One recorded `Commands` write.
+#[derive(Clone)]
+pub struct RecordedWrite {
+    /// Tick the write was applied in.
+    pub tick: Tick,
+    pub component: ComponentId,
+    pub index: u32,
+    pub kind: WriteKind,
+    write: WriteFn,
+}
+
+impl RecordedWrite {
+    pub(crate) fn new(
+        tick: Tick,
+        component: ComponentId,
+        index: u32,
+        kind: WriteKind,
+        write: WriteFn,
+    ) -> Self {
+        Self {
+            tick,
+            component,
+            index,
+            kind,
+            write,
+        }
+    }
+
+    /// Applies the write to `storages` in `frame`'s tick.
+    pub(crate) fn apply(&self, storages: &mut StorageTable, frame: &Frame) {
+        (self.write)(storages, frame)
+    }
+}
+
+impl std::fmt::Debug for RecordedWrite {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RecordedWrite")
+            .field("tick", &self.tick)
+            .field("component", &self.component)
+            .field("index", &self.index)
+            .field("kind", &self.kind)
+            .finish()
+    }
+}
+
+/// Out-of-band writes in the order they were applied, headed with the seed of the
+/// recording world; see `decs::replay`.
+#[derive(Clone, Debug, Default)]
+pub struct ReplayLog {
+    seed: Option<u64>,
+    writes: Vec<RecordedWrite>,
+}
+
+impl ReplayLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// An empty log recorded by a world created with `World::new_with_seed(seed)`.
+    pub fn with_seed(seed: Option<u64>) -> Self {
+        Self {
+            seed,
+            writes: Vec::new(),
+        }
+    }
+
+    /// Seed of the recording world, if it was created with `World::new_with_seed`.
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// Creates a world to replay the log on: seeded like the recording world, so its
+    /// `DeterministicRng` draws the same numbers. Systems still have to be added.
+    pub fn new_world(&self) -> World {
+        World::new_with_recorded_seed(self.seed)
+    }
+
+    pub(crate) fn push(&mut self, write: RecordedWrite) {
+        self.writes.push(write);
+    }
+
+    pub fn len(&self) -> usize {
+        self.writes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.writes.is_empty()
+    }
+
+    /// Every recorded write, oldest first.
+    pub fn writes(&self) -> &[RecordedWrite] {
+        &self.writes
+    }
+
+    /// The writes applied in `tick`, in order.
+    pub fn writes_at(&self, tick: Tick) -> impl Iterator<Item = &RecordedWrite> {
+        self.writes.iter().filter(move |w| w.tick == tick)
+    }
+
+    /// Queues the writes applied in `tick` on `world` as commands from outside the
+    /// simulation, so the next flush applies them ahead of the systems' commands, as
+    /// when they were recorded. Call it before running `tick`. Injected writes are not
+    /// recorded again.
+    pub fn inject(&self, world: &mut World, tick: Tick) {
+        let commands = world.commands();
+        let storages = world.storage_table();
+        for write in self.writes_at(tick) {
+            let write = write.clone();
+            commands.push(move |frame| write.apply(unsafe { &mut *storages }, frame));
+        }
+    }
+}
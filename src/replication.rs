@@ -0,0 +1,342 @@
+//! Replication table and delta encoder for network snapshots.
+//!
+//! Components declared `#[component(replicated)]` are listed once in a
+//! `ReplicationTable`, which keeps per component id how to encode its values (through a
+//! `SnapshotCodec`, after `Component::QUANTIZE`) and its `priority`. A `DeltaEncoder`
+//! turns the table into packets: it encodes the values written since it last sent each
+//! component, highest priority first, until the packet's byte budget is spent. What does
+//! not fit waits for a later packet, so low-priority components give way to important
+//! ones on a congested link.
+//!
+//! ```ignore
+//! #[derive(Clone, Component, Serialize)]
+//! #[component(replicated, priority = 10, quantize = round_position)]
+//! struct Position { x: f32, y: f32 }
+//!
+//! let mut table = ReplicationTable::new();
+//! table.add::<Position>().add::<Health>();
+//! assert!(table.unlisted().is_empty());
+//! let mut encoder = DeltaEncoder::new(table);
+//! let packet = encoder.encode(&Postcard, &world, 1200)?;
+//! ```
+//!
+//! Encoding needs `T: Serialize`, which the registry doesn't know about, so the table is
+//! filled type by type; `unlisted` reports the replicated components missing from it.
+//! Packets carry written values only, removals are not detected.
+//!
+//! Components with a setter enum (`#[component(field_mask)]`, see `decs::fields`) can be
+//! added with `add_fields` instead: values of which only some fields were written with
+//! `Storage::set_field` are then sent as one encoded setter per changed field.
+
+use crate::codec::SnapshotCodec;
+use crate::component::{Component, ComponentId};
+use crate::ecs::Ecs;
+use crate::fields::FieldSetter;
+use crate::tick::Tick;
+use crate::world::World;
+use serde::Serialize;
+
+/// Encodes the values of one component type stamped after a change tick, passing each
+/// index, the field if only one field is encoded, and the bytes to the callback.
+type EncodeChanged<C> = fn(
+    &C,
+    &World,
+    Tick,
+    &mut dyn FnMut(u32, Option<u32>, Vec<u8>),
+) -> Result<(), <C as SnapshotCodec>::Error>;
+
+/// One component of a `ReplicationTable`.
+pub struct Replicated<C: SnapshotCodec> {
+    pub id: ComponentId,
+    pub name: &'static str,
+    /// `ComponentMetadata::priority`.
+    pub priority: u8,
+    /// Whether values are sent through `Component::QUANTIZE`.
+    pub quantized: bool,
+    /// Field names of the setter enum the component was added with (`add_fields`);
+    /// empty if its values are always sent whole.
+    pub fields: &'static [&'static str],
+    encode_changed: EncodeChanged<C>,
+}
+
+impl<C: SnapshotCodec> Replicated<C> {
+    /// Calls `f` with the index and encoded (quantized) value of every value of `world`
+    /// stamped after change tick `since`, in index order; every value if the component
+    /// opts out of change detection.
+    ///
+    /// For components added with `add_fields`, a value of which only some fields changed
+    /// is passed as one encoded setter per changed field instead, with the field's
+    /// index in `fields`; whole values come with None.
+    pub fn encode_changed(
+        &self,
+        codec: &C,
+        world: &World,
+        since: Tick,
+        mut f: impl FnMut(u32, Option<u32>, Vec<u8>),
+    ) -> Result<(), C::Error> {
+        (self.encode_changed)(codec, world, since, &mut f)
+    }
+}
+
+fn encode_changed<T, C>(
+    codec: &C,
+    world: &World,
+    since: Tick,
+    f: &mut dyn FnMut(u32, Option<u32>, Vec<u8>),
+) -> Result<(), C::Error>
+where
+    T: Component + Serialize,
+    C: SnapshotCodec,
+{
+    let Some(storage) = world.existing_storage::<T>() else {
+        return Ok(());
+    };
+    let mut result = Ok(());
+    unsafe { &*storage }.for_each_changed_since(since, |index, value| {
+        if result.is_err() {
+            return;
+        }
+        let bytes = match T::QUANTIZE {
+            Some(quantize) => codec.encode(&quantize(value)),
+            None => codec.encode(value),
+        };
+        match bytes {
+            Ok(bytes) => f(index, None, bytes),
+            Err(err) => result = Err(err),
+        }
+    });
+    result
+}
+
+fn encode_changed_fields<T, F, C>(
+    codec: &C,
+    world: &World,
+    since: Tick,
+    f: &mut dyn FnMut(u32, Option<u32>, Vec<u8>),
+) -> Result<(), C::Error>
+where
+    T: Component + Serialize,
+    F: FieldSetter<T> + Serialize,
+    C: SnapshotCodec,
+{
+    let Some(storage) = world.existing_storage::<T>() else {
+        return Ok(());
+    };
+    let storage = unsafe { &*storage };
+    let mut result = Ok(());
+    storage.for_each_changed_since(since, |index, value| {
+        if result.is_err() {
+            return;
+        }
+        let quantized = T::QUANTIZE.map(|quantize| quantize(value));
+        let value = quantized.as_ref().unwrap_or(value);
+        let mut changed = storage.changed_fields_since::<F>(index, since);
+        if changed == F::ALL {
+            match codec.encode(value) {
+                Ok(bytes) => f(index, None, bytes),
+                Err(err) => result = Err(err),
+            }
+            return;
+        }
+        while changed != 0 {
+            let field = changed.trailing_zeros();
+            changed &= changed - 1;
+            let setter = F::read(value, field).expect("field index within F::NAMES");
+            match codec.encode(&setter) {
+                Ok(bytes) => f(index, Some(field), bytes),
+                Err(err) => {
+                    result = Err(err);
+                    return;
+                }
+            }
+        }
+    });
+    result
+}
+
+/// The replicated components with their encoder, quantizer and priority, by priority
+/// (highest first) and then by id.
+pub struct ReplicationTable<C: SnapshotCodec> {
+    entries: Vec<Replicated<C>>,
+}
+
+impl<C: SnapshotCodec> ReplicationTable<C> {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Adds the registered component `T`; adding it again does nothing.
+    ///
+    /// # Panics
+    /// Panics if `T` is not declared `#[component(replicated)]`.
+    pub fn add<T: Component + Serialize>(&mut self) -> &mut Self {
+        self.insert::<T>(&[], encode_changed::<T, C>)
+    }
+
+    /// Adds the registered component `T` like `add`, sending the fields written with
+    /// `Storage::set_field` through its setter enum `F` when not all of them changed.
+    /// Adding `T` again does nothing.
+    ///
+    /// # Panics
+    /// Panics if `T` is not declared `#[component(replicated)]`.
+    pub fn add_fields<T, F>(&mut self) -> &mut Self
+    where
+        T: Component + Serialize,
+        F: FieldSetter<T> + Serialize,
+    {
+        self.insert::<T>(F::NAMES, encode_changed_fields::<T, F, C>)
+    }
+
+    fn insert<T: Component>(
+        &mut self,
+        fields: &'static [&'static str],
+        encode_changed: EncodeChanged<C>,
+    ) -> &mut Self {
+        assert!(
+            T::METADATA.replicated,
+            "{} is not #[component(replicated)]",
+            std::any::type_name::<T>()
+        );
+        if self.get(T::id()).is_none() {
+            let priority = T::METADATA.priority;
+            let at = self.entries.partition_point(|e| {
+                e.priority > priority || (e.priority == priority && e.id < T::id())
+            });
+            self.entries.insert(
+                at,
+                Replicated {
+                    id: T::id(),
+                    name: std::any::type_name::<T>(),
+                    priority,
+                    quantized: T::QUANTIZE.is_some(),
+                    fields,
+                    encode_changed,
+                },
+            );
+        }
+        self
+    }
+
+    /// Returns the entry of component `id`.
+    pub fn get(&self, id: ComponentId) -> Option<&Replicated<C>> {
+        self.entries.iter().find(|e| e.id == id)
+    }
+
+    /// Iterates the entries in the order packets are filled.
+    pub fn iter(&self) -> impl Iterator<Item = &Replicated<C>> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Ids of the components registered as replicated that were not added.
+    pub fn unlisted(&self) -> Vec<ComponentId> {
+        Ecs::registered()
+            .filter(|(id, metadata)| metadata.replicated && self.get(*id).is_none())
+            .map(|(id, _)| id)
+            .collect()
+    }
+}
+
+impl<C: SnapshotCodec> Default for ReplicationTable<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One encoded value of a `DeltaPacket`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReplicatedValue {
+    pub component: ComponentId,
+    pub index: u32,
+    /// The field `bytes` encodes a setter of, for components added with `add_fields`;
+    /// None when they encode the whole value.
+    pub field: Option<u32>,
+    pub bytes: Vec<u8>,
+}
+
+/// Output of `DeltaEncoder::encode`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DeltaPacket {
+    /// Encoded values, grouped by component in table order and by index within one.
+    pub values: Vec<ReplicatedValue>,
+    /// Total length of the values' bytes, which the budget limits.
+    pub size: usize,
+    /// Components with changes that did not fit; the next packet tries them again.
+    pub deferred: Vec<ComponentId>,
+}
+
+/// Builds bandwidth-limited delta packets from a `ReplicationTable`.
+///
+/// The encoder remembers for each component the change tick it was last sent at, so a
+/// packet holds the values written since then, including values restored by a
+/// rollback. A component's changes go into a packet whole or not at all.
+pub struct DeltaEncoder<C: SnapshotCodec> {
+    table: ReplicationTable<C>,
+    /// Change tick each table entry was last sent at, in table order.
+    sent: Vec<Tick>,
+}
+
+impl<C: SnapshotCodec> DeltaEncoder<C> {
+    /// An encoder whose first packet sends every value of the table's components.
+    pub fn new(table: ReplicationTable<C>) -> Self {
+        let sent = vec![Tick(0); table.len()];
+        Self { table, sent }
+    }
+
+    pub fn table(&self) -> &ReplicationTable<C> {
+        &self.table
+    }
+
+    /// Encodes the changes of `world` since the previous packet into at most `budget`
+    /// bytes of values, highest priority first. Components whose changes don't fit are
+    /// deferred, except that the first component with changes always goes in, so one
+    /// larger than the budget can't stall the encoder. On error no component is marked
+    /// sent.
+    pub fn encode(
+        &mut self,
+        codec: &C,
+        world: &World,
+        budget: usize,
+    ) -> Result<DeltaPacket, C::Error> {
+        let now = world.change_tick();
+        let mut packet = DeltaPacket::default();
+        let mut sent = vec![false; self.sent.len()];
+        for (i, entry) in self.table.entries.iter().enumerate() {
+            let mut values = Vec::new();
+            let mut size = 0;
+            entry.encode_changed(codec, world, self.sent[i], |index, field, bytes| {
+                size += bytes.len();
+                values.push(ReplicatedValue {
+                    component: entry.id,
+                    index,
+                    field,
+                    bytes,
+                });
+            })?;
+            if values.is_empty() || packet.values.is_empty() || packet.size + size <= budget {
+                packet.values.append(&mut values);
+                packet.size += size;
+                sent[i] = true;
+            } else {
+                packet.deferred.push(entry.id);
+            }
+        }
+        for (since, sent) in self.sent.iter_mut().zip(sent) {
+            if sent {
+                *since = now;
+            }
+        }
+        // Writes after the packet stamp something newer than what it sent.
+        world.advance_change_tick();
+        Ok(packet)
+    }
+}
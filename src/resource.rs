@@ -0,0 +1,119 @@
+//! World resources: singleton values stored in their component's storage.
+//!
+//! A resource is a `Component` declared with `#[component(resource)]` and kept at
+//! `RESOURCE_INDEX` of its own `Storage<T>`, so it gets the same per-tick rollback
+//! snapshots as any other component without a separate history. That storage is the
+//! resource's own table: it never holds entity components, so entity 0 keeps its
+//! index, no cleanup system is scheduled for the type and moving or duplicating an
+//! entity leaves resources alone. Don't use a resource type as an entity component.
+//!
+//! Use `World::insert_resource`, `World::resource` and `World::resource_mut` from the
+//! outside. Systems keep the `*mut Storage<T>` from `World::get_storage` and call
+//! `get`/`get_mut(frame, RESOURCE_INDEX)` on it.
+
+use crate::frame::Frame;
+use crate::tick::Tick;
+use decs_macros::Component;
+
+/// Slot of a resource type's storage holding the resource value.
+pub const RESOURCE_INDEX: u32 = 0;
+
+/// Simulation clock resource, advanced by the world at the start of every tick and
+/// restored by rollback with the rest of the storage data.
+///
+/// Systems should read time from here (or from `Frame`) instead of wall clocks, which
+/// desync when ticks are resimulated. Insert it with `World::insert_resource` after
+/// registering it; worlds without the resource skip the update.
+#[derive(Clone, Debug, PartialEq, Component)]
+#[component(resource)]
+pub struct SimTime {
+    /// Tick the clock was last advanced to.
+    pub tick: Tick,
+    /// Seconds simulated so far: the sum of `fixed_dt` over every advanced tick.
+    pub elapsed: f64,
+    /// Step length in seconds of the last advanced tick.
+    pub fixed_dt: f32,
+}
+
+impl SimTime {
+    pub fn new(fixed_dt: f32) -> Self {
+        Self {
+            tick: Tick(0),
+            elapsed: 0.0,
+            fixed_dt,
+        }
+    }
+
+    /// Moves the clock to `tick`, accumulating one step of `fixed_dt`.
+    pub fn advance(&mut self, tick: Tick, fixed_dt: f32) {
+        self.tick = tick;
+        self.fixed_dt = fixed_dt;
+        self.elapsed += fixed_dt as f64;
+    }
+}
+
+impl Default for SimTime {
+    fn default() -> Self {
+        Self::new(Frame::DEFAULT_FIXED_DT)
+    }
+}
+
+/// Rollback netcode tuning, read by `World::resimulate` and by `EndOfTickSystem`'s
+/// history trimming, so input delay and rollback window are set per session (or per
+/// player) as data instead of constants.
+///
+/// Without the resource, `World::history_depth` applies and `resimulate` only checks
+/// the retained history. Like any resource it is rolled back with the world, so change
+/// it between ticks the peers agree on.
+#[derive(Clone, Debug, PartialEq, Eq, Component)]
+#[component(resource)]
+pub struct RollbackConfig {
+    /// Ticks between sampling a local input and the tick it is applied in; see
+    /// `input_tick`.
+    pub input_delay: u32,
+    /// How many ticks `World::resimulate` may go back; each storage keeps the rollback
+    /// history to reach that far (up to `MAX_HISTORY` snapshots).
+    pub max_rollback_frames: u32,
+    /// History is trimmed on ticks that are a multiple of this, dropping the snapshots of
+    /// up to `keyframe_interval` ticks at once. 1 trims every tick; larger intervals
+    /// spend less end-of-tick time for up to `keyframe_interval - 1` extra snapshots.
+    pub keyframe_interval: u32,
+}
+
+impl RollbackConfig {
+    pub fn new(input_delay: u32, max_rollback_frames: u32) -> Self {
+        Self {
+            input_delay,
+            max_rollback_frames,
+            keyframe_interval: 1,
+        }
+    }
+
+    pub fn with_keyframe_interval(mut self, interval: u32) -> Self {
+        assert!(interval > 0, "keyframe interval must be positive");
+        self.keyframe_interval = interval;
+        self
+    }
+
+    /// The tick an input sampled while `current` is the last run tick is applied in.
+    pub fn input_tick(&self, current: Tick) -> Tick {
+        current.wrapping_add(1 + self.input_delay)
+    }
+
+    /// The oldest tick `World::resimulate` can roll back to from `current`.
+    pub fn oldest_rollback_tick(&self, current: Tick) -> Tick {
+        current.wrapping_sub(self.max_rollback_frames)
+    }
+
+    /// Whether history is trimmed at the end of `tick`.
+    pub fn is_keyframe(&self, tick: Tick) -> bool {
+        tick.0.is_multiple_of(self.keyframe_interval.max(1))
+    }
+}
+
+impl Default for RollbackConfig {
+    /// Two ticks of input delay and an eight-tick rollback window.
+    fn default() -> Self {
+        Self::new(2, 8)
+    }
+}
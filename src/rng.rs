@@ -0,0 +1,119 @@
+use crate::frame::Frame;
+use crate::system::System;
+use crate::tick::Tick;
+use decs_macros::Component;
+
+/// SplitMix64 generator: a single `u64` of state, cheap to copy and to snapshot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RngStream {
+    state: u64,
+}
+
+impl RngStream {
+    pub const fn new(state: u64) -> Self {
+        Self { state }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        mix(self.state)
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    /// Uniform float in `[0, 1)`.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 * (1.0 / (1u64 << 24) as f32)
+    }
+
+    /// Uniform integer in `[0, bound)`; `bound` must be non-zero.
+    pub fn below(&mut self, bound: u32) -> u32 {
+        assert!(bound != 0, "bound must be non-zero");
+        ((self.next_u32() as u64 * bound as u64) >> 32) as u32
+    }
+}
+
+/// Seeded gameplay randomness that replays identically after resimulation.
+///
+/// Insert it as a resource (`World::insert_resource`) after registering it like any
+/// other component, or let `World::new_with_seed` do so. There are two ways to draw
+/// from it:
+/// - `next_*` advance the shared state; the resource lives in a storage, so that state
+///   is snapshotted per tick and restored by rollback like component data.
+/// - `stream`/`stream_for` derive an independent `RngStream` from (seed, tick, system
+///   id) without touching the resource, so a system's draws don't depend on how many
+///   numbers other systems took before it in the same tick.
+#[derive(Clone, Debug, PartialEq, Eq, Component)]
+#[component(resource)]
+pub struct DeterministicRng {
+    seed: u64,
+    stream: RngStream,
+}
+
+impl DeterministicRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            stream: RngStream::new(seed),
+        }
+    }
+
+    /// The world seed this generator was created with.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.stream.next_u64()
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        self.stream.next_u32()
+    }
+
+    pub fn next_f32(&mut self) -> f32 {
+        self.stream.next_f32()
+    }
+
+    pub fn below(&mut self, bound: u32) -> u32 {
+        self.stream.below(bound)
+    }
+
+    /// Returns the stream for `system_id` at `tick`. The same inputs always give the
+    /// same sequence.
+    pub fn stream(&self, tick: Tick, system_id: u64) -> RngStream {
+        let tick_key = mix(tick.0 as u64 ^ 0x5851_F42D_4C95_7F2D);
+        RngStream::new(mix(self.seed ^ tick_key) ^ mix(system_id))
+    }
+
+    /// `stream` keyed by the frame's tick and the system's name.
+    pub fn stream_for(&self, frame: &Frame, system: &dyn System) -> RngStream {
+        self.stream(frame.current_tick, system_id(system.name()))
+    }
+}
+
+/// Stable id for a system name (FNV-1a), usable as the `system_id` of
+/// `DeterministicRng::stream`.
+pub fn system_id(name: &str) -> u64 {
+    fnv1a(FNV_OFFSET, name.as_bytes())
+}
+
+/// FNV-1a offset basis, the starting `hash` of `fnv1a`.
+pub(crate) const FNV_OFFSET: u64 = 0xCBF2_9CE4_8422_2325;
+
+/// Folds `bytes` into an FNV-1a `hash`.
+pub(crate) fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for &byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01B3);
+    }
+    hash
+}
+
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
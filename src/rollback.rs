@@ -1,5 +1,9 @@
 use crate::tick::Tick;
+use crate::component::Component;
+use crate::slot::Slot;
 use crate::arena::Arena;
+use crate::leak::{AllocKind, record_alloc, record_free};
+use std::alloc::{AllocError, Layout, handle_alloc_error};
 use std::collections::VecDeque;
 use std::mem::MaybeUninit;
 // Debug-only allocation counters removed: single-threaded environment doesn't need atomics
@@ -8,7 +12,245 @@ use std::mem::MaybeUninit;
 // arena reference by converting Box<Arena> into a raw pointer and then a
 // 'static reference that remains valid until we reconstruct and drop the Box in Drop.
 
-pub type VecQueue<T> = VecDeque<T>;
+/// Number of snapshots `VecQueue` keeps inline before spilling to the heap.
+pub const INLINE_HISTORY: usize = 4;
+
+/// Most past ticks a storage keeps rollback snapshots for.
+pub const MAX_HISTORY: usize = 64;
+
+/// FIFO of rollback snapshots, oldest first.
+///
+/// Storages keep their snapshots here by value, not boxed. The first `INLINE_HISTORY`
+/// entries are stored inline, so storages that rarely change never allocate a queue
+/// buffer; deeper histories live in the heap queue's buffer. Once it spills, newer
+/// entries go to the heap queue until the inline (older) ones have been popped, which
+/// keeps `inline ++ spill` ordered.
+pub struct VecQueue<T> {
+    inline: [Option<T>; INLINE_HISTORY],
+    inline_len: usize,
+    spill: VecDeque<T>,
+}
+
+impl<T> VecQueue<T> {
+    pub const fn new() -> Self {
+        Self {
+            inline: [const { None }; INLINE_HISTORY],
+            inline_len: 0,
+            spill: VecDeque::new(),
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.inline_len + self.spill.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns true if entries have moved to the heap queue.
+    #[inline]
+    pub fn spilled(&self) -> bool {
+        !self.spill.is_empty()
+    }
+
+    pub fn push_back(&mut self, value: T) {
+        if self.spill.is_empty() && self.inline_len < INLINE_HISTORY {
+            self.inline[self.inline_len] = Some(value);
+            self.inline_len += 1;
+        } else {
+            self.spill.push_back(value);
+        }
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.inline_len == 0 {
+            return self.spill.pop_front();
+        }
+        let front = self.inline[0].take();
+        self.inline[..self.inline_len].rotate_left(1);
+        self.inline_len -= 1;
+        front
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        if let Some(back) = self.spill.pop_back() {
+            return Some(back);
+        }
+        if self.inline_len == 0 {
+            return None;
+        }
+        self.inline_len -= 1;
+        self.inline[self.inline_len].take()
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        match self.spill.back() {
+            Some(back) => Some(back),
+            None => self.inline[..self.inline_len].last()?.as_ref(),
+        }
+    }
+
+    /// Frees the heap queue's spare capacity.
+    pub fn shrink_to_fit(&mut self) {
+        self.spill.shrink_to_fit();
+    }
+
+    /// Returns the entry at `index`, counting from the oldest.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index < self.inline_len {
+            self.inline[index].as_ref()
+        } else {
+            self.spill.get(index - self.inline_len)
+        }
+    }
+
+    /// Mutable variant of `get`.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index < self.inline_len {
+            self.inline[index].as_mut()
+        } else {
+            self.spill.get_mut(index - self.inline_len)
+        }
+    }
+
+    /// Iterates oldest to newest.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> {
+        self.inline[..self.inline_len]
+            .iter()
+            .flatten()
+            .chain(self.spill.iter())
+    }
+
+    /// Mutable variant of `iter`.
+    pub fn iter_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut T> {
+        self.inline[..self.inline_len]
+            .iter_mut()
+            .flatten()
+            .chain(self.spill.iter_mut())
+    }
+}
+
+impl<T> Default for VecQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// How a storage records old values for rollback.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum SnapshotPolicy {
+    /// Store each value's old state on its first change within a tick.
+    #[default]
+    PerItem,
+    /// Copy every value of a chunk on the chunk's first modification within a tick.
+    /// Cheaper when most of a chunk changes every tick.
+    WholeChunk,
+    /// Copy the whole chunk only when at least `min_fill` of its 64 slots are occupied,
+    /// falling back to `PerItem` for sparse chunks.
+    Adaptive { min_fill: u32 },
+}
+
+impl SnapshotPolicy {
+    /// Returns true if a chunk with `fill` occupied slots should be copied wholesale.
+    #[inline(always)]
+    pub fn snapshots_chunk(self, fill: u32) -> bool {
+        match self {
+            SnapshotPolicy::PerItem => false,
+            SnapshotPolicy::WholeChunk => fill != 0,
+            SnapshotPolicy::Adaptive { min_fill } => fill != 0 && fill >= min_fill,
+        }
+    }
+}
+
+/// Masks of one chunk of a packed snapshot; `key` is `(storage_idx << 6) | page_idx`.
+#[derive(Clone, Copy, Debug)]
+pub struct PackedChunk {
+    pub key: u16,
+    pub created_mask: u64,
+    pub changed_mask: u64,
+    pub removed_mask: u64,
+    pub snapshot_mask: u64,
+}
+
+impl PackedChunk {
+    /// Indices whose tick-1 value is stored.
+    #[inline(always)]
+    pub fn stored_mask(&self) -> u64 {
+        self.changed_mask | self.removed_mask | self.snapshot_mask
+    }
+}
+
+/// Old values of a history snapshot, packed by `RollbackStorage::set_packed` for
+/// storages with delta rollback (`Storage::enable_delta_rollback`).
+///
+/// Every stored value is kept as its XOR with the value the index had at the end of the
+/// snapshot's tick (zero bytes if it was absent by then), encoded by `encode_delta`.
+/// A value the tick barely touched shrinks to its bitmap and a few bytes, and the
+/// snapshot's pages and arena are freed.
+#[derive(Debug)]
+pub struct PackedRollback {
+    /// Every rollback chunk of the snapshot, sorted by key.
+    pub chunks: Vec<PackedChunk>,
+    /// Encoded values of each chunk's `stored_mask`, in chunk then index order.
+    pub bytes: Vec<u8>,
+}
+
+impl PackedRollback {
+    /// Returns the masks of the chunk at `storage_idx`/`page_idx`, if it recorded anything.
+    pub fn chunk(&self, storage_idx: u32, page_idx: u32) -> Option<&PackedChunk> {
+        let key = ((storage_idx << 6) | page_idx) as u16;
+        self.chunks
+            .binary_search_by_key(&key, |c| c.key)
+            .ok()
+            .map(|i| &self.chunks[i])
+    }
+
+    /// Heap bytes held by the packed snapshot.
+    pub fn heap_size(&self) -> usize {
+        self.chunks.capacity() * size_of::<PackedChunk>() + self.bytes.capacity()
+    }
+}
+
+/// Appends `value ^ base` to `out` as a bitmap of its nonzero bytes followed by those
+/// bytes; a missing `base` counts as zero bytes.
+pub fn encode_delta(value: &[u8], base: Option<&[u8]>, out: &mut Vec<u8>) {
+    debug_assert!(base.is_none_or(|b| b.len() == value.len()));
+    let bitmap_at = out.len();
+    out.resize(bitmap_at + value.len().div_ceil(8), 0);
+    for (i, &byte) in value.iter().enumerate() {
+        let delta = byte ^ base.map_or(0, |b| b[i]);
+        if delta != 0 {
+            out[bitmap_at + i / 8] |= 1 << (i % 8);
+            out.push(delta);
+        }
+    }
+}
+
+/// Reads one value written by `encode_delta` from the front of `input`, advancing it.
+/// `out` holds the base on entry and the decoded value on return.
+pub fn decode_delta(input: &mut &[u8], out: &mut [u8]) {
+    let (bitmap, mut rest) = input.split_at(out.len().div_ceil(8));
+    for (i, byte) in out.iter_mut().enumerate() {
+        if (bitmap[i / 8] >> (i % 8)) & 1 != 0 {
+            *byte ^= rest[0];
+            rest = &rest[1..];
+        }
+    }
+    *input = rest;
+}
+
+/// Bytes of `value`.
+///
+/// # Safety
+///
+/// `T` must have no padding bytes.
+#[inline(always)]
+unsafe fn value_bytes<T>(value: &T) -> &[u8] {
+    unsafe { std::slice::from_raw_parts(value as *const T as *const u8, size_of::<T>()) }
+}
 
 /// A hierarchical rollback storage structure for efficiently tracking changes from Storage<T>.
 ///
@@ -45,6 +287,55 @@ pub type VecQueue<T> = VecDeque<T>;
 /// # Important
 /// The invariants and mask semantics are based on the original Storage<T> at the current tick,
 /// not on RollbackStorage itself. RollbackStorage is a diff/snapshot structure.
+/// How a tick touched an index, as reported by `RollbackStorage::for_each_entry`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RollbackEntryKind {
+    /// No value at the start of the tick, one at its end.
+    Created,
+    /// A value at both ends of the tick, written in between.
+    Changed,
+    /// A value at the start of the tick, none at its end.
+    Removed,
+}
+
+/// One index touched in a snapshot's tick.
+#[derive(Debug)]
+pub struct RollbackEntry<'a, T> {
+    pub index: u32,
+    pub kind: RollbackEntryKind,
+    /// The value at the start of the tick; None for `Created` and in packed snapshots.
+    pub old: Option<&'a T>,
+}
+
+impl<T> Clone for RollbackEntry<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for RollbackEntry<'_, T> {}
+
+/// Calls `f` with each bit set in one of a chunk's (exclusive) `(created, changed,
+/// removed)` masks and how it was touched.
+fn visit_entries(
+    (created, changed, removed): (u64, u64, u64),
+    mut f: impl FnMut(u32, RollbackEntryKind),
+) {
+    let mut mask = created | changed | removed;
+    while mask != 0 {
+        let bit = mask.trailing_zeros();
+        mask &= mask - 1;
+        let kind = if (created >> bit) & 1 != 0 {
+            RollbackEntryKind::Created
+        } else if (removed >> bit) & 1 != 0 {
+            RollbackEntryKind::Removed
+        } else {
+            RollbackEntryKind::Changed
+        };
+        f(bit, kind);
+    }
+}
+
 #[repr(align(64))]
 pub struct RollbackStorage<T: Clone> {
     pub changed_mask: u64, // Set if any child has any change (creation, modification, or removal)
@@ -52,6 +343,9 @@ pub struct RollbackStorage<T: Clone> {
     pub data: [MaybeUninit<Box<RollbackPage<T>, &'static Arena>>; 64],
     pub generation_at_tick_start: u64,
     pub arena_box: Box<Arena>,
+    /// Set once the snapshot's values were packed; its pages are freed then, while
+    /// `changed_mask` still tells which storage indices it recorded.
+    pub packed: Option<Box<PackedRollback>>,
 }
 
 impl<T: Clone> RollbackStorage<T> {
@@ -61,6 +355,7 @@ impl<T: Clone> RollbackStorage<T> {
     }
     /// Creates a new empty RollbackStorage instance.
     pub fn new() -> Self {
+        record_alloc::<T>(AllocKind::RollbackSnapshot);
         let arena_box = Box::new(Arena::new());
         Self {
             changed_mask: 0,
@@ -68,24 +363,13 @@ impl<T: Clone> RollbackStorage<T> {
             data: unsafe { MaybeUninit::uninit().assume_init() },
             generation_at_tick_start: 0,
             arena_box,
+            packed: None,
         }
     }
 
     pub fn reset_for_tick(&mut self, tick: Tick) {
-        let mut mask = self.changed_mask;
-        while mask != 0 {
-            let start = mask.trailing_zeros() as usize;
-            let shifted = mask >> start;
-            let run_len = shifted.trailing_ones() as usize;
-
-            for i in start..start + run_len {
-                unsafe {
-                    self.data[i].assume_init_drop();
-                }
-            }
-
-            mask &= !((u64::MAX >> (64 - run_len)) << start);
-        }
+        self.drop_pages();
+        self.packed = None;
         self.changed_mask = 0;
         self.arena_box.reset();
         self.generation_at_tick_start = 0;
@@ -94,14 +378,21 @@ impl<T: Clone> RollbackStorage<T> {
 
     /// Creates a new RollbackStorage instance with the given tick.
     pub fn with_tick(tick: Tick) -> Self {
-        let arena_box = Box::new(Arena::new());
-        Self {
+        Self::try_with_tick(tick).unwrap_or_else(|_| handle_alloc_error(Layout::new::<Arena>()))
+    }
+
+    /// Fallible variant of `with_tick`, failing if the snapshot's arena cannot be allocated.
+    pub fn try_with_tick(tick: Tick) -> Result<Self, AllocError> {
+        let arena_box = Box::try_new(Arena::new())?;
+        record_alloc::<T>(AllocKind::RollbackSnapshot);
+        Ok(Self {
             changed_mask: 0,
             tick,
             data: unsafe { MaybeUninit::uninit().assume_init() },
             generation_at_tick_start: 0,
             arena_box,
-        }
+            packed: None,
+        })
     }
     /// Saves the current generation value for rollback (used for all storages).
     pub fn save_generation(&mut self, generation: u64) {
@@ -118,6 +409,195 @@ impl<T: Clone> RollbackStorage<T> {
         self.generation_at_tick_start = 0;
     }
 
+    /// Storage indices whose page is allocated: none once the snapshot is packed.
+    #[inline(always)]
+    fn page_mask(&self) -> u64 {
+        if self.packed.is_some() {
+            0
+        } else {
+            self.changed_mask
+        }
+    }
+
+    /// Drops every allocated page. Leaves `changed_mask` alone.
+    fn drop_pages(&mut self) {
+        // changed_mask set means the page exists; we're dropping Box<RollbackPage<T>>
+        // structures here, which drop the values their chunks store
+        let mut mask = self.page_mask();
+        while mask != 0 {
+            let start = mask.trailing_zeros() as usize;
+            let shifted = mask >> start;
+            let run_len = shifted.trailing_ones() as usize;
+
+            for i in start..start + run_len {
+                unsafe {
+                    self.data[i].assume_init_drop();
+                }
+            }
+
+            mask &= !((u64::MAX >> (64 - run_len)) << start);
+        }
+    }
+
+    /// Returns true if this snapshot's values were packed (see `PackedRollback`).
+    pub fn is_packed(&self) -> bool {
+        self.packed.is_some()
+    }
+
+    /// Returns true if the snapshot recorded `index` as created, changed or removed.
+    pub fn records(&self, index: u32) -> bool {
+        let chunk_idx = index & 63;
+        let page_idx = (index >> 6) & 63;
+        let storage_idx = index >> 12;
+        let mask = match &self.packed {
+            Some(packed) => packed
+                .chunk(storage_idx, page_idx)
+                .map(|c| c.created_mask | c.changed_mask | c.removed_mask),
+            None => self
+                .get_page(storage_idx)
+                .and_then(|page| page.get(page_idx))
+                .map(|c| c.created_mask | c.changed_mask | c.removed_mask),
+        };
+        mask.is_some_and(|mask| (mask >> chunk_idx) & 1 != 0)
+    }
+
+    /// Returns the old value this snapshot stores for `index` (changed, removed or
+    /// whole-chunk snapshotted), i.e. its value at the start of the snapshot's tick.
+    /// Packed snapshots return None.
+    pub fn stored(&self, index: u32) -> Option<&T> {
+        let chunk_idx = index & 63;
+        let chunk = self.get_page(index >> 12)?.get((index >> 6) & 63)?;
+        let stored = chunk.changed_mask | chunk.removed_mask | chunk.snapshot_mask;
+        if (stored >> chunk_idx) & 1 == 0 {
+            return None;
+        }
+        unsafe { Some(chunk.data[chunk_idx as usize].assume_init_ref()) }
+    }
+
+    /// Encodes the values this snapshot stores for `set_packed`. `base` returns the value
+    /// an index had at the end of this snapshot's tick, if it was present then.
+    ///
+    /// # Safety
+    ///
+    /// `T` must be `Copy` without padding bytes, so every byte of a value is initialized.
+    pub unsafe fn encode_packed<'b>(
+        &self,
+        mut base: impl FnMut(u32) -> Option<&'b T>,
+    ) -> PackedRollback
+    where
+        T: 'b,
+    {
+        let mut packed = PackedRollback {
+            chunks: Vec::new(),
+            bytes: Vec::new(),
+        };
+        let mut storage_mask = self.page_mask();
+        while storage_mask != 0 {
+            let storage_idx = storage_mask.trailing_zeros();
+            storage_mask &= storage_mask - 1;
+            let page = unsafe { self.data[storage_idx as usize].assume_init_ref() };
+            let mut page_mask = page.changed_mask;
+            while page_mask != 0 {
+                let page_idx = page_mask.trailing_zeros();
+                page_mask &= page_mask - 1;
+                let chunk = unsafe { page.data[page_idx as usize].assume_init_ref() };
+                let entry = PackedChunk {
+                    key: ((storage_idx << 6) | page_idx) as u16,
+                    created_mask: chunk.created_mask,
+                    changed_mask: chunk.changed_mask,
+                    removed_mask: chunk.removed_mask,
+                    snapshot_mask: chunk.snapshot_mask,
+                };
+                let mut stored = entry.stored_mask();
+                while stored != 0 {
+                    let i = stored.trailing_zeros();
+                    stored &= stored - 1;
+                    let index = (storage_idx << 12) | (page_idx << 6) | i;
+                    let ended_present = (chunk.removed_mask >> i) & 1 == 0;
+                    let base = if ended_present { base(index) } else { None };
+                    unsafe {
+                        encode_delta(
+                            value_bytes(chunk.data[i as usize].assume_init_ref()),
+                            base.map(|b| value_bytes(b)),
+                            &mut packed.bytes,
+                        );
+                    }
+                }
+                packed.chunks.push(entry);
+            }
+        }
+        packed.chunks.shrink_to_fit();
+        packed.bytes.shrink_to_fit();
+        packed
+    }
+
+    /// Replaces the pages of this (history) snapshot with `packed` from `encode_packed`
+    /// and frees its arena.
+    pub fn set_packed(&mut self, packed: PackedRollback) {
+        self.drop_pages();
+        self.arena_box.release();
+        self.packed = Some(Box::new(packed));
+    }
+
+    /// Decodes the values of a packed snapshot for `restore_packed`, in packed order.
+    /// `base` must return the values it returned to `encode_packed`.
+    ///
+    /// # Safety
+    ///
+    /// Same as `encode_packed`.
+    pub unsafe fn decode_packed<'b>(&self, mut base: impl FnMut(u32) -> Option<&'b T>) -> Vec<T>
+    where
+        T: 'b,
+    {
+        let packed = self.packed.as_ref().expect("snapshot is not packed");
+        let mut input = &packed.bytes[..];
+        let mut values = Vec::new();
+        for entry in &packed.chunks {
+            let mut stored = entry.stored_mask();
+            while stored != 0 {
+                let i = stored.trailing_zeros();
+                stored &= stored - 1;
+                let index = ((entry.key as u32) << 6) | i;
+                let mut value = MaybeUninit::<T>::zeroed();
+                if (entry.removed_mask >> i) & 1 == 0
+                    && let Some(base) = base(index)
+                {
+                    unsafe { std::ptr::copy_nonoverlapping(base, value.as_mut_ptr(), 1) };
+                }
+                let bytes = unsafe {
+                    std::slice::from_raw_parts_mut(value.as_mut_ptr() as *mut u8, size_of::<T>())
+                };
+                decode_delta(&mut input, bytes);
+                values.push(unsafe { value.assume_init() });
+            }
+        }
+        values
+    }
+
+    /// Rebuilds the pages of a packed snapshot from the values `decode_packed` returned.
+    pub fn restore_packed(&mut self, values: Vec<T>) {
+        let packed = self.packed.take().expect("snapshot is not packed");
+        let storage_mask = self.changed_mask;
+        self.changed_mask = 0;
+        let mut values = values.into_iter();
+        for entry in &packed.chunks {
+            let page = self.get_or_create_page((entry.key >> 6) as u32);
+            let chunk = page.get_or_create_chunk((entry.key & 63) as u32);
+            chunk.created_mask = entry.created_mask;
+            chunk.changed_mask = entry.changed_mask;
+            chunk.removed_mask = entry.removed_mask;
+            chunk.snapshot_mask = entry.snapshot_mask;
+            let mut stored = entry.stored_mask();
+            while stored != 0 {
+                let i = stored.trailing_zeros() as usize;
+                stored &= stored - 1;
+                chunk.data[i].write(values.next().expect("one value per stored index"));
+            }
+        }
+        debug_assert_eq!(self.changed_mask, storage_mask);
+        self.changed_mask = storage_mask;
+    }
+
     /// Returns the current tick.
     pub fn tick(&self) -> Tick {
         self.tick
@@ -229,11 +709,99 @@ impl<T: Clone> RollbackStorage<T> {
         true
     }
 
+    /// Calls `f` with every index this snapshot recorded as created, changed, removed
+    /// or whole-chunk snapshotted, i.e. every index that may differ from the previous tick.
+    pub fn for_each_touched(&self, mut f: impl FnMut(u32)) {
+        self.for_each_touched_with_presence(|index, _| f(index));
+    }
+
+    /// Like `for_each_touched`, also passing whether the index had a value at the start
+    /// of the snapshot's tick (i.e. an old value is stored for it).
+    pub fn for_each_touched_with_presence(&self, mut f: impl FnMut(u32, bool)) {
+        if let Some(packed) = &self.packed {
+            for chunk in &packed.chunks {
+                let stored = chunk.stored_mask();
+                let mut mask = chunk.created_mask | stored;
+                while mask != 0 {
+                    let bit = mask.trailing_zeros();
+                    f(((chunk.key as u32) << 6) | bit, (stored >> bit) & 1 != 0);
+                    mask &= mask - 1;
+                }
+            }
+            return;
+        }
+        let mut storage_mask = self.changed_mask;
+        while storage_mask != 0 {
+            let storage_idx = storage_mask.trailing_zeros();
+            storage_mask &= storage_mask - 1;
+            let page = unsafe { self.data[storage_idx as usize].assume_init_ref() };
+            let mut page_mask = page.changed_mask;
+            while page_mask != 0 {
+                let page_idx = page_mask.trailing_zeros();
+                page_mask &= page_mask - 1;
+                let chunk = unsafe { page.data[page_idx as usize].assume_init_ref() };
+                let stored = chunk.changed_mask | chunk.removed_mask | chunk.snapshot_mask;
+                let mut mask = chunk.created_mask | stored;
+                while mask != 0 {
+                    let bit = mask.trailing_zeros();
+                    f((storage_idx << 12) | (page_idx << 6) | bit, (stored >> bit) & 1 != 0);
+                    mask &= mask - 1;
+                }
+            }
+        }
+    }
+
+    /// Calls `f` with every index this snapshot recorded as created, changed or removed
+    /// in its tick, in index order, with the value it had at the start of the tick.
+    /// Indices created and removed again within the tick, and values only copied with
+    /// their chunk, are not reported. Packed snapshots report no old values.
+    pub fn for_each_entry(&self, mut f: impl FnMut(RollbackEntry<'_, T>)) {
+        if let Some(packed) = &self.packed {
+            for chunk in &packed.chunks {
+                let base = (chunk.key as u32) << 6;
+                let masks = (chunk.created_mask, chunk.changed_mask, chunk.removed_mask);
+                visit_entries(masks, |bit, kind| {
+                    f(RollbackEntry {
+                        index: base | bit,
+                        kind,
+                        old: None,
+                    })
+                });
+            }
+            return;
+        }
+        let mut storage_mask = self.changed_mask;
+        while storage_mask != 0 {
+            let storage_idx = storage_mask.trailing_zeros();
+            storage_mask &= storage_mask - 1;
+            let page = unsafe { self.data[storage_idx as usize].assume_init_ref() };
+            let mut page_mask = page.changed_mask;
+            while page_mask != 0 {
+                let page_idx = page_mask.trailing_zeros();
+                page_mask &= page_mask - 1;
+                let chunk = unsafe { page.data[page_idx as usize].assume_init_ref() };
+                let base = (storage_idx << 12) | (page_idx << 6);
+                let stored = (chunk.changed_mask | chunk.removed_mask | chunk.snapshot_mask)
+                    & !chunk.consumed_mask;
+                let masks = (chunk.created_mask, chunk.changed_mask, chunk.removed_mask);
+                visit_entries(masks, |bit, kind| {
+                    let old = ((stored >> bit) & 1 != 0)
+                        .then(|| unsafe { chunk.data[bit as usize].assume_init_ref() });
+                    f(RollbackEntry {
+                        index: base | bit,
+                        kind,
+                        old,
+                    })
+                });
+            }
+        }
+    }
+
     pub fn get_page(&self, index: u32) -> Option<&RollbackPage<T>> {
         if index >= 64 {
             return None;
         }
-        if (self.changed_mask >> index) & 1 == 0 {
+        if (self.page_mask() >> index) & 1 == 0 {
             return None;
         }
         unsafe { Some(self.data[index as usize].assume_init_ref()) }
@@ -243,7 +811,7 @@ impl<T: Clone> RollbackStorage<T> {
         if index >= 64 {
             return None;
         }
-        if (self.changed_mask >> index) & 1 == 0 {
+        if (self.page_mask() >> index) & 1 == 0 {
             return None;
         }
         // Don't set changed_mask here - it should only be set when values actually change
@@ -251,16 +819,29 @@ impl<T: Clone> RollbackStorage<T> {
     }
 
     pub fn get_or_create_page(&mut self, index: u32) -> &mut RollbackPage<T> {
+        match self.try_get_or_create_page(index) {
+            Ok(page) => page,
+            Err(_) => handle_alloc_error(Layout::new::<RollbackPage<T>>()),
+        }
+    }
+
+    /// Fallible variant of `get_or_create_page`: returns `AllocError` instead of
+    /// aborting when the arena cannot provide memory for a new page.
+    pub fn try_get_or_create_page(
+        &mut self,
+        index: u32,
+    ) -> Result<&mut RollbackPage<T>, AllocError> {
         assert!(index < 64, "Page index must be in range 0-63");
+        debug_assert!(self.packed.is_none(), "recording into a packed snapshot");
 
         if (self.changed_mask >> index) & 1 == 0 {
             let alloc = self.arena();
-            let page = Box::new_in(RollbackPage::new_with_alloc(alloc), alloc);
+            let page = Box::try_new_in(RollbackPage::new_with_alloc(alloc), alloc)?;
             self.data[index as usize].write(page);
             self.changed_mask |= 1u64 << index;
         }
 
-        unsafe { self.data[index as usize].assume_init_mut() }
+        unsafe { Ok(self.data[index as usize].assume_init_mut()) }
     }
 
     /// Gets a reference to a value at the given global index.
@@ -374,6 +955,9 @@ impl<T: Clone> RollbackStorage<T> {
     /// Note: changed_mask is kept set if pages still have chunks with created/removed masks
     /// (needed to track structure existence).
     pub fn clear_changed_masks(&mut self) {
+        if self.packed.is_some() {
+            return;
+        }
         // Use changed_mask to visit all pages
         let mut storage_mask = self.changed_mask;
         let mut new_changed_mask = 0u64;
@@ -404,7 +988,7 @@ impl<T: Clone> RollbackStorage<T> {
     /// Verifies that all invariants hold for this RollbackStorage and all its Pages.
     /// Returns true if all invariants are satisfied, false otherwise.
     pub fn verify_invariants(&self) -> bool {
-        let mut mask = self.changed_mask;
+        let mut mask = self.page_mask();
         while mask != 0 {
             let start = mask.trailing_zeros() as usize;
             let shifted = mask >> start;
@@ -431,22 +1015,8 @@ impl<T: Clone> Default for RollbackStorage<T> {
 
 impl<T: Clone> Drop for RollbackStorage<T> {
     fn drop(&mut self) {
-        // Drop all pages that exist (changed_mask set means the page exists)
-        // At this level, we're dropping Box<RollbackPage<T>> structures, not values
-        let mut mask = self.changed_mask;
-        while mask != 0 {
-            let start = mask.trailing_zeros() as usize;
-            let shifted = mask >> start;
-            let run_len = shifted.trailing_ones() as usize;
-
-            for i in start..start + run_len {
-                unsafe {
-                    self.data[i].assume_init_drop();
-                }
-            }
-
-            mask &= !((u64::MAX >> (64 - run_len)) << start);
-        }
+        record_free::<T>(AllocKind::RollbackSnapshot);
+        self.drop_pages();
         // arena_box drops automatically here
     }
 }
@@ -465,6 +1035,7 @@ pub struct RollbackPage<T> {
 
 impl<T> RollbackPage<T> {
     pub fn new_with_alloc(alloc: &'static Arena) -> Self {
+        record_alloc::<T>(AllocKind::RollbackPage);
         Self {
             changed_mask: 0,
             data: unsafe { MaybeUninit::uninit().assume_init() },
@@ -499,15 +1070,26 @@ impl<T> RollbackPage<T> {
 
     /// Gets or creates a Chunk at the given index (0-127).
     pub fn get_or_create_chunk(&mut self, index: u32) -> &mut RollbackChunk<T> {
+        match self.try_get_or_create_chunk(index) {
+            Ok(chunk) => chunk,
+            Err(_) => handle_alloc_error(Layout::new::<RollbackChunk<T>>()),
+        }
+    }
+
+    /// Fallible variant of `get_or_create_chunk`.
+    pub fn try_get_or_create_chunk(
+        &mut self,
+        index: u32,
+    ) -> Result<&mut RollbackChunk<T>, AllocError> {
         assert!(index < 64, "Chunk index must be in range 0-63");
 
         if (self.changed_mask >> index) & 1 == 0 {
-            let chunk = Box::new_in(RollbackChunk::new(), self.alloc);
+            let chunk = Box::try_new_in(RollbackChunk::new(), self.alloc)?;
             self.data[index as usize].write(chunk);
             self.changed_mask |= 1u64 << index;
         }
 
-        unsafe { self.data[index as usize].assume_init_mut() }
+        unsafe { Ok(self.data[index as usize].assume_init_mut()) }
     }
 
     /// Verifies that all invariants hold for this RollbackPage and all its Chunks.
@@ -566,6 +1148,7 @@ impl<T> RollbackPage<T> {
 
 impl<T> Drop for RollbackPage<T> {
     fn drop(&mut self) {
+        record_free::<T>(AllocKind::RollbackPage);
         // Drop all chunks that exist (changed_mask set means the chunk exists)
         // At this level, we're dropping Box<RollbackChunk<T>> structures, not values
         let mut mask = self.changed_mask;
@@ -590,28 +1173,73 @@ impl<T> Drop for RollbackPage<T> {
 /// # Mask Semantics
 ///
 /// See RollbackStorage documentation for details on created_mask, changed_mask, and removed_mask.
+///
+/// ## snapshot_mask
+/// - Bit at index `i` is set (1) if the item's value at tick-1 was copied when the whole chunk
+///   was snapshotted on its first modification (see `SnapshotPolicy`)
+/// - The stored value is kept regardless of the other masks, so later changes/removals of the
+///   item don't store it again
 #[repr(align(64))]
 pub struct RollbackChunk<T> {
     pub created_mask: u64,
     pub changed_mask: u64,
     pub removed_mask: u64,
+    pub snapshot_mask: u64,
+    /// Stored values a rollback moved back into the live storage instead of cloning
+    /// them. Their slots are uninitialized; only set on snapshots about to be discarded.
+    pub consumed_mask: u64,
     pub data: [MaybeUninit<T>; 64],
 }
 
 impl<T> RollbackChunk<T> {
     /// Creates a new RollbackChunk
     pub fn new() -> Self {
+        record_alloc::<T>(AllocKind::RollbackChunk);
         Self {
             created_mask: 0,
             changed_mask: 0,
             removed_mask: 0,
+            snapshot_mask: 0,
+            consumed_mask: 0,
             data: unsafe { MaybeUninit::uninit().assume_init() },
         }
     }
 
+    /// Copies every value in `presence_mask` from `values` into this chunk as its tick-1 state.
+    /// Only valid on a chunk that has not recorded any change yet.
+    pub fn snapshot_from(&mut self, values: &[Slot<T>; 64], presence_mask: u64)
+    where
+        T: Component,
+    {
+        debug_assert!(
+            (self.created_mask | self.changed_mask | self.removed_mask | self.snapshot_mask) == 0,
+            "RollbackChunk snapshotted after recording changes"
+        );
+        let mut mask = presence_mask;
+        while mask != 0 {
+            let i = mask.trailing_zeros() as usize;
+            self.data[i].write(unsafe { values[i].assume_init_ref() }.clone());
+            mask &= mask - 1;
+        }
+        self.snapshot_mask = presence_mask;
+    }
+
+    /// Returns true if the old value at `index` still has to be stored, i.e. it is neither
+    /// stored by an earlier change/removal in this tick nor part of a chunk snapshot.
+    #[inline(always)]
+    pub fn needs_old_value(&self, index: u32) -> bool {
+        ((self.changed_mask | self.removed_mask | self.snapshot_mask) >> index) & 1 == 0
+    }
+
     pub fn verify_invariants(&self) -> bool {
-        // No specific invariants to check for rollback chunks
-        true
+        // At most one of created/changed/removed may be set for any index,
+        // and items created this tick had no tick-1 value to snapshot
+        self.created_mask & self.changed_mask == 0
+            && self.created_mask & self.removed_mask == 0
+            && self.changed_mask & self.removed_mask == 0
+            && self.created_mask & self.snapshot_mask == 0
+            && self.consumed_mask & !(self.changed_mask | self.removed_mask | self.snapshot_mask)
+                == 0
     }
 
     /// Clears the changed_mask at Chunk level.
@@ -628,9 +1256,11 @@ impl<T> Default for RollbackChunk<T> {
 
 impl<T> Drop for RollbackChunk<T> {
     fn drop(&mut self) {
-        // Only drop items that have stored values (changed or removed), not created items
+        record_free::<T>(AllocKind::RollbackChunk);
+        // Only drop items that have stored values (changed, removed or snapshotted), not created items
         // Created items don't have values in rollback storage, so nothing to drop
-        let mut mask = self.changed_mask | self.removed_mask;
+        let mut mask =
+            (self.changed_mask | self.removed_mask | self.snapshot_mask) & !self.consumed_mask;
 
         while mask != 0 {
             let start = mask.trailing_zeros() as usize;
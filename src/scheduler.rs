@@ -1,6 +1,10 @@
+use crate::error::Error;
 use crate::frame::Frame;
-use crate::system::System;
+use crate::system::{System, SystemError, SystemGroup};
+use crate::trace::SystemTrace;
+use crate::world::World;
 use std::any::TypeId;
+use std::cell::RefCell;
 use std::collections::{HashMap, HashSet, VecDeque};
 
 /// A scheduler that manages system execution order based on dependencies.
@@ -8,6 +12,164 @@ use std::collections::{HashMap, HashSet, VecDeque};
 pub struct Scheduler {
     systems: Vec<Box<dyn System>>,
     wavefronts: Vec<Vec<usize>>,
+    error_policy: ErrorPolicy,
+    duplicate_policy: DuplicatePolicy,
+    /// Errors reported by the systems of the last run, in run order.
+    errors: RefCell<Vec<SystemFailure>>,
+    /// Groups turned off with `set_group_enabled`.
+    disabled_groups: HashSet<TypeId>,
+    /// Per system: whether it is nested in one of `disabled_groups`.
+    disabled: Vec<bool>,
+    /// System indices in the order set by `load_order`, kept until a system is added.
+    pinned_order: Option<Vec<usize>>,
+    /// Systems run per tick (feature `system-trace`).
+    trace: SystemTrace,
+    /// Set by `pause`; `World::update` then only runs the ticks requested with `step`.
+    paused: bool,
+    pending_steps: u32,
+    /// Per wavefront: the groups whose last system runs in it, innermost first.
+    group_barriers: Vec<Vec<GroupBarrier>>,
+    /// Callbacks registered with `on_group_completed`.
+    group_listeners: RefCell<Vec<GroupListener>>,
+}
+
+/// Notification passed to `Scheduler::on_group_completed` callbacks: every system
+/// nested in the group, at any depth, finished for the tick.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GroupCompleted(pub TypeId);
+
+impl GroupCompleted {
+    /// Returns true if the completed group is `G`.
+    pub fn is<G: SystemGroup>(&self) -> bool {
+        self.0 == TypeId::of::<G>()
+    }
+}
+
+type GroupListener = Box<dyn FnMut(GroupCompleted, &Frame) + Send>;
+
+/// A group completing at the end of a wavefront.
+struct GroupBarrier {
+    group: TypeId,
+    /// The group's systems; it completes unless all of them are disabled.
+    systems: Vec<usize>,
+}
+
+/// What the scheduler does when a system reports a `SystemError`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// Keep running the tick; the errors are only collected.
+    #[default]
+    Continue,
+    /// Skip every system after the failing one. `World::run`/`try_run` then roll the
+    /// world back to the start of the tick, like after a panic with `panic-safety`.
+    AbortTick,
+}
+
+/// What `Scheduler::add_system_unique` does with a system whose type is already
+/// scheduled, e.g. when a plugin and the game both add it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// The new system takes the place of the scheduled one, keeping its position.
+    #[default]
+    Replace,
+    /// The new system is refused: `try_add_system_unique` returns
+    /// `Error::DuplicateSystem` and `add_system_unique` panics.
+    Reject,
+}
+
+/// An error a system reported during a run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SystemFailure {
+    pub system: &'static str,
+    pub error: SystemError,
+}
+
+/// A problem with the schedule found by `Scheduler::validate`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ScheduleProblem {
+    /// `system` borrows a storage of `component` the world doesn't have, e.g. because
+    /// the system was created for another world.
+    MissingStorage {
+        system: &'static str,
+        component: &'static str,
+    },
+    /// `system` borrows `component`, mutably if `mutable`, without declaring it in
+    /// `writes()` (or, for a shared borrow, in `reads()` or `writes()`), so the
+    /// scheduler doesn't order it against the other systems using `component`.
+    UndeclaredAccess {
+        system: &'static str,
+        component: &'static str,
+        mutable: bool,
+    },
+    /// A `before`/`after` of `system` names a type that is neither a scheduled system
+    /// nor a group one is nested in, so the constraint orders nothing.
+    UnknownOrderTarget {
+        system: &'static str,
+        target: TypeId,
+    },
+    /// The ordering constraints and reads/writes leave no valid order for `systems`,
+    /// the systems in a dependency cycle and those ordered after one; they would all
+    /// run in a single wavefront.
+    DependencyCycle { systems: Vec<&'static str> },
+}
+
+impl std::fmt::Display for ScheduleProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScheduleProblem::MissingStorage { system, component } => write!(
+                f,
+                "system {} borrows a storage of {} the world doesn't have",
+                system, component
+            ),
+            ScheduleProblem::UndeclaredAccess {
+                system,
+                component,
+                mutable,
+            } => write!(
+                f,
+                "system {} {} {} without declaring it",
+                system,
+                if *mutable { "writes" } else { "reads" },
+                component
+            ),
+            ScheduleProblem::UnknownOrderTarget { system, target } => write!(
+                f,
+                "system {} is ordered against {:?}, which is not a scheduled system or group",
+                system, target
+            ),
+            ScheduleProblem::DependencyCycle { systems } => {
+                write!(f, "dependency cycle among {}", systems.join(", "))
+            }
+        }
+    }
+}
+
+/// A resolved system order, saved by `Scheduler::save_order` and restored with
+/// `load_order`: the system names in the order a sequential run executes them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SystemOrder {
+    names: Vec<String>,
+}
+
+impl SystemOrder {
+    pub fn new(names: Vec<String>) -> Self {
+        Self { names }
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// FNV-1a hash of the names in order. Peers exchange it to check they run their
+    /// systems in the same order.
+    pub fn hash(&self) -> u64 {
+        self.names
+            .iter()
+            .fold(crate::rng::FNV_OFFSET, |hash, name| {
+                crate::rng::fnv1a(crate::rng::fnv1a(hash, name.as_bytes()), &[0])
+            })
+    }
 }
 
 impl Scheduler {
@@ -16,35 +178,509 @@ impl Scheduler {
         Self {
             systems: Vec::new(),
             wavefronts: Vec::new(),
+            error_policy: ErrorPolicy::Continue,
+            duplicate_policy: DuplicatePolicy::Replace,
+            errors: RefCell::new(Vec::new()),
+            disabled_groups: HashSet::new(),
+            disabled: Vec::new(),
+            pinned_order: None,
+            trace: SystemTrace::new(),
+            paused: false,
+            pending_steps: 0,
+            group_barriers: Vec::new(),
+            group_listeners: RefCell::new(Vec::new()),
+        }
+    }
+
+    pub fn error_policy(&self) -> ErrorPolicy {
+        self.error_policy
+    }
+
+    pub fn set_error_policy(&mut self, policy: ErrorPolicy) {
+        self.error_policy = policy;
+    }
+
+    pub fn duplicate_policy(&self) -> DuplicatePolicy {
+        self.duplicate_policy
+    }
+
+    pub fn set_duplicate_policy(&mut self, policy: DuplicatePolicy) {
+        self.duplicate_policy = policy;
+    }
+
+    /// Stops the game loop helper (`World::update`) from running ticks, e.g. while a
+    /// debugger or editor inspects the world. `World::run` still runs a tick.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Lets `World::update` run ticks again, dropping steps not taken yet.
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.pending_steps = 0;
+    }
+
+    /// Pauses the scheduler if it isn't and has the next `World::update` run `ticks`
+    /// more ticks, whatever time passed.
+    pub fn step(&mut self, ticks: u32) {
+        self.paused = true;
+        self.pending_steps = self.pending_steps.saturating_add(ticks);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Ticks requested with `step` and not run yet.
+    pub fn pending_steps(&self) -> u32 {
+        self.pending_steps
+    }
+
+    /// Whether the game loop may run a tick now; takes a pending step while paused.
+    pub(crate) fn take_step(&mut self) -> bool {
+        if !self.paused {
+            return true;
         }
+        let step = self.pending_steps > 0;
+        self.pending_steps -= step as u32;
+        step
     }
 
-    /// Adds a system to the scheduler.
+    /// Calls `f` whenever all systems of a group finish in a tick, e.g. to hand the
+    /// group's results to an audio or physics engine at that point of the frame. A
+    /// group finishes with the wavefront running its last system; groups finishing
+    /// together are reported innermost first. Groups whose systems are all disabled,
+    /// and those after a system aborting the run, are not reported.
+    pub fn on_group_completed(&mut self, f: impl FnMut(GroupCompleted, &Frame) + Send + 'static) {
+        self.group_listeners.get_mut().push(Box::new(f));
+    }
+
+    /// Calls the `on_group_completed` callbacks for the groups finishing with
+    /// `wavefront`.
+    fn complete_groups(&self, wavefront: usize, frame: &Frame) {
+        let Some(barriers) = self.group_barriers.get(wavefront) else {
+            return;
+        };
+        if barriers.is_empty() {
+            return;
+        }
+        let mut listeners = self.group_listeners.borrow_mut();
+        for barrier in barriers {
+            if barrier.systems.iter().all(|&i| self.disabled[i]) {
+                continue;
+            }
+            for listener in listeners.iter_mut() {
+                listener(GroupCompleted(barrier.group), frame);
+            }
+        }
+    }
+
+    /// The systems run so far, in order; see `decs::trace` and `World::system_trace`.
+    pub fn trace(&self) -> &SystemTrace {
+        &self.trace
+    }
+
+    /// Returns the errors systems reported during the last run, in the order they ran.
+    pub fn last_errors(&self) -> Vec<SystemFailure> {
+        self.errors.borrow().clone()
+    }
+
+    /// Returns the system that aborted the last run under `ErrorPolicy::AbortTick`.
+    pub(crate) fn aborted_by(&self) -> Option<&'static str> {
+        if self.error_policy != ErrorPolicy::AbortTick {
+            return None;
+        }
+        self.errors.borrow().first().map(|failure| failure.system)
+    }
+
+    /// Collects the error `system` reported in its run; returns true if the run must
+    /// stop there.
+    fn collect_error(&self, system: &dyn System) -> bool {
+        let Some(error) = system.take_error() else {
+            return false;
+        };
+        self.errors.borrow_mut().push(SystemFailure {
+            system: system.name(),
+            error,
+        });
+        self.error_policy == ErrorPolicy::AbortTick
+    }
+
+    /// Adds a system to the scheduler. Every call adds a system, even if one of the
+    /// same type is already scheduled; use `add_system_unique` to avoid that.
     pub fn add_system<S: System>(&mut self, system: S) {
+        let disabled = in_disabled_group(&system, &self.disabled_groups);
         self.systems.push(Box::new(system));
+        self.disabled.push(disabled);
+        self.schedule_changed();
+    }
+
+    /// Adds a system unless one of type `S` is already scheduled, in which case the
+    /// `DuplicatePolicy` decides.
+    ///
+    /// # Panics
+    /// Panics if `S` is already scheduled under `DuplicatePolicy::Reject`.
+    pub fn add_system_unique<S: System>(&mut self, system: S) {
+        if let Err(err) = self.try_add_system_unique(system) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Like `add_system_unique`, but returns the rejection as an error.
+    ///
+    /// # Errors
+    /// Returns `Error::DuplicateSystem` if `S` is already scheduled under
+    /// `DuplicatePolicy::Reject`; the scheduler is left unchanged.
+    pub fn try_add_system_unique<S: System>(&mut self, system: S) -> crate::error::Result<()> {
+        let disabled = in_disabled_group(&system, &self.disabled_groups);
+        match self.systems.iter().position(|s| s.as_any().is::<S>()) {
+            Some(_) if self.duplicate_policy == DuplicatePolicy::Reject => {
+                return Err(Error::DuplicateSystem {
+                    system: system.name(),
+                });
+            }
+            Some(idx) => {
+                self.systems[idx] = Box::new(system);
+                self.disabled[idx] = disabled;
+            }
+            None => {
+                self.systems.push(Box::new(system));
+                self.disabled.push(disabled);
+            }
+        }
+        self.schedule_changed();
+        Ok(())
+    }
+
+    /// Drops the wavefronts and the `load_order` pin after the systems changed.
+    fn schedule_changed(&mut self) {
         self.wavefronts.clear();
+        self.group_barriers.clear();
+        self.pinned_order = None;
+    }
+
+    /// Turns every system nested under group `G`, at any depth, off or back on, e.g.
+    /// debug overlays or cheats that are always compiled in. Disabled systems keep
+    /// their place in the wavefronts, like systems skipped by a run condition, so the
+    /// order does not change when the group is toggled.
+    pub fn set_group_enabled<G: SystemGroup>(&mut self, enabled: bool) {
+        let group = TypeId::of::<G>();
+        if enabled {
+            self.disabled_groups.remove(&group);
+        } else {
+            self.disabled_groups.insert(group);
+        }
+        for (disabled, system) in self.disabled.iter_mut().zip(&self.systems) {
+            *disabled = in_disabled_group(system.as_ref(), &self.disabled_groups);
+        }
+    }
+
+    /// Returns false if group `G` was turned off with `set_group_enabled`. Systems of an
+    /// enabled group nested in a disabled one still don't run.
+    pub fn is_group_enabled<G: SystemGroup>(&self) -> bool {
+        !self.disabled_groups.contains(&TypeId::of::<G>())
+    }
+
+    /// Returns the scheduled system of type `S`.
+    pub fn get_system<S: System>(&self) -> Option<&S> {
+        self.systems
+            .iter()
+            .find_map(|s| s.as_any().downcast_ref::<S>())
     }
 
     /// Runs all systems using precomputed wavefronts. Call `build_wavefronts` after
     /// adding systems or changing dependencies before invoking `run`.
     pub fn run(&self, frame: &Frame) {
-        for wave in &self.wavefronts {
+        self.run_except(frame, &[]);
+    }
+
+    /// Like `run`, but skips every system that reads or writes one of the `blocked`
+    /// component types.
+    pub fn run_except(&self, frame: &Frame, blocked: &[TypeId]) {
+        self.errors.borrow_mut().clear();
+        let mut position = 0;
+        for (wavefront, wave) in self.wavefronts.iter().enumerate() {
+            crate::aliasing::begin_batch();
+            for &idx in wave {
+                // 1-based, so commands queued outside systems (issuer 0) apply first.
+                position += 1;
+                let system = &self.systems[idx];
+                if self.disabled[idx] || is_blocked(system.as_ref(), blocked) {
+                    continue;
+                }
+                self.trace.record(
+                    frame.current_tick,
+                    position,
+                    wavefront as u32,
+                    system.as_ref(),
+                );
+                crate::audit::enter_system(system.name());
+                crate::commands::with_issuer(position, || system.run(frame));
+                crate::audit::exit_system();
+                if self.collect_error(system.as_ref()) {
+                    crate::aliasing::end_batch();
+                    return;
+                }
+            }
+            crate::aliasing::end_batch();
+            self.complete_groups(wavefront, frame);
+        }
+    }
+
+    /// Like `run`, but catches a panicking system and stops there, returning its name
+    /// and the panic payload. Systems after it (including the rest of its wavefront)
+    /// do not run. Reported errors are handled like in `run`.
+    #[cfg(feature = "panic-safety")]
+    pub fn run_catching(
+        &self,
+        frame: &Frame,
+    ) -> Result<(), (&'static str, Box<dyn std::any::Any + Send>)> {
+        self.run_catching_except(frame, &[])
+    }
+
+    /// `run_catching` with the `blocked` filter of `run_except`.
+    #[cfg(feature = "panic-safety")]
+    pub fn run_catching_except(
+        &self,
+        frame: &Frame,
+        blocked: &[TypeId],
+    ) -> Result<(), (&'static str, Box<dyn std::any::Any + Send>)> {
+        self.errors.borrow_mut().clear();
+        let mut position = 0;
+        for (wavefront, wave) in self.wavefronts.iter().enumerate() {
+            crate::aliasing::begin_batch();
             for &idx in wave {
-                self.systems[idx].run(frame);
+                position += 1;
+                let system = &self.systems[idx];
+                if self.disabled[idx] || is_blocked(system.as_ref(), blocked) {
+                    continue;
+                }
+                self.trace.record(
+                    frame.current_tick,
+                    position,
+                    wavefront as u32,
+                    system.as_ref(),
+                );
+                crate::audit::enter_system(system.name());
+                let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    crate::commands::with_issuer(position, || system.run(frame));
+                }));
+                crate::audit::exit_system();
+                if let Err(payload) = result {
+                    crate::aliasing::end_batch();
+                    return Err((system.name(), payload));
+                }
+                if self.collect_error(system.as_ref()) {
+                    crate::aliasing::end_batch();
+                    return Ok(());
+                }
             }
+            crate::aliasing::end_batch();
+            self.complete_groups(wavefront, frame);
         }
+        Ok(())
     }
 
-    /// Builds wavefronts based on current systems and dependencies.
+    /// Builds wavefronts based on current systems and dependencies, or on the order
+    /// set by `load_order` while no system was added since.
     pub fn build_wavefronts(&mut self) {
-        self.wavefronts = self.compute_wavefronts();
+        self.wavefronts = match &self.pinned_order {
+            Some(order) => self.pack_order(order),
+            None => self.compute_wavefronts(),
+        };
+        self.group_barriers = self.group_barriers();
+    }
+
+    /// Returns the order a sequential `run` executes the systems in: that of the
+    /// current wavefronts, or of `resolved_order` if they are not built. Persist it and
+    /// restore it with `load_order` to keep the order across restarts and code changes
+    /// that would resolve ties differently.
+    pub fn save_order(&self) -> SystemOrder {
+        let names = if self.wavefronts.is_empty() {
+            self.resolved_order()
+        } else {
+            self.wavefronts
+                .iter()
+                .flatten()
+                .map(|&i| self.systems[i].name())
+                .collect()
+        };
+        SystemOrder::new(names.into_iter().map(str::to_owned).collect())
+    }
+
+    /// Runs the systems in `order` from now on, rebuilding the wavefronts from it; each
+    /// wavefront takes the following systems until one depends on a system already in it.
+    /// Systems of the same type are matched in the order they were added. The order
+    /// stays in effect until a system is added.
+    ///
+    /// Returns `Error::SystemOrderMismatch` if `order` does not name exactly the
+    /// scheduled systems, and `Error::SystemOrderViolated` if it breaks a dependency
+    /// (`before`/`after` or reads/writes); the schedule is then left as it was.
+    pub fn load_order(&mut self, order: &SystemOrder) -> crate::error::Result<()> {
+        if order.names.len() != self.systems.len() {
+            return Err(Error::SystemOrderMismatch);
+        }
+        let mut by_name: HashMap<&str, VecDeque<usize>> = HashMap::new();
+        for (i, system) in self.systems.iter().enumerate() {
+            by_name.entry(system.name()).or_default().push_back(i);
+        }
+        let indices = order
+            .names
+            .iter()
+            .map(|name| by_name.get_mut(name.as_str())?.pop_front())
+            .collect::<Option<Vec<usize>>>()
+            .ok_or(Error::SystemOrderMismatch)?;
+
+        let mut position = vec![0; indices.len()];
+        for (pos, &i) in indices.iter().enumerate() {
+            position[i] = pos;
+        }
+        let graph = self.dependency_graph();
+        for (from, successors) in graph.adjacency.iter().enumerate() {
+            if let Some(&to) = successors.iter().find(|&&to| position[to] < position[from]) {
+                return Err(Error::SystemOrderViolated {
+                    system: self.systems[to].name(),
+                    dependency: self.systems[from].name(),
+                });
+            }
+        }
+
+        self.wavefronts = self.pack_order(&indices);
+        self.group_barriers = self.group_barriers();
+        self.pinned_order = Some(indices);
+        Ok(())
+    }
+
+    /// Splits `order`, a valid topological order, into wavefronts without reordering.
+    fn pack_order(&self, order: &[usize]) -> Vec<Vec<usize>> {
+        let graph = self.dependency_graph();
+        let mut waves: Vec<Vec<usize>> = Vec::new();
+        for &i in order {
+            let joins_last = waves.last().is_some_and(|wave| {
+                !self.systems[i].exclusive()
+                    && wave.iter().all(|&j| {
+                        !self.systems[j].exclusive()
+                            && !graph.adjacency[j].contains(&i)
+                            && !graph.adjacency[i].contains(&j)
+                    })
+            });
+            match waves.last_mut() {
+                Some(wave) if joins_last => wave.push(i),
+                _ => waves.push(vec![i]),
+            }
+        }
+        waves
+    }
+
+    /// Finds the wavefront each group finishes with: the last one running a system
+    /// nested in it.
+    fn group_barriers(&self) -> Vec<Vec<GroupBarrier>> {
+        // Per group: depth, last wavefront and systems, in order of first appearance.
+        let mut groups: Vec<(TypeId, usize, usize, Vec<usize>)> = Vec::new();
+        let mut positions: HashMap<TypeId, usize> = HashMap::new();
+        for (wavefront, wave) in self.wavefronts.iter().enumerate() {
+            for &idx in wave {
+                let mut chain = Vec::new();
+                let mut group = self.systems[idx].parent();
+                while let Some(g) = group {
+                    chain.push(g.as_any().type_id());
+                    group = g.parent();
+                }
+                let depth = chain.len();
+                for (level, id) in chain.into_iter().enumerate() {
+                    let pos = *positions.entry(id).or_insert_with(|| {
+                        groups.push((id, depth - level, 0, Vec::new()));
+                        groups.len() - 1
+                    });
+                    groups[pos].2 = wavefront;
+                    groups[pos].3.push(idx);
+                }
+            }
+        }
+        let mut barriers: Vec<Vec<GroupBarrier>> =
+            self.wavefronts.iter().map(|_| Vec::new()).collect();
+        // Stable, so groups of equal depth keep their order of appearance.
+        groups.sort_by_key(|&(_, depth, ..)| std::cmp::Reverse(depth));
+        for (group, _, wavefront, systems) in groups {
+            barriers[wavefront].push(GroupBarrier { group, systems });
+        }
+        barriers
     }
 
     pub fn wavefronts(&self) -> &[Vec<usize>] {
         &self.wavefronts
     }
 
+    /// Returns the names of all systems in the order a sequential `run` executes them,
+    /// computed from the current systems (without needing `build_wavefronts`).
+    ///
+    /// The order is deterministic: wavefronts in dependency order, each sorted by the
+    /// order systems were added. Dependencies come from `before`/`after` of systems and
+    /// of every enclosing group, inherited through `parent` chains, and from
+    /// reads/writes; ordering constraints only bind systems that conflict, since others
+    /// can run in any order without observable difference. Read-only systems run as
+    /// late as the systems ordered after them allow.
+    pub fn resolved_order(&self) -> Vec<&'static str> {
+        self.compute_wavefronts()
+            .into_iter()
+            .flatten()
+            .map(|i| self.systems[i].name())
+            .collect()
+    }
+
     fn compute_wavefronts(&self) -> Vec<Vec<usize>> {
+        let graph = self.dependency_graph();
+        let adjacency = graph.adjacency.clone();
+        let mut levels = graph.topological_levels();
+        self.delay_read_only(&mut levels, &adjacency);
+        levels
+    }
+
+    /// Moves every read-only system to the last wavefront before its first successor
+    /// (the last one if it has none). Nothing depends on read-only systems running
+    /// early, and no two of them conflict, so they end up sharing the wavefronts before
+    /// the systems ordered after them. Levels holding a dependency cycle stay as they are.
+    fn delay_read_only(&self, levels: &mut Vec<Vec<usize>>, adjacency: &[Vec<usize>]) {
+        let mut level_of = vec![0; self.systems.len()];
+        for (level, wave) in levels.iter().enumerate() {
+            for &i in wave {
+                level_of[i] = level;
+            }
+        }
+        let cyclic = adjacency
+            .iter()
+            .enumerate()
+            .any(|(i, successors)| successors.iter().any(|&j| level_of[j] <= level_of[i]));
+        if cyclic {
+            return;
+        }
+        let last = levels.len().saturating_sub(1);
+        let mut moved = false;
+        for (i, system) in self.systems.iter().enumerate() {
+            if !system.read_only() || system.exclusive() {
+                continue;
+            }
+            let latest = adjacency[i]
+                .iter()
+                .map(|&j| level_of[j] - 1)
+                .min()
+                .unwrap_or(last);
+            if latest > level_of[i] {
+                levels[level_of[i]].retain(|&k| k != i);
+                levels[latest].push(i);
+                level_of[i] = latest;
+                moved = true;
+            }
+        }
+        if moved {
+            levels.retain(|wave| !wave.is_empty());
+            for wave in levels.iter_mut() {
+                wave.sort_unstable();
+            }
+        }
+    }
+
+    fn dependency_graph(&self) -> DependencyGraph {
         let n = self.systems.len();
         let mut graph = DependencyGraph::new(n);
 
@@ -71,8 +707,13 @@ impl Scheduler {
             }
         }
 
+        let exclusive: Vec<bool> = self.systems.iter().map(|s| s.exclusive()).collect();
+
         // Helper to determine if two systems have a write-write or read-write conflict
         let has_conflict = |a: usize, b: usize| -> bool {
+            if exclusive[a] || exclusive[b] {
+                return true;
+            }
             // write-write
             if sys_writes[a].iter().any(|t| sys_writes[b].contains(t)) {
                 return true;
@@ -88,28 +729,6 @@ impl Scheduler {
             false
         };
 
-        // Add system-level before/after edges only when there is a conflict
-        for (i, system) in self.systems.iter().enumerate() {
-            for before_type in system.before() {
-                if let Some(indices) = index_by_type.get(before_type) {
-                    for &j in indices {
-                        if i != j && has_conflict(i, j) {
-                            graph.add_edge(i, j);
-                        }
-                    }
-                }
-            }
-            for after_type in system.after() {
-                if let Some(indices) = index_by_type.get(after_type) {
-                    for &j in indices {
-                        if i != j && has_conflict(j, i) {
-                            graph.add_edge(j, i);
-                        }
-                    }
-                }
-            }
-        }
-
         let mut reads_by_type: HashMap<TypeId, Vec<usize>> = HashMap::new();
         let mut writes_by_type: HashMap<TypeId, Vec<usize>> = HashMap::new();
         for (i, system) in self.systems.iter().enumerate() {
@@ -138,28 +757,40 @@ impl Scheduler {
                 }
             }
         }
-        // Enforce group Before/After constraints among systems
+        // A Before/After target names either a system type or a group; a group stands
+        // for every system nested under it at any depth.
+        let members = |ty: &TypeId| {
+            index_by_type
+                .get(ty)
+                .into_iter()
+                .chain(systems_by_group.get(ty))
+                .flatten()
+                .copied()
+        };
+
+        // Enforce Before/After constraints among systems: each system's own, plus those
+        // of every group it is nested in, which it inherits.
         let mut constrained_edges: HashSet<(usize, usize)> = HashSet::new();
-        for (i, groups) in groups_by_system.iter().enumerate() {
-            for group in groups {
-                for before_type in group.before() {
-                    if let Some(targets) = systems_by_group.get(before_type) {
-                        for &j in targets {
-                            if i != j && has_conflict(i, j) {
-                                graph.add_edge(i, j);
-                                constrained_edges.insert((i, j));
-                            }
-                        }
+        for (i, system) in self.systems.iter().enumerate() {
+            let befores = std::iter::once(system.before())
+                .chain(groups_by_system[i].iter().map(|g| g.before()))
+                .flatten();
+            let afters = std::iter::once(system.after())
+                .chain(groups_by_system[i].iter().map(|g| g.after()))
+                .flatten();
+            for before_type in befores {
+                for j in members(before_type) {
+                    if i != j && has_conflict(i, j) {
+                        graph.add_edge(i, j);
+                        constrained_edges.insert((i, j));
                     }
                 }
-                for after_type in group.after() {
-                    if let Some(sources) = systems_by_group.get(after_type) {
-                        for &j in sources {
-                            if i != j && has_conflict(j, i) {
-                                graph.add_edge(j, i);
-                                constrained_edges.insert((j, i));
-                            }
-                        }
+            }
+            for after_type in afters {
+                for j in members(after_type) {
+                    if i != j && has_conflict(j, i) {
+                        graph.add_edge(j, i);
+                        constrained_edges.insert((j, i));
                     }
                 }
             }
@@ -190,7 +821,81 @@ impl Scheduler {
             }
         }
 
-        graph.topological_levels()
+        graph
+    }
+
+    /// Checks the schedule against `world` before the first tick and returns every
+    /// problem found rather than the first:
+    /// - the storages each system borrows (`System::accesses`) are `world`'s, and
+    ///   declared in its `reads()`/`writes()`;
+    /// - each system's own `before`/`after` names a scheduled system or a group one is
+    ///   nested in (groups' constraints may name groups left empty in some worlds and
+    ///   are not checked);
+    /// - the dependencies leave an order, without a cycle.
+    ///
+    /// Nothing is run or changed.
+    pub fn validate(&self, world: &World) -> Result<(), Vec<ScheduleProblem>> {
+        let mut problems = Vec::new();
+
+        let storages: HashMap<TypeId, *const ()> = world
+            .storages()
+            .map(|s| (s.component_type_id(), s as *const _ as *const ()))
+            .collect();
+        for system in &self.systems {
+            let mut reported = HashSet::new();
+            for access in system.accesses() {
+                if !reported.insert((access.component, access.mutable)) {
+                    continue;
+                }
+                if storages.get(&access.component) != Some(&access.storage) {
+                    problems.push(ScheduleProblem::MissingStorage {
+                        system: system.name(),
+                        component: access.name,
+                    });
+                }
+                let declared = system.writes().contains(&access.component)
+                    || (!access.mutable && system.reads().contains(&access.component));
+                if !declared && !system.exclusive() {
+                    problems.push(ScheduleProblem::UndeclaredAccess {
+                        system: system.name(),
+                        component: access.name,
+                        mutable: access.mutable,
+                    });
+                }
+            }
+        }
+
+        let mut targets: HashSet<TypeId> = HashSet::new();
+        for system in &self.systems {
+            targets.insert(system.as_any().type_id());
+            let mut group = system.parent();
+            while let Some(g) = group {
+                targets.insert(g.as_any().type_id());
+                group = g.parent();
+            }
+        }
+        for system in &self.systems {
+            for &target in system.before().iter().chain(system.after()) {
+                if !targets.contains(&target) {
+                    problems.push(ScheduleProblem::UnknownOrderTarget {
+                        system: system.name(),
+                        target,
+                    });
+                }
+            }
+        }
+
+        if let Some(stuck) = self.dependency_graph().unordered() {
+            problems.push(ScheduleProblem::DependencyCycle {
+                systems: stuck.into_iter().map(|i| self.systems[i].name()).collect(),
+            });
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
     }
 
     /// Returns the number of systems in the scheduler.
@@ -204,6 +909,26 @@ impl Scheduler {
     }
 }
 
+fn in_disabled_group(system: &dyn System, disabled_groups: &HashSet<TypeId>) -> bool {
+    let mut group = system.parent();
+    while let Some(g) = group {
+        if disabled_groups.contains(&g.as_any().type_id()) {
+            return true;
+        }
+        group = g.parent();
+    }
+    false
+}
+
+fn is_blocked(system: &dyn System, blocked: &[TypeId]) -> bool {
+    !blocked.is_empty()
+        && system
+            .reads()
+            .iter()
+            .chain(system.writes())
+            .any(|t| blocked.contains(t))
+}
+
 impl Default for Scheduler {
     fn default() -> Self {
         Self::new()
@@ -243,6 +968,23 @@ impl DependencyGraph {
         }
     }
 
+    /// The nodes no topological order reaches, in a cycle or after one; None if the
+    /// graph is acyclic.
+    fn unordered(self) -> Option<Vec<usize>> {
+        let mut in_degree = self.in_degree;
+        let mut queue: Vec<usize> = (0..self.n).filter(|&i| in_degree[i] == 0).collect();
+        while let Some(node) = queue.pop() {
+            for &adj in &self.adjacency[node] {
+                in_degree[adj] -= 1;
+                if in_degree[adj] == 0 {
+                    queue.push(adj);
+                }
+            }
+        }
+        let stuck: Vec<usize> = (0..self.n).filter(|&i| in_degree[i] > 0).collect();
+        (!stuck.is_empty()).then_some(stuck)
+    }
+
     // Linear topological sort is intentionally removed from public use; levels are used instead.
 
     fn topological_levels(self) -> Vec<Vec<usize>> {
@@ -285,6 +1027,7 @@ impl DependencyGraph {
                 }
             }
             if !level.is_empty() {
+                level.sort_unstable();
                 levels.push(level);
             }
         }
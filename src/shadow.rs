@@ -0,0 +1,221 @@
+//! Naive mirror of every storage, for debugging storage and rollback bookkeeping
+//! (feature `shadow-world`).
+//!
+//! With the feature, each `Storage<T>` keeps a `Shadow<T>`: its values in a `HashMap`,
+//! plus a full copy of that map for every tick it changed in, standing in for rollback
+//! history. Inserts and removals are mirrored wherever the storage records them in the
+//! entity signatures; values written in place (`get_mut`, `ViewMut`, overwriting `set`)
+//! are taken over from the changed masks. The storage is compared with its shadow
+//! whenever its changed masks are cleared (at the end of every tick), when it starts
+//! recording a new tick and after every rollback, panicking at the first index where
+//! they disagree. A bug in the presence masks or in rollback thus fails in the tick
+//! that caused it instead of ticks later.
+//!
+//! Values are compared with `Component::desync_eq`, so only components declared with
+//! `#[component(diff)]` have them checked; presence is checked for every component.
+//! Without the feature `Shadow<T>` is empty and its hooks are inlined no-ops.
+
+use crate::component::Component;
+use crate::tick::Tick;
+#[cfg(feature = "shadow-world")]
+use std::cell::RefCell;
+#[cfg(feature = "shadow-world")]
+use std::collections::{HashMap, VecDeque};
+#[cfg(not(feature = "shadow-world"))]
+use std::marker::PhantomData;
+
+/// Shadow copy of one storage; see the module documentation.
+pub struct Shadow<T> {
+    #[cfg(feature = "shadow-world")]
+    state: RefCell<ShadowState<T>>,
+    #[cfg(not(feature = "shadow-world"))]
+    _marker: PhantomData<T>,
+}
+
+#[cfg(feature = "shadow-world")]
+struct ShadowState<T> {
+    /// Tick the values belong to, as for `RollbackStorage::tick`.
+    tick: Tick,
+    values: HashMap<u32, T>,
+    /// Whether `values` changed since the start of `tick`.
+    changed: bool,
+    /// Values at the end of past ticks that changed them, oldest first.
+    history: VecDeque<(Tick, HashMap<u32, T>)>,
+}
+
+impl<T: Component> Shadow<T> {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(feature = "shadow-world")]
+            state: RefCell::new(ShadowState {
+                tick: Tick(0),
+                values: HashMap::new(),
+                changed: false,
+                history: VecDeque::new(),
+            }),
+            #[cfg(not(feature = "shadow-world"))]
+            _marker: PhantomData,
+        }
+    }
+
+    /// Mirrors an insert (`value` returns the inserted value) or a removal at `index`.
+    #[inline(always)]
+    pub(crate) fn mirror<'a>(
+        &self,
+        index: u32,
+        present: bool,
+        value: impl FnOnce() -> Option<&'a T>,
+    ) where
+        T: 'a,
+    {
+        #[cfg(feature = "shadow-world")]
+        {
+            let mut state = self.state.borrow_mut();
+            state.changed = true;
+            if present {
+                let value = value().expect("mirrored insert without a value");
+                state.values.insert(index, value.clone());
+            } else {
+                state.values.remove(&index);
+            }
+        }
+        #[cfg(not(feature = "shadow-world"))]
+        let _ = (index, present, value);
+    }
+
+    /// Whether the storage starts recording `tick`, so the shadow must be checked and
+    /// moved to it with `begin_tick`.
+    #[inline(always)]
+    pub(crate) fn needs_tick(&self, tick: Tick) -> bool {
+        #[cfg(feature = "shadow-world")]
+        return self.state.borrow().tick != tick;
+        #[cfg(not(feature = "shadow-world"))]
+        {
+            let _ = tick;
+            false
+        }
+    }
+
+    /// Keeps the values of the current tick in history if they changed and moves to `tick`.
+    pub(crate) fn begin_tick(&self, tick: Tick) {
+        #[cfg(feature = "shadow-world")]
+        {
+            let state = &mut *self.state.borrow_mut();
+            if state.changed {
+                state.history.push_back((state.tick, state.values.clone()));
+                while state.history.len() > crate::rollback::MAX_HISTORY + 1 {
+                    state.history.pop_front();
+                }
+            }
+            state.tick = tick;
+            state.changed = false;
+        }
+        #[cfg(not(feature = "shadow-world"))]
+        let _ = tick;
+    }
+
+    /// Restores the values at the end of `target`. Returns false if they are no longer
+    /// kept, in which case the shadow has to be rebuilt with `reset`.
+    pub(crate) fn rollback(&self, target: Tick) -> bool {
+        #[cfg(feature = "shadow-world")]
+        {
+            let state = &mut *self.state.borrow_mut();
+            if state.tick.is_at_or_before(target) {
+                return true;
+            }
+            while let Some((tick, values)) = state.history.pop_back() {
+                if tick.is_at_or_before(target) {
+                    state.tick = target;
+                    state.values = values;
+                    state.changed = true;
+                    return true;
+                }
+            }
+            false
+        }
+        #[cfg(not(feature = "shadow-world"))]
+        {
+            let _ = target;
+            true
+        }
+    }
+
+    /// Replaces the shadow with `values`, recorded for `tick` and without history.
+    #[cfg(feature = "shadow-world")]
+    pub(crate) fn reset<'a>(&self, tick: Tick, values: impl Iterator<Item = (u32, &'a T)>)
+    where
+        T: 'a,
+    {
+        let state = &mut *self.state.borrow_mut();
+        state.tick = tick;
+        state.values = values
+            .map(|(index, value)| (index, value.clone()))
+            .collect();
+        state.changed = false;
+        state.history.clear();
+    }
+
+    /// Checks that the shadow holds `value` at `index`, first taking `value` over if
+    /// `adopt` is set.
+    ///
+    /// # Panics
+    /// Panics if the shadow has no value at `index`, or a different one.
+    #[cfg(feature = "shadow-world")]
+    pub(crate) fn check(&self, index: u32, value: &T, adopt: bool) {
+        let state = &mut *self.state.borrow_mut();
+        let tick = state.tick;
+        match state.values.get_mut(&index) {
+            None => mismatch::<T>(index, tick, "the storage has a value the shadow lacks"),
+            Some(shadow) if adopt => {
+                *shadow = value.clone();
+                state.changed = true;
+            }
+            Some(shadow) => {
+                if !value.desync_eq(shadow) {
+                    mismatch::<T>(
+                        index,
+                        tick,
+                        "the storage and the shadow hold different values",
+                    );
+                }
+            }
+        }
+    }
+
+    /// Checks that the shadow holds no more than the `count` values passed to `check`,
+    /// with `present` telling whether the storage has a value at an index.
+    ///
+    /// # Panics
+    /// Panics naming an index the shadow has a value at and the storage has not.
+    #[cfg(feature = "shadow-world")]
+    pub(crate) fn check_count(&self, count: usize, present: impl Fn(u32) -> bool) {
+        let state = self.state.borrow();
+        if state.values.len() != count
+            && let Some(&index) = state.values.keys().find(|&&index| !present(index))
+        {
+            mismatch::<T>(
+                index,
+                state.tick,
+                "the shadow has a value the storage lacks",
+            );
+        }
+    }
+}
+
+impl<T: Component> Default for Shadow<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "shadow-world")]
+#[cold]
+fn mismatch<T>(index: u32, tick: Tick, what: &str) -> ! {
+    panic!(
+        "shadow mismatch in {} at index {} in {:?}: {}",
+        std::any::type_name::<T>(),
+        index,
+        tick,
+        what
+    )
+}
@@ -0,0 +1,178 @@
+//! Per-entity component signatures: which component ids have a value at each index.
+//!
+//! Every storage created by a `World` points at the world's `Signatures` and flips the
+//! bit of its component at an index whenever a value is inserted there or removed: by
+//! `set`, `remove`, the storage writer, the cleanup systems and rollback.
+//! `World::components_of` reads it, so inspectors and serializers learn an entity's
+//! component types without probing all 256 storages, and `World::archetype_stats`
+//! groups entities by it.
+
+use crate::component::ComponentId;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Component ids a signature holds, the size of a world's storage table.
+const IDS: u32 = 256;
+const WORDS: usize = IDS as usize / 64;
+/// Signatures per block: the indices of one storage-level page.
+const BLOCK: usize = 64 * 64;
+
+type Signature = [u64; WORDS];
+
+/// Component signatures of every index, allocated per storage-level page of indices on
+/// the first insert there. Blocks are kept once allocated.
+#[derive(Clone)]
+pub struct Signatures {
+    blocks: [Option<Box<[Signature]>>; 64],
+}
+
+impl Default for Signatures {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Signatures {
+    pub const fn new() -> Self {
+        Self {
+            blocks: [const { None }; 64],
+        }
+    }
+
+    /// Records whether `index` has a value of component `id`. Ids past the storage
+    /// table are ignored.
+    #[inline]
+    pub fn set(&mut self, index: u32, id: ComponentId, present: bool) {
+        if id >= IDS || index >= 64 * BLOCK as u32 {
+            return;
+        }
+        let block = &mut self.blocks[(index >> 12) as usize];
+        if block.is_none() && !present {
+            return;
+        }
+        let block = block.get_or_insert_with(|| vec![[0; WORDS]; BLOCK].into_boxed_slice());
+        let word = &mut block[(index & 4095) as usize][(id >> 6) as usize];
+        if present {
+            *word |= 1u64 << (id & 63);
+        } else {
+            *word &= !(1u64 << (id & 63));
+        }
+    }
+
+    fn signature(&self, index: u32) -> Signature {
+        match self.blocks.get((index >> 12) as usize) {
+            Some(Some(block)) => block[(index & 4095) as usize],
+            _ => [0; WORDS],
+        }
+    }
+
+    /// Returns true if `index` has a value of component `id`.
+    pub fn contains(&self, index: u32, id: ComponentId) -> bool {
+        id < IDS && (self.signature(index)[(id >> 6) as usize] >> (id & 63)) & 1 != 0
+    }
+
+    /// Iterates the component ids `index` has a value of, in ascending order.
+    pub fn iter(&self, index: u32) -> SignatureIter {
+        SignatureIter::new(self.signature(index))
+    }
+
+    /// Counts the indices holding component `with` per distinct signature. Returns the
+    /// component ids of each signature, without `with`, and its count, in no order.
+    pub fn count_by_signature(&self, with: ComponentId) -> Vec<(Vec<ComponentId>, u32)> {
+        if with >= IDS {
+            return Vec::new();
+        }
+        let (word, bit) = ((with >> 6) as usize, 1u64 << (with & 63));
+        let mut counts: HashMap<Signature, u32> = HashMap::new();
+        for block in self.blocks.iter().flatten() {
+            for signature in block.iter().filter(|s| s[word] & bit != 0) {
+                let mut key = *signature;
+                key[word] &= !bit;
+                *counts.entry(key).or_default() += 1;
+            }
+        }
+        counts
+            .into_iter()
+            .map(|(words, count)| (SignatureIter::new(words).collect(), count))
+            .collect()
+    }
+}
+
+/// Spawned entities grouped by the components they hold; see `World::archetype_stats`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ArchetypeStats {
+    /// Most common first; archetypes of equal count by ascending component ids.
+    pub archetypes: Vec<Archetype>,
+}
+
+/// One combination of components and the number of entities holding exactly it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Archetype {
+    /// Component ids in ascending order, without `Entity`.
+    pub components: Vec<ComponentId>,
+    /// Type names of `components`, in the same order.
+    pub names: Vec<&'static str>,
+    pub count: u32,
+}
+
+impl ArchetypeStats {
+    /// Returns the number of entities holding exactly `components` (in any order).
+    pub fn count_of(&self, components: &[ComponentId]) -> u32 {
+        let mut components = components.to_vec();
+        components.sort_unstable();
+        self.archetypes
+            .iter()
+            .find(|a| a.components == components)
+            .map_or(0, |a| a.count)
+    }
+
+    /// Returns the number of entities counted.
+    pub fn entity_count(&self) -> u32 {
+        self.archetypes.iter().map(|a| a.count).sum()
+    }
+}
+
+impl fmt::Display for ArchetypeStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for a in &self.archetypes {
+            writeln!(f, "{} x [{}]", a.count, a.names.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Ascending iterator over the component ids of one signature.
+pub struct SignatureIter {
+    words: Signature,
+    word: usize,
+    /// Bits of `words[word]` not yielded yet.
+    bits: u64,
+}
+
+impl SignatureIter {
+    fn new(words: Signature) -> Self {
+        Self {
+            bits: words[0],
+            words,
+            word: 0,
+        }
+    }
+}
+
+impl Iterator for SignatureIter {
+    type Item = ComponentId;
+
+    fn next(&mut self) -> Option<ComponentId> {
+        while self.bits == 0 {
+            self.word += 1;
+            if self.word == WORDS {
+                self.word -= 1;
+                return None;
+            }
+            self.bits = self.words[self.word];
+        }
+        let bit = self.bits.trailing_zeros();
+        self.bits &= self.bits - 1;
+        Some((self.word as u32) << 6 | bit)
+    }
+}
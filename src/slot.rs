@@ -0,0 +1,204 @@
+//! How chunks hold component values.
+//!
+//! A chunk keeps the 64 values of its slots in one array, so iterating a component
+//! walks contiguous memory. For large components (a few hundred bytes and up) that
+//! makes every chunk tens of kilobytes, even one holding a single value, and spreads
+//! its masks and the values a query touches over many pages. Such components can be
+//! stored out of line instead:
+//!
+//! ```ignore
+//! #[derive(Clone, Component)]
+//! #[component(out_of_line)]
+//! struct Inventory {
+//!     items: [ItemStack; 64],
+//! }
+//! ```
+//!
+//! Their chunks then hold one pointer per slot, to a value allocated from a pool of
+//! blocks of its size that freed values return to. Views and storage accessors work
+//! the same either way; only the chunk's memory layout changes.
+
+use crate::component::Component;
+use std::alloc::Layout;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::mem::MaybeUninit;
+use std::ptr::NonNull;
+
+/// The representation of a stored value: the value itself (`Inline`) or a pointer to
+/// it (`OutOfLine`). Selected per component with `Component::Repr`.
+pub trait SlotRepr<T>: Sized + 'static {
+    fn new(value: T) -> Self;
+    fn into_inner(self) -> T;
+    fn get(&self) -> &T;
+    fn get_mut(&mut self) -> &mut T;
+}
+
+/// Values stored in the chunk itself; the default.
+#[repr(transparent)]
+pub struct Inline<T>(T);
+
+impl<T: 'static> SlotRepr<T> for Inline<T> {
+    #[inline(always)]
+    fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    #[inline(always)]
+    fn into_inner(self) -> T {
+        self.0
+    }
+
+    #[inline(always)]
+    fn get(&self) -> &T {
+        &self.0
+    }
+
+    #[inline(always)]
+    fn get_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+/// Values stored in pooled blocks the chunk points to; `#[component(out_of_line)]`.
+pub struct OutOfLine<T>(NonNull<T>);
+
+// Safety: an OutOfLine owns its value like a Box.
+unsafe impl<T: Send> Send for OutOfLine<T> {}
+unsafe impl<T: Sync> Sync for OutOfLine<T> {}
+
+impl<T: 'static> SlotRepr<T> for OutOfLine<T> {
+    fn new(value: T) -> Self {
+        let ptr = pool_alloc(Layout::new::<T>()).cast::<T>();
+        unsafe { ptr.write(value) };
+        Self(ptr)
+    }
+
+    fn into_inner(self) -> T {
+        let ptr = self.0;
+        std::mem::forget(self);
+        let value = unsafe { ptr.read() };
+        pool_free(ptr.cast(), Layout::new::<T>());
+        value
+    }
+
+    #[inline(always)]
+    fn get(&self) -> &T {
+        unsafe { self.0.as_ref() }
+    }
+
+    #[inline(always)]
+    fn get_mut(&mut self) -> &mut T {
+        unsafe { self.0.as_mut() }
+    }
+}
+
+impl<T> Drop for OutOfLine<T> {
+    fn drop(&mut self) {
+        unsafe { self.0.drop_in_place() };
+        pool_free(self.0.cast(), Layout::new::<T>());
+    }
+}
+
+/// Freed blocks kept per layout and thread before they go back to the allocator.
+const POOL_BLOCKS: usize = 1024;
+
+thread_local! {
+    static POOL: RefCell<BlockPool> = RefCell::new(BlockPool::default());
+}
+
+#[derive(Default)]
+struct BlockPool {
+    free: HashMap<Layout, Vec<NonNull<u8>>>,
+}
+
+impl Drop for BlockPool {
+    fn drop(&mut self) {
+        for (layout, blocks) in self.free.drain() {
+            for block in blocks {
+                unsafe { std::alloc::dealloc(block.as_ptr(), layout) };
+            }
+        }
+    }
+}
+
+fn pool_alloc(layout: Layout) -> NonNull<u8> {
+    if layout.size() == 0 {
+        return unsafe { NonNull::new_unchecked(layout.align() as *mut u8) };
+    }
+    let pooled = POOL
+        .try_with(|pool| pool.borrow_mut().free.get_mut(&layout)?.pop())
+        .ok()
+        .flatten();
+    pooled.unwrap_or_else(|| {
+        let ptr = unsafe { std::alloc::alloc(layout) };
+        NonNull::new(ptr).unwrap_or_else(|| std::alloc::handle_alloc_error(layout))
+    })
+}
+
+fn pool_free(block: NonNull<u8>, layout: Layout) {
+    if layout.size() == 0 {
+        return;
+    }
+    // Blocks freed on another thread, or once the pool is gone, join that thread's
+    // pool or go straight back to the allocator.
+    let pooled = POOL
+        .try_with(|pool| {
+            let mut pool = pool.borrow_mut();
+            let blocks = pool.free.entry(layout).or_default();
+            let keep = blocks.len() < POOL_BLOCKS;
+            if keep {
+                blocks.push(block);
+            }
+            keep
+        })
+        .unwrap_or(false);
+    if !pooled {
+        unsafe { std::alloc::dealloc(block.as_ptr(), layout) };
+    }
+}
+
+/// One of a chunk's 64 value slots. Only slots set in the chunk's `presence_mask` are
+/// initialized; the methods mirror those of `MaybeUninit<T>`.
+#[repr(transparent)]
+pub struct Slot<T: Component>(MaybeUninit<T::Repr>);
+
+impl<T: Component> Slot<T> {
+    pub const UNINIT: Self = Self(MaybeUninit::uninit());
+
+    /// Stores `value`, without dropping a value the slot may hold.
+    #[inline(always)]
+    pub fn write(&mut self, value: T) -> &mut T {
+        self.0.write(T::Repr::new(value)).get_mut()
+    }
+
+    /// # Safety
+    /// The slot must be initialized.
+    #[inline(always)]
+    pub unsafe fn assume_init_ref(&self) -> &T {
+        unsafe { self.0.assume_init_ref() }.get()
+    }
+
+    /// # Safety
+    /// The slot must be initialized.
+    #[inline(always)]
+    pub unsafe fn assume_init_mut(&mut self) -> &mut T {
+        unsafe { self.0.assume_init_mut() }.get_mut()
+    }
+
+    /// Moves the value out, leaving the slot uninitialized.
+    ///
+    /// # Safety
+    /// The slot must be initialized.
+    #[inline(always)]
+    pub unsafe fn assume_init_read(&self) -> T {
+        unsafe { self.0.assume_init_read() }.into_inner()
+    }
+
+    /// # Safety
+    /// The slot must be initialized.
+    #[inline(always)]
+    pub unsafe fn assume_init_drop(&mut self) {
+        unsafe { self.0.assume_init_drop() }
+    }
+}
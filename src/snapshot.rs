@@ -0,0 +1,89 @@
+//! Read-only views of a world between ticks.
+//!
+//! `World::snapshot` borrows the world immutably, so nothing can write to it (and no
+//! changed mask or rollback snapshot can move) while the handle lives. The handle is
+//! `Send` and `Sync`, which lets render, UI or serialization code read the finished
+//! tick on a scoped thread while the caller prepares the next tick's input:
+//!
+//! ```ignore
+//! std::thread::scope(|scope| {
+//!     let snapshot = world.snapshot();
+//!     scope.spawn(move || render(snapshot));
+//!     input = poll_input();
+//! });
+//! world.run();
+//! ```
+//!
+//! Values are only handed out for `Sync` component types, since they are read from
+//! other threads.
+
+use crate::component::Component;
+use crate::resource::RESOURCE_INDEX;
+use crate::storage::Storage;
+use crate::tick::Tick;
+use crate::world::World;
+
+/// Read-only handle to a world at the tick it was taken; see `decs::snapshot`.
+#[derive(Clone, Copy)]
+pub struct WorldSnapshot<'a> {
+    world: &'a World,
+    tick: Tick,
+}
+
+// Safety: the handle only reads, and only exposes values of `Sync` component types.
+unsafe impl Send for WorldSnapshot<'_> {}
+unsafe impl Sync for WorldSnapshot<'_> {}
+
+impl<'a> WorldSnapshot<'a> {
+    pub(crate) fn new(world: &'a World) -> Self {
+        Self {
+            world,
+            tick: world.current_tick(),
+        }
+    }
+
+    /// Returns the tick the world had completed when the snapshot was taken.
+    pub fn tick(&self) -> Tick {
+        self.tick
+    }
+
+    /// Returns the seed the world was created with (`World::new_with_seed`).
+    pub fn seed(&self) -> Option<u64> {
+        self.world.seed()
+    }
+
+    fn storage<T: Component + Sync>(&self) -> Option<&'a Storage<T>> {
+        self.world
+            .existing_storage::<T>()
+            .map(|storage| unsafe { &*storage })
+    }
+
+    /// Returns the `T` component at `index`.
+    pub fn get<T: Component + Sync>(&self, index: u32) -> Option<&'a T> {
+        self.storage::<T>()?.get(index)
+    }
+
+    /// Iterates the present `T` components with their indices, in index order.
+    pub fn iter<T: Component + Sync>(&self) -> impl Iterator<Item = (u32, &'a T)> + use<'a, T> {
+        let storage = self.storage::<T>();
+        let mut cursor = storage.map(Storage::cursor);
+        std::iter::from_fn(move || {
+            let storage = storage?;
+            let index = cursor.as_mut()?.next(storage)?;
+            Some((index, storage.get(index)?))
+        })
+    }
+
+    /// Returns the `T` resource.
+    pub fn resource<T: Component + Sync>(&self) -> Option<&'a T> {
+        self.get::<T>(RESOURCE_INDEX)
+    }
+
+    /// Returns true if the `T` component at `index` was created, modified or removed in
+    /// the snapshot's tick, as recorded for rollback.
+    pub fn changed_this_tick<T: Component + Sync>(&self, index: u32) -> bool {
+        self.storage::<T>()
+            .and_then(|storage| storage.last_changed_tick(index))
+            == Some(self.tick)
+    }
+}
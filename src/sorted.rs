@@ -0,0 +1,174 @@
+//! Iteration in ascending order of a key component, e.g. a render layer or priority.
+//!
+//! `SortedIndexSystem<K>` keeps a `SortedIndex` of every present `K` in sync with its
+//! storage, the way `SpatialIndexSystem` does: each run re-reads the indices the rollback
+//! snapshots recorded as created, changed or removed since the last one, and rebuilds
+//! after a rollback or once that history was discarded. Other storages are then
+//! iterated in key order with `Storage::iter_sorted_by`:
+//!
+//! ```ignore
+//! let layers = world.scheduler().get_system::<SortedIndexSystem<Layer>>().unwrap();
+//! for (index, sprite) in sprites.iter_sorted_by(layers.index()) { /* ... */ }
+//! ```
+//!
+//! The system runs in `HierarchyGroup`; systems in `SimulationGroup` see the order of
+//! the previous tick.
+
+use crate::component::Component;
+use crate::storage::Storage;
+use crate::system::{System, SystemGroup};
+use crate::tick::Tick;
+use crate::world::{HierarchyGroup, World};
+use std::any::{Any, TypeId};
+use std::cell::UnsafeCell;
+use std::collections::{BTreeSet, HashMap};
+
+/// Indices ordered by their key, ties broken by index.
+pub struct SortedIndex<K: Ord + Clone> {
+    order: BTreeSet<(K, u32)>,
+    keys: HashMap<u32, K>,
+}
+
+impl<K: Ord + Clone> Default for SortedIndex<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + Clone> SortedIndex<K> {
+    pub fn new() -> Self {
+        Self {
+            order: BTreeSet::new(),
+            keys: HashMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Returns the indexed key of `index`.
+    pub fn key(&self, index: u32) -> Option<&K> {
+        self.keys.get(&index)
+    }
+
+    /// Inserts `index` with `key`, moving it if it was already indexed.
+    pub fn insert(&mut self, index: u32, key: K) {
+        if let Some(old) = self.keys.get(&index) {
+            if *old == key {
+                return;
+            }
+            self.order.remove(&(old.clone(), index));
+        }
+        self.order.insert((key.clone(), index));
+        self.keys.insert(index, key);
+    }
+
+    /// Removes `index`, returning whether it was indexed.
+    pub fn remove(&mut self, index: u32) -> bool {
+        match self.keys.remove(&index) {
+            Some(old) => {
+                self.order.remove(&(old, index));
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.order.clear();
+        self.keys.clear();
+    }
+
+    /// Iterates the indices in ascending key order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = u32> + '_ {
+        self.order.iter().map(|&(_, index)| index)
+    }
+
+    /// Iterates the indices and their keys in ascending key order.
+    pub fn iter_with_keys(&self) -> impl DoubleEndedIterator<Item = (u32, &K)> + '_ {
+        self.order.iter().map(|(key, index)| (*index, key))
+    }
+}
+
+pub struct SortedIndexSystem<K: Component + Ord> {
+    reads: [TypeId; 1],
+    storage: *mut Storage<K>,
+    index: UnsafeCell<SortedIndex<K>>,
+    synced: UnsafeCell<Option<Tick>>,
+}
+
+// Safety: like the other systems, the storage pointer and the index are only touched
+// from `run`, which the scheduler never calls concurrently for the same system.
+unsafe impl<K: Component + Ord> Send for SortedIndexSystem<K> {}
+unsafe impl<K: Component + Ord> Sync for SortedIndexSystem<K> {}
+
+impl<K: Component + Ord> SortedIndexSystem<K> {
+    pub fn new(world: &mut World) -> Self {
+        Self {
+            reads: [TypeId::of::<K>()],
+            storage: world.get_storage::<K>(),
+            index: UnsafeCell::new(SortedIndex::new()),
+            synced: UnsafeCell::new(None),
+        }
+    }
+
+    /// The index as of the last run.
+    pub fn index(&self) -> &SortedIndex<K> {
+        unsafe { &*self.index.get() }
+    }
+
+    /// Rebuilds the index from the storage, e.g. after restoring a world outside `run`.
+    pub fn rebuild(&self) {
+        let storage = unsafe { &*self.storage };
+        let sorted = unsafe { &mut *self.index.get() };
+        sorted.clear();
+        let mut cursor = storage.cursor();
+        while let Some(index) = cursor.next(storage) {
+            sorted.insert(index, storage.get(index).unwrap().clone());
+        }
+    }
+
+    fn sync(&self, tick: Tick) {
+        let storage = unsafe { &*self.storage };
+        let synced = unsafe { &mut *self.synced.get() };
+        let last = synced.replace(tick);
+
+        let sorted = unsafe { &mut *self.index.get() };
+        let refresh = |index: u32| match storage.get(index) {
+            Some(key) => sorted.insert(index, key.clone()),
+            None => {
+                sorted.remove(index);
+            }
+        };
+        let synced_incrementally = last.is_some_and(|last| {
+            tick.is_after(last) && storage.for_each_touched_since(last, refresh)
+        });
+        if !synced_incrementally {
+            self.rebuild();
+        }
+    }
+}
+
+impl<K: Component + Ord> System for SortedIndexSystem<K> {
+    fn run(&self, frame: &crate::frame::Frame) {
+        crate::aliasing::track_borrow::<K>(self.name(), false);
+        self.sync(frame.current_tick);
+    }
+
+    fn reads(&self) -> &[TypeId] {
+        &self.reads
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn parent(&self) -> Option<&dyn SystemGroup> {
+        Some(HierarchyGroup::instance())
+    }
+}
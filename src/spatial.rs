@@ -0,0 +1,219 @@
+//! Uniform-grid spatial index over a position component.
+//!
+//! `SpatialIndexSystem<T>` keeps a `SpatialGrid` of every present `T` in sync with its
+//! storage. There are no per-write hooks in the storage, so the system reads the
+//! rollback snapshots instead: every index a tick created, changed or removed is
+//! re-inserted from (or dropped according to) the live storage. When the tick did not
+//! move forward since the last sync (a rollback or resimulation) or the needed history
+//! was already discarded, the grid is rebuilt from scratch.
+//!
+//! The system runs in `HierarchyGroup`, after the simulation systems that move things;
+//! those systems see the grid of the previous tick. Look it up with
+//! `world.scheduler().get_system::<SpatialIndexSystem<T>>()`.
+
+use crate::component::Component;
+use crate::storage::Storage;
+use crate::system::{System, SystemGroup};
+use crate::tick::Tick;
+use crate::world::{HierarchyGroup, World};
+use std::any::{Any, TypeId};
+use std::cell::UnsafeCell;
+use std::collections::HashMap;
+
+/// Components that have a 2D position the spatial index can use.
+pub trait SpatialPosition: Component {
+    fn spatial_position(&self) -> [f32; 2];
+}
+
+/// Buckets indices into square cells of `cell_size`. Queries only visit the cells
+/// overlapping the query area and return indices in ascending order.
+pub struct SpatialGrid {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<u32>>,
+    entries: HashMap<u32, [f32; 2]>,
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: f32) -> Self {
+        assert!(cell_size > 0.0, "cell_size must be positive");
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+            entries: HashMap::new(),
+        }
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the indexed position of `index`.
+    pub fn position(&self, index: u32) -> Option<[f32; 2]> {
+        self.entries.get(&index).copied()
+    }
+
+    fn cell_of(&self, position: [f32; 2]) -> (i32, i32) {
+        (
+            (position[0] / self.cell_size).floor() as i32,
+            (position[1] / self.cell_size).floor() as i32,
+        )
+    }
+
+    /// Inserts `index` at `position`, moving it if it was already indexed.
+    pub fn insert(&mut self, index: u32, position: [f32; 2]) {
+        let cell = self.cell_of(position);
+        if let Some(old) = self.entries.insert(index, position) {
+            let old_cell = self.cell_of(old);
+            if old_cell == cell {
+                return;
+            }
+            self.remove_from_cell(old_cell, index);
+        }
+        self.cells.entry(cell).or_default().push(index);
+    }
+
+    /// Removes `index`, returning whether it was indexed.
+    pub fn remove(&mut self, index: u32) -> bool {
+        match self.entries.remove(&index) {
+            Some(old) => {
+                self.remove_from_cell(self.cell_of(old), index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn remove_from_cell(&mut self, cell: (i32, i32), index: u32) {
+        if let Some(bucket) = self.cells.get_mut(&cell) {
+            bucket.retain(|&i| i != index);
+            if bucket.is_empty() {
+                self.cells.remove(&cell);
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.entries.clear();
+    }
+
+    /// Indices whose position lies inside the box `min..=max`.
+    pub fn query_aabb(&self, min: [f32; 2], max: [f32; 2]) -> Vec<u32> {
+        self.query(min, max, |p| {
+            p[0] >= min[0] && p[0] <= max[0] && p[1] >= min[1] && p[1] <= max[1]
+        })
+    }
+
+    /// Indices whose position is within `radius` of `center`.
+    pub fn query_radius(&self, center: [f32; 2], radius: f32) -> Vec<u32> {
+        let min = [center[0] - radius, center[1] - radius];
+        let max = [center[0] + radius, center[1] + radius];
+        self.query(min, max, |p| {
+            let (dx, dy) = (p[0] - center[0], p[1] - center[1]);
+            dx * dx + dy * dy <= radius * radius
+        })
+    }
+
+    fn query(&self, min: [f32; 2], max: [f32; 2], hit: impl Fn([f32; 2]) -> bool) -> Vec<u32> {
+        let (min_x, min_y) = self.cell_of(min);
+        let (max_x, max_y) = self.cell_of(max);
+        let mut out = Vec::new();
+        for x in min_x..=max_x {
+            for y in min_y..=max_y {
+                if let Some(bucket) = self.cells.get(&(x, y)) {
+                    out.extend(bucket.iter().copied().filter(|i| hit(self.entries[i])));
+                }
+            }
+        }
+        out.sort_unstable();
+        out
+    }
+}
+
+pub struct SpatialIndexSystem<T: SpatialPosition> {
+    reads: [TypeId; 1],
+    storage: *mut Storage<T>,
+    grid: UnsafeCell<SpatialGrid>,
+    synced: UnsafeCell<Option<Tick>>,
+}
+
+// Safety: like the other systems, the storage pointer and the grid are only touched
+// from `run`, which the scheduler never calls concurrently for the same system.
+unsafe impl<T: SpatialPosition> Send for SpatialIndexSystem<T> {}
+unsafe impl<T: SpatialPosition> Sync for SpatialIndexSystem<T> {}
+
+impl<T: SpatialPosition> SpatialIndexSystem<T> {
+    pub fn new(world: &mut World, cell_size: f32) -> Self {
+        Self {
+            reads: [TypeId::of::<T>()],
+            storage: world.get_storage::<T>(),
+            grid: UnsafeCell::new(SpatialGrid::new(cell_size)),
+            synced: UnsafeCell::new(None),
+        }
+    }
+
+    /// The grid as of the last run.
+    pub fn grid(&self) -> &SpatialGrid {
+        unsafe { &*self.grid.get() }
+    }
+
+    /// Rebuilds the grid from the storage, e.g. after restoring a world outside `run`.
+    pub fn rebuild(&self) {
+        let storage = unsafe { &*self.storage };
+        let grid = unsafe { &mut *self.grid.get() };
+        grid.clear();
+        let mut cursor = storage.cursor();
+        while let Some(index) = cursor.next(storage) {
+            grid.insert(index, storage.get(index).unwrap().spatial_position());
+        }
+    }
+
+    fn sync(&self, tick: Tick) {
+        let storage = unsafe { &*self.storage };
+        let synced = unsafe { &mut *self.synced.get() };
+        let last = synced.replace(tick);
+
+        // Indices touched since the last sync, including the rest of that tick after
+        // the previous run; refreshing an index twice is harmless.
+        let grid = unsafe { &mut *self.grid.get() };
+        let refresh = |index: u32| match storage.get(index) {
+            Some(value) => grid.insert(index, value.spatial_position()),
+            None => {
+                grid.remove(index);
+            }
+        };
+        let synced_incrementally = last.is_some_and(|last| {
+            tick.is_after(last) && storage.for_each_touched_since(last, refresh)
+        });
+        if !synced_incrementally {
+            self.rebuild();
+        }
+    }
+}
+
+impl<T: SpatialPosition> System for SpatialIndexSystem<T> {
+    fn run(&self, frame: &crate::frame::Frame) {
+        crate::aliasing::track_borrow::<T>(self.name(), false);
+        self.sync(frame.current_tick);
+    }
+
+    fn reads(&self) -> &[TypeId] {
+        &self.reads
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn parent(&self) -> Option<&dyn SystemGroup> {
+        Some(HierarchyGroup::instance())
+    }
+}
@@ -1,8 +1,22 @@
+use crate::audit::{self, StructuralOp};
+use crate::bitmap::HierBitmap;
 use crate::component::Component;
-use crate::rollback::{RollbackStorage, VecQueue};
-use crate::tick::Tick;
-use std::any::Any;
-use std::mem::MaybeUninit;
+use crate::error::{Error, Result};
+use crate::fields::{FieldChanges, FieldSetter};
+use crate::invariants::{InvariantLevel, InvariantReport, InvariantViolation};
+use crate::layout::ChunkLayout;
+use crate::leak::{AllocKind, record_alloc, record_free};
+use crate::net::{Divergence, DivergenceKind};
+use crate::rollback::{MAX_HISTORY, RollbackChunk, RollbackStorage, SnapshotPolicy, VecQueue};
+use crate::shadow::Shadow;
+use crate::signature::Signatures;
+use crate::slot::Slot;
+use crate::sorted::SortedIndex;
+use crate::tick::{ChangeTick, Tick};
+use crate::world::World;
+use std::alloc::{Layout, handle_alloc_error};
+use std::any::{Any, TypeId};
+use std::sync::atomic::{AtomicU32, Ordering};
 
 /// Trait for storage-like structures that can verify their invariants.
 pub trait StorageLike: Any {
@@ -11,14 +25,107 @@ pub trait StorageLike: Any {
     /// Returns true if all invariants are satisfied, false otherwise.
     fn verify_invariants(&self) -> bool;
 
+    /// Like `verify_invariants`, but records every failing storage/page/chunk of both the
+    /// live storage and its rollback snapshots into `report` instead of stopping at the first.
+    fn verify_report(&self, report: &mut InvariantReport);
+
+    /// Returns the `TypeId` of the stored component type.
+    fn component_type_id(&self) -> TypeId;
+
+    /// Returns the type name of the stored component type.
+    fn component_name(&self) -> &'static str;
+
     fn changed_mask_zero(&self) -> bool;
 
     fn clear_changed_masks_all_levels(&mut self);
 
+    /// Clears changed masks on every present page and chunk, even where the
+    /// storage/page level bits were never propagated (e.g. after a system panicked).
+    fn reset_changed_masks(&mut self);
+
+    /// Rotates the rollback snapshot to `tick`.
+    /// This is a type-erased method that internally calls Storage<T>::ensure_rollback_tick.
+    fn rotate_rollback_tick(&mut self, tick: Tick);
+
     /// Rolls back this storage to the specified tick.
     /// This is a type-erased method that internally calls Storage<T>::rollback.
     fn rollback(&mut self, target_tick: Tick);
 
+    /// Returns the newest tick whose rollback snapshot was discarded from history, if any.
+    fn history_floor(&self) -> Option<Tick>;
+
+    /// Type-erased `Storage::trim_history`.
+    fn trim_history(&mut self, depth: usize);
+
+    /// Type-erased `Storage::pack_history`.
+    fn pack_history(&mut self);
+
+    /// Type-erased `Storage::compact`.
+    fn compact(&mut self) -> usize;
+
+    /// Returns how many rollback snapshots are pooled for reuse.
+    fn pooled_snapshots(&self) -> usize;
+
+    /// Type-erased `Storage::release_pooled`.
+    fn release_pooled(&mut self, max: usize) -> usize;
+
+    /// Type-erased `Storage::history_through`.
+    fn history_through(&self, tick: Tick) -> usize;
+
+    /// Type-erased `Storage::discard_history_through`.
+    fn discard_history_through(&mut self, tick: Tick, max: usize) -> usize;
+
+    /// Type-erased `Storage::structure_generation`.
+    fn structure_generation(&self) -> u64;
+
+    /// Returns true if a snapshot newer than `tick` recorded changes, i.e. rolling back
+    /// to `tick` has values to restore.
+    fn has_changes_after(&self, tick: Tick) -> bool;
+
+    /// Appends the indices where this storage and `other` (a storage of the same
+    /// component from another world, or None if that world has none) differ, up to `limit`
+    /// entries in `out`. `self_is_a` tells which side of the divergences `self` is.
+    fn diff_into(
+        &self,
+        other: Option<&dyn StorageLike>,
+        self_is_a: bool,
+        limit: usize,
+        out: &mut Vec<Divergence>,
+    );
+
+    /// FNV-1a hash of the present indices and, for `#[component(diff)]` types, of the
+    /// formatted values; the same state `diff_into` compares.
+    fn checksum(&self) -> u64;
+
+    /// Removes the value at `index` (recorded in `frame`) and sets it at the same index
+    /// of `dst`'s storage for the type, in `dst`'s current tick. Returns false if no
+    /// value was present.
+    fn move_to(&mut self, frame: &crate::frame::Frame, index: u32, dst: &mut World) -> bool;
+
+    /// Fills `dst`'s (empty) storage for the type with this storage's values by sharing
+    /// its chunks; see `Storage::share_into`.
+    fn branch_into(&self, dst: &mut World);
+
+    /// Brings `dst`'s storage for the type up to date with the values written here
+    /// after change tick `since`; see `Storage::extract_changed_into`.
+    fn extract_changed_into(&self, since: Tick, dst: &mut World);
+
+    /// Type-erased `Storage::remove`.
+    fn remove(&mut self, frame: &crate::frame::Frame, index: u32) -> bool;
+
+    /// Sets a clone of the value at `src` at `dst`, recorded in `frame`. Returns false
+    /// if no value was present at `src`.
+    fn clone_value(&mut self, frame: &crate::frame::Frame, src: u32, dst: u32) -> bool;
+
+    /// Type-erased `Storage::for_each_touched_at`.
+    fn for_each_touched_at(&self, tick: Tick, f: &mut dyn FnMut(u32, bool, bool));
+
+    /// Returns `Component::EVENT` of the stored component type.
+    fn is_event(&self) -> bool;
+
+    /// Returns `Component::RESOURCE` of the stored component type.
+    fn is_resource(&self) -> bool;
+
     /// Returns a reference to the underlying Any trait object for downcasting.
     fn as_any(&self) -> &dyn Any;
 
@@ -51,65 +158,496 @@ pub struct Storage<T: Component> {
     pub fullness_mask: u64,
     pub changed_mask: u64,
     pub count: u32,
-    pub rollback: Box<RollbackStorage<T>>,
-    pub prev: VecQueue<Box<RollbackStorage<T>>>,
-    pub rollback_pool: Vec<Box<RollbackStorage<T>>>,
+    pub rollback: RollbackStorage<T>,
+    pub prev: VecQueue<RollbackStorage<T>>,
+    pub rollback_pool: Vec<RollbackStorage<T>>,
     pub data: [*mut Page<T>; 64],
     pub generation: u64,
     pub default_chunk_ptr: *const Chunk<T>,
     pub default_page_ptr: *const Page<T>,
+    /// Tick of the newest snapshot dropped from `prev` once the history limit was hit.
+    pub history_floor: Option<Tick>,
+    /// How old values are recorded for rollback; see `SnapshotPolicy`.
+    pub snapshot_policy: SnapshotPolicy,
+    /// Bumped whenever a page or chunk is created or dropped, or a page-level fullness
+    /// bit changes. Generated systems key their cached query plans on it.
+    pub structure_version: u64,
+    /// Newest change tick stamped on each page; see `Chunk::changed_ticks`.
+    pub changed_ticks: [Tick; 64],
+    /// Whether history snapshots are packed into byte diffs; see `enable_delta_rollback`.
+    pub delta_rollback: bool,
+    /// Entity signatures of the owning World, kept up to date on every insert and
+    /// removal; null for storages created outside a World.
+    pub signatures: *mut Signatures,
+    /// Change counter of the owning World (`World::change_tick`) writes are stamped
+    /// with; null for storages created outside a World, which stamp `Tick(1)`.
+    pub change_tick: *const ChangeTick,
+    /// Allocator and alignment of the chunks; see `set_chunk_layout`.
+    pub chunk_layout: ChunkLayout,
+    /// Naive copy of the values checked against the storage; empty without the
+    /// `shadow-world` feature. See `decs::shadow`.
+    pub shadow: Shadow<T>,
+    /// Fields written per value, once `set_field` was used; see `decs::fields`.
+    pub(crate) fields: FieldChanges,
 }
 
 impl<T: Component> Storage<T> {
+    /// All ones when `T` keeps change detection, zero when it opts out, so that
+    /// `changed_mask |= bits & CHANGE_BITS` compiles away for opted-out components.
+    pub const CHANGE_BITS: u64 = if T::CHANGE_DETECTION { u64::MAX } else { 0 };
+
     /// Creates a new empty Storage instance.
     pub fn new() -> Self {
         // Allocate default chunk (will be leaked intentionally as static default)
         let default_chunk_ptr: *const Chunk<T> = Box::into_raw(Box::new(Chunk::<T>::new()));
 
         // Allocate default page (will be leaked intentionally as static default)
-        let default_page_ptr: *const Page<T> = Box::into_raw(Box::new(Page::<T> {
-            presence_mask: 0,
-            fullness_mask: 0,
-            changed_mask: 0,
-            count: 0,
-            data: [default_chunk_ptr as *mut Chunk<T>; 64],
-        }));
+        let default_page_ptr: *const Page<T> =
+            Box::into_raw(Box::new(Page::<T>::new(default_chunk_ptr)));
         Self {
             presence_mask: 0,
             fullness_mask: 0,
             changed_mask: 0,
             count: 0,
-            rollback: Box::new(RollbackStorage::new()),
+            rollback: RollbackStorage::new(),
             prev: VecQueue::new(),
             rollback_pool: Vec::new(),
             data: [default_page_ptr as *mut Page<T>; 64],
             generation: 1,
             default_chunk_ptr,
             default_page_ptr,
+            history_floor: None,
+            snapshot_policy: SnapshotPolicy::PerItem,
+            structure_version: 0,
+            changed_ticks: [Tick(0); 64],
+            delta_rollback: false,
+            signatures: std::ptr::null_mut(),
+            change_tick: std::ptr::null(),
+            chunk_layout: ChunkLayout::new(),
+            shadow: Shadow::new(),
+            fields: FieldChanges::default(),
+        }
+    }
+
+    /// Invalidates query plans cached against this storage's page/chunk layout.
+    #[inline(always)]
+    pub fn mark_structure_changed(&mut self) {
+        self.structure_version = self.structure_version.wrapping_add(1);
+    }
+
+    /// Returns the value writes are currently stamped with; see `change_tick`.
+    #[inline(always)]
+    fn current_change_tick(&self) -> Tick {
+        unsafe { self.change_tick.as_ref() }.map_or(Tick(1), ChangeTick::get)
+    }
+
+    /// Records in the owning World's entity signatures that `index` gained (`present`)
+    /// or lost its value.
+    #[inline(always)]
+    pub(crate) fn note_presence(&self, index: u32, present: bool) {
+        note_presence::<T>(self.signatures, index, present);
+        self.shadow.mirror(index, present, || self.get(index));
+    }
+
+    /// Checks the storage against its shadow and moves the shadow to `tick` when the
+    /// storage starts recording it (feature `shadow-world`).
+    #[inline(always)]
+    fn begin_shadow_tick(&self, tick: Tick) {
+        if self.shadow.needs_tick(tick) {
+            self.check_shadow(true);
+            self.shadow.begin_tick(tick);
+        }
+    }
+
+    /// Compares every value with the shadow (feature `shadow-world`), first taking over
+    /// the values written in place since the changed masks were last cleared if
+    /// `adopt_changed` is set. Panics at the first index where the two disagree.
+    #[inline(always)]
+    fn check_shadow(&self, adopt_changed: bool) {
+        #[cfg(feature = "shadow-world")]
+        {
+            let present = self.presence_bitmap();
+            for index in present.iter() {
+                let page = unsafe { &*self.data[(index >> 12) as usize] };
+                let chunk = unsafe { &*page.data[((index >> 6) & 63) as usize] };
+                let chunk_idx = (index & 63) as usize;
+                // Without change detection any value may have been written in place.
+                let changed = !T::CHANGE_DETECTION || (chunk.changed_mask >> chunk_idx) & 1 != 0;
+                let value = unsafe { chunk.data[chunk_idx].assume_init_ref() };
+                self.shadow.check(index, value, adopt_changed && changed);
+            }
+            self.shadow
+                .check_count(present.len(), |index| present.contains(index));
+        }
+        #[cfg(not(feature = "shadow-world"))]
+        let _ = adopt_changed;
+    }
+
+    /// Rebuilds the shadow from the values, recorded for `tick` (feature `shadow-world`).
+    fn reset_shadow(&self, tick: Tick) {
+        #[cfg(feature = "shadow-world")]
+        self.shadow.reset(
+            tick,
+            self.presence_bitmap()
+                .iter()
+                .filter_map(|index| Some((index, self.get(index)?))),
+        );
+        #[cfg(not(feature = "shadow-world"))]
+        let _ = tick;
+    }
+
+    /// Returns a counter that increases whenever a page or chunk is allocated or freed
+    /// (including by rollback) or a chunk becomes or stops being full.
+    ///
+    /// Caches derived from the storage's layout (query plans, spatial indices, render
+    /// extraction) can remember it and rebuild only when it moved, instead of diffing
+    /// masks. Writing a value into an existing slot of a non-full chunk leaves it as is.
+    #[inline(always)]
+    pub fn structure_generation(&self) -> u64 {
+        self.structure_version
+    }
+
+    /// Packs this storage's history snapshots into byte diffs against the values that
+    /// followed them (see `PackedRollback`), trading CPU in `pack_history` and `rollback`
+    /// for rollback memory. Meant for large components of which a tick changes few bytes.
+    ///
+    /// # Safety
+    ///
+    /// `T` must have no padding bytes, since every byte of its values is read.
+    pub unsafe fn enable_delta_rollback(&mut self)
+    where
+        T: Copy,
+    {
+        self.delta_rollback = true;
+    }
+
+    /// With delta rollback enabled, packs the history snapshots recorded since the last
+    /// call. `EndOfTickSystem` calls it every tick, once every write of the tick has been
+    /// recorded, so live values agree with the history.
+    pub fn pack_history(&mut self) {
+        if !self.delta_rollback {
+            return;
+        }
+        // Packed snapshots form a prefix of history; pack the rest oldest first, so the
+        // snapshots each one is diffed against are still unpacked.
+        let mut first = self.prev.len();
+        while first > 0 && !self.prev.get(first - 1).is_some_and(|rb| rb.is_packed()) {
+            first -= 1;
+        }
+        for pos in first..self.prev.len() {
+            let snapshot = self.prev.get(pos).unwrap();
+            let packed =
+                unsafe { snapshot.encode_packed(|index| self.value_after_snapshot(pos, index)) };
+            self.prev.get_mut(pos).unwrap().set_packed(packed);
+        }
+    }
+
+    /// Unpacks every packed snapshot newer than `target_tick`, plus the newest one at or
+    /// before it, which becomes the current snapshot after rolling back. Goes newest
+    /// first, so the snapshots each one was diffed against are unpacked already.
+    fn unpack_history(&mut self, target_tick: Tick) {
+        if !self.delta_rollback {
+            return;
+        }
+        for pos in (0..self.prev.len()).rev() {
+            let snapshot = self.prev.get(pos).unwrap();
+            let reached = snapshot.tick().is_at_or_before(target_tick);
+            if snapshot.is_packed() {
+                let values = unsafe {
+                    snapshot.decode_packed(|index| self.value_after_snapshot(pos, index))
+                };
+                self.prev.get_mut(pos).unwrap().restore_packed(values);
+            }
+            if reached {
+                break;
+            }
+        }
+    }
+
+    /// The value `index` had at the end of the tick of `prev[pos]`: the old value stored
+    /// by the next newer snapshot that recorded one, else the live value.
+    fn value_after_snapshot(&self, pos: usize, index: u32) -> Option<&T> {
+        self.prev
+            .iter()
+            .skip(pos + 1)
+            .chain(std::iter::once(&self.rollback))
+            .find_map(|rb| {
+                debug_assert!(!rb.is_packed());
+                rb.stored(index)
+            })
+            .or_else(|| self.get(index))
+    }
+
+    /// Sets how old values are recorded for rollback from the next modified chunk on.
+    pub fn set_snapshot_policy(&mut self, policy: SnapshotPolicy) {
+        self.snapshot_policy = policy;
+    }
+
+    /// Allocates this storage's chunks with `layout` from now on; see `decs::layout`.
+    ///
+    /// # Panics
+    /// Panics if the storage holds values, since their chunks must be freed with the
+    /// layout they were allocated with.
+    pub fn set_chunk_layout(&mut self, layout: ChunkLayout) {
+        assert!(
+            self.presence_mask == 0,
+            "set the chunk layout of {} before inserting values",
+            std::any::type_name::<T>()
+        );
+        self.chunk_layout = layout;
+    }
+
+    /// Makes the empty `dst` hold the values of this storage by sharing its chunks
+    /// copy-on-write: each side copies a shared chunk before writing it. Chunks changed
+    /// since the last `reset_changed_masks` are copied right away, so `dst` starts with
+    /// clear changed masks. `dst` has no rollback history from before `tick`.
+    pub fn share_into(&self, dst: &mut Storage<T>, tick: Tick) {
+        assert!(
+            dst.presence_mask == 0,
+            "branch into a storage of {} holding values",
+            std::any::type_name::<T>()
+        );
+        dst.chunk_layout = self.chunk_layout;
+        dst.snapshot_policy = self.snapshot_policy;
+        dst.delta_rollback = self.delta_rollback;
+        dst.generation = self.generation;
+
+        let mut storage_mask = self.presence_mask;
+        while storage_mask != 0 {
+            let storage_idx = storage_mask.trailing_zeros() as usize;
+            storage_mask &= storage_mask - 1;
+            let page = unsafe { &*self.data[storage_idx] };
+            let mut dst_page = Box::new(Page::new(dst.default_chunk_ptr));
+            dst_page.presence_mask = page.presence_mask;
+            dst_page.fullness_mask = page.fullness_mask;
+            dst_page.count = page.count;
+            dst_page.changed_ticks = page.changed_ticks;
+            let mut page_mask = page.presence_mask;
+            while page_mask != 0 {
+                let page_idx = page_mask.trailing_zeros() as usize;
+                page_mask &= page_mask - 1;
+                let chunk = page.data[page_idx];
+                dst_page.data[page_idx] = unsafe {
+                    if (*chunk).changed_mask == 0 {
+                        (*chunk).share();
+                        chunk
+                    } else {
+                        let copy = dst.chunk_layout.alloc::<T>();
+                        (*copy).clone_from_chunk(&*chunk);
+                        copy
+                    }
+                };
+            }
+            dst.data[storage_idx] = Box::into_raw(dst_page);
+        }
+        dst.presence_mask = self.presence_mask;
+        dst.fullness_mask = self.fullness_mask;
+        dst.count = self.count;
+        dst.changed_ticks = self.changed_ticks;
+
+        dst.rollback.reset_for_tick(tick);
+        dst.history_floor = Some(tick);
+        dst.mark_structure_changed();
+        dst.reset_shadow(tick);
+    }
+
+    /// Makes `dst` (in `frame`'s tick) hold the values of this storage by copying those
+    /// stamped after change tick `since` (every value if `T` opts out of change
+    /// detection) and removing the values this storage no longer has. `dst` must only
+    /// be written this way for the result to equal this storage.
+    pub fn extract_changed_into(
+        &self,
+        since: Tick,
+        dst: &mut Storage<T>,
+        frame: &crate::frame::Frame,
+    ) {
+        let chunk_presence = |storage: &Storage<T>, storage_idx: u32, page_idx: u32| {
+            storage
+                .chunk_slice(storage_idx, page_idx)
+                .map_or(0, |(_, presence)| presence)
+        };
+
+        let mut storage_mask = dst.presence_mask;
+        while storage_mask != 0 {
+            let storage_idx = storage_mask.trailing_zeros();
+            storage_mask &= storage_mask - 1;
+            let mut page_mask = unsafe { (*dst.data[storage_idx as usize]).presence_mask };
+            while page_mask != 0 {
+                let page_idx = page_mask.trailing_zeros();
+                page_mask &= page_mask - 1;
+                let mut stale = chunk_presence(dst, storage_idx, page_idx)
+                    & !chunk_presence(self, storage_idx, page_idx);
+                while stale != 0 {
+                    let chunk_idx = stale.trailing_zeros();
+                    stale &= stale - 1;
+                    dst.remove(frame, (storage_idx << 12) | (page_idx << 6) | chunk_idx);
+                }
+            }
+        }
+
+        self.for_each_changed_since(since, |index, value| {
+            dst.set(frame, index, value.clone());
+        });
+    }
+
+    /// Calls `f` with every present value stamped after change tick `since`, in index
+    /// order; with every present value if `T` opts out of change detection.
+    pub fn for_each_changed_since(&self, since: Tick, mut f: impl FnMut(u32, &T)) {
+        let changed = |ticks: &[Tick; 64], mask: u64| {
+            if T::CHANGE_DETECTION {
+                changed_since(ticks, mask, since)
+            } else {
+                mask
+            }
+        };
+        let mut storage_mask = changed(&self.changed_ticks, self.presence_mask);
+        while storage_mask != 0 {
+            let storage_idx = storage_mask.trailing_zeros();
+            storage_mask &= storage_mask - 1;
+            let page = unsafe { &*self.data[storage_idx as usize] };
+            let mut page_mask = changed(&page.changed_ticks, page.presence_mask);
+            while page_mask != 0 {
+                let page_idx = page_mask.trailing_zeros();
+                page_mask &= page_mask - 1;
+                let chunk = unsafe { &*page.data[page_idx as usize] };
+                let mut chunk_mask = changed(&chunk.changed_ticks, chunk.presence_mask);
+                while chunk_mask != 0 {
+                    let chunk_idx = chunk_mask.trailing_zeros();
+                    chunk_mask &= chunk_mask - 1;
+                    let value = unsafe { chunk.data[chunk_idx as usize].assume_init_ref() };
+                    f((storage_idx << 12) | (page_idx << 6) | chunk_idx, value);
+                }
+            }
+        }
+    }
+
+    /// Gives this storage a private copy of the chunk at `storage_idx`/`page_idx` if it is
+    /// shared with a branch world (`World::branch`). Does nothing if the chunk is absent.
+    #[inline(always)]
+    pub fn unshare_chunk(&mut self, storage_idx: u32, page_idx: u32) {
+        if (self.presence_mask >> storage_idx) & 1 == 0 {
+            return;
+        }
+        let page = unsafe { &mut *self.data[storage_idx as usize] };
+        if (page.presence_mask >> page_idx) & 1 != 0 {
+            page.unshare_chunk(page_idx as usize, &self.chunk_layout);
+        }
+    }
+
+    /// On the first modification of the chunk at `storage_idx`/`page_idx` in the current tick,
+    /// copies the whole live chunk into the rollback snapshot if the snapshot policy picks it.
+    /// Must run before the live chunk is mutated, and before a pointer to it is taken: it
+    /// also unshares the chunk. Rotates the rollback snapshot to `tick` first.
+    #[inline(always)]
+    pub(crate) fn snapshot_chunk_on_first_touch(
+        &mut self,
+        tick: Tick,
+        storage_idx: u32,
+        page_idx: u32,
+    ) {
+        crate::tick::check_guarded_write::<T>(tick);
+        self.begin_shadow_tick(tick);
+        self.unshare_chunk(storage_idx, page_idx);
+        if self.snapshot_policy == SnapshotPolicy::PerItem {
+            return;
+        }
+        self.ensure_rollback_tick(tick);
+        let touched = self
+            .rollback
+            .get_page(storage_idx)
+            .is_some_and(|page| (page.changed_mask >> page_idx) & 1 != 0);
+        if touched || (self.presence_mask >> storage_idx) & 1 == 0 {
+            return;
+        }
+        let page = unsafe { &*self.data[storage_idx as usize] };
+        if (page.presence_mask >> page_idx) & 1 == 0 {
+            return;
+        }
+        let chunk = unsafe { &*page.data[page_idx as usize] };
+        if self
+            .snapshot_policy
+            .snapshots_chunk(chunk.presence_mask.count_ones())
+        {
+            self.rollback
+                .get_or_create_page(storage_idx)
+                .get_or_create_chunk(page_idx)
+                .snapshot_from(&chunk.data, chunk.presence_mask);
         }
     }
 
     #[inline]
     pub fn ensure_rollback_tick(&mut self, ct: Tick) {
+        if self.try_ensure_rollback_tick(ct).is_err() {
+            handle_alloc_error(Layout::new::<RollbackStorage<T>>());
+        }
+    }
+
+    /// Fallible variant of `ensure_rollback_tick`: rotates the current rollback snapshot
+    /// into history, returning `Error::AllocationFailed` if a new snapshot cannot be allocated.
+    /// A snapshot that recorded no changes is reused for `ct` without touching history.
+    #[inline]
+    pub fn try_ensure_rollback_tick(&mut self, ct: Tick) -> Result<()> {
+        crate::tick::check_guarded_write::<T>(ct);
+        self.begin_shadow_tick(ct);
         if self.rollback.tick() != ct {
-            let new_current: Box<RollbackStorage<T>> =
-                if let Some(mut pooled) = self.rollback_pool.pop() {
-                    pooled.reset_for_tick(ct);
-                    pooled
-                } else {
-                    Box::new(RollbackStorage::with_tick(ct))
-                };
+            // Nothing was recorded for the current tick: retag the snapshot instead of
+            // pushing an empty one into history. Snapshots holding a saved generation
+            // (Entity storage) are kept so generation restore still finds every tick.
+            if self.rollback.changed_mask == 0 && self.rollback.get_saved_generation() == 0 {
+                self.rollback.reset_for_tick(ct);
+                return Ok(());
+            }
+            let new_current = if let Some(mut pooled) = self.rollback_pool.pop() {
+                pooled.reset_for_tick(ct);
+                pooled
+            } else {
+                RollbackStorage::try_with_tick(ct)?
+            };
             let old = std::mem::replace(&mut self.rollback, new_current);
             let mut merged = std::mem::take(&mut self.prev);
             merged.push_back(old);
             self.prev = merged;
 
-            // Limit rollback history to 64 ticks
+            // Limit rollback history to MAX_HISTORY ticks
             // This prevents unbounded memory growth but limits maximum rollback distance
-            while self.prev.len() > 64 {
-                self.prev.pop_front();
+            while self.prev.len() > MAX_HISTORY {
+                if let Some(dropped) = self.prev.pop_front() {
+                    self.history_floor = Some(dropped.tick());
+                }
             }
         }
+        Ok(())
+    }
+
+    /// Storage indices holding a value written after `since`.
+    #[inline]
+    pub fn changed_since(&self, since: Tick) -> u64 {
+        changed_since(&self.changed_ticks, self.presence_mask, since)
+    }
+
+    /// Stamps the values in `bits` of the given chunk, and its page, with the current
+    /// change tick. The chunk must exist.
+    #[inline]
+    pub(crate) fn stamp_changed(&mut self, storage_idx: u32, page_idx: u32, bits: u64) {
+        if !T::CHANGE_DETECTION {
+            return;
+        }
+        let tick = self.current_change_tick();
+        self.changed_ticks[storage_idx as usize] = tick;
+        let page = unsafe { &mut *self.data[storage_idx as usize] };
+        page.changed_ticks[page_idx as usize] = tick;
+        let chunk = unsafe { &mut *page.data[page_idx as usize] };
+        let mut m = bits;
+        while m != 0 {
+            let chunk_idx = m.trailing_zeros();
+            let before = std::mem::replace(&mut chunk.changed_ticks[chunk_idx as usize], tick);
+            self.fields.note(
+                (storage_idx << 12) | (page_idx << 6) | chunk_idx,
+                tick,
+                before,
+            );
+            m &= m - 1;
+        }
     }
 
     /// Gets a reference to a value at the given global index.
@@ -140,6 +678,298 @@ impl<T: Component> Storage<T> {
         }
     }
 
+    /// Returns the tick of the newest rollback snapshot that recorded a creation,
+    /// modification or removal of `index`, or `None` if it is not in the history.
+    pub fn last_changed_tick(&self, index: u32) -> Option<Tick> {
+        if index >= 64 * 64 * 64 {
+            return None;
+        }
+
+        std::iter::once(&self.rollback)
+            .chain(self.prev.iter().rev())
+            .find(|rb| rb.records(index))
+            .map(|rb| rb.tick())
+    }
+
+    /// Returns the value `index` held before it was removed in the current rollback
+    /// snapshot's tick, or `None` if it was not removed then (or was inserted again).
+    ///
+    /// The snapshot keeps the value from the start of that tick, so a value modified
+    /// earlier in the same tick reads as it was before the modification. The snapshot
+    /// only moves on when the storage is first written in a tick: when nothing was
+    /// written yet, this still answers for the last tick that wrote.
+    pub fn removed_value(&self, index: u32) -> Option<&T> {
+        if !self.rollback.verify_was_removed(index) {
+            return None;
+        }
+        self.rollback.stored(index)
+    }
+
+    /// Writes one field of the value at `index` through `field`, a setter generated by
+    /// `#[component(field_mask)]`. The value is marked changed as with `get_mut`, and
+    /// `changed_fields_since` reports that field alone. Returns false if no value is
+    /// present.
+    pub fn set_field<F: FieldSetter<T>>(
+        &mut self,
+        frame: &crate::frame::Frame,
+        index: u32,
+        field: F,
+    ) -> bool {
+        self.fields.begin_field_write(field.index());
+        let value = self.get_mut(frame, index).map(|value| value as *mut T);
+        self.fields.end_field_write();
+        match value {
+            Some(value) => {
+                field.apply(unsafe { &mut *value });
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Bits (in `F::NAMES` order) of the fields of the value at `index` written after
+    /// change tick `since`: those written with `set_field`, or all of them after any
+    /// other write. 0 if the value is absent or unchanged; all bits for components that
+    /// opt out of change detection.
+    pub fn changed_fields_since<F: FieldSetter<T>>(&self, index: u32, since: Tick) -> u64 {
+        let Some(stamp) = self.value_change_tick(index) else {
+            return 0;
+        };
+        if !T::CHANGE_DETECTION {
+            return F::ALL;
+        }
+        self.fields.changed_since(index, stamp, since, F::ALL)
+    }
+
+    /// Returns the change tick (`World::change_tick`) stamped on the value at `index`
+    /// by its last write, which `Changed=[...]` queries compare against, or None if no
+    /// value is present.
+    pub fn value_change_tick(&self, index: u32) -> Option<Tick> {
+        self.get(index)?;
+        let page = unsafe { &*self.data[(index >> 12) as usize] };
+        let chunk = unsafe { &*page.data[((index >> 6) & 63) as usize] };
+        Some(chunk.changed_ticks[(index & 63) as usize])
+    }
+
+    /// Returns the smallest present index that is `>= from`, skipping empty pages and
+    /// chunks through the presence masks.
+    pub fn next_present(&self, from: u32) -> Option<u32> {
+        let mut idx = from;
+        while idx < 64 * 64 * 64 {
+            let storage_idx = idx >> 12;
+            let storage_mask = self.presence_mask & (u64::MAX << storage_idx);
+            if storage_mask == 0 {
+                return None;
+            }
+            let next_storage = storage_mask.trailing_zeros();
+            if next_storage != storage_idx {
+                idx = next_storage << 12;
+                continue;
+            }
+
+            let page = unsafe { &*self.data[storage_idx as usize] };
+            let page_idx = (idx >> 6) & 63;
+            let page_mask = page.presence_mask & (u64::MAX << page_idx);
+            if page_mask == 0 {
+                idx = (storage_idx + 1) << 12;
+                continue;
+            }
+            let next_page = page_mask.trailing_zeros();
+            if next_page != page_idx {
+                idx = (storage_idx << 12) | (next_page << 6);
+                continue;
+            }
+
+            let chunk = unsafe { &*page.data[page_idx as usize] };
+            let chunk_mask = chunk.presence_mask & (u64::MAX << (idx & 63));
+            if chunk_mask != 0 {
+                return Some((idx & !63) | chunk_mask.trailing_zeros());
+            }
+            idx = (idx & !63) + 64;
+        }
+        None
+    }
+
+    /// Returns the smallest index without a value, found through the fullness masks,
+    /// or None if every slot is taken.
+    pub fn first_free_index(&self) -> Option<u32> {
+        // Missing pages/chunks point at the shared empty defaults, so their masks read as 0.
+        let storage_idx = self.fullness_mask.trailing_ones();
+        if storage_idx == 64 {
+            return None;
+        }
+        let page = unsafe { &*self.data[storage_idx as usize] };
+        let page_idx = page.fullness_mask.trailing_ones();
+        if page_idx == 64 {
+            return None;
+        }
+        let chunk = unsafe { &*page.data[page_idx as usize] };
+        let chunk_idx = chunk.fullness_mask.trailing_ones();
+        if chunk_idx == 64 {
+            return None;
+        }
+        Some((storage_idx << 12) | (page_idx << 6) | chunk_idx)
+    }
+
+    /// Returns a cursor positioned at index 0; see `StorageCursor`.
+    pub fn cursor(&self) -> StorageCursor {
+        StorageCursor::new()
+    }
+
+    /// Copies the presence masks of every level into a `HierBitmap`, to be combined with
+    /// the bitmaps of other storages into a custom join (see `decs::bitmap`).
+    pub fn presence_bitmap(&self) -> HierBitmap {
+        HierBitmap::from_masks(self.presence_mask, |s| {
+            let page = unsafe { &*self.data[s as usize] };
+            std::array::from_fn(|p| {
+                if (page.presence_mask >> p) & 1 != 0 {
+                    unsafe { (*page.data[p]).presence_mask }
+                } else {
+                    0
+                }
+            })
+        })
+    }
+
+    /// Iterates this storage's values in ascending order of the keys in `sorted`, e.g.
+    /// the index of a `SortedIndexSystem`. Indices without a value here are skipped.
+    pub fn iter_sorted_by<'a, K: Ord + Clone>(
+        &'a self,
+        sorted: &'a SortedIndex<K>,
+    ) -> impl Iterator<Item = (u32, &'a T)> + 'a {
+        sorted
+            .iter()
+            .filter_map(move |index| Some((index, self.get(index)?)))
+    }
+
+    /// Calls `f(index, was_present, present)` for every index the rollback snapshot of
+    /// `tick` recorded as touched, with whether it had a value at the start of that tick
+    /// and whether it has one now. Calls nothing if the snapshot is no longer kept.
+    pub fn for_each_touched_at(&self, tick: Tick, mut f: impl FnMut(u32, bool, bool)) {
+        if let Some(snapshot) = self.rollback_at(tick) {
+            snapshot.for_each_touched_with_presence(|index, was_present| {
+                f(index, was_present, self.get(index).is_some())
+            });
+        }
+    }
+
+    /// The rollback snapshot recording tick `tick`, if still kept: the current tick's or
+    /// one of the history. Read its touches with `RollbackStorage::for_each_entry`.
+    pub fn rollback_at(&self, tick: Tick) -> Option<&RollbackStorage<T>> {
+        self.prev
+            .iter()
+            .chain(std::iter::once(&self.rollback))
+            .find(|snapshot| snapshot.tick() == tick)
+    }
+
+    /// Ticks of the kept rollback snapshots, oldest first.
+    pub fn rollback_ticks(&self) -> impl Iterator<Item = Tick> + '_ {
+        self.prev
+            .iter()
+            .chain(std::iter::once(&self.rollback))
+            .map(|snapshot| snapshot.tick())
+    }
+
+    /// Calls `f` with every index the rollback snapshots of tick `since` and later
+    /// recorded as created, changed or removed, for caches derived from the values that
+    /// sync incrementally. Returns false without calling `f` if part of that history
+    /// was already discarded, in which case the cache has to be rebuilt.
+    pub fn for_each_touched_since(&self, since: Tick, mut f: impl FnMut(u32)) -> bool {
+        if self
+            .history_floor
+            .is_some_and(|floor| !floor.is_before(since))
+        {
+            return false;
+        }
+        for snapshot in self.prev.iter().chain(std::iter::once(&self.rollback)) {
+            if snapshot.tick().is_at_or_after(since) {
+                snapshot.for_each_touched(&mut f);
+            }
+        }
+        true
+    }
+
+    /// Returns the 64 value slots of the chunk at `storage_idx`/`page_idx` together with
+    /// its presence mask, or None if the chunk doesn't exist. Only slots whose bit is set
+    /// in the mask are initialized.
+    #[inline]
+    pub fn chunk_slice(&self, storage_idx: u32, page_idx: u32) -> Option<(&[Slot<T>; 64], u64)> {
+        if storage_idx >= 64 || page_idx >= 64 || (self.presence_mask >> storage_idx) & 1 == 0 {
+            return None;
+        }
+        let page = unsafe { &*self.data[storage_idx as usize] };
+        if (page.presence_mask >> page_idx) & 1 == 0 {
+            return None;
+        }
+        let chunk = unsafe { &*page.data[page_idx as usize] };
+        Some((&chunk.data, chunk.presence_mask))
+    }
+
+    /// Mutable variant of `chunk_slice` for processing a chunk's values in place.
+    ///
+    /// Every present value is treated as modified in `frame`'s tick: its old value is
+    /// recorded for rollback and it is marked changed, exactly as if `get_mut` had been
+    /// called for each of them. Only the slots set in the returned mask may be written;
+    /// presence cannot be changed through the slice.
+    pub fn chunk_slice_mut(
+        &mut self,
+        frame: &crate::frame::Frame,
+        storage_idx: u32,
+        page_idx: u32,
+    ) -> Option<(&mut [Slot<T>; 64], u64)> {
+        self.chunk_slice(storage_idx, page_idx)?;
+        self.ensure_rollback_tick(frame.current_tick);
+        self.snapshot_chunk_on_first_touch(frame.current_tick, storage_idx, page_idx);
+
+        let page = unsafe { &mut *self.data[storage_idx as usize] };
+        let chunk = unsafe { &mut *page.data[page_idx as usize] };
+        let presence = chunk.presence_mask;
+
+        let rb_chunk = self
+            .rollback
+            .get_or_create_page(storage_idx)
+            .get_or_create_chunk(page_idx);
+        // Items created this tick stay created; the rest become changed
+        let changed = presence & !rb_chunk.created_mask;
+        let mut m = changed;
+        while m != 0 {
+            let i = m.trailing_zeros();
+            if rb_chunk.needs_old_value(i) {
+                let old_val = unsafe { chunk.data[i as usize].assume_init_ref() }.clone();
+                rb_chunk.data[i as usize].write(old_val);
+            }
+            m &= m - 1;
+        }
+        rb_chunk.removed_mask &= !changed;
+        rb_chunk.changed_mask |= changed;
+
+        chunk.changed_mask |= presence & Self::CHANGE_BITS;
+        page.changed_mask |= (1u64 << page_idx) & Self::CHANGE_BITS;
+        self.changed_mask |= (1u64 << storage_idx) & Self::CHANGE_BITS;
+        self.stamp_changed(storage_idx, page_idx, presence);
+
+        Some((&mut chunk.data, presence))
+    }
+
+    /// Returns the value at `index` for mutation, first inserting `init()` if none is
+    /// present. An insertion is recorded in `frame`'s rollback snapshot as a creation,
+    /// like `set`; an existing value is marked changed, like `get_mut`, so use `get` for
+    /// reads.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of range, like `set`.
+    pub fn get_or_insert_with(
+        &mut self,
+        frame: &crate::frame::Frame,
+        index: u32,
+        init: impl FnOnce() -> T,
+    ) -> &mut T {
+        if self.get(index).is_none() {
+            self.set(frame, index, init());
+        }
+        self.get_mut(frame, index).expect("value was just inserted")
+    }
+
     /// Gets a mutable reference to a value at the given global index.
     /// Returns None if the value doesn't exist.
     #[inline(always)]
@@ -171,26 +1001,28 @@ impl<T: Component> Storage<T> {
             }
 
             self.ensure_rollback_tick(frame.current_tick);
+            self.snapshot_chunk_on_first_touch(frame.current_tick, storage_idx, page_idx);
+            // Unsharing may have replaced the chunk.
+            let chunk_ptr = (*page_ptr).data[page_idx as usize];
 
             // Mark changed at all levels
-            (*chunk_ptr).changed_mask |= bit;
-            (*page_ptr).changed_mask |= 1u64 << page_idx;
-            self.changed_mask |= 1u64 << storage_idx;
+            (*chunk_ptr).changed_mask |= bit & Self::CHANGE_BITS;
+            (*page_ptr).changed_mask |= (1u64 << page_idx) & Self::CHANGE_BITS;
+            self.changed_mask |= (1u64 << storage_idx) & Self::CHANGE_BITS;
+            self.stamp_changed(storage_idx, page_idx, bit);
 
             // Handle rollback
             let rb_page = self.rollback.get_or_create_page(storage_idx);
             let rb_chunk = rb_page.get_or_create_chunk(page_idx);
 
             let was_created = (rb_chunk.created_mask & bit) != 0;
-            let was_changed = (rb_chunk.changed_mask & bit) != 0;
-            let was_removed = (rb_chunk.removed_mask & bit) != 0;
 
             if was_created {
                 rb_chunk.removed_mask &= !bit;
                 rb_chunk.changed_mask &= !bit;
                 rb_chunk.created_mask |= bit;
             } else {
-                if !was_changed && !was_removed {
+                if rb_chunk.needs_old_value(chunk_idx) {
                     let old_val = (*chunk_ptr).data[chunk_idx as usize]
                         .assume_init_ref()
                         .clone();
@@ -208,7 +1040,66 @@ impl<T: Component> Storage<T> {
         }
     }
 
+    /// Fallible variant of `set`.
+    ///
+    /// Returns `Error::IndexOutOfRange` instead of panicking for indices past the
+    /// hierarchy, and `Error::AllocationFailed` if a page, chunk or rollback snapshot
+    /// cannot be allocated. On error the storage is left unchanged (apart from
+    /// empty structures that were successfully pre-allocated).
+    pub fn try_set(&mut self, frame: &crate::frame::Frame, index: u32, value: T) -> Result<()> {
+        if index >= 64 * 64 * 64 {
+            return Err(Error::IndexOutOfRange(index));
+        }
+
+        let page_idx = (index >> 6) & 63;
+        let storage_idx = index >> 12;
+
+        // Pre-allocate every structure `set` could need so that it cannot abort.
+        self.try_ensure_rollback_tick(frame.current_tick)?;
+        self.snapshot_chunk_on_first_touch(frame.current_tick, storage_idx, page_idx);
+        self.rollback
+            .try_get_or_create_page(storage_idx)?
+            .try_get_or_create_chunk(page_idx)?;
+
+        let storage_bit = 1u64 << storage_idx;
+        let page_was_changed = self.changed_mask & storage_bit;
+        if (self.presence_mask >> storage_idx) & 1 == 0 {
+            let new_page = Box::try_new(Page::new(self.default_chunk_ptr))?;
+            self.data[storage_idx as usize] = Box::into_raw(new_page);
+            self.presence_mask |= 1u64 << storage_idx;
+            self.changed_mask |= (1u64 << storage_idx) & Self::CHANGE_BITS;
+        }
+
+        let page = unsafe { &mut *self.data[storage_idx as usize] };
+        if (page.presence_mask >> page_idx) & 1 == 0 {
+            let new_chunk = match self.chunk_layout.try_alloc() {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    if page.presence_mask == 0 {
+                        // Release the page we may have just created for this call.
+                        unsafe { drop(Box::from_raw(self.data[storage_idx as usize])) };
+                        self.data[storage_idx as usize] = self.default_page_ptr as *mut Page<T>;
+                        self.presence_mask &= !storage_bit;
+                        self.changed_mask = (self.changed_mask & !storage_bit) | page_was_changed;
+                    }
+                    return Err(e.into());
+                }
+            };
+            page.data[page_idx as usize] = new_chunk;
+            page.presence_mask |= 1u64 << page_idx;
+            self.mark_structure_changed();
+            page.changed_mask |= (1u64 << page_idx) & Self::CHANGE_BITS;
+        }
+
+        self.set(frame, index, value);
+        Ok(())
+    }
+
     /// Sets a value at the given global index.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of range; aborts if an allocation fails.
+    /// Use `try_set` to handle both conditions as errors.
     #[inline(always)]
     pub fn set(&mut self, frame: &crate::frame::Frame, index: u32, value: T) {
         let chunk_idx = index & 63;
@@ -217,6 +1108,8 @@ impl<T: Component> Storage<T> {
 
         assert!(storage_idx < 64, "Storage index out of range");
 
+        self.snapshot_chunk_on_first_touch(frame.current_tick, storage_idx, page_idx);
+
         // Create page if needed
         let page_was_new = (self.presence_mask >> storage_idx) & 1 == 0;
 
@@ -224,7 +1117,7 @@ impl<T: Component> Storage<T> {
             let new_page = Box::new(Page::new(self.default_chunk_ptr));
             self.data[storage_idx as usize] = Box::into_raw(new_page);
             self.presence_mask |= 1u64 << storage_idx;
-            self.changed_mask |= 1u64 << storage_idx;
+            self.changed_mask |= (1u64 << storage_idx) & Self::CHANGE_BITS;
 
             debug_assert!(
                 self.fullness_mask & !self.presence_mask == 0,
@@ -239,10 +1132,9 @@ impl<T: Component> Storage<T> {
             let chunk_was_new = (page.presence_mask >> page_idx) & 1 == 0;
 
             if chunk_was_new {
-                let new_chunk = Box::new(Chunk::new());
-                page.data[page_idx as usize] = Box::into_raw(new_chunk);
+                page.data[page_idx as usize] = self.chunk_layout.alloc();
                 page.presence_mask |= 1u64 << page_idx;
-                page.changed_mask |= 1u64 << page_idx;
+                page.changed_mask |= (1u64 << page_idx) & Self::CHANGE_BITS;
                 debug_assert!(
                     page.fullness_mask & !page.presence_mask == 0,
                     "Page fullness_mask invariant violated after creating chunk"
@@ -261,7 +1153,8 @@ impl<T: Component> Storage<T> {
             chunk.data[chunk_idx as usize].write(value);
             chunk.presence_mask |= 1u64 << chunk_idx;
             chunk.fullness_mask |= 1u64 << chunk_idx; // fullness_mask == presence_mask at Chunk level
-            chunk.changed_mask |= 1u64 << chunk_idx;
+            chunk.changed_mask |= (1u64 << chunk_idx) & Self::CHANGE_BITS;
+            self.stamp_changed(storage_idx, page_idx, 1u64 << chunk_idx);
 
             let presence_mask = chunk.presence_mask;
             let chunk_is_full = presence_mask == u64::MAX;
@@ -277,48 +1170,8 @@ impl<T: Component> Storage<T> {
         let rollback_page = self.rollback.get_or_create_page(storage_idx);
         let rollback_chunk = rollback_page.get_or_create_chunk(page_idx);
 
-        // Check if item was removed or created earlier in this tick (idempotent operations)
-        let was_removed = (rollback_chunk.removed_mask >> chunk_idx) & 1 != 0;
-        let was_created = (rollback_chunk.created_mask >> chunk_idx) & 1 != 0;
-
-        // Update chunk level
-        if was_created {
-            // Item was created earlier in this tick and is being modified
-            // Created + modified on same tick should remain as created only
-            // Don't store old value (created items don't have old values)
-            // Keep created_mask, clear removed_mask and changed_mask
-            rollback_chunk.removed_mask &= !(1u64 << chunk_idx);
-            rollback_chunk.changed_mask &= !(1u64 << chunk_idx);
-            rollback_chunk.created_mask |= 1u64 << chunk_idx;
-        } else if was_present {
-            // Value existed before this tick - this is a change, store old value
-            // Only store the old value on the FIRST change within the tick
-            if ((rollback_chunk.changed_mask >> chunk_idx) & 1 == 0)
-                && ((rollback_chunk.removed_mask >> chunk_idx) & 1 == 0)
-                && let Some(old_val) = old_value
-            {
-                rollback_chunk.data[chunk_idx as usize].write(old_val);
-            }
-            // Clear created_mask and removed_mask, set changed_mask
-            rollback_chunk.removed_mask &= !(1u64 << chunk_idx);
-            rollback_chunk.created_mask &= !(1u64 << chunk_idx);
-            rollback_chunk.changed_mask |= 1u64 << chunk_idx;
-        } else if was_removed {
-            // Value didn't exist in Storage, but was removed earlier in this tick
-            // This means the item EXISTED BEFORE and was successfully removed
-            // This is an idempotent operation: Remove->Add = Change
-            // The old value is already stored in RollbackStorage from the remove operation
-            // Clear removed_mask and created_mask, set changed_mask
-            rollback_chunk.removed_mask &= !(1u64 << chunk_idx);
-            rollback_chunk.created_mask &= !(1u64 << chunk_idx);
-            rollback_chunk.changed_mask |= 1u64 << chunk_idx;
-        } else {
-            // Value didn't exist before - this is a creation
-            // Clear removed_mask and changed_mask, set created_mask
-            rollback_chunk.removed_mask &= !(1u64 << chunk_idx);
-            rollback_chunk.changed_mask &= !(1u64 << chunk_idx);
-            rollback_chunk.created_mask |= 1u64 << chunk_idx;
-        }
+        let (was_created, was_removed) =
+            record_set_in_rollback(rollback_chunk, chunk_idx, was_present, old_value);
 
         // Update page level (reusing the same page reference)
         // Any change (creation, modification, or removal) sets changed_mask
@@ -336,6 +1189,8 @@ impl<T: Component> Storage<T> {
             if !was_present {
                 page.count = page.count.saturating_add(1);
                 self.count = self.count.saturating_add(1);
+                audit::record::<T>(frame.current_tick, index, StructuralOp::Insert);
+                self.note_presence(index, true);
             }
 
             // Update page masks (presence_mask already set if chunk_was_new)
@@ -344,6 +1199,10 @@ impl<T: Component> Storage<T> {
                 // Already set above when creating chunk
             }
 
+            if chunk_was_new || (chunk_is_full && !was_present) {
+                self.mark_structure_changed();
+            }
+
             // Use count to determine if page is full: all 64 chunks exist AND all are full
             if chunk_is_full {
                 page.fullness_mask |= 1u64 << page_idx;
@@ -351,7 +1210,7 @@ impl<T: Component> Storage<T> {
                 page.fullness_mask &= !(1u64 << page_idx);
             }
             page.fullness_mask &= page.presence_mask;
-            page.changed_mask |= 1u64 << page_idx;
+            page.changed_mask |= (1u64 << page_idx) & Self::CHANGE_BITS;
 
             debug_assert!(
                 page.fullness_mask & !page.presence_mask == 0,
@@ -374,7 +1233,7 @@ impl<T: Component> Storage<T> {
             }
 
             self.fullness_mask &= self.presence_mask;
-            self.changed_mask |= 1u64 << storage_idx;
+            self.changed_mask |= (1u64 << storage_idx) & Self::CHANGE_BITS;
             debug_assert!(
                 self.fullness_mask & !self.presence_mask == 0,
                 "Storage fullness_mask invariant violated after mask update"
@@ -411,6 +1270,23 @@ impl<T: Component> Storage<T> {
         }
     }
 
+    /// Returns a cursor for writing many values in one tick.
+    ///
+    /// The writer caches the page, chunk and rollback chunk of the last index it touched,
+    /// so runs of nearby indices skip the per-call lookups and rollback rotation of `set`.
+    /// The resulting masks, counts and rollback history are identical to calling `set`.
+    #[inline]
+    pub fn writer<'a>(&'a mut self, frame: &crate::frame::Frame) -> StorageWriter<'a, T> {
+        self.ensure_rollback_tick(frame.current_tick);
+        StorageWriter {
+            storage: self,
+            key: u32::MAX,
+            page: std::ptr::null_mut(),
+            chunk: std::ptr::null_mut(),
+            rollback_chunk: std::ptr::null_mut(),
+        }
+    }
+
     /// Removes a value at the given global index.
     /// Returns true if the value was removed, false if it didn't exist.
     #[inline(always)]
@@ -444,16 +1320,22 @@ impl<T: Component> Storage<T> {
                 return false;
             }
 
+            self.snapshot_chunk_on_first_touch(frame.current_tick, storage_idx, page_idx);
+            // Unsharing may have replaced the chunk.
+            let chunk = unsafe { &mut *page.data[page_idx as usize] };
+            audit::record::<T>(frame.current_tick, index, StructuralOp::Remove);
+            self.note_presence(index, false);
+
             // Read old value for rollback before dropping
             let old_value = unsafe { chunk.data[chunk_idx as usize].assume_init_read() };
 
             // Only set changed_mask if we're actually removing a value
-            page.changed_mask |= 1u64 << page_idx;
+            page.changed_mask |= (1u64 << page_idx) & Self::CHANGE_BITS;
 
             // Remove value: clear both presence_mask and fullness_mask
             chunk.presence_mask &= !(1u64 << chunk_idx);
             chunk.fullness_mask &= !(1u64 << chunk_idx); // fullness_mask == presence_mask at Chunk level
-            chunk.changed_mask |= 1u64 << chunk_idx;
+            chunk.changed_mask |= (1u64 << chunk_idx) & Self::CHANGE_BITS;
 
             // Cache presence_mask before dropping mutable reference
             let chunk_has_present = chunk.presence_mask != 0;
@@ -477,13 +1359,16 @@ impl<T: Component> Storage<T> {
         // Update page masks and counts after potential rollback rotation
         {
             let page = unsafe { &mut *self.data[storage_idx as usize] };
+            if !chunk_has_present || (page.fullness_mask >> page_idx) & 1 != 0 {
+                self.mark_structure_changed();
+            }
             if !chunk_has_present {
                 page.presence_mask &= !(1u64 << page_idx);
             }
             // After removing a value, the chunk cannot be full, so clear fullness_mask
             page.fullness_mask &= !(1u64 << page_idx);
             page.fullness_mask &= page.presence_mask;
-            page.changed_mask |= 1u64 << page_idx;
+            page.changed_mask |= (1u64 << page_idx) & Self::CHANGE_BITS;
 
             // Update counts: decrement when removing a value (use saturating_sub to prevent overflow)
             page.count = page.count.saturating_sub(1);
@@ -496,7 +1381,7 @@ impl<T: Component> Storage<T> {
                 // Drop owned chunk and reset pointer to default
                 let chunk_ptr = page.data[page_idx as usize];
                 // Mask indicates chunk exists, so it cannot be default
-                drop(Box::from_raw(chunk_ptr));
+                self.chunk_layout.release(chunk_ptr);
                 page.data[page_idx as usize] = self.default_chunk_ptr as *mut Chunk<T>;
                 debug_assert!(
                     page.fullness_mask & !page.presence_mask == 0,
@@ -516,7 +1401,7 @@ impl<T: Component> Storage<T> {
         // After removing a value, the page cannot be full (count decreased), so clear fullness_mask
         self.fullness_mask &= !(1u64 << storage_idx);
         self.fullness_mask &= self.presence_mask;
-        self.changed_mask |= 1u64 << storage_idx;
+        self.changed_mask |= (1u64 << storage_idx) & Self::CHANGE_BITS;
 
         // Check if item was created in this tick (idempotent: Add->Remove = no change)
         // An item is considered "created in this tick" if it has created_mask set
@@ -540,9 +1425,7 @@ impl<T: Component> Storage<T> {
             } else {
                 // Item existed before this tick - this is a removal
                 // Store old value ONLY if this is the first change within the tick
-                if ((rollback_chunk.changed_mask >> chunk_idx) & 1 == 0)
-                    && ((rollback_chunk.removed_mask >> chunk_idx) & 1 == 0)
-                {
+                if rollback_chunk.needs_old_value(chunk_idx) {
                     rollback_chunk.data[chunk_idx as usize].write(old_value);
                 }
 
@@ -555,12 +1438,18 @@ impl<T: Component> Storage<T> {
 
         // Update rollback storage at page and storage levels
         if was_created_in_rollback {
-            // Add→Remove = no change: clear hierarchical changed_mask bits if no other changes remain
+            // Add→Remove = no change: clear hierarchical changed_mask bits if no other changes remain.
+            // A whole-chunk snapshot counts as a change: the bits are what keeps it reachable
+            // for rollback and for being dropped with the tick.
             if (self.rollback.changed_mask >> storage_idx) & 1 != 0 {
                 let rollback_page = self.rollback.get_or_create_page(storage_idx);
                 let chunk_has_other_changes = {
                     if let Some(chunk) = rollback_page.get(page_idx) {
-                        (chunk.created_mask | chunk.changed_mask | chunk.removed_mask) != 0
+                        (chunk.created_mask
+                            | chunk.changed_mask
+                            | chunk.removed_mask
+                            | chunk.snapshot_mask)
+                            != 0
                     } else {
                         false
                     }
@@ -599,6 +1488,9 @@ impl<T: Component> Storage<T> {
                 "Storage fullness_mask invariant violated after dropping page"
             );
         }
+        if !chunk_has_present {
+            self.check_shrunk_page(storage_idx);
+        }
 
         // Verify rollback invariants (only if not idempotent)
         if !was_created_in_rollback {
@@ -612,12 +1504,35 @@ impl<T: Component> Storage<T> {
         true
     }
 
+    /// Returns `Error::HistoryExhausted` if changes newer than `target_tick` were already
+    /// discarded from history, so a rollback to it could only be partial.
+    pub fn check_rollback_target(&self, target_tick: Tick) -> Result<()> {
+        match self.history_floor {
+            Some(oldest) if oldest.is_after(target_tick) => Err(Error::HistoryExhausted {
+                target: target_tick,
+                oldest,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Fallible variant of `rollback` that refuses to perform a partial rollback.
+    pub fn try_rollback(&mut self, target_tick: Tick) -> Result<()> {
+        self.check_rollback_target(target_tick)?;
+        self.rollback(target_tick);
+        Ok(())
+    }
+
     pub fn rollback(&mut self, target_tick: Tick)
     where
         T: Clone,
     {
-        // Efficient rollback using bitmasks to track visited indices.
-        // This ensures at most 1 clone and 1 drop per index without HashMap/Vec allocations.
+        // Efficient rollback using bitmasks to track visited indices, without HashMap/Vec
+        // allocations. The snapshots undone here are discarded afterwards, so their stored
+        // values are moved (or swapped) back instead of cloned: no clone and at most one
+        // drop per index.
+        self.check_shadow(true);
+        self.unpack_history(target_tick);
 
         // Find the generation value at or before target_tick
         // Search from newest to oldest: self.rollback -> self.prev.iter().rev()
@@ -626,7 +1541,7 @@ impl<T: Component> Storage<T> {
 
         // We can stop searching once we find a tick <= target_tick
         for rb in all_rollbacks_rev {
-            if rb.tick() <= target_tick {
+            if rb.tick().is_at_or_before(target_tick) {
                 found_generation = Some(rb.get_saved_generation());
                 break;
             }
@@ -638,10 +1553,20 @@ impl<T: Component> Storage<T> {
         // Optimization: iterate newest to oldest and stop when tick <= target_tick
         let relevant_rollbacks_rev = std::iter::once(&self.rollback)
             .chain(self.prev.iter().rev())
-            .take_while(|rb| rb.tick() > target_tick);
+            .take_while(|rb| rb.tick().is_after(target_tick));
 
+        let mut relevant_count = 0usize;
         for rb in relevant_rollbacks_rev {
             unified_storage_mask |= rb.changed_mask;
+            relevant_count += 1;
+        }
+        // Snapshots newer than target_tick form a suffix of prev + current. Skipping by
+        // count (instead of comparing ticks from the oldest end) stays correct when the
+        // history straddles a tick wraparound.
+        let skip_count = self.prev.len() + 1 - relevant_count;
+
+        if unified_storage_mask != 0 {
+            self.mark_structure_changed();
         }
 
         // Iterate through each storage index that has changes
@@ -657,7 +1582,7 @@ impl<T: Component> Storage<T> {
             // Re-create the iterator for this scope (borrow checker)
             let relevant_rollbacks_rev = std::iter::once(&self.rollback)
                 .chain(self.prev.iter().rev())
-                .take_while(|rb| rb.tick() > target_tick);
+                .take_while(|rb| rb.tick().is_after(target_tick));
 
             for rb in relevant_rollbacks_rev {
                 if let Some(rb_page) = rb.get_page(storage_idx) {
@@ -676,17 +1601,19 @@ impl<T: Component> Storage<T> {
                 let mut visited_mask = 0u64;
 
                 // Process rollback states from oldest to newest (self.prev is ordered oldest to newest)
-                // Optimization: skip ticks <= target_tick used skip_while
+                // Optimization: skip the snapshots at or before target_tick
                 // Note: We MUST iterate Oldest -> Newest for correct "first modification" restoration logic
+                let change_tick = self.current_change_tick();
                 let relevant_rollbacks = self
                     .prev
-                    .iter()
-                    .chain(std::iter::once(&self.rollback))
-                    .skip_while(|rb| rb.tick() <= target_tick);
+                    .iter_mut()
+                    .chain(std::iter::once(&mut self.rollback))
+                    .skip(skip_count);
+                let signatures = self.signatures;
 
                 for rb in relevant_rollbacks {
-                    if let Some(rb_page) = rb.get_page(storage_idx)
-                        && let Some(rb_chunk) = rb_page.get(page_idx)
+                    if let Some(rb_page) = rb.get_page_mut(storage_idx)
+                        && let Some(rb_chunk) = rb_page.get_mut(page_idx)
                     {
                         // Process all three change types for this chunk
                         let combined_mask =
@@ -714,6 +1641,7 @@ impl<T: Component> Storage<T> {
                                 if (self.presence_mask >> storage_idx) & 1 != 0 {
                                     let page = unsafe { &mut *self.data[storage_idx_usize] };
                                     if (page.presence_mask >> page_idx) & 1 != 0 {
+                                        page.unshare_chunk(page_idx_usize, &self.chunk_layout);
                                         let chunk = unsafe { &mut *page.data[page_idx_usize] };
                                         if (chunk.presence_mask >> chunk_idx) & 1 != 0 {
                                             unsafe {
@@ -723,6 +1651,11 @@ impl<T: Component> Storage<T> {
                                             chunk.fullness_mask &= !(1u64 << chunk_idx);
                                             page.count = page.count.saturating_sub(1);
                                             self.count = self.count.saturating_sub(1);
+                                            note_presence::<T>(
+                                                signatures,
+                                                (storage_idx << 12) | (page_idx << 6) | chunk_idx,
+                                                false,
+                                            );
                                             // chunk cannot be full after removal
                                             page.fullness_mask &= !(1u64 << page_idx);
                                             // drop chunk if it became empty
@@ -730,7 +1663,7 @@ impl<T: Component> Storage<T> {
                                                 let chunk_ptr = page.data[page_idx_usize];
                                                 // Chunk became empty, drop it
                                                 unsafe {
-                                                    drop(Box::from_raw(chunk_ptr));
+                                                    self.chunk_layout.release(chunk_ptr);
                                                 }
                                                 page.data[page_idx_usize] =
                                                     self.default_chunk_ptr as *mut Chunk<T>;
@@ -740,12 +1673,8 @@ impl<T: Component> Storage<T> {
                                     }
                                 }
                             } else {
-                                // Restore changed/removed item (at most 1 clone + 1 drop)
-                                // Clone old value from rollback storage
-                                let old_value = unsafe {
-                                    rb_chunk.data[chunk_idx_usize].assume_init_ref().clone()
-                                };
-
+                                // Restore changed/removed item by moving the old value out
+                                // of the snapshot, which is discarded below.
                                 // Ensure page/chunk exist
                                 if (self.presence_mask >> storage_idx) & 1 == 0 {
                                     let new_page = Box::new(Page::new(self.default_chunk_ptr));
@@ -755,26 +1684,51 @@ impl<T: Component> Storage<T> {
 
                                 let page = unsafe { &mut *self.data[storage_idx_usize] };
                                 if (page.presence_mask >> page_idx) & 1 == 0 {
-                                    let new_chunk = Box::new(Chunk::new());
-                                    page.data[page_idx_usize] = Box::into_raw(new_chunk);
+                                    page.data[page_idx_usize] = self.chunk_layout.alloc();
                                     page.presence_mask |= 1u64 << page_idx;
+                                } else {
+                                    page.unshare_chunk(page_idx_usize, &self.chunk_layout);
                                 }
 
                                 let chunk = unsafe { &mut *page.data[page_idx_usize] };
                                 let was_present = (chunk.presence_mask >> chunk_idx) & 1 != 0;
+                                let stored = &mut rb_chunk.data[chunk_idx_usize];
 
                                 if was_present {
+                                    // The live value takes the old one's slot and is dropped
+                                    // with the snapshot.
                                     unsafe {
-                                        chunk.data[chunk_idx_usize].assume_init_drop();
+                                        std::mem::swap(
+                                            chunk.data[chunk_idx_usize].assume_init_mut(),
+                                            stored.assume_init_mut(),
+                                        );
                                     }
                                 } else {
+                                    chunk.data[chunk_idx_usize]
+                                        .write(unsafe { stored.assume_init_read() });
+                                    rb_chunk.consumed_mask |= 1u64 << chunk_idx;
                                     page.count = page.count.saturating_add(1);
                                     self.count = self.count.saturating_add(1);
+                                    note_presence::<T>(
+                                        signatures,
+                                        (storage_idx << 12) | (page_idx << 6) | chunk_idx,
+                                        true,
+                                    );
                                 }
 
-                                chunk.data[chunk_idx_usize].write(old_value);
                                 chunk.presence_mask |= 1u64 << chunk_idx;
                                 chunk.fullness_mask |= 1u64 << chunk_idx;
+                                // Restored values count as changed for Changed=[T] readers.
+                                if T::CHANGE_DETECTION {
+                                    self.fields.note(
+                                        (storage_idx << 12) | (page_idx << 6) | chunk_idx,
+                                        change_tick,
+                                        chunk.changed_ticks[chunk_idx_usize],
+                                    );
+                                    chunk.changed_ticks[chunk_idx_usize] = change_tick;
+                                    page.changed_ticks[page_idx_usize] = change_tick;
+                                    self.changed_ticks[storage_idx_usize] = change_tick;
+                                }
                                 // update page fullness bit for this chunk
                                 if chunk.presence_mask == u64::MAX {
                                     page.fullness_mask |= 1u64 << page_idx;
@@ -811,6 +1765,7 @@ impl<T: Component> Storage<T> {
                             self.fullness_mask &= self.presence_mask;
                         }
                     }
+                    self.check_shrunk_page(storage_idx);
                 }
             }
         }
@@ -819,14 +1774,139 @@ impl<T: Component> Storage<T> {
         if let Some(generation_value) = found_generation {
             self.generation = generation_value;
         }
+        self.discard_history_after(target_tick);
+        if self.shadow.rollback(target_tick) {
+            self.check_shadow(false);
+        } else {
+            self.reset_shadow(target_tick);
+        }
         self.clear_changed_masks();
     }
 
+    /// Drops the snapshots of ticks newer than `target_tick` once they have been undone,
+    /// so re-simulating those ticks records fresh history instead of appending behind
+    /// stale snapshots. The discarded snapshots are recycled through `rollback_pool`.
+    fn discard_history_after(&mut self, target_tick: Tick) {
+        while let Some(back) = self.prev.back()
+            && back.tick().is_after(target_tick)
+        {
+            if let Some(stale) = self.prev.pop_back() {
+                self.rollback_pool.push(stale);
+            }
+        }
+        if self.rollback.tick().is_after(target_tick) {
+            let replacement = match self.prev.pop_back() {
+                Some(previous) => previous,
+                None => match self.rollback_pool.pop() {
+                    Some(mut pooled) => {
+                        pooled.reset_for_tick(target_tick);
+                        pooled
+                    }
+                    None => RollbackStorage::with_tick(target_tick),
+                },
+            };
+            let stale = std::mem::replace(&mut self.rollback, replacement);
+            self.rollback_pool.push(stale);
+        }
+    }
+
+    /// Drops the oldest snapshots until at most `depth` past ticks remain in history,
+    /// recycling them through `rollback_pool`, and shrinks the pool to `depth` entries.
+    pub fn trim_history(&mut self, depth: usize) {
+        while self.prev.len() > depth {
+            if let Some(dropped) = self.prev.pop_front() {
+                self.history_floor = Some(dropped.tick());
+                self.rollback_pool.push(dropped);
+            }
+        }
+        self.rollback_pool.truncate(depth);
+    }
+
+    /// Releases the memory this storage keeps around for reuse after heavy churn: the
+    /// pooled rollback snapshots, the arena of the current snapshot if it recorded
+    /// nothing yet, and the spare capacity of the history queue. Returns how many pooled
+    /// snapshots were freed.
+    ///
+    /// Live pages and chunks are not moved, as their position is part of every index
+    /// they hold; they are already freed as soon as they empty. History is kept as is.
+    /// The next ticks allocate again what they need, so call it in quiet frames, e.g.
+    /// through `World::request_compaction`.
+    pub fn compact(&mut self) -> usize {
+        let freed = self.rollback_pool.len();
+        self.rollback_pool = Vec::new();
+        if self.rollback.changed_mask == 0 {
+            self.rollback.arena_box.release();
+        }
+        self.prev.shrink_to_fit();
+        freed
+    }
+
+    /// Returns how many history snapshots are at or before `tick`, i.e. only needed to
+    /// roll back further than `tick`.
+    pub fn history_through(&self, tick: Tick) -> usize {
+        (0..self.prev.len())
+            .take_while(|&pos| self.prev.get(pos).unwrap().tick().is_at_or_before(tick))
+            .count()
+    }
+
+    /// Frees up to `max` of the oldest history snapshots at or before `tick`, instead of
+    /// pooling them like `trim_history`. Returns how many were freed; rolling back before
+    /// `tick` fails with `Error::HistoryExhausted` afterwards.
+    pub fn discard_history_through(&mut self, tick: Tick, max: usize) -> usize {
+        let mut freed = 0;
+        while freed < max
+            && self
+                .prev
+                .get(0)
+                .is_some_and(|snapshot| snapshot.tick().is_at_or_before(tick))
+        {
+            let dropped = self.prev.pop_front().unwrap();
+            self.history_floor = Some(dropped.tick());
+            freed += 1;
+        }
+        freed
+    }
+
+    /// Frees up to `max` pooled rollback snapshots; returns how many.
+    pub fn release_pooled(&mut self, max: usize) -> usize {
+        let freed = max.min(self.rollback_pool.len());
+        self.rollback_pool
+            .truncate(self.rollback_pool.len() - freed);
+        freed
+    }
+
+    /// Clears the changed_mask of every present page and chunk, walking presence masks
+    /// instead of changed masks so partially propagated changes are cleared too.
+    pub fn reset_changed_masks(&mut self) {
+        crate::tick::check_guarded_clear::<T>();
+        self.check_shadow(true);
+        let mut storage_mask = self.presence_mask;
+        while storage_mask != 0 {
+            let storage_idx = storage_mask.trailing_zeros() as usize;
+            storage_mask &= storage_mask - 1;
+            let page = unsafe { &mut *self.data[storage_idx] };
+            let mut page_mask = page.presence_mask;
+            while page_mask != 0 {
+                let page_idx = page_mask.trailing_zeros() as usize;
+                page_mask &= page_mask - 1;
+                // Shared chunks are never changed; don't write to them.
+                let chunk = unsafe { &mut *page.data[page_idx] };
+                if chunk.changed_mask != 0 {
+                    chunk.changed_mask = 0;
+                }
+            }
+            page.changed_mask = 0;
+        }
+        self.changed_mask = 0;
+    }
+
     /// Clears the changed_mask at all levels (Storage, Page, and Chunk).
     /// This recursively clears changed_mask for all pages and chunks that have changes.
     /// Uses changed_mask & presence_mask to efficiently iterate only over changed items.
     /// Note: Does NOT clear rollback masks - those must persist for rollback operations.
     pub fn clear_changed_masks(&mut self) {
+        crate::tick::check_guarded_clear::<T>();
+        self.check_shadow(true);
         let mut storage_mask = self.changed_mask & self.presence_mask;
         while storage_mask != 0 {
             let start = storage_mask.trailing_zeros() as usize;
@@ -847,9 +1927,141 @@ impl<T: Component> Storage<T> {
                 }
                 page.changed_mask = 0;
             }
-            storage_mask &= !((u64::MAX >> (64 - run_len)) << start);
+            storage_mask &= !((u64::MAX >> (64 - run_len)) << start);
+        }
+        self.changed_mask = 0;
+    }
+
+    /// Propagates the chunk at (`storage_idx`, `page_idx`) up the hierarchy: if its
+    /// `changed_mask` is non-zero, sets the chunk's bit in the page and the page's bit in
+    /// the storage. This is the step generated systems run after each chunk; code that
+    /// writes chunk values and `changed_mask` directly (network apply, scripting) calls it
+    /// so `clear_changed_masks` and page-level change queries find the chunk.
+    ///
+    /// Only masks are updated. `Changed` filters also compare per-value change ticks,
+    /// which `set`, `get_mut` and `ViewMut` stamp at write time.
+    #[inline]
+    pub fn propagate_changed(&mut self, storage_idx: u32, page_idx: u32) {
+        if (self.presence_mask >> storage_idx) & 1 == 0 {
+            return;
+        }
+        let page = unsafe { &mut *self.data[storage_idx as usize] };
+        if (page.presence_mask >> page_idx) & 1 == 0 {
+            return;
+        }
+        if unsafe { (*page.data[page_idx as usize]).changed_mask } != 0 {
+            page.changed_mask |= 1u64 << page_idx;
+            self.changed_mask |= 1u64 << storage_idx;
+        }
+    }
+
+    /// Runs `propagate_changed` for every present chunk and returns the resulting
+    /// storage-level `changed_mask`. Bits are only ever added: pages keep the bits of
+    /// chunks freed by removals this tick.
+    pub fn summarize_changes(&mut self) -> u64 {
+        let mut storage_mask = self.presence_mask;
+        while storage_mask != 0 {
+            let storage_idx = storage_mask.trailing_zeros() as usize;
+            storage_mask &= storage_mask - 1;
+            let page = unsafe { &mut *self.data[storage_idx] };
+            let mut page_mask = page.presence_mask;
+            while page_mask != 0 {
+                let page_idx = page_mask.trailing_zeros() as usize;
+                page_mask &= page_mask - 1;
+                if unsafe { (*page.data[page_idx]).changed_mask } != 0 {
+                    page.changed_mask |= 1u64 << page_idx;
+                }
+            }
+            if page.changed_mask != 0 {
+                self.changed_mask |= 1u64 << storage_idx;
+            }
+        }
+        self.changed_mask
+    }
+
+    /// Asserts that page `storage_idx` (feature `shrink-checks`) was shrunk after
+    /// values were removed from it; see `assert_shrunk_page`. `remove` and `rollback`
+    /// call it where they may have freed chunks or the page.
+    #[inline(always)]
+    fn check_shrunk_page(&self, storage_idx: u32) {
+        #[cfg(feature = "shrink-checks")]
+        self.assert_shrunk_page(storage_idx);
+        #[cfg(not(feature = "shrink-checks"))]
+        let _ = storage_idx;
+    }
+
+    /// Panics unless every empty chunk of page `storage_idx`, and the page itself if it
+    /// is empty, was freed: each slot without values must point at the shared default
+    /// chunk or page again, each slot with values at a chunk or page of its own, and
+    /// the defaults must still be empty.
+    pub fn assert_shrunk_page(&self, storage_idx: u32) {
+        let name = std::any::type_name::<T>();
+        let default_chunk = unsafe { &*self.default_chunk_ptr };
+        let default_page = unsafe { &*self.default_page_ptr };
+        assert!(
+            default_chunk.presence_mask == 0,
+            "default chunk of {} holds values",
+            name
+        );
+        assert!(
+            default_page.presence_mask == 0
+                && default_page.count == 0
+                && default_page
+                    .data
+                    .iter()
+                    .all(|&c| std::ptr::eq(c, self.default_chunk_ptr)),
+            "default page of {} holds chunks",
+            name
+        );
+
+        let page_ptr = self.data[storage_idx as usize];
+        if (self.presence_mask >> storage_idx) & 1 == 0 {
+            assert!(
+                std::ptr::eq(page_ptr, self.default_page_ptr),
+                "page {} of {} is empty but was not freed",
+                storage_idx,
+                name
+            );
+            return;
+        }
+        assert!(
+            !std::ptr::eq(page_ptr, self.default_page_ptr),
+            "page {} of {} is present but points at the default page",
+            storage_idx,
+            name
+        );
+        let page = unsafe { &*page_ptr };
+        assert!(
+            page.presence_mask != 0,
+            "page {} of {} has no chunks left but was kept",
+            storage_idx,
+            name
+        );
+        for (page_idx, &chunk_ptr) in page.data.iter().enumerate() {
+            let is_default = std::ptr::eq(chunk_ptr, self.default_chunk_ptr);
+            if (page.presence_mask >> page_idx) & 1 == 0 {
+                assert!(
+                    is_default,
+                    "chunk {}/{} of {} is empty but was not freed",
+                    storage_idx, page_idx, name
+                );
+            } else {
+                assert!(
+                    !is_default && unsafe { (*chunk_ptr).presence_mask } != 0,
+                    "chunk {}/{} of {} is present but holds no values",
+                    storage_idx,
+                    page_idx,
+                    name
+                );
+            }
+        }
+    }
+
+    /// `assert_shrunk_page` for every page.
+    pub fn assert_shrunk(&self) {
+        for storage_idx in 0..64 {
+            self.assert_shrunk_page(storage_idx);
         }
-        self.changed_mask = 0;
     }
 
     /// Verifies that all invariants hold for this Storage and all its Pages.
@@ -888,6 +2100,328 @@ impl<T: Component> Storage<T> {
 
         true
     }
+
+    /// Records every failing level of this storage and its rollback history into `report`.
+    pub fn verify_report(&self, report: &mut InvariantReport) {
+        let violation = |rollback_tick, level| InvariantViolation {
+            component_id: T::id(),
+            type_name: std::any::type_name::<T>(),
+            rollback_tick,
+            level,
+        };
+
+        let mut total_count = 0u32;
+        let mut mask = self.presence_mask;
+        while mask != 0 {
+            let storage_idx = mask.trailing_zeros();
+            mask &= mask - 1;
+            let page = unsafe { &*self.data[storage_idx as usize] };
+            total_count = total_count.saturating_add(page.count);
+
+            let mut chunk_count = 0u32;
+            let mut chunk_mask = page.presence_mask;
+            while chunk_mask != 0 {
+                let page_idx = chunk_mask.trailing_zeros();
+                chunk_mask &= chunk_mask - 1;
+                let chunk = unsafe { &*page.data[page_idx as usize] };
+                chunk_count = chunk_count.saturating_add(chunk.presence_mask.count_ones());
+                if !chunk.verify_invariants() {
+                    report.push(violation(
+                        None,
+                        InvariantLevel::Chunk {
+                            storage_idx,
+                            page_idx,
+                        },
+                    ));
+                }
+            }
+            if page.fullness_mask & !page.presence_mask != 0
+                || page.count as usize > 64 * 64
+                || chunk_count != page.count
+            {
+                report.push(violation(None, InvariantLevel::Page { storage_idx }));
+            }
+        }
+        if self.fullness_mask & !self.presence_mask != 0
+            || self.count as usize > 64 * 64 * 64
+            || total_count != self.count
+        {
+            report.push(violation(None, InvariantLevel::Storage));
+        }
+
+        for rb in self.prev.iter().chain(std::iter::once(&self.rollback)) {
+            let mut page_mask = rb.changed_mask;
+            while page_mask != 0 {
+                let storage_idx = page_mask.trailing_zeros();
+                page_mask &= page_mask - 1;
+                let Some(rb_page) = rb.get_page(storage_idx) else {
+                    continue;
+                };
+                let mut chunk_mask = rb_page.changed_mask;
+                while chunk_mask != 0 {
+                    let page_idx = chunk_mask.trailing_zeros();
+                    chunk_mask &= chunk_mask - 1;
+                    if let Some(rb_chunk) = rb_page.get(page_idx)
+                        && !rb_chunk.verify_invariants()
+                    {
+                        report.push(violation(
+                            Some(rb.tick()),
+                            InvariantLevel::Chunk {
+                                storage_idx,
+                                page_idx,
+                            },
+                        ));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Records a `set` of `chunk_idx` in the current tick's rollback chunk, applying the
+/// same-tick idempotence rules (Add->Change = Add, Remove->Add = Change). `old_value`
+/// is the value that was overwritten, if any. Returns whether the slot had already been
+/// created or removed earlier in this tick.
+#[inline(always)]
+fn record_set_in_rollback<T>(
+    rollback_chunk: &mut RollbackChunk<T>,
+    chunk_idx: u32,
+    was_present: bool,
+    old_value: Option<T>,
+) -> (bool, bool) {
+    // Check if item was removed or created earlier in this tick (idempotent operations)
+    let was_removed = (rollback_chunk.removed_mask >> chunk_idx) & 1 != 0;
+    let was_created = (rollback_chunk.created_mask >> chunk_idx) & 1 != 0;
+
+    // Update chunk level
+    if was_created {
+        // Item was created earlier in this tick and is being modified
+        // Created + modified on same tick should remain as created only
+        // Don't store old value (created items don't have old values)
+        // Keep created_mask, clear removed_mask and changed_mask
+        rollback_chunk.removed_mask &= !(1u64 << chunk_idx);
+        rollback_chunk.changed_mask &= !(1u64 << chunk_idx);
+        rollback_chunk.created_mask |= 1u64 << chunk_idx;
+    } else if was_present {
+        // Value existed before this tick - this is a change, store old value
+        // Only store the old value on the FIRST change within the tick
+        if rollback_chunk.needs_old_value(chunk_idx)
+            && let Some(old_val) = old_value
+        {
+            rollback_chunk.data[chunk_idx as usize].write(old_val);
+        }
+        // Clear created_mask and removed_mask, set changed_mask
+        rollback_chunk.removed_mask &= !(1u64 << chunk_idx);
+        rollback_chunk.created_mask &= !(1u64 << chunk_idx);
+        rollback_chunk.changed_mask |= 1u64 << chunk_idx;
+    } else if was_removed {
+        // Value didn't exist in Storage, but was removed earlier in this tick
+        // This means the item EXISTED BEFORE and was successfully removed
+        // This is an idempotent operation: Remove->Add = Change
+        // The old value is already stored in RollbackStorage from the remove operation
+        // Clear removed_mask and created_mask, set changed_mask
+        rollback_chunk.removed_mask &= !(1u64 << chunk_idx);
+        rollback_chunk.created_mask &= !(1u64 << chunk_idx);
+        rollback_chunk.changed_mask |= 1u64 << chunk_idx;
+    } else {
+        // Value didn't exist before - this is a creation
+        // Clear removed_mask and changed_mask, set created_mask
+        rollback_chunk.removed_mask &= !(1u64 << chunk_idx);
+        rollback_chunk.changed_mask &= !(1u64 << chunk_idx);
+        rollback_chunk.created_mask |= 1u64 << chunk_idx;
+    }
+
+    (was_created, was_removed)
+}
+
+/// Cached write cursor over a `Storage`, created by `Storage::writer`.
+///
+/// Holds the storage mutably for its whole lifetime, so the cached page, chunk and
+/// rollback chunk pointers cannot be invalidated while it is alive.
+pub struct StorageWriter<'a, T: Component> {
+    storage: &'a mut Storage<T>,
+    /// `index >> 6` of the cached chunk, or `u32::MAX` before the first write.
+    key: u32,
+    page: *mut Page<T>,
+    chunk: *mut Chunk<T>,
+    rollback_chunk: *mut RollbackChunk<T>,
+}
+
+impl<'a, T: Component> StorageWriter<'a, T> {
+    /// Sets a value at the given global index, like `Storage::set`.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of range.
+    #[inline(always)]
+    pub fn set(&mut self, index: u32, value: T) {
+        let key = index >> 6;
+        if key != self.key {
+            self.seek(key);
+        }
+
+        let chunk_idx = index & 63;
+        let chunk = unsafe { &mut *self.chunk };
+        let was_present = (chunk.presence_mask >> chunk_idx) & 1 != 0;
+
+        let old_value = if was_present {
+            Some(unsafe { chunk.data[chunk_idx as usize].assume_init_read() })
+        } else {
+            None
+        };
+
+        chunk.data[chunk_idx as usize].write(value);
+        chunk.presence_mask |= 1u64 << chunk_idx;
+        chunk.fullness_mask |= 1u64 << chunk_idx;
+        chunk.changed_mask |= (1u64 << chunk_idx) & Storage::<T>::CHANGE_BITS;
+        self.storage
+            .stamp_changed(self.key >> 6, self.key & 63, 1u64 << chunk_idx);
+
+        let rollback_chunk = unsafe { &mut *self.rollback_chunk };
+        record_set_in_rollback(rollback_chunk, chunk_idx, was_present, old_value);
+
+        if !was_present {
+            let page = unsafe { &mut *self.page };
+            page.count = page.count.saturating_add(1);
+            self.storage.count = self.storage.count.saturating_add(1);
+            audit::record::<T>(self.storage.rollback.tick(), index, StructuralOp::Insert);
+            self.storage.note_presence(index, true);
+
+            if chunk.presence_mask == u64::MAX {
+                page.fullness_mask |= 1u64 << (key & 63);
+                self.storage.mark_structure_changed();
+            }
+            if page.count == 64 * 64 {
+                self.storage.fullness_mask |= 1u64 << (key >> 6);
+            }
+        }
+    }
+
+    /// Gets a reference to a value at the given global index.
+    #[inline(always)]
+    pub fn get(&self, index: u32) -> Option<&T> {
+        if index >> 6 == self.key {
+            let chunk = unsafe { &*self.chunk };
+            let chunk_idx = index & 63;
+            if (chunk.presence_mask >> chunk_idx) & 1 != 0 {
+                return Some(unsafe { chunk.data[chunk_idx as usize].assume_init_ref() });
+            }
+            return None;
+        }
+        self.storage.get(index)
+    }
+
+    /// Creates the page/chunk for `key` if needed, marks the path changed in both the
+    /// storage and its rollback snapshot, and caches the pointers.
+    #[cold]
+    fn seek(&mut self, key: u32) {
+        let page_idx = key & 63;
+        let storage_idx = key >> 6;
+        assert!(storage_idx < 64, "Storage index out of range");
+
+        let storage = &mut *self.storage;
+        let tick = storage.rollback.tick();
+        storage.snapshot_chunk_on_first_touch(tick, storage_idx, page_idx);
+        if (storage.presence_mask >> storage_idx) & 1 == 0 {
+            let new_page = Box::new(Page::new(storage.default_chunk_ptr));
+            storage.data[storage_idx as usize] = Box::into_raw(new_page);
+            storage.presence_mask |= 1u64 << storage_idx;
+        }
+        storage.changed_mask |= (1u64 << storage_idx) & Storage::<T>::CHANGE_BITS;
+
+        let page = unsafe { &mut *storage.data[storage_idx as usize] };
+        if (page.presence_mask >> page_idx) & 1 == 0 {
+            page.data[page_idx as usize] = storage.chunk_layout.alloc();
+            page.presence_mask |= 1u64 << page_idx;
+            storage.mark_structure_changed();
+        }
+        page.changed_mask |= (1u64 << page_idx) & Storage::<T>::CHANGE_BITS;
+
+        // get_or_create_* mark the rollback page/storage changed bits themselves
+        let rollback_page = storage.rollback.get_or_create_page(storage_idx);
+        let rollback_chunk: *mut RollbackChunk<T> = rollback_page.get_or_create_chunk(page_idx);
+
+        self.key = key;
+        self.page = page;
+        self.chunk = page.data[page_idx as usize];
+        self.rollback_chunk = rollback_chunk;
+    }
+}
+
+/// Resumable position over the present indices of a `Storage`.
+///
+/// The cursor only holds the next index to visit, so it can be kept in a system's state
+/// and continued on a later tick: budgeted systems process a few items per tick without
+/// rescanning from index 0. Items added or removed in between are picked up or skipped
+/// through the presence masks.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct StorageCursor {
+    position: u32,
+}
+
+impl StorageCursor {
+    /// Index past the last slot of a storage; a cursor here is finished.
+    pub const END: u32 = 64 * 64 * 64;
+
+    pub const fn new() -> Self {
+        Self { position: 0 }
+    }
+
+    /// Creates a cursor that resumes at `position`.
+    pub const fn at(position: u32) -> Self {
+        Self { position }
+    }
+
+    /// The next index the cursor will consider.
+    pub fn position(&self) -> u32 {
+        self.position
+    }
+
+    /// Returns true once the cursor has passed the last present index.
+    pub fn is_finished(&self) -> bool {
+        self.position >= Self::END
+    }
+
+    /// Moves the cursor back to index 0.
+    pub fn reset(&mut self) {
+        self.position = 0;
+    }
+
+    /// Returns the next present index of `storage` and advances past it, or None
+    /// (finishing the cursor) when no present index remains.
+    pub fn next<T: Component>(&mut self, storage: &Storage<T>) -> Option<u32> {
+        match storage.next_present(self.position) {
+            Some(index) => {
+                self.position = index + 1;
+                Some(index)
+            }
+            None => {
+                self.position = Self::END;
+                None
+            }
+        }
+    }
+
+    /// Visits up to `budget` present indices, returning how many were visited.
+    /// Restarts from index 0 on the call after the cursor finished.
+    pub fn step<T: Component>(
+        &mut self,
+        storage: &Storage<T>,
+        budget: usize,
+        mut f: impl FnMut(u32, &T),
+    ) -> usize {
+        if self.is_finished() {
+            self.reset();
+        }
+        let mut visited = 0;
+        while visited < budget {
+            let Some(index) = self.next(storage) else {
+                break;
+            };
+            f(index, storage.get(index).unwrap());
+            visited += 1;
+        }
+        visited
+    }
 }
 
 impl<T: Component> StorageLike for Storage<T> {
@@ -895,10 +2429,192 @@ impl<T: Component> StorageLike for Storage<T> {
         Storage::verify_invariants(self)
     }
 
+    fn verify_report(&self, report: &mut InvariantReport) {
+        Storage::verify_report(self, report)
+    }
+
+    fn component_type_id(&self) -> TypeId {
+        TypeId::of::<T>()
+    }
+
+    fn component_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    fn checksum(&self) -> u64 {
+        let mut hash = crate::rng::FNV_OFFSET;
+        let mut cursor = self.cursor();
+        while let Some(index) = cursor.next(self) {
+            hash = crate::rng::fnv1a(hash, &index.to_le_bytes());
+            if let Some(text) = self.get(index).and_then(T::desync_debug) {
+                hash = crate::rng::fnv1a(hash, text.as_bytes());
+            }
+        }
+        hash
+    }
+
+    fn remove(&mut self, frame: &crate::frame::Frame, index: u32) -> bool {
+        Storage::remove(self, frame, index)
+    }
+
+    fn clone_value(&mut self, frame: &crate::frame::Frame, src: u32, dst: u32) -> bool {
+        let Some(value) = self.get(src).cloned() else {
+            return false;
+        };
+        self.set(frame, dst, value);
+        true
+    }
+
+    fn for_each_touched_at(&self, tick: Tick, f: &mut dyn FnMut(u32, bool, bool)) {
+        Storage::for_each_touched_at(self, tick, f);
+    }
+
+    fn is_event(&self) -> bool {
+        T::EVENT
+    }
+
+    fn is_resource(&self) -> bool {
+        T::RESOURCE
+    }
+
+    fn move_to(&mut self, frame: &crate::frame::Frame, index: u32, dst: &mut World) -> bool {
+        let Some(value) = self.get(index).cloned() else {
+            return false;
+        };
+        self.remove(frame, index);
+        let dst_frame = dst.frame();
+        dst.get_storage_mut::<T>().set(&dst_frame, index, value);
+        true
+    }
+
+    fn branch_into(&self, dst: &mut World) {
+        let tick = dst.current_tick();
+        self.share_into(dst.get_storage_mut::<T>(), tick);
+    }
+
+    fn extract_changed_into(&self, since: Tick, dst: &mut World) {
+        let frame = dst.frame();
+        self.extract_changed_into(since, dst.get_storage_mut::<T>(), &frame);
+    }
+
+    fn diff_into(
+        &self,
+        other: Option<&dyn StorageLike>,
+        self_is_a: bool,
+        limit: usize,
+        out: &mut Vec<Divergence>,
+    ) {
+        let other = other.and_then(|o| o.as_any().downcast_ref::<Storage<T>>());
+        let (a, b) = if self_is_a {
+            (Some(self), other)
+        } else {
+            (other, Some(self))
+        };
+        let page = |s: Option<&Storage<T>>, i: u32| match s {
+            Some(s) if (s.presence_mask >> i) & 1 != 0 => Some(unsafe { &*s.data[i as usize] }),
+            _ => None,
+        };
+        let chunk = |p: Option<&Page<T>>, i: u32| match p {
+            Some(p) if (p.presence_mask >> i) & 1 != 0 => Some(unsafe { &*p.data[i as usize] }),
+            _ => None,
+        };
+        let presence = |c: Option<&Chunk<T>>| c.map_or(0, |c| c.presence_mask);
+        let value = |c: Option<&Chunk<T>>, i: u32| {
+            c.and_then(|c| unsafe { c.data[i as usize].assume_init_ref() }.desync_debug())
+        };
+
+        let mut storage_mask = a.map_or(0, |s| s.presence_mask) | b.map_or(0, |s| s.presence_mask);
+        while storage_mask != 0 && out.len() < limit {
+            let storage_idx = storage_mask.trailing_zeros();
+            storage_mask &= storage_mask - 1;
+            let (page_a, page_b) = (page(a, storage_idx), page(b, storage_idx));
+            let mut page_mask =
+                page_a.map_or(0, |p| p.presence_mask) | page_b.map_or(0, |p| p.presence_mask);
+            while page_mask != 0 && out.len() < limit {
+                let page_idx = page_mask.trailing_zeros();
+                page_mask &= page_mask - 1;
+                let (chunk_a, chunk_b) = (chunk(page_a, page_idx), chunk(page_b, page_idx));
+                let mut item_mask = presence(chunk_a) | presence(chunk_b);
+                while item_mask != 0 && out.len() < limit {
+                    let i = item_mask.trailing_zeros();
+                    item_mask &= item_mask - 1;
+                    let kind = match (
+                        (presence(chunk_a) >> i) & 1 != 0,
+                        (presence(chunk_b) >> i) & 1 != 0,
+                    ) {
+                        (true, false) => DivergenceKind::OnlyInA,
+                        (false, true) => DivergenceKind::OnlyInB,
+                        _ => {
+                            let (va, vb) = unsafe {
+                                (
+                                    chunk_a.unwrap().data[i as usize].assume_init_ref(),
+                                    chunk_b.unwrap().data[i as usize].assume_init_ref(),
+                                )
+                            };
+                            if va.desync_eq(vb) {
+                                continue;
+                            }
+                            DivergenceKind::ValueMismatch
+                        }
+                    };
+                    out.push(Divergence {
+                        component: std::any::type_name::<T>(),
+                        index: (storage_idx << 12) | (page_idx << 6) | i,
+                        kind,
+                        a: value(chunk_a, i),
+                        b: value(chunk_b, i),
+                    });
+                }
+            }
+        }
+    }
+
     fn rollback(&mut self, target_tick: Tick) {
         Storage::rollback(self, target_tick)
     }
 
+    fn history_floor(&self) -> Option<Tick> {
+        self.history_floor
+    }
+
+    fn trim_history(&mut self, depth: usize) {
+        Storage::trim_history(self, depth);
+    }
+
+    fn pack_history(&mut self) {
+        Storage::pack_history(self);
+    }
+
+    fn compact(&mut self) -> usize {
+        Storage::compact(self)
+    }
+
+    fn pooled_snapshots(&self) -> usize {
+        self.rollback_pool.len()
+    }
+
+    fn release_pooled(&mut self, max: usize) -> usize {
+        Storage::release_pooled(self, max)
+    }
+
+    fn history_through(&self, tick: Tick) -> usize {
+        Storage::history_through(self, tick)
+    }
+
+    fn discard_history_through(&mut self, tick: Tick, max: usize) -> usize {
+        Storage::discard_history_through(self, tick, max)
+    }
+
+    fn structure_generation(&self) -> u64 {
+        self.structure_version
+    }
+
+    fn has_changes_after(&self, tick: Tick) -> bool {
+        std::iter::once(&self.rollback)
+            .chain(self.prev.iter())
+            .any(|rb| rb.tick().is_after(tick) && rb.changed_mask != 0)
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -914,6 +2630,14 @@ impl<T: Component> StorageLike for Storage<T> {
     fn clear_changed_masks_all_levels(&mut self) {
         self.clear_changed_masks();
     }
+
+    fn reset_changed_masks(&mut self) {
+        Storage::reset_changed_masks(self)
+    }
+
+    fn rotate_rollback_tick(&mut self, tick: Tick) {
+        self.ensure_rollback_tick(tick);
+    }
 }
 
 impl<T: Component> Default for Storage<T> {
@@ -935,63 +2659,28 @@ impl Storage<crate::entity::Entity> {
         self.generation = self.rollback.get_saved_generation();
     }
 
-    /// Finds the first zero bit (first free index) in a mask.
-    /// Returns the index of the first 0 bit, or None if all bits are set.
-    /// Uses trailing_ones() to find how many consecutive 1s are at the start,
-    /// which directly gives us the position of the first 0.
-    fn first_0_index(mask: u64) -> Option<usize> {
-        let ones = mask.trailing_ones() as usize;
-        if ones == 64 { None } else { Some(ones) }
-    }
-
     /// Spawns a new entity by finding the first free index.
     ///
     /// Uses global generation counter that wraps at 64 bits.
-    /// Returns `Some(Entity)` if a free slot was found, `None` if storage is full.
+    /// Returns `Some(Entity)` if a free slot was found, `None` if storage is full
+    /// or an allocation failed. See `try_spawn` for the reason.
     pub fn spawn(&mut self, frame: &crate::frame::Frame) -> Option<crate::entity::Entity> {
+        self.try_spawn(frame).ok()
+    }
+
+    /// Spawns a new entity, returning `Error::StorageFull` when every slot is taken
+    /// and `Error::AllocationFailed` when the backing page/chunk cannot be allocated.
+    pub fn try_spawn(&mut self, frame: &crate::frame::Frame) -> Result<crate::entity::Entity> {
         // Increment generation for new entity
         self.generation = self.generation.wrapping_add(1);
 
-        // Find first non-full storage slot using mask
-        if let Some(storage_idx) = Self::first_0_index(self.fullness_mask) {
-            let storage_bit = 1u64 << storage_idx;
-
-            // Ensure page exists
-            if (self.presence_mask & storage_bit) == 0 {
-                let new_page = Box::new(Page::new(self.default_chunk_ptr));
-                self.data[storage_idx] = Box::into_raw(new_page);
-                self.presence_mask |= storage_bit;
-            }
-
-            let page = unsafe { &mut *self.data[storage_idx] };
-
-            // Find first non-full chunk in page using mask
-            if let Some(page_idx) = Self::first_0_index(page.fullness_mask) {
-                let page_bit = 1u64 << page_idx;
-
-                // Ensure chunk exists
-                if (page.presence_mask & page_bit) == 0 {
-                    let new_chunk = Box::new(crate::storage::Chunk::new());
-                    page.data[page_idx] = Box::into_raw(new_chunk);
-                    page.presence_mask |= page_bit;
-                }
-
-                let chunk = unsafe { &mut *page.data[page_idx] };
-
-                // Find first free index in chunk using mask
-                if let Some(chunk_idx) = Self::first_0_index(chunk.fullness_mask) {
-                    let global_index = (storage_idx * 64 * 64 + page_idx * 64 + chunk_idx) as u32;
-                    let entity = crate::entity::Entity::new(global_index, self.generation);
+        let global_index = self.first_free_index().ok_or(Error::StorageFull)?;
+        let entity = crate::entity::Entity::new(global_index, self.generation);
 
-                    // Set the entity in storage
-                    self.set(frame, global_index, entity);
-
-                    return Some(entity);
-                }
-            }
-        }
+        // Set the entity in storage (allocates the page/chunk if needed)
+        self.try_set(frame, global_index, entity)?;
 
-        None // Storage is full
+        Ok(entity)
     }
 }
 
@@ -1064,6 +2753,8 @@ impl Storage<crate::hierarchy::ChildOf> {
 
         let bit = 1u64 << chunk_idx;
 
+        self.snapshot_chunk_on_first_touch(frame.current_tick, storage_idx, page_idx);
+
         let page_was_new = (self.presence_mask >> storage_idx) & 1 == 0;
         if page_was_new {
             let new_page = Box::new(Page::new(self.default_chunk_ptr));
@@ -1075,8 +2766,7 @@ impl Storage<crate::hierarchy::ChildOf> {
         let page = unsafe { &mut *self.data[storage_idx as usize] };
         let chunk_was_new = (page.presence_mask >> page_idx) & 1 == 0;
         if chunk_was_new {
-            let new_chunk = Box::new(Chunk::new());
-            page.data[page_idx as usize] = Box::into_raw(new_chunk);
+            page.data[page_idx as usize] = self.chunk_layout.alloc();
             page.presence_mask |= 1u64 << page_idx;
             page.changed_mask |= 1u64 << page_idx;
         }
@@ -1091,7 +2781,7 @@ impl Storage<crate::hierarchy::ChildOf> {
 
         if was_present {
             // store old value once
-            if (rb_chunk.changed_mask & bit) == 0 && (rb_chunk.removed_mask & bit) == 0 {
+            if rb_chunk.needs_old_value(chunk_idx) {
                 let old_val = unsafe { chunk.data[chunk_idx as usize].assume_init_ref().clone() };
                 rb_chunk.data[chunk_idx as usize].write(old_val);
             }
@@ -1125,6 +2815,15 @@ impl Storage<crate::hierarchy::ChildOf> {
 
             page.count = page.count.saturating_add(1);
             self.count = self.count.saturating_add(1);
+            audit::record::<crate::hierarchy::ChildOf>(
+                frame.current_tick,
+                index,
+                StructuralOp::Insert,
+            );
+            // `note_presence` would borrow all of self while the rollback page is held.
+            if let Some(signatures) = unsafe { self.signatures.as_mut() } {
+                signatures.set(index, crate::hierarchy::ChildOf::id(), true);
+            }
 
             // rollback mark as created
             rb_chunk.removed_mask &= !bit;
@@ -1135,6 +2834,9 @@ impl Storage<crate::hierarchy::ChildOf> {
         }
 
         // update page and storage masks
+        if chunk_was_new || (!was_present && chunk.presence_mask == u64::MAX) {
+            self.mark_structure_changed();
+        }
         if !chunk_was_new {
             page.presence_mask |= 1u64 << page_idx;
         }
@@ -1156,6 +2858,10 @@ impl Storage<crate::hierarchy::ChildOf> {
         }
         self.fullness_mask &= self.presence_mask;
         self.changed_mask |= 1u64 << storage_idx;
+        self.stamp_changed(storage_idx, page_idx, bit);
+        if !was_present {
+            self.shadow.mirror(index, true, || self.get(index));
+        }
     }
 }
 
@@ -1171,6 +2877,14 @@ impl<T: Component> Drop for Storage<T> {
                 let page_ptr = self.data[i];
                 // Mask indicates page exists, so it cannot be the default pointer
                 unsafe {
+                    let page = &mut *page_ptr;
+                    let mut chunks = page.presence_mask;
+                    while chunks != 0 {
+                        let c = chunks.trailing_zeros() as usize;
+                        self.chunk_layout.release(page.data[c]);
+                        chunks &= chunks - 1;
+                    }
+                    page.presence_mask = 0;
                     drop(Box::from_raw(page_ptr));
                 }
             }
@@ -1190,27 +2904,108 @@ impl<T: Component> Drop for Storage<T> {
 /// # Mask Semantics
 ///
 /// See Storage documentation for details on presence_mask and fullness_mask.
+/// `Storage::note_presence` for code that holds part of the storage borrowed.
+#[inline(always)]
+fn note_presence<T: Component>(signatures: *mut Signatures, index: u32, present: bool) {
+    if let Some(signatures) = unsafe { signatures.as_mut() } {
+        signatures.set(index, T::id(), present);
+    }
+}
+
+/// Bits of `mask` whose entry in `ticks` is after `since` (wrap-aware, so stamps must
+/// stay within 2^31 change ticks of `since`).
+#[inline]
+pub fn changed_since(ticks: &[Tick; 64], mask: u64, since: Tick) -> u64 {
+    let mut out = 0u64;
+    let mut m = mask;
+    while m != 0 {
+        let i = m.trailing_zeros();
+        if ticks[i as usize].is_after(since) {
+            out |= 1u64 << i;
+        }
+        m &= m - 1;
+    }
+    out
+}
+
+/// Requests the cache line at `ptr` into all cache levels; never faults.
+#[cfg(all(feature = "prefetch", any(target_arch = "x86", target_arch = "x86_64")))]
+#[inline(always)]
+fn prefetch_read(ptr: *const u8) {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::{_MM_HINT_T0, _mm_prefetch};
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::{_MM_HINT_T0, _mm_prefetch};
+    unsafe { _mm_prefetch::<_MM_HINT_T0>(ptr as *const i8) }
+}
+
 #[repr(align(64))]
 pub struct Page<T: Component> {
     pub presence_mask: u64,
     pub fullness_mask: u64,
     pub changed_mask: u64,
     pub count: u32,
+    /// Newest change tick stamped on each chunk; see `Chunk::changed_ticks`.
+    pub changed_ticks: [Tick; 64],
     pub data: [*mut Chunk<T>; 64],
 }
 
 impl<T: Component> Page<T> {
     /// Creates a new Page.
     pub fn new(default_chunk_ptr: *const Chunk<T>) -> Self {
+        record_alloc::<T>(AllocKind::Page);
         Self {
             presence_mask: 0,
             fullness_mask: 0,
             changed_mask: 0,
             count: 0,
+            changed_ticks: [Tick(0); 64],
             data: [default_chunk_ptr as *mut Chunk<T>; 64],
         }
     }
 
+    /// Present chunks holding a value written after `since`.
+    #[inline]
+    pub fn changed_since(&self, since: Tick) -> u64 {
+        changed_since(&self.changed_ticks, self.presence_mask, since)
+    }
+
+    /// Starts loading the masks and first values of the lowest chunk in `page_mask`, the
+    /// one a query visits next, while it works on the current one. Only with the
+    /// `prefetch` feature on x86 targets; a no-op otherwise.
+    #[inline(always)]
+    pub fn prefetch_next(&self, page_mask: u64) {
+        #[cfg(all(feature = "prefetch", any(target_arch = "x86", target_arch = "x86_64")))]
+        if page_mask != 0 {
+            let chunk = self.data[page_mask.trailing_zeros() as usize];
+            prefetch_read(chunk as *const u8);
+            prefetch_read(unsafe { std::ptr::addr_of!((*chunk).data) } as *const u8);
+        }
+        #[cfg(not(all(feature = "prefetch", any(target_arch = "x86", target_arch = "x86_64"))))]
+        let _ = page_mask;
+    }
+
+    /// Replaces the present chunk at `page_idx` with a private copy allocated from
+    /// `layout` if it is shared with another storage. Call it before writing the chunk.
+    #[inline(always)]
+    pub fn unshare_chunk(&mut self, page_idx: usize, layout: &ChunkLayout) {
+        if unsafe { (*self.data[page_idx]).is_shared() } {
+            self.copy_shared_chunk(page_idx, layout);
+        }
+    }
+
+    #[cold]
+    fn copy_shared_chunk(&mut self, page_idx: usize, layout: &ChunkLayout) {
+        let shared = self.data[page_idx];
+        let copy = layout.alloc::<T>();
+        unsafe {
+            (*copy).clone_from_chunk(&*shared);
+            (*copy).changed_mask = (*shared).changed_mask;
+            layout.release(shared);
+        }
+        self.data[page_idx] = copy;
+    }
+
     /// Verifies that all invariants hold for this Page.
     pub fn verify_invariants(&self) -> bool {
         if self.fullness_mask & !self.presence_mask != 0 {
@@ -1249,19 +3044,9 @@ impl<T: Component> Page<T> {
 
 impl<T: Component> Drop for Page<T> {
     fn drop(&mut self) {
-        // Only drop chunks where presence_mask indicates they exist
-        let mut mask = self.presence_mask;
-        while mask != 0 {
-            let start = mask.trailing_zeros() as usize;
-            let shifted = mask >> start;
-            let run_len = shifted.trailing_ones() as usize;
-            for i in start..start + run_len {
-                unsafe {
-                    drop(Box::from_raw(self.data[i]));
-                }
-            }
-            mask &= !((u64::MAX >> (64 - run_len)) << start);
-        }
+        record_free::<T>(AllocKind::Page);
+        // Chunks are freed by the owning storage, which knows their `ChunkLayout`.
+        debug_assert!(self.presence_mask == 0, "page dropped with live chunks");
     }
 }
 
@@ -1271,7 +3056,13 @@ pub struct Chunk<T: Component> {
     pub presence_mask: u64,
     pub fullness_mask: u64,
     pub changed_mask: u64,
-    pub data: [MaybeUninit<T>; 64],
+    /// `World::change_tick` at the last write of each value. Unlike `changed_mask`
+    /// nothing clears these, so readers compare them with their own last run instead
+    /// of relying on a global clear between ticks.
+    pub changed_ticks: [Tick; 64],
+    pub data: [Slot<T>; 64],
+    /// Storages holding this chunk: more than one after `World::branch` shared it.
+    refs: AtomicU32,
 }
 
 impl<T: Component> Default for Chunk<T> {
@@ -1283,12 +3074,53 @@ impl<T: Component> Default for Chunk<T> {
 impl<T: Component> Chunk<T> {
     /// Creates a new empty Chunk.
     pub fn new() -> Self {
+        record_alloc::<T>(AllocKind::Chunk);
         Self {
             presence_mask: 0,
             fullness_mask: 0,
             changed_mask: 0,
-            data: [const { MaybeUninit::uninit() }; 64],
+            changed_ticks: [Tick(0); 64],
+            data: [Slot::UNINIT; 64],
+            refs: AtomicU32::new(1),
+        }
+    }
+
+    /// Whether another storage shares this chunk, so it must be copied before a write.
+    #[inline(always)]
+    pub fn is_shared(&self) -> bool {
+        self.refs.load(Ordering::Acquire) > 1
+    }
+
+    /// Adds a storage holding this chunk.
+    pub(crate) fn share(&self) {
+        self.refs.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Drops a storage's hold on this chunk; returns true if it was the last one, which
+    /// must free it.
+    pub(crate) fn release(&self) -> bool {
+        self.refs.fetch_sub(1, Ordering::AcqRel) == 1
+    }
+
+    /// Copies the values and masks of `other` into this empty chunk, except
+    /// `changed_mask`, which starts clear.
+    pub(crate) fn clone_from_chunk(&mut self, other: &Chunk<T>) {
+        debug_assert!(self.presence_mask == 0);
+        let mut m = other.presence_mask;
+        while m != 0 {
+            let i = m.trailing_zeros() as usize;
+            self.data[i].write(unsafe { other.data[i].assume_init_ref().clone() });
+            m &= m - 1;
         }
+        self.presence_mask = other.presence_mask;
+        self.fullness_mask = other.fullness_mask;
+        self.changed_ticks = other.changed_ticks;
+    }
+
+    /// Present values written after `since`.
+    #[inline]
+    pub fn changed_since(&self, since: Tick) -> u64 {
+        changed_since(&self.changed_ticks, self.presence_mask, since)
     }
 
     /// Verifies that all invariants hold for this Chunk.
@@ -1300,6 +3132,7 @@ impl<T: Component> Chunk<T> {
 
 impl<T: Component> Drop for Chunk<T> {
     fn drop(&mut self) {
+        record_free::<T>(AllocKind::Chunk);
         // Only drop values where presence_mask indicates they exist
         let mut mask = self.presence_mask;
         while mask != 0 {
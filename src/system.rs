@@ -1,8 +1,13 @@
 use crate::component::{Component, Destroyed};
+use crate::entity::Entity;
+use crate::event::DespawnedEvent;
+use crate::resource::{RESOURCE_INDEX, RollbackConfig};
 use crate::storage::Storage;
+use crate::tick::Tick;
 use crate::world::World;
-use decs::world::{CleanupGroup, SimulationGroup};
+use decs::world::{CleanupGroup, DestroyGroup, EndOfTickGroup, SimulationGroup, StorageTable};
 use std::any::{Any, TypeId};
+use std::cell::Cell;
 use std::marker::PhantomData;
 
 pub trait SystemGroup: Any + Send + Sync + 'static {
@@ -53,22 +58,326 @@ pub trait System: Any + Send + Sync + 'static {
         &[]
     }
 
+    /// The storages the system borrows when it runs, as taken from its parameters.
+    /// Generated by `system!`; `Scheduler::validate` checks them against the world and
+    /// against `reads`/`writes`. Systems returning an empty list are not checked.
+    fn accesses(&self) -> Vec<StorageAccess> {
+        Vec::new()
+    }
+
     fn parent(&self) -> Option<&dyn SystemGroup> {
         Some(SimulationGroup::instance())
     }
 
+    /// Whether the system writes nothing: no storage in `writes()`, no commands and
+    /// not `exclusive`. `system!` systems without `&mut ViewMut`, mutable lookups or
+    /// `Commands` return true and implement `ReadOnlySystem`.
+    fn read_only(&self) -> bool {
+        false
+    }
+
+    /// Whether the system may touch any storage (like `ApplyCommandsSystem`). The
+    /// scheduler treats exclusive systems as conflicting with every other system, so
+    /// group ordering always applies to them.
+    fn exclusive(&self) -> bool {
+        false
+    }
+
     /// Returns a reference to the underlying Any trait object for downcasting.
     fn as_any(&self) -> &dyn Any;
 
     fn debug_counts(&self) -> (usize, usize) {
         (0, 0)
     }
+
+    /// Explains why the system's query would skip `index` on its next run: one entry
+    /// per failing filter, empty if the index matches. Debugging aid generated by
+    /// `system!`; systems without a query return an empty list.
+    fn explain(&self, _index: u32) -> Vec<QueryMismatch> {
+        Vec::new()
+    }
+
+    /// Returns how many indices the system's query would visit if it ran now, counted
+    /// from the presence, changed and index-range masks alone: the query function is not
+    /// called and nothing is written. Generated by `system!`; systems without a query
+    /// return 0.
+    fn match_count(&self) -> usize {
+        0
+    }
+
+    /// Whether `match_count` is nonzero, stopping at the first matching chunk. Cheap
+    /// enough for run conditions and editor UIs.
+    fn matches_any(&self) -> bool {
+        self.match_count() != 0
+    }
+
+    /// Returns and clears the first error of the last run. `system!` systems whose query
+    /// function returns `Result<(), SystemError>` record it; the scheduler collects it
+    /// after every run (see `Scheduler::last_errors`).
+    fn take_error(&self) -> Option<SystemError> {
+        None
+    }
+}
+
+/// Marker for systems that only read, implemented by `system!` for those whose
+/// `System::read_only` is true. Read-only systems never conflict with one another, so
+/// the scheduler runs each as late as the systems depending on it allow, gathering
+/// them into shared wavefronts instead of spreading them over the schedule.
+pub trait ReadOnlySystem: System {}
+
+/// A recoverable failure reported by a system, e.g. a missing asset, instead of a panic.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SystemError {
+    pub message: String,
+    /// Index the query function failed at; filled in by `system!`.
+    pub index: Option<u32>,
+}
+
+impl SystemError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            index: None,
+        }
+    }
+}
+
+impl From<&str> for SystemError {
+    fn from(message: &str) -> Self {
+        Self::new(message)
+    }
+}
+
+impl From<String> for SystemError {
+    fn from(message: String) -> Self {
+        Self::new(message)
+    }
+}
+
+impl std::fmt::Display for SystemError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.index {
+            Some(index) => write!(f, "{} (at index {})", self.message, index),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for SystemError {}
+
+/// A storage a system borrows; see `System::accesses`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StorageAccess {
+    pub component: TypeId,
+    pub name: &'static str,
+    pub mutable: bool,
+    /// The storage the system holds, to tell it apart from another world's.
+    pub storage: *const (),
+}
+
+impl StorageAccess {
+    pub fn new<T: Component>(storage: *const Storage<T>, mutable: bool) -> Self {
+        Self {
+            component: TypeId::of::<T>(),
+            name: std::any::type_name::<T>(),
+            mutable,
+            storage: storage as *const (),
+        }
+    }
+}
+
+/// A query filter that rejects an index; see `System::explain`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QueryMismatch {
+    /// A required component (a view parameter, `All` or `Changed` type) is absent.
+    Missing(&'static str),
+    /// A `Changed` component is present but was not written since the system last ran.
+    Unchanged(&'static str),
+    /// A `None` component is present.
+    Excluded(&'static str),
+    /// The index is outside the system's `set_index_range`.
+    OutOfRange,
+}
+
+impl std::fmt::Display for QueryMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryMismatch::Missing(component) => write!(f, "missing required {}", component),
+            QueryMismatch::Unchanged(component) => {
+                write!(f, "{} not changed since the last run", component)
+            }
+            QueryMismatch::Excluded(component) => write!(f, "excluded by None=[{}]", component),
+            QueryMismatch::OutOfRange => write!(f, "outside the system's index range"),
+        }
+    }
+}
+
+/// Page-level matches of a generated query, cached between runs.
+///
+/// Holds the candidate page mask of every matching storage index and stays valid while
+/// the `structure_version` of each of the query's storages is unchanged. Queries with
+/// `Changed` filters depend on per-tick masks and are not cached.
+#[derive(Default)]
+pub struct QueryPlanCache {
+    versions: Vec<u64>,
+    pages: Vec<(usize, u64)>,
+    valid: bool,
+    rebuilds: u64,
+}
+
+impl QueryPlanCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if the plan was built against exactly these storage versions.
+    #[inline(always)]
+    pub fn is_current(&self, versions: &[u64]) -> bool {
+        self.valid && self.versions == versions
+    }
+
+    /// Clears the plan so it can be refilled for `versions`.
+    pub fn rebuild(&mut self, versions: &[u64]) {
+        self.versions.clear();
+        self.versions.extend_from_slice(versions);
+        self.pages.clear();
+        self.valid = true;
+        self.rebuilds += 1;
+    }
+
+    /// Records the candidate page mask of a matching storage index.
+    #[inline(always)]
+    pub fn push(&mut self, storage_idx: usize, page_mask: u64) {
+        self.pages.push((storage_idx, page_mask));
+    }
+
+    /// Forces a rescan on the next run.
+    pub fn invalidate(&mut self) {
+        self.valid = false;
+    }
+
+    /// The cached `(storage_idx, page_mask)` pairs, in ascending storage index order.
+    pub fn pages(&self) -> &[(usize, u64)] {
+        &self.pages
+    }
+
+    /// Number of times the plan has been rescanned.
+    pub fn rebuilds(&self) -> u64 {
+        self.rebuilds
+    }
+}
+
+/// Half-open range of global indices a generated system iterates; see the generated
+/// `set_index_range`. Gives the range as masks at each level of a storage, for
+/// intersection with the storage, page and chunk masks of a query.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IndexRange {
+    start: u32,
+    end: u32,
+}
+
+impl Default for IndexRange {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+impl IndexRange {
+    /// Every index of a storage.
+    pub const ALL: Self = Self {
+        start: 0,
+        end: 64 * 64 * 64,
+    };
+
+    /// Clamps `range` to the indices of a storage.
+    pub fn new(range: std::ops::Range<u32>) -> Self {
+        let end = range.end.min(Self::ALL.end);
+        Self {
+            start: range.start.min(end),
+            end,
+        }
+    }
+
+    pub fn range(&self) -> std::ops::Range<u32> {
+        self.start..self.end
+    }
+
+    /// Packs the range into one word, so a system can keep it in an `AtomicU64`.
+    pub fn to_bits(self) -> u64 {
+        ((self.start as u64) << 32) | self.end as u64
+    }
+
+    /// Inverse of `to_bits`.
+    pub fn from_bits(bits: u64) -> Self {
+        Self {
+            start: (bits >> 32) as u32,
+            end: bits as u32,
+        }
+    }
+
+    #[inline(always)]
+    pub fn contains(&self, index: u32) -> bool {
+        self.start <= index && index < self.end
+    }
+
+    /// Storage indices holding an index of the range.
+    #[inline(always)]
+    pub fn storage_mask(&self) -> u64 {
+        self.level_mask(0, 12)
+    }
+
+    /// Chunks of page `storage_idx` holding an index of the range.
+    #[inline(always)]
+    pub fn page_mask(&self, storage_idx: usize) -> u64 {
+        self.level_mask((storage_idx as u32) << 12, 6)
+    }
+
+    /// Values of the chunk `storage_idx`/`page_idx` in the range.
+    #[inline(always)]
+    pub fn chunk_mask(&self, storage_idx: usize, page_idx: usize) -> u64 {
+        self.level_mask(((storage_idx as u32) << 12) | ((page_idx as u32) << 6), 0)
+    }
+
+    /// Bits of the 64 slots of `1 << shift` indices each from `base` that overlap the
+    /// range.
+    #[inline(always)]
+    fn level_mask(&self, base: u32, shift: u32) -> u64 {
+        if *self == Self::ALL {
+            return u64::MAX;
+        }
+        let lo = self.start.max(base);
+        let hi = self.end.min(base + (64 << shift));
+        if lo >= hi {
+            return 0;
+        }
+        let first = (lo - base) >> shift;
+        let last = (hi - 1 - base) >> shift;
+        (u64::MAX << first) & (u64::MAX >> (63 - last))
+    }
 }
 
 pub struct ComponentCleanupSystem<T: Component> {
     pub writes: [TypeId; 1],
     pub t_storage: *mut Storage<T>,
     pub destroyed_storage: *const Storage<Destroyed>,
+    /// The World's destroyed retention when it applies to `T` (the Entity cleanup),
+    /// else null; see `World::set_destroyed_retention`.
+    pub retention: *const Cell<u32>,
+}
+
+/// Returns true once the entity destroyed at `index` was kept for `retention` ticks, so
+/// its slot may be freed. Entities whose destruction left the rollback history count as
+/// expired.
+fn retention_expired(
+    destroyed: &Storage<Destroyed>,
+    index: u32,
+    now: Tick,
+    retention: u32,
+) -> bool {
+    retention == 0
+        || destroyed
+            .last_changed_tick(index)
+            .is_none_or(|at| !at.wrapping_add(retention).is_after(now))
 }
 
 // Safety: DestroySystem is only used within the same thread context where the storages are valid.
@@ -86,9 +395,31 @@ impl<T: Component> ComponentCleanupSystem<T> {
             writes: [TypeId::of::<T>()],
             t_storage: t_ptr,
             destroyed_storage: d_ptr,
+            retention: std::ptr::null(),
         }
     }
 
+    /// Clears from `mask`, the destroyed indices of chunk `storage_idx`/`page_idx`, the
+    /// entities still within the retention.
+    fn expired(&self, now: Tick, storage_idx: usize, page_idx: usize, mask: u64) -> u64 {
+        let retention = unsafe { self.retention.as_ref() }.map_or(0, Cell::get);
+        if retention == 0 {
+            return mask;
+        }
+        let destroyed = unsafe { &*self.destroyed_storage };
+        let base = ((storage_idx << 12) | (page_idx << 6)) as u32;
+        let mut expired = 0;
+        let mut bits = mask;
+        while bits != 0 {
+            let chunk_idx = bits.trailing_zeros();
+            bits &= bits - 1;
+            if retention_expired(destroyed, base | chunk_idx, now, retention) {
+                expired |= 1u64 << chunk_idx;
+            }
+        }
+        expired
+    }
+
     /// Removes all T components from entities that have the Destroyed component.
     /// Iterates through the intersection of presence masks at each level (both T and Destroyed)
     /// and removes T components directly. Only entities that exist in both storages will be cleaned up.
@@ -98,6 +429,10 @@ impl<T: Component> ComponentCleanupSystem<T> {
             let destroyed_storage = &*self.destroyed_storage;
 
             let mut storage_mask = t_storage.presence_mask & destroyed_storage.presence_mask;
+            if storage_mask != 0 {
+                // Record the removals in this tick's snapshot, not the last one written.
+                t_storage.ensure_rollback_tick(frame.current_tick);
+            }
 
             while storage_mask != 0 {
                 let storage_start = storage_mask.trailing_zeros() as usize;
@@ -125,8 +460,16 @@ impl<T: Component> ComponentCleanupSystem<T> {
                                 let d_chunk = &*d_page.data[page_idx];
                                 d_chunk.presence_mask
                             };
-                            let mut remove_mask = t_chunk_mask & destroyed_chunk_mask;
+                            let mut remove_mask = self.expired(
+                                frame.current_tick,
+                                storage_idx,
+                                page_idx,
+                                t_chunk_mask & destroyed_chunk_mask,
+                            );
                             let page_mut = &mut *t_storage.data[storage_idx];
+                            if remove_mask != 0 {
+                                page_mut.unshare_chunk(page_idx, &t_storage.chunk_layout);
+                            }
 
                             while remove_mask != 0 {
                                 let chunk_start = remove_mask.trailing_zeros() as usize;
@@ -136,6 +479,14 @@ impl<T: Component> ComponentCleanupSystem<T> {
                                 for chunk_idx in (chunk_start..chunk_start + chunk_run_len).rev() {
                                     let chunk_mut = &mut *page_mut.data[page_idx];
                                     let old_value = chunk_mut.data[chunk_idx].assume_init_read();
+                                    let index =
+                                        ((storage_idx << 12) | (page_idx << 6) | chunk_idx) as u32;
+                                    crate::audit::record::<T>(
+                                        frame.current_tick,
+                                        index,
+                                        crate::audit::StructuralOp::Remove,
+                                    );
+                                    t_storage.note_presence(index, false);
 
                                     let was_created_in_rollback =
                                         if t_storage.rollback.tick() != frame.current_tick {
@@ -181,6 +532,11 @@ impl<T: Component> ComponentCleanupSystem<T> {
                                         rb_chunk_mut.changed_mask &= !(1u64 << chunk_idx);
                                     }
 
+                                    if chunk_mut.presence_mask == u64::MAX
+                                        || chunk_mut.presence_mask == 1u64 << chunk_idx
+                                    {
+                                        t_storage.mark_structure_changed();
+                                    }
                                     chunk_mut.presence_mask &= !(1u64 << chunk_idx);
                                     chunk_mut.fullness_mask &= !(1u64 << chunk_idx);
                                     chunk_mut.changed_mask |= 1u64 << chunk_idx;
@@ -199,7 +555,7 @@ impl<T: Component> ComponentCleanupSystem<T> {
                                             page_mut.data[page_idx],
                                             t_storage.default_chunk_ptr,
                                         ) {
-                                            drop(Box::from_raw(page_mut.data[page_idx]));
+                                            t_storage.chunk_layout.release(page_mut.data[page_idx]);
                                         }
                                         let dc = t_storage.default_chunk_ptr as *mut _;
                                         page_mut.data[page_idx] = dc;
@@ -238,6 +594,8 @@ impl<T: Component> ComponentCleanupSystem<T> {
 
 impl<T: Component> System for ComponentCleanupSystem<T> {
     fn run(&self, frame: &crate::frame::Frame) {
+        crate::aliasing::track_borrow::<T>(self.name(), true);
+        crate::aliasing::track_borrow::<Destroyed>(self.name(), false);
         self.cleanup_destroyed_components(frame);
     }
 
@@ -259,6 +617,187 @@ impl<T: Component> System for ComponentCleanupSystem<T> {
     }
 }
 
+/// `ComponentCleanupSystem<Entity>` that also sends a `DespawnedEvent` for every entity
+/// it removes, when that event type is registered. Entities stay until the World's
+/// destroyed retention expired, so `spawn` doesn't hand out their slot before.
+pub struct EntityCleanupSystem {
+    inner: ComponentCleanupSystem<Entity>,
+    events: *mut Storage<DespawnedEvent>,
+    writes: Vec<TypeId>,
+}
+
+// Safety: see ComponentCleanupSystem.
+unsafe impl Send for EntityCleanupSystem {}
+unsafe impl Sync for EntityCleanupSystem {}
+
+impl EntityCleanupSystem {
+    pub fn new(world: &mut World) -> Self {
+        // Creating the event storage here schedules its cleanup before this system, so
+        // last tick's events are dropped before this tick's are sent.
+        let events = if DespawnedEvent::id() == u32::MAX {
+            std::ptr::null_mut()
+        } else {
+            world.get_storage::<DespawnedEvent>()
+        };
+        let mut writes = vec![TypeId::of::<Entity>()];
+        if !events.is_null() {
+            writes.push(TypeId::of::<DespawnedEvent>());
+        }
+        let mut inner = ComponentCleanupSystem::new(world);
+        inner.retention = world.destroyed_retention_cell();
+        Self {
+            inner,
+            events,
+            writes,
+        }
+    }
+}
+
+impl System for EntityCleanupSystem {
+    fn run(&self, frame: &crate::frame::Frame) {
+        if self.events.is_null() {
+            self.inner.run(frame);
+            return;
+        }
+        let despawned: Vec<Entity> = unsafe {
+            let entities = &*self.inner.t_storage;
+            let destroyed = &*self.inner.destroyed_storage;
+            let mut cursor = destroyed.cursor();
+            std::iter::from_fn(|| cursor.next(destroyed))
+                .filter_map(|index| entities.get(index).copied())
+                .collect()
+        };
+        self.inner.run(frame);
+        crate::aliasing::track_borrow::<DespawnedEvent>(self.name(), true);
+        let entities = unsafe { &*self.inner.t_storage };
+        let events = unsafe { &mut *self.events };
+        // Retained entities were not removed yet.
+        for entity in despawned
+            .into_iter()
+            .filter(|e| entities.get(e.index()).is_none())
+        {
+            crate::event::send(events, frame, DespawnedEvent(entity));
+        }
+    }
+
+    fn reads(&self) -> &[TypeId] {
+        self.inner.reads()
+    }
+
+    fn writes(&self) -> &[TypeId] {
+        &self.writes
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn parent(&self) -> Option<&dyn SystemGroup> {
+        self.inner.parent()
+    }
+}
+
+/// Per-tick maintenance that must run after every other system; `World::new` adds it
+/// in `EndOfTickGroup`, after `DestroyGroup`. In order, it:
+/// 1. clears the changed masks of every storage,
+/// 2. trims each storage's rollback history to `World::history_depth` ticks, recycling
+///    the dropped snapshots through the storage's pool. With a `RollbackConfig`
+///    resource, it keeps what rolling back `max_rollback_frames` ticks needs instead,
+///    trimming on keyframe ticks only,
+/// 3. packs the history of storages with delta rollback (`Storage::pack_history`),
+/// 4. saves the Entity generation in the tick's snapshot, so rolling back to this tick
+///    restores the generation its spawns left behind,
+/// 5. if `World::request_compaction` was called, compacts every storage
+///    (`Storage::compact`).
+///
+/// It is exclusive, so no other system shares its wavefront.
+pub struct EndOfTickSystem {
+    storages: *mut StorageTable,
+    entities: *mut Storage<Entity>,
+    history_depth: Cell<usize>,
+    compact_requested: Cell<bool>,
+}
+
+// Safety: the storage table is boxed by the World, which outlives its scheduler's systems;
+// systems run on the thread that owns the world.
+unsafe impl Send for EndOfTickSystem {}
+unsafe impl Sync for EndOfTickSystem {}
+
+impl EndOfTickSystem {
+    pub fn new(world: &mut World) -> Self {
+        Self {
+            storages: world.storage_table(),
+            entities: world.get_entity_storage(),
+            history_depth: Cell::new(crate::rollback::MAX_HISTORY),
+            compact_requested: Cell::new(false),
+        }
+    }
+
+    pub fn history_depth(&self) -> usize {
+        self.history_depth.get()
+    }
+
+    pub(crate) fn set_history_depth(&self, depth: usize) {
+        self.history_depth.set(depth);
+    }
+
+    pub(crate) fn request_compaction(&self) {
+        self.compact_requested.set(true);
+    }
+
+    /// The `RollbackConfig` resource, if its storage exists and holds one.
+    fn rollback_config(storages: &StorageTable) -> Option<RollbackConfig> {
+        storages
+            .get(RollbackConfig::id() as usize)?
+            .as_ref()?
+            .as_any()
+            .downcast_ref::<Storage<RollbackConfig>>()?
+            .get(RESOURCE_INDEX)
+            .cloned()
+    }
+}
+
+impl System for EndOfTickSystem {
+    fn run(&self, frame: &crate::frame::Frame) {
+        let storages = unsafe { &mut *self.storages };
+        let (depth, trim) = match Self::rollback_config(storages) {
+            Some(config) => (
+                // The current snapshot covers the newest of the window's ticks.
+                (config.max_rollback_frames as usize)
+                    .saturating_sub(1)
+                    .clamp(1, crate::rollback::MAX_HISTORY),
+                config.is_keyframe(frame.current_tick),
+            ),
+            None => (self.history_depth.get(), true),
+        };
+        for storage in storages.iter_mut().flatten() {
+            storage.clear_changed_masks_all_levels();
+            if trim {
+                storage.trim_history(depth);
+            }
+            storage.pack_history();
+        }
+        unsafe { (*self.entities).save_generation_for_rollback() };
+        if self.compact_requested.replace(false) {
+            for storage in storages.iter_mut().flatten() {
+                storage.compact();
+            }
+        }
+    }
+
+    fn exclusive(&self) -> bool {
+        true
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn parent(&self) -> Option<&dyn SystemGroup> {
+        Some(EndOfTickGroup::instance())
+    }
+}
+
 pub struct TemporaryComponentCleanupSystem<T: Component, Group: SystemGroup> {
     pub writes: [TypeId; 1],
     pub t_storage: *mut Storage<T>,
@@ -284,7 +823,7 @@ impl<T: Component, Group: SystemGroup> TemporaryComponentCleanupSystem<T, Group>
 
     /// Clears the entire storage by dropping all components, chunks, and pages.
     /// This will drop everything in the storage unconditionally.
-    fn cleanup_storage(&self) {
+    fn cleanup_storage(&self, frame: &crate::frame::Frame) {
         unsafe {
             let t_storage = &mut *self.t_storage;
 
@@ -318,6 +857,7 @@ impl<T: Component, Group: SystemGroup> TemporaryComponentCleanupSystem<T, Group>
                             // Get mutable reference to the chunk
                             // Note: chunk must exist since we're iterating based on page_mask_iter
                             let t_page = &mut *t_storage.data[storage_idx];
+                            t_page.unshare_chunk(page_idx, &t_storage.chunk_layout);
                             let t_chunk = &mut *t_page.data[page_idx];
 
                             // Drop all component data in the chunk
@@ -334,6 +874,16 @@ impl<T: Component, Group: SystemGroup> TemporaryComponentCleanupSystem<T, Group>
                                 {
                                     // Drop the component data
                                     t_chunk.data[chunk_component_idx].assume_init_drop();
+                                    let index = ((storage_idx << 12)
+                                        | (page_idx << 6)
+                                        | chunk_component_idx)
+                                        as u32;
+                                    crate::audit::record::<T>(
+                                        frame.current_tick,
+                                        index,
+                                        crate::audit::StructuralOp::Remove,
+                                    );
+                                    t_storage.note_presence(index, false);
 
                                     // Update masks - remove value
                                     // Clear both presence_mask and fullness_mask
@@ -363,10 +913,11 @@ impl<T: Component, Group: SystemGroup> TemporaryComponentCleanupSystem<T, Group>
 
                             // Drop the chunk itself
                             let _ = t_chunk;
+                            t_storage.mark_structure_changed();
                             debug_assert!((t_page.presence_mask >> page_idx) & 1 != 0);
                             // Drop chunk and reset to default
                             if !std::ptr::eq(t_page.data[page_idx], t_storage.default_chunk_ptr) {
-                                drop(Box::from_raw(t_page.data[page_idx]));
+                                t_storage.chunk_layout.release(t_page.data[page_idx]);
                             }
                             let dc = t_storage.default_chunk_ptr as *mut _;
                             t_page.data[page_idx] = dc;
@@ -419,8 +970,9 @@ impl<T: Component, Group: SystemGroup> TemporaryComponentCleanupSystem<T, Group>
 }
 
 impl<T: Component, Group: SystemGroup> System for TemporaryComponentCleanupSystem<T, Group> {
-    fn run(&self, _frame: &crate::frame::Frame) {
-        self.cleanup_storage();
+    fn run(&self, frame: &crate::frame::Frame) {
+        crate::aliasing::track_borrow::<T>(self.name(), true);
+        self.cleanup_storage(frame);
     }
 
     fn reads(&self) -> &[TypeId] {
@@ -439,3 +991,55 @@ impl<T: Component, Group: SystemGroup> System for TemporaryComponentCleanupSyste
         Some(Group::instance())
     }
 }
+
+/// Removes `Destroyed` in `DestroyGroup`, after the cleanup systems ran. Without destroyed
+/// retention the whole storage is cleared; with it, only the markers of entities whose
+/// retention expired (the ones `EntityCleanupSystem` freed this tick) are removed.
+pub struct DestroyedCleanupSystem {
+    inner: TemporaryComponentCleanupSystem<Destroyed, DestroyGroup>,
+    retention: *const Cell<u32>,
+}
+
+// Safety: see TemporaryComponentCleanupSystem; the retention cell is boxed by the World.
+unsafe impl Send for DestroyedCleanupSystem {}
+unsafe impl Sync for DestroyedCleanupSystem {}
+
+impl DestroyedCleanupSystem {
+    pub fn new(world: &mut World) -> Self {
+        Self {
+            inner: TemporaryComponentCleanupSystem::new(world),
+            retention: world.destroyed_retention_cell(),
+        }
+    }
+}
+
+impl System for DestroyedCleanupSystem {
+    fn run(&self, frame: &crate::frame::Frame) {
+        let retention = unsafe { (*self.retention).get() };
+        if retention == 0 {
+            self.inner.run(frame);
+            return;
+        }
+        crate::aliasing::track_borrow::<Destroyed>(self.name(), true);
+        let destroyed = unsafe { &mut *self.inner.t_storage };
+        let mut cursor = destroyed.cursor();
+        let expired: Vec<u32> = std::iter::from_fn(|| cursor.next(destroyed))
+            .filter(|&index| retention_expired(destroyed, index, frame.current_tick, retention))
+            .collect();
+        for index in expired {
+            destroyed.remove(frame, index);
+        }
+    }
+
+    fn writes(&self) -> &[TypeId] {
+        self.inner.writes()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn parent(&self) -> Option<&dyn SystemGroup> {
+        self.inner.parent()
+    }
+}
@@ -0,0 +1,355 @@
+//! Property-based fuzz harness for storages and rollback.
+//!
+//! `fuzz_component` drives a `World` through random sequences of `set`, `remove`,
+//! entity `spawn`/despawn and `rollback` across ticks, and after every tick checks
+//! `World::verify_all` plus the stored values against a naive `HashMap` model.
+//! Downstream crates can run it against their own components to validate
+//! `Clone`/`Drop` behavior under rollback:
+//!
+//! ```ignore
+//! Ecs::register::<MyComponent>();
+//! decs::testing::fuzz_component(&FuzzConfig::default(), |rng| MyComponent::random(rng));
+//! ```
+//!
+//! Failures panic with the seed and tick so the run can be reproduced.
+//!
+//! `lockstep` checks a whole simulation for determinism instead: it builds two worlds
+//! from the same seed, feeds both the same inputs and compares `net::world_checksum`
+//! after every tick, reporting the first divergence with `net::diff_worlds`.
+
+use crate::component::Component;
+use crate::entity::Entity;
+use crate::net::{DesyncReport, diff_worlds, world_checksum};
+use crate::rollback::SnapshotPolicy;
+use crate::tick::Tick;
+use crate::world::World;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{self, Debug};
+
+/// Small deterministic xorshift64* generator used by the harness.
+#[derive(Clone, Debug)]
+pub struct FuzzRng(u64);
+
+impl FuzzRng {
+    pub fn new(seed: u64) -> Self {
+        // Zero is a fixed point of xorshift; remap it.
+        Self(if seed == 0 {
+            0x9E37_79B9_7F4A_7C15
+        } else {
+            seed
+        })
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.0 = x;
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Returns a value in `0..n` (`n` must be non-zero).
+    pub fn below(&mut self, n: u32) -> u32 {
+        (((self.next_u64() >> 32) * n as u64) >> 32) as u32
+    }
+
+    /// Returns true with probability `percent / 100`.
+    pub fn chance(&mut self, percent: u32) -> bool {
+        self.below(100) < percent
+    }
+}
+
+/// Parameters of a fuzz run.
+#[derive(Clone, Debug)]
+pub struct FuzzConfig {
+    pub seed: u64,
+    pub ticks: u32,
+    pub ops_per_tick: u32,
+    /// Component indices are drawn from `0..index_range` (at most 64 * 64 * 64).
+    pub index_range: u32,
+    /// Percent chance of rolling back at the end of a tick.
+    pub rollback_percent: u32,
+    /// Maximum number of ticks a single rollback goes back (capped by retained history).
+    pub max_rollback_depth: u32,
+    /// Rollback snapshot policy of the fuzzed storage.
+    pub snapshot_policy: SnapshotPolicy,
+}
+
+impl Default for FuzzConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0x5EED,
+            ticks: 200,
+            ops_per_tick: 64,
+            index_range: 3 * 4096,
+            rollback_percent: 10,
+            max_rollback_depth: 16,
+            snapshot_policy: SnapshotPolicy::PerItem,
+        }
+    }
+}
+
+/// Counts of operations performed by a fuzz run.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct FuzzStats {
+    pub sets: u32,
+    pub removes: u32,
+    pub spawns: u32,
+    pub despawns: u32,
+    pub rollbacks: u32,
+}
+
+#[derive(Clone)]
+struct ModelState<T> {
+    values: HashMap<u32, T>,
+    entities: HashMap<u32, Entity>,
+}
+
+/// Runs a fuzz session for component `T`, which must already be registered.
+/// `generate` produces the values written by `set`.
+pub fn fuzz_component<T, F>(config: &FuzzConfig, mut generate: F) -> FuzzStats
+where
+    T: Component + PartialEq + Debug,
+    F: FnMut(&mut FuzzRng) -> T,
+{
+    assert!(config.index_range > 0 && config.index_range <= 64 * 64 * 64);
+    let mut rng = FuzzRng::new(config.seed);
+    let mut stats = FuzzStats::default();
+    let mut world = World::new();
+    world
+        .get_storage_mut::<T>()
+        .set_snapshot_policy(config.snapshot_policy);
+    let mut model = ModelState {
+        values: HashMap::new(),
+        entities: HashMap::new(),
+    };
+    // End-of-tick model states, oldest first; bounded by the storage history limit.
+    let mut history: VecDeque<(Tick, ModelState<T>)> = VecDeque::new();
+    history.push_back((world.current_tick(), model.clone()));
+
+    for _ in 0..config.ticks {
+        let tick = world.advance_tick();
+        let frame = world.frame();
+        let storage = unsafe { &mut *world.get_storage::<T>() };
+        let entities = unsafe { &mut *world.get_entity_storage() };
+
+        for _ in 0..config.ops_per_tick {
+            let index = rng.below(config.index_range);
+            match rng.below(10) {
+                0..=4 => {
+                    let value = generate(&mut rng);
+                    storage.set(&frame, index, value.clone());
+                    model.values.insert(index, value);
+                    stats.sets += 1;
+                }
+                5..=7 => {
+                    let removed = storage.remove(&frame, index);
+                    assert_eq!(
+                        removed,
+                        model.values.remove(&index).is_some(),
+                        "remove({}) disagrees with model (seed {:#x}, {:?})",
+                        index,
+                        config.seed,
+                        tick
+                    );
+                    stats.removes += 1;
+                }
+                8 => {
+                    if let Some(entity) = entities.spawn(&frame) {
+                        model.entities.insert(entity.index(), entity);
+                        stats.spawns += 1;
+                    }
+                }
+                _ => {
+                    let removed = entities.remove(&frame, index);
+                    assert_eq!(
+                        removed,
+                        model.entities.remove(&index).is_some(),
+                        "despawn({}) disagrees with model (seed {:#x}, {:?})",
+                        index,
+                        config.seed,
+                        tick
+                    );
+                    stats.despawns += 1;
+                }
+            }
+        }
+
+        history.push_back((tick, model.clone()));
+        while history.len() > 64 {
+            history.pop_front();
+        }
+
+        if config.max_rollback_depth > 0 && rng.chance(config.rollback_percent) {
+            let max_depth = config.max_rollback_depth.min(history.len() as u32 - 1);
+            if max_depth > 0 {
+                let depth = 1 + rng.below(max_depth);
+                let target = tick.wrapping_sub(depth);
+                world.try_rollback(target).unwrap_or_else(|e| {
+                    panic!(
+                        "rollback failed (seed {:#x}, {:?}): {}",
+                        config.seed, tick, e
+                    )
+                });
+                while history.back().is_some_and(|(t, _)| t.is_after(target)) {
+                    history.pop_back();
+                }
+                model = history.back().expect("target is retained").1.clone();
+                stats.rollbacks += 1;
+            }
+        }
+
+        check_world(&mut world, &model, config);
+    }
+
+    stats
+}
+
+fn check_world<T: Component + PartialEq + Debug>(
+    world: &mut World,
+    model: &ModelState<T>,
+    config: &FuzzConfig,
+) {
+    let tick = world.current_tick();
+    let report = world.verify_all();
+    assert!(
+        report.is_ok(),
+        "invariants violated (seed {:#x}, {:?}): {}",
+        config.seed,
+        tick,
+        report
+    );
+
+    let storage = unsafe { &*world.get_storage::<T>() };
+    let entities = unsafe { &*world.get_entity_storage() };
+    assert_eq!(
+        storage.count as usize,
+        model.values.len(),
+        "component count mismatch (seed {:#x}, {:?})",
+        config.seed,
+        tick
+    );
+    assert_eq!(
+        entities.count as usize,
+        model.entities.len(),
+        "entity count mismatch (seed {:#x}, {:?})",
+        config.seed,
+        tick
+    );
+    for index in 0..config.index_range {
+        assert_eq!(
+            storage.get(index),
+            model.values.get(&index),
+            "value mismatch at {} (seed {:#x}, {:?})",
+            index,
+            config.seed,
+            tick
+        );
+    }
+    for (index, entity) in &model.entities {
+        assert_eq!(
+            entities.get(*index),
+            Some(entity),
+            "entity mismatch at {} (seed {:#x}, {:?})",
+            index,
+            config.seed,
+            tick
+        );
+    }
+}
+
+/// Parameters of a lockstep run.
+#[derive(Clone, Debug)]
+pub struct LockstepConfig {
+    /// Passed to `setup` for both worlds.
+    pub seed: u64,
+    pub ticks: u32,
+}
+
+impl Default for LockstepConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0x5EED,
+            ticks: 600,
+        }
+    }
+}
+
+/// First tick after which the two worlds of a lockstep run disagreed.
+#[derive(Clone, Debug)]
+pub struct LockstepDivergence {
+    pub seed: u64,
+    pub tick: Tick,
+    /// `world_checksum` of the first and second world.
+    pub checksums: (u64, u64),
+    pub report: DesyncReport,
+}
+
+impl fmt::Display for LockstepDivergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "lockstep worlds diverged after {:?} (seed {:#x}): checksums {:#018x} != {:#018x}",
+            self.tick, self.seed, self.checksums.0, self.checksums.1
+        )?;
+        for d in &self.report.divergences {
+            writeln!(
+                f,
+                "  {} at {}: {:?} (a: {:?}, b: {:?})",
+                d.component, d.index, d.kind, d.a, d.b
+            )?;
+        }
+        if self.report.truncated {
+            writeln!(f, "  ...")?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs two worlds built by `setup(world, seed)` (which also adds their systems) for
+/// `config.ticks` ticks. Before every tick `input(world, tick)` is applied to both with
+/// the tick about to run; after it the worlds' `world_checksum`s must match. Returns the
+/// per-tick checksums, or the first divergence.
+pub fn try_lockstep<S, I>(
+    config: &LockstepConfig,
+    setup: S,
+    mut input: I,
+) -> Result<Vec<u64>, LockstepDivergence>
+where
+    S: Fn(&mut World, u64),
+    I: FnMut(&mut World, Tick),
+{
+    let mut a = World::new();
+    let mut b = World::new();
+    setup(&mut a, config.seed);
+    setup(&mut b, config.seed);
+    let mut checksums = Vec::with_capacity(config.ticks as usize);
+    for _ in 0..config.ticks {
+        for world in [&mut a, &mut b] {
+            let tick = world.current_tick().next();
+            input(world, tick);
+            world.run();
+        }
+        let (ca, cb) = (world_checksum(&a), world_checksum(&b));
+        if ca != cb {
+            return Err(LockstepDivergence {
+                seed: config.seed,
+                tick: a.current_tick(),
+                checksums: (ca, cb),
+                report: diff_worlds(&a, &b),
+            });
+        }
+        checksums.push(ca);
+    }
+    Ok(checksums)
+}
+
+/// `try_lockstep` that panics with the divergence report.
+pub fn lockstep<S, I>(config: &LockstepConfig, setup: S, input: I) -> Vec<u64>
+where
+    S: Fn(&mut World, u64),
+    I: FnMut(&mut World, Tick),
+{
+    try_lockstep(config, setup, input).unwrap_or_else(|divergence| panic!("{}", divergence))
+}
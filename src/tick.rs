@@ -1,18 +1,146 @@
 use std::cell::Cell;
+use std::cmp::Ordering;
 use std::fmt;
+use std::marker::PhantomData;
 use std::ops::{Add, Sub};
 
 thread_local! {
     pub static CURRENT_TICK: Cell<Tick> = const { Cell::new(Tick(0)) };
 }
 
+/// Counter stamped on every changed value (`Chunk::changed_ticks`), one per world
+/// (`World::change_tick`). Unlike the world tick it never goes back on rollback;
+/// `Changed=[...]` systems advance it on every run and match the stamps newer than
+/// their previous run.
+#[derive(Debug)]
+pub struct ChangeTick(Cell<Tick>);
+
+impl ChangeTick {
+    pub const fn new() -> Self {
+        Self(Cell::new(Tick(1)))
+    }
+
+    /// Returns the value writes are currently stamped with.
+    pub fn get(&self) -> Tick {
+        self.0.get()
+    }
+
+    /// Advances the counter and returns the new value.
+    pub fn advance(&self) -> Tick {
+        let tick = self.0.get().next();
+        self.0.set(tick);
+        tick
+    }
+
+    pub(crate) fn set(&self, tick: Tick) {
+        self.0.set(tick);
+    }
+}
+
+impl Default for ChangeTick {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(debug_assertions)]
+thread_local! {
+    /// Tick of the open `TickGuard`, if any.
+    static GUARDED_TICK: Cell<Option<Tick>> = const { Cell::new(None) };
+}
+
+/// Scope of one manually driven tick, returned by `World::begin_tick`.
+///
+/// While it is alive, debug builds panic when a storage is written for another tick or
+/// has its changed masks cleared (`World::advance_tick`, `clear_changed_masks`, a
+/// rollback). Either would otherwise mix two ticks in one rollback snapshot without any
+/// error. Release builds check nothing. Guards are per thread and don't nest.
+#[must_use = "the tick is only guarded until the guard is dropped"]
+pub struct TickGuard {
+    tick: Tick,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl TickGuard {
+    pub(crate) fn new(tick: Tick) -> Self {
+        #[cfg(debug_assertions)]
+        GUARDED_TICK.with(|guarded| {
+            if let Some(open) = guarded.get() {
+                panic!("begin_tick({tick:?}) while the guard of {open:?} is open");
+            }
+            guarded.set(Some(tick));
+        });
+        Self {
+            tick,
+            _not_send: PhantomData,
+        }
+    }
+
+    /// The guarded tick.
+    pub fn tick(&self) -> Tick {
+        self.tick
+    }
+}
+
+impl Drop for TickGuard {
+    fn drop(&mut self) {
+        #[cfg(debug_assertions)]
+        GUARDED_TICK.with(|guarded| guarded.set(None));
+    }
+}
+
+/// Panics in debug builds if a `TickGuard` is open for another tick than `tick`, the
+/// tick a storage of `T` is about to be written for.
+#[inline(always)]
+pub(crate) fn check_guarded_write<T>(tick: Tick) {
+    #[cfg(debug_assertions)]
+    GUARDED_TICK.with(|guarded| {
+        if let Some(open) = guarded.get()
+            && open != tick
+        {
+            panic!(
+                "{} written for {:?} inside the TickGuard of {:?}",
+                std::any::type_name::<T>(),
+                tick,
+                open
+            );
+        }
+    });
+    #[cfg(not(debug_assertions))]
+    let _ = tick;
+}
+
+/// Panics in debug builds if a `TickGuard` is open, before the changed masks of a
+/// storage of `T` are cleared.
+#[inline(always)]
+pub(crate) fn check_guarded_clear<T>() {
+    #[cfg(debug_assertions)]
+    GUARDED_TICK.with(|guarded| {
+        if let Some(open) = guarded.get() {
+            panic!(
+                "changed masks of {} cleared inside the TickGuard of {:?}",
+                std::any::type_name::<T>(),
+                open
+            );
+        }
+    });
+}
+
 /// Absolute tick in modular 32-bit time.
+///
+/// The derived `Ord` compares raw values and is only meaningful for sorting/keys;
+/// use `is_after`, `is_before` or `wrapping_cmp` for tick-time ordering that stays
+/// correct across wraparound.
 #[derive(Copy, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct Tick(pub u32);
 
 /// Signed linear delta between two ticks.
 /// Range: -(2^31) ..= +(2^31 - 1)
 #[derive(Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct TickDelta(pub i32);
 
 impl Tick {
@@ -42,6 +170,51 @@ impl Tick {
         self.diff(other).0 < 0
     }
 
+    /// Returns true if `self` is `other` or happens after it.
+    pub fn is_at_or_after(self, other: Tick) -> bool {
+        self.diff(other).0 >= 0
+    }
+
+    /// Returns true if `self` is `other` or happens before it.
+    pub fn is_at_or_before(self, other: Tick) -> bool {
+        self.diff(other).0 <= 0
+    }
+
+    /// Wrap-aware ordering in tick-time (valid while ticks are less than 2^31 apart).
+    pub fn wrapping_cmp(self, other: Tick) -> Ordering {
+        self.diff(other).0.cmp(&0)
+    }
+
+    /// Absolute wrap-aware distance between two ticks.
+    pub fn distance(self, other: Tick) -> u32 {
+        self.diff(other).0.unsigned_abs()
+    }
+
+    /// The tick immediately after `self`, wrapping at `u32::MAX`.
+    pub fn next(self) -> Tick {
+        self.wrapping_add(1)
+    }
+
+    /// Add `n` ticks with wrapping.
+    pub fn wrapping_add(self, n: u32) -> Tick {
+        Tick(self.0.wrapping_add(n))
+    }
+
+    /// Subtract `n` ticks with wrapping.
+    pub fn wrapping_sub(self, n: u32) -> Tick {
+        Tick(self.0.wrapping_sub(n))
+    }
+
+    /// Add `n` ticks, clamping at `u32::MAX`.
+    pub fn saturating_add(self, n: u32) -> Tick {
+        Tick(self.0.saturating_add(n))
+    }
+
+    /// Subtract `n` ticks, clamping at zero.
+    pub fn saturating_sub(self, n: u32) -> Tick {
+        Tick(self.0.saturating_sub(n))
+    }
+
     /// Add a tick delta with wrapping.
     pub fn add_delta(self, delta: TickDelta) -> Tick {
         Tick(self.0.wrapping_add(delta.0 as u32))
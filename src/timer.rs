@@ -0,0 +1,322 @@
+//! Timer and cooldown components advanced in simulated time.
+//!
+//! `Timer` counts up to a duration and reports the ticks it finished in; `Cooldown`
+//! counts down after being triggered and reports when it is ready again. Both are
+//! advanced by the step of the `SimTime` resource (`Frame::fixed_dt` in worlds without
+//! it) in `TimerGroup`, ahead of the simulation systems, and keep all their state in
+//! the component. A rollback therefore restores the time left along with the rest of
+//! the world, and a resimulated tick finishes the same timers again, unlike timers
+//! driven by wall clocks or counted in system-local state.
+//!
+//! ```ignore
+//! Ecs::register::<Timer>();
+//! Ecs::register::<Cooldown>();
+//! world.get_storage_mut::<Timer>().set(&f, index, Timer::repeating(0.5));
+//!
+//! system!(FireSystem { query
+//!     fn update(timer: View<Timer>, cooldown: &mut ViewMut<Cooldown>) {
+//!         if timer.just_finished() && cooldown.try_trigger() { /* fire */ }
+//!     }
+//! });
+//! ```
+//!
+//! Register the components with `Ecs::register`; creating their storages schedules
+//! `TimerSystem` and `CooldownSystem`.
+
+use crate::component::Component;
+use crate::frame::Frame;
+use crate::resource::{RESOURCE_INDEX, SimTime};
+use crate::storage::Storage;
+use crate::system::{ComponentCleanupSystem, System, SystemGroup};
+use crate::world::{StorageTable, TimerGroup, World};
+use std::any::{Any, TypeId};
+
+/// Counts simulated seconds up to `duration`. A one-shot timer then stays finished; a
+/// repeating one starts over, carrying the overshoot.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Timer {
+    pub duration: f32,
+    pub elapsed: f32,
+    pub repeating: bool,
+    /// Times the timer finished during the last tick that advanced it; more than one
+    /// for a repeating timer shorter than the step.
+    pub times_finished: u32,
+}
+
+impl Timer {
+    /// A one-shot timer of `duration` seconds.
+    pub fn new(duration: f32) -> Self {
+        Self {
+            duration,
+            elapsed: 0.0,
+            repeating: false,
+            times_finished: 0,
+        }
+    }
+
+    /// A timer finishing every `duration` seconds.
+    pub fn repeating(duration: f32) -> Self {
+        Self {
+            repeating: true,
+            ..Self::new(duration)
+        }
+    }
+
+    /// Advances the timer by `dt` seconds.
+    pub fn tick(&mut self, dt: f32) {
+        self.times_finished = 0;
+        if self.finished() {
+            return;
+        }
+        self.elapsed += dt;
+        if self.duration <= 0.0 {
+            self.elapsed = 0.0;
+            self.times_finished = 1;
+            return;
+        }
+        while self.elapsed >= self.duration {
+            self.times_finished += 1;
+            if !self.repeating {
+                self.elapsed = self.duration;
+                break;
+            }
+            self.elapsed -= self.duration;
+        }
+    }
+
+    /// Whether a one-shot timer has run out. Repeating timers never are.
+    pub fn finished(&self) -> bool {
+        !self.repeating && self.elapsed >= self.duration
+    }
+
+    /// Whether the timer finished during the last tick that advanced it.
+    pub fn just_finished(&self) -> bool {
+        self.times_finished > 0
+    }
+
+    /// Seconds until the timer next finishes.
+    pub fn remaining(&self) -> f32 {
+        (self.duration - self.elapsed).max(0.0)
+    }
+
+    /// Elapsed part of the duration, from 0 to 1, e.g. to drive an animation.
+    pub fn fraction(&self) -> f32 {
+        if self.duration <= 0.0 {
+            1.0
+        } else {
+            (self.elapsed / self.duration).min(1.0)
+        }
+    }
+
+    /// Starts the timer over.
+    pub fn reset(&mut self) {
+        self.elapsed = 0.0;
+        self.times_finished = 0;
+    }
+}
+
+/// Blocks an action for `duration` simulated seconds after it was triggered.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cooldown {
+    pub duration: f32,
+    /// Seconds until the cooldown is ready; 0 when it is.
+    pub remaining: f32,
+}
+
+impl Cooldown {
+    /// A cooldown of `duration` seconds that is ready.
+    pub fn new(duration: f32) -> Self {
+        Self {
+            duration,
+            remaining: 0.0,
+        }
+    }
+
+    pub fn ready(&self) -> bool {
+        self.remaining <= 0.0
+    }
+
+    /// Starts the cooldown, whether or not it was ready.
+    pub fn trigger(&mut self) {
+        self.remaining = self.duration;
+    }
+
+    /// Starts the cooldown if it is ready; returns whether it was.
+    pub fn try_trigger(&mut self) -> bool {
+        let ready = self.ready();
+        if ready {
+            self.trigger();
+        }
+        ready
+    }
+
+    /// Advances the cooldown by `dt` seconds.
+    pub fn tick(&mut self, dt: f32) {
+        self.remaining = (self.remaining - dt).max(0.0);
+    }
+}
+
+#[allow(non_upper_case_globals)]
+static mut __DECS_COMPONENT_ID_Timer: u32 = u32::MAX;
+
+impl Component for Timer {
+    fn id() -> u32 {
+        unsafe { __DECS_COMPONENT_ID_Timer }
+    }
+
+    fn initialize(id: u32) {
+        unsafe {
+            if __DECS_COMPONENT_ID_Timer == u32::MAX {
+                __DECS_COMPONENT_ID_Timer = id;
+            }
+        }
+    }
+
+    fn schedule_cleanup_system(world: &mut World) {
+        let cleanup = ComponentCleanupSystem::<Timer>::new(world);
+        world.scheduler_mut().add_system(cleanup);
+        let timers = TimerSystem::new(world);
+        world.scheduler_mut().add_system(timers);
+    }
+}
+
+#[allow(non_upper_case_globals)]
+static mut __DECS_COMPONENT_ID_Cooldown: u32 = u32::MAX;
+
+impl Component for Cooldown {
+    fn id() -> u32 {
+        unsafe { __DECS_COMPONENT_ID_Cooldown }
+    }
+
+    fn initialize(id: u32) {
+        unsafe {
+            if __DECS_COMPONENT_ID_Cooldown == u32::MAX {
+                __DECS_COMPONENT_ID_Cooldown = id;
+            }
+        }
+    }
+
+    fn schedule_cleanup_system(world: &mut World) {
+        let cleanup = ComponentCleanupSystem::<Cooldown>::new(world);
+        world.scheduler_mut().add_system(cleanup);
+        let cooldowns = CooldownSystem::new(world);
+        world.scheduler_mut().add_system(cooldowns);
+    }
+}
+
+/// Step of the tick being run: the `SimTime` resource's, or `frame`'s without it.
+fn step(storages: &StorageTable, frame: &Frame) -> f32 {
+    let time = storages
+        .get(SimTime::id() as usize)
+        .and_then(|s| s.as_ref())
+        .and_then(|s| s.as_any().downcast_ref::<Storage<SimTime>>())
+        .and_then(|s| s.get(RESOURCE_INDEX));
+    time.map_or(frame.fixed_dt, |time| time.fixed_dt)
+}
+
+/// Advances every `Timer` that is running or finished in the previous tick.
+pub struct TimerSystem {
+    storages: *const StorageTable,
+    timers: *mut Storage<Timer>,
+}
+
+// Safety: the storage table is boxed by the World, which outlives its scheduler's systems;
+// systems run on the thread that owns the world.
+unsafe impl Send for TimerSystem {}
+unsafe impl Sync for TimerSystem {}
+
+impl TimerSystem {
+    pub fn new(world: &mut World) -> Self {
+        Self {
+            storages: world.storage_table(),
+            timers: world.get_storage::<Timer>(),
+        }
+    }
+}
+
+impl System for TimerSystem {
+    fn run(&self, frame: &Frame) {
+        crate::aliasing::track_borrow::<SimTime>(self.name(), false);
+        crate::aliasing::track_borrow::<Timer>(self.name(), true);
+        let dt = step(unsafe { &*self.storages }, frame);
+        let timers = unsafe { &mut *self.timers };
+        let mut cursor = timers.cursor();
+        while let Some(index) = cursor.next(timers) {
+            let timer = timers.get(index).unwrap();
+            // Finished one-shot timers are only written to clear `times_finished`.
+            if !timer.finished() || timer.just_finished() {
+                timers.get_mut(frame, index).unwrap().tick(dt);
+            }
+        }
+    }
+
+    fn reads(&self) -> &[TypeId] {
+        static READS: &[TypeId] = &[TypeId::of::<SimTime>()];
+        READS
+    }
+
+    fn writes(&self) -> &[TypeId] {
+        static WRITES: &[TypeId] = &[TypeId::of::<Timer>()];
+        WRITES
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn parent(&self) -> Option<&dyn SystemGroup> {
+        Some(TimerGroup::instance())
+    }
+}
+
+/// Advances every `Cooldown` that is not ready.
+pub struct CooldownSystem {
+    storages: *const StorageTable,
+    cooldowns: *mut Storage<Cooldown>,
+}
+
+// Safety: as for `TimerSystem`.
+unsafe impl Send for CooldownSystem {}
+unsafe impl Sync for CooldownSystem {}
+
+impl CooldownSystem {
+    pub fn new(world: &mut World) -> Self {
+        Self {
+            storages: world.storage_table(),
+            cooldowns: world.get_storage::<Cooldown>(),
+        }
+    }
+}
+
+impl System for CooldownSystem {
+    fn run(&self, frame: &Frame) {
+        crate::aliasing::track_borrow::<SimTime>(self.name(), false);
+        crate::aliasing::track_borrow::<Cooldown>(self.name(), true);
+        let dt = step(unsafe { &*self.storages }, frame);
+        let cooldowns = unsafe { &mut *self.cooldowns };
+        let mut cursor = cooldowns.cursor();
+        while let Some(index) = cursor.next(cooldowns) {
+            if !cooldowns.get(index).unwrap().ready() {
+                cooldowns.get_mut(frame, index).unwrap().tick(dt);
+            }
+        }
+    }
+
+    fn reads(&self) -> &[TypeId] {
+        static READS: &[TypeId] = &[TypeId::of::<SimTime>()];
+        READS
+    }
+
+    fn writes(&self) -> &[TypeId] {
+        static WRITES: &[TypeId] = &[TypeId::of::<Cooldown>()];
+        WRITES
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn parent(&self) -> Option<&dyn SystemGroup> {
+        Some(TimerGroup::instance())
+    }
+}
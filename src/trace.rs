@@ -0,0 +1,160 @@
+//! Opt-in system execution trace (feature `system-trace`).
+//!
+//! Records, for every tick the scheduler runs, the order the systems ran in, the
+//! wavefront each one belonged to and the storages it declared it reads and writes.
+//! Two machines that diverge in lockstep can dump the trace with `to_json` and diff it:
+//! identical traces put the divergence in system logic, different ones in scheduling
+//! (system registration order, groups, disabled systems, blocked components).
+//!
+//! The trace belongs to the scheduler and keeps the newest `MAX_ENTRIES` entries;
+//! `World::system_trace` returns it with component names resolved. A tick run again
+//! after a rollback is traced again. Without the feature `record` is an inlined no-op
+//! and the trace is always empty.
+
+use crate::audit::json_string;
+use crate::system::System;
+use crate::tick::Tick;
+use std::any::TypeId;
+#[cfg(feature = "system-trace")]
+use std::cell::RefCell;
+#[cfg(feature = "system-trace")]
+use std::collections::VecDeque;
+use std::fmt::Write;
+
+/// Number of entries kept before the oldest are dropped.
+pub const MAX_ENTRIES: usize = 1 << 16;
+
+/// One system run, as returned by `World::system_trace`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceEntry {
+    pub tick: Tick,
+    /// 1-based position of the system in the tick's run order, counting the systems
+    /// that were skipped (disabled or blocked).
+    pub position: u32,
+    /// Index of the wavefront the system ran in.
+    pub wavefront: u32,
+    pub system: &'static str,
+    pub exclusive: bool,
+    /// Names of the component types the system reads, in declaration order.
+    pub reads: Vec<&'static str>,
+    /// Names of the component types the system writes, in declaration order.
+    pub writes: Vec<&'static str>,
+}
+
+/// A traced run before its component types are named.
+#[cfg(feature = "system-trace")]
+struct RawEntry {
+    tick: Tick,
+    position: u32,
+    wavefront: u32,
+    system: &'static str,
+    exclusive: bool,
+    reads: Vec<TypeId>,
+    writes: Vec<TypeId>,
+}
+
+/// The scheduler's trace; empty without the `system-trace` feature.
+#[derive(Default)]
+pub struct SystemTrace {
+    #[cfg(feature = "system-trace")]
+    entries: RefCell<VecDeque<RawEntry>>,
+}
+
+impl SystemTrace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records that `system` ran at `position` in wavefront `wavefront` of `tick`;
+    /// called by the scheduler before running it.
+    #[inline(always)]
+    pub(crate) fn record(&self, tick: Tick, position: u32, wavefront: u32, system: &dyn System) {
+        #[cfg(feature = "system-trace")]
+        {
+            let mut entries = self.entries.borrow_mut();
+            if entries.len() == MAX_ENTRIES {
+                entries.pop_front();
+            }
+            entries.push_back(RawEntry {
+                tick,
+                position,
+                wavefront,
+                system: system.name(),
+                exclusive: system.exclusive(),
+                reads: system.reads().to_vec(),
+                writes: system.writes().to_vec(),
+            });
+        }
+        #[cfg(not(feature = "system-trace"))]
+        let _ = (tick, position, wavefront, system);
+    }
+
+    /// Returns the trace oldest first, naming component types with `name`.
+    pub(crate) fn entries(&self, name: impl Fn(TypeId) -> &'static str) -> Vec<TraceEntry> {
+        #[cfg(feature = "system-trace")]
+        return self
+            .entries
+            .borrow()
+            .iter()
+            .map(|e| TraceEntry {
+                tick: e.tick,
+                position: e.position,
+                wavefront: e.wavefront,
+                system: e.system,
+                exclusive: e.exclusive,
+                reads: e.reads.iter().map(|&ty| name(ty)).collect(),
+                writes: e.writes.iter().map(|&ty| name(ty)).collect(),
+            })
+            .collect();
+        #[cfg(not(feature = "system-trace"))]
+        {
+            let _ = name;
+            Vec::new()
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        #[cfg(feature = "system-trace")]
+        return self.entries.borrow().len();
+        #[cfg(not(feature = "system-trace"))]
+        0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        #[cfg(feature = "system-trace")]
+        self.entries.borrow_mut().clear();
+    }
+}
+
+/// Formats entries as a JSON array of `{"tick":..,"position":..,"wavefront":..,
+/// "system":..,"exclusive":..,"reads":[..],"writes":[..]}` objects.
+pub fn to_json(entries: &[TraceEntry]) -> String {
+    let mut out = String::from("[");
+    for (i, e) in entries.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        let _ = write!(
+            out,
+            "{{\"tick\":{},\"position\":{},\"wavefront\":{},\"system\":{},\"exclusive\":{},\"reads\":{},\"writes\":{}}}",
+            e.tick.0,
+            e.position,
+            e.wavefront,
+            json_string(e.system),
+            e.exclusive,
+            json_array(&e.reads),
+            json_array(&e.writes)
+        );
+    }
+    out.push(']');
+    out
+}
+
+fn json_array(names: &[&'static str]) -> String {
+    let names: Vec<_> = names.iter().map(|name| json_string(name)).collect();
+    format!("[{}]", names.join(","))
+}
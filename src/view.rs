@@ -1,6 +1,9 @@
 use crate::component::Component;
+use crate::entity::Entity;
+use crate::frame::Frame;
 use crate::storage::{Chunk, Storage};
 use crate::tick::Tick;
+use std::marker::PhantomData;
 use std::ops::{Deref, DerefMut};
 
 /// Immutable view wrapper for component data.
@@ -10,12 +13,37 @@ use std::ops::{Deref, DerefMut};
 /// **IMPORTANT**: `View` should ONLY be used within Systems. Do not use this for direct storage manipulation.
 ///
 /// Systems are responsible for maintaining all storage invariants when using View/ViewMut.
+///
+/// # Lifetime
+///
+/// The lifetime `'a` is tied to the `&Frame` borrow of the running `System::run` call,
+/// so a view (or the reference it hands out) cannot outlive the iteration that produced it.
+/// This holds even when the viewed value itself lives longer:
+///
+/// ```compile_fail
+/// use decs::entity::Entity;
+/// use decs::frame::Frame;
+/// use decs::tick::Tick;
+/// use decs::view::View;
+///
+/// let entity: &'static Entity = Box::leak(Box::new(Entity::new(1, 1)));
+/// let stashed: View<'static, Entity>;
+/// {
+///     let frame = Frame::new(Tick(0));
+///     stashed = View::new(entity, 1, &frame);
+/// }
+/// assert_eq!(stashed.index(), 1);
+/// ```
 pub struct View<'a, T: Component> {
     pub data: &'a T,
+    pub index: u32,
+    _scope: PhantomData<&'a Frame>,
 }
 
 /// Mutable view wrapper for component data.
 ///
+/// Like `View`, its lifetime is bound to the `&Frame` borrow of the running system.
+///
 /// # Usage Restrictions
 ///
 /// **IMPORTANT**: `ViewMut` should ONLY be used within Systems. Do not use this for direct storage manipulation.
@@ -44,6 +72,9 @@ pub struct View<'a, T: Component> {
 ///     storage.changed_mask |= 1u128 << storage_idx;
 /// }
 /// ```
+///
+/// Outside generated systems, `Storage::propagate_changed` performs this step for one
+/// chunk and `Storage::summarize_changes` for the whole storage.
 pub struct ViewMut<'a, T: Component + Clone> {
     pub chunk: &'a mut Chunk<T>,
     pub index: u32,
@@ -51,11 +82,166 @@ pub struct ViewMut<'a, T: Component + Clone> {
     pub storage_idx: u32,
     pub page_idx: u32,
     pub current_tick: Tick,
+    _scope: PhantomData<&'a Frame>,
+}
+
+/// Access to a component by `Entity` handle from inside a generated system, for
+/// following references such as a target or an owner.
+///
+/// Declare it as a query parameter: `targets: &Lookup<Health>` only reads, while
+/// `targets: &mut Lookup<Health>` also allows `get_mut` and makes the system a writer of
+/// `Health`. A lookup doesn't filter the query, which still iterates the entities
+/// matching its `View`/`ViewMut` parameters.
+///
+/// Handles are checked against the entity's current generation, so a reference to a
+/// despawned (or despawned and reused) index resolves to `None`. So does the entity being
+/// iterated when the query also views `T` and the access would alias that view (`get`
+/// under a `ViewMut<T>`, `get_mut` under either view): use the view instead.
+pub struct Lookup<'a, T: Component> {
+    storage: *mut Storage<T>,
+    entities: *const Storage<Entity>,
+    viewed: Option<(u32, bool)>,
+    frame: &'a Frame,
+}
+
+impl<'a, T: Component> Lookup<'a, T> {
+    /// Used by generated systems. `viewed` is the index being iterated and whether it is
+    /// viewed mutably, when the query also has a view of `T`.
+    pub fn new(
+        storage: *mut Storage<T>,
+        entities: *const Storage<Entity>,
+        viewed: Option<(u32, bool)>,
+        frame: &'a Frame,
+    ) -> Self {
+        Self {
+            storage,
+            entities,
+            viewed,
+            frame,
+        }
+    }
+
+    /// Returns the index of `entity` if the handle is still alive.
+    #[inline(always)]
+    fn alive_index(&self, entity: Entity) -> Option<u32> {
+        if entity.is_none() {
+            return None;
+        }
+        let current = unsafe { (*self.entities).get(entity.index()) };
+        (current == Some(&entity)).then_some(entity.index())
+    }
+
+    /// Returns true if `entity` is alive and has a `T`.
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.alive_index(entity)
+            .is_some_and(|index| unsafe { (*self.storage).get(index) }.is_some())
+    }
+
+    /// Returns the `T` of `entity`, or `None` if the handle is stale or it has none.
+    pub fn get(&self, entity: Entity) -> Option<&T> {
+        let index = self.alive_index(entity)?;
+        if self.viewed == Some((index, true)) {
+            return None;
+        }
+        unsafe { (*self.storage).get(index) }
+    }
+
+    /// Returns the `T` of `entity` for modification, marking it changed and recording
+    /// its old value for rollback like `Storage::get_mut`.
+    pub fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+        let index = self.alive_index(entity)?;
+        if self.viewed.is_some_and(|(viewed, _)| viewed == index) {
+            return None;
+        }
+        unsafe { (*self.storage).get_mut(self.frame, index) }
+    }
+}
+
+/// Read access to the whole chunk of the entity being iterated, for neighbour
+/// operations (flocking, local averages) that stay within 64 consecutive indices
+/// instead of looking entities up across the storage.
+///
+/// Declare it as a query parameter: `boids: ChunkView<Boid>`. Like `All=[T]` it
+/// requires the iterated entity to have a `T` and makes the system a reader of `T`,
+/// so the query must not also write `T` through a `ViewMut` or a mutable `Lookup`.
+/// Slots are the low 6 bits of an index; `presence_mask` tells which of them hold a
+/// value, including the iterated one.
+pub struct ChunkView<'a, T: Component> {
+    chunk: &'a Chunk<T>,
+    base: u32,
+    slot: u32,
+}
+
+impl<'a, T: Component> ChunkView<'a, T> {
+    /// Used by generated systems. `base` is the index of the chunk's first slot and
+    /// `slot` the one being iterated.
+    pub fn new(chunk: &'a Chunk<T>, base: u32, slot: u32, _frame: &'a Frame) -> Self {
+        Self { chunk, base, slot }
+    }
+
+    /// Slots of the chunk holding a value.
+    #[inline(always)]
+    pub fn presence_mask(&self) -> u64 {
+        self.chunk.presence_mask
+    }
+
+    /// Global storage index of slot 0.
+    #[inline(always)]
+    pub fn first_index(&self) -> u32 {
+        self.base
+    }
+
+    /// Slot of the entity being iterated.
+    #[inline(always)]
+    pub fn slot(&self) -> u32 {
+        self.slot
+    }
+
+    /// Returns the value in `slot`, or `None` if it is empty or out of range.
+    #[inline]
+    pub fn get(&self, slot: u32) -> Option<&'a T> {
+        if slot >= 64 || self.chunk.presence_mask & (1u64 << slot) == 0 {
+            return None;
+        }
+        Some(unsafe { self.chunk.data[slot as usize].assume_init_ref() })
+    }
+
+    /// Iterates the chunk's values with their global storage index, in index order.
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &'a T)> + 'a {
+        let chunk = self.chunk;
+        let base = self.base;
+        let mut mask = chunk.presence_mask;
+        std::iter::from_fn(move || {
+            if mask == 0 {
+                return None;
+            }
+            let slot = mask.trailing_zeros();
+            mask &= mask - 1;
+            let value = unsafe { chunk.data[slot as usize].assume_init_ref() };
+            Some((base | slot, value))
+        })
+    }
+
+    /// Iterates the values of the other slots, skipping the entity being iterated.
+    pub fn neighbors(&self) -> impl Iterator<Item = (u32, &'a T)> + 'a {
+        let own = self.base | self.slot;
+        self.iter().filter(move |(index, _)| *index != own)
+    }
 }
 
 impl<'a, T: Component> View<'a, T> {
-    pub fn new(data: &'a T) -> Self {
-        Self { data }
+    pub fn new(data: &'a T, index: u32, _frame: &'a Frame) -> Self {
+        Self {
+            data,
+            index,
+            _scope: PhantomData,
+        }
+    }
+
+    /// Returns the global storage index of the entity being processed.
+    #[inline(always)]
+    pub fn index(&self) -> u32 {
+        self.index
     }
 }
 
@@ -74,7 +260,7 @@ impl<'a, T: Component + Clone> ViewMut<'a, T> {
         storage: *mut Storage<T>,
         storage_idx: u32,
         page_idx: u32,
-        current_tick: Tick,
+        frame: &'a Frame,
     ) -> Self {
         Self {
             chunk,
@@ -82,8 +268,52 @@ impl<'a, T: Component + Clone> ViewMut<'a, T> {
             storage,
             storage_idx,
             page_idx,
-            current_tick,
+            current_tick: frame.current_tick,
+            _scope: PhantomData,
+        }
+    }
+
+    /// Returns the global storage index of the entity being processed.
+    /// (The `index` field is the slot within the current chunk.)
+    #[inline(always)]
+    pub fn index(&self) -> u32 {
+        (self.storage_idx << 12) | (self.page_idx << 6) | self.index
+    }
+
+    /// Returns the most recent tick in which this value was created or modified,
+    /// as recorded by the rollback history, or `None` if it has not changed
+    /// within the retained history window.
+    pub fn last_changed_tick(&self) -> Option<Tick> {
+        unsafe { (*self.storage).last_changed_tick(self.index()) }
+    }
+
+    /// Applies `f` to a copy of the value and writes it back only if `f` returns `true`.
+    ///
+    /// Unlike `DerefMut`, the value is marked changed (and its old value captured for
+    /// rollback) only when the closure reports a modification, so conditional
+    /// mutations don't produce spurious change bits. Returns the closure's flag.
+    #[inline]
+    pub fn update(&mut self, f: impl FnOnce(&mut T) -> bool) -> bool {
+        let mut value = (**self).clone();
+        let modified = f(&mut value);
+        if modified {
+            **self = value;
         }
+        modified
+    }
+
+    /// Like `update`, but decides whether the value was modified by comparing
+    /// the result with the current value.
+    #[inline]
+    pub fn update_if_ne(&mut self, f: impl FnOnce(&mut T)) -> bool
+    where
+        T: PartialEq,
+    {
+        self.update(|value| {
+            let before = value.clone();
+            f(value);
+            *value != before
+        })
     }
 }
 
@@ -102,19 +332,23 @@ impl<'a, T: Component + Clone> DerefMut for ViewMut<'a, T> {
             // Check if this is the first change in this chunk for the current processing
             let chunk_not_changed_before = self.chunk.changed_mask == 0;
             // Mark item-level change in chunk
-            self.chunk.changed_mask |= bit;
+            self.chunk.changed_mask |= bit & Storage::<T>::CHANGE_BITS;
 
             // Ensure rollback tick matches current tick
             let storage_mut = &mut *self.storage;
             storage_mut.ensure_rollback_tick(self.current_tick);
+            storage_mut.snapshot_chunk_on_first_touch(
+                self.current_tick,
+                self.storage_idx,
+                self.page_idx,
+            );
+            storage_mut.stamp_changed(self.storage_idx, self.page_idx, bit);
 
             // Access rollback page/chunk
             let rb_page = storage_mut.rollback.get_or_create_page(self.storage_idx);
             let rb_chunk = rb_page.get_or_create_chunk(self.page_idx);
 
             let was_created = (rb_chunk.created_mask & bit) != 0;
-            let was_changed = (rb_chunk.changed_mask & bit) != 0;
-            let was_removed = (rb_chunk.removed_mask & bit) != 0;
 
             if was_created {
                 // Created + modified in same tick remains created only; no old value stored
@@ -123,7 +357,7 @@ impl<'a, T: Component + Clone> DerefMut for ViewMut<'a, T> {
                 rb_chunk.created_mask |= bit;
             } else {
                 // Store old value only once per tick if not already tracked
-                if !was_changed && !was_removed {
+                if rb_chunk.needs_old_value(self.index) {
                     let old_val = self.chunk.data[self.index as usize]
                         .assume_init_ref()
                         .clone();
@@ -1,41 +1,371 @@
 #![allow(non_upper_case_globals)]
-use crate::component::Component;
+use crate::arena::Arena;
+use crate::bundle::Bundle;
+use crate::changes::ChangeSummary;
+use crate::commands::{ApplyCommandsSystem, CommandQueue, Commands};
+use crate::component::{Component, ComponentId, Destroyed};
 use crate::entity::Entity;
+use crate::error::{Error, Result};
+use crate::event::Event;
+use crate::expiry::Expiries;
 use crate::frame::Frame;
+use crate::gc::GarbageReport;
+use crate::hierarchy::{ChildOf, Parent};
+use crate::invariants::InvariantReport;
+use crate::layout::ChunkLayout;
+use crate::leak::{LeakEntry, LeakReport};
+use crate::replay::ReplayLog;
+use crate::resource::{RESOURCE_INDEX, RollbackConfig, SimTime};
+use crate::rng::DeterministicRng;
+use crate::rollback::MAX_HISTORY;
 use crate::scheduler::Scheduler;
+use crate::signature::{Archetype, ArchetypeStats, Signatures};
+use crate::snapshot::WorldSnapshot;
 use crate::storage::{Storage, StorageLike};
-use crate::tick::Tick;
+use crate::system::EndOfTickSystem;
+use crate::tick::{ChangeTick, Tick, TickGuard};
+use crate::trace::TraceEntry;
+use std::any::TypeId;
+use std::cell::Cell;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::ptr::NonNull;
+use std::rc::Rc;
 
 decs_macros::system_group!(SimulationGroup { Before=[CleanupGroup] });
+decs_macros::system_group!(TimerGroup { Before=[SimulationGroup, CleanupGroup] });
+decs_macros::system_group!(CommandsFlushGroup { After=[SimulationGroup], Before=[HierarchyGroup, CleanupGroup, DestroyGroup] });
 decs_macros::system_group!(HierarchyGroup { After=[SimulationGroup] });
 decs_macros::system_group!(CleanupGroup { After=[HierarchyGroup] });
+decs_macros::system_group!(DestroyOrderGroup { After=[HierarchyGroup, CommandsFlushGroup], Before=[CleanupGroup, DestroyGroup, EndOfTickGroup] });
 decs_macros::system_group!(DestroyGroup { After=[CleanupGroup] });
+decs_macros::system_group!(EndOfTickGroup { After=[DestroyGroup] });
+
+/// Where `World::get_component` and `has_component` look: a raw storage index, or an
+/// `Entity` handle, which resolves to its index only while it is alive (its generation
+/// still matches the one stored there).
+pub trait ComponentIndex {
+    fn resolve(self, world: &World) -> Option<u32>;
+}
+
+impl ComponentIndex for u32 {
+    fn resolve(self, _world: &World) -> Option<u32> {
+        Some(self)
+    }
+}
+
+impl ComponentIndex for Entity {
+    fn resolve(self, world: &World) -> Option<u32> {
+        world.is_alive(self).then_some(self.index())
+    }
+}
+
+/// Every storage of a world, indexed by component id.
+pub(crate) type StorageTable = [Option<Box<dyn StorageLike>>; 256];
 
 pub struct World {
     storage_mask: [u64; 4],
-    storage_ptrs: [Option<Box<dyn StorageLike>>; 256],
+    /// Boxed so `EndOfTickSystem` can keep a pointer to it while the world moves.
+    storage_ptrs: Box<StorageTable>,
     storage_raw_ptrs: [*mut (); 256],
     current_tick: Tick,
     scheduler: Scheduler,
+    fixed_dt: f32,
+    accumulator: f64,
+    alpha: f32,
+    rollback_priority: [i32; 256],
+    pending_rollback: Option<PendingRollback>,
+    commands: Box<CommandQueue>,
+    /// Boxed so storages can keep a pointer to it while the world moves.
+    signatures: Box<Signatures>,
+    /// Ticks destroyed entities keep their slot; boxed so the cleanup systems can keep a
+    /// pointer to it while the world moves.
+    destroyed_retention: Box<Cell<u32>>,
+    /// Counter the storages stamp writes with; shared with the frames so `Changed`
+    /// systems advance this world's counter only.
+    change_tick: Rc<ChangeTick>,
+    confirmed_tick: Option<Tick>,
+    /// Change tick of the source world at the last `extract_changed_into` with this
+    /// world as the target.
+    last_extraction: Tick,
+    /// Arena behind `Frame::scratch` while a tick runs; boxed so the frame can keep a
+    /// pointer to it.
+    scratch: Box<Arena>,
+    /// Seed given to `new_with_seed`.
+    seed: Option<u64>,
+    /// Worlds saved by `save_checkpoint`, by label.
+    checkpoints: HashMap<String, World>,
+}
+
+/// State of an incremental `World::rollback_to`.
+struct PendingRollback {
+    target: Tick,
+    /// Component ids still to restore, highest priority first.
+    remaining: VecDeque<u32>,
+    storages_per_step: usize,
 }
 
 impl World {
-    /// Creates a new empty World.
+    /// Creates a new World with no entities.
+    ///
+    /// The scheduler is not empty: every world runs `ApplyCommandsSystem` (in
+    /// `CommandsFlushGroup`), the Entity and Destroyed cleanups and, last,
+    /// `EndOfTickSystem`, which clears the changed masks and trims rollback history.
+    /// Their wavefronts are built here, so `run` on a bare world advances the tick and
+    /// clears the masks as before; adding systems or storages drops the wavefronts
+    /// until `build_wavefronts` or `finalize`.
     pub fn new() -> Self {
         let mut world = Self {
             storage_mask: [0; 4],
-            storage_ptrs: [const { None }; 256],
+            storage_ptrs: Box::new([const { None }; 256]),
             storage_raw_ptrs: [std::ptr::null_mut(); 256],
             current_tick: Tick(0),
             scheduler: Scheduler::new(),
+            fixed_dt: Frame::DEFAULT_FIXED_DT,
+            accumulator: 0.0,
+            alpha: 0.0,
+            rollback_priority: [0; 256],
+            pending_rollback: None,
+            commands: Box::default(),
+            signatures: Box::default(),
+            destroyed_retention: Box::default(),
+            change_tick: Rc::default(),
+            confirmed_tick: None,
+            last_extraction: Tick(0),
+            scratch: Box::default(),
+            seed: None,
+            checkpoints: HashMap::new(),
         };
+        world.rollback_priority[Entity::id() as usize] = i32::MAX;
 
         let _ = world.get_storage::<Entity>();
         let _ = world.get_storage::<crate::component::Destroyed>();
 
+        let apply = ApplyCommandsSystem::new(&mut world);
+        world.scheduler.add_system(apply);
+        let end_of_tick = EndOfTickSystem::new(&mut world);
+        world.scheduler.add_system(end_of_tick);
+        world.scheduler.build_wavefronts();
+
+        world
+    }
+
+    /// Creates a World whose `DeterministicRng` resource is seeded with `seed`. The seed is
+    /// kept with the world (`seed`, `stats`, snapshots and branches), so a bug report
+    /// only needs it and the input log to replay a session.
+    ///
+    /// # Panics
+    /// Panics if `DeterministicRng` was not registered with `Ecs::register`.
+    pub fn new_with_seed(seed: u64) -> Self {
+        assert!(
+            DeterministicRng::id() != u32::MAX,
+            "register DeterministicRng with Ecs::register before World::new_with_seed"
+        );
+        let mut world = Self::new();
+        world.seed = Some(seed);
+        world.insert_resource(DeterministicRng::new(seed));
         world
     }
 
+    /// Returns the seed the world was created with by `new_with_seed`.
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// `new_with_seed(seed)` if a seed was recorded, `new()` otherwise.
+    pub(crate) fn new_with_recorded_seed(seed: Option<u64>) -> Self {
+        match seed {
+            Some(seed) => Self::new_with_seed(seed),
+            None => Self::new(),
+        }
+    }
+
+    /// Returns the tick, seed and entity and storage counts, e.g. for a bug report.
+    pub fn stats(&self) -> WorldStats {
+        WorldStats {
+            tick: self.current_tick,
+            seed: self.seed,
+            entities: self
+                .existing_storage::<Entity>()
+                .map_or(0, |storage| unsafe { (*storage).count }),
+            storages: self.storages().count(),
+        }
+    }
+
+    /// Creates the storage of every component registered with `Ecs::register`, which
+    /// schedules its cleanup system, then builds the wavefronts. Call it once after
+    /// adding systems so despawning clears every component type, including ones no
+    /// system touched yet, and storages created later don't invalidate the schedule.
+    pub fn finalize(&mut self) {
+        crate::ecs::Ecs::create_registered_storages(self);
+        self.scheduler.build_wavefronts();
+    }
+
+    /// Returns the number of past ticks each storage keeps rollback snapshots for, unless
+    /// a `RollbackConfig` resource overrides it.
+    pub fn history_depth(&self) -> usize {
+        self.end_of_tick().history_depth()
+    }
+
+    /// Sets how many past ticks of rollback history `EndOfTickSystem` keeps per storage,
+    /// at most `MAX_HISTORY` (the default). Rolling back further than this fails with
+    /// `Error::HistoryExhausted`; lower depths free snapshots sooner.
+    pub fn set_history_depth(&mut self, depth: usize) {
+        assert!(depth > 0, "history depth must be positive");
+        self.end_of_tick().set_history_depth(depth.min(MAX_HISTORY));
+    }
+
+    /// Makes `EndOfTickSystem` compact every storage at the end of the next tick
+    /// (`Storage::compact`), releasing the snapshots and arenas kept for reuse. Meant for
+    /// quiet frames after heavy churn, e.g. once a match ends or a level unloads.
+    pub fn request_compaction(&mut self) {
+        self.end_of_tick().request_compaction();
+    }
+
+    /// Returns for how many ticks destroyed entities keep their slot; see
+    /// `set_destroyed_retention`.
+    pub fn destroyed_retention(&self) -> u32 {
+        self.destroyed_retention.get()
+    }
+
+    /// Keeps entities destroyed at tick `t` (given `Destroyed` then) alive with their
+    /// `Destroyed` marker until the cleanup of tick `t + ticks`, e.g. until peers acked
+    /// the despawn or a rollback past it can no longer happen. Their other components
+    /// are still removed at once. The Entity value keeps the slot taken, so `spawn` only
+    /// reuses it once freed, and `DespawnedEvent`s are sent then.
+    ///
+    /// Expiry is read from the `Destroyed` storage's rollback history, so a rollback
+    /// restores the remaining retention, and retention longer than `history_depth`
+    /// ends when the destruction leaves the history. 0 (the default) frees slots in the
+    /// tick they were destroyed.
+    pub fn set_destroyed_retention(&mut self, ticks: u32) {
+        self.destroyed_retention.set(ticks);
+    }
+
+    pub(crate) fn destroyed_retention_cell(&self) -> *const Cell<u32> {
+        &*self.destroyed_retention
+    }
+
+    /// Returns the value writes to this world's storages are currently stamped with
+    /// (`Storage::value_change_tick`).
+    pub fn change_tick(&self) -> Tick {
+        self.change_tick.get()
+    }
+
+    /// Advances this world's change counter and returns the new value, so later writes
+    /// are told apart from the ones before.
+    pub fn advance_change_tick(&self) -> Tick {
+        self.change_tick.advance()
+    }
+
+    fn end_of_tick(&self) -> &EndOfTickSystem {
+        self.scheduler
+            .get_system::<EndOfTickSystem>()
+            .expect("World::new adds an EndOfTickSystem")
+    }
+
+    pub(crate) fn storage_table(&mut self) -> *mut StorageTable {
+        &mut *self.storage_ptrs
+    }
+
+    /// Returns a read-only handle to the world at the current tick that can be sent to
+    /// another thread; see `decs::snapshot`.
+    pub fn snapshot(&self) -> WorldSnapshot<'_> {
+        WorldSnapshot::new(self)
+    }
+
+    /// Returns a new world holding this world's components at the current tick, e.g. a
+    /// short-lived speculative copy for AI evaluation. Both worlds share the component
+    /// chunks copy-on-write, so a branch costs its pages until either side writes.
+    ///
+    /// The branch has no systems and no rollback history before the current tick; its
+    /// timing, rollback priorities and destroyed retention are this world's.
+    pub fn branch(&self) -> World {
+        let mut branch = World::new();
+        branch.set_tick(self.current_tick);
+        branch.seed = self.seed;
+        branch.fixed_dt = self.fixed_dt;
+        branch.rollback_priority = self.rollback_priority;
+        branch
+            .destroyed_retention
+            .set(self.destroyed_retention.get());
+        branch.change_tick.set(self.change_tick.get());
+        *branch.signatures = (*self.signatures).clone();
+        for storage in self.storage_ptrs.iter().flatten() {
+            storage.branch_into(&mut branch);
+        }
+        branch
+    }
+
+    /// Saves the components and resources of the current tick under `label`, replacing
+    /// an older checkpoint of that label, e.g. before entering editor play mode or a
+    /// puzzle attempt. Unlike the rollback history, checkpoints are kept until removed.
+    /// Like a `branch`, a checkpoint shares the chunks copy-on-write.
+    pub fn save_checkpoint(&mut self, label: impl Into<String>) {
+        let checkpoint = self.branch();
+        self.checkpoints.insert(label.into(), checkpoint);
+    }
+
+    /// Makes the components and resources equal to the checkpoint saved under `label`,
+    /// writing the differences in the current tick: the restore is change-detected and
+    /// can itself be rolled back. The tick, the systems and the entity generation
+    /// counter stay, so handles of entities spawned after the checkpoint remain stale.
+    /// Events are not restored. The checkpoint is kept for further restores.
+    ///
+    /// # Errors
+    /// Returns `Error::CheckpointNotFound` if no checkpoint is saved under `label`.
+    pub fn restore_checkpoint(&mut self, label: &str) -> Result<()> {
+        let mut checkpoint = self
+            .checkpoints
+            .remove(label)
+            .ok_or(Error::CheckpointNotFound)?;
+        // Types that had no storage when it was saved had no values then.
+        crate::ecs::Ecs::create_registered_storages(&mut checkpoint);
+        for (id, storage) in checkpoint.storage_ptrs.iter().enumerate() {
+            if let Some(storage) = storage
+                && !storage.is_event()
+                && self.storage_ptrs[id].is_some()
+            {
+                storage.extract_changed_into(Tick(0), self);
+            }
+        }
+        self.checkpoints.insert(label.to_string(), checkpoint);
+        Ok(())
+    }
+
+    /// Drops the checkpoint saved under `label`. Returns false if there was none.
+    pub fn remove_checkpoint(&mut self, label: &str) -> bool {
+        self.checkpoints.remove(label).is_some()
+    }
+
+    /// Labels of the saved checkpoints, in no particular order.
+    pub fn checkpoints(&self) -> impl Iterator<Item = &str> {
+        self.checkpoints.keys().map(String::as_str)
+    }
+
+    /// Returns a handle for queueing commands; see `decs::commands`.
+    pub fn commands(&mut self) -> Commands {
+        Commands::new(self.command_queue(), self.storage_table())
+    }
+
+    /// Starts recording the `Commands` writes queued from outside the simulation into a
+    /// new `ReplayLog` headed with the world's seed, replacing any log being recorded;
+    /// see `decs::replay`.
+    pub fn start_replay_recording(&mut self) {
+        self.commands.start_recording(self.seed);
+    }
+
+    /// Stops recording and returns the log, or None if the world wasn't recording.
+    pub fn take_replay_log(&mut self) -> Option<ReplayLog> {
+        self.commands.take_recording()
+    }
+
+    /// Returns the queue `ApplyCommandsSystem` drains. It stays at the same address for
+    /// the lifetime of the world.
+    pub fn command_queue(&self) -> *const CommandQueue {
+        &*self.commands
+    }
+
     /// Returns an immutable reference to the scheduler.
     pub fn scheduler(&self) -> &Scheduler {
         &self.scheduler
@@ -56,11 +386,283 @@ impl World {
         crate::tick::CURRENT_TICK.with(|c| c.set(tick));
     }
 
+    /// Returns the simulation step length in seconds.
+    pub fn fixed_dt(&self) -> f32 {
+        self.fixed_dt
+    }
+
+    /// Sets the simulation step length in seconds used by `update` and
+    /// reported to systems through `Frame::fixed_dt`.
+    pub fn set_fixed_dt(&mut self, fixed_dt: f32) {
+        assert!(fixed_dt > 0.0, "fixed_dt must be positive");
+        self.fixed_dt = fixed_dt;
+    }
+
+    /// Returns the interpolation alpha left over by the last `update`.
+    pub fn alpha(&self) -> f32 {
+        self.alpha
+    }
+
+    /// Builds the frame for the current tick with the world's timing.
+    /// Render extraction running outside the scheduler can use this to
+    /// read `alpha` after `update`.
+    pub fn frame(&self) -> Frame {
+        Frame::with_timing(self.current_tick, self.fixed_dt, self.alpha)
+            .with_change_tick(self.change_tick.clone())
+    }
+
+    /// Opens a `TickGuard` for driving `frame`'s tick by hand (tools, tests, replays):
+    /// until it is dropped, debug builds panic if any storage is written for another
+    /// tick or has its changed masks cleared.
+    pub fn begin_tick(&self, frame: &Frame) -> TickGuard {
+        TickGuard::new(frame.current_tick)
+    }
+
+    /// Builds the frame systems run with, whose scratch arena is the world's.
+    fn tick_frame(&self) -> Frame {
+        // `run_tick` resets the arena only after dropping the frame, and allocations
+        // borrow the frame.
+        unsafe { self.frame().with_scratch(NonNull::from(&*self.scratch)) }
+    }
+
+    /// Game loop helper: accumulates `real_dt` seconds of wall-clock time,
+    /// runs as many fixed steps as fit and stores the remainder as `alpha`.
+    /// Returns the number of steps run.
+    ///
+    /// While the world is paused (`pause`, `step`) time doesn't accumulate, so
+    /// resuming doesn't catch up: each call only runs the ticks requested with `step`
+    /// and leaves `alpha` as it was, so rendering keeps showing the paused state.
+    pub fn update(&mut self, real_dt: f64) -> u32 {
+        if self.scheduler.is_paused() {
+            let mut steps = 0;
+            while self.scheduler.take_step() {
+                self.run();
+                steps += 1;
+            }
+            return steps;
+        }
+        let dt = self.fixed_dt as f64;
+        self.accumulator += real_dt.max(0.0);
+        let mut steps = 0;
+        while self.accumulator >= dt {
+            self.accumulator -= dt;
+            self.run();
+            steps += 1;
+        }
+        self.alpha = (self.accumulator / dt) as f32;
+        steps
+    }
+
+    /// Pauses the game loop helper; see `Scheduler::pause`.
+    pub fn pause(&mut self) {
+        self.scheduler.pause();
+    }
+
+    /// Has the next `update` run `ticks` more ticks, pausing the world if it isn't;
+    /// see `Scheduler::step`.
+    pub fn step(&mut self, ticks: u32) {
+        self.scheduler.step(ticks);
+    }
+
+    /// Lets `update` run ticks again; see `Scheduler::resume`.
+    pub fn resume(&mut self) {
+        self.scheduler.resume();
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.scheduler.is_paused()
+    }
+
+    /// Runs one tick of all scheduled systems.
+    ///
+    /// With the `panic-safety` feature a panicking system no longer leaves storages
+    /// half-updated: the world is rolled back to the start of the tick and this panics
+    /// again naming the failing system. Use `try_run` to handle that case instead.
     pub fn run(&mut self) {
-        self.current_tick = Tick(self.current_tick.0.wrapping_add(1));
-        let frame = Frame::new(self.current_tick());
-        self.scheduler.run(&frame);
+        #[cfg(feature = "panic-safety")]
+        if let Err((error, Some(payload))) = self.run_tick() {
+            let message = payload
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+                .unwrap_or("non-string panic payload");
+            panic!("{}: {}", error, message);
+        }
+        #[cfg(not(feature = "panic-safety"))]
+        let _ = self.run_tick();
+    }
+
+    /// Runs one tick of all scheduled systems.
+    ///
+    /// With the `panic-safety` feature each system runs under `catch_unwind`; if one
+    /// panics, the world is rolled back to the start of the tick (so the tick can be
+    /// re-run) and `Error::SystemPanicked` names the system. Without the feature a
+    /// panic propagates as usual.
+    ///
+    /// Under `ErrorPolicy::AbortTick`, a system returning an error is handled the same
+    /// way with `Error::SystemFailed`, with or without the feature; `run` then only
+    /// rolls back, leaving the error in `Scheduler::last_errors`.
+    pub fn try_run(&mut self) -> Result<()> {
+        #[cfg(feature = "panic-safety")]
+        return self.run_tick().map_err(|(error, _)| error);
+        #[cfg(not(feature = "panic-safety"))]
+        self.run_tick()
+    }
+
+    /// Headless loop helper: runs `ticks` ticks back to back, calling
+    /// `per_tick(world, tick)` with each tick about to run first (to apply its inputs).
+    /// Stops at the first tick that fails like `try_run`.
+    pub fn simulate(
+        &mut self,
+        ticks: u32,
+        per_tick: impl FnMut(&mut World, Tick),
+    ) -> Result<Simulation> {
+        self.simulate_with(ticks, SimulateOptions::default(), per_tick)
+    }
+
+    /// `simulate` with maintenance after every tick and `world_checksum` recording set
+    /// by `options`.
+    pub fn simulate_with(
+        &mut self,
+        ticks: u32,
+        options: SimulateOptions,
+        mut per_tick: impl FnMut(&mut World, Tick),
+    ) -> Result<Simulation> {
+        let mut simulation = Simulation {
+            first: self.current_tick.next(),
+            ticks: 0,
+            checksums: Vec::new(),
+        };
+        for _ in 0..ticks {
+            let tick = self.current_tick.next();
+            per_tick(self, tick);
+            self.try_run()?;
+            if let Some(lag) = options.confirm_lag {
+                self.confirm_tick(tick.wrapping_sub(lag));
+            }
+            if options.garbage_budget > 0 {
+                self.collect_garbage(options.garbage_budget);
+            }
+            if options.checksums {
+                simulation.checksums.push(crate::net::world_checksum(self));
+            }
+            simulation.ticks += 1;
+        }
+        Ok(simulation)
+    }
 
+    /// Rolls the tick begun at `start` back if a system aborted it under
+    /// `ErrorPolicy::AbortTick`.
+    fn abort_failed_tick(&mut self, start: Tick) -> Result<()> {
+        let Some(system) = self.scheduler.aborted_by() else {
+            return Ok(());
+        };
+        let tick = self.current_tick;
+        for storage in self.storage_ptrs.iter_mut().flatten() {
+            storage.reset_changed_masks();
+        }
+        self.rollback(start);
+        Err(Error::SystemFailed { system, tick })
+    }
+
+    /// Errors carry the panic payload when a system panicked.
+    #[cfg(feature = "panic-safety")]
+    #[allow(clippy::type_complexity)]
+    fn run_tick(
+        &mut self,
+    ) -> std::result::Result<(), (Error, Option<Box<dyn std::any::Any + Send>>)> {
+        self.step_rollback();
+        let start = self.current_tick;
+        self.current_tick = self.current_tick.next();
+        self.advance_sim_time();
+        let frame = self.tick_frame();
+        let blocked = self.rollback_blocked_types();
+        let result = self.scheduler.run_catching_except(&frame, &blocked);
+        drop(frame);
+        self.scratch.reset();
+        if let Err((system, payload)) = result {
+            for storage in self.storage_ptrs.iter_mut().flatten() {
+                storage.reset_changed_masks();
+            }
+            let error = Error::SystemPanicked {
+                system,
+                tick: self.current_tick,
+            };
+            self.rollback(start);
+            return Err((error, Some(payload)));
+        }
+        self.clear_changed_masks_if_unscheduled();
+        self.abort_failed_tick(start).map_err(|error| (error, None))
+    }
+
+    #[cfg(not(feature = "panic-safety"))]
+    fn run_tick(&mut self) -> Result<()> {
+        self.step_rollback();
+        let start = self.current_tick;
+        self.current_tick = self.current_tick.next();
+        self.advance_sim_time();
+        let frame = self.tick_frame();
+        let blocked = self.rollback_blocked_types();
+        self.scheduler.run_except(&frame, &blocked);
+        drop(frame);
+        self.scratch.reset();
+        self.clear_changed_masks_if_unscheduled();
+        self.abort_failed_tick(start)
+    }
+
+    /// Until the wavefronts are (re)built nothing runs, `EndOfTickSystem` included, so
+    /// `run` clears the changed masks itself as it did before the system existed.
+    fn clear_changed_masks_if_unscheduled(&mut self) {
+        if self.scheduler.wavefronts().is_empty() {
+            self.clear_changed_masks();
+        }
+    }
+
+    /// Advances to the next tick outside of `run`, performing the full per-tick rotation
+    /// in the order rollback expects:
+    /// 1. clears the changed masks left by the previous tick,
+    /// 2. increments the tick,
+    /// 3. rotates the rollback snapshot of every storage to the new tick,
+    /// 4. saves the Entity generation so spawns in this tick can be rolled back,
+    /// 5. advances the `SimTime` resource, if present.
+    ///
+    /// Use this when driving storages manually (tools, tests, benches) instead of
+    /// calling `set_tick` and `ensure_rollback_tick` by hand. Returns the new tick.
+    pub fn advance_tick(&mut self) -> Tick {
+        self.clear_changed_masks();
+        let tick = self.current_tick.next();
+        self.set_tick(tick);
+        let pending = self.pending_rollback.as_ref().map(|p| &p.remaining);
+        for (id, storage) in self.storage_ptrs.iter_mut().enumerate() {
+            if let Some(storage) = storage
+                && !pending.is_some_and(|p| p.contains(&(id as u32)))
+            {
+                storage.rotate_rollback_tick(tick);
+            }
+        }
+        unsafe { (*self.get_entity_storage()).save_generation_for_rollback() };
+        self.advance_sim_time();
+        tick
+    }
+
+    /// Advances the `SimTime` resource, if present, to the current tick.
+    fn advance_sim_time(&mut self) {
+        let frame = self.frame();
+        let Some(storage) = self.existing_storage::<SimTime>() else {
+            return;
+        };
+        if let Some(time) = unsafe { (*storage).get_mut(&frame, RESOURCE_INDEX) } {
+            time.advance(frame.current_tick, frame.fixed_dt);
+        }
+    }
+
+    /// Clears the hierarchical changed masks of every registered storage.
+    ///
+    /// `EndOfTickSystem` (at the end of every `run`) and `advance_tick` clear them once
+    /// the Changed-consuming systems have seen the tick's changes. Call it directly when
+    /// mutating storages outside the scheduler and the changes should not be reported
+    /// as Changed on the next run. Covers storages created after any earlier call.
+    pub fn clear_changed_masks(&mut self) {
         for seg in 0..4 {
             let base = seg * 64;
             let mut remaining_mask = self.storage_mask[seg];
@@ -97,7 +699,9 @@ impl World {
         let present = (self.storage_mask[seg] >> bit) & 1 != 0;
 
         if !present {
-            let storage_box: Box<Storage<T>> = Box::default();
+            let mut storage_box: Box<Storage<T>> = Box::default();
+            storage_box.signatures = &mut *self.signatures;
+            storage_box.change_tick = Rc::as_ptr(&self.change_tick);
             let raw = Box::into_raw(storage_box);
             self.storage_mask[seg] |= 1u64 << bit;
             let trait_box: Box<dyn StorageLike> = unsafe { Box::from_raw(raw) };
@@ -110,6 +714,12 @@ impl World {
         self.storage_raw_ptrs[index] as *mut Storage<T>
     }
 
+    /// Returns the storage for `T` without creating it; None for unregistered types too.
+    pub(crate) fn existing_storage<T: Component>(&self) -> Option<*mut Storage<T>> {
+        let raw = *self.storage_raw_ptrs.get(T::id() as usize)?;
+        (!raw.is_null()).then_some(raw as *mut Storage<T>)
+    }
+
     /// Returns a mutable reference to the storage for component type T.
     /// Creates the storage if it doesn't exist.
     pub fn get_storage_mut<T: Component>(&mut self) -> &mut Storage<T> {
@@ -117,6 +727,224 @@ impl World {
         unsafe { &mut *ptr }
     }
 
+    /// Returns the `T` component at `index` for mutation in the current tick, inserting
+    /// `init()` first if it is missing; see `Storage::get_or_insert_with`.
+    pub fn get_or_insert_component<T: Component>(
+        &mut self,
+        index: u32,
+        init: impl FnOnce() -> T,
+    ) -> &mut T {
+        let frame = self.frame();
+        self.get_storage_mut::<T>()
+            .get_or_insert_with(&frame, index, init)
+    }
+
+    /// Stores `value` as the `T` resource, replacing any previous one. The write is
+    /// recorded in the current tick's rollback snapshot.
+    pub fn insert_resource<T: Component>(&mut self, value: T) {
+        const { assert!(T::RESOURCE, "resource types need #[component(resource)]") };
+        let frame = self.frame();
+        self.get_storage_mut::<T>()
+            .set(&frame, RESOURCE_INDEX, value);
+    }
+
+    /// Returns true if `entity` is not `Entity::none()` and still occupies its index with
+    /// the same generation.
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        !entity.is_none() && self.get_component::<Entity>(entity.index()) == Some(&entity)
+    }
+
+    /// Returns the `T` at `at`: a raw index, or an `Entity` handle, which yields None once
+    /// the entity was despawned (even if its index was reused). Doesn't create the
+    /// storage, so it works on `&World` for any registered type.
+    pub fn get_component<T: Component>(&self, at: impl ComponentIndex) -> Option<&T> {
+        let index = at.resolve(self)?;
+        let storage = self.existing_storage::<T>()?;
+        unsafe { (*storage).get(index) }
+    }
+
+    /// Returns true if a `T` is stored at `at`; see `get_component`.
+    pub fn has_component<T: Component>(&self, at: impl ComponentIndex) -> bool {
+        self.get_component::<T>(at).is_some()
+    }
+
+    /// Returns the `T` resource, if one was inserted.
+    pub fn resource<T: Component>(&self) -> Option<&T> {
+        const { assert!(T::RESOURCE, "resource types need #[component(resource)]") };
+        let storage = self.existing_storage::<T>()?;
+        unsafe { (*storage).get(RESOURCE_INDEX) }
+    }
+
+    /// Returns the `T` resource for mutation in the current tick.
+    pub fn resource_mut<T: Component>(&mut self) -> Option<&mut T> {
+        const { assert!(T::RESOURCE, "resource types need #[component(resource)]") };
+        let frame = self.frame();
+        self.get_storage_mut::<T>().get_mut(&frame, RESOURCE_INDEX)
+    }
+
+    /// Removes the `T` resource, returning whether one was present.
+    pub fn remove_resource<T: Component>(&mut self) -> bool {
+        const { assert!(T::RESOURCE, "resource types need #[component(resource)]") };
+        let frame = self.frame();
+        self.get_storage_mut::<T>().remove(&frame, RESOURCE_INDEX)
+    }
+
+    /// Spawns an entity and inserts `bundle` on it in `frame`'s tick. Returns None if
+    /// the entity storage is full.
+    pub fn spawn_bundle<B: Bundle>(&mut self, frame: &Frame, bundle: B) -> Option<Entity> {
+        let entity = unsafe { (*self.get_entity_storage()).spawn(frame)? };
+        bundle.insert(self, frame, entity.index());
+        Some(entity)
+    }
+
+    /// Sets every component of `bundle` at `index`, all recorded for `frame`'s tick.
+    pub fn insert_bundle<B: Bundle>(&mut self, frame: &Frame, index: u32, bundle: B) {
+        bundle.insert(self, frame, index);
+    }
+
+    /// Sets `value` at `index` in `frame`'s tick and removes it again in the cleanup of
+    /// the tick `ttl_ticks` later, so simulation systems see it for `ttl_ticks` ticks.
+    /// Inserting again restarts the countdown. Requires `Expiries` to be registered;
+    /// see `decs::expiry`.
+    pub fn insert_temporary<T: Component>(
+        &mut self,
+        frame: &Frame,
+        index: u32,
+        value: T,
+        ttl_ticks: u32,
+    ) {
+        let scheduled = !self.scheduler.wavefronts().is_empty();
+        self.get_storage_mut::<T>().set(frame, index, value);
+        let expiries = self.get_storage_mut::<Expiries>();
+        let mut entry = expiries.get(index).cloned().unwrap_or_default();
+        entry.set(T::id(), frame.current_tick.wrapping_add(ttl_ticks));
+        expiries.set(frame, index, entry);
+        if scheduled && self.scheduler.wavefronts().is_empty() {
+            // Creating a storage (Expiries schedules ExpirySystem) cleared the schedule.
+            self.scheduler.build_wavefronts();
+        }
+    }
+
+    /// Sets every component of `bundle` at `index` as one operation and returns the
+    /// tick the writes were recorded for. That is `frame`'s tick unless one of the
+    /// bundle's storages already rotated its rollback snapshot to a newer tick (e.g.
+    /// storages skipped by `advance_tick` during an incremental rollback); then all
+    /// writes use the newest tick, so a rollback never keeps part of the set.
+    pub fn write_components<B: Bundle>(&mut self, frame: &Frame, index: u32, bundle: B) -> Tick {
+        let tick = B::newest_snapshot_tick(self, frame.current_tick);
+        let frame = Frame::with_timing(tick, frame.fixed_dt, frame.alpha);
+        bundle.insert(self, &frame, index);
+        tick
+    }
+
+    /// Removes every component of bundle type `B` at `index` in `frame`'s tick. Returns
+    /// true if any of them was present.
+    pub fn remove_bundle<B: Bundle>(&mut self, frame: &Frame, index: u32) -> bool {
+        B::remove(self, frame, index)
+    }
+
+    /// Moves every component at `index` to the same index of `dst`, removing them here
+    /// in the current tick and setting them there in `dst`'s. Keeping the index keeps
+    /// `Entity` references between moved entities valid. Returns false if nothing was
+    /// stored at `index`.
+    pub fn move_entity_to(&mut self, index: u32, dst: &mut World) -> bool {
+        let frame = self.frame();
+        let mut moved = false;
+        for storage in self.storage_ptrs.iter_mut().flatten() {
+            if !storage.is_resource() {
+                moved |= storage.move_to(&frame, index, dst);
+            }
+        }
+        moved
+    }
+
+    /// `duplicate_entity_except` without exclusions.
+    pub fn duplicate_entity(&mut self, frame: &Frame, src: Entity) -> Option<Entity> {
+        self.duplicate_entity_except(frame, src, &[])
+    }
+
+    /// Spawns an entity and sets a clone of every component of `src` on it in `frame`'s
+    /// tick, except the component ids in `exclude`, e.g. for editor copy-paste or
+    /// spawning projectiles from a template entity. `Destroyed` and events are not
+    /// copied. A copied `ChildOf` makes the copy a new child of `src`'s parent (linked
+    /// by the next hierarchy update), and `Parent` is dropped, as the children stay with
+    /// `src`. Returns None if `src` is not alive or the entity storage is full.
+    pub fn duplicate_entity_except(
+        &mut self,
+        frame: &Frame,
+        src: Entity,
+        exclude: &[ComponentId],
+    ) -> Option<Entity> {
+        let entities = self.get_entity_storage();
+        if unsafe { (*entities).get(src.index()) } != Some(&src) {
+            return None;
+        }
+        let entity = unsafe { (*entities).spawn(frame)? };
+        let skipped = [Entity::id(), Destroyed::id(), Parent::id()];
+        for (id, storage) in self.storage_ptrs.iter_mut().enumerate() {
+            let Some(storage) = storage else {
+                continue;
+            };
+            let id = id as ComponentId;
+            if skipped.contains(&id)
+                || exclude.contains(&id)
+                || storage.is_event()
+                || storage.is_resource()
+            {
+                continue;
+            }
+            storage.clone_value(frame, src.index(), entity.index());
+        }
+        if let Some(child_of) = self.existing_storage::<ChildOf>() {
+            let child_of = unsafe { &mut *child_of };
+            if let Some(link) = child_of.get(entity.index()) {
+                let parent = link.parent.or(link.pending_parent);
+                let link = ChildOf {
+                    parent: None,
+                    next_sibling: None,
+                    prev_sibling: None,
+                    pending_parent: parent,
+                };
+                child_of.set(frame, entity.index(), link);
+            }
+        }
+        Some(entity)
+    }
+
+    /// Brings the `types` components of `render_world` up to date with this world, in
+    /// `render_world`'s current tick: copies the values written since the previous
+    /// extraction into it and removes the ones removed here since. Values are matched by
+    /// their change tick stamps (`Chunk::changed_ticks`), so changes made by any number
+    /// of ticks in between, including a rollback, are picked up; components opting out
+    /// of change detection are copied whole. Meant for a render world fed by one
+    /// simulation world and not written otherwise. Ids without a storage here are
+    /// skipped.
+    pub fn extract_changed_into(&self, render_world: &mut World, types: &[ComponentId]) {
+        let since = render_world.last_extraction;
+        render_world.last_extraction = self.change_tick.get();
+        for &id in types {
+            if let Some(Some(storage)) = self.storage_ptrs.get(id as usize) {
+                storage.extract_changed_into(since, render_world);
+            }
+        }
+        // Writes after the extraction stamp something newer than what it copied.
+        self.change_tick.advance();
+    }
+
+    /// Sends `event` for the current tick; see `decs::event`. Returns its storage index,
+    /// or None if the event storage is full.
+    pub fn send_event<E: Event>(&mut self, event: E) -> Option<u32> {
+        let frame = self.frame();
+        crate::event::send(self.get_storage_mut::<E>(), &frame, event)
+    }
+
+    /// Iterates the `E` events sent this tick, in index order.
+    pub fn events<E: Event>(&self) -> impl Iterator<Item = &E> {
+        self.existing_storage::<E>()
+            .into_iter()
+            .flat_map(|storage| crate::event::read(unsafe { &*storage }))
+    }
+
     /// Verifies that all invariants hold for this World and all its storages.
     /// Also checks that all changed_mask values are 0 at every level (Storage, Page, Chunk).
     ///
@@ -146,6 +974,320 @@ impl World {
         true
     }
 
+    /// Runs the invariant checks of every storage and its rollback history, reporting
+    /// which storage, component type and level failed.
+    pub fn verify_all(&self) -> InvariantReport {
+        let mut report = InvariantReport::new();
+        for storage in self.storages() {
+            storage.verify_report(&mut report);
+        }
+        report
+    }
+
+    /// Returns the newest tick set with `confirm_tick`.
+    pub fn confirmed_tick(&self) -> Option<Tick> {
+        self.confirmed_tick
+    }
+
+    /// Marks every tick up to `tick` as confirmed, e.g. acknowledged by every peer: the
+    /// world will not be rolled back before it, so the history only that needs counts as
+    /// garbage for `pending_garbage` and `collect_garbage`.
+    pub fn confirm_tick(&mut self, tick: Tick) {
+        self.confirmed_tick = Some(tick);
+    }
+
+    /// Counts the memory held without being needed; see `decs::gc`.
+    pub fn pending_garbage(&self) -> GarbageReport {
+        let mut report = GarbageReport::default();
+        if let Some(destroyed) = self.existing_storage::<crate::component::Destroyed>() {
+            report.destroyed_entities = unsafe { (*destroyed).count } as usize;
+        }
+        for storage in self.storages() {
+            report.pooled_snapshots += storage.pooled_snapshots();
+            if let Some(confirmed) = self.confirmed_tick {
+                report.confirmed_snapshots += storage.history_through(confirmed);
+            }
+        }
+        report
+    }
+
+    /// Frees at most `budget` snapshots counted by `pending_garbage`: confirmed history
+    /// first, then pooled snapshots. Returns how many were freed; call it again in later
+    /// frames until `pending_garbage().collectable()` is 0.
+    pub fn collect_garbage(&mut self, budget: usize) -> usize {
+        let mut freed = 0;
+        if let Some(confirmed) = self.confirmed_tick {
+            for storage in self.storage_ptrs.iter_mut().flatten() {
+                freed += storage.discard_history_through(confirmed, budget - freed);
+            }
+        }
+        for storage in self.storage_ptrs.iter_mut().flatten() {
+            freed += storage.release_pooled(budget - freed);
+        }
+        freed
+    }
+
+    /// Summarizes what the current tick created, changed and removed, e.g. right after
+    /// `run` for a debug overlay; see `decs::changes`.
+    pub fn change_summary(&self) -> ChangeSummary {
+        let storages = self.storage_ptrs.iter().enumerate();
+        ChangeSummary::collect(
+            self.current_tick,
+            storages.filter_map(|(id, s)| Some((id as ComponentId, &**s.as_ref()?))),
+        )
+    }
+
+    /// Returns the system execution trace with component types named, oldest first.
+    /// Empty without the `system-trace` feature; see `decs::trace`.
+    pub fn system_trace(&self) -> Vec<TraceEntry> {
+        self.scheduler.trace().entries(|ty| {
+            self.storages()
+                .find(|s| s.component_type_id() == ty)
+                .map_or("unknown", |s| s.component_name())
+        })
+    }
+
+    /// Returns the live page/chunk/rollback allocation counts of every registered storage.
+    /// Counts are process-wide per component type and are only tracked with the
+    /// `leak-tracking` feature; without it every count is zero.
+    pub fn leak_report(&self) -> LeakReport {
+        LeakReport {
+            entries: self
+                .storages()
+                .map(|s| LeakEntry {
+                    type_name: s.component_name(),
+                    live: crate::leak::live_allocations_by_name(s.component_name()),
+                })
+                .collect(),
+        }
+    }
+
+    /// Looks up an existing storage by its dense `Component::id()`.
+    ///
+    /// Storages live in a flat array indexed by component id, so this (like `get_storage`)
+    /// is a bounds check and an array read. Returns None for ids without a storage yet.
+    pub fn get_storage_by_id(&self, id: u32) -> Option<&dyn StorageLike> {
+        self.storage_ptrs.get(id as usize)?.as_deref()
+    }
+
+    /// Iterates the ids of the components that have a value at `index`, in ascending
+    /// order, from the signature storages keep up to date on insert and removal. The
+    /// Entity component (id 0) is included for spawned entities.
+    pub fn components_of(&self, index: u32) -> impl Iterator<Item = ComponentId> + use<> {
+        self.signatures.iter(index)
+    }
+
+    /// Groups the spawned entities by the set of components they hold, most common first.
+    /// Accidental combinations (a tag that should have been removed, a missing marker)
+    /// show up as small archetypes, and the sizes help choose snapshot policies and chunk
+    /// layouts. Walks every signature, so it is meant for tools rather than every tick.
+    pub fn archetype_stats(&self) -> ArchetypeStats {
+        let mut archetypes: Vec<Archetype> = self
+            .signatures
+            .count_by_signature(Entity::id())
+            .into_iter()
+            .map(|(components, count)| Archetype {
+                names: components
+                    .iter()
+                    .map(|&id| {
+                        self.get_storage_by_id(id)
+                            .map_or("?", |s| s.component_name())
+                    })
+                    .collect(),
+                components,
+                count,
+            })
+            .collect();
+        archetypes.sort_unstable_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| a.components.cmp(&b.components))
+        });
+        ArchetypeStats { archetypes }
+    }
+
+    /// Mutable variant of `get_storage_by_id`.
+    pub fn get_storage_by_id_mut(&mut self, id: u32) -> Option<&mut dyn StorageLike> {
+        self.storage_ptrs.get_mut(id as usize)?.as_deref_mut()
+    }
+
+    /// Iterates over every registered storage in component id order.
+    pub fn storages(&self) -> impl Iterator<Item = &dyn StorageLike> {
+        self.storage_ptrs.iter().flatten().map(|s| &**s)
+    }
+
+    /// Sets the priority of `T`'s storage in an incremental `rollback_to`; higher values
+    /// are restored first. Entity defaults to `i32::MAX` and every other type to 0.
+    pub fn set_rollback_priority<T: Component>(&mut self, priority: i32) {
+        self.rollback_priority[T::id() as usize] = priority;
+    }
+
+    /// Allocates the chunks of `T`'s storage with `layout`, e.g. from a dedicated arena
+    /// for hot components or at huge page alignment for large ones; see `decs::layout`.
+    /// Creates the storage if needed.
+    ///
+    /// # Panics
+    /// Panics if `T`'s storage already holds values; configure layouts before spawning.
+    pub fn set_chunk_layout<T: Component>(&mut self, layout: ChunkLayout) {
+        self.get_storage_mut::<T>().set_chunk_layout(layout);
+    }
+
+    /// Incremental variant of `try_rollback` for very large rollbacks.
+    ///
+    /// Storages without changes newer than `target_tick` are rolled back immediately.
+    /// The others are restored `storages_per_step` at a time in priority order: one step
+    /// now and one at the start of every following `run`. Until its storage is restored,
+    /// every system reading or writing that component is skipped, so only the affected
+    /// systems wait; nothing outside the scheduler should touch those storages either.
+    /// Calling `rollback`/`rollback_to` again first completes the pending rollback.
+    pub fn rollback_to(&mut self, target_tick: Tick, storages_per_step: usize) -> Result<()> {
+        self.finish_rollback();
+        for storage in self.storage_ptrs.iter().flatten() {
+            if let Some(oldest) = storage.history_floor()
+                && oldest.is_after(target_tick)
+            {
+                return Err(Error::HistoryExhausted {
+                    target: target_tick,
+                    oldest,
+                });
+            }
+        }
+        let mut remaining = Vec::new();
+        for (id, storage) in self.storage_ptrs.iter_mut().enumerate() {
+            if let Some(storage) = storage {
+                if storage.has_changes_after(target_tick) {
+                    remaining.push(id as u32);
+                } else {
+                    storage.rollback(target_tick);
+                }
+            }
+        }
+        remaining.sort_by_key(|&id| (std::cmp::Reverse(self.rollback_priority[id as usize]), id));
+        self.set_tick(target_tick);
+        self.pending_rollback = Some(PendingRollback {
+            target: target_tick,
+            remaining: remaining.into(),
+            storages_per_step: storages_per_step.max(1),
+        });
+        self.step_rollback();
+        Ok(())
+    }
+
+    /// Returns true while an incremental `rollback_to` still has storages to restore.
+    pub fn rollback_pending(&self) -> bool {
+        self.pending_rollback.is_some()
+    }
+
+    /// Restores the next storages of a pending `rollback_to`; `run` calls this at the
+    /// start of every tick. Returns true once nothing is pending.
+    pub fn step_rollback(&mut self) -> bool {
+        let Some(pending) = self.pending_rollback.as_mut() else {
+            return true;
+        };
+        for _ in 0..pending.storages_per_step {
+            let Some(id) = pending.remaining.pop_front() else {
+                break;
+            };
+            if let Some(storage) = self.storage_ptrs[id as usize].as_mut() {
+                storage.rollback(pending.target);
+            }
+        }
+        if !pending.remaining.is_empty() {
+            return false;
+        }
+        self.pending_rollback = None;
+        debug_assert!(
+            self.verify_invariants(),
+            "World invariants violated after rollback"
+        );
+        true
+    }
+
+    /// Completes a pending `rollback_to` at once.
+    pub fn finish_rollback(&mut self) {
+        if let Some(pending) = self.pending_rollback.as_mut() {
+            pending.storages_per_step = usize::MAX;
+            self.step_rollback();
+        }
+    }
+
+    /// Component types whose storages a pending `rollback_to` has not restored yet.
+    fn rollback_blocked_types(&self) -> Vec<TypeId> {
+        self.pending_rollback
+            .as_ref()
+            .map(|p| {
+                p.remaining
+                    .iter()
+                    .filter_map(|&id| self.storage_ptrs[id as usize].as_ref())
+                    .map(|s| s.component_type_id())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Fallible variant of `rollback`: verifies that every storage still retains the
+    /// history needed to reach `target_tick` before touching any of them, returning
+    /// `Error::HistoryExhausted` instead of performing a partial rollback.
+    pub fn try_rollback(&mut self, target_tick: Tick) -> Result<()> {
+        for storage in self.storage_ptrs.iter().flatten() {
+            if let Some(oldest) = storage.history_floor()
+                && oldest.is_after(target_tick)
+            {
+                return Err(Error::HistoryExhausted {
+                    target: target_tick,
+                    oldest,
+                });
+            }
+        }
+        self.rollback(target_tick);
+        Ok(())
+    }
+
+    /// The tick a local input sampled now is applied in: the next tick, delayed by the
+    /// `RollbackConfig`'s `input_delay` if the resource is present.
+    pub fn input_tick(&self) -> Tick {
+        match self.resource::<RollbackConfig>() {
+            Some(config) => config.input_tick(self.current_tick),
+            None => self.current_tick.next(),
+        }
+    }
+
+    /// Re-runs the ticks from `first` up to the current tick after their input changed,
+    /// e.g. once a late remote input for `first` arrived: rolls back to the end of the
+    /// tick before it, then calls `input(world, tick)` with each tick about to run and
+    /// runs it. Returns how many ticks were resimulated; none if `first` was not run yet.
+    ///
+    /// With a `RollbackConfig` resource, `first` must be at most `max_rollback_frames`
+    /// ticks back, else `Error::RollbackWindowExceeded` is returned before anything is
+    /// touched. Missing history fails like `try_rollback`, and a failing tick like
+    /// `try_run`.
+    pub fn resimulate<I>(&mut self, first: Tick, mut input: I) -> Result<u32>
+    where
+        I: FnMut(&mut World, Tick),
+    {
+        let end = self.current_tick;
+        let target = first.wrapping_sub(1);
+        let frames = end.diff(target).value();
+        if frames <= 0 {
+            return Ok(0);
+        }
+        if let Some(config) = self.resource::<RollbackConfig>()
+            && frames as u32 > config.max_rollback_frames
+        {
+            return Err(Error::RollbackWindowExceeded {
+                target,
+                max_frames: config.max_rollback_frames,
+            });
+        }
+        self.try_rollback(target)?;
+        while self.current_tick != end {
+            let tick = self.current_tick.next();
+            input(self, tick);
+            self.try_run()?;
+        }
+        Ok(frames as u32)
+    }
+
     /// Rolls back all component storages to the specified tick.
     /// This iterates through all active storages and calls their rollback method.
     /// After rolling back all storages, sets the world tick to target_tick.
@@ -154,6 +1296,7 @@ impl World {
     /// Only works for components that implement Clone (required by Storage::rollback).
     /// The world tick is updated to target_tick after all rollbacks complete.
     pub fn rollback(&mut self, target_tick: Tick) {
+        self.finish_rollback();
         // Iterate through all storage segments
         for seg in 0..4 {
             let base = seg * 64;
@@ -219,4 +1362,51 @@ impl Default for World {
     }
 }
 
+/// Summary of a world returned by `World::stats`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WorldStats {
+    pub tick: Tick,
+    /// Seed of `World::new_with_seed`, if the world was created with one.
+    pub seed: Option<u64>,
+    /// Spawned entities.
+    pub entities: u32,
+    pub storages: usize,
+}
+
+impl fmt::Display for WorldStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.tick)?;
+        if let Some(seed) = self.seed {
+            write!(f, ", seed {:#x}", seed)?;
+        }
+        write!(
+            f,
+            ", {} entities, {} storages",
+            self.entities, self.storages
+        )
+    }
+}
+
+/// Per-tick work of `World::simulate_with` besides running the tick.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SimulateOptions {
+    /// Records `net::world_checksum` after every tick in `Simulation::checksums`.
+    pub checksums: bool,
+    /// Confirms each tick this many ticks after it ran (`World::confirm_tick`), for
+    /// servers that never roll back further.
+    pub confirm_lag: Option<u32>,
+    /// Snapshots `World::collect_garbage` may free after every tick; 0 frees none.
+    pub garbage_budget: usize,
+}
+
+/// What `World::simulate` ran.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Simulation {
+    /// The tick run first.
+    pub first: Tick,
+    pub ticks: u32,
+    /// `world_checksum` after each tick, if requested with `SimulateOptions::checksums`.
+    pub checksums: Vec<u64>,
+}
+
 // (existing Drop above handles dropping scheduler first, then storages)
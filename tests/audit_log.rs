@@ -0,0 +1,72 @@
+#![cfg(feature = "audit-log")]
+
+use decs::audit::{self, StructuralOp};
+use decs::component::Destroyed;
+use decs::ecs::Ecs;
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Health(i32);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Health>();
+    });
+}
+
+#[test]
+fn logs_inserts_and_removals_with_system_names() {
+    register_components_once();
+    audit::clear();
+    let mut world = World::new();
+    let t1 = world.advance_tick();
+    let f = world.frame();
+    {
+        let health = world.get_storage_mut::<Health>();
+        health.set(&f, 3, Health(10));
+        health.set(&f, 3, Health(9)); // overwrite: not structural
+        health.set(&f, 4, Health(10));
+        assert!(health.remove(&f, 4));
+    }
+    world.get_storage_mut::<Destroyed>().set(&f, 3, Destroyed());
+    world.scheduler_mut().build_wavefronts();
+    world.run();
+
+    let ops: Vec<_> = audit::entries_for_tick(t1)
+        .into_iter()
+        .filter(|e| e.component.ends_with("Health"))
+        .map(|e| (e.index, e.op, e.system))
+        .collect();
+    assert_eq!(
+        ops,
+        vec![
+            (3, StructuralOp::Insert, None),
+            (4, StructuralOp::Insert, None),
+            (4, StructuralOp::Remove, None),
+        ]
+    );
+
+    let cleanup: Vec<_> = audit::entries_for_tick(t1.next())
+        .into_iter()
+        .filter(|e| e.component.ends_with("Health"))
+        .collect();
+    assert_eq!(cleanup.len(), 1);
+    assert_eq!((cleanup[0].index, cleanup[0].op), (3, StructuralOp::Remove));
+    assert!(
+        cleanup[0]
+            .system
+            .unwrap()
+            .contains("ComponentCleanupSystem")
+    );
+
+    let json = audit::to_json(&cleanup);
+    assert!(json.starts_with("[{\"tick\":2,\"component\":\""));
+    assert!(json.contains("\"index\":3,\"op\":\"remove\",\"system\":\""));
+    assert_eq!(audit::to_json(&[]), "[]");
+
+    audit::clear();
+    assert!(audit::entries().is_empty());
+}
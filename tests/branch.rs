@@ -0,0 +1,128 @@
+use decs::ecs::Ecs;
+use decs::error::Error;
+use decs::storage::Chunk;
+use decs::system;
+use decs::tick::Tick;
+use decs::view::{View, ViewMut};
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Hp(u32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Regen(u32);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Hp>();
+        Ecs::register::<Regen>();
+    });
+}
+
+system!(RegenSystem { query
+    fn update(regen: View<Regen>, hp: &mut ViewMut<Hp>)
+    {
+        hp.0 += regen.0;
+    }
+});
+
+fn chunk_of(world: &mut World, index: u32) -> *const Chunk<Hp> {
+    let storage = world.get_storage_mut::<Hp>();
+    let page = unsafe { &*storage.data[(index >> 12) as usize] };
+    page.data[((index >> 6) & 63) as usize]
+}
+
+fn hp(world: &mut World, index: u32) -> Option<u32> {
+    world.get_storage_mut::<Hp>().get(index).map(|hp| hp.0)
+}
+
+/// A world at tick 2 with `Hp` at indices 1 and 70, which live in different chunks.
+fn populated_world() -> World {
+    register_components_once();
+    let mut world = World::new();
+    world.finalize();
+    let f = world.frame();
+    world.get_storage_mut::<Hp>().set(&f, 1, Hp(10));
+    world.get_storage_mut::<Hp>().set(&f, 70, Hp(20));
+    world.get_storage_mut::<Regen>().set(&f, 1, Regen(5));
+    world.run();
+    world.run();
+    world
+}
+
+#[test]
+fn branch_shares_chunks_until_either_world_writes() {
+    let mut world = populated_world();
+    let mut branch = world.branch();
+    assert_eq!(branch.current_tick(), world.current_tick());
+    assert_eq!(hp(&mut branch, 1), Some(10));
+    assert_eq!(hp(&mut branch, 70), Some(20));
+    assert_eq!(chunk_of(&mut branch, 1), chunk_of(&mut world, 1));
+    assert!(unsafe { (*chunk_of(&mut world, 1)).is_shared() });
+
+    // Writing in the branch copies only the written chunk.
+    let f = branch.frame();
+    branch.get_storage_mut::<Hp>().get_mut(&f, 1).unwrap().0 = 11;
+    assert_ne!(chunk_of(&mut branch, 1), chunk_of(&mut world, 1));
+    assert_eq!(chunk_of(&mut branch, 70), chunk_of(&mut world, 70));
+    assert!(!unsafe { (*chunk_of(&mut world, 1)).is_shared() });
+    assert_eq!(hp(&mut world, 1), Some(10));
+    assert_eq!(hp(&mut branch, 1), Some(11));
+
+    // And so does removing in the original.
+    let f = world.frame();
+    assert!(world.get_storage_mut::<Hp>().remove(&f, 70));
+    assert_eq!(hp(&mut world, 70), None);
+    assert_eq!(hp(&mut branch, 70), Some(20));
+
+    assert!(world.verify_invariants());
+    assert!(branch.verify_invariants());
+    drop(world);
+    assert_eq!(hp(&mut branch, 70), Some(20));
+    assert!(branch.verify_invariants());
+}
+
+#[test]
+fn systems_of_a_branch_leave_the_original_untouched() {
+    let mut world = populated_world();
+    let mut branch = world.branch();
+    let system = RegenSystem::new(&mut branch);
+    branch.scheduler_mut().add_system(system);
+    branch.finalize();
+
+    branch.run();
+    branch.run();
+    assert_eq!(hp(&mut branch, 1), Some(20));
+    assert_eq!(hp(&mut world, 1), Some(10));
+
+    // The original keeps running on its own chunks.
+    world.run();
+    assert_eq!(hp(&mut world, 1), Some(10));
+    assert!(world.components_of(1).eq(branch.components_of(1)));
+    assert!(world.verify_invariants());
+    assert!(branch.verify_invariants());
+}
+
+#[test]
+fn branch_cannot_roll_back_past_its_creation() {
+    let world = populated_world();
+    let tick = world.current_tick();
+    let mut branch = world.branch();
+    let f = branch.frame();
+    branch.get_storage_mut::<Hp>().set(&f, 1, Hp(0));
+    branch.run();
+
+    assert_eq!(
+        branch.try_rollback(Tick(tick.0 - 1)),
+        Err(Error::HistoryExhausted {
+            target: Tick(tick.0 - 1),
+            oldest: tick
+        })
+    );
+    branch.rollback(tick);
+    assert_eq!(hp(&mut branch, 1), Some(0));
+    assert_eq!(hp(&mut branch, 70), Some(20));
+}
@@ -0,0 +1,103 @@
+use decs::ecs::Ecs;
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Burning {
+    ticks_left: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Smoke(f32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct DamageOverTime(i32);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Burning>();
+        Ecs::register::<Smoke>();
+        Ecs::register::<DamageOverTime>();
+    });
+}
+
+fn burning_state(world: &mut World, index: u32) -> (bool, bool, bool) {
+    (
+        world.get_storage_mut::<Burning>().get(index).is_some(),
+        world.get_storage_mut::<Smoke>().get(index).is_some(),
+        world
+            .get_storage_mut::<DamageOverTime>()
+            .get(index)
+            .is_some(),
+    )
+}
+
+#[test]
+fn insert_and_remove_bundle_roll_back_together() {
+    register_components_once();
+    let mut world = World::new();
+    let t1 = world.advance_tick();
+    let frame = world.frame();
+    let entity = world
+        .spawn_bundle(&frame, (Smoke(0.5),))
+        .expect("entity storage has room");
+    let index = entity.index();
+    assert_eq!(burning_state(&mut world, index), (false, true, false));
+
+    let t2 = world.advance_tick();
+    let frame = world.frame();
+    world.insert_bundle(
+        &frame,
+        index,
+        (Burning { ticks_left: 3 }, Smoke(1.0), DamageOverTime(4)),
+    );
+    assert_eq!(burning_state(&mut world, index), (true, true, true));
+
+    world.advance_tick();
+    let frame = world.frame();
+    assert!(world.remove_bundle::<(Burning, DamageOverTime)>(&frame, index));
+    assert!(!world.remove_bundle::<(Burning, DamageOverTime)>(&frame, index));
+    assert_eq!(burning_state(&mut world, index), (false, true, false));
+
+    world.rollback(t2);
+    assert_eq!(burning_state(&mut world, index), (true, true, true));
+    assert_eq!(
+        world.get_storage_mut::<Smoke>().get(index),
+        Some(&Smoke(1.0))
+    );
+
+    world.rollback(t1);
+    assert_eq!(burning_state(&mut world, index), (false, true, false));
+    assert_eq!(
+        world.get_storage_mut::<Smoke>().get(index),
+        Some(&Smoke(0.5))
+    );
+    assert!(world.verify_invariants());
+}
+
+#[test]
+fn write_components_uses_one_tick_across_rotated_storages() {
+    register_components_once();
+    let mut world = World::new();
+    let t1 = world.advance_tick();
+    let index = 9;
+
+    // Smoke's snapshot is already open for the next tick, Burning's is not.
+    let t2 = t1.next();
+    let ahead = decs::frame::Frame::new(t2);
+    world.get_storage_mut::<Smoke>().set(&ahead, 3, Smoke(0.1));
+
+    let frame = world.frame();
+    let tick = world.write_components(&frame, index, (Burning { ticks_left: 2 }, Smoke(0.7)));
+    assert_eq!(tick, t2);
+    assert_eq!(world.get_storage_mut::<Burning>().rollback.tick(), t2);
+    assert_eq!(world.get_storage_mut::<Smoke>().rollback.tick(), t2);
+    assert_eq!(burning_state(&mut world, index), (true, true, false));
+
+    // Rolling back to t1 undoes the whole set, not just the rotated half.
+    world.rollback(t1);
+    assert_eq!(burning_state(&mut world, index), (false, false, false));
+    assert!(world.verify_invariants());
+}
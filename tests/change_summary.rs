@@ -0,0 +1,83 @@
+use decs::component::Component;
+use decs::ecs::Ecs;
+use decs::system;
+use decs::view::{View, ViewMut};
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Pos(i32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Vel(i32);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Pos>();
+        Ecs::register::<Vel>();
+    });
+}
+
+system!(MoveSystem { query
+    fn update(vel: View<Vel>, pos: &mut ViewMut<Pos>)
+    {
+        pos.0 += vel.0;
+    }
+});
+
+#[test]
+fn change_summary_counts_the_touches_of_the_last_tick() {
+    register_components_once();
+    let mut world = World::new();
+    let system = MoveSystem::new(&mut world);
+    world.scheduler_mut().add_system(system);
+    world.finalize();
+    world.run();
+    let f = world.frame();
+    for index in [1, 4097, 4098] {
+        world.get_storage_mut::<Pos>().set(&f, index, Pos(0));
+        world.get_storage_mut::<Vel>().set(&f, index, Vel(1));
+    }
+    world.get_storage_mut::<Pos>().set(&f, 9, Pos(0));
+
+    let summary = world.change_summary();
+    assert_eq!(summary.tick, world.current_tick());
+    let pos = summary
+        .components
+        .iter()
+        .find(|c| c.id == Pos::id())
+        .unwrap();
+    assert_eq!((pos.created, pos.changed, pos.removed), (4, 0, 0));
+    assert_eq!(summary.hot_pages, [(1, 4), (0, 3)]);
+    assert_eq!(summary.top_entities[..3], [(1, 2), (4097, 2), (4098, 2)]);
+
+    world.run();
+    let f = world.frame();
+    world.get_storage_mut::<Vel>().remove(&f, 1);
+    let summary = world.change_summary();
+    let pos = summary
+        .components
+        .iter()
+        .find(|c| c.id == Pos::id())
+        .unwrap();
+    assert_eq!((pos.created, pos.changed, pos.removed), (0, 3, 0));
+    let vel = summary
+        .components
+        .iter()
+        .find(|c| c.id == Vel::id())
+        .unwrap();
+    assert_eq!((vel.created, vel.changed, vel.removed), (0, 0, 1));
+    assert_eq!(summary.total(), 4);
+    assert_eq!(summary.hot_pages, [(0, 2), (1, 2)]);
+    assert_eq!(summary.top_entities[0], (1, 2));
+    assert!(summary.to_string().contains(": +0 ~3 -0"));
+
+    // Only the systems' writes are left in the next tick.
+    world.run();
+    let summary = world.change_summary();
+    assert_eq!(summary.components.len(), 1);
+    assert_eq!(summary.components[0].changed, 2);
+    assert_eq!(summary.hot_pages, [(1, 2)]);
+}
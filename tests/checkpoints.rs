@@ -0,0 +1,104 @@
+use decs::ecs::Ecs;
+use decs::error::Error;
+use decs::system;
+use decs::view::{View, ViewMut};
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Hp(u32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Regen(u32);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Hp>();
+        Ecs::register::<Regen>();
+    });
+}
+
+system!(RegenSystem { query
+    fn update(regen: View<Regen>, hp: &mut ViewMut<Hp>)
+    {
+        hp.0 += regen.0;
+    }
+});
+
+fn hp(world: &mut World, index: u32) -> Option<u32> {
+    world.get_storage_mut::<Hp>().get(index).map(|hp| hp.0)
+}
+
+fn regen_world() -> World {
+    register_components_once();
+    let mut world = World::new();
+    let system = RegenSystem::new(&mut world);
+    world.scheduler_mut().add_system(system);
+    world.finalize();
+    let f = world.frame();
+    world.get_storage_mut::<Hp>().set(&f, 1, Hp(10));
+    world.get_storage_mut::<Regen>().set(&f, 1, Regen(5));
+    world.run();
+    world
+}
+
+#[test]
+fn restoring_a_checkpoint_undoes_everything_since_it_was_saved() {
+    let mut world = regen_world();
+    world.save_checkpoint("edit");
+    assert_eq!(world.checkpoints().collect::<Vec<_>>(), ["edit"]);
+
+    world.run();
+    world.run();
+    let f = world.frame();
+    world.get_storage_mut::<Hp>().set(&f, 4096, Hp(1));
+    world.get_storage_mut::<Regen>().remove(&f, 1);
+    world.run();
+    assert_eq!(hp(&mut world, 1), Some(25));
+
+    let played = world.current_tick();
+    world.restore_checkpoint("edit").unwrap();
+    assert_eq!(world.current_tick(), played);
+    assert_eq!(hp(&mut world, 1), Some(15));
+    assert_eq!(hp(&mut world, 4096), None);
+    assert_eq!(world.get_storage_mut::<Regen>().get(1), Some(&Regen(5)));
+    world.run();
+    assert_eq!(hp(&mut world, 1), Some(20));
+    assert!(world.verify_invariants());
+
+    // The checkpoint is kept, and unaffected by writes after the restore.
+    world.restore_checkpoint("edit").unwrap();
+    assert_eq!(hp(&mut world, 1), Some(15));
+}
+
+#[test]
+fn a_restore_can_be_rolled_back() {
+    let mut world = regen_world();
+    world.save_checkpoint("retry");
+    world.run();
+    world.run();
+    let before = world.current_tick();
+    world.run();
+    world.restore_checkpoint("retry").unwrap();
+    assert_eq!(hp(&mut world, 1), Some(15));
+    world.rollback(before);
+    assert_eq!(hp(&mut world, 1), Some(25));
+}
+
+#[test]
+fn unknown_checkpoints_are_reported() {
+    let mut world = regen_world();
+    assert_eq!(
+        world.restore_checkpoint("missing"),
+        Err(Error::CheckpointNotFound)
+    );
+    world.save_checkpoint("a");
+    assert!(world.remove_checkpoint("a"));
+    assert!(!world.remove_checkpoint("a"));
+    assert_eq!(
+        world.restore_checkpoint("a"),
+        Err(Error::CheckpointNotFound)
+    );
+}
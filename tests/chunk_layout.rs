@@ -0,0 +1,143 @@
+#![feature(allocator_api)]
+use decs::ecs::Ecs;
+use decs::error::Error;
+use decs::frame::Frame;
+use decs::layout::ChunkLayout;
+use decs::tick::Tick;
+use decs::world::World;
+use decs_macros::Component;
+use std::alloc::{AllocError, Allocator, Global, Layout};
+use std::ptr::NonNull;
+use std::sync::Once;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Hot(u64);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Cold(u64);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Starved(u64);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Hot>();
+        Ecs::register::<Cold>();
+        Ecs::register::<Starved>();
+    });
+}
+
+/// Global allocator wrapper counting live allocations.
+struct Counting {
+    live: AtomicUsize,
+}
+
+unsafe impl Allocator for Counting {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.live.fetch_add(1, Ordering::Relaxed);
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.live.fetch_sub(1, Ordering::Relaxed);
+        unsafe { Global.deallocate(ptr, layout) }
+    }
+}
+
+/// Allocator that always fails.
+struct Exhausted;
+
+unsafe impl Allocator for Exhausted {
+    fn allocate(&self, _: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        Err(AllocError)
+    }
+
+    unsafe fn deallocate(&self, _: NonNull<u8>, _: Layout) {
+        unreachable!("Exhausted never hands out memory")
+    }
+}
+
+static HOT_CHUNKS: Counting = Counting {
+    live: AtomicUsize::new(0),
+};
+
+fn chunk_addr(world: &mut World, index: u32) -> usize {
+    let storage = world.get_storage_mut::<Hot>();
+    let page = unsafe { &*storage.data[(index >> 12) as usize] };
+    page.data[((index >> 6) & 63) as usize] as usize
+}
+
+#[test]
+fn chunks_come_from_the_configured_allocator_and_alignment() {
+    register_components_once();
+    let mut world = World::new();
+    world.set_chunk_layout::<Hot>(
+        ChunkLayout::new()
+            .allocator(&HOT_CHUNKS)
+            .aligned(ChunkLayout::PAGE),
+    );
+
+    let f = world.frame();
+    for index in [0, 1, 64, 5000] {
+        world
+            .get_storage_mut::<Hot>()
+            .set(&f, index, Hot(index as u64));
+        world
+            .get_storage_mut::<Cold>()
+            .set(&f, index, Cold(index as u64));
+    }
+    assert_eq!(HOT_CHUNKS.live.load(Ordering::Relaxed), 3);
+    for index in [0, 64, 5000] {
+        assert_eq!(chunk_addr(&mut world, index) % ChunkLayout::PAGE, 0);
+    }
+    assert_eq!(
+        world
+            .get_storage_mut::<Hot>()
+            .chunk_layout
+            .align_of::<Hot>(),
+        ChunkLayout::PAGE
+    );
+
+    // Emptied chunks go back to the allocator; rollback allocates them again.
+    world.set_tick(Tick(1));
+    let f = Frame::new(Tick(1));
+    world.get_storage_mut::<Hot>().remove(&f, 64);
+    assert_eq!(HOT_CHUNKS.live.load(Ordering::Relaxed), 2);
+    world.rollback(Tick(0));
+    assert_eq!(world.get_storage_mut::<Hot>().get(64), Some(&Hot(64)));
+    assert_eq!(HOT_CHUNKS.live.load(Ordering::Relaxed), 3);
+    assert!(world.verify_invariants());
+
+    drop(world);
+    assert_eq!(HOT_CHUNKS.live.load(Ordering::Relaxed), 0);
+}
+
+#[test]
+#[should_panic(expected = "before inserting values")]
+fn layout_cannot_change_once_values_exist() {
+    register_components_once();
+    let mut world = World::new();
+    let f = world.frame();
+    world.get_storage_mut::<Cold>().set(&f, 3, Cold(3));
+    world.set_chunk_layout::<Cold>(ChunkLayout::new().aligned(ChunkLayout::CACHE_LINE_PAIR));
+}
+
+#[test]
+fn failed_chunk_allocation_leaves_the_storage_unchanged() {
+    register_components_once();
+    let mut world = World::new();
+    world.set_chunk_layout::<Starved>(ChunkLayout::new().allocator(&Exhausted));
+
+    let f = world.frame();
+    let storage = world.get_storage_mut::<Starved>();
+    assert_eq!(
+        storage.try_set(&f, 5000, Starved(1)),
+        Err(Error::AllocationFailed)
+    );
+    assert_eq!(storage.presence_mask, 0);
+    assert_eq!(storage.changed_mask, 0);
+    assert_eq!(storage.get(5000), None);
+    assert!(world.verify_invariants());
+}
@@ -0,0 +1,107 @@
+use decs::ecs::Ecs;
+use decs::system;
+use decs::system::System;
+use decs::view::{ChunkView, View, ViewMut};
+use decs::world::World;
+use decs_macros::Component;
+use std::any::TypeId;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Pos(i32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Crowd(u32);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Pos>();
+        Ecs::register::<Crowd>();
+    });
+}
+
+system!(CrowdSystem { query
+    fn update(pos: View<Pos>, flock: ChunkView<Pos>, crowd: &mut ViewMut<Crowd>)
+    {
+        crowd.0 = flock
+            .neighbors()
+            .filter(|(_, other)| (other.0 - pos.0).abs() <= 2)
+            .count() as u32;
+    }
+});
+
+system!(ChunkSizeSystem { query
+    fn update(flock: ChunkView<Pos>, crowd: &mut ViewMut<Crowd>)
+    {
+        crowd.0 = flock.iter().count() as u32 + 100 * flock.slot();
+    }
+});
+
+fn world_with<S: System>(new: fn(&mut World) -> S, entities: &[(u32, Option<i32>)]) -> World {
+    register_components_once();
+    let mut world = World::new();
+    let system = new(&mut world);
+    world.scheduler_mut().add_system(system);
+    world.finalize();
+    let f = world.frame();
+    for &(index, pos) in entities {
+        if let Some(pos) = pos {
+            world.get_storage_mut::<Pos>().set(&f, index, Pos(pos));
+        }
+        world.get_storage_mut::<Crowd>().set(&f, index, Crowd(0));
+    }
+    world
+}
+
+fn crowd(world: &mut World, index: u32) -> u32 {
+    world.get_storage_mut::<Crowd>().get(index).unwrap().0
+}
+
+#[test]
+fn chunk_view_sees_the_neighbours_in_the_same_chunk_only() {
+    // 63 and 64 are close but in different chunks.
+    let entities = [
+        (0, Some(0)),
+        (1, Some(1)),
+        (5, Some(10)),
+        (63, Some(2)),
+        (64, Some(3)),
+        (70, Some(4)),
+    ];
+    let mut world = world_with(CrowdSystem::new, &entities);
+    let system = world.scheduler().get_system::<CrowdSystem>().unwrap();
+    assert!(system.reads().contains(&TypeId::of::<Pos>()));
+    world.run();
+    assert_eq!(crowd(&mut world, 0), 2);
+    assert_eq!(crowd(&mut world, 1), 2);
+    assert_eq!(crowd(&mut world, 5), 0);
+    assert_eq!(crowd(&mut world, 63), 2);
+    assert_eq!(crowd(&mut world, 64), 1);
+    assert_eq!(crowd(&mut world, 70), 1);
+}
+
+#[test]
+fn chunk_view_requires_the_component() {
+    let entities = [(4096 + 2, Some(0)), (4096 + 3, None), (4096 + 9, Some(0))];
+    let mut world = world_with(ChunkSizeSystem::new, &entities);
+    world.run();
+    assert_eq!(crowd(&mut world, 4096 + 2), 202);
+    assert_eq!(crowd(&mut world, 4096 + 3), 0);
+    assert_eq!(crowd(&mut world, 4096 + 9), 902);
+
+    let f = world.frame();
+    let storage = world.get_storage_mut::<Pos>();
+    let page = unsafe { &*storage.data[1] };
+    let chunk = unsafe { &*page.data[0] };
+    let view = ChunkView::new(chunk, 4096, 2, &f);
+    assert_eq!(view.presence_mask(), (1 << 2) | (1 << 9));
+    assert_eq!(view.first_index(), 4096);
+    assert_eq!(view.get(9), Some(&Pos(0)));
+    assert_eq!(view.get(3), None);
+    assert_eq!(view.get(64), None);
+    assert_eq!(
+        view.neighbors().map(|(index, _)| index).collect::<Vec<_>>(),
+        [4096 + 9]
+    );
+}
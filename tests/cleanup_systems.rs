@@ -20,11 +20,16 @@ struct Health {
 #[derive(Clone, Debug, PartialEq, Component)]
 struct DestroyedTag;
 
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Loot(u32);
+
 static INIT: Once = Once::new();
 fn register_components_once() {
     INIT.call_once(|| {
         Ecs::register::<Position>();
         Ecs::register::<Health>();
+        Ecs::register::<Loot>();
+        Ecs::register::<DestroyedTag>();
         // Built-in Destroyed component is already registered in World::new
     });
 }
@@ -124,3 +129,82 @@ fn cleanup_preserves_invariants_across_pages() {
     world.scheduler_mut().build_wavefronts();
     world.run();
 }
+
+#[test]
+fn finalize_schedules_cleanup_for_untouched_components() {
+    register_components_once();
+
+    let mut world = World::new();
+    world.finalize();
+    assert!(!world.scheduler().wavefronts().is_empty());
+
+    // Loot's storage already exists, so touching it doesn't clear the wavefronts.
+    let f = world.frame();
+    world.get_storage_mut::<Loot>().set(&f, 3, Loot(50));
+    world.get_storage_mut::<Loot>().set(&f, 4, Loot(10));
+    world.get_storage_mut::<DestroyedTag>().set(&f, 3, DestroyedTag);
+    world
+        .get_storage_mut::<decs::component::Destroyed>()
+        .set(&f, 3, decs::component::Destroyed {});
+    assert!(!world.scheduler().wavefronts().is_empty());
+
+    world.run();
+    let loot = world.get_storage_mut::<Loot>();
+    assert!(loot.get(3).is_none());
+    assert_eq!(loot.get(4), Some(&Loot(10)));
+    assert!(world.get_storage_mut::<DestroyedTag>().get(3).is_none());
+    assert!(world.verify_invariants());
+}
+
+#[test]
+fn destroyed_retention_keeps_the_slot_until_it_expires() {
+    register_components_once();
+    let mut world = World::new();
+    world.finalize();
+    world.set_destroyed_retention(3);
+    let entities = world.get_entity_storage();
+
+    world.run();
+    let f = world.frame();
+    let entity = world
+        .spawn_bundle(&f, (Position { x: 0.0, y: 0.0 },))
+        .unwrap();
+    let index = entity.index();
+    world.run();
+    let f = world.frame();
+    world
+        .get_storage_mut::<decs::component::Destroyed>()
+        .set(&f, index, decs::component::Destroyed());
+    let destroyed_at = world.current_tick();
+
+    // Components go at once; the entity and its marker stay for the retention.
+    world.run();
+    assert!(world.get_storage_mut::<Position>().get(index).is_none());
+    for _ in 1..3 {
+        assert_eq!(unsafe { (*entities).get(index) }, Some(&entity));
+        assert!(world
+            .get_storage_mut::<decs::component::Destroyed>()
+            .get(index)
+            .is_some());
+        let f = world.frame();
+        let other = unsafe { (*entities).spawn(&f) }.unwrap();
+        assert_ne!(other.index(), index);
+        world.run();
+    }
+    assert_eq!(world.current_tick().0, destroyed_at.0 + 3);
+    assert!(unsafe { (*entities).get(index) }.is_none());
+    assert!(world
+        .get_storage_mut::<decs::component::Destroyed>()
+        .get(index)
+        .is_none());
+    assert!(world.verify_invariants());
+
+    // Rolling back into the retention brings the entity back, still destroyed, and
+    // re-simulating frees it at the same tick.
+    world.rollback(destroyed_at.wrapping_add(1));
+    assert_eq!(unsafe { (*entities).get(index) }, Some(&entity));
+    world.run();
+    assert_eq!(unsafe { (*entities).get(index) }, Some(&entity));
+    world.run();
+    assert!(unsafe { (*entities).get(index) }.is_none());
+}
@@ -0,0 +1,255 @@
+use decs::commands::{ApplyCommandsSystem, Commands, with_issuer};
+use decs::ecs::Ecs;
+use decs::frame::Frame;
+use decs::storage::Storage;
+use decs::system;
+use decs::system::System;
+use decs::view::{View, ViewMut};
+use decs::world::World;
+use decs_macros::Component;
+use std::any::{Any, TypeId};
+use std::sync::{Arc, Mutex, Once};
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Tag(u32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Seen(u32);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Tag>();
+        Ecs::register::<Seen>();
+    });
+}
+
+/// Tags index 5 with the current tick through commands, declaring no writes.
+struct QueueTag {
+    commands: Commands,
+    tags: *mut Storage<Tag>,
+}
+
+unsafe impl Send for QueueTag {}
+unsafe impl Sync for QueueTag {}
+
+impl QueueTag {
+    fn new(world: &mut World) -> Self {
+        Self {
+            commands: world.commands(),
+            tags: world.get_storage::<Tag>(),
+        }
+    }
+}
+
+impl System for QueueTag {
+    fn run(&self, frame: &Frame) {
+        unsafe {
+            self.commands
+                .insert(self.tags, 5, Tag(frame.current_tick.0))
+        };
+    }
+
+    fn reads(&self) -> &[TypeId] {
+        &[]
+    }
+
+    fn writes(&self) -> &[TypeId] {
+        &[]
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+system!(CopyTag { query
+    fn update(tag: View<Tag>, seen: &mut ViewMut<Seen>)
+    {
+        seen.0 = tag.0;
+    }
+    Parent=[decs::world::HierarchyGroup]
+});
+
+fn world_with_systems() -> World {
+    let mut world = World::new();
+    let f = world.frame();
+    world.get_storage_mut::<Seen>().set(&f, 5, Seen(0));
+    let queue = QueueTag::new(&mut world);
+    let copy = CopyTag::new(&mut world);
+    world.scheduler_mut().add_system(queue);
+    world.scheduler_mut().add_system(copy);
+    world.scheduler_mut().build_wavefronts();
+    world
+}
+
+#[test]
+fn commands_are_applied_before_later_groups() {
+    register_components_once();
+    let mut world = world_with_systems();
+
+    // QueueTag declares no writes, so only the flush barrier orders CopyTag after the
+    // insert.
+    world.run();
+    assert_eq!(world.get_storage_mut::<Tag>().get(5), Some(&Tag(1)));
+    assert_eq!(world.get_storage_mut::<Seen>().get(5), Some(&Seen(1)));
+    world.run();
+    assert_eq!(world.get_storage_mut::<Seen>().get(5), Some(&Seen(2)));
+    assert!(
+        world
+            .scheduler()
+            .get_system::<ApplyCommandsSystem>()
+            .is_some()
+    );
+}
+
+#[test]
+fn commands_queued_outside_a_tick_apply_at_the_next_flush() {
+    register_components_once();
+    let mut world = World::new();
+    let tags = world.get_storage::<Tag>();
+    world.scheduler_mut().build_wavefronts();
+
+    let commands = world.commands();
+    unsafe { commands.insert(tags, 3, Tag(7)) };
+    // Commands run in issue order and may queue more, applied in the same flush.
+    commands.push(move |_| {
+        let tag = unsafe { (*tags).get(3).cloned() }.expect("insert applied first");
+        unsafe { commands.insert(tags, 4, Tag(tag.0 + 1)) };
+    });
+    assert_eq!(unsafe { &*world.command_queue() }.len(), 2);
+    assert!(world.get_storage_mut::<Tag>().get(3).is_none());
+
+    world.run();
+    assert_eq!(world.get_storage_mut::<Tag>().get(3), Some(&Tag(7)));
+    assert_eq!(world.get_storage_mut::<Tag>().get(4), Some(&Tag(8)));
+    assert!(unsafe { &*world.command_queue() }.is_empty());
+
+    unsafe { world.commands().remove(tags, 3) };
+    world.run();
+    assert!(world.get_storage_mut::<Tag>().get(3).is_none());
+    assert!(world.verify_invariants());
+}
+
+system!(AddMissingSeen { query
+    fn update(tag: View<Tag>, commands: Commands)
+    {
+        commands.insert_component(tag.index(), Seen(tag.0));
+    }
+    None=[Seen]
+});
+
+#[test]
+fn query_without_component_inserts_it_through_commands() {
+    register_components_once();
+    let mut world = World::new();
+    let f = world.frame();
+    for index in [1, 2, 70] {
+        world
+            .get_storage_mut::<Tag>()
+            .set(&f, index, Tag(index * 10));
+    }
+    world.get_storage_mut::<Seen>().set(&f, 2, Seen(0));
+    let system = AddMissingSeen::new(&mut world);
+    world.scheduler_mut().add_system(system);
+    world.scheduler_mut().build_wavefronts();
+
+    // Only the tags without a `Seen` match; the inserts land at the command flush.
+    world.run();
+    let seen = world.get_storage_mut::<Seen>();
+    assert_eq!(seen.get(1), Some(&Seen(10)));
+    assert_eq!(seen.get(2), Some(&Seen(0)));
+    assert_eq!(seen.get(70), Some(&Seen(700)));
+
+    let f = world.frame();
+    world.get_storage_mut::<Seen>().remove(&f, 1);
+    world.run();
+    assert_eq!(world.get_storage_mut::<Seen>().get(1), Some(&Seen(10)));
+    assert!(world.verify_invariants());
+}
+
+/// Issues the commands of schedule positions `1..=systems` from `threads` threads, each
+/// going through its share backwards, and returns the order they were applied in and
+/// the final value of the contended index.
+fn apply_from_threads(threads: usize, systems: u32) -> (Vec<u32>, Option<Tag>) {
+    let mut world = World::new();
+    let _ = world.get_storage::<Tag>();
+    world.scheduler_mut().build_wavefronts();
+    let commands = world.commands();
+    let applied = Arc::new(Mutex::new(Vec::new()));
+    std::thread::scope(|scope| {
+        for thread in 0..threads as u32 {
+            let applied = applied.clone();
+            scope.spawn(move || {
+                for position in (1..=systems).rev().filter(|p| p % threads as u32 == thread) {
+                    with_issuer(position, || {
+                        let applied = applied.clone();
+                        commands.push(move |_| applied.lock().unwrap().push(position));
+                        commands.insert_component(5, Tag(position));
+                    });
+                }
+            });
+        }
+    });
+    world.run();
+    let order = applied.lock().unwrap().clone();
+    (order, world.get_storage_mut::<Tag>().get(5).cloned())
+}
+
+#[test]
+fn commands_apply_in_schedule_order_regardless_of_threads() {
+    register_components_once();
+    let expected = ((1..=12).collect::<Vec<_>>(), Some(Tag(12)));
+    for threads in [1, 2, 3, 8] {
+        assert_eq!(
+            apply_from_threads(threads, 12),
+            expected,
+            "{} threads",
+            threads
+        );
+    }
+}
+
+/// Like `QueueTag`, but for any index and value.
+struct QueueValue(Commands, *mut Storage<Tag>, u32);
+
+unsafe impl Send for QueueValue {}
+unsafe impl Sync for QueueValue {}
+
+impl System for QueueValue {
+    fn run(&self, _frame: &Frame) {
+        unsafe { self.0.insert(self.1, 9, Tag(self.2)) };
+    }
+
+    fn reads(&self) -> &[TypeId] {
+        &[]
+    }
+
+    fn writes(&self) -> &[TypeId] {
+        &[]
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[test]
+fn outside_commands_apply_before_system_commands() {
+    register_components_once();
+    let mut world = World::new();
+    let tags = world.get_storage::<Tag>();
+    let commands = world.commands();
+    world
+        .scheduler_mut()
+        .add_system(QueueValue(commands, tags, 1));
+    world
+        .scheduler_mut()
+        .add_system(QueueValue(commands, tags, 2));
+    world.scheduler_mut().build_wavefronts();
+
+    // Queued before the tick, so the systems' later inserts win.
+    unsafe { commands.insert(tags, 9, Tag(0)) };
+    world.run();
+    assert_eq!(world.get_storage_mut::<Tag>().get(9), Some(&Tag(2)));
+}
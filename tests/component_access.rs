@@ -0,0 +1,71 @@
+use decs::component::Destroyed;
+use decs::ecs::Ecs;
+use decs::entity::Entity;
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Health(u32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Unused;
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Health>();
+        Ecs::register::<Unused>();
+    });
+}
+
+#[test]
+fn reads_components_by_index() {
+    register_components_once();
+    let mut world = World::new();
+    let f = world.frame();
+    world.get_storage_mut::<Health>().set(&f, 7, Health(3));
+
+    assert_eq!(world.get_component::<Health>(7), Some(&Health(3)));
+    assert!(world.has_component::<Health>(7));
+    assert!(!world.has_component::<Health>(8));
+    // No storage was created for it, and reading doesn't create one.
+    assert!(!world.has_component::<Unused>(7));
+    assert!(
+        world
+            .storages()
+            .all(|s| !s.component_name().ends_with("Unused"))
+    );
+}
+
+#[test]
+fn entity_handles_are_generation_checked() {
+    register_components_once();
+    let mut world = World::new();
+    world.finalize();
+    let f = world.frame();
+    let entity = unsafe { (*world.get_entity_storage()).spawn(&f) }.unwrap();
+    world
+        .get_storage_mut::<Health>()
+        .set(&f, entity.index(), Health(10));
+    assert!(world.is_alive(entity));
+    assert_eq!(world.get_component::<Health>(entity), Some(&Health(10)));
+    assert!(!world.is_alive(Entity::none()));
+    assert!(!world.has_component::<Health>(Entity::none()));
+
+    world
+        .get_storage_mut::<Destroyed>()
+        .set(&f, entity.index(), Destroyed());
+    world.run();
+    let f = world.frame();
+    let reused = unsafe { (*world.get_entity_storage()).spawn(&f) }.unwrap();
+    assert_eq!(reused.index(), entity.index());
+    world
+        .get_storage_mut::<Health>()
+        .set(&f, reused.index(), Health(1));
+
+    assert!(!world.is_alive(entity));
+    assert_eq!(world.get_component::<Health>(entity), None);
+    assert_eq!(world.get_component::<Health>(reused), Some(&Health(1)));
+    assert!(world.has_component::<Health>(entity.index()));
+}
@@ -0,0 +1,74 @@
+use decs::component::{Component, ComponentMetadata};
+use decs::ecs::Ecs;
+use decs::entity::Entity;
+use decs_macros::Component;
+use std::sync::Once;
+
+/// Linear velocity in meters per second.
+///
+/// Integrated by the physics step.
+#[derive(Clone, Debug, PartialEq, Component)]
+#[component(category = "physics", replicated)]
+struct Velocity(f32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+#[component(category = "debug")]
+struct DebugLabel(String);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Plain;
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Velocity>();
+        Ecs::register::<DebugLabel>();
+        Ecs::register::<Plain>();
+    });
+}
+
+#[test]
+fn derive_records_doc_category_and_replication() {
+    register_components_once();
+    assert_eq!(
+        Ecs::metadata(Velocity::id()),
+        Some(ComponentMetadata {
+            name: std::any::type_name::<Velocity>(),
+            doc: "Linear velocity in meters per second.\n\nIntegrated by the physics step.",
+            category: Some("physics"),
+            replicated: true,
+            priority: 0,
+        })
+    );
+    let label = Ecs::metadata(DebugLabel::id()).unwrap();
+    assert_eq!(label.category, Some("debug"));
+    assert!(!label.replicated);
+    assert_eq!(
+        Ecs::metadata(Plain::id()),
+        Some(ComponentMetadata {
+            name: std::any::type_name::<Plain>(),
+            ..ComponentMetadata::NONE
+        })
+    );
+    assert_eq!(Ecs::metadata(250), None);
+}
+
+#[test]
+fn registered_components_can_be_filtered_by_metadata() {
+    register_components_once();
+    let replicated: Vec<_> = Ecs::registered()
+        .filter(|(_, metadata)| metadata.replicated)
+        .map(|(id, _)| id)
+        .collect();
+    assert_eq!(replicated, [Velocity::id()]);
+
+    let physics: Vec<_> = Ecs::registered()
+        .filter(|(_, metadata)| metadata.category == Some("physics"))
+        .map(|(_, metadata)| metadata.name)
+        .collect();
+    assert_eq!(physics, [std::any::type_name::<Velocity>()]);
+
+    let (id, entity) = Ecs::registered().next().unwrap();
+    assert_eq!(id, Entity::id());
+    assert_eq!(entity.name, std::any::type_name::<Entity>());
+}
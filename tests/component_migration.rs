@@ -0,0 +1,83 @@
+use decs::component::Component;
+use decs::ecs::Ecs;
+use decs_macros::Component;
+use std::sync::Once;
+
+/// Hit points in tenths; version 1 stored whole points.
+#[derive(Clone, Debug, PartialEq, Component)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+#[component(version = 2)]
+struct Health(u32);
+
+#[derive(Clone, Component)]
+struct Marker;
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Health>();
+        Ecs::register::<Marker>();
+    });
+}
+
+#[cfg(feature = "serde")]
+impl decs::migrate::Migrate for Health {
+    fn migrate<'de, D: serde::Deserializer<'de>>(version: u32, data: D) -> Result<Self, D::Error> {
+        use serde::Deserialize;
+        match version {
+            1 => u32::deserialize(data).map(|points| Health(points * 10)),
+            _ => Err(decs::migrate::unsupported_version::<Self, D::Error>(
+                version,
+            )),
+        }
+    }
+}
+
+#[test]
+fn registry_records_component_versions() {
+    register_components_once();
+    assert_eq!(Health::VERSION, 2);
+    assert_eq!(Ecs::version(Health::id()), Some(2));
+    assert_eq!(Ecs::version(Marker::id()), Some(1));
+    assert_eq!(Ecs::version(decs::entity::Entity::id()), Some(1));
+    assert_eq!(Ecs::version(255), None);
+}
+
+#[cfg(feature = "serde")]
+mod serde_migration {
+    use super::*;
+    use decs::migrate::{Versioned, deserialize_versioned};
+    use serde::Deserialize;
+    use serde::de::value::{Error, MapDeserializer, SeqDeserializer, U32Deserializer};
+
+    fn versioned(version: u32, value: u32) -> Result<Versioned<Health>, Error> {
+        let fields = vec![("version", version), ("value", value)];
+        Versioned::deserialize(MapDeserializer::<_, Error>::new(fields.into_iter()))
+    }
+
+    #[test]
+    fn current_version_deserializes_directly() {
+        let health: Health = deserialize_versioned(2, U32Deserializer::<Error>::new(75)).unwrap();
+        assert_eq!(health, Health(75));
+        assert_eq!(versioned(2, 75).unwrap(), Versioned(Health(75)));
+    }
+
+    #[test]
+    fn older_versions_are_migrated() {
+        let health: Health = deserialize_versioned(1, U32Deserializer::<Error>::new(7)).unwrap();
+        assert_eq!(health, Health(70));
+        assert_eq!(versioned(1, 7).unwrap(), Versioned(Health(70)));
+
+        let seq = SeqDeserializer::<_, Error>::new(vec![1u32, 3].into_iter());
+        assert_eq!(Versioned::deserialize(seq).unwrap(), Versioned(Health(30)));
+    }
+
+    #[test]
+    fn unknown_versions_are_rejected() {
+        let newer = versioned(3, 5).unwrap_err().to_string();
+        assert!(newer.contains("unsupported version 3"), "{}", newer);
+        let dropped = deserialize_versioned::<Health, _>(0, U32Deserializer::<Error>::new(5));
+        assert!(dropped.is_err());
+    }
+}
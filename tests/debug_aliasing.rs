@@ -0,0 +1,112 @@
+#![cfg(feature = "debug-aliasing")]
+
+use decs::ecs::Ecs;
+use decs::frame::Frame;
+use decs::hierarchy::{ChildOf, Parent, UpdateHierarchySystem};
+use decs::storage::Storage;
+use decs::system; // for `system!`
+use decs::system::System;
+use decs::view::ViewMut;
+use decs::world::World;
+use decs_macros::Component;
+use std::any::Any;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Health {
+    hp: i32,
+}
+
+static INIT: Once = Once::new();
+fn register_components_once() {
+    INIT.call_once(|| {
+        Ecs::register::<Health>();
+        Ecs::register::<ChildOf>();
+        Ecs::register::<Parent>();
+    });
+}
+
+system!(RegenSystem {
+    query fn update(h: &mut ViewMut<Health>) {
+        h.hp += 1;
+    }
+});
+
+/// Reads Health through a raw pointer but forgets to declare it in `reads()`,
+/// so the scheduler places it in the same wavefront as `RegenSystem`.
+struct UndeclaredReader {
+    health: *const Storage<Health>,
+}
+
+unsafe impl Send for UndeclaredReader {}
+unsafe impl Sync for UndeclaredReader {}
+
+impl System for UndeclaredReader {
+    fn run(&self, _frame: &Frame) {
+        decs::aliasing::track_borrow::<Health>(self.name(), false);
+        let _ = unsafe { (*self.health).get(0) };
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[test]
+#[should_panic(expected = "storage aliasing conflict")]
+fn undeclared_read_in_same_wavefront_panics() {
+    register_components_once();
+    let mut world = World::new();
+    {
+        let f = world.frame();
+        world
+            .get_storage_mut::<Health>()
+            .set(&f, 0, Health { hp: 1 });
+    }
+    let regen = RegenSystem::new(&mut world);
+    let reader = UndeclaredReader {
+        health: world.get_storage::<Health>(),
+    };
+    world.scheduler_mut().add_system(regen);
+    world.scheduler_mut().add_system(reader);
+    world.scheduler_mut().build_wavefronts();
+    world.run();
+}
+
+#[test]
+fn declared_systems_do_not_conflict() {
+    register_components_once();
+    let mut world = World::new();
+    {
+        let f = world.frame();
+        world
+            .get_storage_mut::<Health>()
+            .set(&f, 0, Health { hp: 1 });
+    }
+    let regen = RegenSystem::new(&mut world);
+    world.scheduler_mut().add_system(regen);
+    world.scheduler_mut().build_wavefronts();
+    world.run();
+    world.run();
+}
+
+#[test]
+fn hierarchy_update_is_ordered_against_entity_cleanup() {
+    register_components_once();
+    let mut world = World::new();
+    let update = UpdateHierarchySystem::new(&mut world);
+    world.scheduler_mut().add_system(update);
+    world.finalize();
+
+    let f = world.frame();
+    let entities = world.get_entity_storage();
+    let parent = unsafe { (*entities).spawn(&f) }.unwrap();
+    let child = unsafe { (*entities).spawn(&f) }.unwrap();
+    world
+        .get_storage_mut::<ChildOf>()
+        .set_parent(&f, child.index(), parent);
+    world.run();
+
+    let link = unsafe { (*world.get_storage::<ChildOf>()).get(child.index()) };
+    assert_eq!(link.unwrap().parent, Some(parent));
+}
@@ -0,0 +1,113 @@
+use decs::ecs::Ecs;
+use decs::frame::Frame;
+use decs::system;
+use decs::tick::Tick;
+use decs::view::ViewMut;
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+/// 128 bytes without padding, of which a tick changes four.
+#[derive(Clone, Copy, Debug, PartialEq, Component)]
+struct Body {
+    words: [u32; 32],
+}
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Body>();
+    });
+}
+
+system!(DriftSystem { query
+    fn update(body: &mut ViewMut<Body>, frame: &Frame)
+    {
+        let slot = (frame.current_tick.0 as usize + body.index() as usize) % 32;
+        body.words[slot] += 1;
+    }
+});
+
+const ENTITIES: u32 = 100;
+
+fn world(delta: bool) -> World {
+    let mut world = World::new();
+    let f = world.frame();
+    let bodies = world.get_storage_mut::<Body>();
+    if delta {
+        unsafe { bodies.enable_delta_rollback() };
+    }
+    for i in 0..ENTITIES {
+        bodies.set(&f, i, Body { words: [i; 32] });
+    }
+    let system = DriftSystem::new(&mut world);
+    world.scheduler_mut().add_system(system);
+    world.scheduler_mut().build_wavefronts();
+    world
+}
+
+fn bodies(world: &mut World) -> Vec<Option<Body>> {
+    let storage = world.get_storage_mut::<Body>();
+    (0..ENTITIES).map(|i| storage.get(i).copied()).collect()
+}
+
+/// Runs a tick, then removes one body and brings back another within the same tick.
+fn step(world: &mut World) {
+    world.run();
+    let f = world.frame();
+    let t = f.current_tick.0;
+    let storage = world.get_storage_mut::<Body>();
+    storage.remove(&f, t * 7 % ENTITIES);
+    storage.set(&f, t * 13 % ENTITIES, Body { words: [t; 32] });
+}
+
+#[test]
+fn packed_history_rolls_back_like_plain_history() {
+    register_components_once();
+    let mut plain = world(false);
+    let mut delta = world(true);
+    let mut states = vec![(plain.current_tick(), bodies(&mut plain))];
+    for _ in 0..20 {
+        step(&mut plain);
+        step(&mut delta);
+        let state = bodies(&mut plain);
+        assert_eq!(bodies(&mut delta), state);
+        states.push((plain.current_tick(), state));
+    }
+
+    // Everything before the current tick is packed, far below the size of the values.
+    let storage = delta.get_storage_mut::<Body>();
+    assert!(storage.prev.iter().all(|rb| rb.is_packed()));
+    let mut stored = 0;
+    let mut packed_bytes = 0;
+    for rb in storage.prev.iter() {
+        let packed = rb.packed.as_ref().unwrap();
+        stored += packed
+            .chunks
+            .iter()
+            .map(|c| c.stored_mask().count_ones() as usize)
+            .sum::<usize>();
+        packed_bytes += packed.heap_size();
+    }
+    assert!(stored > 15 * ENTITIES as usize);
+    assert!(packed_bytes * 4 < stored * size_of::<Body>());
+
+    for target in [15, 9, 3] {
+        let (tick, expected) = &states[target];
+        plain.rollback(*tick);
+        delta.rollback(*tick);
+        assert_eq!(&bodies(&mut plain), expected);
+        assert_eq!(&bodies(&mut delta), expected);
+        assert!(delta.verify_invariants());
+    }
+
+    // Re-simulating on top of the restored history keeps both in step.
+    for _ in 0..5 {
+        step(&mut plain);
+        step(&mut delta);
+        assert_eq!(bodies(&mut delta), bodies(&mut plain));
+    }
+    let (tick, expected): &(Tick, _) = &states[5];
+    delta.rollback(*tick);
+    assert_eq!(&bodies(&mut delta), expected);
+}
@@ -0,0 +1,85 @@
+use decs::component::Destroyed;
+use decs::ecs::Ecs;
+use decs::entity::Entity;
+use decs::event::DespawnedEvent;
+use decs::frame::Frame;
+use decs::storage::Storage;
+use decs::system::System;
+use decs::world::World;
+use std::any::{Any, TypeId};
+use std::sync::{Arc, Mutex, Once};
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<DespawnedEvent>();
+    });
+}
+
+/// Simulation system logging the despawn events it sees, with the tick.
+struct RecordDespawns {
+    events: *const Storage<DespawnedEvent>,
+    reads: [TypeId; 1],
+    log: Arc<Mutex<Vec<(u32, Entity)>>>,
+}
+
+unsafe impl Send for RecordDespawns {}
+unsafe impl Sync for RecordDespawns {}
+
+impl System for RecordDespawns {
+    fn run(&self, frame: &Frame) {
+        let events = unsafe { &*self.events };
+        let mut log = self.log.lock().unwrap();
+        for event in decs::event::read(events) {
+            log.push((frame.current_tick.0, event.0));
+        }
+    }
+
+    fn reads(&self) -> &[TypeId] {
+        &self.reads
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[test]
+fn cleanup_sends_despawned_events_seen_next_tick() {
+    register_components_once();
+    let mut world = World::new();
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let recorder = RecordDespawns {
+        events: world.get_storage::<DespawnedEvent>(),
+        reads: [TypeId::of::<DespawnedEvent>()],
+        log: log.clone(),
+    };
+    world.scheduler_mut().add_system(recorder);
+    world.scheduler_mut().build_wavefronts();
+
+    let f = world.frame();
+    let entities = world.get_entity_storage();
+    let a = unsafe { (*entities).spawn(&f) }.unwrap();
+    let b = unsafe { (*entities).spawn(&f) }.unwrap();
+    let kept = unsafe { (*entities).spawn(&f) }.unwrap();
+    let destroyed = world.get_storage_mut::<Destroyed>();
+    destroyed.set(&f, a.index(), Destroyed());
+    destroyed.set(&f, b.index(), Destroyed());
+
+    // Tick 1: cleanup removes a and b and sends the events after simulation ran.
+    world.run();
+    let sent: Vec<DespawnedEvent> = world.events::<DespawnedEvent>().copied().collect();
+    assert_eq!(sent, [DespawnedEvent(a), DespawnedEvent(b)]);
+    assert!(unsafe { (*entities).get(a.index()) }.is_none());
+    assert!(unsafe { (*entities).get(kept.index()) }.is_some());
+    assert!(log.lock().unwrap().is_empty());
+
+    // Tick 2: simulation sees them once, then cleanup drops them.
+    world.run();
+    assert_eq!(*log.lock().unwrap(), [(2, a), (2, b)]);
+    assert_eq!(world.events::<DespawnedEvent>().count(), 0);
+
+    world.run();
+    assert_eq!(log.lock().unwrap().len(), 2);
+    assert!(world.verify_invariants());
+}
@@ -0,0 +1,128 @@
+use decs::component::{Component, Destroyed};
+use decs::ecs::Ecs;
+use decs::entity::Entity;
+use decs::hierarchy::{ChildOf, Parent, UpdateHierarchySystem};
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Position(i32, i32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Health(u32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Selected;
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Position>();
+        Ecs::register::<Health>();
+        Ecs::register::<Selected>();
+        Ecs::register::<ChildOf>();
+        Ecs::register::<Parent>();
+    });
+}
+
+fn world() -> World {
+    register_components_once();
+    let mut world = World::new();
+    let update = UpdateHierarchySystem::new(&mut world);
+    world.scheduler_mut().add_system(update);
+    world.finalize();
+    world
+}
+
+fn get<T: Component>(world: &mut World, index: u32) -> Option<T> {
+    world.get_storage_mut::<T>().get(index).cloned()
+}
+
+fn spawn(world: &mut World) -> Entity {
+    let f = world.frame();
+    unsafe { (*world.get_entity_storage()).spawn(&f) }.unwrap()
+}
+
+#[test]
+fn duplicate_clones_every_component() {
+    let mut world = world();
+    let src = spawn(&mut world);
+    let f = world.frame();
+    world
+        .get_storage_mut::<Position>()
+        .set(&f, src.index(), Position(3, 4));
+    world
+        .get_storage_mut::<Health>()
+        .set(&f, src.index(), Health(7));
+    world
+        .get_storage_mut::<Selected>()
+        .set(&f, src.index(), Selected);
+
+    let copy = world.duplicate_entity(&f, src).unwrap();
+    assert_ne!(copy, src);
+    assert_eq!(
+        get::<Position>(&mut world, copy.index()),
+        Some(Position(3, 4))
+    );
+    assert_eq!(get::<Health>(&mut world, copy.index()), Some(Health(7)));
+    assert!(get::<Selected>(&mut world, copy.index()).is_some());
+    // The copy is independent of its source.
+    world
+        .get_storage_mut::<Health>()
+        .set(&f, copy.index(), Health(1));
+    assert_eq!(get::<Health>(&mut world, src.index()), Some(Health(7)));
+
+    let partial = world
+        .duplicate_entity_except(&f, src, &[Selected::id(), Health::id()])
+        .unwrap();
+    assert!(get::<Position>(&mut world, partial.index()).is_some());
+    assert!(get::<Health>(&mut world, partial.index()).is_none());
+    assert!(get::<Selected>(&mut world, partial.index()).is_none());
+}
+
+#[test]
+fn duplicate_skips_dead_sources_and_destroyed_markers() {
+    let mut world = world();
+    let src = spawn(&mut world);
+    let f = world.frame();
+    world
+        .get_storage_mut::<Health>()
+        .set(&f, src.index(), Health(7));
+    world
+        .get_storage_mut::<Destroyed>()
+        .set(&f, src.index(), Destroyed());
+    let copy = world.duplicate_entity(&f, src).unwrap();
+    assert!(get::<Destroyed>(&mut world, copy.index()).is_none());
+
+    world.run();
+    let f = world.frame();
+    assert_eq!(world.duplicate_entity(&f, src), None);
+    assert_eq!(get::<Health>(&mut world, copy.index()), Some(Health(7)));
+}
+
+#[test]
+fn duplicated_child_joins_the_same_parent() {
+    let mut world = world();
+    let parent = spawn(&mut world);
+    let child = spawn(&mut world);
+    let f = world.frame();
+    world
+        .get_storage_mut::<ChildOf>()
+        .set_parent(&f, child.index(), parent);
+    world.run();
+
+    let f = world.frame();
+    let copy = world.duplicate_entity(&f, child).unwrap();
+    let parent_copy = world.duplicate_entity(&f, parent).unwrap();
+    assert!(get::<Parent>(&mut world, parent_copy.index()).is_none());
+    world.run();
+
+    let link = get::<ChildOf>(&mut world, copy.index()).unwrap();
+    assert_eq!(link.parent, Some(parent));
+    let link = get::<ChildOf>(&mut world, child.index()).unwrap();
+    assert_eq!(link.parent, Some(parent));
+    let children = get::<Parent>(&mut world, parent.index()).unwrap();
+    assert_eq!(children.first_child, child);
+    assert_eq!(children.last_child, copy);
+}
@@ -0,0 +1,154 @@
+use decs::ecs::Ecs;
+use decs::error::Error;
+use decs::rollback::MAX_HISTORY;
+use decs::system;
+use decs::system::EndOfTickSystem;
+use decs::tick::Tick;
+use decs::view::ViewMut;
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Counter(u32);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Counter>();
+    });
+}
+
+system!(CountSystem { query
+    fn update(counter: &mut ViewMut<Counter>)
+    {
+        counter.0 += 1;
+    }
+});
+
+fn counting_world() -> World {
+    let mut world = World::new();
+    let f = world.frame();
+    world.get_storage_mut::<Counter>().set(&f, 3, Counter(0));
+    let system = CountSystem::new(&mut world);
+    world.scheduler_mut().add_system(system);
+    world.scheduler_mut().build_wavefronts();
+    world
+}
+
+#[test]
+fn end_of_tick_runs_last_and_clears_changed_masks() {
+    register_components_once();
+    let mut world = counting_world();
+    // World::new schedules the Entity and Destroyed cleanups, ApplyCommandsSystem and
+    // then EndOfTickSystem (index 3), which gets the last wavefront to itself.
+    assert!(world.scheduler().get_system::<EndOfTickSystem>().is_some());
+    assert_eq!(world.scheduler().wavefronts().last(), Some(&vec![3]));
+
+    world.run();
+    let counters = world.get_storage_mut::<Counter>();
+    assert_eq!(counters.get(3), Some(&Counter(1)));
+    assert_eq!(counters.changed_mask, 0);
+    assert!(world.verify_invariants());
+}
+
+#[test]
+fn history_is_trimmed_to_the_configured_depth() {
+    register_components_once();
+    let mut world = counting_world();
+    assert_eq!(world.history_depth(), MAX_HISTORY);
+    world.set_history_depth(3);
+    for _ in 0..10 {
+        world.run();
+    }
+    let counters = world.get_storage_mut::<Counter>();
+    assert_eq!(counters.prev.len(), 3);
+    assert!(counters.rollback_pool.len() <= 3);
+    assert_eq!(counters.get(3), Some(&Counter(10)));
+
+    assert!(matches!(
+        world.rollback_to(Tick(5), 1),
+        Err(Error::HistoryExhausted { .. })
+    ));
+    world.rollback(Tick(8));
+    assert_eq!(world.get_storage_mut::<Counter>().get(3), Some(&Counter(8)));
+    assert!(world.verify_invariants());
+}
+
+#[test]
+fn rollback_restores_generation_saved_at_end_of_tick() {
+    register_components_once();
+    let mut world = counting_world();
+    let entities = world.get_entity_storage();
+    let spawn_next_tick = |world: &mut World| {
+        world.commands().push(move |frame| unsafe {
+            (*entities).spawn(frame).unwrap();
+        });
+        world.run();
+        world.current_tick()
+    };
+
+    let t1 = spawn_next_tick(&mut world);
+    spawn_next_tick(&mut world);
+    let undone = unsafe { (*entities).get(1) }.copied().unwrap();
+
+    // Re-simulating tick 2 after the rollback hands out the same entity again.
+    world.rollback(t1);
+    assert!(unsafe { (*entities).get(1) }.is_none());
+    spawn_next_tick(&mut world);
+    assert_eq!(unsafe { (*entities).get(1) }.copied(), Some(undone));
+}
+
+#[test]
+fn requested_compaction_frees_pooled_snapshots_at_end_of_tick() {
+    register_components_once();
+    let mut world = counting_world();
+    world.set_history_depth(2);
+    for _ in 0..5 {
+        world.run();
+    }
+    assert!(!world.get_storage_mut::<Counter>().rollback_pool.is_empty());
+
+    world.request_compaction();
+    world.run();
+    let counters = world.get_storage_mut::<Counter>();
+    assert!(counters.rollback_pool.is_empty());
+    assert_eq!(counters.prev.len(), 2);
+    assert_eq!(counters.get(3), Some(&Counter(6)));
+
+    // Only the requested tick compacts; history keeps working afterwards.
+    world.run();
+    world.run();
+    assert!(!world.get_storage_mut::<Counter>().rollback_pool.is_empty());
+    world.rollback(Tick(7));
+    assert_eq!(world.get_storage_mut::<Counter>().get(3), Some(&Counter(7)));
+    assert!(world.verify_invariants());
+}
+
+#[test]
+fn bare_world_schedules_the_built_in_systems() {
+    register_components_once();
+    let mut world = World::new();
+    assert_eq!(
+        world.scheduler().resolved_order(),
+        [
+            "decs::commands::ApplyCommandsSystem",
+            "decs::system::EntityCleanupSystem",
+            "decs::system::DestroyedCleanupSystem",
+            "decs::system::EndOfTickSystem",
+        ]
+    );
+    assert_eq!(world.scheduler().wavefronts().len(), 4);
+    world.run();
+    assert_eq!(world.current_tick(), Tick(1));
+
+    // Creating Counter's storage schedules its cleanup and drops the wavefronts; until
+    // they are built again `run` clears the changed masks itself, like EndOfTickSystem.
+    let f = world.frame();
+    world.get_storage_mut::<Counter>().set(&f, 3, Counter(0));
+    assert!(world.scheduler().wavefronts().is_empty());
+    world.run();
+    assert_eq!(world.current_tick(), Tick(2));
+    assert_eq!(world.get_storage_mut::<Counter>().changed_mask, 0);
+    assert!(world.verify_invariants());
+}
@@ -0,0 +1,107 @@
+use decs::component::{Component, Destroyed};
+use decs::ecs::Ecs;
+use decs::entity::Entity;
+use decs::frame::Frame;
+use decs::tick::Tick;
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Position(i32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Velocity(i32);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Position>();
+        Ecs::register::<Velocity>();
+    });
+}
+
+fn components_of(world: &World, index: u32) -> Vec<u32> {
+    world.components_of(index).collect()
+}
+
+#[test]
+fn signature_follows_set_remove_cleanup_and_rollback() {
+    register_components_once();
+    let mut world = World::new();
+    world.finalize();
+    let mut full = vec![Entity::id(), Position::id(), Velocity::id()];
+    full.sort_unstable();
+    let mut without_velocity = vec![Entity::id(), Position::id()];
+    without_velocity.sort_unstable();
+
+    world.set_tick(Tick(1));
+    let f = Frame::new(world.current_tick());
+    let index = world
+        .spawn_bundle(&f, (Position(1), Velocity(2)))
+        .unwrap()
+        .index();
+    assert_eq!(components_of(&world, index), full);
+    assert!(components_of(&world, index + 1).is_empty());
+
+    world.set_tick(Tick(2));
+    let f = Frame::new(world.current_tick());
+    world.get_storage_mut::<Velocity>().remove(&f, index);
+    assert_eq!(components_of(&world, index), without_velocity);
+
+    // Rollback restores the values and so the signature.
+    world.rollback(Tick(1));
+    assert_eq!(components_of(&world, index), full);
+    world.rollback(Tick(0));
+    assert!(components_of(&world, index).is_empty());
+
+    // Despawning goes through the cleanup systems.
+    let f = world.frame();
+    let index = world
+        .spawn_bundle(&f, (Position(1), Velocity(2)))
+        .unwrap()
+        .index();
+    world
+        .get_storage_mut::<Destroyed>()
+        .set(&f, index, Destroyed());
+    world.run();
+    assert!(components_of(&world, index).is_empty());
+}
+
+#[test]
+fn archetype_stats_bucket_spawned_entities_by_signature() {
+    register_components_once();
+    let mut world = World::new();
+    world.finalize();
+    let f = world.frame();
+    for i in 0..5 {
+        world.spawn_bundle(&f, (Position(i), Velocity(i))).unwrap();
+    }
+    for i in 0..3 {
+        world.spawn_bundle(&f, (Position(i),)).unwrap();
+    }
+    let bare = unsafe { (*world.get_entity_storage()).spawn(&f) }.unwrap();
+    // Values without a spawned entity are not counted.
+    world
+        .get_storage_mut::<Velocity>()
+        .set(&f, 4000, Velocity(0));
+
+    let stats = world.archetype_stats();
+    assert_eq!(stats.entity_count(), 9);
+    assert_eq!(stats.count_of(&[Velocity::id(), Position::id()]), 5);
+    assert_eq!(stats.count_of(&[Position::id()]), 3);
+    assert_eq!(stats.count_of(&[]), 1);
+    assert_eq!(stats.count_of(&[Velocity::id()]), 0);
+    let counts: Vec<u32> = stats.archetypes.iter().map(|a| a.count).collect();
+    assert_eq!(counts, [5, 3, 1]);
+    assert_eq!(
+        stats.archetypes[1].names,
+        [std::any::type_name::<Position>()]
+    );
+    assert!(stats.to_string().starts_with("5 x ["));
+
+    world
+        .get_storage_mut::<Position>()
+        .set(&f, bare.index(), Position(9));
+    assert_eq!(world.archetype_stats().count_of(&[Position::id()]), 4);
+}
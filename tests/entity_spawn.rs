@@ -70,3 +70,17 @@ fn spawn_respects_fullness_mask_across_page() {
     world.scheduler_mut().build_wavefronts();
     world.run();
 }
+
+#[test]
+fn try_spawn_reports_full_storage() {
+    let mut world = World::new();
+    let ent_storage = world.get_entity_storage();
+    let f = Frame::new(world.current_tick());
+    let storage = unsafe { &mut *ent_storage };
+    for _ in 0..64 * 64 * 64 {
+        assert!(storage.try_spawn(&f).is_ok());
+    }
+    assert_eq!(storage.try_spawn(&f), Err(decs::error::Error::StorageFull));
+    assert!(storage.spawn(&f).is_none());
+    assert!(world.verify_invariants());
+}
@@ -0,0 +1,118 @@
+use decs::component::Component;
+use decs::ecs::Ecs;
+use decs::system;
+use decs::tick::Tick;
+use decs::view::{View, ViewMut};
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Pos(i32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Vel(i32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+#[component(change_detection = false)]
+struct Tint(u8);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Pos>();
+        Ecs::register::<Vel>();
+        Ecs::register::<Tint>();
+    });
+}
+
+system!(MoveSystem { query
+    fn update(vel: View<Vel>, pos: &mut ViewMut<Pos>)
+    {
+        pos.0 += vel.0;
+    }
+});
+
+/// A simulation world with a moving entity at index 1 and a resting one at index 2.
+fn sim_world() -> World {
+    register_components_once();
+    let mut world = World::new();
+    let system = MoveSystem::new(&mut world);
+    world.scheduler_mut().add_system(system);
+    world.finalize();
+    let f = world.frame();
+    world.get_storage_mut::<Pos>().set(&f, 1, Pos(0));
+    world.get_storage_mut::<Vel>().set(&f, 1, Vel(2));
+    world.get_storage_mut::<Pos>().set(&f, 2, Pos(100));
+    world.get_storage_mut::<Tint>().set(&f, 2, Tint(7));
+    world
+}
+
+fn render_world() -> World {
+    register_components_once();
+    let mut world = World::new();
+    world.finalize();
+    world
+}
+
+fn pos(world: &mut World, index: u32) -> Option<i32> {
+    world.get_storage_mut::<Pos>().get(index).map(|pos| pos.0)
+}
+
+/// Change tick stamped on the `Pos` value at `index`.
+fn pos_stamp(world: &mut World, index: u32) -> Tick {
+    let storage = world.get_storage_mut::<Pos>();
+    let page = unsafe { &*storage.data[(index >> 12) as usize] };
+    let chunk = unsafe { &*page.data[((index >> 6) & 63) as usize] };
+    chunk.changed_ticks[(index & 63) as usize]
+}
+
+#[test]
+fn only_values_changed_since_the_last_extraction_are_copied() {
+    let mut sim = sim_world();
+    let mut render = render_world();
+    let types = [Pos::id(), Tint::id()];
+
+    sim.run();
+    sim.extract_changed_into(&mut render, &types);
+    assert_eq!(pos(&mut render, 1), Some(2));
+    assert_eq!(pos(&mut render, 2), Some(100));
+    assert_eq!(render.get_storage_mut::<Tint>().get(2), Some(&Tint(7)));
+    assert!(render.get_storage_mut::<Vel>().get(1).is_none());
+    let resting = pos_stamp(&mut render, 2);
+
+    sim.run();
+    sim.run();
+    sim.extract_changed_into(&mut render, &types);
+    assert_eq!(pos(&mut render, 1), Some(6));
+    assert_eq!(pos_stamp(&mut render, 2), resting);
+
+    // Removals are mirrored too.
+    let f = sim.frame();
+    sim.get_storage_mut::<Pos>().remove(&f, 2);
+    sim.get_storage_mut::<Tint>().remove(&f, 2);
+    sim.extract_changed_into(&mut render, &types);
+    assert_eq!(pos(&mut render, 2), None);
+    assert!(render.get_storage_mut::<Tint>().get(2).is_none());
+    assert_eq!(pos(&mut render, 1), Some(6));
+    assert!(render.verify_invariants());
+}
+
+#[test]
+fn values_restored_by_a_rollback_are_extracted() {
+    let mut sim = sim_world();
+    let mut render = render_world();
+    let types = [Pos::id()];
+
+    sim.run();
+    let before = sim.current_tick();
+    sim.run();
+    sim.run();
+    sim.extract_changed_into(&mut render, &types);
+    assert_eq!(pos(&mut render, 1), Some(6));
+
+    sim.rollback(before);
+    sim.extract_changed_into(&mut render, &types);
+    assert_eq!(pos(&mut render, 1), Some(2));
+    assert_eq!(pos(&mut render, 2), Some(100));
+}
@@ -0,0 +1,127 @@
+use decs::ecs::Ecs;
+use decs::fields::FieldSetter;
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+#[component(field_mask(Debug, PartialEq))]
+struct Transform {
+    position: (f32, f32),
+    rotation: f32,
+    uniform_scale: f32,
+}
+
+#[derive(Clone, Debug, PartialEq, Component)]
+#[component(field_mask)]
+struct Pair(u8, u8);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Transform>();
+        Ecs::register::<Pair>();
+    });
+}
+
+const IDENTITY: Transform = Transform {
+    position: (0.0, 0.0),
+    rotation: 0.0,
+    uniform_scale: 1.0,
+};
+
+#[test]
+fn setter_enums_follow_the_declared_fields() {
+    assert_eq!(
+        TransformField::NAMES,
+        ["position", "rotation", "uniform_scale"]
+    );
+    assert_eq!(TransformField::ALL, 0b111);
+    assert_eq!(TransformField::UniformScale(2.0).index(), 2);
+    assert_eq!(
+        TransformField::read(&IDENTITY, 0),
+        Some(TransformField::Position((0.0, 0.0)))
+    );
+    assert_eq!(TransformField::read(&IDENTITY, 3), None);
+
+    let mut pair = Pair(1, 2);
+    PairField::Field1(5).apply(&mut pair);
+    assert_eq!(pair, Pair(1, 5));
+    assert_eq!(PairField::NAMES, ["0", "1"]);
+}
+
+#[test]
+fn set_field_marks_only_the_written_field() {
+    register_components_once();
+    let mut world = World::new();
+    world.finalize();
+    let f = world.frame();
+    let before = f.change_tick();
+    f.advance_change_tick();
+    let storage = world.get_storage_mut::<Transform>();
+    storage.set(&f, 0, IDENTITY);
+    assert!(!storage.set_field(&f, 1, TransformField::Rotation(1.0)));
+
+    let set = f.change_tick();
+    f.advance_change_tick();
+    assert!(storage.set_field(&f, 0, TransformField::Rotation(1.0)));
+    assert_eq!(storage.get(0).unwrap().rotation, 1.0);
+    assert_eq!(
+        storage.changed_fields_since::<TransformField>(0, set),
+        0b010
+    );
+    // The value itself was written before that.
+    assert_eq!(
+        storage.changed_fields_since::<TransformField>(0, before),
+        0b111
+    );
+
+    storage.set_field(&f, 0, TransformField::Position((1.0, 2.0)));
+    assert_eq!(
+        storage.changed_fields_since::<TransformField>(0, set),
+        0b011
+    );
+
+    let rotated = f.change_tick();
+    f.advance_change_tick();
+    assert_eq!(
+        storage.changed_fields_since::<TransformField>(0, rotated),
+        0
+    );
+    storage.set_field(&f, 0, TransformField::UniformScale(2.0));
+    assert_eq!(
+        storage.changed_fields_since::<TransformField>(0, rotated),
+        0b100
+    );
+    assert_eq!(
+        storage.changed_fields_since::<TransformField>(0, set),
+        0b111
+    );
+
+    // Only the last two change ticks are kept field by field.
+    let scaled = f.change_tick();
+    f.advance_change_tick();
+    storage.set_field(&f, 0, TransformField::Rotation(2.0));
+    assert_eq!(
+        storage.changed_fields_since::<TransformField>(0, rotated),
+        0b110
+    );
+    assert_eq!(
+        storage.changed_fields_since::<TransformField>(0, set),
+        0b111
+    );
+
+    // Other writes may change any field.
+    let last = f.change_tick();
+    f.advance_change_tick();
+    storage.get_mut(&f, 0).unwrap().rotation = 3.0;
+    assert_eq!(
+        storage.changed_fields_since::<TransformField>(0, last),
+        0b111
+    );
+    assert_eq!(
+        storage.changed_fields_since::<TransformField>(0, scaled),
+        0b111
+    );
+    assert_eq!(storage.changed_fields_since::<TransformField>(1, before), 0);
+}
@@ -0,0 +1,74 @@
+#![feature(allocator_api)]
+
+use decs::ecs::Ecs;
+use decs::frame::Frame;
+use decs::system;
+use decs::view::ViewMut;
+use decs::world::World;
+use decs_macros::Component;
+use std::cell::RefCell;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Path(u32);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Path>();
+    });
+}
+
+thread_local! {
+    /// Address of every scratch buffer `PathSystem` allocated.
+    static BUFFERS: RefCell<Vec<usize>> = const { RefCell::new(Vec::new()) };
+}
+
+system!(PathSystem { query
+    fn update(frame: &Frame, path: &mut ViewMut<Path>)
+    {
+        let mut steps = Vec::with_capacity_in(8, frame.scratch());
+        steps.extend(0..=path.0);
+        BUFFERS.with(|buffers| buffers.borrow_mut().push(steps.as_ptr() as usize));
+        path.0 = steps.iter().sum();
+    }
+});
+
+fn take_buffers() -> Vec<usize> {
+    BUFFERS.with(|buffers| std::mem::take(&mut *buffers.borrow_mut()))
+}
+
+#[test]
+fn scratch_memory_is_reused_every_tick() {
+    register_components_once();
+    let mut world = World::new();
+    let system = PathSystem::new(&mut world);
+    world.scheduler_mut().add_system(system);
+    world.finalize();
+    let f = world.frame();
+    world.get_storage_mut::<Path>().set(&f, 0, Path(2));
+    world.get_storage_mut::<Path>().set(&f, 1, Path(3));
+
+    world.run();
+    let first = take_buffers();
+    assert_eq!(first.len(), 2);
+    assert_ne!(first[0], first[1]);
+    assert_eq!(world.get_storage_mut::<Path>().get(0), Some(&Path(3)));
+    assert_eq!(world.get_storage_mut::<Path>().get(1), Some(&Path(6)));
+
+    world.run();
+    assert_eq!(take_buffers(), first);
+    assert_eq!(world.get_storage_mut::<Path>().get(1), Some(&Path(21)));
+}
+
+#[test]
+fn frames_outside_run_own_their_scratch() {
+    let a = Frame::default();
+    let b = Frame::default();
+    let mut left = Vec::new_in(a.scratch());
+    let mut right = Vec::new_in(b.scratch());
+    left.extend([1u64, 2, 3]);
+    right.extend([4u64, 5]);
+    assert_eq!(left.iter().chain(&right).sum::<u64>(), 15);
+    assert_ne!(left.as_ptr(), right.as_ptr());
+}
@@ -0,0 +1,77 @@
+use decs::ecs::Ecs;
+use decs::frame::Frame;
+use decs::system; // for `system!`
+use decs::tick::Tick;
+use decs::view::{View, ViewMut};
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Position {
+    x: f32,
+}
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Velocity {
+    x: f32,
+}
+
+static INIT: Once = Once::new();
+fn register_components_once() {
+    INIT.call_once(|| {
+        Ecs::register::<Position>();
+        Ecs::register::<Velocity>();
+    });
+}
+
+system!(IntegrateSystem {
+    query fn update(frame: &Frame, pos: &mut ViewMut<Position>, vel: View<Velocity>) {
+        pos.x += vel.x * frame.fixed_dt;
+    }
+});
+
+#[test]
+fn frame_new_derives_elapsed_from_tick() {
+    let f = Frame::with_timing(Tick(30), 0.5, 0.25);
+    assert_eq!(f.fixed_dt, 0.5);
+    assert_eq!(f.elapsed, 15.0);
+    assert_eq!(f.alpha, 0.25);
+
+    let d = Frame::default();
+    assert_eq!(d.current_tick, Tick(0));
+    assert_eq!(d.fixed_dt, Frame::DEFAULT_FIXED_DT);
+    assert_eq!(d.alpha, 0.0);
+}
+
+#[test]
+fn update_runs_fixed_steps_and_keeps_remainder_as_alpha() {
+    register_components_once();
+    let mut world = World::new();
+    world.set_fixed_dt(0.25);
+    {
+        let f = world.frame();
+        world
+            .get_storage_mut::<Position>()
+            .set(&f, 3, Position { x: 0.0 });
+        world
+            .get_storage_mut::<Velocity>()
+            .set(&f, 3, Velocity { x: 2.0 });
+    }
+    let sys = IntegrateSystem::new(&mut world);
+    world.scheduler_mut().add_system(sys);
+    world.scheduler_mut().build_wavefronts();
+
+    assert_eq!(world.update(0.625), 2);
+    assert_eq!(world.current_tick(), Tick(2));
+    assert_eq!(world.alpha(), 0.5);
+    let x = unsafe { (*world.get_storage::<Position>()).get(3).unwrap().x };
+    assert_eq!(x, 1.0);
+
+    // The carried remainder completes a third step.
+    assert_eq!(world.update(0.125), 1);
+    assert_eq!(world.alpha(), 0.0);
+    let frame = world.frame();
+    assert_eq!(frame.current_tick, Tick(3));
+    assert_eq!(frame.elapsed, 0.75);
+}
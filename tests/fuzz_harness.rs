@@ -0,0 +1,110 @@
+use decs::ecs::Ecs;
+use decs::rollback::SnapshotPolicy;
+use decs::testing::{FuzzConfig, fuzz_component};
+use decs_macros::Component;
+use std::sync::Once;
+use std::sync::atomic::{AtomicIsize, Ordering};
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Small {
+    v: u32,
+}
+
+static LIVE_TRACKED: AtomicIsize = AtomicIsize::new(0);
+
+/// Heap-owning component that counts live instances to catch double drops and leaks.
+#[derive(Debug, PartialEq, Component)]
+struct Tracked {
+    name: String,
+    data: Vec<u64>,
+}
+
+impl Tracked {
+    fn new(v: u64) -> Self {
+        LIVE_TRACKED.fetch_add(1, Ordering::SeqCst);
+        Self {
+            name: format!("t{}", v),
+            data: vec![v; (v % 5) as usize],
+        }
+    }
+}
+
+impl Clone for Tracked {
+    fn clone(&self) -> Self {
+        LIVE_TRACKED.fetch_add(1, Ordering::SeqCst);
+        Self {
+            name: self.name.clone(),
+            data: self.data.clone(),
+        }
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        LIVE_TRACKED.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+static INIT: Once = Once::new();
+fn register_components_once() {
+    INIT.call_once(|| {
+        Ecs::register::<Small>();
+        Ecs::register::<Tracked>();
+    });
+}
+
+#[test]
+fn fuzz_small_component_across_seeds() {
+    register_components_once();
+    for seed in 1..=4u64 {
+        let config = FuzzConfig {
+            seed,
+            ticks: 120,
+            ..FuzzConfig::default()
+        };
+        let stats = fuzz_component(&config, |rng| Small {
+            v: rng.next_u64() as u32,
+        });
+        assert!(stats.sets > 0 && stats.removes > 0 && stats.rollbacks > 0);
+    }
+}
+
+#[test]
+fn fuzz_drop_heavy_component_balances_clones_and_drops() {
+    register_components_once();
+    for snapshot_policy in [SnapshotPolicy::PerItem, SnapshotPolicy::WholeChunk] {
+        let config = FuzzConfig {
+            seed: 0xD20,
+            ticks: 150,
+            rollback_percent: 25,
+            snapshot_policy,
+            ..FuzzConfig::default()
+        };
+        let stats = fuzz_component(&config, |rng| Tracked::new(rng.next_u64() % 1000));
+        assert!(stats.rollbacks > 0);
+        assert_eq!(LIVE_TRACKED.load(Ordering::SeqCst), 0);
+    }
+}
+
+#[test]
+fn fuzz_chunk_snapshot_policies_on_dense_chunks() {
+    register_components_once();
+    for snapshot_policy in [
+        SnapshotPolicy::WholeChunk,
+        SnapshotPolicy::Adaptive { min_fill: 32 },
+    ] {
+        for seed in 1..=3u64 {
+            let config = FuzzConfig {
+                seed,
+                ticks: 120,
+                index_range: 256,
+                snapshot_policy,
+                ..FuzzConfig::default()
+            };
+            let stats = fuzz_component(&config, |rng| Small {
+                v: rng.next_u64() as u32,
+            });
+            assert!(stats.sets > 0 && stats.removes > 0 && stats.rollbacks > 0);
+        }
+    }
+}
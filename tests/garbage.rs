@@ -0,0 +1,81 @@
+use decs::component::Destroyed;
+use decs::ecs::Ecs;
+use decs::error::Error;
+use decs::gc::GarbageReport;
+use decs::tick::Tick;
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Hp(u32);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Hp>();
+    });
+}
+
+/// Runs `ticks` ticks, each writing a new `Hp` at index 1.
+fn run_writes(world: &mut World, ticks: u32) {
+    for _ in 0..ticks {
+        let f = world.frame();
+        let value = Hp(world.current_tick().0);
+        world.get_storage_mut::<Hp>().set(&f, 1, value);
+        world.run();
+    }
+}
+
+#[test]
+fn confirmed_history_and_pooled_snapshots_are_collected_within_budget() {
+    register_components_once();
+    let mut world = World::new();
+    world.finalize();
+    run_writes(&mut world, 8);
+    assert_eq!(world.pending_garbage(), GarbageReport::default());
+
+    // Rolling back pools the snapshots of the undone ticks.
+    world.confirm_tick(Tick(5));
+    world.rollback(Tick(6));
+    let pending = world.pending_garbage();
+    assert!(pending.confirmed_snapshots >= 5, "{:?}", pending);
+    assert!(pending.pooled_snapshots >= 1, "{:?}", pending);
+
+    assert_eq!(world.collect_garbage(3), 3);
+    let after = world.pending_garbage();
+    assert_eq!(after.collectable(), pending.collectable() - 3);
+    assert_eq!(after.pooled_snapshots, pending.pooled_snapshots);
+
+    assert_eq!(world.collect_garbage(usize::MAX), after.collectable());
+    assert!(world.pending_garbage().is_empty());
+
+    // The confirmed tick is still reachable, older ticks no longer.
+    assert_eq!(
+        world.try_rollback(Tick(4)),
+        Err(Error::HistoryExhausted {
+            target: Tick(4),
+            oldest: Tick(5)
+        })
+    );
+    world.try_rollback(Tick(5)).unwrap();
+    assert_eq!(world.get_storage_mut::<Hp>().get(1), Some(&Hp(5)));
+    assert!(world.verify_invariants());
+}
+
+#[test]
+fn destroyed_entities_count_until_cleaned_up() {
+    register_components_once();
+    let mut world = World::new();
+    world.finalize();
+    let f = world.frame();
+    let entity = unsafe { (*world.get_entity_storage()).spawn(&f) }.unwrap();
+    world
+        .get_storage_mut::<Destroyed>()
+        .set(&f, entity.index(), Destroyed());
+    assert_eq!(world.pending_garbage().destroyed_entities, 1);
+    assert_eq!(world.collect_garbage(10), 0);
+
+    world.run();
+    assert_eq!(world.pending_garbage().destroyed_entities, 0);
+}
@@ -0,0 +1,115 @@
+#![allow(non_upper_case_globals)]
+
+use decs::ecs::Ecs;
+use decs::system;
+use decs::view::{View, ViewMut};
+use decs::world::{SimulationGroup, World};
+use decs_macros::Component;
+use std::cell::RefCell;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Position(i32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Velocity(i32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Volume(i32);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Position>();
+        Ecs::register::<Velocity>();
+        Ecs::register::<Volume>();
+    });
+}
+
+thread_local! {
+    static LOG: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+fn log(entry: String) {
+    LOG.with(|log| log.borrow_mut().push(entry));
+}
+
+fn take_log() -> Vec<String> {
+    LOG.with(|log| log.take())
+}
+
+decs_macros::system_group!(PhysicsGroup { Parent=SimulationGroup });
+
+system!(IntegrateSystem {
+    query fn update(velocity: View<Velocity>, position: &mut ViewMut<Position>) {
+        position.0 += velocity.0;
+        log(format!("integrate {}", position.0));
+    }
+    Parent=[PhysicsGroup]
+});
+
+// Reads what physics writes, so it runs after the whole group.
+system!(MixSystem {
+    query fn update(position: View<Position>, volume: &mut ViewMut<Volume>) {
+        volume.0 = position.0;
+        log(format!("mix {}", volume.0));
+    }
+    Parent=[SimulationGroup]
+});
+
+fn world() -> World {
+    register_components_once();
+    let mut world = World::new();
+    let integrate = IntegrateSystem::new(&mut world);
+    let mix = MixSystem::new(&mut world);
+    world.scheduler_mut().add_system(mix);
+    world.scheduler_mut().add_system(integrate);
+    world.scheduler_mut().on_group_completed(|group, frame| {
+        let name = if group.is::<PhysicsGroup>() {
+            "physics"
+        } else if group.is::<SimulationGroup>() {
+            "simulation"
+        } else {
+            return;
+        };
+        log(format!("{} done in {:?}", name, frame.current_tick));
+    });
+    world.finalize();
+    let f = world.frame();
+    world.get_storage_mut::<Position>().set(&f, 0, Position(0));
+    world.get_storage_mut::<Velocity>().set(&f, 0, Velocity(2));
+    world.get_storage_mut::<Volume>().set(&f, 0, Volume(0));
+    world
+}
+
+#[test]
+fn groups_complete_after_their_last_system() {
+    let mut world = world();
+    take_log();
+    world.run();
+    world.run();
+    assert_eq!(
+        take_log(),
+        [
+            "integrate 2",
+            "physics done in Tick(1)",
+            "mix 2",
+            "simulation done in Tick(1)",
+            "integrate 4",
+            "physics done in Tick(2)",
+            "mix 4",
+            "simulation done in Tick(2)",
+        ]
+    );
+}
+
+#[test]
+fn disabled_groups_do_not_complete() {
+    let mut world = world();
+    world
+        .scheduler_mut()
+        .set_group_enabled::<PhysicsGroup>(false);
+    take_log();
+    world.run();
+    assert_eq!(take_log(), ["mix 0", "simulation done in Tick(1)"]);
+}
@@ -0,0 +1,80 @@
+use decs::bitmap::HierBitmap;
+use decs::ecs::Ecs;
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Position(i32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Velocity(i32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Frozen;
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Position>();
+        Ecs::register::<Velocity>();
+        Ecs::register::<Frozen>();
+    });
+}
+
+#[test]
+fn presence_bitmaps_compose_into_a_join() {
+    register_components_once();
+    let mut world = World::new();
+    let f = world.frame();
+    // Spread over several chunks and pages.
+    let indices = [1, 63, 64, 700, 4095, 4096, 9000, 262_143];
+    for (i, &index) in indices.iter().enumerate() {
+        world
+            .get_storage_mut::<Position>()
+            .set(&f, index, Position(0));
+        if i % 2 == 0 {
+            world
+                .get_storage_mut::<Velocity>()
+                .set(&f, index, Velocity(1));
+        }
+        if i % 3 == 0 {
+            world.get_storage_mut::<Frozen>().set(&f, index, Frozen);
+        }
+    }
+
+    let positions = world.get_storage_mut::<Position>().presence_bitmap();
+    let velocities = world.get_storage_mut::<Velocity>().presence_bitmap();
+    let frozen = world.get_storage_mut::<Frozen>().presence_bitmap();
+    assert_eq!(positions.iter().collect::<Vec<_>>(), indices);
+    assert_eq!(positions.len(), indices.len());
+
+    let moving = (&positions & &velocities).and_not(&frozen);
+    assert_eq!(moving.iter().collect::<Vec<_>>(), [64, 4095]);
+    assert_eq!(moving, &(&positions & &velocities) & &!&frozen);
+
+    let either = &velocities | &frozen;
+    assert_eq!(either.iter().collect::<Vec<_>>(), [1, 64, 700, 4095, 9000]);
+
+    // A bitmap is a copy; removing the value leaves it as it was.
+    world.get_storage_mut::<Position>().remove(&f, 64);
+    assert!(positions.contains(64));
+    assert!(
+        !world
+            .get_storage_mut::<Position>()
+            .presence_bitmap()
+            .contains(64)
+    );
+}
+
+#[test]
+fn complement_covers_every_other_index() {
+    let bitmap: HierBitmap = [0, 5, 4096].into_iter().collect();
+    let rest = !&bitmap;
+    assert_eq!(rest.len(), HierBitmap::CAPACITY as usize - 3);
+    assert!(!rest.contains(5) && rest.contains(6));
+    assert_eq!(rest.iter().take(3).collect::<Vec<_>>(), [1, 2, 3]);
+    assert!((&bitmap & &rest).is_empty());
+    assert_eq!(!rest, bitmap);
+    assert!(HierBitmap::new().is_empty());
+}
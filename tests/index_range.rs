@@ -0,0 +1,113 @@
+use decs::ecs::Ecs;
+use decs::system;
+use decs::system::{IndexRange, QueryMismatch, System};
+use decs::view::{View, ViewMut};
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Counter(u32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Boost(u32);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Counter>();
+        Ecs::register::<Boost>();
+    });
+}
+
+system!(CountSystem { query
+    fn update(counter: &mut ViewMut<Counter>)
+    {
+        counter.0 += 1;
+    }
+});
+
+system!(BoostSystem { query
+    fn update(boost: View<Boost>, counter: &mut ViewMut<Counter>)
+    {
+        counter.0 += boost.0;
+    }
+    Changed=[Boost]
+});
+
+/// Indices on both sides of chunk, page and storage-index boundaries.
+const INDICES: [u32; 7] = [0, 63, 64, 4095, 4096, 4160, 70000];
+
+fn world_with<S: System>(new: fn(&mut World) -> S) -> World {
+    register_components_once();
+    let mut world = World::new();
+    let system = new(&mut world);
+    world.scheduler_mut().add_system(system);
+    world.finalize();
+    let f = world.frame();
+    for index in INDICES {
+        world
+            .get_storage_mut::<Counter>()
+            .set(&f, index, Counter(0));
+        world.get_storage_mut::<Boost>().set(&f, index, Boost(10));
+    }
+    world
+}
+
+fn counters(world: &mut World) -> Vec<u32> {
+    let storage = world.get_storage_mut::<Counter>();
+    INDICES.iter().map(|&i| storage.get(i).unwrap().0).collect()
+}
+
+#[test]
+fn index_range_masks_cover_exactly_the_range() {
+    let range = IndexRange::new(64..4097);
+    assert_eq!(range.storage_mask(), 0b11);
+    assert_eq!(range.page_mask(0), u64::MAX << 1);
+    assert_eq!(range.page_mask(1), 1);
+    assert_eq!(range.page_mask(2), 0);
+    assert_eq!(range.chunk_mask(1, 0), 1);
+    assert_eq!(range.chunk_mask(0, 1), u64::MAX);
+    let (start, end) = (5, 3);
+    let reversed = IndexRange::new(start..end);
+    assert_eq!(reversed.range(), 3..3);
+    assert_eq!(reversed.storage_mask(), 0);
+    assert_eq!(IndexRange::new(0..u32::MAX), IndexRange::ALL);
+    assert_eq!(IndexRange::from_bits(range.to_bits()), range);
+    assert_eq!(
+        IndexRange::from_bits(IndexRange::ALL.to_bits()),
+        IndexRange::ALL
+    );
+}
+
+#[test]
+fn systems_only_iterate_their_index_range() {
+    let mut world = world_with(CountSystem::new);
+    let system = world.scheduler().get_system::<CountSystem>().unwrap();
+    assert_eq!(system.index_range(), 0..64 * 64 * 64);
+    system.set_index_range(64..4097);
+    world.run();
+    assert_eq!(counters(&mut world), [0, 0, 1, 1, 1, 0, 0]);
+
+    // Two halves over consecutive ticks cover every index once.
+    let system = world.scheduler().get_system::<CountSystem>().unwrap();
+    system.set_index_range(0..4096);
+    world.run();
+    let system = world.scheduler().get_system::<CountSystem>().unwrap();
+    system.set_index_range(4096..u32::MAX);
+    world.run();
+    assert_eq!(counters(&mut world), [1, 1, 2, 2, 2, 1, 1]);
+
+    let system = world.scheduler().get_system::<CountSystem>().unwrap();
+    assert_eq!(system.explain(0), [QueryMismatch::OutOfRange]);
+    assert!(system.explain(4096).is_empty());
+}
+
+#[test]
+fn changed_filtered_systems_respect_the_index_range() {
+    let mut world = world_with(BoostSystem::new);
+    let system = world.scheduler().get_system::<BoostSystem>().unwrap();
+    system.set_index_range(4000..5000);
+    world.run();
+    assert_eq!(counters(&mut world), [0, 0, 0, 10, 10, 10, 0]);
+}
@@ -0,0 +1,114 @@
+use decs::ecs::Ecs;
+use decs::island::{Island, Islands};
+use decs::net::world_checksum;
+use decs::system;
+use decs::view::{View, ViewMut};
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+#[component(diff)]
+struct Position(i32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Velocity(i32);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Position>();
+        Ecs::register::<Velocity>();
+        Ecs::register::<Island>();
+    });
+}
+
+system!(MoveSystem { query
+    fn update(pos: &mut ViewMut<Position>, vel: View<Velocity>)
+    {
+        pos.0 += vel.0;
+    }
+});
+
+fn islands() -> Islands {
+    Islands::new(|world, _| {
+        let system = MoveSystem::new(world);
+        world.scheduler_mut().add_system(system);
+    })
+}
+
+/// Spawns an untagged bystander and six movers split between islands 1 and 2, then
+/// moves the movers out of the lobby world.
+fn populate(islands: &mut Islands) -> (World, Vec<u32>) {
+    let mut lobby = World::new();
+    let f = lobby.frame();
+    lobby.spawn_bundle(&f, (Position(0),)).unwrap();
+    let indices = (0..6)
+        .map(|i| {
+            let island = Island(1 + i % 2);
+            let bundle = (Position(i as i32 * 100), Velocity(i as i32 + 1), island);
+            lobby.spawn_bundle(&f, bundle).unwrap().index()
+        })
+        .collect();
+    assert_eq!(islands.split_from(&mut lobby), 6);
+    (lobby, indices)
+}
+
+fn position(world: &mut World, index: u32) -> Option<i32> {
+    world.get_storage_mut::<Position>().get(index).map(|p| p.0)
+}
+
+#[test]
+fn split_moves_tagged_entities_keeping_their_index() {
+    register_components_once();
+    let mut islands = islands();
+    let (mut lobby, indices) = populate(&mut islands);
+    assert_eq!(islands.ids().collect::<Vec<_>>(), [1, 2]);
+
+    // Only the untagged bystander stays in the lobby.
+    assert_eq!(position(&mut lobby, 0), Some(0));
+    for &index in &indices {
+        assert!(unsafe { (*lobby.get_entity_storage()).get(index) }.is_none());
+        assert_eq!(position(&mut lobby, index), None);
+    }
+
+    let island_one = islands.world_mut(1);
+    let entity = unsafe { (*island_one.get_entity_storage()).get(indices[0]) };
+    assert_eq!(entity.map(|e| e.index()), Some(indices[0]));
+    assert_eq!(position(island_one, indices[0]), Some(0));
+    assert_eq!(position(island_one, indices[1]), None);
+    assert_eq!(position(islands.world_mut(2), indices[1]), Some(100));
+
+    // Each island only advances its own members.
+    islands.run();
+    islands.run();
+    assert_eq!(position(islands.world_mut(1), indices[2]), Some(206));
+    assert_eq!(position(islands.world_mut(2), indices[3]), Some(308));
+    assert!(islands.world(1).unwrap().verify_invariants());
+    assert!(lobby.verify_invariants());
+
+    // Splitting again finds nothing left to move.
+    assert_eq!(islands.split_from(&mut lobby), 0);
+    assert!(islands.remove(2).is_some());
+    assert_eq!(islands.len(), 1);
+}
+
+#[test]
+fn parallel_run_matches_sequential_run() {
+    register_components_once();
+    let mut sequential = islands();
+    let mut parallel = islands();
+    populate(&mut sequential);
+    populate(&mut parallel);
+
+    for _ in 0..5 {
+        sequential.run();
+        unsafe { parallel.run_parallel() };
+    }
+    for id in [1, 2] {
+        let a = sequential.world(id).unwrap();
+        let b = parallel.world(id).unwrap();
+        assert_eq!(a.current_tick(), b.current_tick());
+        assert_eq!(world_checksum(a), world_checksum(b));
+    }
+}
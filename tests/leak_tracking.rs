@@ -0,0 +1,51 @@
+#![cfg(feature = "leak-tracking")]
+
+use decs::ecs::Ecs;
+use decs::leak::{LiveAllocations, live_allocations};
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Soak {
+    v: u32,
+}
+
+static INIT: Once = Once::new();
+fn register_components_once() {
+    INIT.call_once(|| {
+        Ecs::register::<Soak>();
+    });
+}
+
+#[test]
+fn soak_rollback_recycling_returns_to_zero() {
+    register_components_once();
+    let mut peak = LiveAllocations::default();
+    {
+        let mut world = World::new();
+        for round in 0..200u32 {
+            world.advance_tick();
+            let frame = world.frame();
+            let s = world.get_storage_mut::<Soak>();
+            for i in 0..300u32 {
+                let index = (i * 97 + round * 13) % 20_000;
+                if (i + round) % 3 == 0 {
+                    s.remove(&frame, index);
+                } else {
+                    s.set(&frame, index, Soak { v: round });
+                }
+            }
+            if round % 25 == 24 {
+                let target = world.current_tick().wrapping_sub(5);
+                world.rollback(target);
+            }
+            let live = world.leak_report().get::<Soak>().unwrap();
+            peak.rollback_snapshots = peak.rollback_snapshots.max(live.rollback_snapshots);
+        }
+        // Snapshots stay bounded by the history limit plus the current one and the pool.
+        assert!(peak.rollback_snapshots <= 64 + 1 + 64);
+        assert!(world.leak_report().to_string().contains("Soak"));
+    }
+    assert_eq!(live_allocations::<Soak>(), LiveAllocations::default());
+}
@@ -0,0 +1,99 @@
+use decs::ecs::Ecs;
+use decs::net::{DivergenceKind, world_checksum};
+use decs::system;
+use decs::testing::{FuzzRng, LockstepConfig, lockstep, try_lockstep};
+use decs::tick::Tick;
+use decs::view::{View, ViewMut};
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+#[component(diff)]
+struct Position(i32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Velocity(i32);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Position>();
+        Ecs::register::<Velocity>();
+    });
+}
+
+system!(MoveSystem { query
+    fn update(pos: &mut ViewMut<Position>, vel: View<Velocity>)
+    {
+        pos.0 += vel.0;
+    }
+});
+
+fn setup(world: &mut World, seed: u64) {
+    let mut rng = FuzzRng::new(seed);
+    let f = world.frame();
+    for i in 0..100 {
+        world
+            .get_storage_mut::<Position>()
+            .set(&f, i, Position(rng.below(1000) as i32));
+        world
+            .get_storage_mut::<Velocity>()
+            .set(&f, i, Velocity(rng.below(7) as i32 - 3));
+    }
+    let system = MoveSystem::new(world);
+    world.scheduler_mut().add_system(system);
+    world.scheduler_mut().build_wavefronts();
+}
+
+/// Adds a unit at index 200 on every 10th tick.
+fn spawn_input(world: &mut World, tick: Tick) {
+    if tick.0.is_multiple_of(10) {
+        let f = world.frame();
+        world
+            .get_storage_mut::<Position>()
+            .set(&f, 200, Position(tick.0 as i32));
+    }
+}
+
+#[test]
+fn identical_worlds_stay_in_lockstep() {
+    register_components_once();
+    let config = LockstepConfig { seed: 7, ticks: 50 };
+    let checksums = lockstep(&config, setup, spawn_input);
+    assert_eq!(checksums.len(), 50);
+    assert!(checksums.windows(2).any(|w| w[0] != w[1]));
+
+    // Checksums are reproducible from the seed alone.
+    assert_eq!(lockstep(&config, setup, spawn_input), checksums);
+    let other = LockstepConfig { seed: 8, ..config };
+    assert_ne!(lockstep(&other, setup, spawn_input), checksums);
+    let mut world = World::new();
+    setup(&mut world, 7);
+    world.run();
+    assert_eq!(world_checksum(&world), checksums[0]);
+}
+
+#[test]
+fn divergent_input_is_reported_at_its_tick() {
+    register_components_once();
+    let config = LockstepConfig { seed: 7, ticks: 50 };
+    // Inputs alternate between the two worlds; only the first world sees the 21st call
+    // (tick 11).
+    let mut calls = 0;
+    let divergence = try_lockstep(&config, setup, |world, tick| {
+        calls += 1;
+        if calls == 21 {
+            let f = world.frame();
+            world.get_storage_mut::<Position>().set(&f, 5, Position(-1));
+        }
+        spawn_input(world, tick);
+    })
+    .unwrap_err();
+
+    assert_eq!(divergence.tick, Tick(11));
+    assert_ne!(divergence.checksums.0, divergence.checksums.1);
+    let d = &divergence.report.divergences[0];
+    assert_eq!((d.index, d.kind), (5, DivergenceKind::ValueMismatch));
+    assert!(divergence.to_string().contains("diverged after"));
+}
@@ -0,0 +1,112 @@
+use decs::ecs::Ecs;
+use decs::entity::Entity;
+use decs::system;
+use decs::system::System;
+use decs::view::{Lookup, View, ViewMut};
+use decs::world::World;
+use decs_macros::Component;
+use std::any::TypeId;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Health(i32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Target(Entity);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Health>();
+        Ecs::register::<Target>();
+    });
+}
+
+system!(AttackSystem { query
+    fn update(target: View<Target>, health: &mut Lookup<Health>)
+    {
+        if let Some(hp) = health.get_mut(target.0) {
+            hp.0 -= 3;
+        }
+    }
+});
+
+system!(LeechSystem { query
+    fn update(hp: &mut ViewMut<Health>, target: View<Target>, health: &Lookup<Health>)
+    {
+        // Its own health is reachable through `hp` only.
+        if let Some(other) = health.get(target.0) {
+            hp.0 += other.0 / 10;
+        }
+    }
+});
+
+fn spawn(world: &mut World, health: Option<i32>) -> Entity {
+    let f = world.frame();
+    let entity = unsafe { (*world.get_entity_storage()).spawn(&f) }.unwrap();
+    if let Some(hp) = health {
+        world
+            .get_storage_mut::<Health>()
+            .set(&f, entity.index(), Health(hp));
+    }
+    entity
+}
+
+fn health(world: &mut World, entity: Entity) -> Option<i32> {
+    world
+        .get_storage_mut::<Health>()
+        .get(entity.index())
+        .map(|h| h.0)
+}
+
+#[test]
+fn lookup_follows_live_handles_only() {
+    register_components_once();
+    let mut world = World::new();
+    let boss = spawn(&mut world, Some(100));
+    let attacker = spawn(&mut world, None);
+    let confused = spawn(&mut world, None);
+    let f = world.frame();
+    let targets = world.get_storage_mut::<Target>();
+    targets.set(&f, attacker.index(), Target(boss));
+    // Same index, older generation: must not resolve.
+    let stale = Entity::new(boss.index(), boss.generation() + 1);
+    targets.set(&f, confused.index(), Target(stale));
+
+    let system = AttackSystem::new(&mut world);
+    assert!(system.writes().contains(&TypeId::of::<Health>()));
+    assert!(system.reads().contains(&TypeId::of::<Entity>()));
+    world.scheduler_mut().add_system(system);
+    world.scheduler_mut().build_wavefronts();
+
+    let before = world.current_tick();
+    world.run();
+    world.run();
+    assert_eq!(health(&mut world, boss), Some(94));
+    assert!(world.verify_invariants());
+
+    // Writes through the lookup are recorded for rollback.
+    world.rollback(before);
+    assert_eq!(health(&mut world, boss), Some(100));
+}
+
+#[test]
+fn lookup_skips_the_entity_viewed_mutably() {
+    register_components_once();
+    let mut world = World::new();
+    let vampire = spawn(&mut world, Some(10));
+    let victim = spawn(&mut world, Some(50));
+    let narcissist = spawn(&mut world, Some(20));
+    let f = world.frame();
+    let targets = world.get_storage_mut::<Target>();
+    targets.set(&f, vampire.index(), Target(victim));
+    targets.set(&f, narcissist.index(), Target(narcissist));
+
+    let system = LeechSystem::new(&mut world);
+    world.scheduler_mut().add_system(system);
+    world.scheduler_mut().build_wavefronts();
+    world.run();
+    assert_eq!(health(&mut world, vampire), Some(15));
+    assert_eq!(health(&mut world, victim), Some(50));
+    assert_eq!(health(&mut world, narcissist), Some(20));
+}
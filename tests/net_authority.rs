@@ -0,0 +1,99 @@
+use decs::ecs::Ecs;
+use decs::net::{self, Authority, Owned, Remote};
+use decs::system;
+use decs::view::ViewMut;
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Position {
+    x: f32,
+}
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Position>();
+        Ecs::register::<Authority>();
+        Ecs::register::<Owned>();
+        Ecs::register::<Remote>();
+    });
+}
+
+system!(Predict { query
+    fn update(pos: &mut ViewMut<Position>)
+    {
+        pos.x += 1.0;
+    }
+    All=[Owned]
+});
+
+system!(Interpolate { query
+    fn update(pos: &mut ViewMut<Position>)
+    {
+        pos.x += 100.0;
+    }
+    All=[Remote]
+});
+
+const LOCAL: u32 = 1;
+
+#[test]
+fn owned_and_remote_filters_split_simulation() {
+    register_components_once();
+    let mut world = World::new();
+    let f = world.frame();
+    for i in 0..6u32 {
+        world
+            .get_storage_mut::<Position>()
+            .set(&f, i, Position { x: 0.0 });
+        net::set_authority(&mut world, i, i % 3, LOCAL);
+    }
+    net::clear_authority(&mut world, 5);
+
+    let predict = Predict::new(&mut world);
+    let interpolate = Interpolate::new(&mut world);
+    world.scheduler_mut().add_system(predict);
+    world.scheduler_mut().add_system(interpolate);
+    world.scheduler_mut().build_wavefronts();
+    world.run();
+
+    let xs: Vec<f32> = (0..6)
+        .map(|i| world.get_storage_mut::<Position>().get(i).unwrap().x)
+        .collect();
+    assert_eq!(xs, vec![100.0, 1.0, 100.0, 100.0, 1.0, 0.0]);
+    assert!(net::is_owned(&mut world, 4));
+    assert!(!net::is_owned(&mut world, 3));
+    assert!(world.verify_invariants());
+}
+
+#[test]
+fn ownership_follows_local_peer_and_rollback() {
+    register_components_once();
+    let mut world = World::new();
+    let t1 = world.advance_tick();
+    for i in 0..4u32 {
+        net::set_authority(&mut world, i, i % 2, LOCAL);
+    }
+    assert!(net::is_owned(&mut world, 1));
+
+    // Authority transfer in a later tick is undone by rollback.
+    world.advance_tick();
+    net::set_authority(&mut world, 1, 0, LOCAL);
+    assert!(!net::is_owned(&mut world, 1));
+    world.rollback(t1);
+    assert!(net::is_owned(&mut world, 1));
+    assert!(world.get_storage_mut::<Remote>().get(1).is_none());
+
+    // Becoming peer 0 flips every marker.
+    world.advance_tick();
+    net::refresh_ownership(&mut world, 0);
+    for i in 0..4u32 {
+        assert_eq!(net::is_owned(&mut world, i), i % 2 == 0);
+        assert_eq!(
+            world.get_storage_mut::<Remote>().get(i).is_some(),
+            i % 2 == 1
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use decs::ecs::Ecs;
+use decs::net::{DivergenceKind, diff_worlds, diff_worlds_limit};
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+#[component(diff)]
+struct Health(i32);
+
+#[derive(Clone, Component)]
+struct Opaque(#[allow(dead_code)] u8);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Health>();
+        Ecs::register::<Opaque>();
+    });
+}
+
+fn world_with(health: &[(u32, i32)], opaque: &[u32]) -> World {
+    let mut world = World::new();
+    let f = world.frame();
+    for &(i, hp) in health {
+        world.get_storage_mut::<Health>().set(&f, i, Health(hp));
+    }
+    for &i in opaque {
+        world
+            .get_storage_mut::<Opaque>()
+            .set(&f, i, Opaque(i as u8));
+    }
+    world
+}
+
+#[test]
+fn identical_worlds_are_in_sync() {
+    register_components_once();
+    let a = world_with(&[(1, 10), (5000, 20)], &[3]);
+    let b = world_with(&[(1, 10), (5000, 20)], &[3]);
+    assert!(diff_worlds(&a, &b).is_in_sync());
+}
+
+#[test]
+fn reports_presence_and_value_divergences() {
+    register_components_once();
+    let a = world_with(&[(1, 10), (64, 5), (5000, 20)], &[3, 4]);
+    // Opaque values differ but are not compared; only its presence is.
+    let b = world_with(&[(1, 11), (5000, 20), (70000, 1)], &[3]);
+    let report = diff_worlds(&a, &b);
+    assert!(!report.truncated);
+    let found: Vec<_> = report
+        .divergences
+        .iter()
+        .map(|d| (d.index, d.kind, d.a.as_deref(), d.b.as_deref()))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            (
+                1,
+                DivergenceKind::ValueMismatch,
+                Some("Health(10)"),
+                Some("Health(11)")
+            ),
+            (64, DivergenceKind::OnlyInA, Some("Health(5)"), None),
+            (70000, DivergenceKind::OnlyInB, None, Some("Health(1)")),
+            (4, DivergenceKind::OnlyInA, None, None),
+        ]
+    );
+    assert!(report.divergences[0].component.ends_with("Health"));
+    assert!(report.divergences[3].component.ends_with("Opaque"));
+
+    let limited = diff_worlds_limit(&a, &b, 2);
+    assert_eq!(limited.divergences.len(), 2);
+    assert!(limited.truncated);
+}
+
+#[test]
+fn storage_missing_on_one_side_counts_as_empty() {
+    register_components_once();
+    let a = world_with(&[], &[]);
+    let b = world_with(&[(9, 1)], &[]);
+    let report = diff_worlds(&a, &b);
+    assert_eq!(report.divergences.len(), 1);
+    assert_eq!(report.divergences[0].kind, DivergenceKind::OnlyInB);
+    let report = diff_worlds(&b, &a);
+    assert_eq!(report.divergences[0].kind, DivergenceKind::OnlyInA);
+}
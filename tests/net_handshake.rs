@@ -0,0 +1,88 @@
+use decs::component::Component;
+use decs::ecs::Ecs;
+use decs::error::Error;
+use decs::net::Handshake;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Component)]
+struct Position {
+    #[allow(dead_code)]
+    x: f32,
+}
+
+#[derive(Clone, Component)]
+struct Velocity {
+    #[allow(dead_code)]
+    x: f32,
+}
+
+#[derive(Clone, Component)]
+struct WidePosition {
+    #[allow(dead_code)]
+    x: f64,
+}
+
+#[derive(Clone, Component)]
+#[component(version = 2)]
+struct Health(#[allow(dead_code)] i32);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Position>();
+        Ecs::register::<Health>();
+    });
+}
+
+#[test]
+fn schema_hash_follows_the_fields() {
+    assert_eq!(Position::SCHEMA_HASH, Velocity::SCHEMA_HASH);
+    assert_ne!(Position::SCHEMA_HASH, WidePosition::SCHEMA_HASH);
+    assert_ne!(Position::SCHEMA_HASH, 0);
+}
+
+#[test]
+fn identical_registries_validate() {
+    register_components_once();
+    let local = Handshake::local();
+    let health = local
+        .components
+        .iter()
+        .find(|c| c.id == Health::id())
+        .unwrap();
+    assert!(health.name.ends_with("Health"));
+    assert_eq!(health.version, 2);
+    assert_eq!(health.schema_hash, Health::SCHEMA_HASH);
+
+    assert_eq!(local.validate(&Handshake::local()), Ok(()));
+    assert!(local.mismatches(&local.clone()).is_empty());
+}
+
+#[test]
+fn differing_registries_are_rejected() {
+    register_components_once();
+    let local = Handshake::local();
+
+    let mut remote = local.clone();
+    let entry = remote
+        .components
+        .iter_mut()
+        .find(|c| c.id == Position::id())
+        .unwrap();
+    entry.schema_hash = WidePosition::SCHEMA_HASH;
+    assert_eq!(
+        local.validate(&remote),
+        Err(Error::RegistryMismatch { id: Position::id() })
+    );
+
+    let mut remote = local.clone();
+    remote.components.retain(|c| c.id != Health::id());
+    let mismatches = local.mismatches(&remote);
+    assert_eq!(mismatches.len(), 1);
+    assert_eq!(mismatches[0].id, Health::id());
+    assert!(mismatches[0].local.is_some());
+    assert_eq!(mismatches[0].remote, None);
+    // Rejected from either side.
+    assert!(remote.validate(&local).is_err());
+}
@@ -100,7 +100,7 @@ fn count_all_velocity(world: &mut World) -> u32 {
 fn count_changed_position(world: &mut World) -> u32 {
     unsafe {
         let pos = &*world.get_storage::<Position>();
-        let rb = &*pos.rollback;
+        let rb = &pos.rollback;
         let mut total: u32 = 0;
         let mut storage_mask = rb.changed_mask;
         while storage_mask != 0 {
@@ -343,3 +343,260 @@ fn changed_filter_after_remove_and_add_counts_change() {
     let c = count_storage_changed_position(&mut world);
     assert_eq!(c, 0);
 }
+
+system!(MovePosNotFrozen { query
+    fn update(pos: &mut ViewMut<Position>)
+    {
+        pos.x += 1.0;
+    }
+    None=[Frozen]
+});
+
+#[test]
+fn none_filter_is_ordered_against_cleanup_of_excluded_type() {
+    use decs::system::System;
+    register_components_once();
+    let mut world = World::new();
+    let sys = MovePosNotFrozen::new(&mut world);
+    assert!(sys.reads().contains(&std::any::TypeId::of::<Frozen>()));
+    world.scheduler_mut().add_system(sys);
+    world.scheduler_mut().build_wavefronts();
+
+    // Frozen's cleanup system writes Frozen, so it must not share a wavefront with the query.
+    let names: Vec<Vec<String>> = format!("{:?}", world.scheduler())
+        .lines()
+        .map(|l| l.split(", ").map(str::to_owned).collect())
+        .collect();
+    for wave in names {
+        let has_query = wave.iter().any(|n| n.contains("MovePosNotFrozen"));
+        let has_cleanup = wave
+            .iter()
+            .any(|n| n.contains("ComponentCleanupSystem") && n.contains("Frozen"));
+        assert!(!(has_query && has_cleanup));
+    }
+}
+
+static WIDE_MATCHED: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+
+system!(WideQuery { query
+    fn update(_pos: View<Position>, _vel: View<Velocity>)
+    {
+        WIDE_MATCHED.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    }
+    None=[Frozen]
+});
+
+#[test]
+fn wide_query_matches_brute_force_across_storage_indices() {
+    register_components_once();
+    let mut world = World::new();
+    let mut expected = 0u32;
+    {
+        let f = Frame::new(world.current_tick());
+        // Spread entities over many storage indices so every page walk is exercised.
+        for i in (0..64 * 64 * 64u32).step_by(389) {
+            let has_pos = i % 3 != 0;
+            let has_vel = i % 5 != 0;
+            let frozen = i % 7 == 0;
+            if has_pos {
+                world
+                    .get_storage_mut::<Position>()
+                    .set(&f, i, Position { x: 0.0, y: 0.0 });
+            }
+            if has_vel {
+                world
+                    .get_storage_mut::<Velocity>()
+                    .set(&f, i, Velocity { x: 0.0, y: 0.0 });
+            }
+            if frozen {
+                world.get_storage_mut::<Frozen>().set(&f, i, Frozen);
+            }
+            if has_pos && has_vel && !frozen {
+                expected += 1;
+            }
+        }
+    }
+    let sys = WideQuery::new(&mut world);
+    world.scheduler_mut().add_system(sys);
+    world.scheduler_mut().build_wavefronts();
+    WIDE_MATCHED.store(0, std::sync::atomic::Ordering::Relaxed);
+    world.run();
+    assert_eq!(
+        WIDE_MATCHED.load(std::sync::atomic::Ordering::Relaxed),
+        expected
+    );
+}
+
+static CHANGED_SEEN: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+
+system!(CountChangedPositions { query
+    fn update(_pos: View<Position>)
+    {
+        CHANGED_SEEN.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    }
+    Changed=[Position]
+});
+
+#[test]
+fn changed_filter_matches_writes_since_last_run() {
+    use std::sync::atomic::Ordering::Relaxed;
+    register_components_once();
+    let mut world = World::new();
+    let sys = CountChangedPositions::new(&mut world);
+    world.scheduler_mut().add_system(sys);
+    world.scheduler_mut().build_wavefronts();
+    let run = |world: &mut World| {
+        CHANGED_SEEN.store(0, Relaxed);
+        world.run();
+        CHANGED_SEEN.load(Relaxed)
+    };
+
+    let t0 = world.current_tick();
+    {
+        let f = world.frame();
+        let pos = world.get_storage_mut::<Position>();
+        for i in 0..100u32 {
+            pos.set(&f, i * 7, Position { x: 0.0, y: 0.0 });
+        }
+    }
+    assert_eq!(run(&mut world), 100);
+    assert_eq!(run(&mut world), 0);
+
+    // Clearing the changed masks no longer hides writes from the next run.
+    {
+        let f = world.frame();
+        let pos = world.get_storage_mut::<Position>();
+        pos.set(&f, 7, Position { x: 1.0, y: 0.0 });
+        pos.get_mut(&f, 70).unwrap().x = 2.0;
+    }
+    world.clear_changed_masks();
+    assert_eq!(run(&mut world), 2);
+    assert_eq!(run(&mut world), 0);
+
+    // Values restored by rollback count as changed.
+    world.rollback(t0);
+    assert_eq!(run(&mut world), 2);
+}
+
+static SELF_SEEN: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+
+system!(BumpChangedVelocities { query
+    fn update(vel: &mut ViewMut<Velocity>)
+    {
+        vel.x += 1.0;
+        SELF_SEEN.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    }
+    Changed=[Velocity]
+});
+
+#[test]
+fn changed_filter_ignores_the_systems_own_writes() {
+    use std::sync::atomic::Ordering::Relaxed;
+    register_components_once();
+    let mut world = World::new();
+    {
+        let f = world.frame();
+        let vel = world.get_storage_mut::<Velocity>();
+        for i in 0..10u32 {
+            vel.set(&f, i, Velocity { x: 0.0, y: 0.0 });
+        }
+    }
+    let sys = BumpChangedVelocities::new(&mut world);
+    world.scheduler_mut().add_system(sys);
+    world.scheduler_mut().build_wavefronts();
+
+    world.run();
+    assert_eq!(SELF_SEEN.load(Relaxed), 10);
+    world.run();
+    assert_eq!(SELF_SEEN.load(Relaxed), 10);
+
+    {
+        let f = world.frame();
+        world
+            .get_storage_mut::<Velocity>()
+            .get_mut(&f, 3)
+            .unwrap()
+            .y = 1.0;
+    }
+    world.run();
+    assert_eq!(SELF_SEEN.load(Relaxed), 11);
+    let vel = world.get_storage_mut::<Velocity>();
+    assert_eq!(vel.get(3).unwrap().x, 2.0);
+    assert_eq!(vel.get(4).unwrap().x, 1.0);
+}
+
+system!(WatchChangedPositions { query
+    fn update(_pos: View<Position>)
+    {
+    }
+    Changed=[Position]
+});
+
+#[test]
+fn worlds_on_one_thread_keep_their_own_change_tick() {
+    use decs::system::System;
+    register_components_once();
+    let mut world = World::new();
+    let mut other = World::new();
+    {
+        let f = world.frame();
+        world
+            .get_storage_mut::<Position>()
+            .set(&f, 0, Position { x: 0.0, y: 0.0 });
+    }
+    let watch = WatchChangedPositions::new(&mut world);
+    watch.run(&world.frame());
+    assert_eq!(watch.match_count(), 0);
+
+    // Changed systems of another world advance that world's counter only.
+    let tick = world.change_tick();
+    let other_watch = WatchChangedPositions::new(&mut other);
+    for _ in 0..3 {
+        other_watch.run(&other.frame());
+    }
+    assert_eq!(world.change_tick(), tick);
+    assert!(other.change_tick().is_after(tick));
+
+    {
+        let f = world.frame();
+        world
+            .get_storage_mut::<Position>()
+            .set(&f, 1, Position { x: 1.0, y: 0.0 });
+    }
+    assert_eq!(watch.match_count(), 1);
+}
+
+system!(ThawFrozen { query
+    fn update(_frozen: &mut ViewMut<Frozen>)
+    {
+    }
+});
+
+fn wave_of(world: &World, name: &str) -> usize {
+    format!("{:?}", world.scheduler())
+        .lines()
+        .position(|wave| wave.contains(name))
+        .unwrap()
+}
+
+#[test]
+fn none_filter_orders_against_writers_of_excluded_type() {
+    register_components_once();
+    // Like any reader of Frozen, the query runs after the systems writing it, whatever
+    // the order they were added in, so it sees this tick's Frozen set instead of racing
+    // with its updates.
+    for query_first in [false, true] {
+        let mut world = World::new();
+        let thaw = ThawFrozen::new(&mut world);
+        let query = MovePosNotFrozen::new(&mut world);
+        if query_first {
+            world.scheduler_mut().add_system(query);
+            world.scheduler_mut().add_system(thaw);
+        } else {
+            world.scheduler_mut().add_system(thaw);
+            world.scheduler_mut().add_system(query);
+        }
+        world.scheduler_mut().build_wavefronts();
+        assert!(wave_of(&world, "ThawFrozen") < wave_of(&world, "MovePosNotFrozen"));
+    }
+}
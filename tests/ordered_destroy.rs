@@ -0,0 +1,140 @@
+use decs::component::Destroyed;
+use decs::destroy::{DestroyOrder, OrderedDestroySystem};
+use decs::ecs::Ecs;
+use decs::entity::Entity;
+use decs::frame::Frame;
+use decs::hierarchy::{ChildOf, HierarchyOrder, Parent, UpdateHierarchySystem};
+use decs::world::World;
+use std::sync::{Arc, Mutex, Once};
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<ChildOf>();
+        Ecs::register::<Parent>();
+    });
+}
+
+/// Puts `first` before every other destroyed entity, and logs teardowns.
+struct FirstOrder {
+    first: Arc<Mutex<Entity>>,
+    log: Arc<Mutex<Vec<Entity>>>,
+}
+
+impl DestroyOrder for FirstOrder {
+    fn before(&self, entity: Entity, out: &mut Vec<Entity>) {
+        let first = *self.first.lock().unwrap();
+        if entity != first {
+            out.push(first);
+        }
+    }
+
+    fn tear_down(&self, _frame: &Frame, entity: Entity) {
+        self.log.lock().unwrap().push(entity);
+    }
+}
+
+/// A world with a parent and three children linked under it.
+fn hierarchy_world(order: Option<FirstOrder>) -> (World, Entity, [Entity; 3]) {
+    register_components_once();
+    let mut world = World::new();
+    let update = UpdateHierarchySystem::new(&mut world);
+    world.scheduler_mut().add_system(update);
+    let hierarchy = HierarchyOrder::new(&mut world);
+    let mut destroy = OrderedDestroySystem::new(&mut world).with(hierarchy);
+    if let Some(order) = order {
+        destroy = destroy.with(order);
+    }
+    world.scheduler_mut().add_system(destroy);
+    world.finalize();
+
+    let f = world.frame();
+    let entities = world.get_entity_storage();
+    let parent = unsafe { (*entities).spawn(&f) }.unwrap();
+    let children = [(); 3].map(|_| unsafe { (*entities).spawn(&f) }.unwrap());
+    let child_storage = world.get_storage_mut::<ChildOf>();
+    for child in children {
+        child_storage.set(
+            &f,
+            child.index(),
+            ChildOf {
+                parent: None,
+                next_sibling: None,
+                prev_sibling: None,
+                pending_parent: Some(parent),
+            },
+        );
+    }
+    world.run();
+    (world, parent, children)
+}
+
+fn destroy(world: &mut World, entities: &[Entity]) {
+    let f = world.frame();
+    for entity in entities {
+        world
+            .get_storage_mut::<Destroyed>()
+            .set(&f, entity.index(), Destroyed());
+    }
+}
+
+fn last_order(world: &World) -> Vec<Entity> {
+    let system = world.scheduler().get_system::<OrderedDestroySystem>();
+    system.unwrap().last_order()
+}
+
+#[test]
+fn destroyed_child_is_unlinked_from_its_siblings() {
+    let (mut world, parent, [c1, c2, c3]) = hierarchy_world(None);
+    destroy(&mut world, &[c2]);
+    world.run();
+
+    let children = world.get_storage_mut::<ChildOf>();
+    assert!(children.get(c2.index()).is_none());
+    assert_eq!(children.get(c1.index()).unwrap().next_sibling, Some(c3));
+    assert_eq!(children.get(c3.index()).unwrap().prev_sibling, Some(c1));
+    let p = world
+        .get_storage_mut::<Parent>()
+        .get(parent.index())
+        .unwrap();
+    assert_eq!((p.first_child, p.last_child), (c1, c3));
+    assert!(world.verify_invariants());
+}
+
+#[test]
+fn children_are_torn_down_before_their_parent() {
+    let (mut world, parent, [c1, c2, c3]) = hierarchy_world(None);
+    destroy(&mut world, &[parent, c1, c3]);
+    world.run();
+    assert_eq!(last_order(&world), [c1, c3, parent]);
+
+    // The surviving child is orphaned instead of pointing at a dead parent.
+    let survivor = world.get_storage_mut::<ChildOf>().get(c2.index()).unwrap();
+    assert_eq!(survivor.parent, None);
+    assert_eq!((survivor.prev_sibling, survivor.next_sibling), (None, None));
+    assert!(
+        world
+            .get_storage_mut::<Parent>()
+            .get(parent.index())
+            .is_none()
+    );
+
+    world.run();
+    assert!(last_order(&world).is_empty());
+}
+
+#[test]
+fn user_orders_are_combined_with_the_hierarchy() {
+    let first = Arc::new(Mutex::new(Entity::none()));
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let order = FirstOrder {
+        first: first.clone(),
+        log: log.clone(),
+    };
+    let (mut world, parent, [c1, c2, c3]) = hierarchy_world(Some(order));
+    *first.lock().unwrap() = c3;
+    destroy(&mut world, &[parent, c1, c2, c3]);
+    world.run();
+    assert_eq!(last_order(&world), [c3, c1, c2, parent]);
+    assert_eq!(*log.lock().unwrap(), [c3, c1, c2, parent]);
+}
@@ -0,0 +1,90 @@
+use decs::ecs::Ecs;
+use decs::storage::Chunk;
+use decs::system;
+use decs::view::ViewMut;
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::{Arc, Once};
+
+#[derive(Clone, Debug, PartialEq, Component)]
+#[component(out_of_line)]
+struct Inventory {
+    slots: [u64; 64],
+    // Counts live clones, to catch leaked or double-dropped values.
+    owner: Arc<()>,
+}
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct InlineInventory {
+    slots: [u64; 64],
+}
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Gold(u64);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Inventory>();
+        Ecs::register::<InlineInventory>();
+        Ecs::register::<Gold>();
+    });
+}
+
+system!(SellSystem {
+    query fn update(inventory: &mut ViewMut<Inventory>, gold: &mut ViewMut<Gold>) {
+        gold.0 += inventory.slots[0];
+        inventory.slots[0] = 0;
+    }
+});
+
+fn inventory(first: u64, owner: &Arc<()>) -> Inventory {
+    let mut slots = [0; 64];
+    slots[0] = first;
+    Inventory {
+        slots,
+        owner: owner.clone(),
+    }
+}
+
+#[test]
+fn chunks_hold_pointers_to_large_values() {
+    let pointers = size_of::<Chunk<Inventory>>();
+    let inline = size_of::<Chunk<InlineInventory>>();
+    assert!(inline > 64 * size_of::<InlineInventory>());
+    assert!(pointers < inline / 16);
+}
+
+#[test]
+fn out_of_line_values_update_and_roll_back() {
+    register_components_once();
+    let owner = Arc::new(());
+    {
+        let mut world = World::new();
+        let sell = SellSystem::new(&mut world);
+        world.scheduler_mut().add_system(sell);
+        world.finalize();
+        let start = world.current_tick();
+        let f = world.frame();
+        for i in 0..100u32 {
+            world
+                .get_storage_mut::<Inventory>()
+                .set(&f, i, inventory(i as u64, &owner));
+            world.get_storage_mut::<Gold>().set(&f, i, Gold(1));
+        }
+        world.get_storage_mut::<Inventory>().remove(&f, 7);
+
+        world.run();
+        let sold = world.current_tick();
+        assert_eq!(world.get_component::<Gold>(42), Some(&Gold(43)));
+        assert_eq!(world.get_component::<Gold>(7), Some(&Gold(1)));
+        assert_eq!(world.get_component::<Inventory>(42).unwrap().slots[0], 0);
+
+        world.rollback(start);
+        assert_ne!(world.current_tick(), sold);
+        assert_eq!(world.get_component::<Inventory>(42).unwrap().slots[0], 42);
+        assert_eq!(world.get_component::<Gold>(42), Some(&Gold(1)));
+        assert!(world.verify_invariants());
+    }
+    assert_eq!(Arc::strong_count(&owner), 1);
+}
@@ -86,3 +86,97 @@ fn removing_all_values_drops_page_and_chunk() {
     world.scheduler_mut().build_wavefronts();
     world.run();
 }
+
+#[test]
+fn verify_all_reports_failing_storage_and_level() {
+    use decs::invariants::InvariantLevel;
+    register_components_once();
+    let mut world = World::new();
+    {
+        let f = Frame::new(world.current_tick());
+        let s = world.get_storage_mut::<Position>();
+        s.set(&f, 4096 + 70, Position { x: 1.0, y: 1.0 });
+    }
+    assert!(world.verify_all().is_ok());
+
+    // Corrupt the chunk holding index 4166 (storage 1, page 1) and the storage count.
+    {
+        let s = world.get_storage_mut::<Position>();
+        unsafe { (*(*s.data[1]).data[1]).fullness_mask = 0 };
+        s.count += 1;
+    }
+    let report = world.verify_all();
+    assert!(!report.is_ok());
+    let levels: Vec<InvariantLevel> = report
+        .violations
+        .iter()
+        .inspect(|v| {
+            assert_eq!(
+                v.component_id,
+                <Position as decs::component::Component>::id()
+            );
+            assert!(v.type_name.ends_with("Position"));
+            assert_eq!(v.rollback_tick, None);
+        })
+        .map(|v| v.level)
+        .collect();
+    assert_eq!(
+        levels,
+        vec![
+            InvariantLevel::Chunk {
+                storage_idx: 1,
+                page_idx: 1
+            },
+            InvariantLevel::Storage
+        ]
+    );
+    assert!(report.to_string().contains("chunk 1/1"));
+
+    // Restore so the storage drops cleanly.
+    {
+        let s = world.get_storage_mut::<Position>();
+        unsafe { (*(*s.data[1]).data[1]).fullness_mask = 0 };
+        s.count -= 1;
+    }
+}
+
+#[test]
+#[should_panic(expected = "invariant check failed")]
+fn invariant_check_system_panics_on_rollback_corruption() {
+    use decs::invariants::InvariantCheckSystem;
+    register_components_once();
+    let mut world = World::new();
+    world.set_tick(decs::tick::Tick(1));
+    {
+        let f = Frame::new(world.current_tick());
+        world
+            .get_storage_mut::<Position>()
+            .set(&f, 0, Position { x: 0.0, y: 0.0 });
+    }
+    world.set_tick(decs::tick::Tick(2));
+    {
+        let f = Frame::new(world.current_tick());
+        world
+            .get_storage_mut::<Position>()
+            .set(&f, 0, Position { x: 1.0, y: 0.0 });
+    }
+    let check = InvariantCheckSystem::new(&mut world, 1);
+    world.scheduler_mut().add_system(check);
+    world.scheduler_mut().build_wavefronts();
+    world.run();
+
+    // Mark the modified index as created too: created/changed must be exclusive.
+    {
+        let s = world.get_storage_mut::<Position>();
+        let rb = s
+            .prev
+            .iter_mut()
+            .chain(std::iter::once(&mut s.rollback))
+            .find(|rb| rb.tick() == decs::tick::Tick(2))
+            .unwrap();
+        let chunk = rb.get_page_mut(0).unwrap().get_mut(0).unwrap();
+        assert_eq!(chunk.changed_mask & 1, 1);
+        chunk.created_mask |= 1;
+    }
+    world.run();
+}
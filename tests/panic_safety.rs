@@ -0,0 +1,93 @@
+#![cfg(feature = "panic-safety")]
+
+use decs::ecs::Ecs;
+use decs::error::Error;
+use decs::system; // for `system!`
+use decs::tick::Tick;
+use decs::view::ViewMut;
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Counter {
+    v: i32,
+}
+
+static INIT: Once = Once::new();
+fn register_components_once() {
+    INIT.call_once(|| {
+        Ecs::register::<Counter>();
+    });
+}
+
+// Panics on the entity whose counter reached 3, after earlier entities were already modified.
+system!(BumpSystem {
+    query fn update(c: &mut ViewMut<Counter>) {
+        c.v += 1;
+        if c.v == 3 {
+            panic!("counter overflow");
+        }
+    }
+});
+
+fn world_with_counters(values: &[i32]) -> World {
+    let mut world = World::new();
+    {
+        let f = world.frame();
+        let s = world.get_storage_mut::<Counter>();
+        for (i, &v) in values.iter().enumerate() {
+            s.set(&f, i as u32 * 100, Counter { v });
+        }
+    }
+    let sys = BumpSystem::new(&mut world);
+    world.scheduler_mut().add_system(sys);
+    world.scheduler_mut().build_wavefronts();
+    world
+}
+
+fn counters(world: &mut World) -> Vec<i32> {
+    let s = world.get_storage_mut::<Counter>();
+    (0..3).map(|i| s.get(i * 100).unwrap().v).collect()
+}
+
+#[test]
+fn panicking_system_rolls_back_the_tick() {
+    register_components_once();
+    let mut world = world_with_counters(&[0, 0, 1]);
+
+    assert!(world.try_run().is_ok());
+    assert_eq!(counters(&mut world), vec![1, 1, 2]);
+
+    let err = world.try_run().unwrap_err();
+    match err {
+        Error::SystemPanicked { system, tick } => {
+            assert!(system.ends_with("BumpSystem"));
+            assert_eq!(tick, Tick(2));
+        }
+        other => panic!("unexpected error {:?}", other),
+    }
+    // Entities bumped before the panic are restored and the tick is undone.
+    assert_eq!(counters(&mut world), vec![1, 1, 2]);
+    assert_eq!(world.current_tick(), Tick(1));
+    assert_eq!(world.get_storage_mut::<Counter>().changed_mask, 0);
+    assert!(world.verify_all().is_ok());
+
+    // Fix the offending value and re-run the tick.
+    {
+        let f = world.frame();
+        world
+            .get_storage_mut::<Counter>()
+            .set(&f, 200, Counter { v: 10 });
+    }
+    assert!(world.try_run().is_ok());
+    assert_eq!(counters(&mut world), vec![2, 2, 11]);
+}
+
+#[test]
+#[should_panic(expected = "BumpSystem panicked")]
+fn run_reports_the_panicking_system() {
+    register_components_once();
+    let mut world = world_with_counters(&[2]);
+    world.run();
+}
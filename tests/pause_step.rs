@@ -0,0 +1,87 @@
+use decs::ecs::Ecs;
+use decs::system;
+use decs::view::ViewMut;
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Counter(u32);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Counter>();
+    });
+}
+
+system!(CountSystem {
+    query fn update(counter: &mut ViewMut<Counter>) {
+        counter.0 += 1;
+    }
+});
+
+fn world() -> World {
+    register_components_once();
+    let mut world = World::new();
+    let count = CountSystem::new(&mut world);
+    world.scheduler_mut().add_system(count);
+    world.finalize();
+    world.set_fixed_dt(0.5);
+    let f = world.frame();
+    world.get_storage_mut::<Counter>().set(&f, 0, Counter(0));
+    world
+}
+
+fn count(world: &World) -> u32 {
+    world.get_component::<Counter>(0).unwrap().0
+}
+
+#[test]
+fn paused_updates_only_run_requested_steps() {
+    let mut world = world();
+    assert_eq!(world.update(1.25), 2);
+    assert_eq!(world.alpha(), 0.5);
+
+    world.pause();
+    assert!(world.is_paused());
+    assert_eq!(world.update(10.0), 0);
+    assert_eq!(count(&world), 2);
+    // Rendering keeps interpolating from where the simulation stopped.
+    assert_eq!(world.alpha(), 0.5);
+
+    world.step(1);
+    world.step(2);
+    assert_eq!(world.scheduler().pending_steps(), 3);
+    assert_eq!(world.update(0.0), 3);
+    assert_eq!(world.update(0.0), 0);
+    assert_eq!(count(&world), 5);
+    // Running a tick by hand isn't blocked.
+    world.run();
+    assert_eq!(count(&world), 6);
+
+    // Time spent paused isn't caught up after resuming.
+    world.step(4);
+    world.resume();
+    assert_eq!(world.scheduler().pending_steps(), 0);
+    assert_eq!(world.update(0.25), 1);
+    assert_eq!(count(&world), 7);
+}
+
+#[test]
+fn stepping_matches_running_unpaused() {
+    let mut stepped = world();
+    stepped.step(3);
+    stepped.update(0.0);
+    stepped.step(2);
+    stepped.update(0.0);
+
+    let mut unpaused = world();
+    unpaused.update(2.5);
+
+    assert_eq!(stepped.current_tick(), unpaused.current_tick());
+    assert_eq!(
+        decs::net::world_checksum(&stepped),
+        decs::net::world_checksum(&unpaused)
+    );
+}
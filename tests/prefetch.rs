@@ -0,0 +1,54 @@
+#![cfg(feature = "prefetch")]
+use decs::ecs::Ecs;
+use decs::system;
+use decs::view::{View, ViewMut};
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Velocity(u32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Distance(u32);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Velocity>();
+        Ecs::register::<Distance>();
+    });
+}
+
+system!(MoveSystem {
+    query fn update(velocity: View<Velocity>, distance: &mut ViewMut<Distance>) {
+        distance.0 += velocity.0;
+    }
+});
+
+#[test]
+fn prefetching_queries_visit_every_match_once() {
+    register_components_once();
+    let mut world = World::new();
+    let moves = MoveSystem::new(&mut world);
+    world.scheduler_mut().add_system(moves);
+    world.finalize();
+    let f = world.frame();
+    // Sparse across chunks and pages, so most chunks have a next one to prefetch.
+    let indices: Vec<u32> = (0..20_000).step_by(37).collect();
+    for &i in &indices {
+        world
+            .get_storage_mut::<Velocity>()
+            .set(&f, i, Velocity(i % 5));
+        if i % 3 != 0 {
+            world.get_storage_mut::<Distance>().set(&f, i, Distance(0));
+        }
+    }
+
+    world.run();
+    world.run();
+    for &i in &indices {
+        let expected = (i % 3 != 0).then(|| Distance(2 * (i % 5)));
+        assert_eq!(world.get_component::<Distance>(i).cloned(), expected);
+    }
+}
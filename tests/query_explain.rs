@@ -0,0 +1,83 @@
+use decs::ecs::Ecs;
+use decs::system;
+use decs::system::QueryMismatch::{Excluded, Missing, Unchanged};
+use decs::system::System;
+use decs::view::{View, ViewMut};
+use decs::world::World;
+use decs_macros::Component;
+use std::any::type_name;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Health(i32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Poisoned;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Immune;
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Health>();
+        Ecs::register::<Poisoned>();
+        Ecs::register::<Immune>();
+    });
+}
+
+system!(PoisonSystem { query
+    fn update(hp: &mut ViewMut<Health>, _poison: View<Poisoned>)
+    {
+        hp.0 -= 1;
+    }
+    None=[Immune]
+    Changed=[Poisoned]
+});
+
+#[test]
+fn explain_reports_each_failing_filter() {
+    register_components_once();
+    let mut world = World::new();
+    let f = world.frame();
+    for index in 1..4 {
+        world.get_storage_mut::<Health>().set(&f, index, Health(10));
+    }
+    world.get_storage_mut::<Poisoned>().set(&f, 1, Poisoned);
+    world.get_storage_mut::<Poisoned>().set(&f, 3, Poisoned);
+    world.get_storage_mut::<Immune>().set(&f, 3, Immune);
+    let system = PoisonSystem::new(&mut world);
+
+    assert_eq!(system.explain(1), []);
+    assert_eq!(system.explain(2), [Missing(type_name::<Poisoned>())]);
+    assert_eq!(system.explain(3), [Excluded(type_name::<Immune>())]);
+    assert_eq!(
+        system.explain(4),
+        [
+            Missing(type_name::<Health>()),
+            Missing(type_name::<Poisoned>())
+        ]
+    );
+
+    world.scheduler_mut().add_system(system);
+    world.scheduler_mut().build_wavefronts();
+    world.run();
+    let health = world.get_storage_mut::<Health>();
+    assert_eq!(health.get(1), Some(&Health(9)));
+    assert_eq!(health.get(3), Some(&Health(10)));
+
+    // The poison was seen; it only matches again once rewritten.
+    let system = world.scheduler().get_system::<PoisonSystem>().unwrap();
+    let explained = system.explain(1);
+    assert_eq!(explained, [Unchanged(type_name::<Poisoned>())]);
+    assert!(
+        explained[0]
+            .to_string()
+            .contains("not changed since the last run")
+    );
+
+    let f = world.frame();
+    world.get_storage_mut::<Poisoned>().set(&f, 1, Poisoned);
+    let system = world.scheduler().get_system::<PoisonSystem>().unwrap();
+    assert_eq!(system.explain(1), []);
+}
@@ -0,0 +1,90 @@
+use decs::ecs::Ecs;
+use decs::system;
+use decs::system::{ReadOnlySystem, System};
+use decs::view::{View, ViewMut};
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Health(u32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Name(&'static str);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Health>();
+        Ecs::register::<Name>();
+    });
+}
+
+system!(RegenSystem {
+    query fn update(health: &mut ViewMut<Health>) {
+        health.0 += 1;
+    }
+});
+
+// Nothing writes Name, so this could run first; it waits for HealthBarSystem instead.
+system!(NameplateSystem {
+    query fn update(name: View<Name>) {
+        let _ = name.0;
+    }
+});
+
+system!(HealthBarSystem {
+    query fn update(health: View<Health>) {
+        let _ = health.0;
+    }
+});
+
+fn assert_read_only<S: ReadOnlySystem>(system: &S) -> bool {
+    system.read_only()
+}
+
+/// The wavefront running the system named `name`, from the scheduler's debug output.
+fn wavefront_of(world: &World, name: &str) -> String {
+    format!("{:?}", world.scheduler())
+        .lines()
+        .find(|line| line.contains(name))
+        .unwrap()
+        .split(" names ")
+        .next()
+        .unwrap()
+        .to_string()
+}
+
+#[test]
+fn read_only_systems_share_a_wavefront() {
+    register_components_once();
+    let mut world = World::new();
+    let regen = RegenSystem::new(&mut world);
+    let nameplate = NameplateSystem::new(&mut world);
+    let health_bar = HealthBarSystem::new(&mut world);
+    assert!(!regen.read_only());
+    assert!(assert_read_only(&nameplate));
+    assert!(assert_read_only(&health_bar));
+    world.scheduler_mut().add_system(nameplate);
+    world.scheduler_mut().add_system(regen);
+    world.scheduler_mut().add_system(health_bar);
+    world.finalize();
+
+    assert_ne!(
+        wavefront_of(&world, "RegenSystem"),
+        wavefront_of(&world, "HealthBarSystem")
+    );
+    assert_eq!(
+        wavefront_of(&world, "NameplateSystem"),
+        wavefront_of(&world, "HealthBarSystem")
+    );
+    let order = world.scheduler().resolved_order();
+    let position = |name: &str| order.iter().position(|s| s.ends_with(name)).unwrap();
+    assert!(position("RegenSystem") < position("NameplateSystem"));
+
+    let f = world.frame();
+    world.get_storage_mut::<Health>().set(&f, 0, Health(1));
+    world.get_storage_mut::<Name>().set(&f, 0, Name("orc"));
+    world.run();
+    assert_eq!(world.get_component::<Health>(0), Some(&Health(2)));
+}
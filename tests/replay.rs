@@ -0,0 +1,120 @@
+use decs::commands::Commands;
+use decs::component::Component;
+use decs::ecs::Ecs;
+use decs::net::world_checksum;
+use decs::replay::WriteKind;
+use decs::rng::DeterministicRng;
+use decs::system;
+use decs::view::View;
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+#[component(diff)]
+struct Ship(u32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+#[component(diff)]
+struct Shield(u32);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Ship>();
+        Ecs::register::<Shield>();
+        Ecs::register::<DeterministicRng>();
+    });
+}
+
+// Issues commands of its own, which a replay issues again and are not recorded.
+system!(ShieldSystem { query
+    fn update(ship: View<Ship>, commands: Commands)
+    {
+        commands.insert_component(ship.index(), Shield(ship.0 * 10));
+    }
+    None=[Shield]
+});
+
+fn world() -> World {
+    register_components_once();
+    let mut world = World::new();
+    let shields = ShieldSystem::new(&mut world);
+    world.scheduler_mut().add_system(shields);
+    world.finalize();
+    world
+}
+
+#[test]
+fn records_and_replays_out_of_band_writes() {
+    let mut session = world();
+    session.start_replay_recording();
+    let start = session.current_tick();
+
+    // Ships arriving from the network between ticks.
+    session.commands().insert_component(3, Ship(1));
+    session.run();
+    session.run();
+    session.commands().insert_component(9, Ship(2));
+    session.commands().remove_component::<Shield>(3);
+    session.commands().push(|_| {});
+    session.run();
+    session.run();
+    let end = session.current_tick();
+
+    let log = session.take_replay_log().unwrap();
+    assert_eq!(session.take_replay_log().map(|log| log.len()), None);
+    let writes: Vec<_> = log
+        .writes()
+        .iter()
+        .map(|w| (w.tick, w.component, w.index, w.kind))
+        .collect();
+    let t1 = start.next();
+    let t3 = t1.next().next();
+    assert_eq!(
+        writes,
+        [
+            (t1, Ship::id(), 3, WriteKind::Insert),
+            (t3, Ship::id(), 9, WriteKind::Insert),
+            (t3, Shield::id(), 3, WriteKind::Remove),
+        ]
+    );
+    assert_eq!(log.writes_at(t3).count(), 2);
+
+    let mut replay = world();
+    while replay.current_tick() != end {
+        let tick = replay.current_tick().next();
+        log.inject(&mut replay, tick);
+        replay.run();
+    }
+    assert_eq!(world_checksum(&replay), world_checksum(&session));
+    assert_eq!(replay.get_component::<Shield>(3), Some(&Shield(10)));
+    assert_eq!(replay.get_component::<Shield>(9), Some(&Shield(20)));
+}
+
+#[test]
+fn log_header_keeps_the_seed_of_the_recording_world() {
+    register_components_once();
+    let mut session = World::new_with_seed(0xC0FFEE);
+    session.start_replay_recording();
+    session.run();
+    let log = session.take_replay_log().unwrap();
+    assert_eq!(log.seed(), Some(0xC0FFEE));
+
+    let mut replay = log.new_world();
+    assert_eq!(replay.seed(), Some(0xC0FFEE));
+    let mut fresh = World::new_with_seed(0xC0FFEE);
+    assert_eq!(
+        replay
+            .resource_mut::<DeterministicRng>()
+            .unwrap()
+            .next_u64(),
+        fresh.resource_mut::<DeterministicRng>().unwrap().next_u64()
+    );
+
+    let mut unseeded = world();
+    unseeded.start_replay_recording();
+    let log = unseeded.take_replay_log().unwrap();
+    assert_eq!(log.seed(), None);
+    assert_eq!(log.new_world().seed(), None);
+}
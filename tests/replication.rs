@@ -0,0 +1,193 @@
+#![cfg(feature = "postcard")]
+use decs::codec::{Postcard, SnapshotCodec};
+use decs::component::Component;
+use decs::ecs::Ecs;
+use decs::fields::FieldSetter;
+use decs::replication::{DeltaEncoder, ReplicationTable};
+use decs::world::World;
+use decs_macros::Component;
+use serde::Serialize;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component, Serialize)]
+#[component(replicated, priority = 10, quantize = round_position)]
+struct Position(f32);
+
+fn round_position(position: &Position) -> Position {
+    Position(position.0.round())
+}
+
+#[derive(Clone, Debug, PartialEq, Component, Serialize)]
+#[component(replicated, priority = 1)]
+struct Name(String);
+
+#[derive(Clone, Debug, PartialEq, Component, Serialize)]
+#[component(replicated)]
+struct Score(u32);
+
+#[derive(Clone, Debug, PartialEq, Component, Serialize)]
+#[component(replicated)]
+struct Forgotten(u8);
+
+#[derive(Clone, Debug, PartialEq, Component, Serialize)]
+struct Local(u8);
+
+#[derive(Clone, Debug, PartialEq, Component, Serialize)]
+#[component(replicated, field_mask(Serialize))]
+struct Body {
+    velocity: f32,
+    mass: f32,
+    label: String,
+}
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Position>();
+        Ecs::register::<Name>();
+        Ecs::register::<Score>();
+        Ecs::register::<Forgotten>();
+        Ecs::register::<Local>();
+        Ecs::register::<Body>();
+    });
+}
+
+fn table() -> ReplicationTable<Postcard> {
+    let mut table = ReplicationTable::new();
+    table
+        .add::<Score>()
+        .add::<Name>()
+        .add::<Position>()
+        .add::<Score>();
+    table
+}
+
+fn encoded<T: Serialize>(value: &T) -> Vec<u8> {
+    Postcard.encode(value).unwrap()
+}
+
+#[test]
+fn table_is_ordered_by_priority_from_the_attributes() {
+    register_components_once();
+    let table = table();
+    let ids: Vec<_> = table.iter().map(|entry| entry.id).collect();
+    assert_eq!(ids, [Position::id(), Name::id(), Score::id()]);
+    let position = table.get(Position::id()).unwrap();
+    assert_eq!(position.priority, 10);
+    assert!(position.quantized);
+    assert!(!table.get(Score::id()).unwrap().quantized);
+    assert_eq!(Ecs::metadata(Name::id()).unwrap().priority, 1);
+    assert_eq!(table.unlisted(), [Forgotten::id(), Body::id()]);
+}
+
+#[test]
+#[should_panic(expected = "is not #[component(replicated)]")]
+fn table_rejects_components_that_are_not_replicated() {
+    register_components_once();
+    ReplicationTable::<Postcard>::new().add::<Local>();
+}
+
+#[test]
+fn packets_send_changes_by_priority_within_the_budget() {
+    register_components_once();
+    let mut world = World::new();
+    world.finalize();
+    let f = world.frame();
+    world
+        .get_storage_mut::<Position>()
+        .set(&f, 1, Position(1.4));
+    world
+        .get_storage_mut::<Position>()
+        .set(&f, 2, Position(2.6));
+    world
+        .get_storage_mut::<Name>()
+        .set(&f, 1, Name("a long name".into()));
+    world.get_storage_mut::<Score>().set(&f, 1, Score(7));
+    world.get_storage_mut::<Local>().set(&f, 1, Local(0));
+
+    let mut encoder = DeltaEncoder::new(table());
+    let budget = 2 * encoded(&Position(1.0)).len() + encoded(&Score(7)).len();
+    let packet = encoder.encode(&Postcard, &world, budget).unwrap();
+    let sent: Vec<_> = packet
+        .values
+        .iter()
+        .map(|value| (value.component, value.index))
+        .collect();
+    assert_eq!(
+        sent,
+        [(Position::id(), 1), (Position::id(), 2), (Score::id(), 1)]
+    );
+    assert_eq!(packet.values[0].bytes, encoded(&Position(1.0)));
+    assert_eq!(packet.values[1].bytes, encoded(&Position(3.0)));
+    assert_eq!(packet.size, budget);
+    assert_eq!(packet.deferred, [Name::id()]);
+
+    // The deferred name goes out next, even though it alone exceeds the budget.
+    world.run();
+    let f = world.frame();
+    world.get_storage_mut::<Score>().get_mut(&f, 1).unwrap().0 = 8;
+    let packet = encoder.encode(&Postcard, &world, 1).unwrap();
+    assert_eq!(packet.values.len(), 1);
+    assert_eq!(packet.values[0].component, Name::id());
+    assert_eq!(packet.deferred, [Score::id()]);
+
+    let packet = encoder.encode(&Postcard, &world, 1).unwrap();
+    assert_eq!(packet.values.len(), 1);
+    assert_eq!(packet.values[0].bytes, encoded(&Score(8)));
+    assert!(
+        encoder
+            .encode(&Postcard, &world, 1)
+            .unwrap()
+            .values
+            .is_empty()
+    );
+}
+
+#[test]
+fn packets_send_only_the_fields_written_with_setters() {
+    register_components_once();
+    let mut world = World::new();
+    world.finalize();
+    let f = world.frame();
+    let body = Body {
+        velocity: 1.0,
+        mass: 2.0,
+        label: "crate".into(),
+    };
+    world.get_storage_mut::<Body>().set(&f, 3, body.clone());
+
+    let mut table = ReplicationTable::new();
+    table.add_fields::<Body, BodyField>();
+    assert_eq!(table.get(Body::id()).unwrap().fields, BodyField::NAMES);
+    let mut encoder = DeltaEncoder::new(table);
+    let packet = encoder.encode(&Postcard, &world, usize::MAX).unwrap();
+    assert_eq!(packet.values.len(), 1);
+    assert_eq!(packet.values[0].field, None);
+    assert_eq!(packet.values[0].bytes, encoded(&body));
+
+    world.run();
+    let f = world.frame();
+    let storage = world.get_storage_mut::<Body>();
+    storage.set_field(&f, 3, BodyField::Velocity(4.0));
+    storage.set_field(&f, 3, BodyField::Label("box".into()));
+    let packet = encoder.encode(&Postcard, &world, usize::MAX).unwrap();
+    let sent: Vec<_> = packet
+        .values
+        .iter()
+        .map(|value| (value.index, value.field, value.bytes.clone()))
+        .collect();
+    assert_eq!(
+        sent,
+        [
+            (3, Some(0), encoded(&BodyField::Velocity(4.0))),
+            (3, Some(2), encoded(&BodyField::Label("box".into()))),
+        ]
+    );
+
+    world.run();
+    let f = world.frame();
+    world.get_storage_mut::<Body>().get_mut(&f, 3).unwrap().mass = 5.0;
+    let packet = encoder.encode(&Postcard, &world, usize::MAX).unwrap();
+    assert_eq!(packet.values.len(), 1);
+    assert_eq!(packet.values[0].field, None);
+}
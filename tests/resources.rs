@@ -0,0 +1,186 @@
+use decs::component::Destroyed;
+use decs::ecs::Ecs;
+use decs::resource::SimTime;
+use decs::rng::{DeterministicRng, system_id};
+use decs::tick::Tick;
+use decs::world::World;
+use std::sync::Once;
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<DeterministicRng>();
+        Ecs::register::<SimTime>();
+    });
+}
+
+#[test]
+fn resource_insert_get_remove() {
+    register_components_once();
+    let mut world = World::new();
+    assert!(world.resource::<DeterministicRng>().is_none());
+    world.advance_tick();
+    world.insert_resource(DeterministicRng::new(7));
+    assert_eq!(world.resource::<DeterministicRng>().unwrap().seed(), 7);
+    world.resource_mut::<DeterministicRng>().unwrap().next_u64();
+    assert!(world.remove_resource::<DeterministicRng>());
+    assert!(!world.remove_resource::<DeterministicRng>());
+    assert!(world.resource::<DeterministicRng>().is_none());
+}
+
+#[test]
+fn rng_replays_identically_after_rollback() {
+    register_components_once();
+    let mut world = World::new();
+    let t1 = world.advance_tick();
+    world.insert_resource(DeterministicRng::new(42));
+
+    let draw = |world: &mut World| {
+        let mut out = Vec::new();
+        for _ in 0..3 {
+            world.advance_tick();
+            let rng = world.resource_mut::<DeterministicRng>().unwrap();
+            out.push((rng.next_u64(), rng.below(10), rng.next_f32()));
+        }
+        out
+    };
+
+    let first = draw(&mut world);
+    world.rollback(t1);
+    let second = draw(&mut world);
+    assert_eq!(first, second);
+    assert!(first.windows(2).all(|w| w[0] != w[1]));
+    assert!(
+        first
+            .iter()
+            .all(|&(_, below, f)| below < 10 && (0.0..1.0).contains(&f))
+    );
+}
+
+#[test]
+fn streams_depend_only_on_seed_tick_and_system() {
+    let rng = DeterministicRng::new(1);
+    let id = system_id("MoveSystem");
+    assert_eq!(id, system_id("MoveSystem"));
+    assert_ne!(id, system_id("SpawnSystem"));
+
+    let mut a = rng.stream(Tick(5), id);
+    let mut b = rng.stream(Tick(5), id);
+    assert_eq!(a.next_u64(), b.next_u64());
+    assert_ne!(
+        rng.stream(Tick(5), id).next_u64(),
+        rng.stream(Tick(6), id).next_u64()
+    );
+    assert_ne!(
+        rng.stream(Tick(5), id).next_u64(),
+        rng.stream(Tick(5), system_id("SpawnSystem")).next_u64()
+    );
+    assert_ne!(
+        rng.stream(Tick(5), id).next_u64(),
+        DeterministicRng::new(2).stream(Tick(5), id).next_u64()
+    );
+}
+
+#[test]
+fn sim_time_follows_ticks_and_rollback() {
+    register_components_once();
+    let mut world = World::new();
+    world.set_fixed_dt(0.5);
+    world.insert_resource(SimTime::new(0.5));
+
+    world.advance_tick();
+    let t2 = world.advance_tick();
+    let time = world.resource::<SimTime>().unwrap();
+    assert_eq!((time.tick, time.elapsed, time.fixed_dt), (t2, 1.0, 0.5));
+
+    world.set_fixed_dt(0.25);
+    world.run();
+    world.run();
+    let time = world.resource::<SimTime>().unwrap();
+    assert_eq!(
+        (time.tick, time.elapsed, time.fixed_dt),
+        (Tick(4), 1.5, 0.25)
+    );
+
+    world.rollback(t2);
+    let time = world.resource::<SimTime>().unwrap();
+    assert_eq!((time.tick, time.elapsed, time.fixed_dt), (t2, 1.0, 0.5));
+}
+
+#[test]
+fn seeded_worlds_draw_the_same_numbers_and_report_the_seed() {
+    register_components_once();
+    let draw = |world: &mut World| {
+        world.run();
+        world.resource_mut::<DeterministicRng>().unwrap().next_u64()
+    };
+    let mut a = World::new_with_seed(0x5EED);
+    let mut b = World::new_with_seed(0x5EED);
+    assert_eq!(draw(&mut a), draw(&mut b));
+    assert_eq!(a.resource::<DeterministicRng>().unwrap().seed(), 0x5EED);
+
+    assert_eq!(a.seed(), Some(0x5EED));
+    assert_eq!(a.snapshot().seed(), Some(0x5EED));
+    assert_eq!(a.branch().seed(), Some(0x5EED));
+    assert_eq!(World::new().seed(), None);
+
+    let stats = a.stats();
+    assert_eq!(stats.tick, a.current_tick());
+    assert_eq!(stats.seed, Some(0x5EED));
+    assert_eq!(stats.entities, 0);
+    assert_eq!(
+        stats.to_string(),
+        format!(
+            "{:?}, seed 0x5eed, 0 entities, {} storages",
+            a.current_tick(),
+            stats.storages
+        )
+    );
+}
+
+#[test]
+fn despawning_the_first_entity_keeps_resources() {
+    register_components_once();
+    let mut world = World::new_with_seed(3);
+    world.insert_resource(SimTime::new(1.0));
+    world.scheduler_mut().build_wavefronts();
+
+    let f = world.frame();
+    let entity = unsafe { (*world.get_entity_storage()).spawn(&f) }.unwrap();
+    assert_eq!(entity.index(), 0);
+    world
+        .get_storage_mut::<Destroyed>()
+        .set(&f, entity.index(), Destroyed());
+    world.run();
+    world.run();
+
+    assert!(unsafe { (*world.get_entity_storage()).get(entity.index()) }.is_none());
+    assert_eq!(world.resource::<DeterministicRng>().unwrap().seed(), 3);
+    assert!(world.resource::<SimTime>().is_some());
+}
+
+#[test]
+fn moving_or_duplicating_entity_zero_leaves_resources() {
+    register_components_once();
+    let mut world = World::new_with_seed(3);
+    world.insert_resource(SimTime::new(1.0));
+
+    let f = world.frame();
+    let entity = unsafe { (*world.get_entity_storage()).spawn(&f) }.unwrap();
+    assert_eq!(entity.index(), 0);
+    let copy = world.duplicate_entity(&f, entity).unwrap();
+    assert_eq!(world.get_storage_mut::<SimTime>().count, 1);
+    assert!(
+        world
+            .get_storage_mut::<SimTime>()
+            .get(copy.index())
+            .is_none()
+    );
+
+    let mut other = World::new();
+    assert!(world.move_entity_to(entity.index(), &mut other));
+    assert!(other.resource::<SimTime>().is_none());
+    assert!(other.resource::<DeterministicRng>().is_none());
+    assert!(world.resource::<SimTime>().is_some());
+    assert_eq!(world.resource::<DeterministicRng>().unwrap().seed(), 3);
+}
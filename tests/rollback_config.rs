@@ -0,0 +1,114 @@
+use decs::ecs::Ecs;
+use decs::error::Error;
+use decs::resource::RollbackConfig;
+use decs::system;
+use decs::tick::Tick;
+use decs::view::{View, ViewMut};
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Step(i32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Total(i32);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Step>();
+        Ecs::register::<Total>();
+        Ecs::register::<RollbackConfig>();
+    });
+}
+
+system!(AccumulateSystem { query
+    fn update(step: View<Step>, total: &mut ViewMut<Total>)
+    {
+        total.0 += step.0;
+    }
+});
+
+fn world_with_config(config: RollbackConfig) -> World {
+    register_components_once();
+    let mut world = World::new();
+    let system = AccumulateSystem::new(&mut world);
+    world.scheduler_mut().add_system(system);
+    world.finalize();
+    world.insert_resource(config);
+    let f = world.frame();
+    world.get_storage_mut::<Total>().set(&f, 1, Total(0));
+    world
+}
+
+fn apply_input(world: &mut World, step: i32) {
+    let f = world.frame();
+    world.get_storage_mut::<Step>().set(&f, 1, Step(step));
+}
+
+fn total(world: &mut World) -> i32 {
+    world.get_storage_mut::<Total>().get(1).unwrap().0
+}
+
+#[test]
+fn late_input_is_resimulated_within_the_window() {
+    let mut world = world_with_config(RollbackConfig::new(2, 4));
+    assert_eq!(world.input_tick(), Tick(3));
+    for _ in 0..6 {
+        apply_input(&mut world, 1);
+        world.run();
+    }
+    assert_eq!(world.current_tick(), Tick(6));
+    assert_eq!(total(&mut world), 6);
+
+    // The input of tick 4 turns out to have been 10.
+    let late =
+        |world: &mut World, tick: Tick| apply_input(world, if tick == Tick(4) { 10 } else { 1 });
+    assert_eq!(world.resimulate(Tick(4), late), Ok(3));
+    assert_eq!(world.current_tick(), Tick(6));
+    assert_eq!(total(&mut world), 15);
+
+    // Inputs for ticks not run yet need no rollback.
+    assert_eq!(world.resimulate(Tick(7), late), Ok(0));
+
+    // The whole window can be resimulated.
+    assert_eq!(world.resimulate(Tick(3), late), Ok(4));
+    assert_eq!(total(&mut world), 15);
+
+    // Going back 5 ticks exceeds the window, and nothing is touched.
+    assert_eq!(
+        world.resimulate(Tick(2), late),
+        Err(Error::RollbackWindowExceeded {
+            target: Tick(1),
+            max_frames: 4
+        })
+    );
+    assert_eq!(total(&mut world), 15);
+    assert!(world.verify_invariants());
+}
+
+fn run_ticks(config: RollbackConfig, ticks: u32) -> World {
+    let mut world = world_with_config(config);
+    for _ in 0..ticks {
+        apply_input(&mut world, 1);
+        world.run();
+    }
+    world
+}
+
+#[test]
+fn history_is_trimmed_to_the_window_on_keyframes() {
+    // Three ticks back from tick 9 at most.
+    let mut world = run_ticks(RollbackConfig::new(0, 3), 9);
+    assert_eq!(world.input_tick(), Tick(10));
+    assert!(world.try_rollback(Tick(5)).is_err());
+    world.try_rollback(Tick(6)).unwrap();
+    assert_eq!(total(&mut world), 6);
+
+    // Trimmed last on tick 8, so tick 9 still keeps one tick more.
+    let mut world = run_ticks(RollbackConfig::new(0, 3).with_keyframe_interval(4), 9);
+    assert!(world.try_rollback(Tick(4)).is_err());
+    world.try_rollback(Tick(5)).unwrap();
+    assert_eq!(total(&mut world), 5);
+}
@@ -0,0 +1,148 @@
+use decs::ecs::Ecs;
+use decs::world::World;
+use decs_macros::Component;
+use std::cell::Cell;
+use std::sync::Once;
+
+thread_local! {
+    static CREATED: Cell<usize> = const { Cell::new(0) };
+    static CLONES: Cell<usize> = const { Cell::new(0) };
+    static DROPS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Counts its clones and drops on the current thread.
+#[derive(Debug, PartialEq, Component)]
+struct Probe(u32);
+
+impl Probe {
+    fn new(value: u32) -> Self {
+        CREATED.with(|c| c.set(c.get() + 1));
+        Probe(value)
+    }
+}
+
+impl Clone for Probe {
+    fn clone(&self) -> Self {
+        CLONES.with(|c| c.set(c.get() + 1));
+        CREATED.with(|c| c.set(c.get() + 1));
+        Probe(self.0)
+    }
+}
+
+impl Drop for Probe {
+    fn drop(&mut self) {
+        DROPS.with(|c| c.set(c.get() + 1));
+    }
+}
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Probe>();
+    });
+}
+
+fn counts() -> (usize, usize) {
+    (CLONES.with(Cell::get), DROPS.with(Cell::get))
+}
+
+fn probe(world: &mut World, index: u32) -> Option<u32> {
+    world.get_storage_mut::<Probe>().get(index).map(|p| p.0)
+}
+
+#[test]
+#[cfg_attr(
+    feature = "shadow-world",
+    ignore = "the shadow storage clones the values it mirrors"
+)]
+fn rollback_moves_stored_values_back_without_cloning() {
+    register_components_once();
+    let mut world = World::new();
+    world.finalize();
+    let start = world.current_tick();
+    let f = world.frame();
+    world.get_storage_mut::<Probe>().set(&f, 1, Probe::new(1));
+    world.get_storage_mut::<Probe>().set(&f, 2, Probe::new(2));
+    world.run();
+
+    let f = world.frame();
+    world.get_storage_mut::<Probe>().set(&f, 1, Probe::new(10));
+    world.get_storage_mut::<Probe>().remove(&f, 2);
+    world.get_storage_mut::<Probe>().set(&f, 3, Probe::new(3));
+
+    let (clones, drops) = counts();
+    world.rollback(start);
+    // Release the undone snapshots, which hold the values the restore displaced.
+    world.collect_garbage(usize::MAX);
+    let (clones_after, drops_after) = counts();
+    assert_eq!(clones_after - clones, 0);
+    // The overwritten live value of index 1 and the value created at index 3.
+    assert_eq!(drops_after - drops, 2);
+
+    assert_eq!(probe(&mut world, 1), Some(1));
+    assert_eq!(probe(&mut world, 2), Some(2));
+    assert_eq!(probe(&mut world, 3), None);
+    assert!(world.verify_invariants());
+
+    drop(world);
+    assert_eq!(CREATED.with(Cell::get), DROPS.with(Cell::get));
+}
+
+#[test]
+fn restored_values_survive_a_second_rollback() {
+    register_components_once();
+    let mut world = World::new();
+    world.finalize();
+    let f = world.frame();
+    world.get_storage_mut::<Probe>().set(&f, 1, Probe::new(1));
+    world.run();
+    let start = world.current_tick();
+    for value in 2..5 {
+        let f = world.frame();
+        world
+            .get_storage_mut::<Probe>()
+            .set(&f, 1, Probe::new(value));
+        world.run();
+    }
+    // Writes outside `run` belong to the tick that last ran.
+    world.rollback(start.next());
+    assert_eq!(probe(&mut world, 1), Some(3));
+    world.rollback(start);
+    assert_eq!(probe(&mut world, 1), Some(2));
+    world.run();
+    assert_eq!(probe(&mut world, 1), Some(2));
+
+    drop(world);
+    assert_eq!(CREATED.with(Cell::get), DROPS.with(Cell::get));
+}
+
+#[test]
+fn undone_add_keeps_the_whole_chunk_snapshot_reachable() {
+    register_components_once();
+    let mut world = World::new();
+    world.finalize();
+    world
+        .get_storage_mut::<Probe>()
+        .set_snapshot_policy(decs::rollback::SnapshotPolicy::WholeChunk);
+    let f = world.frame();
+    for i in 0..8 {
+        world.get_storage_mut::<Probe>().set(&f, i, Probe::new(i));
+    }
+    world.run();
+    let start = world.current_tick();
+    world.run();
+
+    // The add snapshots the chunk; undoing it must not unlink the snapshot.
+    let f = world.frame();
+    world.get_storage_mut::<Probe>().set(&f, 20, Probe::new(20));
+    world.get_storage_mut::<Probe>().remove(&f, 20);
+    world.get_storage_mut::<Probe>().set(&f, 3, Probe::new(30));
+    world.run();
+
+    world.rollback(start);
+    assert_eq!(probe(&mut world, 3), Some(3));
+    assert!(world.verify_invariants());
+
+    drop(world);
+    assert_eq!(CREATED.with(Cell::get), DROPS.with(Cell::get));
+}
@@ -204,3 +204,165 @@ fn rollback_boundary_indices_created_changed_removed() {
     world.scheduler_mut().build_wavefronts();
     world.run();
 }
+
+#[test]
+fn whole_chunk_snapshot_restores_every_mutation_path() {
+    register_components_once();
+    let mut s = decs::storage::Storage::<TestC>::new();
+    s.set_snapshot_policy(decs::rollback::SnapshotPolicy::WholeChunk);
+
+    let f1 = Frame::new(decs::tick::Tick(1));
+    for i in 0..48 {
+        s.set(&f1, i, TestC { v: i as i32 });
+    }
+    s.clear_changed_masks();
+
+    let f2 = Frame::new(decs::tick::Tick(2));
+    s.get_mut(&f2, 1).unwrap().v = -1;
+    {
+        let rb_chunk = s.rollback.get_page(0).unwrap().get(0).unwrap();
+        assert_eq!(rb_chunk.snapshot_mask, (1u64 << 48) - 1);
+        assert_eq!(rb_chunk.changed_mask, 1 << 1);
+    }
+    s.set(&f2, 2, TestC { v: -2 });
+    s.set(&f2, 2, TestC { v: -3 });
+    assert!(s.remove(&f2, 3));
+    assert!(s.remove(&f2, 4));
+    s.set(&f2, 4, TestC { v: -4 });
+    s.set(&f2, 50, TestC { v: 50 });
+    {
+        let rb_chunk = s.rollback.get_page(0).unwrap().get(0).unwrap();
+        assert!(rb_chunk.verify_invariants());
+        assert_eq!(rb_chunk.changed_mask, (1 << 1) | (1 << 2) | (1 << 4));
+        assert_eq!(rb_chunk.removed_mask, 1 << 3);
+        assert_eq!(rb_chunk.created_mask, 1 << 50);
+    }
+
+    s.rollback(decs::tick::Tick(1));
+    for i in 0..48 {
+        assert_eq!(s.get(i), Some(&TestC { v: i as i32 }));
+    }
+    assert_eq!(s.get(50), None);
+    assert_eq!(s.count, 48);
+}
+
+#[test]
+fn adaptive_snapshot_skips_sparse_chunks() {
+    register_components_once();
+    let mut s = decs::storage::Storage::<TestC>::new();
+    s.set_snapshot_policy(decs::rollback::SnapshotPolicy::Adaptive { min_fill: 32 });
+
+    let f1 = Frame::new(decs::tick::Tick(1));
+    for i in (0..8).chain(64..128) {
+        s.set(&f1, i, TestC { v: i as i32 });
+    }
+
+    let f2 = Frame::new(decs::tick::Tick(2));
+    s.set(&f2, 0, TestC { v: 0 });
+    s.set(&f2, 64, TestC { v: 0 });
+    let rb_page = s.rollback.get_page(0).unwrap();
+    assert_eq!(rb_page.get(0).unwrap().snapshot_mask, 0);
+    assert_eq!(rb_page.get(1).unwrap().snapshot_mask, u64::MAX);
+}
+
+#[test]
+fn vec_queue_keeps_order_across_inline_and_spill() {
+    use decs::rollback::{INLINE_HISTORY, VecQueue};
+    let mut q = VecQueue::new();
+    for i in 0..INLINE_HISTORY + 3 {
+        q.push_back(i);
+    }
+    assert!(q.spilled());
+    assert_eq!(q.pop_front(), Some(0));
+    q.push_back(100);
+    assert_eq!(q.pop_back(), Some(100));
+    assert_eq!(q.back(), Some(&(INLINE_HISTORY + 2)));
+    let expected: Vec<usize> = (1..INLINE_HISTORY + 3).collect();
+    assert_eq!(q.iter().copied().collect::<Vec<_>>(), expected);
+    assert_eq!(
+        q.iter().rev().copied().collect::<Vec<_>>(),
+        expected.iter().rev().copied().collect::<Vec<_>>()
+    );
+    for v in q.iter_mut() {
+        *v += 1;
+    }
+    while q.len() > 1 {
+        q.pop_front();
+    }
+    assert_eq!(q.pop_back(), Some(INLINE_HISTORY + 3));
+    assert!(q.is_empty() && q.pop_front().is_none());
+}
+
+#[test]
+fn ticks_without_changes_do_not_grow_history() {
+    register_components_once();
+    let mut world = World::new();
+    let first = world.advance_tick();
+    let f = world.frame();
+    world.get_storage_mut::<TestC>().set(&f, 10, TestC { v: 1 });
+    for _ in 0..40 {
+        world.advance_tick();
+    }
+    let changed_at = world.advance_tick();
+    let f = world.frame();
+    world.get_storage_mut::<TestC>().set(&f, 10, TestC { v: 2 });
+    for _ in 0..40 {
+        world.advance_tick();
+    }
+
+    {
+        let s = world.get_storage_mut::<TestC>();
+        assert!(s.prev.len() <= 2, "history grew to {}", s.prev.len());
+        assert!(!s.prev.spilled());
+    }
+
+    // Rolling back into the quiet stretch keeps the first value
+    world.rollback(changed_at.wrapping_sub(5));
+    assert_eq!(
+        world.get_storage_mut::<TestC>().get(10),
+        Some(&TestC { v: 1 })
+    );
+    world.rollback(first.wrapping_sub(1));
+    assert_eq!(world.get_storage_mut::<TestC>().get(10), None);
+}
+
+#[test]
+fn rollback_discards_undone_history_before_resimulating() {
+    register_components_once();
+    let mut world = World::new();
+    let start = world.advance_tick();
+    let set = |world: &mut World, v: i32| {
+        let f = world.frame();
+        world.get_storage_mut::<TestC>().set(&f, 10, TestC { v });
+    };
+    set(&mut world, 1);
+    for v in [2, 3] {
+        world.advance_tick();
+        set(&mut world, v);
+    }
+
+    world.rollback(start);
+    assert!(
+        world
+            .get_storage_mut::<TestC>()
+            .rollback_ticks()
+            .all(|tick| !tick.is_after(start))
+    );
+
+    // Re-simulated ticks record new history; rolling back to one of them must not
+    // restore the values of the undone timeline.
+    let redo = world.advance_tick();
+    set(&mut world, 20);
+    world.advance_tick();
+    set(&mut world, 30);
+    world.rollback(redo);
+    assert_eq!(
+        world.get_storage_mut::<TestC>().get(10),
+        Some(&TestC { v: 20 })
+    );
+    world.rollback(start);
+    assert_eq!(
+        world.get_storage_mut::<TestC>().get(10),
+        Some(&TestC { v: 1 })
+    );
+}
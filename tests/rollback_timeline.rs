@@ -0,0 +1,83 @@
+use decs::ecs::Ecs;
+use decs::rollback::RollbackEntryKind;
+use decs::storage::Storage;
+use decs::system;
+use decs::tick::Tick;
+use decs::view::{View, ViewMut};
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Pos(i32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Vel(i32);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Pos>();
+        Ecs::register::<Vel>();
+    });
+}
+
+system!(MoveSystem {
+    query fn update(vel: View<Vel>, pos: &mut ViewMut<Pos>) {
+        pos.0 += vel.0;
+    }
+});
+
+fn entries(storage: &Storage<Pos>, tick: Tick) -> Vec<(u32, RollbackEntryKind, Option<Pos>)> {
+    let mut out = Vec::new();
+    storage.rollback_at(tick).unwrap().for_each_entry(|entry| {
+        out.push((entry.index, entry.kind, entry.old.cloned()));
+    });
+    out
+}
+
+#[test]
+fn snapshots_list_what_each_tick_changed() {
+    register_components_once();
+    let mut world = World::new();
+    let system = MoveSystem::new(&mut world);
+    world.scheduler_mut().add_system(system);
+    world.finalize();
+    world.run();
+    let setup = world.current_tick();
+    let f = world.frame();
+    for index in [1, 4097, 9] {
+        world.get_storage_mut::<Pos>().set(&f, index, Pos(5));
+    }
+    world.get_storage_mut::<Vel>().set(&f, 1, Vel(1));
+    world.get_storage_mut::<Vel>().set(&f, 4097, Vel(2));
+
+    world.run();
+    let moved = world.current_tick();
+    let f = world.frame();
+    world.get_storage_mut::<Pos>().remove(&f, 9);
+    // Created and removed again within the tick: nothing to report.
+    world.get_storage_mut::<Pos>().set(&f, 20, Pos(0));
+    world.get_storage_mut::<Pos>().remove(&f, 20);
+
+    let storage = world.get_storage_mut::<Pos>();
+    assert_eq!(
+        entries(storage, setup),
+        [
+            (1, RollbackEntryKind::Created, None),
+            (9, RollbackEntryKind::Created, None),
+            (4097, RollbackEntryKind::Created, None),
+        ]
+    );
+    assert_eq!(
+        entries(storage, moved),
+        [
+            (1, RollbackEntryKind::Changed, Some(Pos(5))),
+            (9, RollbackEntryKind::Removed, Some(Pos(5))),
+            (4097, RollbackEntryKind::Changed, Some(Pos(5))),
+        ]
+    );
+    let ticks: Vec<_> = storage.rollback_ticks().collect();
+    assert!(ticks.contains(&setup) && ticks.last() == Some(&moved));
+    assert!(storage.rollback_at(moved.next()).is_none());
+}
@@ -0,0 +1,102 @@
+use decs::condition::{RunCondition, RunIf, every_n_ticks, on_event};
+use decs::ecs::Ecs;
+use decs::frame::Frame;
+use decs::system;
+use decs::view::ViewMut;
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Counter(u32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+#[component(event)]
+struct Explosion {
+    radius: u32,
+}
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Counter>();
+        Ecs::register::<Explosion>();
+    });
+}
+
+system!(CountSystem { query
+    fn update(counter: &mut ViewMut<Counter>)
+    {
+        counter.0 += 1;
+    }
+});
+
+fn world_with_counter(condition: impl RunCondition) -> World {
+    let mut world = World::new();
+    let f = world.frame();
+    world.get_storage_mut::<Counter>().set(&f, 0, Counter(0));
+    world.get_storage_mut::<Explosion>();
+    let system = CountSystem::new(&mut world);
+    let system = RunIf::new(&mut world, system, condition);
+    world.scheduler_mut().add_system(system);
+    world.scheduler_mut().build_wavefronts();
+    world
+}
+
+fn count(world: &mut World) -> u32 {
+    world.get_storage_mut::<Counter>().get(0).unwrap().0
+}
+
+#[test]
+fn every_n_ticks_runs_on_multiples() {
+    register_components_once();
+    let mut world = world_with_counter(every_n_ticks(3));
+    let mut counts = Vec::new();
+    for _ in 0..7 {
+        world.run();
+        counts.push(count(&mut world));
+    }
+    assert_eq!(counts, [0, 0, 1, 1, 1, 2, 2]);
+    assert!(world.scheduler().get_system::<CountSystem>().is_some());
+}
+
+#[test]
+fn and_or_combine_conditions() {
+    register_components_once();
+    let odd = |frame: &Frame| frame.current_tick.0 % 2 == 1;
+    let mut world = world_with_counter(every_n_ticks(3).and(odd));
+    for _ in 0..9 {
+        world.run();
+    }
+    // Ticks 3 and 9.
+    assert_eq!(count(&mut world), 2);
+
+    let mut world =
+        world_with_counter(every_n_ticks(4).or(|frame: &Frame| frame.current_tick.0 == 1));
+    for _ in 0..8 {
+        world.run();
+    }
+    // Ticks 1, 4 and 8.
+    assert_eq!(count(&mut world), 3);
+}
+
+#[test]
+fn on_event_runs_only_on_ticks_with_events() {
+    register_components_once();
+    let mut world = world_with_counter(on_event::<Explosion>());
+    world.run();
+    assert_eq!(count(&mut world), 0);
+
+    world.send_event(Explosion { radius: 2 });
+    world.send_event(Explosion { radius: 5 });
+    let radii: Vec<u32> = world.events::<Explosion>().map(|e| e.radius).collect();
+    assert_eq!(radii, [2, 5]);
+    world.run();
+    assert_eq!(count(&mut world), 1);
+
+    // Events are dropped at the end of the tick they were handled in.
+    assert_eq!(world.events::<Explosion>().count(), 0);
+    world.run();
+    assert_eq!(count(&mut world), 1);
+    assert!(world.verify_invariants());
+}
@@ -0,0 +1,137 @@
+use decs::ecs::Ecs;
+use decs::frame::Frame;
+use decs::scheduler::ScheduleProblem;
+use decs::storage::Storage;
+use decs::system;
+use decs::system::{StorageAccess, System};
+use decs::view::{View, ViewMut};
+use decs::world::World;
+use decs_macros::Component;
+use std::any::{Any, TypeId};
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Heat(u32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Pressure(u32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Stray(u32);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Heat>();
+        Ecs::register::<Pressure>();
+        Ecs::register::<Stray>();
+    });
+}
+
+system!(BoilSystem {
+    query fn update(pressure: View<Pressure>, heat: &mut ViewMut<Heat>) {
+        heat.0 += pressure.0;
+    }
+});
+
+// Together with BoilSystem, a dependency cycle: each writes what the other reads.
+system!(CompressSystem {
+    query fn update(heat: View<Heat>, pressure: &mut ViewMut<Pressure>) {
+        pressure.0 += heat.0;
+    }
+});
+
+system!(StraySystem {
+    query fn update(stray: &mut ViewMut<Stray>) {
+        stray.0 += 1;
+    }
+});
+
+/// Writes Heat without declaring it, after a system that is never scheduled.
+struct SloppySystem {
+    heat: *mut Storage<Heat>,
+}
+
+unsafe impl Send for SloppySystem {}
+unsafe impl Sync for SloppySystem {}
+
+impl System for SloppySystem {
+    fn run(&self, _frame: &Frame) {}
+
+    fn after(&self) -> &[TypeId] {
+        static AFTER: &[TypeId] = &[TypeId::of::<StraySystem>()];
+        AFTER
+    }
+
+    fn accesses(&self) -> Vec<StorageAccess> {
+        vec![StorageAccess::new(self.heat, true)]
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[test]
+fn valid_schedule_passes() {
+    register_components_once();
+    let mut world = World::new();
+    let boil = BoilSystem::new(&mut world);
+    world.scheduler_mut().add_system(boil);
+    world.finalize();
+    assert_eq!(world.scheduler().validate(&world), Ok(()));
+}
+
+#[test]
+fn reports_every_problem_at_once() {
+    register_components_once();
+    let mut other = World::new();
+    let stray = StraySystem::new(&mut other);
+
+    let mut world = World::new();
+    let boil = BoilSystem::new(&mut world);
+    let compress = CompressSystem::new(&mut world);
+    let sloppy = SloppySystem {
+        heat: world.get_storage::<Heat>(),
+    };
+    world.scheduler_mut().add_system(boil);
+    world.scheduler_mut().add_system(compress);
+    world.scheduler_mut().add_system(sloppy);
+    // Built for another world: its Stray storage isn't this one's.
+    let stray_problem = ScheduleProblem::MissingStorage {
+        system: stray.name(),
+        component: std::any::type_name::<Stray>(),
+    };
+
+    let problems = world.scheduler().validate(&world).unwrap_err();
+    assert!(!problems.contains(&stray_problem));
+    assert!(problems.contains(&ScheduleProblem::UndeclaredAccess {
+        system: std::any::type_name::<SloppySystem>(),
+        component: std::any::type_name::<Heat>(),
+        mutable: true,
+    }));
+    assert!(problems.contains(&ScheduleProblem::UnknownOrderTarget {
+        system: std::any::type_name::<SloppySystem>(),
+        target: TypeId::of::<StraySystem>(),
+    }));
+    let cycle = problems
+        .iter()
+        .find_map(|p| match p {
+            ScheduleProblem::DependencyCycle { systems } => Some(systems.clone()),
+            _ => None,
+        })
+        .unwrap();
+    assert!(cycle.contains(&std::any::type_name::<BoilSystem>()));
+    assert!(cycle.contains(&std::any::type_name::<CompressSystem>()));
+
+    world.scheduler_mut().add_system(stray);
+    let problems = world.scheduler().validate(&world).unwrap_err();
+    assert!(problems.contains(&stray_problem));
+    // The target is scheduled now.
+    assert!(
+        !problems
+            .iter()
+            .any(|p| matches!(p, ScheduleProblem::UnknownOrderTarget { .. }))
+    );
+    assert_eq!(problems.len(), 3);
+}
@@ -1,3 +1,5 @@
+#![allow(non_upper_case_globals)]
+
 use decs::ecs::Ecs;
 use decs::frame::Frame;
 use decs::system; // for `system!`
@@ -74,3 +76,160 @@ fn scheduler_can_build_multiple_wavefronts() {
     world.run();
     assert!(world.verify_invariants());
 }
+
+decs_macros::system_group!(PhysicsGroup { Parent=decs::world::SimulationGroup });
+decs_macros::system_group!(ContactGroup { Parent=PhysicsGroup });
+decs_macros::system_group!(ResponseGroup { After=[ContactGroup], Before=[decs::world::CleanupGroup] });
+
+system!(LateWriter { query fn update(pos: &mut ViewMut<Position>) { pos.y = 0.0; } Parent=[decs::world::CleanupGroup] });
+system!(ContactSolver { query fn update(pos: &mut ViewMut<Position>) { pos.y += 1.0; } Parent=[ContactGroup] });
+system!(ResponseSystem { query fn update(pos: &mut ViewMut<Position>) { pos.x += pos.y; } Parent=[ResponseGroup] });
+
+#[test]
+fn nested_groups_inherit_ancestor_ordering() {
+    register_components_once();
+    let mut world = World::new();
+    // Added in reverse of the required order: without the constraints inherited from
+    // SimulationGroup, writers of the same component would run in insertion order.
+    let late = LateWriter::new(&mut world);
+    let response = ResponseSystem::new(&mut world);
+    let contact = ContactSolver::new(&mut world);
+    world.scheduler_mut().add_system(late);
+    world.scheduler_mut().add_system(response);
+    world.scheduler_mut().add_system(contact);
+
+    let order = world.scheduler().resolved_order();
+    let position = |name: &str| order.iter().position(|n| n.contains(name)).unwrap();
+    assert!(position("ContactSolver") < position("ResponseSystem"));
+    assert!(position("ResponseSystem") < position("LateWriter"));
+
+    // Flattening is stable and matches the wavefronts, each sorted by insertion order.
+    world.scheduler_mut().build_wavefronts();
+    assert_eq!(world.scheduler().resolved_order(), order);
+    for wave in world.scheduler().wavefronts() {
+        assert!(wave.is_sorted());
+    }
+
+    let f = world.frame();
+    world
+        .get_storage_mut::<Position>()
+        .set(&f, 0, Position { x: 0.0, y: 0.0 });
+    world.run();
+    let pos = world.get_storage_mut::<Position>().get(0).cloned().unwrap();
+    assert_eq!(pos, Position { x: 1.0, y: 0.0 });
+}
+
+#[test]
+fn disabled_group_turns_off_nested_systems() {
+    register_components_once();
+    let mut world = World::new();
+    // Disabling before the systems are added covers them too.
+    world
+        .scheduler_mut()
+        .set_group_enabled::<PhysicsGroup>(false);
+    let response = ResponseSystem::new(&mut world);
+    let contact = ContactSolver::new(&mut world);
+    world.scheduler_mut().add_system(response);
+    world.scheduler_mut().add_system(contact);
+    world.scheduler_mut().build_wavefronts();
+    let waves = world.scheduler().wavefronts().to_vec();
+    assert!(!world.scheduler().is_group_enabled::<PhysicsGroup>());
+    // Only the disabled group itself reports as disabled.
+    assert!(world.scheduler().is_group_enabled::<ContactGroup>());
+
+    let f = world.frame();
+    world
+        .get_storage_mut::<Position>()
+        .set(&f, 0, Position { x: 0.0, y: 1.0 });
+    world.run();
+    let pos = world.get_storage_mut::<Position>().get(0).cloned().unwrap();
+    assert_eq!(pos, Position { x: 1.0, y: 1.0 });
+
+    world
+        .scheduler_mut()
+        .set_group_enabled::<PhysicsGroup>(true);
+    assert_eq!(world.scheduler().wavefronts(), waves.as_slice());
+    world.run();
+    let pos = world.get_storage_mut::<Position>().get(0).cloned().unwrap();
+    assert_eq!(pos, Position { x: 3.0, y: 2.0 });
+}
+
+#[test]
+fn add_system_schedules_every_instance() {
+    register_components_once();
+    let mut world = World::new();
+    {
+        let f = Frame::new(world.current_tick());
+        let pos = world.get_storage_mut::<Position>();
+        pos.set(&f, 0, Position { x: 0.0, y: 0.0 });
+        let vel = world.get_storage_mut::<Velocity>();
+        vel.set(&f, 0, Velocity { x: 1.0, y: 1.0 });
+    }
+    let len = world.scheduler().len();
+    let s1 = SimSystem::new(&mut world);
+    let s2 = SimSystem::new(&mut world);
+    world.scheduler_mut().add_system(s1);
+    world.scheduler_mut().add_system(s2);
+    assert_eq!(world.scheduler().len(), len + 2);
+    world.scheduler_mut().build_wavefronts();
+    world.run();
+
+    let pos_ptr = world.get_storage::<Position>();
+    let p = unsafe { (*pos_ptr).get(0).unwrap() };
+    assert_eq!(p.x, 2.0);
+}
+
+#[test]
+fn adding_a_unique_system_type_again_replaces_it() {
+    register_components_once();
+    let mut world = World::new();
+    {
+        let f = Frame::new(world.current_tick());
+        let pos = world.get_storage_mut::<Position>();
+        pos.set(&f, 0, Position { x: 0.0, y: 0.0 });
+        let vel = world.get_storage_mut::<Velocity>();
+        vel.set(&f, 0, Velocity { x: 1.0, y: 1.0 });
+    }
+    let len = world.scheduler().len();
+    let s1 = SimSystem::new(&mut world);
+    let s2 = SimSystem::new(&mut world);
+    world.scheduler_mut().add_system_unique(s1);
+    world.scheduler_mut().add_system_unique(s2);
+    assert_eq!(world.scheduler().len(), len + 1);
+    world.scheduler_mut().build_wavefronts();
+    world.run();
+
+    let pos_ptr = world.get_storage::<Position>();
+    let p = unsafe { (*pos_ptr).get(0).unwrap() };
+    assert_eq!(p.x, 1.0);
+}
+
+#[test]
+fn reject_policy_refuses_a_scheduled_system_type() {
+    register_components_once();
+    let mut world = World::new();
+    world
+        .scheduler_mut()
+        .set_duplicate_policy(decs::scheduler::DuplicatePolicy::Reject);
+    let s1 = NoopSystem::new(&mut world);
+    let s2 = NoopSystem::new(&mut world);
+    world.scheduler_mut().add_system_unique(s1);
+    let len = world.scheduler().len();
+    let err = world.scheduler_mut().try_add_system_unique(s2).unwrap_err();
+    assert!(matches!(err, decs::error::Error::DuplicateSystem { .. }));
+    assert_eq!(world.scheduler().len(), len);
+}
+
+#[test]
+#[should_panic(expected = "is already scheduled")]
+fn add_system_panics_on_a_rejected_duplicate() {
+    register_components_once();
+    let mut world = World::new();
+    world
+        .scheduler_mut()
+        .set_duplicate_policy(decs::scheduler::DuplicatePolicy::Reject);
+    let s1 = NoopSystem::new(&mut world);
+    let s2 = NoopSystem::new(&mut world);
+    world.scheduler_mut().add_system_unique(s1);
+    world.scheduler_mut().add_system_unique(s2);
+}
@@ -478,3 +478,76 @@ fn independent_components_merge_wavefronts() {
     world.scheduler_mut().build_wavefronts();
     world.run();
 }
+
+#[test]
+fn readers_of_one_writer_run_after_it() {
+    register_components_once();
+    let mut world = World::new();
+    let f = Frame::new(world.current_tick());
+    let pos = world.get_storage_mut::<Position>();
+    pos.set(&f, 0, Position { x: 0.0, y: 0.0 });
+    let order = Arc::new(Mutex::new([0; 32]));
+    let step = Arc::new(AtomicU32::new(0));
+    world.scheduler_mut().add_system(ReadPosA {
+        order: order.clone(),
+        step: step.clone(),
+    });
+    world.scheduler_mut().add_system(ReadPosB {
+        order: order.clone(),
+        step: step.clone(),
+    });
+    world.scheduler_mut().add_system(WriteVel {
+        order: order.clone(),
+        step: step.clone(),
+    });
+    world.scheduler_mut().add_system(WritePos {
+        order: order.clone(),
+        step: step.clone(),
+    });
+    world.scheduler_mut().build_wavefronts();
+    world.run();
+    let o = order.lock().unwrap();
+    let at = |tag: u32| o[1..5].iter().position(|&v| v == tag).unwrap();
+    assert!(at(100) < at(200));
+    assert!(at(100) < at(300));
+    assert!(o[1..5].contains(&400));
+}
+
+system!(Integrate {
+    query fn update(pos: &mut ViewMut<Position>, vel: View<Velocity>) {
+        pos.x += vel.x;
+        pos.y += vel.y;
+    }
+});
+
+#[test]
+fn query_system_runs_between_hand_written_writer_and_reader() {
+    register_components_once();
+    let mut world = World::new();
+    let f = Frame::new(world.current_tick());
+    world
+        .get_storage_mut::<Position>()
+        .set(&f, 0, Position { x: 0.0, y: 0.0 });
+    world
+        .get_storage_mut::<Velocity>()
+        .set(&f, 0, Velocity { x: 1.0, y: 2.0 });
+    let order = Arc::new(Mutex::new([0; 32]));
+    let step = Arc::new(AtomicU32::new(0));
+    let integrate = Integrate::new(&mut world);
+    world.scheduler_mut().add_system(ReadPosB {
+        order: order.clone(),
+        step: step.clone(),
+    });
+    world.scheduler_mut().add_system(integrate);
+    world.scheduler_mut().add_system(WriteVel {
+        order: order.clone(),
+        step: step.clone(),
+    });
+    world.scheduler_mut().build_wavefronts();
+    assert!(world.scheduler().wavefronts().len() >= 3);
+    world.run();
+    let o = order.lock().unwrap();
+    assert!(o[1] == 400 && o[2] == 300);
+    let pos = world.get_storage_mut::<Position>().get(0).cloned();
+    assert_eq!(pos, Some(Position { x: 1.0, y: 2.0 }));
+}
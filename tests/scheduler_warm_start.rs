@@ -0,0 +1,154 @@
+use decs::ecs::Ecs;
+use decs::error::Error;
+use decs::scheduler::SystemOrder;
+use decs::system;
+use decs::view::{View, ViewMut};
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Position(i32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Velocity(i32);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Position>();
+        Ecs::register::<Velocity>();
+    });
+}
+
+system!(WritePos { query
+    fn update(pos: &mut ViewMut<Position>)
+    {
+        pos.0 += 1;
+    }
+});
+
+system!(ReadPos { query
+    fn update(pos: View<Position>)
+    {
+        let _ = pos.0;
+    }
+});
+
+system!(Damp { query
+    fn update(vel: &mut ViewMut<Velocity>)
+    {
+        vel.0 /= 2;
+    }
+});
+
+/// A world scheduling `WritePos`, `ReadPos` and `Damp` next to the built-in systems.
+fn world() -> World {
+    register_components_once();
+    let mut world = World::new();
+    let write = WritePos::new(&mut world);
+    let read = ReadPos::new(&mut world);
+    let damp = Damp::new(&mut world);
+    world.scheduler_mut().add_system(write);
+    world.scheduler_mut().add_system(read);
+    world.scheduler_mut().add_system(damp);
+    world.finalize();
+    world
+}
+
+fn position(names: &[String], system: &str) -> usize {
+    names
+        .iter()
+        .position(|name| name.ends_with(system))
+        .unwrap()
+}
+
+/// The saved order with `Damp` moved to run right after `ReadPos`.
+fn damp_after_read(world: &World) -> Vec<String> {
+    let mut names = world.scheduler().save_order().names().to_vec();
+    let damp = names.remove(position(&names, "::Damp"));
+    names.insert(position(&names, "::ReadPos") + 1, damp);
+    names
+}
+
+#[test]
+fn saved_order_round_trips_with_a_stable_hash() {
+    let mut world = world();
+    let saved = world.scheduler().save_order();
+    assert_eq!(saved.names().len(), world.scheduler().len());
+    assert_eq!(
+        saved.hash(),
+        SystemOrder::new(saved.names().to_vec()).hash()
+    );
+
+    world.scheduler_mut().load_order(&saved).unwrap();
+    assert_eq!(world.scheduler().save_order(), saved);
+    assert_eq!(world.scheduler().save_order().hash(), saved.hash());
+
+    let mut swapped = saved.names().to_vec();
+    swapped.swap(0, 1);
+    assert_ne!(SystemOrder::new(swapped).hash(), saved.hash());
+}
+
+#[test]
+fn loaded_order_is_kept_until_a_system_is_added() {
+    let mut world = world();
+    let saved = world.scheduler().save_order();
+    assert!(position(saved.names(), "::Damp") < position(saved.names(), "::ReadPos"));
+
+    let order = SystemOrder::new(damp_after_read(&world));
+    world.scheduler_mut().load_order(&order).unwrap();
+    assert_eq!(world.scheduler().save_order(), order);
+    world.scheduler_mut().build_wavefronts();
+    world.run();
+    assert_eq!(world.scheduler().save_order(), order);
+
+    let extra = WritePos::new(&mut world);
+    world.scheduler_mut().add_system(extra);
+    world.scheduler_mut().build_wavefronts();
+    let rebuilt = world.scheduler().save_order();
+    assert!(position(rebuilt.names(), "::Damp") < position(rebuilt.names(), "::ReadPos"));
+}
+
+#[test]
+fn orders_that_do_not_fit_the_schedule_are_rejected() {
+    let mut world = world();
+    let saved = world.scheduler().save_order().names().to_vec();
+    let write = position(&saved, "::WritePos");
+    let read = position(&saved, "::ReadPos");
+    let damp = position(&saved, "::Damp");
+    let before = world.scheduler().wavefronts().to_vec();
+
+    let mismatched = [
+        saved[1..].to_vec(),
+        [saved.clone(), vec!["Gone".to_string()]].concat(),
+        {
+            let mut unknown = saved.clone();
+            unknown[damp] = "Gone".to_string();
+            unknown
+        },
+        {
+            let mut twice = saved.clone();
+            twice[damp] = saved[write].clone();
+            twice
+        },
+    ];
+    for names in mismatched {
+        assert_eq!(
+            world.scheduler_mut().load_order(&SystemOrder::new(names)),
+            Err(Error::SystemOrderMismatch)
+        );
+    }
+
+    let mut reads_first = saved.clone();
+    reads_first.swap(write, read);
+    let Err(Error::SystemOrderViolated { system, dependency }) = world
+        .scheduler_mut()
+        .load_order(&SystemOrder::new(reads_first))
+    else {
+        panic!("reading before the write must be rejected");
+    };
+    assert!(system.ends_with("::ReadPos"));
+    assert!(dependency.ends_with("::WritePos"));
+    assert_eq!(world.scheduler().wavefronts(), before);
+}
@@ -0,0 +1,89 @@
+#![cfg(feature = "shadow-world")]
+
+use decs::ecs::Ecs;
+use decs::storage::Chunk;
+use decs::system;
+use decs::view::{View, ViewMut};
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+#[component(diff)]
+struct Hp(u32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Regen(u32);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Hp>();
+        Ecs::register::<Regen>();
+    });
+}
+
+system!(RegenSystem { query
+    fn update(regen: View<Regen>, hp: &mut ViewMut<Hp>)
+    {
+        hp.0 += regen.0;
+    }
+});
+
+fn world() -> World {
+    register_components_once();
+    let mut world = World::new();
+    let system = RegenSystem::new(&mut world);
+    world.scheduler_mut().add_system(system);
+    world.finalize();
+    let f = world.frame();
+    world.get_storage_mut::<Hp>().set(&f, 1, Hp(10));
+    world.get_storage_mut::<Hp>().set(&f, 70, Hp(20));
+    world.get_storage_mut::<Regen>().set(&f, 1, Regen(5));
+    world
+}
+
+fn chunk_of(world: &mut World, index: u32) -> &mut Chunk<Hp> {
+    let storage = world.get_storage_mut::<Hp>();
+    let page = unsafe { &*storage.data[(index >> 12) as usize] };
+    unsafe { &mut *page.data[((index >> 6) & 63) as usize] }
+}
+
+#[test]
+fn consistent_storages_pass_the_shadow_checks() {
+    let mut world = world();
+    world.run();
+    let start = world.current_tick();
+    world.run();
+    let f = world.frame();
+    world.get_storage_mut::<Hp>().get_mut(&f, 70).unwrap().0 = 25;
+    world.get_storage_mut::<Hp>().remove(&f, 1);
+    world.run();
+
+    world.rollback(start);
+    assert_eq!(world.get_storage_mut::<Hp>().get(1), Some(&Hp(15)));
+    assert_eq!(world.get_storage_mut::<Hp>().get(70), Some(&Hp(20)));
+    world.run();
+    world.run();
+    assert_eq!(world.get_storage_mut::<Hp>().get(1), Some(&Hp(25)));
+}
+
+#[test]
+#[should_panic(expected = "shadow mismatch in shadow_world::Hp at index 70")]
+fn values_written_without_marking_them_changed_are_caught() {
+    let mut world = world();
+    world.run();
+    unsafe { chunk_of(&mut world, 70).data[6].assume_init_mut().0 = 99 };
+    world.run();
+}
+
+#[test]
+#[should_panic(expected = "the shadow has a value the storage lacks")]
+fn values_lost_from_the_presence_masks_are_caught() {
+    let mut world = world();
+    world.run();
+    // Leaks the value, which the panic makes moot.
+    chunk_of(&mut world, 70).presence_mask &= !(1 << 6);
+    chunk_of(&mut world, 70).fullness_mask &= !(1 << 6);
+    world.run();
+}
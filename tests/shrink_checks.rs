@@ -0,0 +1,90 @@
+use decs::ecs::Ecs;
+use decs::storage::Storage;
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Mass(u32);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Mass>();
+    });
+}
+
+/// A world at tick 1 with `Mass` at 0 and 1 (one chunk), 64 and 128 (two more chunks of
+/// the same page) and 4096 (a second page).
+fn populated_world() -> &'static mut World {
+    register_components_once();
+    // Leaked: the corrupted storages below must not be dropped while unwinding.
+    let world = Box::leak(Box::new(World::new()));
+    world.finalize();
+    let f = world.frame();
+    for index in [0, 1, 64, 128, 4096] {
+        world.get_storage_mut::<Mass>().set(&f, index, Mass(index));
+    }
+    world.run();
+    world
+}
+
+/// Clears the presence bit of chunk 0/1 while keeping the chunk, as a free path that
+/// forgets to release it would.
+fn leak_second_chunk(storage: &mut Storage<Mass>) {
+    let page = unsafe { &mut *storage.data[0] };
+    unsafe { (*page.data[1]).presence_mask = 0 };
+    page.presence_mask &= !0b10;
+    page.count -= 1;
+    storage.count -= 1;
+}
+
+#[test]
+fn removing_and_rolling_back_frees_emptied_chunks_and_pages() {
+    let world = populated_world();
+    let before = world.current_tick();
+    world.advance_tick();
+    let f = world.frame();
+    let storage = world.get_storage_mut::<Mass>();
+    for index in [0, 1, 4096] {
+        assert!(storage.remove(&f, index));
+    }
+    storage.assert_shrunk();
+    assert_eq!(storage.presence_mask, 1);
+
+    world.rollback(before);
+    let storage = world.get_storage_mut::<Mass>();
+    assert_eq!(storage.get(4096), Some(&Mass(4096)));
+    storage.assert_shrunk();
+
+    // Undoing creations frees what they allocated.
+    world.advance_tick();
+    let f = world.frame();
+    world.get_storage_mut::<Mass>().set(&f, 8192, Mass(0));
+    world.rollback(before);
+    let storage = world.get_storage_mut::<Mass>();
+    assert_eq!(storage.presence_mask, 0b11);
+    storage.assert_shrunk();
+}
+
+#[test]
+#[should_panic(expected = "chunk 0/1 of shrink_checks::Mass is empty but was not freed")]
+fn a_chunk_left_allocated_fails_the_check() {
+    let world = populated_world();
+    let storage = world.get_storage_mut::<Mass>();
+    leak_second_chunk(storage);
+    storage.assert_shrunk_page(0);
+}
+
+// The shadow storage would catch the corruption first.
+#[cfg(all(feature = "shrink-checks", not(feature = "shadow-world")))]
+#[test]
+#[should_panic(expected = "is empty but was not freed")]
+fn remove_checks_the_page_it_freed_a_chunk_of() {
+    let world = populated_world();
+    leak_second_chunk(world.get_storage_mut::<Mass>());
+    let f = world.frame();
+    let storage = world.get_storage_mut::<Mass>();
+    storage.remove(&f, 0);
+    storage.remove(&f, 1);
+}
@@ -0,0 +1,129 @@
+use decs::ecs::Ecs;
+use decs::error::Error;
+use decs::net::world_checksum;
+use decs::scheduler::ErrorPolicy;
+use decs::system;
+use decs::system::SystemError;
+use decs::tick::Tick;
+use decs::view::{View, ViewMut};
+use decs::world::{SimulateOptions, World};
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Fuel(u32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Burn(u32);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Fuel>();
+        Ecs::register::<Burn>();
+    });
+}
+
+system!(BurnSystem { query
+    fn update(burn: View<Burn>, fuel: &mut ViewMut<Fuel>) -> Result<(), SystemError>
+    {
+        if fuel.0 < burn.0 {
+            return Err("out of fuel".into());
+        }
+        fuel.0 -= burn.0;
+        Ok(())
+    }
+});
+
+fn world() -> World {
+    register_components_once();
+    let mut world = World::new();
+    let system = BurnSystem::new(&mut world);
+    world.scheduler_mut().add_system(system);
+    world.finalize();
+    let f = world.frame();
+    world.get_storage_mut::<Fuel>().set(&f, 0, Fuel(100));
+    world.get_storage_mut::<Burn>().set(&f, 0, Burn(1));
+    world
+}
+
+/// Burns faster from tick 4 on.
+fn throttle(world: &mut World, tick: Tick) {
+    if tick == Tick(4) {
+        let f = world.frame();
+        world.get_storage_mut::<Burn>().set(&f, 0, Burn(10));
+    }
+}
+
+#[test]
+fn simulate_matches_a_manual_loop() {
+    let mut manual = world();
+    let mut checksums = Vec::new();
+    for _ in 0..6 {
+        let tick = manual.current_tick().next();
+        throttle(&mut manual, tick);
+        manual.run();
+        checksums.push(world_checksum(&manual));
+    }
+
+    let mut simulated = world();
+    let mut seen = Vec::new();
+    let options = SimulateOptions {
+        checksums: true,
+        ..SimulateOptions::default()
+    };
+    let simulation = simulated
+        .simulate_with(6, options, |world, tick| {
+            seen.push(tick);
+            throttle(world, tick);
+        })
+        .unwrap();
+    assert_eq!(simulation.first, Tick(1));
+    assert_eq!(simulation.ticks, 6);
+    assert_eq!(seen, (1..=6).map(Tick).collect::<Vec<_>>());
+    assert_eq!(simulation.checksums, checksums);
+    assert_eq!(simulated.get_component::<Fuel>(0), Some(&Fuel(67)));
+
+    // Without options nothing is recorded.
+    let simulation = simulated.simulate(2, |_, _| {}).unwrap();
+    assert_eq!(simulation.first, Tick(7));
+    assert!(simulation.checksums.is_empty());
+}
+
+#[test]
+fn simulate_confirms_and_collects_old_history() {
+    let mut world = world();
+    let options = SimulateOptions {
+        confirm_lag: Some(3),
+        garbage_budget: usize::MAX,
+        ..SimulateOptions::default()
+    };
+    world.simulate_with(10, options, |_, _| {}).unwrap();
+    assert_eq!(world.confirmed_tick(), Some(Tick(7)));
+    assert_eq!(world.pending_garbage().collectable(), 0);
+    world.rollback(Tick(8));
+    assert_eq!(world.get_component::<Fuel>(0), Some(&Fuel(92)));
+}
+
+#[test]
+fn simulate_stops_at_a_failing_tick() {
+    let mut world = world();
+    world
+        .scheduler_mut()
+        .set_error_policy(ErrorPolicy::AbortTick);
+    let f = world.frame();
+    world.get_storage_mut::<Fuel>().set(&f, 0, Fuel(25));
+    let mut calls = 0;
+    let result = world.simulate(20, |world, tick| {
+        calls += 1;
+        throttle(world, tick);
+    });
+    // Ticks 1-3 burn 3, ticks 4 and 5 burn 20; tick 6 runs out.
+    match result {
+        Err(Error::SystemFailed { tick, .. }) => assert_eq!(tick, Tick(6)),
+        other => panic!("expected SystemFailed, got {:?}", other),
+    }
+    assert_eq!(calls, 6);
+    assert_eq!(world.current_tick(), Tick(5));
+    assert_eq!(world.get_component::<Fuel>(0), Some(&Fuel(2)));
+}
@@ -0,0 +1,138 @@
+#![cfg(all(feature = "bincode", feature = "postcard"))]
+use decs::codec::{self, Bincode, Postcard, SnapshotCodec};
+use decs::ecs::Ecs;
+use decs::migrate::{Migrate, unsupported_version};
+use decs::rng::DeterministicRng;
+use decs::world::World;
+use decs_macros::Component;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component, Serialize, Deserialize)]
+struct Position {
+    x: i32,
+    y: i32,
+}
+
+/// `Armor` as version 1 wrote it: whole points.
+#[derive(Clone, Debug, PartialEq, Component, Serialize, Deserialize)]
+struct ArmorV1(u32);
+
+/// Armor in tenths of a point.
+#[derive(Clone, Debug, PartialEq, Component, Serialize, Deserialize)]
+#[component(version = 2)]
+struct Armor(u32);
+
+impl Migrate for Armor {
+    fn migrate<'de, D: Deserializer<'de>>(version: u32, data: D) -> Result<Self, D::Error> {
+        match version {
+            1 => ArmorV1::deserialize(data).map(|old| Armor(old.0 * 10)),
+            _ => Err(unsupported_version::<Self, D::Error>(version)),
+        }
+    }
+}
+
+impl Migrate for Position {
+    fn migrate<'de, D: Deserializer<'de>>(version: u32, _data: D) -> Result<Self, D::Error> {
+        Err(unsupported_version::<Self, D::Error>(version))
+    }
+}
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Position>();
+        Ecs::register::<ArmorV1>();
+        Ecs::register::<Armor>();
+        Ecs::register::<DeterministicRng>();
+    });
+}
+
+fn positions(world: &mut World) -> Vec<(u32, Position)> {
+    let storage = world.get_storage_mut::<Position>();
+    let mut out = Vec::new();
+    let mut cursor = storage.cursor();
+    while let Some(index) = cursor.next(storage) {
+        out.push((index, storage.get(index).unwrap().clone()));
+    }
+    out
+}
+
+fn round_trip<C: SnapshotCodec>(codec: &C) -> usize {
+    let mut source = World::new();
+    let f = source.frame();
+    for index in [0, 63, 64, 5000] {
+        let value = Position {
+            x: index as i32,
+            y: -1,
+        };
+        source.get_storage_mut::<Position>().set(&f, index, value);
+    }
+    let bytes = codec::encode_storage(codec, source.get_storage_mut::<Position>()).unwrap();
+
+    // Values missing from the data are removed, the others overwritten.
+    let mut target = World::new();
+    let f = target.frame();
+    target
+        .get_storage_mut::<Position>()
+        .set(&f, 7, Position { x: 0, y: 0 });
+    target
+        .get_storage_mut::<Position>()
+        .set(&f, 63, Position { x: 0, y: 0 });
+    codec::decode_storage(codec, target.get_storage_mut::<Position>(), &f, &bytes).unwrap();
+    assert_eq!(positions(&mut target), positions(&mut source));
+    assert!(target.verify_invariants());
+    bytes.len()
+}
+
+#[test]
+fn storages_round_trip_through_both_codecs() {
+    register_components_once();
+    let bincode_len = round_trip(&Bincode);
+    let postcard_len = round_trip(&Postcard);
+    assert!(
+        postcard_len < bincode_len,
+        "{} >= {}",
+        postcard_len,
+        bincode_len
+    );
+}
+
+#[test]
+fn older_versions_are_migrated_and_bad_data_is_rejected() {
+    register_components_once();
+    let mut world = World::new();
+    let f = world.frame();
+    world.get_storage_mut::<ArmorV1>().set(&f, 3, ArmorV1(4));
+    let old = codec::encode_storage(&Postcard, world.get_storage_mut::<ArmorV1>()).unwrap();
+
+    world.get_storage_mut::<Armor>().set(&f, 9, Armor(1));
+    codec::decode_storage(&Postcard, world.get_storage_mut::<Armor>(), &f, &old).unwrap();
+    assert_eq!(world.get_storage_mut::<Armor>().get(3), Some(&Armor(40)));
+    assert_eq!(world.get_storage_mut::<Armor>().get(9), None);
+
+    // Truncated data fails before the storage is touched.
+    let current = codec::encode_storage(&Bincode, world.get_storage_mut::<Armor>()).unwrap();
+    let truncated = &current[..current.len() - 1];
+    assert!(
+        codec::decode_storage(&Bincode, world.get_storage_mut::<Armor>(), &f, truncated).is_err()
+    );
+    assert_eq!(world.get_storage_mut::<Armor>().get(3), Some(&Armor(40)));
+}
+
+#[test]
+fn header_carries_the_tick_and_seed() {
+    register_components_once();
+    let mut world = World::new_with_seed(42);
+    world.run();
+    let bytes = codec::encode_header(&Postcard, &world).unwrap();
+    let header = codec::decode_header(&Postcard, &bytes).unwrap();
+    assert_eq!(header.tick, world.current_tick());
+    assert_eq!(header.seed, Some(42));
+    assert_eq!(header.new_world().seed(), Some(42));
+
+    let bytes = codec::encode_header(&Bincode, &World::new()).unwrap();
+    let header = codec::decode_header(&Bincode, &bytes).unwrap();
+    assert_eq!(header.seed, None);
+    assert_eq!(header.new_world().seed(), None);
+}
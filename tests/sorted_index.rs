@@ -0,0 +1,101 @@
+use decs::ecs::Ecs;
+use decs::sorted::{SortedIndex, SortedIndexSystem};
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Component)]
+struct Layer(i32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Sprite(&'static str);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Layer>();
+        Ecs::register::<Sprite>();
+    });
+}
+
+fn set_layer(world: &mut World, index: u32, layer: i32) {
+    let frame = world.frame();
+    world
+        .get_storage_mut::<Layer>()
+        .set(&frame, index, Layer(layer));
+}
+
+fn sorted(world: &World) -> &SortedIndex<Layer> {
+    world
+        .scheduler()
+        .get_system::<SortedIndexSystem<Layer>>()
+        .unwrap()
+        .index()
+}
+
+fn order(world: &World) -> Vec<u32> {
+    sorted(world).iter().collect()
+}
+
+#[test]
+fn index_orders_by_key_then_index() {
+    let mut sorted = SortedIndex::new();
+    sorted.insert(4, 2);
+    sorted.insert(1, 5);
+    sorted.insert(9, 2);
+    sorted.insert(3, -1);
+    assert_eq!(sorted.iter().collect::<Vec<_>>(), vec![3, 4, 9, 1]);
+
+    sorted.insert(1, 0);
+    assert!(sorted.remove(4));
+    assert!(!sorted.remove(4));
+    assert_eq!(
+        sorted.iter_with_keys().collect::<Vec<_>>(),
+        vec![(3, &-1), (1, &0), (9, &2)]
+    );
+    assert_eq!(sorted.iter().next_back(), Some(9));
+    assert_eq!(sorted.key(1), Some(&0));
+    assert_eq!(sorted.len(), 3);
+}
+
+#[test]
+fn system_tracks_changes_and_rollback() {
+    register_components_once();
+    let mut world = World::new();
+    let system = SortedIndexSystem::<Layer>::new(&mut world);
+    world.scheduler_mut().add_system(system);
+    world.finalize();
+
+    let t0 = world.current_tick();
+    set_layer(&mut world, 0, 3);
+    set_layer(&mut world, 70, 1);
+    set_layer(&mut world, 5000, 2);
+    let frame = world.frame();
+    let sprites = world.get_storage_mut::<Sprite>();
+    sprites.set(&frame, 0, Sprite("hud"));
+    sprites.set(&frame, 70, Sprite("ground"));
+    world.run();
+    assert_eq!(order(&world), vec![70, 5000, 0]);
+
+    // Only indices with both components are yielded, in layer order.
+    let sprites = unsafe { &*world.get_storage::<Sprite>() };
+    let drawn: Vec<_> = sprites
+        .iter_sorted_by(sorted(&world))
+        .map(|(index, sprite)| (index, sprite.0))
+        .collect();
+    assert_eq!(drawn, vec![(70, "ground"), (0, "hud")]);
+
+    // Incremental updates: a key change, a removal and an insert.
+    set_layer(&mut world, 0, 0);
+    let frame = world.frame();
+    world.get_storage_mut::<Layer>().remove(&frame, 5000);
+    set_layer(&mut world, 9, 1);
+    world.run();
+    assert_eq!(order(&world), vec![0, 9, 70]);
+    assert_eq!(sorted(&world).key(0), Some(&Layer(0)));
+
+    // A rollback rebuilds the order of the restored tick.
+    world.rollback(t0);
+    world.run();
+    assert_eq!(order(&world), vec![70, 5000, 0]);
+}
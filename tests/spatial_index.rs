@@ -0,0 +1,94 @@
+use decs::ecs::Ecs;
+use decs::spatial::{SpatialGrid, SpatialIndexSystem, SpatialPosition};
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Pos {
+    x: f32,
+    y: f32,
+}
+
+impl SpatialPosition for Pos {
+    fn spatial_position(&self) -> [f32; 2] {
+        [self.x, self.y]
+    }
+}
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Pos>();
+    });
+}
+
+fn set(world: &mut World, index: u32, x: f32, y: f32) {
+    let frame = world.frame();
+    world
+        .get_storage_mut::<Pos>()
+        .set(&frame, index, Pos { x, y });
+}
+
+fn grid(world: &World) -> &SpatialGrid {
+    world
+        .scheduler()
+        .get_system::<SpatialIndexSystem<Pos>>()
+        .unwrap()
+        .grid()
+}
+
+#[test]
+fn grid_queries() {
+    let mut grid = SpatialGrid::new(4.0);
+    grid.insert(1, [0.5, 0.5]);
+    grid.insert(2, [3.0, 0.0]);
+    grid.insert(3, [-5.0, 9.0]);
+    grid.insert(4, [100.0, 100.0]);
+    assert_eq!(grid.query_aabb([-10.0, -1.0], [3.0, 10.0]), vec![1, 2, 3]);
+    assert_eq!(grid.query_radius([0.0, 0.0], 1.0), vec![1]);
+    assert_eq!(grid.query_radius([0.0, 0.0], 3.0), vec![1, 2]);
+
+    grid.insert(4, [1.0, 1.0]);
+    assert_eq!(grid.query_radius([0.0, 0.0], 2.0), vec![1, 4]);
+    assert!(grid.remove(1));
+    assert!(!grid.remove(1));
+    assert_eq!(grid.query_radius([0.0, 0.0], 2.0), vec![4]);
+    assert_eq!(grid.len(), 3);
+}
+
+#[test]
+fn system_tracks_storage_and_rollback() {
+    register_components_once();
+    let mut world = World::new();
+    let system = SpatialIndexSystem::<Pos>::new(&mut world, 8.0);
+    world.scheduler_mut().add_system(system);
+    world.scheduler_mut().build_wavefronts();
+
+    // Writes between runs belong to the current tick, so rolling back to it keeps them.
+    let t0 = world.current_tick();
+    set(&mut world, 0, 1.0, 1.0);
+    set(&mut world, 70, 20.0, 20.0);
+    set(&mut world, 5000, -3.0, 2.0);
+    world.run();
+    assert_eq!(grid(&world).query_radius([0.0, 0.0], 5.0), vec![0, 5000]);
+
+    // Incremental updates: a move, a removal and an insert.
+    set(&mut world, 70, 2.0, 0.0);
+    let frame = world.frame();
+    world.get_storage_mut::<Pos>().remove(&frame, 5000);
+    set(&mut world, 9, 50.0, 50.0);
+    world.run();
+    assert_eq!(grid(&world).query_radius([0.0, 0.0], 5.0), vec![0, 70]);
+    assert_eq!(grid(&world).len(), 3);
+
+    // A run with no changes keeps the grid.
+    world.run();
+    assert_eq!(grid(&world).query_aabb([40.0, 40.0], [60.0, 60.0]), vec![9]);
+
+    world.rollback(t0);
+    world.run();
+    assert_eq!(grid(&world).query_radius([0.0, 0.0], 5.0), vec![0, 5000]);
+    assert_eq!(grid(&world).position(70), Some([20.0, 20.0]));
+    assert_eq!(grid(&world).position(9), None);
+}
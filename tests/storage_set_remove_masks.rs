@@ -1,5 +1,6 @@
 use decs::ecs::Ecs;
 use decs::frame::Frame;
+use decs::storage::StorageLike;
 use decs::system; // for `system!`
 use decs::view::View;
 use decs::world::World;
@@ -17,11 +18,18 @@ struct TestC {
     v: i32,
 }
 
+#[derive(Clone, Debug, PartialEq, Component)]
+#[component(change_detection = false)]
+struct Untracked {
+    v: i32,
+}
+
 static INIT: Once = Once::new();
 fn register_components_once() {
     INIT.call_once(|| {
         Ecs::register::<Position>();
         Ecs::register::<TestC>();
+        Ecs::register::<Untracked>();
     });
 }
 
@@ -43,6 +51,22 @@ fn set_creates_page_chunk_and_marks_masks() {
     assert!(world.verify_invariants());
 }
 
+#[test]
+fn try_set_out_of_range_returns_error_without_mutation() {
+    register_components_once();
+    let mut world = World::new();
+    let frame = Frame::new(world.current_tick());
+    let s = world.get_storage_mut::<TestC>();
+    assert_eq!(
+        s.try_set(&frame, 64 * 64 * 64, TestC { v: 1 }),
+        Err(decs::error::Error::IndexOutOfRange(64 * 64 * 64))
+    );
+    assert_eq!(s.count, 0);
+    assert_eq!(s.try_set(&frame, 4097, TestC { v: 2 }), Ok(()));
+    assert_eq!(s.get(4097).unwrap().v, 2);
+    assert!(world.verify_invariants());
+}
+
 #[test]
 fn remove_nonexistent_returns_false() {
     register_components_once();
@@ -211,3 +235,348 @@ fn presence_fullness_invariants_after_many_sets_and_removes() {
     world.scheduler_mut().build_wavefronts();
     world.run();
 }
+
+fn assert_same_layout(a: &decs::storage::Storage<TestC>, b: &decs::storage::Storage<TestC>) {
+    assert_eq!(a.presence_mask, b.presence_mask);
+    assert_eq!(a.fullness_mask, b.fullness_mask);
+    assert_eq!(a.changed_mask, b.changed_mask);
+    assert_eq!(a.count, b.count);
+    let mut pages = a.presence_mask;
+    while pages != 0 {
+        let si = pages.trailing_zeros() as usize;
+        pages &= pages - 1;
+        let (pa, pb) = unsafe { (&*a.data[si], &*b.data[si]) };
+        assert_eq!(pa.presence_mask, pb.presence_mask, "page {si}");
+        assert_eq!(pa.fullness_mask, pb.fullness_mask, "page {si}");
+        assert_eq!(pa.changed_mask, pb.changed_mask, "page {si}");
+        assert_eq!(pa.count, pb.count, "page {si}");
+        let mut chunks = pa.presence_mask;
+        while chunks != 0 {
+            let pi = chunks.trailing_zeros() as usize;
+            chunks &= chunks - 1;
+            let (ca, cb) = unsafe { (&*pa.data[pi], &*pb.data[pi]) };
+            assert_eq!(ca.presence_mask, cb.presence_mask, "chunk {si}/{pi}");
+            assert_eq!(ca.fullness_mask, cb.fullness_mask, "chunk {si}/{pi}");
+            assert_eq!(ca.changed_mask, cb.changed_mask, "chunk {si}/{pi}");
+        }
+    }
+    for i in 0..64 * 64 * 64 {
+        assert_eq!(a.get(i), b.get(i), "value at {i}");
+    }
+}
+
+#[test]
+fn writer_matches_set_including_rollback() {
+    register_components_once();
+    let mut by_set = decs::storage::Storage::<TestC>::new();
+    let mut by_writer = decs::storage::Storage::<TestC>::new();
+
+    let f1 = Frame::new(decs::tick::Tick(1));
+    for i in [3u32, 64, 5000, 70000] {
+        by_set.set(&f1, i, TestC { v: i as i32 });
+        by_writer.set(&f1, i, TestC { v: i as i32 });
+    }
+    assert!(by_set.remove(&f1, 64));
+    assert!(by_writer.remove(&f1, 64));
+    by_set.clear_changed_masks();
+    by_writer.clear_changed_masks();
+
+    // Fill a whole page (hits chunk/page/storage fullness), overwrite old values,
+    // revive a removed slot and jump back and forth between chunks.
+    let f2 = Frame::new(decs::tick::Tick(2));
+    let indices: Vec<u32> = (4096..8192).chain([3, 64, 70000, 5, 70001, 4]).collect();
+    for &i in &indices {
+        by_set.set(&f2, i, TestC { v: -(i as i32) });
+    }
+    {
+        let mut w = by_writer.writer(&f2);
+        for &i in &indices {
+            w.set(i, TestC { v: -(i as i32) });
+        }
+        assert_eq!(w.get(4), Some(&TestC { v: -4 }));
+        assert_eq!(w.get(6), None);
+    }
+    assert_ne!(by_writer.fullness_mask, 0);
+    assert_same_layout(&by_set, &by_writer);
+
+    by_set.rollback(decs::tick::Tick(1));
+    by_writer.rollback(decs::tick::Tick(1));
+    assert_same_layout(&by_set, &by_writer);
+    assert_eq!(by_writer.count, 3);
+    assert_eq!(by_writer.get(3), Some(&TestC { v: 3 }));
+    assert_eq!(by_writer.get(64), None);
+}
+
+#[test]
+fn change_detection_opt_out_skips_changed_masks_but_keeps_rollback() {
+    use decs::component::Component;
+    register_components_once();
+    const { assert!(!Untracked::CHANGE_DETECTION && TestC::CHANGE_DETECTION) };
+
+    let mut world = World::new();
+    let t1 = world.advance_tick();
+    let f1 = world.frame();
+    {
+        let s = world.get_storage_mut::<Untracked>();
+        for i in [1u32, 2, 70, 5000] {
+            s.set(&f1, i, Untracked { v: i as i32 });
+        }
+        let mut w = s.writer(&f1);
+        w.set(71, Untracked { v: 71 });
+    }
+    world.advance_tick();
+    let f2 = world.frame();
+    {
+        let s = world.get_storage_mut::<Untracked>();
+        s.get_mut(&f2, 1).unwrap().v = -1;
+        s.set(&f2, 2, Untracked { v: -2 });
+        assert!(s.remove(&f2, 70));
+        s.set(&f2, 9000, Untracked { v: 9000 });
+
+        assert_eq!(s.changed_mask, 0);
+        let mut pages = s.presence_mask;
+        while pages != 0 {
+            let si = pages.trailing_zeros() as usize;
+            pages &= pages - 1;
+            let page = unsafe { &*s.data[si] };
+            assert_eq!(page.changed_mask, 0);
+            let mut chunks = page.presence_mask;
+            while chunks != 0 {
+                let pi = chunks.trailing_zeros() as usize;
+                chunks &= chunks - 1;
+                assert_eq!(unsafe { (*page.data[pi]).changed_mask }, 0);
+            }
+        }
+    }
+
+    world.rollback(t1);
+    let s = world.get_storage_mut::<Untracked>();
+    assert_eq!(s.get(1), Some(&Untracked { v: 1 }));
+    assert_eq!(s.get(2), Some(&Untracked { v: 2 }));
+    assert_eq!(s.get(70), Some(&Untracked { v: 70 }));
+    assert_eq!(s.get(9000), None);
+    assert_eq!(s.count, 5);
+}
+
+#[test]
+fn chunk_slices_expose_values_and_record_rollback() {
+    register_components_once();
+    let mut world = World::new();
+    let t1 = world.advance_tick();
+    let f1 = world.frame();
+    {
+        let s = world.get_storage_mut::<TestC>();
+        for i in [64u32, 65, 100] {
+            s.set(&f1, i, TestC { v: i as i32 });
+        }
+        assert!(s.chunk_slice(0, 0).is_none());
+        assert!(s.chunk_slice(0, 64).is_none());
+        let (values, presence) = s.chunk_slice(0, 1).unwrap();
+        assert_eq!(presence, 0b11 | (1 << 36));
+        assert_eq!(unsafe { values[36].assume_init_ref() }.v, 100);
+    }
+
+    world.advance_tick();
+    let f2 = world.frame();
+    {
+        let s = world.get_storage_mut::<TestC>();
+        s.set(&f2, 66, TestC { v: 66 });
+        let (values, mut presence) = s.chunk_slice_mut(&f2, 0, 1).unwrap();
+        while presence != 0 {
+            let i = presence.trailing_zeros() as usize;
+            unsafe { values[i].assume_init_mut() }.v *= 10;
+            presence &= presence - 1;
+        }
+        assert_eq!(s.get(65), Some(&TestC { v: 650 }));
+        assert_eq!(s.changed_mask, 1);
+        assert!(s.rollback.verify_was_modified(64));
+        assert!(s.rollback.verify_was_created(66));
+    }
+
+    world.rollback(t1);
+    let s = world.get_storage_mut::<TestC>();
+    assert_eq!(s.get(64), Some(&TestC { v: 64 }));
+    assert_eq!(s.get(100), Some(&TestC { v: 100 }));
+    assert_eq!(s.get(66), None);
+}
+
+#[test]
+fn cursor_resumes_across_ticks() {
+    register_components_once();
+    let mut world = World::new();
+    world.advance_tick();
+    let f1 = world.frame();
+    let indices = [3u32, 70, 4095, 4096, 200_000];
+    {
+        let s = world.get_storage_mut::<TestC>();
+        for &i in &indices {
+            s.set(&f1, i, TestC { v: i as i32 });
+        }
+    }
+
+    let s = world.get_storage_mut::<TestC>();
+    let mut cursor = s.cursor();
+    let mut seen = Vec::new();
+    let visited = cursor.step(s, 2, |i, c| {
+        assert_eq!(c.v, i as i32);
+        seen.push(i);
+    });
+    assert_eq!(visited, 2);
+    assert_eq!(cursor.position(), 71);
+
+    // Changes between ticks are seen when the cursor gets there.
+    world.advance_tick();
+    let f2 = world.frame();
+    {
+        let s = world.get_storage_mut::<TestC>();
+        s.remove(&f2, 4095);
+        s.set(&f2, 10, TestC { v: 10 });
+        s.set(&f2, 5000, TestC { v: 5000 });
+    }
+    let s = world.get_storage_mut::<TestC>();
+    cursor.step(s, 10, |i, _| seen.push(i));
+    assert_eq!(seen, vec![3, 70, 4096, 5000, 200_000]);
+    assert!(cursor.is_finished());
+    assert_eq!(cursor.next(s), None);
+
+    // A finished cursor restarts from index 0 on the next step.
+    let mut first = Vec::new();
+    cursor.step(s, 2, |i, _| first.push(i));
+    assert_eq!(first, vec![3, 10]);
+    assert_eq!(s.next_present(200_001), None);
+}
+
+#[test]
+fn propagate_changed_and_summarize_changes_lift_chunk_bits() {
+    register_components_once();
+    let mut world = World::new();
+    let f = world.frame();
+    let s = world.get_storage_mut::<TestC>();
+    let far = (2 << 12) | (7 << 6) | 3;
+    for i in [5u32, 70, far] {
+        s.set(&f, i, TestC { v: 0 });
+    }
+    s.clear_changed_masks();
+
+    // An external writer touching values and chunk masks directly.
+    let chunk = |s: &decs::storage::Storage<TestC>, si: usize, pi: usize| unsafe {
+        &mut *(*s.data[si]).data[pi]
+    };
+    chunk(s, 0, 0).changed_mask |= 1 << 5;
+    assert_eq!(s.changed_mask, 0);
+    s.propagate_changed(0, 0);
+    assert_eq!(s.changed_mask, 1);
+    assert_eq!(unsafe { (*s.data[0]).changed_mask }, 1);
+
+    // Absent pages and chunks are ignored.
+    s.propagate_changed(5, 0);
+    s.propagate_changed(0, 9);
+
+    chunk(s, 0, 1).changed_mask |= 1 << 6;
+    chunk(s, 2, 7).changed_mask |= 1 << 3;
+    assert_eq!(s.summarize_changes(), 0b101);
+    assert_eq!(unsafe { (*s.data[0]).changed_mask }, 0b11);
+    assert_eq!(unsafe { (*s.data[2]).changed_mask }, 1 << 7);
+
+    s.clear_changed_masks();
+    assert_eq!(chunk(s, 0, 1).changed_mask, 0);
+    assert_eq!(chunk(s, 2, 7).changed_mask, 0);
+    assert!(s.verify_invariants());
+}
+
+#[test]
+fn get_or_insert_with_records_creation_then_modification() {
+    register_components_once();
+    let mut world = World::new();
+    let t0 = world.current_tick();
+    let t1 = world.advance_tick();
+    world.get_or_insert_component(5, || TestC { v: 0 }).v += 1;
+    {
+        let s = world.get_storage_mut::<TestC>();
+        assert_eq!(s.get(5), Some(&TestC { v: 1 }));
+        assert!(s.rollback.verify_was_created(5));
+        assert!(s.verify_invariants());
+    }
+
+    world.advance_tick();
+    let f2 = world.frame();
+    {
+        let s = world.get_storage_mut::<TestC>();
+        let value = s.get_or_insert_with(&f2, 5, || panic!("value is present"));
+        value.v = 2;
+        assert!(s.rollback.verify_was_modified(5));
+        assert_eq!(s.changed_mask, 1);
+    }
+
+    world.rollback(t1);
+    assert_eq!(
+        world.get_storage_mut::<TestC>().get(5),
+        Some(&TestC { v: 1 })
+    );
+    world.rollback(t0);
+    assert_eq!(world.get_storage_mut::<TestC>().get(5), None);
+}
+
+#[test]
+fn structure_generation_moves_only_on_layout_changes() {
+    register_components_once();
+    let mut world = World::new();
+    let t0 = world.current_tick();
+    world.advance_tick();
+    let f = world.frame();
+    let s = world.get_storage_mut::<TestC>();
+    let g0 = s.structure_generation();
+
+    // The first value allocates a page and a chunk; neighbours reuse them.
+    s.set(&f, 3, TestC { v: 0 });
+    let g1 = s.structure_generation();
+    assert!(g1 > g0);
+    s.set(&f, 4, TestC { v: 0 });
+    s.set(&f, 3, TestC { v: 1 });
+    assert_eq!(s.structure_generation(), g1);
+
+    // A new chunk, then freeing it again.
+    s.set(&f, 64, TestC { v: 0 });
+    let g2 = s.structure_generation();
+    assert!(g2 > g1);
+    s.remove(&f, 64);
+    let g3 = s.structure_generation();
+    assert!(g3 > g2);
+    s.remove(&f, 4);
+    assert_eq!(s.structure_generation(), g3);
+
+    world.rollback(t0);
+    let s = world.get_storage_mut::<TestC>();
+    assert!(s.structure_generation() > g3);
+    let erased: &dyn StorageLike = s;
+    assert_eq!(erased.structure_generation(), s.structure_generation());
+}
+
+#[test]
+fn removed_value_reads_the_value_removed_this_tick() {
+    register_components_once();
+    let mut world = World::new();
+    let f = world.frame();
+    world.get_storage_mut::<TestC>().set(&f, 3, TestC { v: 3 });
+    world
+        .get_storage_mut::<TestC>()
+        .set(&f, 70, TestC { v: 70 });
+
+    world.advance_tick();
+    let f = world.frame();
+    let s = world.get_storage_mut::<TestC>();
+    assert!(s.remove(&f, 3));
+    assert!(s.remove(&f, 70));
+    assert_eq!(s.removed_value(3), Some(&TestC { v: 3 }));
+    // The chunk of index 70 was freed; the value is still there.
+    assert_eq!(s.removed_value(70), Some(&TestC { v: 70 }));
+
+    // Neither inserted-then-removed values nor reinserted ones count as removed.
+    s.set(&f, 4, TestC { v: 4 });
+    s.remove(&f, 4);
+    assert_eq!(s.removed_value(4), None);
+    s.set(&f, 70, TestC { v: 71 });
+    assert_eq!(s.removed_value(70), None);
+    assert_eq!(s.removed_value(5), None);
+    assert_eq!(s.removed_value(u32::MAX), None);
+}
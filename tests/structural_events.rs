@@ -0,0 +1,93 @@
+use decs::component::Destroyed;
+use decs::ecs::Ecs;
+use decs::event::{ComponentAdded, ComponentRemoved, DespawnedEvent};
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Health(i32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Shield(i32);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Health>();
+        Ecs::register::<Shield>();
+        Ecs::register::<DespawnedEvent>();
+        Ecs::register::<ComponentAdded>();
+        Ecs::register::<ComponentRemoved>();
+    });
+}
+
+fn added(world: &World) -> Vec<(u32, bool)> {
+    world
+        .events::<ComponentAdded>()
+        .map(|e| (e.index, e.is::<Health>()))
+        .collect()
+}
+
+fn removed(world: &World) -> Vec<(u32, bool)> {
+    world
+        .events::<ComponentRemoved>()
+        .map(|e| (e.index, e.is::<Health>()))
+        .collect()
+}
+
+#[test]
+fn inserts_and_removals_are_reported_once_per_tick() {
+    register_components_once();
+    let mut world = World::new();
+    world.finalize();
+
+    let f = world.frame();
+    world.get_storage_mut::<Health>().set(&f, 1, Health(10));
+    world.get_storage_mut::<Health>().set(&f, 2, Health(10));
+    world.get_storage_mut::<Shield>().set(&f, 2, Shield(5));
+    world.run();
+    assert_eq!(added(&world), [(1, true), (2, true), (2, false)]);
+    assert!(removed(&world).is_empty());
+
+    // Overwrites aren't structural, and an insert removed in the same tick cancels out.
+    let f = world.frame();
+    world.get_storage_mut::<Health>().set(&f, 1, Health(9));
+    world.get_storage_mut::<Health>().set(&f, 3, Health(1));
+    world.get_storage_mut::<Health>().remove(&f, 3);
+    world.get_storage_mut::<Shield>().remove(&f, 2);
+    world.run();
+    assert!(added(&world).is_empty());
+    assert_eq!(removed(&world), [(2, false)]);
+
+    // Events don't report themselves, and last until the next tick's cleanup.
+    world.run();
+    assert!(added(&world).is_empty());
+    assert!(removed(&world).is_empty());
+    assert!(world.verify_invariants());
+}
+
+#[test]
+fn despawn_reports_every_removed_component() {
+    register_components_once();
+    let mut world = World::new();
+    world.finalize();
+
+    let f = world.frame();
+    let entities = world.get_entity_storage();
+    let entity = unsafe { (*entities).spawn(&f) }.unwrap();
+    let index = entity.index();
+    world.get_storage_mut::<Health>().set(&f, index, Health(3));
+    world.run();
+    assert_eq!(added(&world), [(index, false), (index, true)]);
+
+    let f = world.frame();
+    world
+        .get_storage_mut::<Destroyed>()
+        .set(&f, index, Destroyed());
+    world.run();
+    assert_eq!(removed(&world), [(index, false), (index, true)]);
+    assert_eq!(world.events::<DespawnedEvent>().count(), 1);
+    // `Destroyed` was added and removed within the tick.
+    assert!(added(&world).is_empty());
+}
@@ -0,0 +1,91 @@
+use decs::ecs::Ecs;
+use decs::error::Error;
+use decs::scheduler::ErrorPolicy;
+use decs::system;
+use decs::system::SystemError;
+use decs::view::{View, ViewMut};
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Asset(u32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Loaded(u32);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Asset>();
+        Ecs::register::<Loaded>();
+    });
+}
+
+system!(LoadAsset { query
+    fn update(asset: View<Asset>, loaded: &mut ViewMut<Loaded>) -> Result<(), SystemError>
+    {
+        if asset.0 == 0 {
+            return Err("missing asset".into());
+        }
+        loaded.0 = asset.0;
+        Ok(())
+    }
+});
+
+fn world_with_assets(assets: &[(u32, u32)]) -> World {
+    register_components_once();
+    let mut world = World::new();
+    let system = LoadAsset::new(&mut world);
+    world.scheduler_mut().add_system(system);
+    world.finalize();
+    let f = world.frame();
+    for &(index, asset) in assets {
+        world
+            .get_storage_mut::<Asset>()
+            .set(&f, index, Asset(asset));
+        world.get_storage_mut::<Loaded>().set(&f, index, Loaded(0));
+    }
+    world
+}
+
+#[test]
+fn first_error_is_collected_and_the_tick_continues() {
+    let mut world = world_with_assets(&[(1, 7), (70, 0), (5000, 0)]);
+    world.run();
+
+    let errors = world.scheduler().last_errors();
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].system.ends_with("LoadAsset"));
+    assert_eq!(errors[0].error.message, "missing asset");
+    assert_eq!(errors[0].error.index, Some(70));
+    assert_eq!(errors[0].error.to_string(), "missing asset (at index 70)");
+    // The rest of the query still ran.
+    assert_eq!(world.get_storage_mut::<Loaded>().get(1), Some(&Loaded(7)));
+
+    // Errors only describe the last run.
+    let f = world.frame();
+    world.get_storage_mut::<Asset>().set(&f, 70, Asset(2));
+    world.get_storage_mut::<Asset>().remove(&f, 5000);
+    world.run();
+    assert!(world.scheduler().last_errors().is_empty());
+    assert_eq!(world.get_storage_mut::<Loaded>().get(70), Some(&Loaded(2)));
+}
+
+#[test]
+fn abort_policy_rolls_the_tick_back() {
+    let mut world = world_with_assets(&[(1, 7), (2, 0)]);
+    world
+        .scheduler_mut()
+        .set_error_policy(ErrorPolicy::AbortTick);
+    let start = world.current_tick();
+
+    match world.try_run() {
+        Err(Error::SystemFailed { system, .. }) => assert!(system.ends_with("LoadAsset")),
+        other => panic!("expected SystemFailed, got {:?}", other),
+    }
+    assert_eq!(world.current_tick(), start);
+    assert_eq!(world.get_storage_mut::<Loaded>().get(1), Some(&Loaded(0)));
+    assert_eq!(world.scheduler().last_errors()[0].error.index, Some(2));
+    assert!(world.verify_invariants());
+}
@@ -70,7 +70,7 @@ fn count_none_frozen_with_vel(world: &mut World) -> u32 {
 fn count_changed_position(world: &mut World) -> u32 {
     unsafe {
         let pos = &*world.get_storage::<Position>();
-        let rb = &*pos.rollback;
+        let rb = &pos.rollback;
         let mut total: u32 = 0;
         let mut storage_mask = rb.changed_mask;
         while storage_mask != 0 {
@@ -300,3 +300,146 @@ fn viewmut_counts_items_and_mutates_values() {
         assert_eq!(p.x, 2.0);
     }
 }
+
+static CACHED_MATCHED: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+
+system!(CountMovingNotFrozen {
+    query fn update(_pos: View<Position>, _vel: View<Velocity>) {
+        CACHED_MATCHED.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    }
+    None=[Frozen]
+});
+
+#[test]
+fn query_plan_is_reused_until_structure_changes() {
+    use decs::system::System;
+    use std::sync::atomic::Ordering;
+    register_components_once();
+    let mut world = World::new();
+    let frame = Frame::new(world.current_tick());
+    for i in 0..200u32 {
+        world
+            .get_storage_mut::<Position>()
+            .set(&frame, i, Position { x: 0.0, y: 0.0 });
+        world
+            .get_storage_mut::<Velocity>()
+            .set(&frame, i, Velocity { x: 1.0, y: 0.0 });
+        if i % 4 == 0 {
+            world.get_storage_mut::<Frozen>().set(&frame, i, Frozen);
+        }
+    }
+
+    let sys = CountMovingNotFrozen::new(&mut world);
+    let run = |world: &mut World| {
+        CACHED_MATCHED.store(0, Ordering::Relaxed);
+        sys.run(&frame);
+        assert_eq!(
+            CACHED_MATCHED.load(Ordering::Relaxed),
+            count_none_frozen_with_vel(world)
+        );
+        (
+            CACHED_MATCHED.load(Ordering::Relaxed),
+            sys.query_plan().rebuilds(),
+        )
+    };
+
+    assert_eq!(run(&mut world), (150, 1));
+    // Value-only changes keep the plan
+    world
+        .get_storage_mut::<Position>()
+        .set(&frame, 3, Position { x: 9.0, y: 9.0 });
+    assert!(world.get_storage_mut::<Velocity>().remove(&frame, 197));
+    assert_eq!(run(&mut world), (149, 1));
+
+    // A new page invalidates it
+    world
+        .get_storage_mut::<Position>()
+        .set(&frame, 5000, Position { x: 0.0, y: 0.0 });
+    world
+        .get_storage_mut::<Velocity>()
+        .set(&frame, 5000, Velocity { x: 0.0, y: 0.0 });
+    assert_eq!(run(&mut world).1, 2);
+
+    // Filling a Frozen chunk excludes the whole page of the None filter, and
+    // removing from the full chunk brings it back
+    for i in 0..64u32 {
+        world.get_storage_mut::<Frozen>().set(&frame, i, Frozen);
+    }
+    assert_eq!(run(&mut world), (102, 3));
+    assert!(world.get_storage_mut::<Frozen>().remove(&frame, 7));
+    assert_eq!(run(&mut world), (103, 4));
+    assert_eq!(run(&mut world), (103, 4));
+
+    // Rolling back restored structure invalidates it as well
+    let before = world.current_tick();
+    world.advance_tick();
+    let next = world.frame();
+    world
+        .get_storage_mut::<Position>()
+        .set(&next, 9000, Position { x: 0.0, y: 0.0 });
+    world
+        .get_storage_mut::<Velocity>()
+        .set(&next, 9000, Velocity { x: 0.0, y: 0.0 });
+    assert_eq!(run(&mut world), (104, 5));
+    world.rollback(before);
+    assert_eq!(run(&mut world), (103, 6));
+}
+
+static CHANGED_MATCHED: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+
+system!(CountChangedPositions {
+    query fn update(_pos: View<Position>) {
+        CHANGED_MATCHED.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    }
+    Changed=[Position]
+});
+
+#[test]
+fn match_count_walks_the_masks_without_running_the_query() {
+    use decs::system::System;
+    use std::sync::atomic::Ordering;
+    register_components_once();
+    let mut world = World::new();
+    let frame = world.frame();
+    for i in 0..200u32 {
+        world
+            .get_storage_mut::<Position>()
+            .set(&frame, i, Position { x: 0.0, y: 0.0 });
+        world
+            .get_storage_mut::<Velocity>()
+            .set(&frame, i, Velocity { x: 1.0, y: 0.0 });
+        if i % 4 == 0 {
+            world.get_storage_mut::<Frozen>().set(&frame, i, Frozen);
+        }
+    }
+
+    let moving = CountMovingNotFrozen::new(&mut world);
+    assert_eq!(
+        moving.match_count() as u32,
+        count_none_frozen_with_vel(&mut world)
+    );
+    assert!(moving.matches_any());
+    moving.set_index_range(0..100);
+    assert_eq!(moving.match_count(), 75);
+    moving.set_index_range(10_000..20_000);
+    assert_eq!(moving.match_count(), 0);
+    assert!(!moving.matches_any());
+
+    let changed = CountChangedPositions::new(&mut world);
+    assert_eq!(changed.match_count(), 200);
+    assert_eq!(CHANGED_MATCHED.load(Ordering::Relaxed), 0);
+    changed.run(&frame);
+    assert_eq!(CHANGED_MATCHED.load(Ordering::Relaxed), 200);
+    assert!(!changed.matches_any());
+
+    for i in [3, 70, 150] {
+        world
+            .get_storage_mut::<Position>()
+            .get_mut(&frame, i)
+            .unwrap()
+            .x = 1.0;
+    }
+    assert_eq!(changed.match_count(), 3);
+    assert!(changed.matches_any());
+    assert_eq!(CHANGED_MATCHED.load(Ordering::Relaxed), 200);
+}
@@ -0,0 +1,124 @@
+#![cfg(feature = "system-trace")]
+
+use decs::ecs::Ecs;
+use decs::system;
+use decs::trace;
+use decs::view::{View, ViewMut};
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Pos(i32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Vel(i32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Far(bool);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Pos>();
+        Ecs::register::<Vel>();
+        Ecs::register::<Far>();
+    });
+}
+
+system!(MoveSystem { query
+    fn update(vel: View<Vel>, pos: &mut ViewMut<Pos>)
+    {
+        pos.0 += vel.0;
+    }
+});
+
+system!(RangeSystem { query
+    fn update(pos: View<Pos>, far: &mut ViewMut<Far>)
+    {
+        far.0 = pos.0 > 10;
+    }
+});
+
+fn world() -> World {
+    register_components_once();
+    let mut world = World::new();
+    let range = RangeSystem::new(&mut world);
+    let movement = MoveSystem::new(&mut world);
+    world.scheduler_mut().add_system(range);
+    world.scheduler_mut().add_system(movement);
+    world.finalize();
+    let f = world.frame();
+    world.get_storage_mut::<Pos>().set(&f, 0, Pos(0));
+    world.get_storage_mut::<Vel>().set(&f, 0, Vel(4));
+    world.get_storage_mut::<Far>().set(&f, 0, Far(false));
+    world
+}
+
+#[test]
+fn records_run_order_wavefronts_and_storages() {
+    let mut world = world();
+    world.run();
+    world.run();
+    let trace = world.system_trace();
+    assert!(!trace.is_empty());
+
+    let ticks: Vec<_> = trace.iter().map(|e| e.tick).collect();
+    let first = ticks[0];
+    assert!(ticks.iter().all(|&t| t == first || t == first.next()));
+    let tick: Vec<_> = trace.iter().filter(|e| e.tick == first).collect();
+    assert!(tick.windows(2).all(|w| w[0].position < w[1].position));
+    assert!(tick.windows(2).all(|w| w[0].wavefront <= w[1].wavefront));
+
+    let ours: Vec<_> = tick
+        .iter()
+        .filter(|e| e.system.ends_with("MoveSystem") || e.system.ends_with("RangeSystem"))
+        .collect();
+    assert_eq!(ours.len(), 2);
+    // RangeSystem reads what MoveSystem writes, so it runs in a later wavefront even
+    // though it was added first.
+    assert!(ours[0].system.ends_with("MoveSystem"));
+    assert!(ours[0].wavefront < ours[1].wavefront);
+    let movement = ours
+        .iter()
+        .find(|e| e.system.ends_with("MoveSystem"))
+        .unwrap();
+    assert!(movement.writes.iter().any(|name| name.ends_with("Pos")));
+    assert!(movement.reads.iter().any(|name| name.ends_with("Vel")));
+    assert!(!movement.exclusive);
+
+    // The second tick ran the same systems in the same order.
+    let order = |tick| {
+        trace
+            .iter()
+            .filter(move |e| e.tick == tick)
+            .map(|e| (e.position, e.system))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(order(first), order(first.next()));
+
+    world.scheduler().trace().clear();
+    assert!(world.system_trace().is_empty());
+}
+
+#[test]
+fn exports_the_trace_as_json() {
+    let mut world = world();
+    world.run();
+    let trace: Vec<_> = world
+        .system_trace()
+        .into_iter()
+        .filter(|e| e.system.ends_with("MoveSystem"))
+        .collect();
+    assert_eq!(trace.len(), 1);
+    let e = &trace[0];
+    let json = trace::to_json(&trace);
+    assert_eq!(
+        json,
+        format!(
+            "[{{\"tick\":{},\"position\":{},\"wavefront\":{},\"system\":\"{}\",\"exclusive\":false,\"reads\":[\"{}\"],\"writes\":[\"{}\"]}}]",
+            e.tick.0, e.position, e.wavefront, e.system, e.reads[0], e.writes[0]
+        )
+    );
+    assert_eq!(trace::to_json(&[]), "[]");
+}
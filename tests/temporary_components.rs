@@ -0,0 +1,99 @@
+use decs::component::Component;
+use decs::ecs::Ecs;
+use decs::expiry::Expiries;
+use decs::system;
+use decs::view::{View, ViewMut};
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Invulnerable;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Burning(u32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct ShieldedTicks(u32);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Invulnerable>();
+        Ecs::register::<Burning>();
+        Ecs::register::<ShieldedTicks>();
+        Ecs::register::<Expiries>();
+    });
+}
+
+system!(ShieldSystem { query
+    fn update(ticks: &mut ViewMut<ShieldedTicks>, _shield: View<Invulnerable>)
+    {
+        ticks.0 += 1;
+    }
+});
+
+fn world() -> World {
+    let mut world = World::new();
+    let system = ShieldSystem::new(&mut world);
+    world.scheduler_mut().add_system(system);
+    world.finalize();
+    world
+}
+
+fn has<T: Component>(world: &mut World, index: u32) -> bool {
+    world.get_storage_mut::<T>().get(index).is_some()
+}
+
+#[test]
+fn temporary_component_is_seen_for_its_ttl_then_removed() {
+    register_components_once();
+    let mut world = world();
+    world.run();
+    let f = world.frame();
+    let index = world.spawn_bundle(&f, (ShieldedTicks(0),)).unwrap().index();
+    world.insert_temporary(&f, index, Invulnerable, 3);
+    world.insert_temporary(&f, index, Burning(5), 1);
+    let inserted = world.current_tick();
+    assert_eq!(
+        world
+            .get_storage_mut::<Expiries>()
+            .get(index)
+            .unwrap()
+            .get(Burning::id()),
+        Some(inserted.wrapping_add(1))
+    );
+
+    world.run();
+    assert!(!has::<Burning>(&mut world, index));
+    assert!(has::<Invulnerable>(&mut world, index));
+    world.run();
+    world.run();
+    assert!(!has::<Invulnerable>(&mut world, index));
+    assert!(!has::<Expiries>(&mut world, index));
+    let shielded = world.get_storage_mut::<ShieldedTicks>().get(index).cloned();
+    assert_eq!(shielded, Some(ShieldedTicks(3)));
+    assert!(world.verify_invariants());
+
+    // Rolling back restores the component with its remaining time.
+    world.rollback(inserted.wrapping_add(2));
+    assert!(has::<Invulnerable>(&mut world, index));
+    world.run();
+    assert!(!has::<Invulnerable>(&mut world, index));
+    assert!(!has::<Expiries>(&mut world, index));
+}
+
+#[test]
+fn inserting_again_restarts_the_countdown() {
+    register_components_once();
+    let mut world = world();
+    let f = world.frame();
+    world.insert_temporary(&f, 7, Invulnerable, 2);
+    world.run();
+    let f = world.frame();
+    world.insert_temporary(&f, 7, Invulnerable, 2);
+    world.run();
+    assert!(has::<Invulnerable>(&mut world, 7));
+    world.run();
+    assert!(!has::<Invulnerable>(&mut world, 7));
+}
@@ -0,0 +1,68 @@
+#![cfg(debug_assertions)]
+
+use decs::ecs::Ecs;
+use decs::frame::Frame;
+use decs::tick::Tick;
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Health(u32);
+
+fn world() -> World {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Health>();
+    });
+    let mut world = World::new();
+    world.finalize();
+    world
+}
+
+#[test]
+fn writes_for_the_guarded_tick_pass() {
+    let mut world = world();
+    let f = world.frame();
+    {
+        let guard = world.begin_tick(&f);
+        assert_eq!(guard.tick(), f.current_tick);
+        world.get_storage_mut::<Health>().set(&f, 1, Health(10));
+        world.get_storage_mut::<Health>().get_mut(&f, 1).unwrap().0 = 5;
+        world.get_storage_mut::<Health>().remove(&f, 1);
+    }
+    // Dropping the guard lifts the checks.
+    world.advance_tick();
+    let f = world.frame();
+    let _guard = world.begin_tick(&f);
+    world.get_storage_mut::<Health>().set(&f, 2, Health(1));
+}
+
+#[test]
+#[should_panic(expected = "written for Tick(7) inside the TickGuard of Tick(0)")]
+fn writes_for_another_tick_panic() {
+    let mut world = world();
+    let _guard = world.begin_tick(&world.frame());
+    world
+        .get_storage_mut::<Health>()
+        .set(&Frame::new(Tick(7)), 1, Health(10));
+}
+
+#[test]
+#[should_panic(expected = "cleared inside the TickGuard of Tick(0)")]
+fn clearing_changed_masks_mid_tick_panics() {
+    let mut world = world();
+    let f = world.frame();
+    let _guard = world.begin_tick(&f);
+    world.get_storage_mut::<Health>().set(&f, 1, Health(10));
+    world.advance_tick();
+}
+
+#[test]
+#[should_panic(expected = "while the guard of Tick(0) is open")]
+fn guards_do_not_nest() {
+    let world = world();
+    let f = world.frame();
+    let _outer = world.begin_tick(&f);
+    let _inner = world.begin_tick(&f);
+}
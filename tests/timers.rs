@@ -0,0 +1,124 @@
+use decs::component::Component;
+use decs::ecs::Ecs;
+use decs::resource::SimTime;
+use decs::system;
+use decs::timer::{Cooldown, Timer};
+use decs::view::{View, ViewMut};
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Shots(u32);
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<SimTime>();
+        Ecs::register::<Timer>();
+        Ecs::register::<Cooldown>();
+        Ecs::register::<Shots>();
+    });
+}
+
+// Fires on every timer finish the cooldown allows.
+system!(FireSystem { query
+    fn update(timer: View<Timer>, cooldown: &mut ViewMut<Cooldown>, shots: &mut ViewMut<Shots>)
+    {
+        for _ in 0..timer.times_finished {
+            if cooldown.try_trigger() {
+                shots.0 += 1;
+            }
+        }
+    }
+});
+
+fn world(dt: f32) -> World {
+    register_components_once();
+    let mut world = World::new();
+    let fire = FireSystem::new(&mut world);
+    world.scheduler_mut().add_system(fire);
+    world.finalize();
+    world.set_fixed_dt(dt);
+    world.insert_resource(SimTime::new(dt));
+    world
+}
+
+fn get<T: Component>(world: &mut World, index: u32) -> T {
+    world.get_storage_mut::<T>().get(index).cloned().unwrap()
+}
+
+#[test]
+fn timer_and_cooldown_helpers() {
+    let mut timer = Timer::new(1.0);
+    timer.tick(0.75);
+    assert_eq!(
+        (timer.finished(), timer.remaining(), timer.fraction()),
+        (false, 0.25, 0.75)
+    );
+    timer.tick(0.5);
+    assert!(timer.finished() && timer.just_finished());
+    timer.tick(0.5);
+    assert!(timer.finished() && !timer.just_finished());
+
+    let mut repeating = Timer::repeating(0.25);
+    repeating.tick(0.625);
+    assert_eq!(repeating.times_finished, 2);
+    assert_eq!(repeating.elapsed, 0.125);
+    assert!(!repeating.finished());
+
+    let mut cooldown = Cooldown::new(1.0);
+    assert!(cooldown.try_trigger());
+    assert!(!cooldown.try_trigger());
+    cooldown.tick(1.0);
+    assert!(cooldown.ready());
+}
+
+#[test]
+fn timers_advance_before_simulation_systems() {
+    let mut world = world(0.25);
+    let f = world.frame();
+    world.get_storage_mut::<Timer>().set(&f, 0, Timer::new(0.5));
+    world
+        .get_storage_mut::<Cooldown>()
+        .set(&f, 0, Cooldown::new(0.0));
+    world.get_storage_mut::<Shots>().set(&f, 0, Shots(0));
+
+    world.run();
+    assert_eq!(get::<Shots>(&mut world, 0), Shots(0));
+    world.run();
+    // FireSystem saw the timer finish in the tick it finished in.
+    assert_eq!(get::<Shots>(&mut world, 0), Shots(1));
+    assert!(get::<Timer>(&mut world, 0).just_finished());
+    world.run();
+    assert_eq!(get::<Shots>(&mut world, 0), Shots(1));
+    assert!(!get::<Timer>(&mut world, 0).just_finished());
+}
+
+#[test]
+fn resimulated_ticks_fire_the_same_shots() {
+    let mut world = world(0.25);
+    let f = world.frame();
+    world
+        .get_storage_mut::<Timer>()
+        .set(&f, 0, Timer::repeating(0.25));
+    world
+        .get_storage_mut::<Cooldown>()
+        .set(&f, 0, Cooldown::new(0.5));
+    world.get_storage_mut::<Shots>().set(&f, 0, Shots(0));
+
+    world.run();
+    let first = world.current_tick();
+    for _ in 0..6 {
+        world.run();
+    }
+    let shots = get::<Shots>(&mut world, 0);
+    let cooldown = get::<Cooldown>(&mut world, 0);
+    // Every tick finishes the timer, every other one is let through by the cooldown.
+    assert_eq!(shots, Shots(4));
+
+    assert_eq!(world.resimulate(first.next(), |_, _| {}), Ok(6));
+    assert_eq!(get::<Shots>(&mut world, 0), shots);
+    assert_eq!(get::<Cooldown>(&mut world, 0), cooldown);
+    assert_eq!(get::<Timer>(&mut world, 0).elapsed, 0.0);
+}
@@ -165,3 +165,119 @@ fn view_and_viewmut_combination_multiple_chunks() {
     world.scheduler_mut().build_wavefronts();
     world.run();
 }
+
+static SEEN: std::sync::Mutex<Vec<(u32, Option<decs::tick::Tick>)>> =
+    std::sync::Mutex::new(Vec::new());
+
+system!(RecordIndexAndTick {
+    query fn update(pos: &mut ViewMut<Position>, vel: View<Velocity>) {
+        assert_eq!(pos.index(), vel.index());
+        SEEN.lock().unwrap().push((pos.index(), pos.last_changed_tick()));
+        pos.x += vel.x;
+    }
+});
+
+#[test]
+fn views_expose_global_index_and_last_changed_tick() {
+    register_components_once();
+    let mut world = World::new();
+    world.set_tick(decs::tick::Tick(5));
+    {
+        let f = Frame::new(world.current_tick());
+        for i in [3u32, 4100] {
+            let pos = world.get_storage_mut::<Position>();
+            pos.set(&f, i, Position { x: 0.0, y: 0.0 });
+            let vel = world.get_storage_mut::<Velocity>();
+            vel.set(&f, i, Velocity { x: 1.0, y: 1.0 });
+        }
+    }
+    world.set_tick(decs::tick::Tick(8));
+    let sys = RecordIndexAndTick::new(&mut world);
+    world.scheduler_mut().add_system(sys);
+    world.scheduler_mut().build_wavefronts();
+    world.run();
+
+    let seen = SEEN.lock().unwrap().clone();
+    assert_eq!(
+        seen,
+        vec![
+            (3, Some(decs::tick::Tick(5))),
+            (4100, Some(decs::tick::Tick(5)))
+        ]
+    );
+    let pos_ptr = world.get_storage::<Position>();
+    assert_eq!(
+        unsafe { (*pos_ptr).last_changed_tick(3) },
+        Some(decs::tick::Tick(9))
+    );
+}
+
+system!(ConditionalUpdate {
+    query fn update(pos: &mut ViewMut<Position>, vel: View<Velocity>) {
+        if vel.x > 0.0 {
+            pos.update(|p| {
+                p.x += vel.x;
+                true
+            });
+        } else {
+            pos.update_if_ne(|p| p.y = p.y.max(0.0));
+        }
+    }
+});
+
+#[test]
+fn viewmut_update_marks_changed_only_when_modified() {
+    register_components_once();
+    let mut world = World::new();
+    world.set_tick(decs::tick::Tick(20));
+    {
+        let f = Frame::new(world.current_tick());
+        for i in 0..3u32 {
+            let pos = world.get_storage_mut::<Position>();
+            pos.set(&f, i, Position { x: 0.0, y: 1.0 });
+        }
+        let vel = world.get_storage_mut::<Velocity>();
+        vel.set(&f, 0, Velocity { x: 2.0, y: 0.0 });
+        vel.set(&f, 1, Velocity { x: 0.0, y: 0.0 });
+        vel.set(&f, 2, Velocity { x: 0.0, y: 0.0 });
+    }
+    let sys = ConditionalUpdate::new(&mut world);
+    world.scheduler_mut().add_system(sys);
+    world.scheduler_mut().build_wavefronts();
+    world.run();
+    assert!(world.verify_invariants());
+
+    let pos_ptr = world.get_storage::<Position>();
+    let rb = unsafe { &(*pos_ptr).rollback };
+    assert_eq!(rb.tick(), decs::tick::Tick(21));
+    assert!(rb.verify_was_modified(0));
+    assert!(rb.verify_not_changed(1));
+    assert!(rb.verify_not_changed(2));
+    assert_eq!(unsafe { (*pos_ptr).get(0).unwrap().x }, 2.0);
+}
+
+system!(ExplicitLifetimeSystem {
+    query fn update<'a>(pos: &mut ViewMut<'a, Position>, vel: View<'a, Velocity>) {
+        let v: &'a Velocity = vel.data;
+        pos.y += v.y;
+    }
+});
+
+#[test]
+fn views_accept_explicit_frame_scoped_lifetimes() {
+    register_components_once();
+    let mut world = World::new();
+    {
+        let f = Frame::new(world.current_tick());
+        let pos = world.get_storage_mut::<Position>();
+        pos.set(&f, 7, Position { x: 0.0, y: 0.0 });
+        let vel = world.get_storage_mut::<Velocity>();
+        vel.set(&f, 7, Velocity { x: 0.0, y: 3.0 });
+    }
+    let sys = ExplicitLifetimeSystem::new(&mut world);
+    world.scheduler_mut().add_system(sys);
+    world.scheduler_mut().build_wavefronts();
+    world.run();
+    let pos_ptr = world.get_storage::<Position>();
+    assert_eq!(unsafe { (*pos_ptr).get(7).unwrap().y }, 3.0);
+}
@@ -243,3 +243,59 @@ fn world_system_run_then_rollback() {
     let p = unsafe { (*pos_ptr).get(0).unwrap() };
     assert_eq!((p.x, p.y), (1.0, 2.0));
 }
+
+#[test]
+fn world_clear_changed_masks_covers_every_storage() {
+    register_components_once();
+    let mut world = World::new();
+    let f = Frame::new(world.current_tick());
+    world
+        .get_storage_mut::<Position>()
+        .set(&f, 5, Position { x: 1.0, y: 1.0 });
+    world
+        .get_storage_mut::<Velocity>()
+        .set(&f, 4100, Velocity { x: 0.0, y: 1.0 });
+    assert_ne!(world.get_storage_mut::<Velocity>().changed_mask, 0);
+    world.clear_changed_masks();
+    assert!(world.storages().all(|s| s.changed_mask_zero()));
+    assert!(world.verify_invariants());
+
+    // A storage first touched after the previous clear is covered too
+    world.get_storage_mut::<Frozen>().set(&f, 5, Frozen);
+    world
+        .get_storage_mut::<Position>()
+        .set(&f, 6, Position { x: 2.0, y: 2.0 });
+    assert_ne!(world.get_storage_mut::<Frozen>().changed_mask, 0);
+    world.clear_changed_masks();
+    assert!(world.storages().all(|s| s.changed_mask_zero()));
+}
+
+#[test]
+fn storage_lookup_by_component_id() {
+    use decs::component::Component;
+    register_components_once();
+    let mut world = World::new();
+    assert!(world.get_storage_by_id(Velocity::id()).is_none());
+    assert!(world.get_storage_by_id(1000).is_none());
+
+    let f = Frame::new(world.current_tick());
+    world
+        .get_storage_mut::<Velocity>()
+        .set(&f, 7, Velocity { x: 1.0, y: 2.0 });
+    let storage = world.get_storage_by_id(Velocity::id()).unwrap();
+    assert_eq!(
+        storage.component_type_id(),
+        std::any::TypeId::of::<Velocity>()
+    );
+    let typed = storage
+        .as_any()
+        .downcast_ref::<decs::storage::Storage<Velocity>>()
+        .unwrap();
+    assert_eq!(typed.get(7), Some(&Velocity { x: 1.0, y: 2.0 }));
+
+    world
+        .get_storage_by_id_mut(Velocity::id())
+        .unwrap()
+        .clear_changed_masks_all_levels();
+    assert!(world.get_storage_mut::<Velocity>().changed_mask == 0);
+}
@@ -1,5 +1,7 @@
 use decs::ecs::Ecs;
 use decs::frame::Frame;
+use decs::system;
+use decs::view::ViewMut;
 use decs::world::World;
 use decs_macros::Component;
 use std::sync::Once;
@@ -9,10 +11,22 @@ struct TestC {
     v: i32,
 }
 
+#[derive(Clone, Debug, PartialEq, Component)]
+struct SlowA(i32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct SlowB(i32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct SlowC(i32);
+
 fn register_components_once() {
     static INIT: Once = Once::new();
     INIT.call_once(|| {
         Ecs::register::<TestC>();
+        Ecs::register::<SlowA>();
+        Ecs::register::<SlowB>();
+        Ecs::register::<SlowC>();
     });
 }
 
@@ -106,3 +120,223 @@ fn rollback_idempotent_add_remove_same_tick_no_effect() {
         assert!(unsafe { (*s_ptr).get(4097).is_none() });
     }
 }
+
+#[test]
+fn try_rollback_past_discarded_history_is_rejected() {
+    register_components_once();
+    let mut world = World::new();
+
+    for t in 1..=70u32 {
+        world.set_tick(decs::tick::Tick(t));
+        let frame = Frame::new(world.current_tick());
+        let s = world.get_storage_mut::<TestC>();
+        s.set(&frame, 0, TestC { v: t as i32 });
+    }
+
+    // Snapshots up to tick 5 were discarded (64 kept in history + current)
+    assert_eq!(
+        world.try_rollback(decs::tick::Tick(2)),
+        Err(decs::error::Error::HistoryExhausted {
+            target: decs::tick::Tick(2),
+            oldest: decs::tick::Tick(5),
+        })
+    );
+    let s_ptr = world.get_storage::<TestC>();
+    assert_eq!(unsafe { (*s_ptr).get(0).unwrap().v }, 70);
+
+    assert_eq!(world.try_rollback(decs::tick::Tick(5)), Ok(()));
+    assert_eq!(unsafe { (*s_ptr).get(0).unwrap().v }, 5);
+    assert!(world.verify_invariants());
+}
+
+#[test]
+fn advance_tick_rotates_rollback_and_clears_changed_masks() {
+    register_components_once();
+    let mut world = World::new();
+
+    assert_eq!(world.advance_tick(), decs::tick::Tick(1));
+    {
+        let frame = world.frame();
+        world
+            .get_storage_mut::<TestC>()
+            .set(&frame, 10, TestC { v: 1 });
+    }
+    assert_ne!(world.get_storage_mut::<TestC>().changed_mask, 0);
+
+    assert_eq!(world.advance_tick(), decs::tick::Tick(2));
+    assert_eq!(world.get_storage_mut::<TestC>().changed_mask, 0);
+    assert!(world.verify_invariants());
+
+    let ent = world.get_entity_storage();
+    let generation_before = unsafe { (*ent).generation };
+    {
+        let frame = world.frame();
+        world
+            .get_storage_mut::<TestC>()
+            .set(&frame, 10, TestC { v: 2 });
+        assert!(unsafe { (*ent).spawn(&frame) }.is_some());
+    }
+    assert_ne!(unsafe { (*ent).generation }, generation_before);
+
+    world.rollback(decs::tick::Tick(1));
+    assert_eq!(world.get_storage_mut::<TestC>().get(10).unwrap().v, 1);
+    assert_eq!(unsafe { (*ent).generation }, generation_before);
+    assert!(unsafe { (*ent).get(0) }.is_none());
+}
+
+#[test]
+fn tick_ordering_is_wrap_aware() {
+    use decs::tick::Tick;
+    use std::cmp::Ordering;
+
+    let before = Tick(u32::MAX - 1);
+    let after = before.wrapping_add(3);
+    assert_eq!(after, Tick(1));
+    assert!(after.is_after(before));
+    assert!(before.is_before(after));
+    assert!(after.is_at_or_after(after));
+    assert_eq!(after.wrapping_cmp(before), Ordering::Greater);
+    assert_eq!(before.distance(after), 3);
+    assert_eq!(Tick(u32::MAX).next(), Tick(0));
+    assert_eq!(Tick(u32::MAX).saturating_add(5), Tick(u32::MAX));
+    assert_eq!(Tick(2).saturating_sub(5), Tick(0));
+    assert_eq!(Tick(0).wrapping_sub(1), Tick(u32::MAX));
+}
+
+#[test]
+fn rollback_across_tick_wraparound() {
+    register_components_once();
+    let mut world = World::new();
+
+    world.set_tick(decs::tick::Tick(u32::MAX - 1));
+    {
+        let frame = Frame::new(world.current_tick());
+        world
+            .get_storage_mut::<TestC>()
+            .set(&frame, 7, TestC { v: 1 });
+    }
+    for v in 2..=4 {
+        world.advance_tick();
+        let frame = world.frame();
+        world.get_storage_mut::<TestC>().set(&frame, 7, TestC { v });
+    }
+    assert_eq!(world.current_tick(), decs::tick::Tick(1));
+
+    world.rollback(decs::tick::Tick(u32::MAX));
+    assert_eq!(world.get_storage_mut::<TestC>().get(7).unwrap().v, 2);
+    assert!(world.verify_invariants());
+}
+
+#[test]
+fn rollback_after_resimulating_rolled_back_ticks() {
+    register_components_once();
+    let mut world = World::new();
+    for v in 1..=5 {
+        world.advance_tick();
+        let frame = world.frame();
+        world.get_storage_mut::<TestC>().set(&frame, 3, TestC { v });
+    }
+    world.rollback(decs::tick::Tick(2));
+
+    // Re-simulate ticks 3 and 4 with different values.
+    for v in [30, 40] {
+        world.advance_tick();
+        let frame = world.frame();
+        world.get_storage_mut::<TestC>().set(&frame, 3, TestC { v });
+    }
+    assert_eq!(world.current_tick(), decs::tick::Tick(4));
+
+    world.rollback(decs::tick::Tick(3));
+    assert_eq!(world.get_storage_mut::<TestC>().get(3).unwrap().v, 30);
+    world.rollback(decs::tick::Tick(1));
+    assert_eq!(world.get_storage_mut::<TestC>().get(3).unwrap().v, 1);
+    assert!(world.verify_invariants());
+}
+
+system!(BumpSlowA { query
+    fn update(a: &mut ViewMut<SlowA>)
+    {
+        a.0 += 1;
+    }
+});
+
+system!(BumpSlowB { query
+    fn update(b: &mut ViewMut<SlowB>)
+    {
+        b.0 += 1;
+    }
+});
+
+#[test]
+fn incremental_rollback_restores_in_priority_order_and_blocks_affected_systems() {
+    register_components_once();
+    let mut world = World::new();
+    let a = BumpSlowA::new(&mut world);
+    let b = BumpSlowB::new(&mut world);
+    world.scheduler_mut().add_system(a);
+    world.scheduler_mut().add_system(b);
+    world.set_rollback_priority::<SlowB>(10);
+    world.set_rollback_priority::<SlowC>(5);
+
+    let t1 = world.advance_tick();
+    let f = world.frame();
+    world.get_storage_mut::<SlowA>().set(&f, 0, SlowA(0));
+    world.get_storage_mut::<SlowB>().set(&f, 0, SlowB(0));
+    world.get_storage_mut::<SlowC>().set(&f, 0, SlowC(0));
+    world.advance_tick();
+    let f = world.frame();
+    world.get_storage_mut::<SlowA>().set(&f, 0, SlowA(100));
+    world.get_storage_mut::<SlowB>().set(&f, 0, SlowB(100));
+    world.get_storage_mut::<SlowC>().set(&f, 0, SlowC(100));
+    world.get_storage_mut::<TestC>().set(&f, 0, TestC { v: 1 });
+    world.scheduler_mut().build_wavefronts();
+
+    let values = |world: &mut World| {
+        (
+            world.get_storage_mut::<SlowA>().get(0).unwrap().0,
+            world.get_storage_mut::<SlowB>().get(0).unwrap().0,
+            world.get_storage_mut::<SlowC>().get(0).unwrap().0,
+        )
+    };
+
+    // TestC has changes too but SlowB goes first; one storage per step.
+    world.set_rollback_priority::<TestC>(-1);
+    world.rollback_to(t1, 1).unwrap();
+    assert_eq!(world.current_tick(), t1);
+    assert!(world.rollback_pending());
+    assert_eq!(values(&mut world), (100, 0, 100));
+
+    // SlowC is restored at the start of the tick; SlowA is still pending, so only the
+    // SlowB system runs.
+    world.run();
+    assert_eq!(values(&mut world), (100, 1, 0));
+    // SlowA is restored before its system runs; TestC is still pending.
+    world.run();
+    assert_eq!(values(&mut world), (1, 2, 0));
+    assert!(world.rollback_pending());
+    assert!(world.get_storage_mut::<TestC>().get(0).is_some());
+
+    world.run();
+    assert!(!world.rollback_pending());
+    assert_eq!(values(&mut world), (2, 3, 0));
+    assert!(world.get_storage_mut::<TestC>().get(0).is_none());
+    assert!(world.verify_invariants());
+}
+
+#[test]
+fn rollback_completes_a_pending_incremental_rollback() {
+    register_components_once();
+    let mut world = World::new();
+    let t1 = world.advance_tick();
+    world.advance_tick();
+    let f = world.frame();
+    world.get_storage_mut::<SlowA>().set(&f, 0, SlowA(1));
+    world.get_storage_mut::<SlowB>().set(&f, 0, SlowB(1));
+    world.rollback_to(t1, 1).unwrap();
+    assert!(world.rollback_pending());
+
+    world.rollback(t1);
+    assert!(!world.rollback_pending());
+    assert!(world.get_storage_mut::<SlowA>().get(0).is_none());
+    assert!(world.get_storage_mut::<SlowB>().get(0).is_none());
+}
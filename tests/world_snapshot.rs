@@ -0,0 +1,72 @@
+use decs::ecs::Ecs;
+use decs::resource::SimTime;
+use decs::system;
+use decs::view::ViewMut;
+use decs::world::World;
+use decs_macros::Component;
+use std::sync::Once;
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Position(i32);
+
+#[derive(Clone, Debug, PartialEq, Component)]
+struct Moving;
+
+fn register_components_once() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        Ecs::register::<Position>();
+        Ecs::register::<Moving>();
+        Ecs::register::<SimTime>();
+    });
+}
+
+system!(MoveSystem { query
+    fn update(pos: &mut ViewMut<Position>, _moving: decs::view::View<Moving>)
+    {
+        pos.0 += 1;
+    }
+});
+
+#[test]
+fn snapshot_is_read_on_another_thread() {
+    register_components_once();
+    let mut world = World::new();
+    world.insert_resource(SimTime::default());
+    let f = world.frame();
+    for index in 1..=4 {
+        world
+            .get_storage_mut::<Position>()
+            .set(&f, index, Position(index as i32 * 10));
+    }
+    world.get_storage_mut::<Moving>().set(&f, 2, Moving);
+    let system = MoveSystem::new(&mut world);
+    world.scheduler_mut().add_system(system);
+    world.scheduler_mut().build_wavefronts();
+    world.run();
+
+    let (sum, positions) = std::thread::scope(|scope| {
+        let snapshot = world.snapshot();
+        let reader = scope.spawn(move || {
+            let positions: Vec<(u32, i32)> =
+                snapshot.iter::<Position>().map(|(i, p)| (i, p.0)).collect();
+            let sum: i32 = positions.iter().map(|&(_, p)| p).sum();
+            (sum, positions)
+        });
+        reader.join().unwrap()
+    });
+    assert_eq!(sum, 10 + 21 + 30 + 40);
+    assert_eq!(positions[1], (2, 21));
+
+    let snapshot = world.snapshot();
+    assert_eq!(snapshot.tick(), world.current_tick());
+    assert_eq!(snapshot.get::<Position>(3), Some(&Position(30)));
+    assert_eq!(snapshot.get::<Position>(9), None);
+    assert_eq!(
+        snapshot.resource::<SimTime>().unwrap().tick,
+        snapshot.tick()
+    );
+    assert!(snapshot.changed_this_tick::<Position>(2));
+    assert!(!snapshot.changed_this_tick::<Position>(3));
+    assert_eq!(snapshot.iter::<Moving>().count(), 1);
+}